@@ -0,0 +1,121 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Accessibility Settings
+//!
+//! Stored in `accessibility.json` in the data dir. Besides the flags the
+//! frontend applies as CSS attributes, the backend uses them directly:
+//! high contrast selects the bundled high-contrast theme, reduced motion
+//! turns off aha-moment animations and large QR modules make
+//! `generate_qr_svg` encode with low error correction so each module is
+//! bigger at the same display size.
+//!
+//! The UI scale (webview zoom) is stored separately in `ui_scale.txt` and
+//! applied to every webview window, including at startup.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::settings_file;
+
+/// Settings file for the accessibility options.
+const SETTINGS_FILE: &str = "accessibility.json";
+
+/// UI scale file name under the data dir.
+const UI_SCALE_FILE: &str = "ui_scale.txt";
+
+/// Smallest accepted UI scale factor.
+pub const MIN_UI_SCALE: f64 = 0.5;
+
+/// Largest accepted UI scale factor.
+pub const MAX_UI_SCALE: f64 = 3.0;
+
+/// Cached `large_qr_modules`, read by QR rendering which has no state access.
+static LARGE_QR_MODULES: AtomicBool = AtomicBool::new(false);
+
+/// Accessibility preferences.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Disable animations.
+    pub reduce_motion: bool,
+    /// Use the high-contrast theme regardless of the selected theme.
+    pub high_contrast: bool,
+    /// Enlarge buttons and other touch targets.
+    pub large_touch_targets: bool,
+    /// Render QR codes with fewer, larger modules.
+    pub large_qr_modules: bool,
+}
+
+/// The saved accessibility options.
+pub fn load(data_dir: &Path) -> AccessibilitySettings {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Save the options and apply the backend-side ones.
+pub fn save(data_dir: &Path, settings: &AccessibilitySettings) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, settings)?;
+    LARGE_QR_MODULES.store(settings.large_qr_modules, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Apply saved settings at startup.
+pub fn init(data_dir: &Path) {
+    LARGE_QR_MODULES.store(load(data_dir).large_qr_modules, Ordering::Relaxed);
+}
+
+/// Whether QR codes should be rendered with larger modules.
+pub fn large_qr_modules() -> bool {
+    LARGE_QR_MODULES.load(Ordering::Relaxed)
+}
+
+/// Saved UI scale factor, or 1.0 if unset or out of range.
+pub fn load_ui_scale(data_dir: &Path) -> f64 {
+    std::fs::read_to_string(data_dir.join(UI_SCALE_FILE))
+        .ok()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .filter(|f| (MIN_UI_SCALE..=MAX_UI_SCALE).contains(f))
+        .unwrap_or(1.0)
+}
+
+/// Persist the UI scale factor.
+pub fn save_ui_scale(data_dir: &Path, factor: f64) -> std::io::Result<()> {
+    std::fs::write(data_dir.join(UI_SCALE_FILE), factor.to_string())
+}
+
+/// Apply a UI scale factor to all open webview windows.
+pub fn apply_ui_scale(app: &AppHandle, factor: f64) {
+    for window in app.webview_windows().values() {
+        if let Err(e) = window.set_zoom(factor) {
+            tracing::warn!("Failed to set zoom on {}: {}", window.label(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ui_scale_roundtrip_and_range() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load_ui_scale(temp.path()), 1.0);
+
+        save_ui_scale(temp.path(), 1.25).unwrap();
+        assert_eq!(load_ui_scale(temp.path()), 1.25);
+
+        save_ui_scale(temp.path(), 10.0).unwrap();
+        assert_eq!(load_ui_scale(temp.path()), 1.0, "Out of range falls back");
+    }
+
+    #[test]
+    fn test_settings_default_when_missing() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load(temp.path()), AccessibilitySettings::default());
+    }
+}
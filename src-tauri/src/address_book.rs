@@ -0,0 +1,250 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! OS Address Book Export
+//!
+//! Pushes contacts into the native address book as vCards: macOS Contacts,
+//! Windows People (through the `.vcf` handler) and evolution-data-server on
+//! Linux. Only contacts the user consented to export individually are
+//! pushed; consent is stored in `address_book_consent.json` in the data dir.
+//!
+//! Each export reports which card fields mapped to which vCard property,
+//! so the user sees what the OS now holds. The vCards are handed over in a
+//! private temp file that is deleted once the importer has read it.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use tempfile::TempPath;
+use vauchi_core::contact_card::{ContactField, FieldType};
+
+/// Consent file name under the data dir.
+const CONSENT_FILE: &str = "address_book_consent.json";
+
+/// How long a vCard file opened in another app is kept for it to read.
+const OPENED_FILE_LIFETIME: Duration = Duration::from_secs(5 * 60);
+
+/// Where exported contacts go on this platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressBookTarget {
+    MacosContacts,
+    WindowsPeople,
+    EvolutionDataServer,
+}
+
+/// The address book of the running OS, if it has one.
+pub fn target() -> Option<AddressBookTarget> {
+    if cfg!(target_os = "macos") {
+        Some(AddressBookTarget::MacosContacts)
+    } else if cfg!(target_os = "windows") {
+        Some(AddressBookTarget::WindowsPeople)
+    } else if cfg!(target_os = "linux") {
+        Some(AddressBookTarget::EvolutionDataServer)
+    } else {
+        None
+    }
+}
+
+fn consent_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONSENT_FILE)
+}
+
+/// IDs of contacts the user allowed to export.
+pub fn load_consent(data_dir: &Path) -> BTreeSet<String> {
+    std::fs::read_to_string(consent_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Grant or withdraw export consent for one contact.
+pub fn set_consent(data_dir: &Path, contact_id: &str, allowed: bool) -> std::io::Result<()> {
+    let mut consent = load_consent(data_dir);
+    if allowed {
+        consent.insert(contact_id.to_string());
+    } else {
+        consent.remove(contact_id);
+    }
+    let json = serde_json::to_string_pretty(&consent)?;
+    std::fs::write(consent_path(data_dir), json)
+}
+
+/// How one card field was written to the vCard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldMapping {
+    pub label: String,
+    pub field_type: String,
+    /// vCard property the field became; `None` if it was left out.
+    pub property: Option<String>,
+}
+
+/// vCard property for a field, `None` for types without one.
+fn property_for(field: &ContactField) -> Option<&'static str> {
+    match field.field_type() {
+        FieldType::Email => Some("EMAIL"),
+        FieldType::Phone => Some("TEL"),
+        FieldType::Website => Some("URL"),
+        FieldType::Address => Some("ADR"),
+        FieldType::Birthday => Some("BDAY"),
+        FieldType::Social => Some("X-SOCIALPROFILE"),
+        _ => None,
+    }
+}
+
+/// Escape a vCard text value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Build a vCard 3.0 for a contact and report how each field was mapped.
+pub fn to_vcard(
+    uid: &str,
+    display_name: &str,
+    fields: &[ContactField],
+) -> (String, Vec<FieldMapping>) {
+    let mut lines = vec![
+        "BEGIN:VCARD".to_string(),
+        "VERSION:3.0".to_string(),
+        format!("UID:{}", escape(uid)),
+        format!("FN:{}", escape(display_name)),
+    ];
+    let mut mappings = Vec::new();
+
+    for field in fields {
+        let property = property_for(field);
+        if let Some(property) = property {
+            let value = escape(field.value());
+            let value = match property {
+                // Free-form address in the street component
+                "ADR" => format!(";;{};;;;", value),
+                _ => value,
+            };
+            let label = field.label().trim();
+            if label.is_empty() {
+                lines.push(format!("{}:{}", property, value));
+            } else {
+                lines.push(format!("{};TYPE={}:{}", property, escape(label), value));
+            }
+        }
+        mappings.push(FieldMapping {
+            label: field.label().to_string(),
+            field_type: format!("{:?}", field.field_type()),
+            property: property.map(str::to_string),
+        });
+    }
+
+    lines.push("END:VCARD".to_string());
+    (lines.join("\r\n") + "\r\n", mappings)
+}
+
+/// Directories evolution-data-server installs its import tool to.
+const EDS_TOOL_DIRS: &[&str] = &["/usr/libexec/evolution", "/usr/lib/evolution"];
+
+/// Write `vcards` to a temp file and hand it to the OS address book.
+pub fn push(target: AddressBookTarget, vcards: &str) -> Result<()> {
+    let mut file = tempfile::Builder::new()
+        .prefix("vauchi-")
+        .suffix(".vcf")
+        .tempfile()
+        .context("Failed to create vCard file")?;
+    std::io::Write::write_all(&mut file, vcards.as_bytes())
+        .context("Failed to write vCard file")?;
+    import_file(target, file.into_temp_path())
+}
+
+/// Delete a file opened in another app once it had time to read it.
+fn delete_later(path: TempPath) {
+    std::thread::spawn(move || {
+        std::thread::sleep(OPENED_FILE_LIFETIME);
+        drop(path);
+    });
+}
+
+fn import_file(target: AddressBookTarget, path: TempPath) -> Result<()> {
+    match target {
+        AddressBookTarget::MacosContacts => {
+            // Contacts asks before adding the cards and reads the file
+            // after `open` returns
+            let status = Command::new("open")
+                .args(["-a", "Contacts"])
+                .arg(&path)
+                .status()
+                .context("Failed to open Contacts")?;
+            if !status.success() {
+                bail!("Contacts refused the import");
+            }
+            delete_later(path);
+        }
+        AddressBookTarget::WindowsPeople => {
+            tauri_plugin_opener::open_path(&path, None::<&str>)
+                .context("Failed to open the vCard handler")?;
+            delete_later(path);
+        }
+        AddressBookTarget::EvolutionDataServer => {
+            let tool = EDS_TOOL_DIRS
+                .iter()
+                .map(|dir| Path::new(dir).join("evolution-addressbook-import"))
+                .find(|tool| tool.exists())
+                .context("evolution-data-server import tool not found")?;
+            // The file is deleted when `path` drops, after the import
+            let status = Command::new(tool)
+                .arg("--input-file")
+                .arg(&path)
+                .status()
+                .context("Failed to run the evolution-data-server import")?;
+            if !status.success() {
+                bail!("evolution-data-server import failed");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_consent_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_consent(temp.path()).is_empty());
+
+        set_consent(temp.path(), "alice", true).unwrap();
+        set_consent(temp.path(), "bob", true).unwrap();
+        set_consent(temp.path(), "alice", false).unwrap();
+
+        let consent = load_consent(temp.path());
+        assert_eq!(consent.into_iter().collect::<Vec<_>>(), vec!["bob"]);
+    }
+
+    #[test]
+    fn test_vcard_maps_known_field_types() {
+        let fields = vec![
+            ContactField::new(FieldType::Email, "work", "alice@example.com"),
+            ContactField::new(FieldType::Address, "", "Main St 1, Zurich"),
+            ContactField::new(FieldType::Custom, "pet", "Rex"),
+        ];
+        let (vcard, mappings) = to_vcard("alice", "Alice; A.", &fields);
+
+        assert!(vcard.starts_with("BEGIN:VCARD\r\n"));
+        assert!(vcard.contains("UID:alice\r\n"));
+        assert!(vcard.contains("FN:Alice\\; A.\r\n"));
+        assert!(vcard.contains("EMAIL;TYPE=work:alice@example.com\r\n"));
+        assert!(vcard.contains("ADR:;;Main St 1\\, Zurich;;;;\r\n"));
+        assert!(!vcard.contains("Rex"));
+
+        assert_eq!(mappings[0].property.as_deref(), Some("EMAIL"));
+        assert_eq!(mappings[2].property, None);
+    }
+}
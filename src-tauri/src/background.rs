@@ -0,0 +1,38 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Background Tasks
+//!
+//! The long-running tasks started at launch (schedulers, event listeners)
+//! are spawned here so they can all be stopped before the data dir moves.
+//! Once stopped they stay stopped; the app restarts after the move.
+
+use std::future::Future;
+use std::sync::Mutex;
+
+use tauri::async_runtime::JoinHandle;
+
+/// Running background tasks.
+static TASKS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Spawn a background task that `stop_all` can stop.
+pub fn spawn(task: impl Future<Output = ()> + Send + 'static) {
+    let handle = tauri::async_runtime::spawn(task);
+    TASKS.lock().unwrap().push(handle);
+}
+
+/// Stop all background tasks and the CardDAV server, and wait until none
+/// of them runs anymore.
+pub async fn stop_all() {
+    let tasks = std::mem::take(&mut *TASKS.lock().unwrap());
+    for task in &tasks {
+        task.abort();
+    }
+    for task in tasks {
+        let _ = task.await;
+    }
+    tauri::async_runtime::spawn_blocking(crate::carddav::stop)
+        .await
+        .ok();
+}
@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Backup Envelope
+//!
+//! Exported backups are a JSON envelope around core's encrypted identity
+//! backup, so desktop settings can travel with it:
+//!
+//! ```json
+//! {"format":"vauchi-backup","version":1,"identity":"<base64>","recovery_policy":{...}}
+//! ```
+//!
+//! Import also takes a bare base64 identity backup, as exported by
+//! earlier versions and by other clients. Envelopes from a newer version
+//! are refused rather than restored without their settings.
+
+use serde::{Deserialize, Serialize};
+
+use crate::recovery_policy::RecoveryPolicy;
+
+/// Value of the `format` field.
+const FORMAT: &str = "vauchi-backup";
+
+/// Current envelope version.
+const VERSION: u32 = 1;
+
+/// An exported backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupEnvelope {
+    pub format: String,
+    pub version: u32,
+    /// Base64 of core's encrypted identity backup.
+    pub identity: String,
+    #[serde(default)]
+    pub recovery_policy: Option<RecoveryPolicy>,
+}
+
+impl BackupEnvelope {
+    /// Wrap an encoded identity backup.
+    pub fn new(identity_b64: String, recovery_policy: Option<RecoveryPolicy>) -> Self {
+        Self {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            identity: identity_b64,
+            recovery_policy,
+        }
+    }
+
+    /// Serialize for export.
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).expect("BackupEnvelope serialization should not fail")
+    }
+
+    /// Read exported backup data: an envelope or a bare base64 identity
+    /// backup.
+    pub fn decode(data: &str) -> Result<Self, String> {
+        let data = data.trim();
+        if !data.starts_with('{') {
+            return Ok(Self {
+                format: FORMAT.to_string(),
+                version: 0,
+                identity: data.to_string(),
+                recovery_policy: None,
+            });
+        }
+
+        let envelope: Self =
+            serde_json::from_str(data).map_err(|_| "Backup file is damaged".to_string())?;
+        if envelope.format != FORMAT {
+            return Err("Not a Vauchi backup".to_string());
+        }
+        if envelope.version > VERSION {
+            return Err("This backup needs a newer version of Vauchi".to_string());
+        }
+        Ok(envelope)
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private backup format
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_round_trips_and_bare_backups_still_decode() {
+        let policy = RecoveryPolicy {
+            recovery_threshold: 4,
+            verification_threshold: 1,
+        };
+        let envelope = BackupEnvelope::new("aWRlbnRpdHk=".to_string(), Some(policy));
+        let decoded = BackupEnvelope::decode(&envelope.encode()).unwrap();
+        assert_eq!(decoded, envelope);
+
+        let bare = BackupEnvelope::decode("aWRlbnRpdHk=\n").unwrap();
+        assert_eq!(bare.identity, "aWRlbnRpdHk=");
+        assert_eq!(bare.recovery_policy, None);
+    }
+
+    #[test]
+    fn test_decode_refuses_newer_and_foreign_envelopes() {
+        let mut envelope = BackupEnvelope::new("aWRlbnRpdHk=".to_string(), None);
+        envelope.version = VERSION + 1;
+        assert!(BackupEnvelope::decode(&envelope.encode()).is_err());
+
+        envelope.version = VERSION;
+        envelope.format = "other".to_string();
+        assert!(BackupEnvelope::decode(&envelope.encode()).is_err());
+        assert!(BackupEnvelope::decode("{not json").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Card Update Propagation
+//!
+//! Editing the card does not queue updates right away. The first edit of a
+//! burst keeps the card as it was before, and every edit pushes the due
+//! time back by the debounce window. Once the window passes without
+//! edits, the background loop hands the old and current card to core,
+//! which queues one update per contact who can see a changed field. A
+//! burst of edits so goes out as a single update per contact.
+//!
+//! The pending burst lives in `card_propagation.json` with the window, so
+//! edits made just before quitting are sent on the next start.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use vauchi_core::ContactCard;
+
+use crate::background;
+use crate::clock;
+use crate::events::{self, AppEvent};
+use crate::state::AppState;
+
+/// Propagation state file name under the data dir.
+const PROPAGATION_FILE: &str = "card_propagation.json";
+
+/// Default seconds without edits before updates are queued.
+const DEFAULT_DEBOUNCE_SECS: u64 = 10;
+
+/// Longest allowed debounce window.
+pub const MAX_DEBOUNCE_SECS: u64 = 600;
+
+/// Longest the background loop sleeps, so changed settings apply soon.
+const MAX_POLL_SECS: u64 = 30;
+
+/// Debounce settings and the pending burst of edits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CardPropagation {
+    /// Seconds without edits before updates are queued.
+    pub debounce_secs: u64,
+    /// The card before the first unsent edit.
+    pub baseline: Option<ContactCard>,
+    /// When the pending edits are queued.
+    pub due_at: Option<u64>,
+}
+
+impl Default for CardPropagation {
+    fn default() -> Self {
+        Self {
+            debounce_secs: DEFAULT_DEBOUNCE_SECS,
+            baseline: None,
+            due_at: None,
+        }
+    }
+}
+
+impl CardPropagation {
+    /// Record an edit of `previous`, restarting the debounce window.
+    pub fn record_edit(&mut self, previous: &ContactCard, now: u64) {
+        if self.baseline.is_none() {
+            self.baseline = Some(previous.clone());
+        }
+        self.due_at = Some(now.saturating_add(self.debounce_secs));
+    }
+
+    /// Seconds until the pending edits are due, `Some(0)` if they are, or
+    /// `None` if there are none.
+    fn wait(&self, now: u64) -> Option<u64> {
+        self.due_at.map(|due| due.saturating_sub(now))
+    }
+}
+
+fn propagation_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(PROPAGATION_FILE)
+}
+
+/// Load the propagation state, falling back to defaults.
+pub fn load(data_dir: &Path) -> CardPropagation {
+    std::fs::read_to_string(propagation_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Save the propagation state.
+pub fn save(data_dir: &Path, propagation: &CardPropagation) -> std::io::Result<()> {
+    std::fs::write(
+        propagation_path(data_dir),
+        serde_json::to_string(propagation)?,
+    )
+}
+
+/// Record an edit of the own card. `previous` is the card before the edit.
+pub fn card_changed(data_dir: &Path, previous: &ContactCard) -> std::io::Result<()> {
+    let mut propagation = load(data_dir);
+    propagation.record_edit(previous, clock::now_secs());
+    save(data_dir, &propagation)
+}
+
+/// Queue updates for the pending edits. Returns the number queued.
+fn propagate(state: &AppState, baseline: &ContactCard) -> Result<u32, String> {
+    let Some(current) = state.storage.load_own_card().map_err(|e| e.to_string())? else {
+        return Ok(0);
+    };
+    let identity = state.identity_handle().map_err(|e| e.to_string())?;
+    vauchi_core::sync::queue_card_updates(&identity, &state.storage, baseline, &current)
+        .map_err(|e| e.to_string())
+}
+
+/// Queue the pending edits if they are due. Returns seconds to wait before
+/// the next attempt.
+fn run_once(app: &AppHandle, data_dir: &Path) -> u64 {
+    let propagation = load(data_dir);
+    let now = clock::now_secs();
+    match propagation.wait(now) {
+        None => return MAX_POLL_SECS,
+        Some(secs) if secs > 0 => return secs.min(MAX_POLL_SECS),
+        Some(_) => {}
+    }
+    let Some(baseline) = propagation.baseline.as_ref() else {
+        return MAX_POLL_SECS;
+    };
+
+    let state = app.state::<Mutex<AppState>>();
+    let state = state.lock().unwrap();
+    // An edit may have happened since loading; it restarts the window
+    let current = load(data_dir);
+    if current.due_at != propagation.due_at {
+        return 1;
+    }
+    match propagate(&state, baseline) {
+        Ok(queued) => {
+            tracing::info!("Queued card updates for {} contacts", queued);
+            events::publish(AppEvent::CardUpdated {
+                contacts_notified: queued,
+            });
+        }
+        Err(e) => {
+            tracing::warn!("Failed to queue card updates: {}", e);
+            return MAX_POLL_SECS;
+        }
+    }
+    let sent = CardPropagation {
+        baseline: None,
+        due_at: None,
+        ..current
+    };
+    if let Err(e) = save(data_dir, &sent) {
+        tracing::warn!("Failed to save card propagation state: {}", e);
+    }
+    MAX_POLL_SECS
+}
+
+/// Start the background loop.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    background::spawn(async move {
+        loop {
+            let wait = run_once(&app, &data_dir);
+            tokio::time::sleep(Duration::from_secs(wait.max(1))).await;
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private debounce timing
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_edits_restart_window_and_keep_first_baseline() {
+        let mut propagation = CardPropagation {
+            debounce_secs: 10,
+            ..CardPropagation::default()
+        };
+        assert_eq!(propagation.wait(0), None);
+
+        propagation.record_edit(&ContactCard::new("Before"), 100);
+        assert_eq!(propagation.wait(105), Some(5));
+        propagation.record_edit(&ContactCard::new("Between"), 105);
+        assert_eq!(propagation.wait(110), Some(5));
+        assert_eq!(propagation.wait(120), Some(0));
+        assert_eq!(
+            propagation.baseline.as_ref().map(|c| c.display_name()),
+            Some("Before")
+        );
+    }
+
+    #[test]
+    fn test_state_roundtrip_and_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load(temp.path()).debounce_secs, DEFAULT_DEBOUNCE_SECS);
+
+        card_changed(temp.path(), &ContactCard::new("Alice")).unwrap();
+        let loaded = load(temp.path());
+        assert!(loaded.due_at.is_some());
+        assert!(loaded.baseline.is_some());
+    }
+}
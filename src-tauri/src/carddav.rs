@@ -0,0 +1,685 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Local CardDAV Server
+//!
+//! An optional read-only CardDAV endpoint on `127.0.0.1`, so mail clients
+//! like Thunderbird can autocomplete addresses from Vauchi contacts. It
+//! serves one address book at `/addressbooks/contacts/` with a vCard per
+//! visible contact; hidden and blocked contacts are left out, and nothing
+//! is served in duress mode. Every request needs HTTP Basic credentials
+//! generated on first enable, and is refused while the app is locked.
+//!
+//! Connections are handled by a small fixed pool of threads; past that,
+//! and a short queue, new connections are dropped. A connection that
+//! stalls mid-request times out.
+//!
+//! Settings live in `carddav.json` in the data dir, with the password in
+//! the encrypted settings. Only the parts of
+//! WebDAV/CardDAV that clients use for discovery and reading are
+//! implemented; writes are refused.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use vauchi_core::{AuthMode, SymmetricKey};
+
+use crate::address_book;
+use crate::secure_settings::{self, CARDDAV_PASSWORD};
+use crate::state::AppState;
+use crate::xml;
+
+/// Settings file name under the data dir.
+const SETTINGS_FILE: &str = "carddav.json";
+
+/// Default port, the one Radicale uses.
+pub const DEFAULT_PORT: u16 = 5232;
+
+/// Username for the generated credentials.
+const USERNAME: &str = "vauchi";
+
+/// Path of the served address book.
+const ADDRESS_BOOK: &str = "/addressbooks/contacts/";
+
+/// Threads handling connections.
+const WORKERS: usize = 4;
+
+/// Accepted connections waiting for a free thread.
+const QUEUE_LEN: usize = 16;
+
+/// How long a connection may take to send its request or read the reply.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest accepted request or header line, in bytes.
+const MAX_LINE: usize = 8 * 1024;
+
+/// Most header lines accepted in one request.
+const MAX_HEADERS: usize = 64;
+
+/// Largest request body read; the rest is ignored.
+const MAX_BODY: usize = 64 * 1024;
+
+/// CardDAV server settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CardDavSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub username: String,
+    /// Empty until the server is first enabled. Kept out of `carddav.json`.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub password: String,
+}
+
+impl Default for CardDavSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            username: USERNAME.to_string(),
+            password: String::new(),
+        }
+    }
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_FILE)
+}
+
+/// Settings from `carddav.json`, without the password.
+pub fn load_file(data_dir: &Path) -> CardDavSettings {
+    std::fs::read_to_string(settings_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Load settings, falling back to defaults if missing. Fails if the
+/// password cannot be read, so the server never runs without one.
+pub fn load(data_dir: &Path) -> std::io::Result<CardDavSettings> {
+    let mut settings = load_file(data_dir);
+    // Older versions kept the password in the file
+    if settings.password.is_empty() && settings_path(data_dir).exists() {
+        settings.password = secure_settings::get(data_dir, &CARDDAV_PASSWORD)
+            .map_err(std::io::Error::other)?
+            .unwrap_or_default();
+    }
+    Ok(settings)
+}
+
+/// Persist settings, with the password in the encrypted settings.
+pub fn save(data_dir: &Path, settings: &CardDavSettings) -> std::io::Result<()> {
+    let password = Some(&settings.password).filter(|p| !p.is_empty());
+    secure_settings::set(data_dir, &CARDDAV_PASSWORD, password).map_err(std::io::Error::other)?;
+    let settings = CardDavSettings {
+        password: String::new(),
+        ..settings.clone()
+    };
+    let json = serde_json::to_string_pretty(&settings)?;
+    std::fs::write(settings_path(data_dir), json)
+}
+
+/// Move a password older versions wrote to `carddav.json` into the
+/// encrypted settings.
+pub fn migrate_password(data_dir: &Path) -> std::io::Result<()> {
+    if load_file(data_dir).password.is_empty() {
+        return Ok(());
+    }
+    save(data_dir, &load(data_dir)?)
+}
+
+/// A new random password.
+pub fn generate_password() -> String {
+    hex::encode(&SymmetricKey::generate().as_bytes()[..16])
+}
+
+struct Running {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    accept_loop: thread::JoinHandle<()>,
+}
+
+fn running() -> &'static Mutex<Option<Running>> {
+    static RUNNING: Mutex<Option<Running>> = Mutex::new(None);
+    &RUNNING
+}
+
+/// Port the server is listening on, if it is running.
+pub fn running_port() -> Option<u16> {
+    running().lock().unwrap().as_ref().map(|r| r.port)
+}
+
+/// Start or stop the server to match `settings`, restarting it when the
+/// port or credentials changed.
+pub fn apply(app: &AppHandle, settings: &CardDavSettings) -> std::io::Result<()> {
+    stop();
+    if !settings.enabled {
+        return Ok(());
+    }
+    // An empty password would let anyone on the machine read the contacts
+    if settings.password.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "No CardDAV password is set",
+        ));
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", settings.port))?;
+    let port = listener.local_addr()?.port();
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = stop.clone();
+
+    let credentials = BASE64.encode(format!("{}:{}", settings.username, settings.password));
+    let (queue, pending) = mpsc::sync_channel::<TcpStream>(QUEUE_LEN);
+    let pending = Arc::new(Mutex::new(pending));
+    for _ in 0..WORKERS {
+        let (app, pending, credentials) = (app.clone(), pending.clone(), credentials.clone());
+        thread::spawn(move || worker(&app, &pending, &credentials));
+    }
+
+    // Dropping `queue` when the loop ends stops the workers
+    let accept_loop = thread::spawn(move || {
+        for stream in listener.incoming() {
+            if stop_flag.load(Ordering::Relaxed) {
+                break;
+            }
+            match stream {
+                Ok(stream) => match queue.try_send(stream) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        tracing::debug!("CardDAV busy; dropping connection")
+                    }
+                    Err(TrySendError::Disconnected(_)) => break,
+                },
+                Err(e) => tracing::warn!("CardDAV accept error: {}", e),
+            }
+        }
+    });
+    *running().lock().unwrap() = Some(Running {
+        port,
+        stop,
+        accept_loop,
+    });
+    tracing::info!("CardDAV server listening on 127.0.0.1:{}", port);
+    Ok(())
+}
+
+/// Handle queued connections until the server stops.
+fn worker(app: &AppHandle, pending: &Mutex<Receiver<TcpStream>>, credentials: &str) {
+    loop {
+        let Ok(stream) = pending.lock().unwrap().recv() else {
+            return;
+        };
+        let state = app.state::<Mutex<AppState>>();
+        if let Err(e) = handle_connection(stream, state.inner(), credentials) {
+            tracing::debug!("CardDAV connection error: {}", e);
+        }
+    }
+}
+
+/// Stop the server if it is running.
+pub fn stop() {
+    if let Some(running) = running().lock().unwrap().take() {
+        running.stop.store(true, Ordering::Relaxed);
+        // Wake the accept loop so it sees the flag, then wait for it to
+        // release the port
+        let _ = TcpStream::connect(("127.0.0.1", running.port));
+        let _ = running.accept_loop.join();
+    }
+}
+
+/// Start the server at launch if it is enabled.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    if let Err(e) = load(&data_dir).and_then(|settings| apply(&app, &settings)) {
+        tracing::warn!("Failed to start CardDAV server: {}", e);
+    }
+}
+
+/// A parsed HTTP request.
+struct Request {
+    method: String,
+    path: String,
+    depth: String,
+    authorization: Option<String>,
+    body: String,
+}
+
+fn read_request(stream: impl Read) -> std::io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    read_line(&mut reader, &mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+
+    let mut content_length = 0usize;
+    let mut depth = "0".to_string();
+    let mut authorization = None;
+    for headers in 0.. {
+        let mut line = String::new();
+        if read_line(&mut reader, &mut line)? == 0 || line == "\r\n" || line == "\n" {
+            break;
+        }
+        if headers == MAX_HEADERS {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "too many headers",
+            ));
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.parse().unwrap_or(0),
+            "depth" => depth = value,
+            "authorization" => authorization = Some(value),
+            _ => {}
+        }
+    }
+
+    let mut body = vec![0u8; content_length.min(MAX_BODY)];
+    reader.read_exact(&mut body)?;
+    Ok(Some(Request {
+        method: method.to_string(),
+        path: path.split('?').next().unwrap_or_default().to_string(),
+        depth,
+        authorization,
+        body: String::from_utf8_lossy(&body).to_string(),
+    }))
+}
+
+/// Read one line of at most `MAX_LINE` bytes, failing on a longer one so a
+/// client cannot make the server buffer without bound.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> std::io::Result<usize> {
+    let read = reader.by_ref().take(MAX_LINE as u64).read_line(line)?;
+    if read == MAX_LINE && !line.ends_with('\n') {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "line too long",
+        ));
+    }
+    Ok(read)
+}
+
+/// Compare in constant time so the password cannot be guessed byte by byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn authorized(authorization: Option<&str>, credentials: &str) -> bool {
+    authorization
+        .and_then(|value| value.strip_prefix("Basic "))
+        .is_some_and(|given| constant_time_eq(given.trim().as_bytes(), credentials.as_bytes()))
+}
+
+/// A served vCard.
+struct Card {
+    href: String,
+    etag: String,
+    vcard: String,
+}
+
+/// Whether the app is unlocked: authenticated, or without an app password
+/// to authenticate with.
+fn unlocked(state: &Mutex<AppState>) -> bool {
+    let state = state.lock().unwrap();
+    match state.auth_mode {
+        AuthMode::Normal | AuthMode::Duress => true,
+        AuthMode::Unauthenticated => matches!(state.storage.load_password_config(), Ok(None)),
+    }
+}
+
+/// vCards of the contacts that may be served.
+fn cards(state: &Mutex<AppState>) -> Vec<Card> {
+    let state = state.lock().unwrap();
+    if state.auth_mode == AuthMode::Duress {
+        return Vec::new();
+    }
+    let contacts = match state.cached_contacts() {
+        Ok(contacts) => contacts,
+        Err(e) => {
+            tracing::warn!("CardDAV failed to load contacts: {}", e);
+            return Vec::new();
+        }
+    };
+    contacts
+        .into_iter()
+        .filter(|c| !c.is_hidden() && !c.is_blocked())
+        .map(|c| {
+            let (vcard, _) = address_book::to_vcard(c.id(), c.display_name(), c.card().fields());
+            Card {
+                href: format!("{}{}.vcf", ADDRESS_BOOK, c.id()),
+                etag: etag(vcard.as_bytes()),
+                vcard,
+            }
+        })
+        .collect()
+}
+
+fn etag(data: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(&digest(&SHA256, data).as_ref()[..16]))
+}
+
+fn propstat(href: &str, props: &str) -> String {
+    format!(
+        "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop>\
+         <d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+        xml::escape(href),
+        props
+    )
+}
+
+fn multistatus(responses: &[String]) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+         <d:multistatus xmlns:d=\"DAV:\" xmlns:card=\"urn:ietf:params:xml:ns:carddav\" \
+         xmlns:cs=\"http://calendarserver.org/ns/\">{}</d:multistatus>",
+        responses.concat()
+    )
+}
+
+fn principal_props() -> String {
+    format!(
+        "<d:resourcetype><d:collection/><d:principal/></d:resourcetype>\
+         <d:current-user-principal><d:href>/principal/</d:href></d:current-user-principal>\
+         <card:addressbook-home-set><d:href>/addressbooks/</d:href></card:addressbook-home-set>\
+         <d:displayname>{}</d:displayname>",
+        USERNAME
+    )
+}
+
+fn address_book_props(cards: &[Card]) -> String {
+    let ctag = etag(
+        cards
+            .iter()
+            .map(|c| c.etag.as_str())
+            .collect::<String>()
+            .as_bytes(),
+    );
+    format!(
+        "<d:resourcetype><d:collection/><card:addressbook/></d:resourcetype>\
+         <d:displayname>Vauchi</d:displayname>\
+         <d:current-user-principal><d:href>/principal/</d:href></d:current-user-principal>\
+         <cs:getctag>{}</cs:getctag>",
+        xml::escape(&ctag)
+    )
+}
+
+fn card_props(card: &Card, with_data: bool) -> String {
+    let mut props = format!(
+        "<d:getetag>{}</d:getetag><d:getcontenttype>text/vcard; charset=utf-8</d:getcontenttype>\
+         <d:resourcetype/>",
+        xml::escape(&card.etag)
+    );
+    if with_data {
+        props.push_str(&format!(
+            "<card:address-data>{}</card:address-data>",
+            xml::escape(&card.vcard)
+        ));
+    }
+    props
+}
+
+/// `href` values listed in a REPORT body.
+fn requested_hrefs(body: &str) -> Vec<String> {
+    let mut hrefs = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("href>") {
+        rest = &rest[start + 5..];
+        let Some(end) = rest.find('<') else {
+            break;
+        };
+        let href = rest[..end].trim();
+        if !href.is_empty() {
+            hrefs.push(href.to_string());
+        }
+        rest = &rest[end..];
+    }
+    hrefs
+}
+
+/// HTTP status and body for a request.
+fn respond(request: &Request, cards: impl FnOnce() -> Vec<Card>) -> (u16, &'static str, String) {
+    let path = request.path.as_str();
+    let is_book = path == ADDRESS_BOOK || path == ADDRESS_BOOK.trim_end_matches('/');
+    match request.method.as_str() {
+        "OPTIONS" => (200, "text/plain", String::new()),
+        "PROPFIND" => match path {
+            "/" | "/.well-known/carddav" | "/principal/" | "/principal" => (
+                207,
+                "application/xml",
+                multistatus(&[propstat(path, &principal_props())]),
+            ),
+            "/addressbooks/" | "/addressbooks" => {
+                let cards = cards();
+                let mut responses = vec![propstat(
+                    "/addressbooks/",
+                    "<d:resourcetype><d:collection/></d:resourcetype>",
+                )];
+                if request.depth != "0" {
+                    responses.push(propstat(ADDRESS_BOOK, &address_book_props(&cards)));
+                }
+                (207, "application/xml", multistatus(&responses))
+            }
+            _ if is_book => {
+                let cards = cards();
+                let mut responses = vec![propstat(ADDRESS_BOOK, &address_book_props(&cards))];
+                if request.depth != "0" {
+                    responses.extend(
+                        cards
+                            .iter()
+                            .map(|c| propstat(&c.href, &card_props(c, false))),
+                    );
+                }
+                (207, "application/xml", multistatus(&responses))
+            }
+            _ => match cards().into_iter().find(|c| c.href == path) {
+                Some(card) => (
+                    207,
+                    "application/xml",
+                    multistatus(&[propstat(&card.href, &card_props(&card, false))]),
+                ),
+                None => (404, "text/plain", String::new()),
+            },
+        },
+        "REPORT" if is_book => {
+            let cards = cards();
+            let hrefs = requested_hrefs(&request.body);
+            // addressbook-multiget names hrefs; addressbook-query wants all
+            let multiget = request.body.contains("addressbook-multiget");
+            let responses: Vec<String> = cards
+                .iter()
+                .filter(|c| !multiget || hrefs.contains(&c.href))
+                .map(|c| propstat(&c.href, &card_props(c, true)))
+                .collect();
+            (207, "application/xml", multistatus(&responses))
+        }
+        "GET" | "HEAD" => match cards().into_iter().find(|c| c.href == path) {
+            Some(card) => (200, "text/vcard; charset=utf-8", card.vcard),
+            None => (404, "text/plain", String::new()),
+        },
+        // Read-only: no PUT, DELETE, MKCOL, PROPPATCH
+        _ => (405, "text/plain", String::new()),
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        207 => "Multi-Status",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Bad Request",
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    state: &Mutex<AppState>,
+    credentials: &str,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let Some(request) = read_request(&stream)? else {
+        return Ok(());
+    };
+
+    let (status, content_type, body) = if !authorized(request.authorization.as_deref(), credentials)
+    {
+        (401, "text/plain", String::new())
+    } else if !unlocked(state) {
+        // Duress counts as unlocked and is served an empty address book
+        (403, "text/plain", "Vauchi is locked".to_string())
+    } else {
+        respond(&request, || cards(state))
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nDAV: 1, 3, addressbook\r\nAllow: OPTIONS, GET, HEAD, PROPFIND, REPORT\r\n\
+         Content-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        status_text(status),
+        content_type,
+        body.len()
+    );
+    if status == 401 {
+        head.push_str("WWW-Authenticate: Basic realm=\"Vauchi\"\r\n");
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    if request.method != "HEAD" {
+        stream.write_all(body.as_bytes())?;
+    }
+    Ok(())
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private request routing and auth checks
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, depth: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            depth: depth.to_string(),
+            authorization: None,
+            body: body.to_string(),
+        }
+    }
+
+    fn sample_cards() -> Vec<Card> {
+        ["alice", "bob"]
+            .iter()
+            .map(|id| Card {
+                href: format!("{}{}.vcf", ADDRESS_BOOK, id),
+                etag: etag(id.as_bytes()),
+                vcard: format!("BEGIN:VCARD\r\nFN:{}\r\nEND:VCARD\r\n", id),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_basic_auth_requires_exact_credentials() {
+        let credentials = BASE64.encode("vauchi:secret");
+        let header = format!("Basic {}", credentials);
+        assert!(authorized(Some(&header), &credentials));
+        assert!(!authorized(Some("Basic dmF1Y2hpOndyb25n"), &credentials));
+        assert!(!authorized(None, &credentials));
+    }
+
+    #[test]
+    fn test_propfind_lists_cards_at_depth_one() {
+        let (status, _, body) = respond(&request("PROPFIND", ADDRESS_BOOK, "1", ""), sample_cards);
+        assert_eq!(status, 207);
+        assert!(body.contains("<card:addressbook/>"));
+        assert!(body.contains("/addressbooks/contacts/alice.vcf"));
+        assert!(!body.contains("address-data"));
+
+        let (_, _, body) = respond(&request("PROPFIND", ADDRESS_BOOK, "0", ""), sample_cards);
+        assert!(!body.contains("alice.vcf"));
+    }
+
+    #[test]
+    fn test_multiget_returns_requested_cards_only() {
+        let body = "<card:addressbook-multiget xmlns:d=\"DAV:\">\
+                    <d:href>/addressbooks/contacts/bob.vcf</d:href></card:addressbook-multiget>";
+        let (status, _, body) = respond(&request("REPORT", ADDRESS_BOOK, "1", body), sample_cards);
+        assert_eq!(status, 207);
+        assert!(body.contains("FN:bob"));
+        assert!(!body.contains("FN:alice"));
+    }
+
+    #[test]
+    fn test_get_and_writes() {
+        let path = format!("{}alice.vcf", ADDRESS_BOOK);
+        let (status, _, body) = respond(&request("GET", &path, "0", ""), sample_cards);
+        assert_eq!(status, 200);
+        assert!(body.contains("FN:alice"));
+
+        let (status, _, _) = respond(&request("PUT", &path, "0", ""), sample_cards);
+        assert_eq!(status, 405);
+        let (status, _, _) = respond(&request("GET", "/nope.vcf", "0", ""), sample_cards);
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn test_locked_app_is_not_served() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state = Mutex::new(AppState::new(temp.path()).unwrap());
+        // No app password: nothing to unlock
+        assert!(unlocked(&state));
+
+        let config = vauchi_core::AppPasswordConfig::create("correct horse").unwrap();
+        state
+            .lock()
+            .unwrap()
+            .storage
+            .save_app_password(config.password_hash(), config.password_salt())
+            .unwrap();
+        assert!(!unlocked(&state));
+
+        state.lock().unwrap().auth_mode = AuthMode::Normal;
+        assert!(unlocked(&state));
+    }
+
+    #[test]
+    fn test_request_is_parsed_within_limits() {
+        let raw = "REPORT /addressbooks/contacts/?x=1 HTTP/1.1\r\nDepth: 1\r\nContent-Length: 4\r\n\r\nbody";
+        let request = read_request(raw.as_bytes()).unwrap().unwrap();
+        assert_eq!(request.method, "REPORT");
+        assert_eq!(request.path, ADDRESS_BOOK);
+        assert_eq!(request.depth, "1");
+        assert_eq!(request.body, "body");
+    }
+
+    #[test]
+    fn test_overlong_lines_and_header_floods_are_rejected() {
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE));
+        assert!(read_request(long_line.as_bytes()).is_err());
+
+        let flood = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-A: b\r\n".repeat(MAX_HEADERS + 1)
+        );
+        assert!(read_request(flood.as_bytes()).is_err());
+
+        let allowed = format!("GET / HTTP/1.1\r\n{}\r\n", "X-A: b\r\n".repeat(MAX_HEADERS));
+        assert!(read_request(allowed.as_bytes()).unwrap().is_some());
+    }
+}
@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Clipboard
+//!
+//! Copies text to the OS clipboard. Sensitive values (fingerprints, backup
+//! strings, recovery claims) are cleared again after a configurable
+//! timeout, unless something else was copied in the meantime. Only a
+//! digest of a sensitive value is kept to recognize it; the value itself is
+//! never kept or logged.
+//!
+//! The timeout lives in `clipboard.json` in the data dir.
+
+use std::path::Path;
+use std::time::Duration;
+
+use ring::digest::{digest, Digest, SHA256};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::settings_file;
+
+/// Settings file for the clipboard clearing delay.
+const SETTINGS_FILE: &str = "clipboard.json";
+
+/// Default seconds before a sensitive value is cleared.
+const DEFAULT_CLEAR_AFTER_SECS: u64 = 30;
+
+/// Shortest allowed clear timeout.
+pub const MIN_CLEAR_AFTER_SECS: u64 = 5;
+
+/// Longest allowed clear timeout.
+pub const MAX_CLEAR_AFTER_SECS: u64 = 600;
+
+/// Clipboard preferences.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// Seconds before a sensitive value is cleared.
+    pub clear_after_secs: u64,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            clear_after_secs: DEFAULT_CLEAR_AFTER_SECS,
+        }
+    }
+}
+
+/// The clipboard clearing preferences.
+pub fn load(data_dir: &Path) -> ClipboardSettings {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Change the clipboard clearing preferences.
+pub fn save(data_dir: &Path, settings: &ClipboardSettings) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, settings)
+}
+
+fn fingerprint(value: &str) -> Digest {
+    digest(&SHA256, value.as_bytes())
+}
+
+/// Whether the clipboard still holds the value with this digest.
+fn still_holds(current: Option<&str>, copied: &Digest) -> bool {
+    current.is_some_and(|text| fingerprint(text).as_ref() == copied.as_ref())
+}
+
+/// Copy `value` to the clipboard, clearing it after `clear_after` if it is
+/// still there.
+pub fn copy(app: &AppHandle, value: &str, clear_after: Option<Duration>) -> Result<(), String> {
+    app.clipboard()
+        .write_text(value)
+        .map_err(|e| e.to_string())?;
+
+    let Some(clear_after) = clear_after else {
+        return Ok(());
+    };
+    let copied = fingerprint(value);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(clear_after).await;
+        let current = app.clipboard().read_text().ok();
+        if still_holds(current.as_deref(), &copied) {
+            if let Err(e) = app.clipboard().clear() {
+                tracing::warn!("Failed to clear clipboard: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the private digest matching helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_roundtrip_and_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load(temp.path()).clear_after_secs, DEFAULT_CLEAR_AFTER_SECS);
+        let settings = ClipboardSettings {
+            clear_after_secs: 90,
+        };
+        save(temp.path(), &settings).unwrap();
+        assert_eq!(load(temp.path()), settings);
+    }
+
+    #[test]
+    fn test_only_the_copied_value_is_cleared() {
+        let copied = fingerprint("AB12 CD34");
+        assert!(still_holds(Some("AB12 CD34"), &copied));
+        assert!(!still_holds(Some("something else"), &copied));
+        assert!(!still_holds(None, &copied));
+    }
+}
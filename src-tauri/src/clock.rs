@@ -0,0 +1,85 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Clock
+//!
+//! Wall-clock seconds for app-level timestamps (alerts, deletion grace
+//! periods, content checks). The E2E test server can pin the clock to a
+//! fixed value so screenshots and assertions are reproducible.
+//!
+//! The pin lives in a [`Clock`]; the app uses one process-wide instance,
+//! since the test server handles requests on worker threads. Tests make
+//! their own, so tests running in parallel each keep their own clock.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The app's clock.
+static APP_CLOCK: Clock = Clock::new();
+
+/// Wall clock that can be pinned to a fixed time.
+#[derive(Default)]
+pub struct Clock {
+    /// Pinned Unix timestamp, or 0 when the real clock is used.
+    fixed: AtomicU64,
+}
+
+impl Clock {
+    /// A clock following the system time.
+    pub const fn new() -> Self {
+        Self {
+            fixed: AtomicU64::new(0),
+        }
+    }
+
+    /// Current Unix timestamp in seconds.
+    pub fn now_secs(&self) -> u64 {
+        match self.fixed.load(Ordering::Relaxed) {
+            0 => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            fixed => fixed,
+        }
+    }
+
+    /// Pin the clock to `timestamp`, or restore the real clock with `None`.
+    pub fn set_fixed(&self, timestamp: Option<u64>) {
+        self.fixed.store(timestamp.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+/// Current Unix timestamp in seconds, from the app's clock.
+pub fn now_secs() -> u64 {
+    APP_CLOCK.now_secs()
+}
+
+/// Pin the app's clock to `timestamp`, or restore the real clock with
+/// `None`.
+#[cfg_attr(not(debug_assertions), allow(dead_code))]
+pub fn set_fixed(timestamp: Option<u64>) {
+    APP_CLOCK.set_fixed(timestamp);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_overrides_and_restores() {
+        let clock = Clock::new();
+        clock.set_fixed(Some(1_700_000_000));
+        assert_eq!(clock.now_secs(), 1_700_000_000);
+        clock.set_fixed(None);
+        assert!(clock.now_secs() > 1_700_000_000);
+    }
+
+    #[test]
+    fn test_pinned_clock_leaves_the_app_clock_alone() {
+        let clock = Clock::new();
+        clock.set_fixed(Some(1_700_000_000));
+        assert_ne!(now_secs(), 1_700_000_000);
+        assert_eq!(clock.now_secs(), 1_700_000_000);
+    }
+}
@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Clock Skew
+//!
+//! QR expiry, recovery claim expiry and deletion grace periods all use the
+//! local clock, so a wrong system time shows up as "QR expired". This keeps
+//! the last measured difference to a trusted clock: the relay's `Date`
+//! header on every sync, and optionally an NTP query at startup. The NTP
+//! query is off by default (`clock_check.json`), since it contacts a third
+//! party, and never runs in Tor mode: NTP is UDP and cannot go through Tor.
+//!
+//! When the skew exceeds `MAX_CLOCK_SKEW_SECS`, an
+//! `AppEvent::ClockSkewDetected` is published (and forwarded to the
+//! frontend as `clock://skew`) and expiry failures are reported as
+//! `CommandError::ClockSkew` instead.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::background;
+use crate::clock;
+use crate::commands::troubleshoot::MAX_CLOCK_SKEW_SECS;
+use crate::environment;
+use crate::events::{self, AppEvent};
+use crate::settings_file;
+use crate::state::AppState;
+
+/// Settings file for the clock check.
+const SETTINGS_FILE: &str = "clock_check.json";
+
+/// Frontend event for a clock that is off.
+pub const SKEW_EVENT: &str = "clock://skew";
+
+/// Clock check preferences.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockCheckSettings {
+    /// Query an NTP server at startup.
+    pub ntp_enabled: bool,
+}
+
+/// A measured clock difference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkewMeasurement {
+    /// Seconds the trusted clock is ahead of ours.
+    pub skew_secs: i64,
+    /// `relay` or `ntp`.
+    pub source: String,
+    /// Local Unix seconds of the measurement.
+    pub measured_at: u64,
+}
+
+static LAST: Mutex<Option<SkewMeasurement>> = Mutex::new(None);
+
+/// The clock check preferences.
+pub fn load_settings(data_dir: &Path) -> ClockCheckSettings {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Change the clock check preferences.
+pub fn save_settings(data_dir: &Path, settings: &ClockCheckSettings) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, settings)
+}
+
+/// Whether to query NTP: turned on and not in Tor mode. A Tor config that
+/// cannot be read counts as Tor mode.
+pub fn ntp_allowed(state: &AppState) -> bool {
+    let tor_enabled = state
+        .storage
+        .load_or_create_tor_config()
+        .map_or(true, |config| config.enabled);
+    load_settings(state.data_dir()).ntp_enabled && !tor_enabled
+}
+
+fn exceeds(skew_secs: i64) -> bool {
+    skew_secs.abs() > MAX_CLOCK_SKEW_SECS
+}
+
+/// Remember a measurement, announcing when the clock newly goes off.
+pub fn record(skew_secs: i64, source: &str) {
+    let measurement = SkewMeasurement {
+        skew_secs,
+        source: source.to_string(),
+        measured_at: clock::now_secs(),
+    };
+    let previous = LAST.lock().unwrap().replace(measurement);
+    let was_off = previous.is_some_and(|p| exceeds(p.skew_secs));
+    if exceeds(skew_secs) && !was_off {
+        tracing::warn!("System clock is off by {} seconds ({})", skew_secs, source);
+        events::publish(AppEvent::ClockSkewDetected {
+            skew_secs,
+            source: source.to_string(),
+        });
+    }
+}
+
+/// The last measurement, if any.
+pub fn last() -> Option<SkewMeasurement> {
+    LAST.lock().unwrap().clone()
+}
+
+/// The last measured skew, if it exceeds the threshold.
+pub fn excessive_skew() -> Option<i64> {
+    last().map(|m| m.skew_secs).filter(|s| exceeds(*s))
+}
+
+/// Forward skew warnings to the frontend, and query NTP in the
+/// background if the user enabled it and Tor mode is off.
+pub fn start(app: AppHandle) {
+    let query_ntp = {
+        let state = app.state::<Mutex<AppState>>();
+        let state = state.lock().unwrap();
+        ntp_allowed(&state)
+    };
+    let mut rx = events::subscribe();
+    background::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            if let AppEvent::ClockSkewDetected { .. } = event {
+                if let Err(e) = app.emit(SKEW_EVENT, &event) {
+                    tracing::warn!("Failed to emit clock skew event: {}", e);
+                }
+            }
+        }
+    });
+
+    if query_ntp {
+        tauri::async_runtime::spawn_blocking(|| match environment::ntp_offset_secs() {
+            Ok(skew) => record(skew, "ntp"),
+            Err(e) => tracing::info!("NTP clock check failed: {}", e),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_only_large_skew_is_excessive() {
+        record(30, "relay");
+        assert_eq!(excessive_skew(), None);
+        record(-3600, "ntp");
+        assert_eq!(excessive_skew(), Some(-3600));
+        assert_eq!(last().unwrap().source, "ntp");
+        record(0, "relay");
+        assert_eq!(excessive_skew(), None);
+    }
+
+    #[test]
+    fn test_ntp_is_off_by_default() {
+        let temp = TempDir::new().unwrap();
+        assert!(!load_settings(temp.path()).ntp_enabled);
+        let settings = ClockCheckSettings { ntp_enabled: true };
+        save_settings(temp.path(), &settings).unwrap();
+        assert_eq!(load_settings(temp.path()), settings);
+    }
+
+    #[test]
+    fn test_ntp_is_not_allowed_in_tor_mode() {
+        let temp = TempDir::new().unwrap();
+        let state = AppState::new(temp.path()).unwrap();
+        assert!(!ntp_allowed(&state));
+
+        save_settings(temp.path(), &ClockCheckSettings { ntp_enabled: true }).unwrap();
+        assert!(ntp_allowed(&state));
+
+        let mut tor = state.storage.load_or_create_tor_config().unwrap();
+        tor.enabled = true;
+        state.storage.save_tor_config(&tor).unwrap();
+        assert!(!ntp_allowed(&state));
+    }
+}
@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Accessibility Commands
+//!
+//! Read and update the accessibility settings and the UI scale.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, State};
+
+use crate::accessibility::{self, AccessibilitySettings};
+use crate::error::CommandError;
+use crate::state::AppState;
+
+/// Get the accessibility settings.
+#[tauri::command]
+pub fn get_accessibility_settings(state: State<'_, Mutex<AppState>>) -> AccessibilitySettings {
+    let state = state.lock().unwrap();
+    accessibility::load(state.data_dir())
+}
+
+/// Save the accessibility settings.
+///
+/// The frontend should re-read the active theme afterwards, since high
+/// contrast overrides it.
+#[tauri::command]
+pub fn set_accessibility_settings(
+    settings: AccessibilitySettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    accessibility::save(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save accessibility settings: {}", e)))
+}
+
+/// Get the UI scale factor (1.0 = 100%).
+#[tauri::command]
+pub fn get_ui_scale(state: State<'_, Mutex<AppState>>) -> f64 {
+    let state = state.lock().unwrap();
+    accessibility::load_ui_scale(state.data_dir())
+}
+
+/// Set, persist and apply the UI scale factor.
+#[tauri::command]
+pub fn set_ui_scale(
+    factor: f64,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if !(accessibility::MIN_UI_SCALE..=accessibility::MAX_UI_SCALE).contains(&factor) {
+        return Err(CommandError::Validation(format!(
+            "UI scale must be between {} and {}",
+            accessibility::MIN_UI_SCALE,
+            accessibility::MAX_UI_SCALE
+        )));
+    }
+    {
+        let state = state.lock().unwrap();
+        accessibility::save_ui_scale(state.data_dir(), factor)
+            .map_err(|e| CommandError::Config(format!("Failed to save UI scale: {}", e)))?;
+    }
+    accessibility::apply_ui_scale(&app, factor);
+    Ok(())
+}
@@ -10,6 +10,7 @@ use serde::Serialize;
 use vauchi_core::contact_card::{is_allowed_scheme, ContactAction, ContactField, FieldType};
 
 use crate::error::CommandError;
+use crate::error_stats;
 
 /// Result of opening a contact field.
 #[derive(Serialize)]
@@ -139,59 +140,62 @@ pub async fn open_contact_field(
     label: String,
     value: String,
 ) -> Result<OpenResult, CommandError> {
-    // Parse field type and create a ContactField
-    let ft = parse_field_type(&field_type);
-    let field = ContactField::new(ft, &label, &value);
-
-    // Get the action and URI using vauchi-core's secure URI builder
-    let action = field.to_action();
-    let uri = field.to_uri();
-    let action_type = action_type_str(&action);
-
-    // If no URI can be generated, return copy action
-    let Some(uri_str) = uri else {
-        return Ok(OpenResult {
-            success: false,
-            action: "copy".to_string(),
-            uri: None,
-            error: Some(
-                "No action available for this field. Value copied to clipboard.".to_string(),
-            ),
-        });
-    };
-
-    // Convert geo: URIs to web map URLs for desktop (geo: has no handler on most desktops)
-    let uri_str = geo_to_web_url(&uri_str);
-
-    // Extra security check: validate the URI scheme
-    if let Some(scheme) = uri_str.split(':').next() {
-        if !is_allowed_scheme(scheme) {
-            let scheme_owned = scheme.to_string();
+    error_stats::track("open_contact_field", async {
+        // Parse field type and create a ContactField
+        let ft = parse_field_type(&field_type);
+        let field = ContactField::new(ft, &label, &value);
+
+        // Get the action and URI using vauchi-core's secure URI builder
+        let action = field.to_action();
+        let uri = field.to_uri();
+        let action_type = action_type_str(&action);
+
+        // If no URI can be generated, return copy action
+        let Some(uri_str) = uri else {
             return Ok(OpenResult {
                 success: false,
-                action: "blocked".to_string(),
-                uri: Some(uri_str),
-                error: Some(format!(
-                    "URI scheme '{}' is not allowed for security reasons.",
-                    scheme_owned
-                )),
+                action: "copy".to_string(),
+                uri: None,
+                error: Some(
+                    "No action available for this field. Value copied to clipboard.".to_string(),
+                ),
             });
+        };
+
+        // Convert geo: URIs to web map URLs for desktop (geo: has no handler on most desktops)
+        let uri_str = geo_to_web_url(&uri_str);
+
+        // Extra security check: validate the URI scheme
+        if let Some(scheme) = uri_str.split(':').next() {
+            if !is_allowed_scheme(scheme) {
+                let scheme_owned = scheme.to_string();
+                return Ok(OpenResult {
+                    success: false,
+                    action: "blocked".to_string(),
+                    uri: Some(uri_str),
+                    error: Some(format!(
+                        "URI scheme '{}' is not allowed for security reasons.",
+                        scheme_owned
+                    )),
+                });
+            }
         }
-    }
 
-    // Use the opener plugin to open the URI
-    match tauri_plugin_opener::open_url(&uri_str, None::<&str>) {
-        Ok(_) => Ok(OpenResult {
-            success: true,
-            action: action_type.to_string(),
-            uri: Some(uri_str),
-            error: None,
-        }),
-        Err(e) => Ok(OpenResult {
-            success: false,
-            action: action_type.to_string(),
-            uri: Some(uri_str),
-            error: Some(format!("Failed to open: {}", e)),
-        }),
-    }
+        // Use the opener plugin to open the URI
+        match tauri_plugin_opener::open_url(&uri_str, None::<&str>) {
+            Ok(_) => Ok(OpenResult {
+                success: true,
+                action: action_type.to_string(),
+                uri: Some(uri_str),
+                error: None,
+            }),
+            Err(e) => Ok(OpenResult {
+                success: false,
+                action: action_type.to_string(),
+                uri: Some(uri_str),
+                error: Some(format!("Failed to open: {}", e)),
+            }),
+        }
+    })
+    .await
 }
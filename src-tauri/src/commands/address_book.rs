@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Address Book Commands
+//!
+//! Commands for exporting contacts to the OS address book. A contact is
+//! only exported after the user consented for it with
+//! `set_address_book_consent`; hidden and blocked contacts never are.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::State;
+use vauchi_core::AuthMode;
+
+use crate::address_book::{self, AddressBookTarget, FieldMapping};
+use crate::device_mode;
+use crate::error::CommandError;
+use crate::state::AppState;
+
+/// A contact written to the address book.
+#[derive(Serialize)]
+pub struct ExportedContact {
+    pub contact_id: String,
+    pub display_name: String,
+    pub fields: Vec<FieldMapping>,
+}
+
+/// A requested contact that was not exported.
+#[derive(Serialize)]
+pub struct SkippedContact {
+    pub contact_id: String,
+    pub reason: String,
+}
+
+/// Result of an address book export.
+#[derive(Serialize)]
+pub struct AddressBookExportReport {
+    pub target: AddressBookTarget,
+    pub exported: Vec<ExportedContact>,
+    pub skipped: Vec<SkippedContact>,
+}
+
+/// Get the address book contacts are exported to, `None` if this OS has
+/// no supported one.
+#[tauri::command]
+pub fn get_address_book_target() -> Option<AddressBookTarget> {
+    address_book::target()
+}
+
+/// Get the IDs of contacts the user allowed to export.
+#[tauri::command]
+pub fn get_address_book_consent(state: State<'_, Mutex<AppState>>) -> Vec<String> {
+    let state = state.lock().unwrap();
+    address_book::load_consent(state.data_dir())
+        .into_iter()
+        .collect()
+}
+
+/// Allow or disallow exporting a contact to the address book.
+#[tauri::command]
+pub fn set_address_book_consent(
+    contact_id: String,
+    allowed: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    address_book::set_consent(state.data_dir(), &contact_id, allowed)
+        .map_err(|e| CommandError::Config(format!("Failed to save consent: {}", e)))
+}
+
+/// Export the given contacts to the OS address book.
+///
+/// Contacts without consent, hidden or blocked contacts are skipped and
+/// listed with the reason.
+#[tauri::command]
+pub fn export_to_address_book(
+    contact_ids: Vec<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<AddressBookExportReport, CommandError> {
+    let target = address_book::target().ok_or_else(|| {
+        CommandError::Config("No supported address book on this system".to_string())
+    })?;
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let consent = address_book::load_consent(state.data_dir());
+
+    let mut vcards = String::new();
+    let mut exported = Vec::new();
+    let mut skipped = Vec::new();
+    for contact_id in contact_ids {
+        // Real contacts must not leave the app in duress mode
+        let contact = if state.auth_mode == AuthMode::Duress {
+            None
+        } else {
+            state.cached_contact(&contact_id)?
+        };
+        let reason = match &contact {
+            None => Some("Contact not found"),
+            Some(c) if c.is_hidden() => Some("Contact is hidden"),
+            Some(c) if c.is_blocked() => Some("Contact is blocked"),
+            Some(_) if !consent.contains(&contact_id) => Some("No export consent"),
+            Some(_) => None,
+        };
+        match (contact, reason) {
+            (Some(contact), None) => {
+                let (vcard, fields) = address_book::to_vcard(
+                    contact.id(),
+                    contact.display_name(),
+                    contact.card().fields(),
+                );
+                vcards.push_str(&vcard);
+                exported.push(ExportedContact {
+                    contact_id,
+                    display_name: contact.display_name().to_string(),
+                    fields,
+                });
+            }
+            (_, reason) => skipped.push(SkippedContact {
+                contact_id,
+                reason: reason.unwrap_or_default().to_string(),
+            }),
+        }
+    }
+
+    // The import can wait on the user; don't hold the state meanwhile
+    drop(state);
+    if !exported.is_empty() {
+        address_book::push(target, &vcards)
+            .map_err(|e| CommandError::Config(format!("Address book export failed: {}", e)))?;
+    }
+
+    Ok(AddressBookExportReport {
+        target,
+        exported,
+        skipped,
+    })
+}
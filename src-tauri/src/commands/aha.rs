@@ -4,15 +4,21 @@
 
 //! Aha Moment Commands
 //!
-//! Tracks and triggers milestone celebrations in the desktop app.
+//! Tracks and triggers milestone celebrations in the desktop app: core's
+//! aha moments, checked by the frontend, and the desktop milestones of
+//! `milestones`, reached in commands and picked up with `take_aha_moments`.
 
-use std::path::PathBuf;
 use std::sync::Mutex;
 
 use serde::Serialize;
 use vauchi_core::aha_moments::{AhaMomentTracker, AhaMomentType};
-use vauchi_core::i18n::Locale;
+use vauchi_core::i18n::{get_locale_info, Locale};
 
+use crate::accessibility;
+use crate::clock;
+use crate::commands::i18n::{parse_locale, resolve_locale};
+use crate::error::CommandError;
+use crate::milestones::{self, Milestone};
 use crate::state::AppState;
 
 /// Aha moment data for the frontend.
@@ -51,31 +57,25 @@ fn type_to_string(t: AhaMomentType) -> String {
     }
 }
 
-fn string_to_locale(code: &str) -> Locale {
-    match code.to_lowercase().as_str() {
-        "de" => Locale::German,
-        "fr" => Locale::French,
-        "es" => Locale::Spanish,
-        _ => Locale::English,
-    }
-}
-
-fn tracker_path(data_dir: &std::path::Path) -> PathBuf {
-    data_dir.join("aha_tracker.json")
-}
-
 fn load_tracker(data_dir: &std::path::Path) -> AhaMomentTracker {
-    let path = tracker_path(data_dir);
-    std::fs::read_to_string(&path)
-        .ok()
-        .and_then(|json| AhaMomentTracker::from_json(&json).ok())
-        .unwrap_or_default()
+    milestones::load_core(data_dir)
 }
 
 fn save_tracker(data_dir: &std::path::Path, tracker: &AhaMomentTracker) {
-    let path = tracker_path(data_dir);
-    if let Ok(json) = tracker.to_json() {
-        let _ = std::fs::write(&path, json);
+    if let Err(e) = milestones::save_core(data_dir, tracker) {
+        tracing::warn!("Failed to save aha moments: {}", e);
+    }
+}
+
+/// Whether aha moment texts are translated for `locale` (not the English fallback).
+pub(crate) fn has_localized_content(locale: Locale) -> bool {
+    if locale == Locale::English {
+        return true;
+    }
+    let mut tracker = AhaMomentTracker::default();
+    match tracker.try_trigger(AhaMomentType::CardCreationComplete) {
+        Some(moment) => moment.title_localized(locale) != moment.title_localized(Locale::English),
+        None => false,
     }
 }
 
@@ -89,17 +89,20 @@ pub fn check_aha_moment(
     let state = state.lock().unwrap();
     let data_dir = state.data_dir().to_path_buf();
     drop(state);
+    // Reduced motion turns celebrations into plain messages
+    let reduce_motion = accessibility::load(&data_dir).reduce_motion;
 
     let mut tracker = load_tracker(&data_dir);
     let result = tracker.try_trigger(moment);
     if result.is_some() {
         save_tracker(&data_dir, &tracker);
+        milestones::mark_seen(&data_dir, &type_to_string(moment), clock::now_secs());
     }
     result.map(|m| AhaMomentInfo {
         moment_type: type_to_string(m.moment_type),
         title: m.title().to_string(),
         message: m.message(),
-        has_animation: m.has_animation(),
+        has_animation: m.has_animation() && !reduce_motion,
     })
 }
 
@@ -114,17 +117,20 @@ pub fn check_aha_moment_with_context(
     let state = state.lock().unwrap();
     let data_dir = state.data_dir().to_path_buf();
     drop(state);
+    // Reduced motion turns celebrations into plain messages
+    let reduce_motion = accessibility::load(&data_dir).reduce_motion;
 
     let mut tracker = load_tracker(&data_dir);
     let result = tracker.try_trigger_with_context(moment, context);
     if result.is_some() {
         save_tracker(&data_dir, &tracker);
+        milestones::mark_seen(&data_dir, &type_to_string(moment), clock::now_secs());
     }
     result.map(|m| AhaMomentInfo {
         moment_type: type_to_string(m.moment_type),
         title: m.title().to_string(),
         message: m.message(),
-        has_animation: m.has_animation(),
+        has_animation: m.has_animation() && !reduce_motion,
     })
 }
 
@@ -136,24 +142,104 @@ pub fn check_aha_moment_localized(
     state: tauri::State<'_, Mutex<AppState>>,
 ) -> Option<AhaMomentInfo> {
     let moment = type_from_string(&moment_type)?;
-    let locale = string_to_locale(&locale_code);
+    let locale = parse_locale(&locale_code);
     let state = state.lock().unwrap();
     let data_dir = state.data_dir().to_path_buf();
     drop(state);
+    // Reduced motion turns celebrations into plain messages
+    let reduce_motion = accessibility::load(&data_dir).reduce_motion;
 
     let mut tracker = load_tracker(&data_dir);
     let result = tracker.try_trigger(moment);
     if result.is_some() {
         save_tracker(&data_dir, &tracker);
+        milestones::mark_seen(&data_dir, &type_to_string(moment), clock::now_secs());
     }
     result.map(|m| AhaMomentInfo {
         moment_type: type_to_string(m.moment_type),
         title: m.title_localized(locale),
         message: m.message_localized(locale),
-        has_animation: m.has_animation(),
+        has_animation: m.has_animation() && !reduce_motion,
     })
 }
 
+/// Take the desktop milestones reached since last asked (tenth contact,
+/// first label, first linked device, first exported backup). Each is
+/// returned once.
+#[tauri::command]
+pub fn take_aha_moments(
+    locale_code: Option<String>,
+    state: tauri::State<'_, Mutex<AppState>>,
+) -> Vec<AhaMomentInfo> {
+    let locale = resolve_locale(locale_code, &state);
+    let data_dir = state.lock().unwrap().data_dir().to_path_buf();
+    // Reduced motion turns celebrations into plain messages
+    let reduce_motion = accessibility::load(&data_dir).reduce_motion;
+
+    let mut tracker = milestones::load(&data_dir);
+    let pending = tracker.take_pending(clock::now_secs());
+    if pending.is_empty() {
+        return Vec::new();
+    }
+    if let Err(e) = milestones::save(&data_dir, &tracker) {
+        tracing::warn!("Failed to save milestones: {}", e);
+    }
+    pending
+        .into_iter()
+        .map(|milestone| {
+            let (title, message) = milestone.texts(get_locale_info(locale).code);
+            AhaMomentInfo {
+                moment_type: milestone.as_str().to_string(),
+                title,
+                message,
+                has_animation: !reduce_motion,
+            }
+        })
+        .collect()
+}
+
+/// Whether one aha moment was seen, and when.
+#[derive(Serialize)]
+pub struct AhaProgress {
+    pub moment_type: String,
+    pub seen: bool,
+    /// Unix seconds it was first shown; unknown for moments seen before
+    /// times were recorded.
+    pub seen_at: Option<u64>,
+}
+
+/// List every aha moment, core and desktop, with whether it was seen.
+/// Stays on this machine; nothing is reported anywhere.
+#[tauri::command]
+pub fn get_aha_progress(state: tauri::State<'_, Mutex<AppState>>) -> Vec<AhaProgress> {
+    let data_dir = state.lock().unwrap().data_dir().to_path_buf();
+    let tracker = load_tracker(&data_dir);
+    let desktop = milestones::load(&data_dir);
+
+    let core = AhaMomentType::all()
+        .iter()
+        .map(|t| (type_to_string(*t), tracker.has_seen(*t)));
+    let own = Milestone::ALL
+        .iter()
+        .map(|m| (m.as_str().to_string(), desktop.shown.contains(m)));
+    core.chain(own)
+        .map(|(moment_type, seen)| AhaProgress {
+            seen_at: desktop.seen_at.get(&moment_type).copied(),
+            moment_type,
+            seen,
+        })
+        .collect()
+}
+
+/// Forget all seen aha moments, e.g. on a shared or demo machine, so
+/// they are celebrated again.
+#[tauri::command]
+pub fn reset_aha_moments(state: tauri::State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let data_dir = state.lock().unwrap().data_dir().to_path_buf();
+    milestones::reset(&data_dir)
+        .map_err(|e| CommandError::Config(format!("Failed to reset aha moments: {}", e)))
+}
+
 // INLINE_TEST_REQUIRED: tests access private Tauri command internals and app state setup
 #[cfg(test)]
 mod tests {
@@ -224,4 +310,13 @@ mod tests {
         };
         assert!(info.title.contains("Karte"));
     }
+
+    // @scenario: aha_moments:Milestone celebrations are localized
+    #[test]
+    fn test_german_reported_as_localized() {
+        ensure_init();
+        assert!(has_localized_content(Locale::German));
+        assert!(has_localized_content(Locale::English));
+        assert_eq!(parse_locale("DE"), Locale::German);
+    }
 }
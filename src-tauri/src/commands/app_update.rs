@@ -0,0 +1,387 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! App Update Commands
+//!
+//! Checks for, downloads and installs new app releases with
+//! `tauri-plugin-updater`. Update checks are off until the user turns them
+//! on (`app_update.json`).
+//!
+//! Releases are only installed if signed with the release key, pinned at
+//! build time through `VAUCHI_UPDATER_PUBKEY` (minisign public key, base64)
+//! together with the release feed `VAUCHI_UPDATE_ENDPOINT`. Builds without
+//! them never contact a server.
+//!
+//! In Tor mode, update requests go through the user's Tor SOCKS proxy, or
+//! are not made at all if none is configured. The proxy must be a
+//! `socks5h://` URL on a loopback host, so DNS lookups go through Tor too.
+//! Other HTTP requests, such as content updates, use the same proxy through
+//! `http_proxy`.
+
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_updater::{Updater, UpdaterExt};
+use url::Url;
+
+use crate::error::CommandError;
+use crate::error_stats;
+use crate::secure_settings::{self, UPDATE_TOR_PROXY};
+use crate::state::AppState;
+
+/// Release signing public key, pinned at build time.
+const UPDATER_PUBKEY: Option<&str> = option_env!("VAUCHI_UPDATER_PUBKEY");
+
+/// Release feed URL, pinned at build time.
+const UPDATE_ENDPOINT: Option<&str> = option_env!("VAUCHI_UPDATE_ENDPOINT");
+
+/// Settings file name under the data dir.
+const SETTINGS_FILE: &str = "app_update.json";
+
+/// Download progress event.
+pub const PROGRESS_EVENT: &str = "update://progress";
+
+/// Timeout for update requests.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// App update preferences.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppUpdateSettings {
+    /// Check for new releases.
+    pub enabled: bool,
+    /// SOCKS proxy of a local Tor client (e.g. `socks5h://127.0.0.1:9050`),
+    /// used for update and content requests in Tor mode. Kept in the encrypted
+    /// settings, as it may carry a password.
+    pub tor_proxy: Option<String>,
+}
+
+/// A release newer than the running app.
+#[derive(Serialize)]
+pub struct AppUpdateInfo {
+    pub available: bool,
+    pub current_version: String,
+    pub version: Option<String>,
+    /// Release date (Unix seconds).
+    pub date: Option<i64>,
+    /// Release notes.
+    pub notes: Option<String>,
+}
+
+/// Download progress payload.
+#[derive(Clone, Serialize)]
+pub struct UpdateProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_FILE)
+}
+
+fn load_file(data_dir: &Path) -> AppUpdateSettings {
+    std::fs::read_to_string(settings_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Load settings, falling back to defaults if missing. Fails if the proxy
+/// cannot be read, so updates never go out without it.
+pub fn load_settings(data_dir: &Path) -> Result<AppUpdateSettings, CommandError> {
+    let mut settings = load_file(data_dir);
+    // Older versions kept the proxy in the file
+    if settings.tor_proxy.is_none() && settings_path(data_dir).exists() {
+        settings.tor_proxy = secure_settings::get(data_dir, &UPDATE_TOR_PROXY)?;
+    }
+    Ok(settings)
+}
+
+/// Whether app updates are turned on.
+pub fn updates_enabled(data_dir: &Path) -> bool {
+    load_file(data_dir).enabled
+}
+
+fn save_settings(data_dir: &Path, settings: &AppUpdateSettings) -> std::io::Result<()> {
+    secure_settings::set(data_dir, &UPDATE_TOR_PROXY, settings.tor_proxy.as_ref())
+        .map_err(std::io::Error::other)?;
+    let settings = AppUpdateSettings {
+        tor_proxy: None,
+        ..settings.clone()
+    };
+    std::fs::write(
+        settings_path(data_dir),
+        serde_json::to_string_pretty(&settings)?,
+    )
+}
+
+/// Move a proxy older versions wrote to `app_update.json` into the
+/// encrypted settings.
+pub fn migrate_tor_proxy(data_dir: &Path) -> std::io::Result<()> {
+    if load_file(data_dir).tor_proxy.is_none() {
+        return Ok(());
+    }
+    let settings = load_settings(data_dir).map_err(std::io::Error::other)?;
+    save_settings(data_dir, &settings)
+}
+
+/// The proxy update requests must use, or why they may not be made.
+fn update_proxy(
+    settings: &AppUpdateSettings,
+    tor_enabled: bool,
+) -> Result<Option<Url>, CommandError> {
+    if !settings.enabled {
+        return Err(CommandError::Config(
+            "App updates are turned off".to_string(),
+        ));
+    }
+    proxy_for(settings, tor_enabled)
+}
+
+/// Parse a Tor proxy URL. Only `socks5h://` on a loopback host is
+/// accepted: `socks5://` resolves names locally, leaking DNS lookups.
+fn parse_tor_proxy(proxy: &str) -> Result<Url, CommandError> {
+    let invalid = || {
+        CommandError::Validation(
+            "The Tor proxy must be a socks5h:// URL on this computer, e.g. \
+             socks5h://127.0.0.1:9050"
+                .to_string(),
+        )
+    };
+    let url = Url::parse(proxy).map_err(|_| invalid())?;
+    let loopback = match url.host() {
+        Some(url::Host::Domain(host)) => host.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => IpAddr::V4(ip).is_loopback(),
+        Some(url::Host::Ipv6(ip)) => IpAddr::V6(ip).is_loopback(),
+        None => false,
+    };
+    if url.scheme() != "socks5h" || !loopback {
+        return Err(invalid());
+    }
+    Ok(url)
+}
+
+/// The proxy HTTP requests must use: none outside Tor mode, the configured
+/// SOCKS proxy in Tor mode, and an error if Tor mode has none.
+fn proxy_for(settings: &AppUpdateSettings, tor_enabled: bool) -> Result<Option<Url>, CommandError> {
+    if !tor_enabled {
+        return Ok(None);
+    }
+    match settings.tor_proxy.as_deref() {
+        Some(proxy) => parse_tor_proxy(proxy).map(Some),
+        None => Err(CommandError::Privacy(
+            "Remote requests are off in Tor mode unless a Tor proxy is set".to_string(),
+        )),
+    }
+}
+
+/// The proxy HTTP requests other than app updates must use.
+pub(crate) fn http_proxy(state: &AppState) -> Result<Option<Url>, CommandError> {
+    let tor_enabled = state
+        .storage
+        .load_or_create_tor_config()
+        .map_err(|e| CommandError::Config(e.to_string()))?
+        .enabled;
+    proxy_for(&load_settings(state.data_dir())?, tor_enabled)
+}
+
+/// Build the updater for the current settings.
+fn updater(app: &AppHandle, state: &State<'_, Mutex<AppState>>) -> Result<Updater, CommandError> {
+    let (settings, tor_enabled) = {
+        let state = state.lock().unwrap();
+        let tor_enabled = state
+            .storage
+            .load_or_create_tor_config()
+            .map_err(|e| CommandError::Config(e.to_string()))?
+            .enabled;
+        (load_settings(state.data_dir())?, tor_enabled)
+    };
+    let proxy = update_proxy(&settings, tor_enabled)?;
+
+    let (Some(pubkey), Some(endpoint)) = (UPDATER_PUBKEY, UPDATE_ENDPOINT) else {
+        return Err(CommandError::Config(
+            "This build does not support app updates".to_string(),
+        ));
+    };
+    let endpoint = Url::parse(endpoint)
+        .map_err(|e| CommandError::Config(format!("Invalid update endpoint: {}", e)))?;
+
+    let mut builder = app
+        .updater_builder()
+        .pubkey(pubkey)
+        .timeout(REQUEST_TIMEOUT)
+        .endpoints(vec![endpoint])
+        .map_err(|e| CommandError::Config(e.to_string()))?;
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|e| CommandError::Config(e.to_string()))
+}
+
+fn map_update_error(e: tauri_plugin_updater::Error) -> CommandError {
+    match e {
+        tauri_plugin_updater::Error::Minisign(_)
+        | tauri_plugin_updater::Error::Base64(_)
+        | tauri_plugin_updater::Error::SignatureUtf8(_) => {
+            CommandError::Validation(format!("Update signature is invalid: {}", e))
+        }
+        other => CommandError::Network(other.to_string()),
+    }
+}
+
+/// Get the app update preferences.
+#[tauri::command]
+pub fn get_app_update_settings(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<AppUpdateSettings, CommandError> {
+    let state = state.lock().unwrap();
+    load_settings(state.data_dir())
+}
+
+/// Save the app update preferences.
+#[tauri::command]
+pub fn set_app_update_settings(
+    settings: AppUpdateSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if let Some(proxy) = &settings.tor_proxy {
+        parse_tor_proxy(proxy)?;
+    }
+    let state = state.lock().unwrap();
+    save_settings(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save update settings: {}", e)))
+}
+
+/// Check the release feed for a newer version, with its release notes.
+#[tauri::command]
+pub async fn check_app_update(
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<AppUpdateInfo, CommandError> {
+    error_stats::track("check_app_update", async {
+        let update = updater(&app, &state)?
+            .check()
+            .await
+            .map_err(map_update_error)?;
+        let current_version = app.package_info().version.to_string();
+        Ok(match update {
+            Some(update) => AppUpdateInfo {
+                available: true,
+                current_version,
+                version: Some(update.version.clone()),
+                date: update.date.map(|d| d.unix_timestamp()),
+                notes: update.body.clone(),
+            },
+            None => AppUpdateInfo {
+                available: false,
+                current_version,
+                version: None,
+                date: None,
+                notes: None,
+            },
+        })
+    })
+    .await
+}
+
+/// Download, verify and install `version`, the release the user approved
+/// after [`check_app_update`], reporting progress on `update://progress`.
+/// The new version runs after the next restart. Returns the installed
+/// version, or `None` if already up to date. Fails without installing if
+/// the feed now offers a different version.
+#[tauri::command]
+pub async fn download_app_update(
+    version: String,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<String>, CommandError> {
+    error_stats::track("download_app_update", async {
+        let Some(update) = updater(&app, &state)?
+            .check()
+            .await
+            .map_err(map_update_error)?
+        else {
+            return Ok(None);
+        };
+        if update.version != version {
+            return Err(CommandError::Validation(format!(
+                "Version {} is available instead of {}; check for updates again",
+                update.version, version
+            )));
+        }
+
+        let mut downloaded = 0u64;
+        update
+            .download_and_install(
+                |chunk, total| {
+                    downloaded += chunk as u64;
+                    let progress = UpdateProgress { downloaded, total };
+                    if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+                        tracing::warn!("Failed to emit update progress: {}", e);
+                    }
+                },
+                || tracing::info!("App update downloaded"),
+            )
+            .await
+            .map_err(map_update_error)?;
+
+        tracing::info!("Installed app update {}", update.version);
+        Ok(Some(update.version.clone()))
+    })
+    .await
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private Tor gating
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_updates_are_off_by_default() {
+        let temp = TempDir::new().unwrap();
+        let settings = load_settings(temp.path()).unwrap();
+        assert!(!settings.enabled);
+        assert!(matches!(
+            update_proxy(&settings, false),
+            Err(CommandError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_tor_mode_needs_a_socks_proxy() {
+        let mut settings = AppUpdateSettings {
+            enabled: true,
+            tor_proxy: None,
+        };
+        assert!(matches!(update_proxy(&settings, false), Ok(None)));
+        assert!(matches!(
+            update_proxy(&settings, true),
+            Err(CommandError::Privacy(_))
+        ));
+
+        settings.tor_proxy = Some("http://127.0.0.1:8080".to_string());
+        assert!(update_proxy(&settings, true).is_err());
+
+        // socks5:// resolves names locally
+        settings.tor_proxy = Some("socks5://127.0.0.1:9050".to_string());
+        assert!(update_proxy(&settings, true).is_err());
+
+        settings.tor_proxy = Some("socks5h://proxy.example:9050".to_string());
+        assert!(update_proxy(&settings, true).is_err());
+
+        settings.tor_proxy = Some("socks5h://localhost:9050".to_string());
+        assert!(update_proxy(&settings, true).is_ok());
+
+        settings.tor_proxy = Some("socks5h://127.0.0.1:9050".to_string());
+        let proxy = update_proxy(&settings, true).unwrap().unwrap();
+        assert_eq!(proxy.as_str(), "socks5h://127.0.0.1:9050");
+    }
+}
@@ -41,7 +41,11 @@ pub struct DuressSettingsInput {
 /// Returns "normal", "duress", or "unauthenticated".
 #[tauri::command]
 pub fn get_auth_mode(state: State<'_, Mutex<AppState>>) -> String {
-    let state = state.lock().unwrap();
+    get_auth_mode_impl(&state.lock().unwrap())
+}
+
+/// [`get_auth_mode`] on locked state, shared with the test server.
+pub(crate) fn get_auth_mode_impl(state: &AppState) -> String {
     match state.auth_mode {
         AuthMode::Normal => "normal".to_string(),
         AuthMode::Duress => "duress".to_string(),
@@ -55,10 +59,16 @@ pub fn setup_app_password(
     password: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    setup_app_password_impl(&state.lock().unwrap(), &password)
+}
 
+/// [`setup_app_password`] on locked state, shared with the test server.
+pub(crate) fn setup_app_password_impl(
+    state: &AppState,
+    password: &str,
+) -> Result<(), CommandError> {
     let config =
-        AppPasswordConfig::create(&password).map_err(|e| CommandError::Auth(e.to_string()))?;
+        AppPasswordConfig::create(password).map_err(|e| CommandError::Auth(e.to_string()))?;
     state
         .storage
         .save_app_password(config.password_hash(), config.password_salt())
@@ -76,15 +86,18 @@ pub fn authenticate(
     pin: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<String, CommandError> {
-    let mut state = state.lock().unwrap();
+    authenticate_impl(&mut state.lock().unwrap(), &pin)
+}
 
+/// [`authenticate`] on locked state, shared with the test server.
+pub(crate) fn authenticate_impl(state: &mut AppState, pin: &str) -> Result<String, CommandError> {
     let config = state
         .storage
         .load_password_config()
         .map_err(|e| CommandError::Storage(e.to_string()))?;
 
     match config {
-        Some(config) => match config.verify(&pin) {
+        Some(config) => match config.verify(pin) {
             AuthResult::Normal => {
                 state.auth_mode = AuthMode::Normal;
                 Ok("normal".to_string())
@@ -94,7 +107,7 @@ pub fn authenticate(
                 // Queue encrypted duress alerts for trusted contacts (silent, best-effort).
                 // Failures are logged but do not block authentication.
                 if let Err(e) = state.queue_duress_alerts() {
-                    eprintln!("Warning: Failed to queue duress alerts: {}", e);
+                    tracing::warn!("Failed to queue duress alerts: {}", e);
                 }
                 Ok("duress".to_string())
             }
@@ -110,8 +123,14 @@ pub fn setup_duress_pin(
     duress_pin: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    setup_duress_pin_impl(&state.lock().unwrap(), &duress_pin)
+}
 
+/// [`setup_duress_pin`] on locked state, shared with the test server.
+pub(crate) fn setup_duress_pin_impl(
+    state: &AppState,
+    duress_pin: &str,
+) -> Result<(), CommandError> {
     let mut config = state
         .storage
         .load_password_config()
@@ -119,7 +138,7 @@ pub fn setup_duress_pin(
         .ok_or_else(|| CommandError::Auth("App password not set. Set it up first.".to_string()))?;
 
     config
-        .setup_duress(&duress_pin)
+        .setup_duress(duress_pin)
         .map_err(|e| CommandError::Auth(e.to_string()))?;
 
     state
@@ -133,8 +152,11 @@ pub fn setup_duress_pin(
 /// Disable duress PIN.
 #[tauri::command]
 pub fn disable_duress(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    disable_duress_impl(&state.lock().unwrap())
+}
 
+/// [`disable_duress`] on locked state, shared with the test server.
+pub(crate) fn disable_duress_impl(state: &AppState) -> Result<(), CommandError> {
     state
         .storage
         .disable_duress()
@@ -147,8 +169,11 @@ pub fn disable_duress(state: State<'_, Mutex<AppState>>) -> Result<(), CommandEr
 /// Get duress status (password enabled, duress enabled).
 #[tauri::command]
 pub fn get_duress_status(state: State<'_, Mutex<AppState>>) -> Result<DuressStatus, CommandError> {
-    let state = state.lock().unwrap();
+    get_duress_status_impl(&state.lock().unwrap())
+}
 
+/// [`get_duress_status`] on locked state, shared with the test server.
+pub(crate) fn get_duress_status_impl(state: &AppState) -> Result<DuressStatus, CommandError> {
     let config = state
         .storage
         .load_password_config()
@@ -14,7 +14,11 @@ use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::Serialize;
 use tauri::State;
 
+use crate::backup_envelope::BackupEnvelope;
 use crate::error::CommandError;
+use crate::milestones::{self, Milestone};
+use crate::recovery_policy;
+use crate::secret::SecretString;
 use crate::state::AppState;
 
 /// Backup result containing encrypted data.
@@ -28,9 +32,10 @@ pub struct BackupResult {
 /// Export the identity as an encrypted backup.
 ///
 /// The backup is encrypted with the provided password using Argon2id.
-/// Requires a strong password (zxcvbn score >= 3).
+/// Requires a strong password (zxcvbn score >= 3). The backup is wrapped
+/// in an envelope with the recovery settings so a restore keeps them.
 #[tauri::command]
-pub fn export_backup(password: String, state: State<'_, Mutex<AppState>>) -> BackupResult {
+pub fn export_backup(password: SecretString, state: State<'_, Mutex<AppState>>) -> BackupResult {
     let state = state.lock().unwrap();
 
     let identity = match state.identity.as_ref() {
@@ -44,12 +49,14 @@ pub fn export_backup(password: String, state: State<'_, Mutex<AppState>>) -> Bac
         }
     };
 
-    match identity.export_backup(&password) {
+    match identity.export_backup(password.expose()) {
         Ok(backup) => {
             let encoded = STANDARD.encode(backup.as_bytes());
+            let policy = recovery_policy::load(state.data_dir());
+            milestones::record(state.data_dir(), Milestone::FirstBackupExported);
             BackupResult {
                 success: true,
-                data: Some(encoded),
+                data: Some(BackupEnvelope::new(encoded, Some(policy)).encode()),
                 error: None,
             }
         }
@@ -65,16 +72,17 @@ pub fn export_backup(password: String, state: State<'_, Mutex<AppState>>) -> Bac
 #[tauri::command]
 pub fn import_backup(
     backup_data: String,
-    password: String,
+    password: SecretString,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<String, CommandError> {
     use vauchi_core::IdentityBackup;
 
-    let bytes = STANDARD.decode(&backup_data)?;
+    let envelope = BackupEnvelope::decode(&backup_data).map_err(CommandError::Backup)?;
+    let bytes = STANDARD.decode(&envelope.identity)?;
 
     let backup = IdentityBackup::new(bytes);
 
-    let identity = vauchi_core::Identity::import_backup(&backup, &password)
+    let identity = vauchi_core::Identity::import_backup(&backup, password.expose())
         .map_err(|e| CommandError::Backup(format!("Restore failed: {:?}", e)))?;
 
     let display_name = identity.display_name().to_string();
@@ -82,7 +90,7 @@ pub fn import_backup(
     // Save to storage
     let state = state.lock().unwrap();
     let backup_data = identity
-        .export_backup(&password)
+        .export_backup(password.expose())
         .map_err(|e| CommandError::Backup(format!("Failed to re-export backup: {:?}", e)))?;
 
     state
@@ -90,15 +98,76 @@ pub fn import_backup(
         .save_identity(backup_data.as_bytes(), &display_name)
         .map_err(|e| CommandError::Storage(format!("Failed to save identity: {:?}", e)))?;
 
+    if let Some(policy) = envelope.recovery_policy.filter(|p| p.validate().is_ok()) {
+        recovery_policy::save(state.data_dir(), &policy)?;
+    }
+
     Ok(format!("Restored identity: {}", display_name))
 }
 
+/// Encrypted backups smaller than this cannot hold an identity.
+const MIN_BACKUP_BYTES: usize = 64;
+
+/// Metadata about a backup that can be read without the password.
+#[derive(Serialize, Debug, Clone)]
+pub struct BackupInspection {
+    /// Whether the data looks like a restorable backup.
+    pub valid: bool,
+    /// Size of the decoded (still encrypted) backup in bytes.
+    pub size_bytes: usize,
+    /// Whether restoring would replace an existing identity.
+    pub replaces_identity: bool,
+    /// Reason the backup is not valid, if any.
+    pub error: Option<String>,
+}
+
+/// Inspect base64 backup data without decrypting it.
+///
+/// `replaces_identity` is always false here; the command fills it in.
+pub fn inspect_backup_data(backup_data: &str) -> BackupInspection {
+    let envelope = match BackupEnvelope::decode(backup_data) {
+        Ok(envelope) => envelope,
+        Err(e) => {
+            return BackupInspection {
+                valid: false,
+                size_bytes: 0,
+                replaces_identity: false,
+                error: Some(e),
+            }
+        }
+    };
+    let (valid, size_bytes, error) = match STANDARD.decode(&envelope.identity) {
+        Ok(bytes) if bytes.len() < MIN_BACKUP_BYTES => (
+            false,
+            bytes.len(),
+            Some("Backup is too small to contain an identity".to_string()),
+        ),
+        Ok(bytes) => (true, bytes.len(), None),
+        Err(_) => (false, 0, Some("Backup is not valid base64".to_string())),
+    };
+
+    BackupInspection {
+        valid,
+        size_bytes,
+        replaces_identity: false,
+        error,
+    }
+}
+
+/// Inspect a backup before asking for its password.
+#[tauri::command]
+pub fn inspect_backup(backup_data: String, state: State<'_, Mutex<AppState>>) -> BackupInspection {
+    let mut inspection = inspect_backup_data(&backup_data);
+    inspection.replaces_identity = state.lock().unwrap().identity.is_some();
+    inspection
+}
+
 /// Check password strength before backup.
 #[tauri::command]
-pub fn check_password_strength(password: String) -> Result<String, CommandError> {
+pub fn check_password_strength(password: SecretString) -> Result<String, CommandError> {
     use vauchi_core::identity::password::{password_feedback, validate_password, PasswordStrength};
 
-    match validate_password(&password) {
+    match validate_password(password.expose()) {
         Ok(strength) => {
             let level = match strength {
                 PasswordStrength::Strong => "strong",
@@ -108,7 +177,7 @@ pub fn check_password_strength(password: String) -> Result<String, CommandError>
             Ok(level.to_string())
         }
         Err(_) => {
-            let feedback = password_feedback(&password);
+            let feedback = password_feedback(password.expose());
             Err(CommandError::Validation(if feedback.is_empty() {
                 "Password too weak. Use a longer passphrase.".to_string()
             } else {
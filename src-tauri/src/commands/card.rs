@@ -6,10 +6,12 @@
 
 use std::sync::Mutex;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::State;
 use vauchi_core::{ContactCard, ContactField, FieldType};
 
+use crate::card_propagation;
+use crate::device_mode;
 use crate::error::CommandError;
 use crate::state::AppState;
 
@@ -70,6 +72,7 @@ pub fn add_field(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     // Parse field type
     let ft = match field_type.to_lowercase().as_str() {
@@ -88,12 +91,14 @@ pub fn add_field(
         .unwrap_or_else(|| ContactCard::new(state.display_name().unwrap_or("User")));
 
     // Add field
+    let previous = card.clone();
     let field = ContactField::new(ft, &label, &value);
     card.add_field(field)
         .map_err(|e| CommandError::Card(format!("{}", e)))?;
 
     // Save card
     state.storage.save_own_card(&card)?;
+    card_propagation::card_changed(state.data_dir(), &previous)?;
 
     Ok(())
 }
@@ -105,16 +110,19 @@ pub fn remove_field(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let mut card = state
         .storage
         .load_own_card()?
         .ok_or_else(|| CommandError::Card("No card found".to_string()))?;
 
+    let previous = card.clone();
     card.remove_field(&field_id)
         .map_err(|e| CommandError::Card(format!("{}", e)))?;
 
     state.storage.save_own_card(&card)?;
+    card_propagation::card_changed(state.data_dir(), &previous)?;
 
     Ok(())
 }
@@ -127,6 +135,7 @@ pub fn update_field(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let mut card = state
         .storage
@@ -134,6 +143,7 @@ pub fn update_field(
         .ok_or_else(|| CommandError::Card("No card found".to_string()))?;
 
     // Find and update the field
+    let previous = card.clone();
     let field = card
         .fields_mut()
         .iter_mut()
@@ -142,8 +152,46 @@ pub fn update_field(
 
     field.set_value(&new_value);
 
-    // Save the card
+    // Save the card; contacts get the change once edits settle
     state.storage.save_own_card(&card)?;
+    card_propagation::card_changed(state.data_dir(), &previous)?;
 
     Ok(())
 }
+
+/// Card update propagation settings for the frontend.
+#[derive(Serialize, Deserialize)]
+pub struct CardUpdateSettings {
+    /// Seconds without edits before contacts are sent the changes.
+    pub debounce_secs: u64,
+}
+
+/// Get the card update propagation settings.
+#[tauri::command]
+pub fn get_card_update_settings(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CardUpdateSettings, CommandError> {
+    let state = state.lock().unwrap();
+    Ok(CardUpdateSettings {
+        debounce_secs: card_propagation::load(state.data_dir()).debounce_secs,
+    })
+}
+
+/// Set how long card edits settle before contacts are sent the changes.
+#[tauri::command]
+pub fn set_card_update_settings(
+    settings: CardUpdateSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if settings.debounce_secs > card_propagation::MAX_DEBOUNCE_SECS {
+        return Err(CommandError::Validation(format!(
+            "Debounce window must be at most {} seconds",
+            card_propagation::MAX_DEBOUNCE_SECS
+        )));
+    }
+    let state = state.lock().unwrap();
+    let mut propagation = card_propagation::load(state.data_dir());
+    propagation.debounce_secs = settings.debounce_secs;
+    card_propagation::save(state.data_dir(), &propagation)?;
+    Ok(())
+}
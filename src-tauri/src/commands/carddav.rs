@@ -0,0 +1,101 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! CardDAV Commands
+//!
+//! Commands for the optional local read-only CardDAV server. The
+//! credentials are shown to the user so they can enter them in their mail
+//! client.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::carddav::{self, CardDavSettings};
+use crate::error::CommandError;
+use crate::state::AppState;
+
+/// CardDAV server status for the frontend.
+#[derive(Serialize)]
+pub struct CardDavInfo {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// Address book URL to enter in the mail client.
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+fn load(data_dir: &Path) -> Result<CardDavSettings, CommandError> {
+    carddav::load(data_dir)
+        .map_err(|e| CommandError::Storage(format!("Failed to read the CardDAV password: {}", e)))
+}
+
+fn info(settings: &CardDavSettings) -> CardDavInfo {
+    let running = carddav::running_port();
+    let port = running.unwrap_or(settings.port);
+    CardDavInfo {
+        enabled: settings.enabled,
+        running: running.is_some(),
+        port,
+        url: format!("http://127.0.0.1:{}/addressbooks/contacts/", port),
+        username: settings.username.clone(),
+        password: settings.password.clone(),
+    }
+}
+
+/// Get the CardDAV server status and credentials.
+#[tauri::command]
+pub fn get_carddav_settings(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CardDavInfo, CommandError> {
+    let state = state.lock().unwrap();
+    Ok(info(&load(state.data_dir())?))
+}
+
+/// Enable or disable the CardDAV server, optionally on another port.
+///
+/// Credentials are generated the first time it is enabled.
+#[tauri::command]
+pub fn set_carddav_enabled(
+    enabled: bool,
+    port: Option<u16>,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CardDavInfo, CommandError> {
+    let data_dir = state.lock().unwrap().data_dir().to_path_buf();
+    let mut settings = load(&data_dir)?;
+    settings.enabled = enabled;
+    if let Some(port) = port {
+        settings.port = port;
+    }
+    if enabled && settings.password.is_empty() {
+        settings.password = carddav::generate_password();
+    }
+    carddav::save(&data_dir, &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save CardDAV settings: {}", e)))?;
+    carddav::apply(&app, &settings)
+        .map_err(|e| CommandError::Network(format!("Failed to start CardDAV server: {}", e)))?;
+    Ok(info(&settings))
+}
+
+/// Replace the CardDAV password, disconnecting clients using the old one.
+#[tauri::command]
+pub fn regenerate_carddav_password(
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CardDavInfo, CommandError> {
+    let data_dir = state.lock().unwrap().data_dir().to_path_buf();
+    // The old password is replaced, so it need not be readable
+    let mut settings = carddav::load_file(&data_dir);
+    settings.password = carddav::generate_password();
+    carddav::save(&data_dir, &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save CardDAV settings: {}", e)))?;
+    carddav::apply(&app, &settings)
+        .map_err(|e| CommandError::Network(format!("Failed to restart CardDAV server: {}", e)))?;
+    Ok(info(&settings))
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Clipboard Commands
+//!
+//! Copying from Rust lets sensitive values be cleared again without the
+//! frontend keeping timers, and keeps them out of logs.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{AppHandle, State};
+
+use crate::clipboard::{self, ClipboardSettings};
+use crate::error::CommandError;
+use crate::secret::SecretString;
+use crate::state::AppState;
+
+/// Copy text to the clipboard. Sensitive values are cleared after the
+/// configured timeout if still on the clipboard.
+#[tauri::command]
+pub fn copy_to_clipboard(
+    value: SecretString,
+    sensitive: bool,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let clear_after = sensitive.then(|| {
+        let settings = clipboard::load(state.lock().unwrap().data_dir());
+        Duration::from_secs(settings.clear_after_secs)
+    });
+    clipboard::copy(&app, value.expose(), clear_after)
+        .map_err(|e| CommandError::Config(format!("Failed to copy to clipboard: {}", e)))
+}
+
+/// Get the clipboard settings.
+#[tauri::command]
+pub fn get_clipboard_settings(state: State<'_, Mutex<AppState>>) -> ClipboardSettings {
+    let state = state.lock().unwrap();
+    clipboard::load(state.data_dir())
+}
+
+/// Set how long sensitive values stay on the clipboard.
+#[tauri::command]
+pub fn set_clipboard_settings(
+    settings: ClipboardSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if !(clipboard::MIN_CLEAR_AFTER_SECS..=clipboard::MAX_CLEAR_AFTER_SECS)
+        .contains(&settings.clear_after_secs)
+    {
+        return Err(CommandError::Validation(format!(
+            "Clear timeout must be between {} and {} seconds",
+            clipboard::MIN_CLEAR_AFTER_SECS,
+            clipboard::MAX_CLEAR_AFTER_SECS
+        )));
+    }
+    let state = state.lock().unwrap();
+    clipboard::save(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save clipboard settings: {}", e)))
+}
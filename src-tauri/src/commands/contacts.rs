@@ -6,12 +6,20 @@
 
 use std::sync::Mutex;
 
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::Serialize;
 use tauri::State;
 use vauchi_core::{AuthMode, ContactField};
 
+use crate::commands::i18n::isolate_ltr;
+use crate::device_mode;
 use crate::error::CommandError;
+use crate::events::{self, AppEvent};
+use crate::reverification::{self, NeedsReverification};
 use crate::state::AppState;
+use crate::unread::{self, UnreadEntry};
 
 /// Contact information for the frontend.
 #[derive(Serialize)]
@@ -55,7 +63,7 @@ pub fn list_contacts(state: State<'_, Mutex<AppState>>) -> Result<Vec<ContactInf
             .collect());
     }
 
-    let contacts = state.storage.list_contacts()?;
+    let contacts = state.cached_contacts()?;
 
     Ok(contacts
         .into_iter()
@@ -69,48 +77,120 @@ pub fn list_contacts(state: State<'_, Mutex<AppState>>) -> Result<Vec<ContactInf
         .collect())
 }
 
-/// List contacts with pagination.
+/// A contact list row: just what the list renders. Unset flags are left
+/// out to keep large pages small.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContactRow {
+    pub id: String,
+    pub display_name: String,
+    #[serde(skip_serializing_if = "is_false")]
+    pub verified: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub recovery_trusted: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// A page of contact rows.
+#[derive(Serialize, Debug)]
+pub struct ContactPage {
+    pub rows: Vec<ContactRow>,
+    /// Pass back as `cursor` to get the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+    /// Number of contacts across all pages.
+    pub total: usize,
+}
+
+/// Sort key of a row: case-insensitive name, then ID for a stable order.
+fn row_key(row: &ContactRow) -> (String, String) {
+    (row.display_name.to_lowercase(), row.id.clone())
+}
+
+fn encode_cursor(row: &ContactRow) -> String {
+    let key = serde_json::to_vec(&row_key(row)).unwrap_or_default();
+    BASE64.encode(key)
+}
+
+fn decode_cursor(cursor: &str) -> Result<(String, String), CommandError> {
+    BASE64
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| CommandError::Validation("Invalid contact list cursor".to_string()))
+}
+
+/// Cut one page out of `rows`, sorted by name. A cursor takes precedence
+/// over the offset and stays valid when contacts before it are added or
+/// removed.
+fn page_rows(
+    mut rows: Vec<ContactRow>,
+    offset: Option<u32>,
+    cursor: Option<&str>,
+    limit: u32,
+) -> Result<ContactPage, CommandError> {
+    rows.sort_by_cached_key(row_key);
+    let total = rows.len();
+    let start = match cursor {
+        Some(cursor) => {
+            let after = decode_cursor(cursor)?;
+            rows.partition_point(|row| row_key(row) <= after)
+        }
+        None => (offset.unwrap_or(0) as usize).min(total),
+    };
+    let end = start.saturating_add(limit as usize).min(total);
+    let next_cursor = (end < total && end > start).then(|| encode_cursor(&rows[end - 1]));
+    rows.truncate(end);
+    Ok(ContactPage {
+        rows: rows.split_off(start),
+        next_cursor,
+        total,
+    })
+}
+
+/// List visible contacts a page at a time, sorted by name.
 ///
-/// In duress mode, paginates over decoy contacts.
+/// Pages are addressed by `cursor` (from the previous page) or, failing
+/// that, by `offset`. In duress mode, paginates over decoy contacts.
 #[tauri::command]
 pub fn list_contacts_paginated(
-    offset: u32,
+    offset: Option<u32>,
+    cursor: Option<String>,
     limit: u32,
     state: State<'_, Mutex<AppState>>,
-) -> Result<Vec<ContactInfo>, CommandError> {
+) -> Result<ContactPage, CommandError> {
     let state = state.lock().unwrap();
 
-    if state.auth_mode == AuthMode::Duress {
-        let decoys = state
+    let rows: Vec<ContactRow> = if state.auth_mode == AuthMode::Duress {
+        state
             .storage
             .load_decoy_contacts()
-            .map_err(|e| CommandError::Storage(e.to_string()))?;
-        return Ok(decoys
+            .map_err(|e| CommandError::Storage(e.to_string()))?
             .into_iter()
-            .skip(offset as usize)
-            .take(limit as usize)
-            .map(|(id, display_name, _card)| ContactInfo {
+            .map(|(id, display_name, _card)| ContactRow {
                 id,
                 display_name,
                 verified: false,
                 recovery_trusted: false,
             })
-            .collect());
-    }
-
-    let contacts = state
-        .storage
-        .list_contacts_paginated(offset as usize, limit as usize)?;
+            .collect()
+    } else {
+        // Served from the contact cache after the first page
+        state
+            .cached_contacts()?
+            .iter()
+            .filter(|c| !c.is_hidden())
+            .map(|c| ContactRow {
+                id: c.id().to_string(),
+                display_name: c.display_name().to_string(),
+                verified: c.is_fingerprint_verified(),
+                recovery_trusted: c.is_recovery_trusted(),
+            })
+            .collect()
+    };
 
-    Ok(contacts
-        .into_iter()
-        .map(|c| ContactInfo {
-            id: c.id().to_string(),
-            display_name: c.display_name().to_string(),
-            verified: c.is_fingerprint_verified(),
-            recovery_trusted: c.is_recovery_trusted(),
-        })
-        .collect())
+    page_rows(rows, offset, cursor.as_deref(), limit)
 }
 
 /// Search contacts using SQL-level search.
@@ -194,8 +274,7 @@ pub fn get_contact(
     }
 
     let contact = state
-        .storage
-        .load_contact(&id)?
+        .cached_contact(&id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     let fields: Vec<super::card::FieldInfo> = contact
@@ -223,11 +302,36 @@ pub fn get_contact(
 #[tauri::command]
 pub fn remove_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
-    state
-        .storage
-        .delete_contact(&id)
-        .map_err(CommandError::from)
+    let deleted = state.delete_contact(&id).map_err(CommandError::from)?;
+    reverification::resolve(state.data_dir(), &id)?;
+    Ok(deleted)
+}
+
+/// Get unseen-change counts for contacts with card updates since last viewed.
+#[tauri::command]
+pub fn get_unread_counts(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<BTreeMap<String, UnreadEntry>, CommandError> {
+    let state = state.lock().unwrap();
+    Ok(unread::load(state.data_dir()))
+}
+
+/// Mark a contact's changes as seen.
+#[tauri::command]
+pub fn mark_contact_seen(
+    contact_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<bool, CommandError> {
+    let state = state.lock().unwrap();
+    let had_unread = unread::mark_seen(state.data_dir(), &contact_id)?;
+    if had_unread {
+        events::publish(AppEvent::UnreadChanged {
+            total: unread::total(state.data_dir()),
+        });
+    }
+    Ok(had_unread)
 }
 
 /// Fingerprint info for verification.
@@ -244,7 +348,7 @@ pub struct FingerprintInfo {
 }
 
 /// Format raw hex as groups of 4 uppercase chars for human-readable display.
-fn format_hex_fingerprint(raw_hex: &str) -> String {
+pub(crate) fn format_hex_fingerprint(raw_hex: &str) -> String {
     raw_hex
         .chars()
         .collect::<Vec<_>>()
@@ -269,17 +373,17 @@ pub fn get_contact_fingerprint(
         .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
 
     let contact = state
-        .storage
-        .load_contact(&id)?
+        .cached_contact(&id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     // Use Contact::fingerprint() API for the contact's fingerprint
-    let formatted_their = contact.fingerprint();
+    // LTR-isolated so the groups keep their order in RTL layouts
+    let formatted_their = isolate_ltr(&contact.fingerprint());
     let their_fingerprint = hex::encode(contact.public_key());
 
     // Format our own fingerprint the same way
     let our_fingerprint = hex::encode(identity.signing_keypair().public_key().as_bytes());
-    let formatted_our = format_hex_fingerprint(&our_fingerprint);
+    let formatted_our = isolate_ltr(&format_hex_fingerprint(&our_fingerprint));
 
     Ok(FingerprintInfo {
         their_fingerprint,
@@ -293,11 +397,11 @@ pub fn get_contact_fingerprint(
 #[tauri::command]
 pub fn verify_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     // Load the contact
     let mut contact = state
-        .storage
-        .load_contact(&id)?
+        .cached_contact(&id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     // Mark as verified
@@ -305,21 +409,33 @@ pub fn verify_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<b
 
     // Save the updated contact
     state
-        .storage
         .save_contact(&contact)
         .map_err(|e| CommandError::Contact(format!("Failed to save contact: {:?}", e)))?;
 
+    // Confirms a recovered contact's new fingerprint
+    reverification::resolve(state.data_dir(), &id)?;
+
     Ok(true)
 }
 
+/// List recovered contacts whose new fingerprint has not been confirmed
+/// with `verify_contact` yet.
+#[tauri::command]
+pub fn list_contacts_needing_reverification(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<NeedsReverification>, CommandError> {
+    let state = state.lock().unwrap();
+    Ok(reverification::load(state.data_dir()))
+}
+
 /// Mark a contact as trusted for recovery.
 #[tauri::command]
 pub fn trust_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let mut contact = state
-        .storage
-        .load_contact(&id)?
+        .cached_contact(&id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     if contact.is_blocked() {
@@ -328,10 +444,15 @@ pub fn trust_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<bo
         ));
     }
 
+    if reverification::is_pending(state.data_dir(), &id) {
+        return Err(CommandError::Contact(
+            "Verify this contact's new fingerprint before trusting them for recovery".to_string(),
+        ));
+    }
+
     contact.trust_for_recovery();
 
     state
-        .storage
         .save_contact(&contact)
         .map_err(|e| CommandError::Contact(format!("Failed to save contact: {:?}", e)))?;
 
@@ -345,16 +466,15 @@ pub fn untrust_contact(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let mut contact = state
-        .storage
-        .load_contact(&id)?
+        .cached_contact(&id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     contact.untrust_for_recovery();
 
     state
-        .storage
         .save_contact(&contact)
         .map_err(|e| CommandError::Contact(format!("Failed to save contact: {:?}", e)))?;
 
@@ -366,7 +486,7 @@ pub fn untrust_contact(
 pub fn trusted_contact_count(state: State<'_, Mutex<AppState>>) -> Result<u32, CommandError> {
     let state = state.lock().unwrap();
 
-    let contacts = state.storage.list_contacts()?;
+    let contacts = state.cached_contacts()?;
     let count = contacts.iter().filter(|c| c.is_recovery_trusted()).count();
 
     Ok(count as u32)
@@ -376,16 +496,15 @@ pub fn trusted_contact_count(state: State<'_, Mutex<AppState>>) -> Result<u32, C
 #[tauri::command]
 pub fn hide_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let mut contact = state
-        .storage
-        .load_contact(&id)?
+        .cached_contact(&id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     contact.hide();
 
     state
-        .storage
         .save_contact(&contact)
         .map_err(|e| CommandError::Contact(format!("Failed to save contact: {:?}", e)))?;
 
@@ -396,16 +515,15 @@ pub fn hide_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<boo
 #[tauri::command]
 pub fn unhide_contact(id: String, state: State<'_, Mutex<AppState>>) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let mut contact = state
-        .storage
-        .load_contact(&id)?
+        .cached_contact(&id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     contact.unhide();
 
     state
-        .storage
         .save_contact(&contact)
         .map_err(|e| CommandError::Contact(format!("Failed to save contact: {:?}", e)))?;
 
@@ -419,7 +537,7 @@ pub fn list_hidden_contacts(
 ) -> Result<Vec<ContactInfo>, CommandError> {
     let state = state.lock().unwrap();
 
-    let contacts = state.storage.list_contacts()?;
+    let contacts = state.cached_contacts()?;
     let hidden: Vec<ContactInfo> = contacts
         .into_iter()
         .filter(|c| c.is_hidden())
@@ -459,7 +577,7 @@ pub fn find_duplicates(
 ) -> Result<Vec<DuplicatePairInfo>, CommandError> {
     let state = state.lock().unwrap();
 
-    let contacts = state.storage.list_contacts()?;
+    let contacts = state.cached_contacts()?;
     let all_duplicates = vauchi_core::contact::merge::find_duplicates(&contacts);
 
     // Load dismissed pairs and filter them out
@@ -498,6 +616,7 @@ pub fn dismiss_duplicate(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let (norm1, norm2) =
         vauchi_core::contact::merge::normalize_pair_key(&contact_id_a, &contact_id_b);
@@ -517,6 +636,7 @@ pub fn undismiss_duplicate(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let (norm1, norm2) =
         vauchi_core::contact::merge::normalize_pair_key(&contact_id_a, &contact_id_b);
@@ -542,26 +662,24 @@ pub fn merge_contacts(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<ContactDetails, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     let primary = state
-        .storage
-        .load_contact(&primary_id)?
+        .cached_contact(&primary_id)?
         .ok_or_else(|| CommandError::Contact("Primary contact not found".to_string()))?;
     let secondary = state
-        .storage
-        .load_contact(&secondary_id)?
+        .cached_contact(&secondary_id)?
         .ok_or_else(|| CommandError::Contact("Secondary contact not found".to_string()))?;
 
     let merged = vauchi_core::contact::merge::merge_contacts(&primary, &secondary);
 
     // Save merged contact
     state
-        .storage
         .save_contact(&merged)
         .map_err(|e| CommandError::Contact(format!("Failed to save merged contact: {:?}", e)))?;
 
     // Delete secondary
-    state.storage.delete_contact(&secondary_id).map_err(|e| {
+    state.delete_contact(&secondary_id).map_err(|e| {
         CommandError::Contact(format!("Failed to delete secondary contact: {:?}", e))
     })?;
 
@@ -604,6 +722,7 @@ pub fn set_contact_limit(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     state
         .storage
@@ -612,3 +731,63 @@ pub fn set_contact_limit(
 
     Ok(true)
 }
+
+// INLINE_TEST_REQUIRED: tests exercise the private paging helper
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(names: &[&str]) -> Vec<ContactRow> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| ContactRow {
+                id: format!("id{}", i),
+                display_name: name.to_string(),
+                verified: false,
+                recovery_trusted: false,
+            })
+            .collect()
+    }
+
+    fn names(page: &ContactPage) -> Vec<&str> {
+        page.rows.iter().map(|r| r.display_name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_cursor_walks_all_pages_in_name_order() {
+        let all = rows(&["carol", "Alice", "bob", "Dave", "erin"]);
+        let first = page_rows(all.clone(), None, None, 2).unwrap();
+        assert_eq!(names(&first), vec!["Alice", "bob"]);
+        assert_eq!(first.total, 5);
+
+        let second = page_rows(all.clone(), None, first.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(names(&second), vec!["carol", "Dave"]);
+        let third = page_rows(all, None, second.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(names(&third), vec!["erin"]);
+        assert_eq!(third.next_cursor, None);
+    }
+
+    #[test]
+    fn test_cursor_survives_insertions_before_it() {
+        let first = page_rows(rows(&["Alice", "bob", "carol"]), None, None, 2).unwrap();
+        let grown = rows(&["Aaron", "Alice", "bob", "carol"]);
+        let next = page_rows(grown, None, first.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(names(&next), vec!["carol"]);
+    }
+
+    #[test]
+    fn test_offset_paging_and_invalid_cursor() {
+        let page = page_rows(rows(&["a", "b", "c"]), Some(2), None, 5).unwrap();
+        assert_eq!(names(&page), vec!["c"]);
+        let past_end = page_rows(rows(&["a"]), Some(9), None, 5).unwrap();
+        assert!(past_end.rows.is_empty());
+        assert!(page_rows(rows(&["a"]), None, Some("nope"), 5).is_err());
+    }
+
+    #[test]
+    fn test_unset_flags_are_omitted() {
+        let json = serde_json::to_value(&rows(&["a"])[0]).unwrap();
+        assert_eq!(json, serde_json::json!({"id": "id0", "display_name": "a"}));
+    }
+}
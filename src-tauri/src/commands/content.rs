@@ -7,11 +7,19 @@
 //! Handles remote content update operations (networks, locales, themes, help).
 
 use serde::Serialize;
+use std::path::Path;
 use std::sync::Mutex;
 use tauri::State;
+use url::Url;
 use vauchi_core::content::{ApplyResult, ContentConfig, ContentManager, ContentType, UpdateStatus};
 
+use crate::commands::app_update;
+use crate::content_bundle;
+use crate::content_diff::{self, ContentDiff};
+use crate::content_signing::{self, VerificationStatus};
+use crate::content_versions::{self, ContentVersionInfo};
 use crate::error::CommandError;
+use crate::error_stats;
 use crate::state::AppState;
 
 /// Status of a content update check.
@@ -40,6 +48,8 @@ pub struct ContentApplyResult {
     pub failed: Vec<String>,
     /// Error message if operation failed.
     pub error: Option<String>,
+    /// Signature check of the content manifest, if one was made.
+    pub verification: Option<VerificationStatus>,
 }
 
 /// Settings for content updates.
@@ -51,8 +61,38 @@ pub struct ContentSettings {
     pub content_url: String,
     /// Check interval in seconds.
     pub check_interval_secs: u64,
+    /// Apply updates found by scheduled checks without asking.
+    pub auto_apply: bool,
+    /// Fallback content URLs, tried in order when `content_url` fails.
+    pub mirror_urls: Vec<String>,
+    /// Content types never updated remotely (e.g. `themes`).
+    pub disabled_types: Vec<String>,
 }
 
+impl ContentSettings {
+    /// Whether remote updates of a content type are allowed.
+    pub fn type_enabled(&self, content_type: &str) -> bool {
+        !self.disabled_types.iter().any(|t| t == content_type)
+    }
+
+    /// Content types remote updates may download.
+    fn enabled_types(&self) -> Vec<ContentType> {
+        ALL_CONTENT_TYPES
+            .into_iter()
+            .filter(|ct| self.type_enabled(&content_type_name(*ct)))
+            .collect()
+    }
+}
+
+/// Content settings file name under the data dir.
+const SETTINGS_FILE: &str = "content_settings.json";
+
+/// File holding the time of the last update check.
+pub(crate) const LAST_CHECK_FILE: &str = "content_last_check";
+
+/// Shortest accepted interval between scheduled checks.
+const MIN_CHECK_INTERVAL_SECS: u64 = 15 * 60;
+
 /// Check for available content updates.
 ///
 /// Returns information about which content types have updates available.
@@ -60,13 +100,25 @@ pub struct ContentSettings {
 pub async fn check_content_updates(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<ContentUpdateStatus, CommandError> {
-    let (settings, data_dir) = {
-        let state = state.lock().unwrap();
-        let settings = load_content_settings(&state)?;
-        let data_dir = state.data_dir().to_path_buf();
-        (settings, data_dir)
-    };
+    error_stats::track("check_content_updates", async {
+        let (settings, data_dir, proxy) = {
+            let state = state.lock().unwrap();
+            let settings = load_content_settings(&state)?;
+            let data_dir = state.data_dir().to_path_buf();
+            (settings, data_dir, app_update::http_proxy(&state)?)
+        };
+        check_updates(&data_dir, &settings, proxy.as_ref()).await
+    })
+    .await
+}
 
+/// Check for content updates with the given settings, through `proxy` if
+/// set, and record the check time.
+pub(crate) async fn check_updates(
+    data_dir: &Path,
+    settings: &ContentSettings,
+    proxy: Option<&Url>,
+) -> Result<ContentUpdateStatus, CommandError> {
     if !settings.enabled {
         return Ok(ContentUpdateStatus {
             has_updates: false,
@@ -77,25 +129,24 @@ pub async fn check_content_updates(
         });
     }
 
-    // Create ContentManager with the storage path
-    let config = ContentConfig {
-        storage_path: data_dir.clone(),
-        remote_updates_enabled: true,
-        ..Default::default()
-    };
-
-    let manager = ContentManager::new(config)
-        .map_err(|e| CommandError::Config(format!("Failed to create content manager: {}", e)))?;
-
-    // Check for updates
-    let status = manager.check_for_updates().await;
+    // Check for updates, failing over to the mirrors
+    let urls = content_urls(settings);
+    let mut status = UpdateStatus::Disabled;
+    for (index, url) in urls.iter().enumerate() {
+        status = content_manager(data_dir, url, proxy)?
+            .check_for_updates()
+            .await;
+        match &status {
+            UpdateStatus::CheckFailed(e) if index + 1 < urls.len() => {
+                tracing::warn!("Content check failed, trying next mirror: {}", e);
+            }
+            _ => break,
+        }
+    }
 
     // Update last check time
-    let check_file = data_dir.join("content_last_check");
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    let check_file = data_dir.join(LAST_CHECK_FILE);
+    let timestamp = crate::clock::now_secs();
     let _ = std::fs::write(&check_file, timestamp.to_string());
 
     match status {
@@ -106,13 +157,20 @@ pub async fn check_content_updates(
             enabled: true,
             error: None,
         }),
-        UpdateStatus::UpdatesAvailable(types) => Ok(ContentUpdateStatus {
-            has_updates: true,
-            available_updates: types.into_iter().map(content_type_name).collect(),
-            last_check: Some(timestamp),
-            enabled: true,
-            error: None,
-        }),
+        UpdateStatus::UpdatesAvailable(types) => {
+            let available: Vec<String> = types
+                .into_iter()
+                .map(content_type_name)
+                .filter(|name| settings.type_enabled(name))
+                .collect();
+            Ok(ContentUpdateStatus {
+                has_updates: !available.is_empty(),
+                available_updates: available,
+                last_check: Some(timestamp),
+                enabled: true,
+                error: None,
+            })
+        }
         UpdateStatus::Disabled => Ok(ContentUpdateStatus {
             has_updates: false,
             available_updates: vec![],
@@ -130,6 +188,14 @@ pub async fn check_content_updates(
     }
 }
 
+/// All content types, in display order.
+const ALL_CONTENT_TYPES: [ContentType; 4] = [
+    ContentType::Networks,
+    ContentType::Locales,
+    ContentType::Themes,
+    ContentType::Help,
+];
+
 /// Convert content type to display name.
 fn content_type_name(ct: ContentType) -> String {
     match ct {
@@ -142,51 +208,138 @@ fn content_type_name(ct: ContentType) -> String {
 
 /// Apply available content updates.
 ///
-/// Downloads and caches any available content updates.
+/// Downloads and caches any available content updates, but only after the
+/// content manifest's signature has been verified against the pinned
+/// publisher key, and only if the update applied is the verified one.
 #[tauri::command]
 pub async fn apply_content_updates(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<ContentApplyResult, CommandError> {
-    let (settings, data_dir) = {
-        let state = state.lock().unwrap();
-        let settings = load_content_settings(&state)?;
-        let data_dir = state.data_dir().to_path_buf();
-        (settings, data_dir)
-    };
+    error_stats::track("apply_content_updates", async {
+        let (settings, data_dir, proxy) = {
+            let state = state.lock().unwrap();
+            let settings = load_content_settings(&state)?;
+            let data_dir = state.data_dir().to_path_buf();
+            (settings, data_dir, app_update::http_proxy(&state)?)
+        };
+        apply_updates(&data_dir, &settings, proxy.as_ref()).await
+    })
+    .await
+}
 
+/// Verify and apply content updates with the given settings, through
+/// `proxy` if set. Types the user opted out of are not downloaded.
+pub(crate) async fn apply_updates(
+    data_dir: &Path,
+    settings: &ContentSettings,
+    proxy: Option<&Url>,
+) -> Result<ContentApplyResult, CommandError> {
     if !settings.enabled {
         return Ok(ContentApplyResult {
             success: true,
             applied: vec![],
             failed: vec![],
             error: Some("Content updates are disabled".to_string()),
+            verification: None,
         });
     }
 
-    // Create ContentManager with the storage path
-    let config = ContentConfig {
-        storage_path: data_dir,
-        remote_updates_enabled: true,
-        ..Default::default()
+    // Refuse unsigned or mis-signed content
+    let (verification, verified) = verified_content_url(settings, proxy).await;
+    let Some((url, manifest)) = verified else {
+        tracing::warn!("Refusing content update: {:?}", verification);
+        return Ok(ContentApplyResult {
+            success: false,
+            applied: vec![],
+            failed: vec![],
+            error: Some("Content signature could not be verified".to_string()),
+            verification: Some(verification),
+        });
     };
 
-    let manager = ContentManager::new(config)
-        .map_err(|e| CommandError::Config(format!("Failed to create content manager: {}", e)))?;
+    let enabled = settings.enabled_types();
+    if enabled.is_empty() {
+        return Ok(ContentApplyResult {
+            success: true,
+            applied: vec![],
+            failed: vec![],
+            error: None,
+            verification: Some(verification),
+        });
+    }
+
+    // Apply to a staging copy, taken only if core applied what was verified
+    let current = content_versions::content_dir(data_dir);
+    let staging = data_dir.join(STAGING_DIR);
+    let _ = std::fs::remove_dir_all(&staging);
+    content_versions::copy_tree(&current, &staging)?;
+    let result = content_manager_at(&staging, &url, proxy)?
+        .apply_updates_for(&enabled)
+        .await;
+    let updated = matches!(result, Ok(ApplyResult::Applied { .. }));
+    if !updated {
+        let _ = std::fs::remove_dir_all(&staging);
+    } else if !content_signing::applied_manifest_matches(&staging, &manifest) {
+        let _ = std::fs::remove_dir_all(&staging);
+        tracing::warn!("Refusing content update: applied manifest differs from the verified one");
+        return Ok(ContentApplyResult {
+            success: false,
+            applied: vec![],
+            failed: vec![],
+            error: Some("Downloaded content does not match the verified manifest".to_string()),
+            verification: Some(VerificationStatus::Mismatch),
+        });
+    }
+
+    // Save the current content so a bad update can be rolled back
+    let now = crate::clock::now_secs();
+    let mut snapshots = Vec::new();
+    for content_type in content_versions::CONTENT_TYPES {
+        if !settings.type_enabled(content_type) {
+            continue;
+        }
+        match content_versions::snapshot(data_dir, content_type, now) {
+            Ok(Some(version)) => snapshots.push((*content_type, version)),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to save {} content version: {}", content_type, e),
+        }
+    }
+
+    if updated {
+        if current.exists() {
+            std::fs::remove_dir_all(&current)?;
+        }
+        std::fs::rename(&staging, &current)?;
+    }
 
-    // Apply updates
-    match manager.apply_updates().await {
+    // Only updated types need their previous version
+    let applied: Vec<String> = match &result {
+        Ok(ApplyResult::Applied { applied, .. }) => {
+            applied.iter().cloned().map(content_type_name).collect()
+        }
+        _ => vec![],
+    };
+    for (content_type, version) in snapshots {
+        if !applied.iter().any(|a| a == content_type) {
+            let _ = content_versions::discard(data_dir, content_type, &version);
+        }
+    }
+
+    match result {
         Ok(result) => match result {
             ApplyResult::NoUpdates => Ok(ContentApplyResult {
                 success: true,
                 applied: vec![],
                 failed: vec![],
                 error: None,
+                verification: Some(verification),
             }),
             ApplyResult::Disabled => Ok(ContentApplyResult {
                 success: true,
                 applied: vec![],
                 failed: vec![],
                 error: Some("Content updates are disabled".to_string()),
+                verification: Some(verification),
             }),
             ApplyResult::Applied { applied, failed } => Ok(ContentApplyResult {
                 success: failed.is_empty(),
@@ -196,6 +349,7 @@ pub async fn apply_content_updates(
                     .map(|(ct, err)| format!("{}: {}", content_type_name(ct), err))
                     .collect(),
                 error: None,
+                verification: Some(verification),
             }),
         },
         Err(e) => Ok(ContentApplyResult {
@@ -203,10 +357,87 @@ pub async fn apply_content_updates(
             applied: vec![],
             failed: vec![],
             error: Some(e.to_string()),
+            verification: Some(verification),
         }),
     }
 }
 
+/// Preview of a content update.
+#[derive(Serialize)]
+pub struct ContentPreview {
+    /// Signature check of the content manifest.
+    pub verification: VerificationStatus,
+    /// What applying would change; `None` if the content was not verified.
+    pub changes: Option<ContentDiff>,
+}
+
+/// Staging directory name under the data dir for previews.
+const PREVIEW_DIR: &str = "content_preview";
+
+/// Staging directory name under the data dir for updates being applied.
+const STAGING_DIR: &str = "content_staging";
+
+/// Report what applying the available content updates would change,
+/// without changing anything.
+///
+/// The update is applied to a copy of the content cache, which is then
+/// compared with the current one.
+#[tauri::command]
+pub async fn preview_content_updates(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ContentPreview, CommandError> {
+    error_stats::track("preview_content_updates", async {
+        let (settings, data_dir, proxy) = {
+            let state = state.lock().unwrap();
+            let settings = load_content_settings(&state)?;
+            let data_dir = state.data_dir().to_path_buf();
+            (settings, data_dir, app_update::http_proxy(&state)?)
+        };
+        if !settings.enabled {
+            return Err(CommandError::Config(
+                "Content updates are disabled".to_string(),
+            ));
+        }
+
+        let (verification, verified) = verified_content_url(&settings, proxy.as_ref()).await;
+        let Some((url, manifest)) = verified else {
+            return Ok(ContentPreview {
+                verification,
+                changes: None,
+            });
+        };
+
+        let current = content_versions::content_dir(&data_dir);
+        let staging = data_dir.join(PREVIEW_DIR);
+        let _ = std::fs::remove_dir_all(&staging);
+        content_versions::copy_tree(&current, &staging)?;
+
+        let result = content_manager_at(&staging, &url, proxy.as_ref())?
+            .apply_updates_for(&settings.enabled_types())
+            .await;
+        let mismatch = matches!(result, Ok(ApplyResult::Applied { .. }))
+            && !content_signing::applied_manifest_matches(&staging, &manifest);
+        let changes = content_diff::diff(&current, &staging);
+        let _ = std::fs::remove_dir_all(&staging);
+        result.map_err(|e| CommandError::Content(e.to_string()))?;
+        if mismatch {
+            return Ok(ContentPreview {
+                verification: VerificationStatus::Mismatch,
+                changes: None,
+            });
+        }
+        if changes.is_empty() {
+            tracing::debug!("Content update preview: nothing would change");
+        }
+
+        Ok(ContentPreview {
+            verification,
+            changes: Some(changes),
+        })
+    })
+    .await
+}
+
 /// Get current content update settings.
 #[tauri::command]
 pub fn get_content_settings(
@@ -223,46 +454,138 @@ pub fn set_content_updates_enabled(
     enabled: bool,
 ) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
-    let config_path = state.data_dir().join("content_settings.json");
-
     let mut settings = load_content_settings(&state)?;
     settings.enabled = enabled;
-
-    let json = serde_json::to_string_pretty(&settings)?;
-
-    std::fs::write(&config_path, json)
-        .map_err(|e| CommandError::Config(format!("Failed to save settings: {}", e)))?;
-
-    Ok(())
+    save_content_settings(state.data_dir(), &settings)
 }
 
 /// Set the content update URL.
 #[tauri::command]
 pub fn set_content_url(state: State<'_, Mutex<AppState>>, url: String) -> Result<(), CommandError> {
     let url = url.trim();
-    if url.is_empty() {
-        return Err(CommandError::Validation(
-            "Content URL cannot be empty".to_string(),
-        ));
+    validate_content_url(url)?;
+
+    let state = state.lock().unwrap();
+    let mut settings = load_content_settings(&state)?;
+    settings.content_url = url.to_string();
+    save_content_settings(state.data_dir(), &settings)
+}
+
+/// Set the fallback content URLs, tried in order when the main one fails.
+#[tauri::command]
+pub fn set_content_mirrors(
+    state: State<'_, Mutex<AppState>>,
+    urls: Vec<String>,
+) -> Result<(), CommandError> {
+    let urls: Vec<String> = urls.iter().map(|u| u.trim().to_string()).collect();
+    for url in &urls {
+        validate_content_url(url)?;
     }
-    if !url.starts_with("https://") {
-        return Err(CommandError::Validation(
-            "Content URL must use HTTPS".to_string(),
-        ));
+    let state = state.lock().unwrap();
+    let mut settings = load_content_settings(&state)?;
+    settings.mirror_urls = urls;
+    save_content_settings(state.data_dir(), &settings)
+}
+
+/// Allow or refuse remote updates of one content type.
+#[tauri::command]
+pub fn set_content_type_enabled(
+    state: State<'_, Mutex<AppState>>,
+    content_type: String,
+    enabled: bool,
+) -> Result<(), CommandError> {
+    if !content_versions::CONTENT_TYPES.contains(&content_type.as_str()) {
+        return Err(CommandError::Validation(format!(
+            "Unknown content type: {}",
+            content_type
+        )));
     }
+    let state = state.lock().unwrap();
+    let mut settings = load_content_settings(&state)?;
+    settings.disabled_types.retain(|t| *t != content_type);
+    if !enabled {
+        settings.disabled_types.push(content_type);
+    }
+    save_content_settings(state.data_dir(), &settings)
+}
 
+/// Set the interval between scheduled content update checks.
+#[tauri::command]
+pub fn set_content_check_interval(
+    state: State<'_, Mutex<AppState>>,
+    secs: u64,
+) -> Result<(), CommandError> {
+    if secs < MIN_CHECK_INTERVAL_SECS {
+        return Err(CommandError::Validation(format!(
+            "Check interval must be at least {} seconds",
+            MIN_CHECK_INTERVAL_SECS
+        )));
+    }
     let state = state.lock().unwrap();
-    let config_path = state.data_dir().join("content_settings.json");
+    let mut settings = load_content_settings(&state)?;
+    settings.check_interval_secs = secs;
+    save_content_settings(state.data_dir(), &settings)
+}
 
+/// Choose whether scheduled checks apply updates automatically.
+#[tauri::command]
+pub fn set_content_auto_apply(
+    state: State<'_, Mutex<AppState>>,
+    enabled: bool,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
     let mut settings = load_content_settings(&state)?;
-    settings.content_url = url.to_string();
+    settings.auto_apply = enabled;
+    save_content_settings(state.data_dir(), &settings)
+}
 
-    let json = serde_json::to_string_pretty(&settings)?;
+/// Import a signed content bundle file, for machines without network
+/// access. Returns the content types updated.
+#[tauri::command]
+pub fn import_content_bundle(
+    path: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<String>, CommandError> {
+    let state = state.lock().unwrap();
+    content_bundle::import(state.data_dir(), Path::new(&path), crate::clock::now_secs())
+        .map_err(|e| CommandError::Content(e.to_string()))
+}
 
-    std::fs::write(&config_path, json)
-        .map_err(|e| CommandError::Config(format!("Failed to save settings: {}", e)))?;
+/// List saved content versions, newest first per content type.
+#[tauri::command]
+pub fn list_content_versions(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ContentVersionInfo>, CommandError> {
+    let state = state.lock().unwrap();
+    Ok(content_versions::CONTENT_TYPES
+        .iter()
+        .flat_map(|content_type| content_versions::list(state.data_dir(), content_type))
+        .collect())
+}
 
-    Ok(())
+/// Restore a saved version of a content type.
+///
+/// The content being replaced is saved as a new version first.
+#[tauri::command]
+pub fn rollback_content(
+    content_type: String,
+    version: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if !content_versions::CONTENT_TYPES.contains(&content_type.as_str()) {
+        return Err(CommandError::Validation(format!(
+            "Unknown content type: {}",
+            content_type
+        )));
+    }
+    let state = state.lock().unwrap();
+    content_versions::rollback(
+        state.data_dir(),
+        &content_type,
+        &version,
+        crate::clock::now_secs(),
+    )
+    .map_err(|e| CommandError::Content(format!("Failed to roll back {}: {}", content_type, e)))
 }
 
 /// Get the list of available social networks.
@@ -279,7 +602,7 @@ pub fn get_social_networks(
 
     // Create ContentManager to get networks
     let config = ContentConfig {
-        storage_path: data_dir,
+        storage_path: content_versions::content_dir(&data_dir),
         remote_updates_enabled: true,
         ..Default::default()
     };
@@ -316,9 +639,90 @@ pub struct SocialNetworkInfo {
 
 // === Helper Functions ===
 
+/// Content URLs to try, primary first.
+fn content_urls(settings: &ContentSettings) -> Vec<String> {
+    let mut urls = vec![settings.content_url.clone()];
+    for mirror in &settings.mirror_urls {
+        if !urls.contains(mirror) {
+            urls.push(mirror.clone());
+        }
+    }
+    urls
+}
+
+/// Find the first content URL serving a verified manifest, returning the
+/// URL and the manifest.
+///
+/// If none does, the first definite answer (unsigned, invalid) is reported
+/// rather than a fetch failure of a later mirror.
+async fn verified_content_url(
+    settings: &ContentSettings,
+    proxy: Option<&Url>,
+) -> (VerificationStatus, Option<(String, Vec<u8>)>) {
+    let mut verification = VerificationStatus::FetchFailed;
+    for url in content_urls(settings) {
+        let status = match content_signing::fetch_verified(&url, proxy).await {
+            Ok(manifest) => return (VerificationStatus::Verified, Some((url, manifest))),
+            Err(status) => status,
+        };
+        if verification == VerificationStatus::FetchFailed {
+            verification = status;
+        }
+        tracing::warn!("Content from {} not verified: {:?}", url, status);
+    }
+    (verification, None)
+}
+
+/// Create a ContentManager caching into the content dir and fetching from `url`.
+fn content_manager(
+    data_dir: &Path,
+    url: &str,
+    proxy: Option<&Url>,
+) -> Result<ContentManager, CommandError> {
+    content_manager_at(&content_versions::content_dir(data_dir), url, proxy)
+}
+
+/// Create a ContentManager caching into `storage_path`, fetching through
+/// `proxy` if set.
+fn content_manager_at(
+    storage_path: &Path,
+    url: &str,
+    proxy: Option<&Url>,
+) -> Result<ContentManager, CommandError> {
+    let config = ContentConfig {
+        storage_path: storage_path.to_path_buf(),
+        content_url: url.to_string(),
+        remote_updates_enabled: true,
+        proxy_url: proxy.map(|p| p.to_string()),
+        ..Default::default()
+    };
+    ContentManager::new(config)
+        .map_err(|e| CommandError::Content(format!("Failed to create content manager: {}", e)))
+}
+
+/// Check that a content URL is usable.
+fn validate_content_url(url: &str) -> Result<(), CommandError> {
+    if url.is_empty() {
+        return Err(CommandError::Validation(
+            "Content URL cannot be empty".to_string(),
+        ));
+    }
+    if !url.starts_with("https://") {
+        return Err(CommandError::Validation(
+            "Content URL must use HTTPS".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 /// Load content settings from disk.
 fn load_content_settings(state: &AppState) -> Result<ContentSettings, CommandError> {
-    let config_path = state.data_dir().join("content_settings.json");
+    read_content_settings(state.data_dir())
+}
+
+/// Load content settings from a data dir.
+pub(crate) fn read_content_settings(data_dir: &Path) -> Result<ContentSettings, CommandError> {
+    let config_path = data_dir.join(SETTINGS_FILE);
 
     if config_path.exists() {
         let json = std::fs::read_to_string(&config_path)
@@ -329,10 +733,20 @@ fn load_content_settings(state: &AppState) -> Result<ContentSettings, CommandErr
             enabled: true,
             content_url: "https://vauchi.app/app-files/".to_string(),
             check_interval_secs: 3600, // 1 hour
+            auto_apply: false,
+            mirror_urls: vec![],
+            disabled_types: vec![],
         })
     }
 }
 
+/// Save content settings.
+fn save_content_settings(data_dir: &Path, settings: &ContentSettings) -> Result<(), CommandError> {
+    let json = serde_json::to_string_pretty(settings)?;
+    std::fs::write(data_dir.join(SETTINGS_FILE), json)
+        .map_err(|e| CommandError::Config(format!("Failed to save settings: {}", e)))
+}
+
 /// Get bundled social networks.
 fn get_bundled_networks() -> Vec<SocialNetworkInfo> {
     vec![
@@ -385,6 +799,12 @@ impl<'de> serde::Deserialize<'de> for ContentSettings {
             enabled: bool,
             content_url: String,
             check_interval_secs: u64,
+            #[serde(default)]
+            auto_apply: bool,
+            #[serde(default)]
+            mirror_urls: Vec<String>,
+            #[serde(default)]
+            disabled_types: Vec<String>,
         }
 
         let helper = ContentSettingsHelper::deserialize(deserializer)?;
@@ -392,6 +812,9 @@ impl<'de> serde::Deserialize<'de> for ContentSettings {
             enabled: helper.enabled,
             content_url: helper.content_url,
             check_interval_secs: helper.check_interval_secs,
+            auto_apply: helper.auto_apply,
+            mirror_urls: helper.mirror_urls,
+            disabled_types: helper.disabled_types,
         })
     }
 }
@@ -0,0 +1,28 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Deep Link Commands
+//!
+//! Lets the frontend pick up a `vauchi://` link that arrived before it was
+//! listening (e.g. the link that launched the app).
+
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::deep_link::DeepLinkEvent;
+use crate::state::AppState;
+
+/// Take the pending deep link, if any.
+///
+/// Returns the same payload as the `deep-link` event and clears it, so each
+/// link is handled exactly once.
+#[tauri::command]
+pub fn take_pending_deep_link(state: State<'_, Mutex<AppState>>) -> Option<DeepLinkEvent> {
+    let mut state = state.lock().unwrap();
+    state.pending_deep_link.take().map(|link| DeepLinkEvent {
+        route: link.route().to_string(),
+        link,
+    })
+}
@@ -10,16 +10,24 @@ use std::fmt::Write;
 use std::sync::Mutex;
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use qrcode::QrCode;
-use serde::{Deserialize, Serialize};
-use tauri::State;
+use qrcode::{EcLevel, QrCode};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
 use vauchi_core::exchange::{
     compute_confirmation_mac, DeviceLinkQR, DeviceLinkResponder, DeviceLinkResponse, ProximityProof,
 };
 use vauchi_core::Identity;
+use zeroize::Zeroizing;
 
+use crate::background;
+use crate::device_mode::{self, DeviceMode};
 use crate::error::CommandError;
-use crate::state::AppState;
+use crate::error_stats;
+use crate::events::{self, AppEvent};
+use crate::logging;
+use crate::milestones::{self, Milestone};
+use crate::secret::SecretString;
+use crate::state::{AppState, PendingJoin};
 
 /// Device info for the frontend.
 #[derive(Serialize)]
@@ -34,8 +42,11 @@ pub struct DeviceInfo {
 /// Get list of all linked devices.
 #[tauri::command]
 pub fn list_devices(state: State<'_, Mutex<AppState>>) -> Result<Vec<DeviceInfo>, CommandError> {
-    let state = state.lock().unwrap();
+    list_devices_impl(&state.lock().unwrap())
+}
 
+/// [`list_devices`] on locked state, shared with the test server.
+pub(crate) fn list_devices_impl(state: &AppState) -> Result<Vec<DeviceInfo>, CommandError> {
     // Get current device info from identity
     let identity = state
         .identity
@@ -93,12 +104,35 @@ pub fn get_current_device(state: State<'_, Mutex<AppState>>) -> Result<DeviceInf
     })
 }
 
+/// Get the mode of this device.
+#[tauri::command]
+pub fn get_device_mode(state: State<'_, Mutex<AppState>>) -> DeviceMode {
+    let state = state.lock().unwrap();
+    device_mode::current(&state)
+}
+
+/// Make a linked device a read-only mirror of the others, or a full device
+/// again. Only a full device can change modes, and the primary device
+/// cannot be a mirror. The change reaches the device with the next sync.
+#[tauri::command]
+pub fn set_device_mode(
+    device_id: String,
+    mode: DeviceMode,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::set_mode(&state, &device_id, mode)
+}
+
 /// Generate device link QR data for pairing a new device.
 #[deprecated(note = "Use generate_device_link_qr instead")]
 #[tauri::command]
 pub fn generate_device_link(state: State<'_, Mutex<AppState>>) -> Result<String, CommandError> {
-    let mut state = state.lock().unwrap();
+    generate_device_link_impl(&mut state.lock().unwrap())
+}
 
+/// [`generate_device_link`] on locked state, shared with the test server.
+pub(crate) fn generate_device_link_impl(state: &mut AppState) -> Result<String, CommandError> {
     let identity = state
         .identity
         .as_ref()
@@ -109,7 +143,7 @@ pub fn generate_device_link(state: State<'_, Mutex<AppState>>) -> Result<String,
     let qr_data = qr.to_data_string();
 
     // Store the QR data for use in complete_device_link
-    state.pending_device_link_qr = Some(qr_data.clone());
+    state.pending_device_link_qr = Some(SecretString::from(qr_data.as_str()));
 
     Ok(qr_data)
 }
@@ -140,19 +174,6 @@ pub struct JoinFinishResult {
     pub message: String,
 }
 
-/// Pending device join state stored between steps.
-#[derive(Serialize, Deserialize, Clone)]
-struct PendingJoin {
-    /// The original QR data string.
-    qr_data: String,
-    /// The device name for this new device.
-    device_name: String,
-    /// Confirmation code computed after creating the request (stored because nonce is not recoverable).
-    confirmation_code: String,
-    /// Identity fingerprint from the QR code.
-    fingerprint: String,
-}
-
 /// Start joining another device using link data (Step 1).
 ///
 /// This parses the QR data and creates a join request that must be sent
@@ -176,11 +197,12 @@ pub fn join_device(
     let qr = DeviceLinkQR::from_data_string(&link_data)
         .map_err(|e| CommandError::Device(format!("Invalid link data: {:?}", e)))?;
 
-    // Check if the link has expired
+    // Check if the link has expired (or our clock is off)
     if qr.is_expired() {
         return Err(CommandError::Device(
             "This device link has expired. Please generate a new one.".to_string(),
-        ));
+        )
+        .or_clock_skew());
     }
 
     // Extract the target identity hex before the QR is moved into the responder,
@@ -212,13 +234,12 @@ pub fn join_device(
     let request_b64 = BASE64.encode(&encrypted_request);
 
     // Store pending join state (includes confirmation info since nonce is not recoverable)
-    let pending = PendingJoin {
-        qr_data: link_data,
+    state.pending_device_join = Some(PendingJoin {
+        qr_data: SecretString::new(link_data),
         device_name,
         confirmation_code,
         fingerprint,
-    };
-    state.pending_device_join = Some(serde_json::to_string(&pending).unwrap_or_default());
+    });
 
     Ok(JoinStartResult {
         success: true,
@@ -244,19 +265,18 @@ pub struct JoinConfirmation {
 #[tauri::command]
 pub fn get_join_confirmation_code(
     state: State<'_, Mutex<AppState>>,
-) -> Result<JoinConfirmation, String> {
-    let state = state.lock().unwrap();
+) -> Result<JoinConfirmation, CommandError> {
+    let mut state = state.lock().unwrap();
+    state.clear_expired_device_links();
 
-    let pending_json = state
+    let pending = state
         .pending_device_join
         .as_ref()
-        .ok_or("No pending device join")?;
-    let pending: PendingJoin =
-        serde_json::from_str(pending_json).map_err(|_| "Invalid pending join state")?;
+        .ok_or_else(|| CommandError::Device("No pending device join".to_string()))?;
 
     Ok(JoinConfirmation {
-        confirmation_code: pending.confirmation_code,
-        fingerprint: pending.fingerprint,
+        confirmation_code: pending.confirmation_code.clone(),
+        fingerprint: pending.fingerprint.clone(),
     })
 }
 
@@ -277,16 +297,14 @@ pub fn finish_join_device(
         ));
     }
 
-    // Get pending join state
-    let pending_json = state.pending_device_join.take().ok_or_else(|| {
+    // Get pending join state; it is wiped however this ends
+    state.clear_expired_device_links();
+    let pending = state.pending_device_join.take().ok_or_else(|| {
         CommandError::Device("No pending device join. Call join_device first.".to_string())
     })?;
 
-    let pending: PendingJoin = serde_json::from_str(&pending_json)
-        .map_err(|_| CommandError::Device("Invalid pending join state".to_string()))?;
-
     // Parse the original QR data
-    let qr = DeviceLinkQR::from_data_string(&pending.qr_data)
+    let qr = DeviceLinkQR::from_data_string(pending.qr_data.expose())
         .map_err(|e| CommandError::Device(format!("Invalid QR data: {:?}", e)))?;
 
     // Decode the response
@@ -294,16 +312,19 @@ pub fn finish_join_device(
         CommandError::Device("Invalid response data (not valid base64)".to_string())
     })?;
 
-    // Decrypt the response using the link key
-    let response = DeviceLinkResponse::decrypt(&encrypted_response, qr.link_key())
-        .map_err(|e| CommandError::Device(format!("Failed to decrypt response: {:?}", e)))?;
+    // Decrypt the response using the link key. It carries the master
+    // seed, so it is wiped however this ends
+    let response = Zeroizing::new(
+        DeviceLinkResponse::decrypt(&encrypted_response, qr.link_key())
+            .map_err(|e| CommandError::Device(format!("Failed to decrypt response: {:?}", e)))?,
+    );
 
     // Create identity from the received seed
     let identity = Identity::from_device_link(
         *response.master_seed(),
         response.display_name().to_string(),
         response.device_index(),
-        pending.device_name,
+        pending.device_name.clone(),
     );
 
     let display_name = identity.display_name().to_string();
@@ -314,7 +335,7 @@ pub fn finish_join_device(
         .backup_password()
         .map_err(|e| CommandError::Device(format!("Failed to get backup password: {:?}", e)))?;
     let backup = identity
-        .export_backup(&password)
+        .export_backup(password.expose())
         .map_err(|e| CommandError::Device(format!("Failed to export backup: {:?}", e)))?;
 
     state
@@ -328,7 +349,14 @@ pub fn finish_join_device(
         .save_device_registry(response.registry())
         .map_err(|e| CommandError::Storage(format!("Failed to save device registry: {:?}", e)))?;
 
+    milestones::record(state.data_dir(), Milestone::FirstDeviceLinked);
+    events::publish(AppEvent::DeviceLinked {
+        role: "joiner".to_string(),
+        device_count: response.registry().all_devices().len(),
+    });
+
     // Set identity in app state
+    logging::remember_names([identity.display_name()]);
     state.identity = Some(identity);
 
     Ok(JoinFinishResult {
@@ -339,6 +367,31 @@ pub fn finish_join_device(
     })
 }
 
+/// Seconds between sweeps for expired device link state.
+const LINK_EXPIRY_SWEEP_SECS: u64 = 30;
+
+/// Periodically wipe device link and join state whose QR has expired, so
+/// an abandoned link does not keep its key in memory until the next
+/// device command.
+pub fn start_link_expiry(app: &AppHandle) {
+    let app = app.clone();
+    background::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(LINK_EXPIRY_SWEEP_SECS)).await;
+            let state = app.state::<Mutex<AppState>>();
+            state.lock().unwrap().clear_expired_device_links();
+        }
+    });
+}
+
+/// Clear expired device link state. Returns whether a pending link QR
+/// expired.
+fn clear_expired_link(state: &mut AppState) -> bool {
+    let had_link = state.pending_device_link_qr.is_some();
+    state.clear_expired_device_links();
+    had_link && state.pending_device_link_qr.is_none()
+}
+
 /// Complete a device link request on the existing device.
 ///
 /// This is called on the device that generated the link QR to approve the new device.
@@ -351,7 +404,14 @@ pub fn complete_device_link(
     confirmation_code: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<String, CommandError> {
-    let state = state.lock().unwrap();
+    let mut state = state.lock().unwrap();
+
+    // An expired link is wiped rather than kept around
+    if clear_expired_link(&mut state) {
+        return Err(CommandError::Device(
+            "Device link QR has expired. Generate a new one.".to_string(),
+        ));
+    }
 
     let identity = state.identity.as_ref().ok_or_else(|| {
         CommandError::Identity("No identity found. Cannot complete device link.".to_string())
@@ -362,15 +422,9 @@ pub fn complete_device_link(
         CommandError::Device("No pending device link. Generate a link QR first.".to_string())
     })?;
 
-    let saved_qr = DeviceLinkQR::from_data_string(pending_qr_data)
+    let saved_qr = DeviceLinkQR::from_data_string(pending_qr_data.expose())
         .map_err(|e| CommandError::Device(format!("Invalid saved QR data: {:?}", e)))?;
 
-    if saved_qr.is_expired() {
-        return Err(CommandError::Device(
-            "Device link QR has expired. Generate a new one.".to_string(),
-        ));
-    }
-
     // Get or create device registry
     let registry = state
         .storage
@@ -413,6 +467,12 @@ pub fn complete_device_link(
         .save_device_registry(&updated_registry)
         .map_err(|e| CommandError::Storage(format!("Failed to save registry: {:?}", e)))?;
 
+    milestones::record(state.data_dir(), Milestone::FirstDeviceLinked);
+    events::publish(AppEvent::DeviceLinked {
+        role: "initiator".to_string(),
+        device_count: updated_registry.all_devices().len(),
+    });
+
     // Return the response for the new device
     Ok(BASE64.encode(&encrypted_response))
 }
@@ -440,32 +500,33 @@ pub struct DeviceLinkResponseData {
 pub fn prepare_device_confirmation(
     request_data: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<DeviceConfirmation, String> {
+) -> Result<DeviceConfirmation, CommandError> {
     let mut state = state.lock().unwrap();
 
-    let identity = state
-        .identity
-        .as_ref()
-        .ok_or("No identity found. Cannot prepare device confirmation.")?;
+    // An expired link is wiped rather than kept around
+    if clear_expired_link(&mut state) {
+        return Err(CommandError::Device(
+            "Device link QR has expired. Generate a new one.".to_string(),
+        ));
+    }
 
-    // Get pending QR data
-    let pending_qr_data = state
-        .pending_device_link_qr
-        .as_ref()
-        .ok_or("No pending device link. Generate a link QR first.")?;
+    let identity = state.identity.as_ref().ok_or_else(|| {
+        CommandError::Identity("No identity found. Cannot prepare device confirmation.".to_string())
+    })?;
 
-    let saved_qr = DeviceLinkQR::from_data_string(pending_qr_data)
-        .map_err(|e| format!("Invalid saved QR data: {:?}", e))?;
+    // Get pending QR data
+    let pending_qr_data = state.pending_device_link_qr.as_ref().ok_or_else(|| {
+        CommandError::Device("No pending device link. Generate a link QR first.".to_string())
+    })?;
 
-    if saved_qr.is_expired() {
-        return Err("Device link QR has expired. Generate a new one.".to_string());
-    }
+    let saved_qr = DeviceLinkQR::from_data_string(pending_qr_data.expose())
+        .map_err(|e| CommandError::Device(format!("Invalid saved QR data: {:?}", e)))?;
 
     // Get or create device registry
     let registry = state
         .storage
         .load_device_registry()
-        .map_err(|e| format!("Failed to load registry: {:?}", e))?
+        .map_err(|e| CommandError::Storage(format!("Failed to load registry: {:?}", e)))?
         .unwrap_or_else(|| identity.initial_device_registry());
 
     // Create restored initiator
@@ -474,11 +535,11 @@ pub fn prepare_device_confirmation(
     // Decode and decrypt the request
     let encrypted_request = BASE64
         .decode(&request_data)
-        .map_err(|_| "Invalid request data (not valid base64)".to_string())?;
+        .map_err(|_| CommandError::Device("Invalid request data (not valid base64)".to_string()))?;
 
     let (confirmation, request) = initiator
         .prepare_confirmation(&encrypted_request)
-        .map_err(|e| format!("Failed to prepare confirmation: {:?}", e))?;
+        .map_err(|e| CommandError::Device(format!("Failed to prepare confirmation: {:?}", e)))?;
 
     let result = DeviceConfirmation {
         device_name: confirmation.device_name,
@@ -502,23 +563,24 @@ pub fn prepare_device_confirmation(
 pub fn confirm_device_link_approved(
     confirmation_code: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<DeviceLinkResponseData, String> {
+) -> Result<DeviceLinkResponseData, CommandError> {
     let mut state = state.lock().unwrap();
 
-    let initiator = state
-        .pending_initiator
-        .take()
-        .ok_or("No pending device link initiator. Call prepare_device_confirmation first.")?;
+    let initiator = state.pending_initiator.take().ok_or_else(|| {
+        CommandError::Device(
+            "No pending device link initiator. Call prepare_device_confirmation first.".to_string(),
+        )
+    })?;
 
     let request = state
         .pending_link_request
         .take()
-        .ok_or("No pending device link request.")?;
+        .ok_or_else(|| CommandError::Device("No pending device link request.".to_string()))?;
 
     // Construct evidence-based proximity proof from the confirmation code
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
-        .map_err(|e| format!("Clock error: {e}"))?
+        .map_err(|e| CommandError::Device(format!("Clock error: {e}")))?
         .as_secs();
 
     let mac = compute_confirmation_mac(initiator.qr().link_key(), &confirmation_code);
@@ -529,16 +591,22 @@ pub fn confirm_device_link_approved(
 
     let (encrypted_response, updated_registry, _new_device) = initiator
         .confirm_link(&request, &proof)
-        .map_err(|e| format!("Failed to confirm link: {:?}", e))?;
+        .map_err(|e| CommandError::Device(format!("Failed to confirm link: {:?}", e)))?;
 
     // Save the updated registry
     state
         .storage
         .save_device_registry(&updated_registry)
-        .map_err(|e| format!("Failed to save registry: {:?}", e))?;
+        .map_err(|e| CommandError::Storage(format!("Failed to save registry: {:?}", e)))?;
 
-    // Clear the pending QR data
-    state.pending_device_link_qr = None;
+    // Clear the pending QR data, wiping the link key
+    state.clear_pending_device_link();
+
+    milestones::record(state.data_dir(), Milestone::FirstDeviceLinked);
+    events::publish(AppEvent::DeviceLinked {
+        role: "initiator".to_string(),
+        device_count: updated_registry.all_devices().len(),
+    });
 
     Ok(DeviceLinkResponseData {
         response_data: BASE64.encode(&encrypted_response),
@@ -549,25 +617,42 @@ pub fn confirm_device_link_approved(
 ///
 /// Cleans up all pending device link state without completing the link.
 #[tauri::command]
-pub fn deny_device_link(state: State<'_, Mutex<AppState>>) -> Result<(), String> {
+pub fn deny_device_link(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
     let mut state = state.lock().unwrap();
-    state.pending_initiator = None;
-    state.pending_link_request = None;
-    state.pending_device_link_qr = None;
+    state.clear_pending_device_link();
     Ok(())
 }
 
+/// Pixels per module in rasterized QR codes.
+const PNG_MODULE_PX: u32 = 8;
+
+/// Modules of quiet zone around the code, per QR spec.
+const QUIET_ZONE: usize = 4;
+
+/// Encode data as a QR code. With the large QR modules accessibility
+/// setting, low error correction is used.
+fn encode_qr(data: &str) -> Result<QrCode, CommandError> {
+    // Low error correction needs fewer modules, so each one renders larger
+    let ec_level = if crate::accessibility::large_qr_modules() {
+        EcLevel::L
+    } else {
+        EcLevel::M
+    };
+    QrCode::with_error_correction_level(data.as_bytes(), ec_level)
+        .map_err(|e| CommandError::Validation(format!("Failed to encode QR code: {e}")))
+}
+
 /// Generate an SVG string from QR data.
 ///
 /// Creates a QR code from the given data and renders it as an SVG string
-/// with dark modules drawn as black rectangles on a white background.
-/// Includes a 4-module quiet zone around the code per QR spec.
-pub fn generate_qr_svg(data: &str) -> Result<String, String> {
-    let code =
-        QrCode::new(data.as_bytes()).map_err(|e| format!("Failed to encode QR code: {e}"))?;
+/// with the dark modules drawn as a single black path on a white
+/// background. Horizontal runs of dark modules become one path segment,
+/// which keeps the SVG small enough to animate multipart codes. Includes a
+/// 4-module quiet zone around the code per QR spec.
+pub fn generate_qr_svg(data: &str) -> Result<String, CommandError> {
+    let code = encode_qr(data)?;
     let width = code.width();
-    let quiet_zone = 4;
-    let total = width + quiet_zone * 2;
+    let total = width + QUIET_ZONE * 2;
 
     let mut svg = String::new();
     write!(
@@ -583,26 +668,52 @@ pub fn generate_qr_svg(data: &str) -> Result<String, String> {
     )
     .unwrap();
 
-    // Draw dark modules
+    // Dark modules, one segment per horizontal run
     let colors = code.to_colors();
-    for y in 0..width {
-        for x in 0..width {
-            if colors[y * width + x] == qrcode::Color::Dark {
-                let sx = x + quiet_zone;
-                let sy = y + quiet_zone;
-                write!(
-                    svg,
-                    r#"<rect x="{sx}" y="{sy}" width="1" height="1" fill="black"/>"#,
-                )
-                .unwrap();
+    let mut path = String::new();
+    for (y, row) in colors.chunks(width).enumerate() {
+        let mut x = 0;
+        while x < width {
+            if row[x] != qrcode::Color::Dark {
+                x += 1;
+                continue;
             }
+            let run = row[x..]
+                .iter()
+                .take_while(|c| **c == qrcode::Color::Dark)
+                .count();
+            let sx = x + QUIET_ZONE;
+            let sy = y + QUIET_ZONE;
+            write!(path, "M{sx} {sy}h{run}v1h-{run}z").unwrap();
+            x += run;
         }
     }
+    if !path.is_empty() {
+        write!(svg, r#"<path d="{path}" fill="black"/>"#).unwrap();
+    }
 
     svg.push_str("</svg>");
     Ok(svg)
 }
 
+/// Rasterize QR data as a PNG data URL, for webviews where large SVGs
+/// render slowly.
+pub fn generate_qr_png(data: &str) -> Result<String, CommandError> {
+    let image = encode_qr(data)?
+        .render::<image::Luma<u8>>()
+        .quiet_zone(true)
+        .module_dimensions(PNG_MODULE_PX, PNG_MODULE_PX)
+        .build();
+    let mut png = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| CommandError::Validation(format!("Failed to encode PNG: {e}")))?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        BASE64.encode(png.into_inner())
+    ))
+}
+
 /// Result of generating a device link QR with SVG.
 #[derive(Serialize)]
 pub struct DeviceLinkQRResult {
@@ -618,13 +729,13 @@ pub struct DeviceLinkQRResult {
 #[tauri::command]
 pub fn generate_device_link_qr(
     state: State<'_, Mutex<AppState>>,
-) -> Result<DeviceLinkQRResult, String> {
+) -> Result<DeviceLinkQRResult, CommandError> {
     let mut state = state.lock().unwrap();
 
     let identity = state
         .identity
         .as_ref()
-        .ok_or_else(|| "No identity found".to_string())?;
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
 
     // Generate device link QR
     let qr = DeviceLinkQR::generate(identity);
@@ -635,7 +746,7 @@ pub fn generate_device_link_qr(
     let qr_svg = generate_qr_svg(&qr_data)?;
 
     // Store the QR data for use in complete_device_link
-    state.pending_device_link_qr = Some(qr_data.clone());
+    state.pending_device_link_qr = Some(SecretString::from(qr_data.as_str()));
 
     Ok(DeviceLinkQRResult {
         qr_data,
@@ -652,8 +763,11 @@ pub fn revoke_device(
     device_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, CommandError> {
-    let state = state.lock().unwrap();
+    revoke_device_impl(&state.lock().unwrap(), &device_id)
+}
 
+/// [`revoke_device`] on locked state, shared with the test server.
+pub(crate) fn revoke_device_impl(state: &AppState, device_id: &str) -> Result<bool, CommandError> {
     let identity = state
         .identity
         .as_ref()
@@ -676,7 +790,7 @@ pub fn revoke_device(
         .ok_or_else(|| CommandError::Device("No device registry found".to_string()))?;
 
     // Find and revoke the device
-    let device_id_bytes = hex::decode(&device_id)?;
+    let device_id_bytes = hex::decode(device_id)?;
 
     if device_id_bytes.len() != 32 {
         return Err(CommandError::Validation(
@@ -714,27 +828,34 @@ pub fn revoke_device(
 ///
 /// Returns the base64-encoded encrypted request payload.
 #[tauri::command]
-pub async fn relay_listen_for_request(state: State<'_, Mutex<AppState>>) -> Result<String, String> {
-    let (relay_url, identity_id) = {
-        let state = state.lock().unwrap();
-        let identity = state
-            .identity
-            .as_ref()
-            .ok_or_else(|| "No identity found".to_string())?;
-        let relay_url = state.relay_url().to_string();
-        let identity_id = hex::encode(identity.signing_public_key());
-        (relay_url, identity_id)
-    }; // Lock released before await
-
-    let (payload, sender_token) =
-        crate::relay::listen_for_request(&relay_url, &identity_id, 300).await?;
-
-    {
-        let mut state = state.lock().unwrap();
-        state.pending_sender_token = Some(sender_token);
-    }
+pub async fn relay_listen_for_request(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    error_stats::track("relay_listen_for_request", async {
+        let (relay_url, identity_id) = {
+            let state = state.lock().unwrap();
+            let identity = state
+                .identity
+                .as_ref()
+                .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+            let relay_url = state.relay_url().to_string();
+            let identity_id = hex::encode(identity.signing_public_key());
+            (relay_url, identity_id)
+        }; // Lock released before await
+
+        let (payload, sender_token) =
+            crate::relay::listen_for_request(&relay_url, &identity_id, 300)
+                .await
+                .map_err(CommandError::Relay)?;
+
+        {
+            let mut state = state.lock().unwrap();
+            state.pending_sender_token = Some(sender_token);
+        }
 
-    Ok(BASE64.encode(&payload))
+        Ok(BASE64.encode(&payload))
+    })
+    .await
 }
 
 /// Send a device link response back via relay (initiator/existing device).
@@ -745,21 +866,28 @@ pub async fn relay_listen_for_request(state: State<'_, Mutex<AppState>>) -> Resu
 pub async fn relay_send_response(
     response_data: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<(), String> {
-    let (relay_url, sender_token) = {
-        let mut state = state.lock().unwrap();
-        let relay_url = state.relay_url().to_string();
-        let sender_token = state.pending_sender_token.take().ok_or_else(|| {
-            "No pending sender token. Call relay_listen_for_request first.".to_string()
-        })?;
-        (relay_url, sender_token)
-    }; // Lock released before await
+) -> Result<(), CommandError> {
+    error_stats::track("relay_send_response", async {
+        let (relay_url, sender_token) = {
+            let mut state = state.lock().unwrap();
+            let relay_url = state.relay_url().to_string();
+            let sender_token = state.pending_sender_token.take().ok_or_else(|| {
+                CommandError::Device(
+                    "No pending sender token. Call relay_listen_for_request first.".to_string(),
+                )
+            })?;
+            (relay_url, sender_token)
+        }; // Lock released before await
 
-    let payload = BASE64
-        .decode(&response_data)
-        .map_err(|_| "Invalid response data (not valid base64)".to_string())?;
+        let payload = BASE64.decode(&response_data).map_err(|_| {
+            CommandError::Device("Invalid response data (not valid base64)".to_string())
+        })?;
 
-    crate::relay::send_response(&relay_url, &sender_token, payload).await
+        crate::relay::send_response(&relay_url, &sender_token, payload)
+            .await
+            .map_err(CommandError::Relay)
+    })
+    .await
 }
 
 /// Send a device link request and receive the response via relay (responder/new device).
@@ -772,31 +900,36 @@ pub async fn relay_join_via_relay(
     request_data: String,
     target_identity: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<String, String> {
-    let relay_url = {
-        let state = state.lock().unwrap();
-        state.relay_url().to_string()
-    }; // Lock released before await
-
-    let payload = BASE64
-        .decode(&request_data)
-        .map_err(|_| "Invalid request data (not valid base64)".to_string())?;
+) -> Result<String, CommandError> {
+    error_stats::track("relay_join_via_relay", async {
+        let relay_url = {
+            let state = state.lock().unwrap();
+            state.relay_url().to_string()
+        }; // Lock released before await
+
+        let payload = BASE64.decode(&request_data).map_err(|_| {
+            CommandError::Device("Invalid request data (not valid base64)".to_string())
+        })?;
 
-    // Generate a unique sender token using random bytes
-    let sender_token = {
-        let token_key = vauchi_core::SymmetricKey::generate();
-        hex::encode(token_key.as_bytes())
-    };
+        // Generate a unique sender token using random bytes
+        let sender_token = {
+            let token_key = vauchi_core::SymmetricKey::generate();
+            hex::encode(token_key.as_bytes())
+        };
 
-    let message = crate::relay::DeviceLinkRelayMessage {
-        target_identity,
-        sender_token,
-        payload,
-    };
+        let message = crate::relay::DeviceLinkRelayMessage {
+            target_identity,
+            sender_token,
+            payload,
+        };
 
-    let response = crate::relay::send_and_receive(&relay_url, &message, 300).await?;
+        let response = crate::relay::send_and_receive(&relay_url, &message, 300)
+            .await
+            .map_err(CommandError::Relay)?;
 
-    Ok(BASE64.encode(&response))
+        Ok(BASE64.encode(&response))
+    })
+    .await
 }
 
 /// A single frame of a multipart QR code sequence.
@@ -807,27 +940,52 @@ pub struct MultipartQRFrame {
     pub svg: String,
     /// Raw QR data for client-side Canvas rendering (D-C1: avoid innerHTML)
     pub data: String,
+    /// Pre-rasterized PNG data URL, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub png: Option<String>,
+}
+
+impl MultipartQRFrame {
+    /// Render one frame, optionally with a PNG alongside the SVG.
+    pub fn render(
+        frame_number: usize,
+        total_frames: usize,
+        data: String,
+        with_png: bool,
+    ) -> Result<Self, CommandError> {
+        Ok(Self {
+            frame_number,
+            total_frames,
+            svg: generate_qr_svg(&data)?,
+            png: if with_png {
+                Some(generate_qr_png(&data)?)
+            } else {
+                None
+            },
+            data,
+        })
+    }
 }
 
 /// Generate a multipart QR code sequence for large payloads.
 ///
 /// Each frame contains a `WBMP|frame|total|base64_chunk` header so the
 /// scanning device can reassemble the payload. For small data that fits in
-/// a single QR code the result will contain exactly one frame.
+/// a single QR code the result will contain exactly one frame. With
+/// `png`, each frame also carries a rasterized PNG.
 #[tauri::command]
-pub fn generate_multipart_qr(data: String) -> Result<Vec<MultipartQRFrame>, String> {
+pub fn generate_multipart_qr(
+    data: String,
+    png: Option<bool>,
+) -> Result<Vec<MultipartQRFrame>, CommandError> {
     let bytes = data.as_bytes();
     let chunk_size = 1500; // Safe QR alphanumeric capacity
+    let with_png = png.unwrap_or(false);
 
     // Empty input produces a single frame with empty base64 payload
     if bytes.is_empty() {
         let frame_data = format!("WBMP|1|1|{}", BASE64.encode(b""));
-        return Ok(vec![MultipartQRFrame {
-            frame_number: 1,
-            total_frames: 1,
-            svg: generate_qr_svg(&frame_data)?,
-            data: frame_data,
-        }]);
+        return Ok(vec![MultipartQRFrame::render(1, 1, frame_data, with_png)?]);
     }
 
     let chunks: Vec<&[u8]> = bytes.chunks(chunk_size).collect();
@@ -836,12 +994,12 @@ pub fn generate_multipart_qr(data: String) -> Result<Vec<MultipartQRFrame>, Stri
     let mut frames = Vec::with_capacity(total);
     for (i, chunk) in chunks.iter().enumerate() {
         let frame_data = format!("WBMP|{}|{}|{}", i + 1, total, BASE64.encode(chunk));
-        frames.push(MultipartQRFrame {
-            frame_number: i + 1,
-            total_frames: total,
-            svg: generate_qr_svg(&frame_data)?,
-            data: frame_data,
-        });
+        frames.push(MultipartQRFrame::render(
+            i + 1,
+            total,
+            frame_data,
+            with_png,
+        )?);
     }
 
     Ok(frames)
@@ -872,12 +1030,10 @@ mod tests {
         let mut state = AppState::new(temp_dir.path()).expect("Failed to create state");
 
         // Simulate pending state by setting the QR field
-        state.pending_device_link_qr = Some("fake-qr-data".to_string());
+        state.pending_device_link_qr = Some(SecretString::from("fake-qr-data"));
 
-        // Simulate deny: clear all pending fields (same logic as deny_device_link command)
-        state.pending_initiator = None;
-        state.pending_link_request = None;
-        state.pending_device_link_qr = None;
+        // Same call as the deny_device_link command
+        state.clear_pending_device_link();
 
         assert!(
             state.pending_device_link_qr.is_none(),
@@ -893,10 +1049,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_invalid_or_expired_link_state_is_cleared() {
+        use crate::state::AppState;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let mut state = AppState::new(temp_dir.path()).expect("Failed to create state");
+
+        // Data that no longer parses as a link QR counts as expired
+        state.pending_device_link_qr = Some(SecretString::from("fake-qr-data"));
+        state.pending_device_join = Some(PendingJoin {
+            qr_data: SecretString::from("fake-qr-data"),
+            device_name: "Laptop".to_string(),
+            confirmation_code: "123-456".to_string(),
+            fingerprint: "ab:cd".to_string(),
+        });
+
+        assert!(clear_expired_link(&mut state));
+        assert!(state.pending_device_link_qr.is_none());
+        assert!(state.pending_device_join.is_none());
+        assert!(!clear_expired_link(&mut state));
+    }
+
     #[test]
     fn test_qr_svg_contains_dark_modules() {
         let svg = generate_qr_svg("test-data").unwrap();
-        // QR codes have dark modules rendered as black rects
+        // QR codes have dark modules rendered as a black path
         assert!(
             svg.contains(r#"fill="black""#),
             "SVG should contain dark modules"
@@ -904,24 +1083,24 @@ mod tests {
     }
 
     #[test]
-    fn test_pending_join_serialization_includes_confirmation() {
-        let pending = PendingJoin {
-            qr_data: "WBDL-test".to_string(),
+    fn test_pending_join_is_wiped() {
+        use zeroize::Zeroize;
+
+        let mut pending = PendingJoin {
+            qr_data: SecretString::from("WBDL-test"),
             device_name: "My Desktop".to_string(),
             confirmation_code: "123-456".to_string(),
             fingerprint: "AB:CD:EF".to_string(),
         };
-        let json = serde_json::to_string(&pending).unwrap();
-        let deserialized: PendingJoin = serde_json::from_str(&json).unwrap();
-        assert_eq!(deserialized.confirmation_code, "123-456");
-        assert_eq!(deserialized.fingerprint, "AB:CD:EF");
-        assert_eq!(deserialized.qr_data, "WBDL-test");
-        assert_eq!(deserialized.device_name, "My Desktop");
+        pending.zeroize();
+        assert!(pending.qr_data.expose().is_empty());
+        assert!(pending.confirmation_code.is_empty());
+        assert!(pending.fingerprint.is_empty());
     }
 
     #[test]
     fn test_multipart_qr_single_frame_for_small_data() {
-        let frames = generate_multipart_qr("short-data".to_string()).unwrap();
+        let frames = generate_multipart_qr("short-data".to_string(), None).unwrap();
         assert_eq!(frames.len(), 1);
         assert_eq!(frames[0].frame_number, 1);
         assert_eq!(frames[0].total_frames, 1);
@@ -934,7 +1113,7 @@ mod tests {
     #[test]
     fn test_multipart_qr_multiple_frames_for_large_data() {
         let large = "x".repeat(3000);
-        let frames = generate_multipart_qr(large).unwrap();
+        let frames = generate_multipart_qr(large, None).unwrap();
         assert_eq!(frames.len(), 2);
         assert_eq!(frames[0].frame_number, 1);
         assert_eq!(frames[0].total_frames, 2);
@@ -944,7 +1123,7 @@ mod tests {
 
     #[test]
     fn test_multipart_qr_frame_data_contains_wbmp_header() {
-        let frames = generate_multipart_qr("test-payload".to_string()).unwrap();
+        let frames = generate_multipart_qr("test-payload".to_string(), None).unwrap();
         // The SVG embeds a QR that encodes "WBMP|1|1|<base64>"
         // We verify each frame produces a valid SVG with dark modules
         assert_eq!(frames.len(), 1);
@@ -956,7 +1135,7 @@ mod tests {
 
     #[test]
     fn test_multipart_qr_empty_input_produces_single_frame() {
-        let frames = generate_multipart_qr(String::new()).unwrap();
+        let frames = generate_multipart_qr(String::new(), None).unwrap();
         assert_eq!(frames.len(), 1);
         assert_eq!(frames[0].frame_number, 1);
         assert_eq!(frames[0].total_frames, 1);
@@ -965,7 +1144,7 @@ mod tests {
     #[test]
     fn test_multipart_qr_with_null_bytes_succeeds() {
         let data = "before\0after".to_string();
-        let frames = generate_multipart_qr(data).unwrap();
+        let frames = generate_multipart_qr(data, None).unwrap();
         assert!(!frames.is_empty());
         assert!(
             frames[0].svg.starts_with("<svg"),
@@ -973,6 +1152,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_qr_svg_draws_modules_as_single_path() {
+        let svg = generate_qr_svg(&"WBDL-".repeat(100)).unwrap();
+        assert_eq!(svg.matches("<path").count(), 1);
+        assert_eq!(svg.matches("<rect").count(), 1, "only the background");
+    }
+
+    #[test]
+    fn test_multipart_qr_png_only_when_requested() {
+        let frames = generate_multipart_qr("png-data".to_string(), Some(true)).unwrap();
+        let png = frames[0].png.as_deref().unwrap();
+        assert!(png.starts_with("data:image/png;base64,"));
+
+        let frames = generate_multipart_qr("png-data".to_string(), None).unwrap();
+        assert!(frames[0].png.is_none());
+    }
+
     #[test]
     fn test_generate_qr_svg_with_empty_string_succeeds() {
         let svg = generate_qr_svg("").unwrap();
@@ -0,0 +1,257 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Diagnostics Commands
+//!
+//! Commands for reading logs, performance metrics, startup timings,
+//! error statistics, clock skew and the environment report, managing
+//! local crash reports, and exporting a support bundle or issue report.
+//! Logs and crash messages are redacted before they are written, so
+//! nothing here needs further scrubbing.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::clock_skew::{self, ClockCheckSettings, SkewMeasurement};
+use crate::commands::app_update;
+use crate::crash::{self, CrashReport};
+use crate::environment::{self, EnvironmentReport};
+use crate::error::CommandError;
+use crate::error_stats::{self, ErrorBucket, ErrorStats};
+use crate::help_feedback::{self, HelpFeedback};
+use crate::issue_report::{self, ConfigSummary, IssueReport};
+use crate::logging::{self, LogEntry};
+use crate::metrics::{self, MetricSummary};
+use crate::startup::{self, StartupReport};
+use crate::state::AppState;
+
+/// Default number of log entries returned.
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+/// Number of log entries included in a diagnostics bundle.
+const BUNDLE_LOG_LIMIT: usize = 2000;
+
+/// Support bundle written by `export_diagnostics_bundle`.
+#[derive(Serialize)]
+pub struct DiagnosticsBundle {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub generated_at: u64,
+    pub has_identity: bool,
+    pub logs: Vec<LogEntry>,
+    pub crash_reports: Vec<CrashReport>,
+    /// Error counts per command and kind, hourly.
+    pub error_stats: Vec<ErrorBucket>,
+    /// Help feedback, only when the user opts in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help_feedback: Option<Vec<HelpFeedback>>,
+}
+
+/// Get recent log entries at or above `level` (default "info"), newest last.
+#[tauri::command]
+pub fn get_recent_logs(
+    level: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, Mutex<AppState>>,
+) -> Vec<LogEntry> {
+    let state = state.lock().unwrap();
+    logging::recent_entries(
+        state.data_dir(),
+        level.as_deref().unwrap_or("info"),
+        limit.unwrap_or(DEFAULT_LOG_LIMIT),
+    )
+}
+
+/// Write a diagnostics bundle (JSON) to `path` for attaching to a bug report.
+///
+/// Help feedback is included only with `include_help_feedback`.
+#[tauri::command]
+pub fn export_diagnostics_bundle(
+    path: String,
+    include_help_feedback: Option<bool>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    let state = state.lock().unwrap();
+
+    let bundle = DiagnosticsBundle {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        generated_at: crate::clock::now_secs(),
+        has_identity: state.identity.is_some(),
+        logs: logging::recent_entries(state.data_dir(), "info", BUNDLE_LOG_LIMIT),
+        crash_reports: crash::list_reports(&crash::crash_dir(state.data_dir())),
+        error_stats: error_stats::stats(None).buckets,
+        help_feedback: include_help_feedback
+            .unwrap_or(false)
+            .then(|| help_feedback::load_feedback(state.data_dir())),
+    };
+
+    let path = PathBuf::from(path);
+    let json = serde_json::to_string_pretty(&bundle)?;
+    std::fs::write(&path, json)?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Write an issue report zip for attaching to a bug report and return its
+/// path. Log excerpts are included only with `include_logs`, and a summary of
+/// settings only with `include_config`. Keys and contact data never are.
+#[tauri::command]
+pub fn create_issue_report(
+    include_logs: Option<bool>,
+    include_config: Option<bool>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    let state = state.lock().unwrap();
+    let data_dir = state.data_dir();
+
+    let config = if include_config.unwrap_or(false) {
+        Some(ConfigSummary {
+            locale: state.locale_code().to_string(),
+            relay_url: state.relay_url().to_string(),
+            tor_enabled: state
+                .storage
+                .load_or_create_tor_config()
+                .map_err(|e| CommandError::Config(e.to_string()))?
+                .enabled,
+            ntp_enabled: clock_skew::load_settings(data_dir).ntp_enabled,
+            app_updates_enabled: app_update::updates_enabled(data_dir),
+        })
+    } else {
+        None
+    };
+
+    let report = IssueReport {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        generated_at: crate::clock::now_secs(),
+        has_identity: state.identity.is_some(),
+        environment: environment::load(data_dir),
+        clock_skew: clock_skew::last(),
+        error_stats: error_stats::stats(None).buckets,
+        crash_reports: crash::list_reports(&crash::crash_dir(data_dir)),
+        config,
+    };
+
+    let path = issue_report::write(data_dir, &report, include_logs.unwrap_or(false))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// List locally stored crash reports, newest first.
+#[tauri::command]
+pub fn list_crash_reports(state: State<'_, Mutex<AppState>>) -> Vec<CrashReport> {
+    let state = state.lock().unwrap();
+    crash::list_reports(&crash::crash_dir(state.data_dir()))
+}
+
+/// Delete crash reports by ID, or all of them when `ids` is omitted.
+///
+/// Returns the number of reports deleted.
+#[tauri::command]
+pub fn delete_crash_reports(ids: Option<Vec<String>>, state: State<'_, Mutex<AppState>>) -> usize {
+    let state = state.lock().unwrap();
+    crash::delete_reports(&crash::crash_dir(state.data_dir()), ids.as_deref())
+}
+
+/// Get timing percentiles for commands and sync phases.
+///
+/// Commands are timed to completion, async ones included; `sync` is also
+/// broken down into its `sync:*` phases.
+#[tauri::command]
+pub fn get_performance_metrics(reset: Option<bool>) -> Vec<MetricSummary> {
+    let summary = metrics::snapshot();
+    if reset.unwrap_or(false) {
+        metrics::reset();
+    }
+    summary
+}
+
+/// Get the time spent in each startup phase, for tracking regressions in
+/// time-to-window.
+#[tauri::command]
+pub fn get_startup_report() -> StartupReport {
+    startup::report()
+}
+
+/// Get the environment checks (keychain, webview, clock, locale files)
+/// run on first launch. `refresh: true` runs them again, with the NTP
+/// clock check if it is turned on and Tor mode is off.
+#[tauri::command]
+pub async fn get_environment_report(
+    refresh: Option<bool>,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<EnvironmentReport, CommandError> {
+    error_stats::track("get_environment_report", async {
+        let (data_dir, query_ntp) = {
+            let state = state.lock().unwrap();
+            (
+                state.data_dir().to_path_buf(),
+                clock_skew::ntp_allowed(&state),
+            )
+        };
+        if !refresh.unwrap_or(false) {
+            if let Some(report) = environment::load(&data_dir) {
+                return Ok(report);
+            }
+        }
+        let locales_dir = app
+            .path()
+            .resource_dir()
+            .map(|d| d.join("locales"))
+            .unwrap_or_else(|_| data_dir.join("locales"));
+        tauri::async_runtime::spawn_blocking(move || {
+            environment::run(&data_dir, &locales_dir, query_ntp)
+        })
+        .await
+        .map_err(|e| CommandError::Config(format!("Environment checks failed: {}", e)))
+    })
+    .await
+}
+
+/// Clock skew as last measured against the relay or NTP.
+#[derive(Serialize)]
+pub struct ClockSkewStatus {
+    /// `None` until a sync or NTP query has run.
+    pub last: Option<SkewMeasurement>,
+    /// Whether the last skew is large enough to break expiry checks.
+    pub excessive: bool,
+    pub ntp_enabled: bool,
+}
+
+/// Get the last measured clock skew and whether NTP checks are on.
+#[tauri::command]
+pub fn get_clock_skew_status(state: State<'_, Mutex<AppState>>) -> ClockSkewStatus {
+    let state = state.lock().unwrap();
+    ClockSkewStatus {
+        last: clock_skew::last(),
+        excessive: clock_skew::excessive_skew().is_some(),
+        ntp_enabled: clock_skew::load_settings(state.data_dir()).ntp_enabled,
+    }
+}
+
+/// Turn the NTP clock check on or off. It contacts pool.ntp.org, so it
+/// is off by default; it does not run in Tor mode either way.
+#[tauri::command]
+pub fn set_clock_check_settings(
+    settings: ClockCheckSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    clock_skew::save_settings(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save clock check settings: {}", e)))
+}
+
+/// Get command error counts for the last `hours` (default: all kept),
+/// by hour, command and kind.
+#[tauri::command]
+pub fn get_error_stats(hours: Option<u64>) -> ErrorStats {
+    error_stats::stats(hours)
+}
@@ -4,16 +4,22 @@
 
 //! Emergency Broadcast Commands
 //!
-//! Tauri commands for configuring emergency broadcast settings.
+//! Tauri commands for configuring and triggering emergency broadcasts.
 
 use std::sync::Mutex;
 
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use vauchi_core::api::EmergencyBroadcastConfig;
-use vauchi_core::{PendingUpdate, SymmetricKey, UpdateStatus};
+use vauchi_core::PendingUpdate;
 
+use crate::commands::sync;
+use crate::dead_mans_switch::{self, DeadMansSwitch, SwitchAction};
+use crate::device_mode;
+use crate::emergency_escalation::{self, ActiveBroadcast, EmergencyTier, TierProgress};
+use crate::emergency_sync::{self, CoarseLocation, IncomingEmergencyAlert};
 use crate::error::CommandError;
+use crate::error_stats;
 use crate::state::AppState;
 
 /// Emergency config information for the frontend.
@@ -22,6 +28,8 @@ pub struct EmergencyConfigInfo {
     pub trusted_contact_ids: Vec<String>,
     pub message: String,
     pub include_location: bool,
+    /// Escalation tiers; empty if all contacts are notified at once.
+    pub tiers: Vec<EmergencyTier>,
 }
 
 /// Emergency config input from the frontend.
@@ -30,6 +38,10 @@ pub struct EmergencyConfigInput {
     pub trusted_contact_ids: Vec<String>,
     pub message: String,
     pub include_location: bool,
+    /// Escalation tiers, first tier notified immediately. Contacts in a
+    /// tier are added to the trusted contacts.
+    #[serde(default)]
+    pub tiers: Vec<EmergencyTier>,
 }
 
 /// Get the current emergency broadcast configuration.
@@ -42,10 +54,12 @@ pub fn get_emergency_config(
         .storage
         .load_emergency_config()
         .map_err(|e| CommandError::Storage(e.to_string()))?;
+    let tiers = emergency_escalation::load_tiers(state.data_dir());
     Ok(config.map(|c| EmergencyConfigInfo {
         trusted_contact_ids: c.trusted_contact_ids,
         message: c.message,
         include_location: c.include_location,
+        tiers,
     }))
 }
 
@@ -56,25 +70,53 @@ pub fn save_emergency_config(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    if config
+        .tiers
+        .first()
+        .is_some_and(|tier| tier.contact_ids.is_empty())
+    {
+        return Err(CommandError::Validation(
+            "The first emergency tier needs at least one contact".to_string(),
+        ));
+    }
+
+    let mut trusted_contact_ids = config.trusted_contact_ids;
+    for contact_id in config.tiers.iter().flat_map(|t| &t.contact_ids) {
+        if !trusted_contact_ids.contains(contact_id) {
+            trusted_contact_ids.push(contact_id.clone());
+        }
+    }
     let ec = EmergencyBroadcastConfig {
-        trusted_contact_ids: config.trusted_contact_ids,
+        trusted_contact_ids,
         message: config.message,
         include_location: config.include_location,
     };
     state
         .storage
         .save_emergency_config(&ec)
-        .map_err(|e| CommandError::Storage(e.to_string()))
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    emergency_escalation::save_tiers(state.data_dir(), &config.tiers)?;
+    Ok(())
 }
 
 /// Delete emergency broadcast configuration.
 #[tauri::command]
 pub fn delete_emergency_config(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
     state
         .storage
         .delete_emergency_config()
-        .map_err(|e| CommandError::Storage(e.to_string()))
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    emergency_escalation::save_tiers(state.data_dir(), &[])?;
+    // A switch that broadcasts has nothing left to send
+    let mut switch = dead_mans_switch::load(state.data_dir());
+    if switch.enabled && switch.action == SwitchAction::Broadcast {
+        switch.enabled = false;
+        dead_mans_switch::save(state.data_dir(), &switch)?;
+    }
+    Ok(())
 }
 
 /// Result of sending an emergency broadcast.
@@ -86,109 +128,451 @@ pub struct BroadcastResultInfo {
     pub total: usize,
 }
 
+/// Delivery state of an alert for one trusted contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Handed to the relay.
+    Sent,
+    /// Encrypted and queued; delivered on the next sync.
+    Queued,
+    /// The contact no longer exists.
+    NotFound,
+    /// The contact is blocked.
+    Blocked,
+    /// No secure session with the contact yet.
+    NoSession,
+    /// Encrypting or queueing failed.
+    Failed,
+}
+
+/// Alert delivery for one trusted contact.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientDelivery {
+    pub contact_id: String,
+    pub display_name: Option<String>,
+    pub status: DeliveryStatus,
+}
+
+/// Result of triggering an emergency broadcast.
+#[derive(Serialize)]
+pub struct EmergencyBroadcastReport {
+    /// ID shared by all alerts of this broadcast.
+    pub alert_id: String,
+    /// Contacts notified now.
+    pub recipients: Vec<RecipientDelivery>,
+    /// Escalation tiers and their status; empty for tests.
+    pub tiers: Vec<TierProgress>,
+    /// Why the relay could not be reached, if it could not.
+    pub relay_error: Option<String>,
+}
+
+/// Load the emergency config, failing if there is none.
+fn load_config(state: &AppState) -> Result<EmergencyBroadcastConfig, CommandError> {
+    state
+        .storage
+        .load_emergency_config()
+        .map_err(|e| CommandError::Storage(e.to_string()))?
+        .ok_or_else(|| CommandError::Emergency("Emergency broadcast not configured".to_string()))
+}
+
+/// Our public ID, which identifies our alerts.
+fn sender_id(state: &AppState) -> Result<String, CommandError> {
+    state
+        .identity
+        .as_ref()
+        .map(|identity| identity.public_id())
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))
+}
+
+/// Encrypt and queue the alert (or test alert) sent at `sent_at` for the
+/// given contacts.
+///
+/// Returns the per-recipient status and the updates queued.
+fn queue_alerts(
+    state: &AppState,
+    sent_at: u64,
+    contact_ids: &[String],
+    location: Option<CoarseLocation>,
+    test: bool,
+) -> Result<(Vec<RecipientDelivery>, Vec<PendingUpdate>), CommandError> {
+    let config = load_config(state)?;
+    let sender_id = sender_id(state)?;
+
+    let location = location.filter(|_| config.include_location);
+    let mut recipients = Vec::new();
+    let mut queued = Vec::new();
+
+    for contact_id in contact_ids {
+        let contact = match state.storage.load_contact(contact_id) {
+            Ok(Some(c)) => c,
+            _ => {
+                recipients.push(RecipientDelivery {
+                    contact_id: contact_id.clone(),
+                    display_name: None,
+                    status: DeliveryStatus::NotFound,
+                });
+                continue;
+            }
+        };
+
+        let status = if contact.is_blocked() {
+            DeliveryStatus::Blocked
+        } else {
+            match emergency_sync::queue_alert(
+                &state.storage,
+                contact_id,
+                &sender_id,
+                sent_at,
+                &config.message,
+                location,
+                test,
+            ) {
+                Ok(Some(update)) => {
+                    queued.push(update);
+                    DeliveryStatus::Queued
+                }
+                Ok(None) => DeliveryStatus::NoSession,
+                Err(e) => {
+                    tracing::warn!("Failed to queue emergency alert: {}", e);
+                    DeliveryStatus::Failed
+                }
+            }
+        };
+        recipients.push(RecipientDelivery {
+            contact_id: contact_id.clone(),
+            display_name: Some(contact.display_name().to_string()),
+            status,
+        });
+    }
+
+    Ok((recipients, queued))
+}
+
 /// Send an emergency broadcast to all trusted contacts.
 ///
-/// For each trusted contact with an established ratchet session:
-/// 1. Creates an encrypted EmergencyAlert payload
-/// 2. Queues it as a pending update (indistinguishable from card updates)
+/// Alerts are encrypted with each contact's ratchet and queued as pending
+/// updates (indistinguishable from card updates) for the next sync.
 ///
 /// Returns the number of successfully queued alerts vs total contacts.
 #[tauri::command]
 pub fn send_emergency_broadcast(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<BroadcastResultInfo, CommandError> {
-    use vauchi_core::network::EmergencyAlert;
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let config = load_config(&state)?;
+    let (recipients, queued) = queue_alerts(
+        &state,
+        crate::clock::now_secs(),
+        &config.trusted_contact_ids,
+        None,
+        false,
+    )?;
+    Ok(BroadcastResultInfo {
+        sent: queued.len(),
+        total: recipients.len(),
+    })
+}
+
+/// Trigger an emergency broadcast and deliver it right away.
+///
+/// Queues an alert (the configured message, plus `location` rounded to
+/// about 1 km if the config includes location) for the first tier of
+/// trusted contacts, then pushes the queued alerts to the relay
+/// immediately. If the relay cannot be reached, the alerts stay queued for
+/// the next sync. Later tiers are notified as long as nobody acknowledges.
+#[tauri::command]
+pub async fn trigger_emergency_broadcast(
+    location: Option<CoarseLocation>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<EmergencyBroadcastReport, CommandError> {
+    error_stats::track("trigger_emergency_broadcast", async {
+        broadcast(&state, location, false).await
+    })
+    .await
+}
 
+/// Send a test alert to all trusted contacts.
+///
+/// Runs the same pipeline as `trigger_emergency_broadcast` (encryption,
+/// relay delivery), but recipients see the alert marked as a test, so the
+/// setup can be verified without alarming anyone. All tiers are notified at
+/// once and nothing escalates.
+#[tauri::command]
+pub async fn test_emergency_broadcast(
+    location: Option<CoarseLocation>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<EmergencyBroadcastReport, CommandError> {
+    error_stats::track("test_emergency_broadcast", async {
+        broadcast(&state, location, true).await
+    })
+    .await
+}
+
+/// Get the running emergency broadcast and the status of its tiers.
+#[tauri::command]
+pub fn get_emergency_broadcast_status(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Option<ActiveBroadcast>, CommandError> {
     let state = state.lock().unwrap();
+    Ok(emergency_escalation::load_broadcast(state.data_dir()))
+}
 
-    let config = state
-        .storage
-        .load_emergency_config()
-        .map_err(|e| CommandError::Storage(e.to_string()))?
-        .ok_or_else(|| CommandError::Emergency("Emergency broadcast not configured".to_string()))?;
+/// Stop escalating the running emergency broadcast.
+#[tauri::command]
+pub fn cancel_emergency_broadcast(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    emergency_escalation::clear_broadcast(state.data_dir())?;
+    Ok(())
+}
 
-    let identity = state
-        .identity
-        .as_ref()
-        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+/// Start a broadcast: notify the first tier (or, for tests, every trusted
+/// contact) and track the broadcast for escalation.
+pub(crate) async fn broadcast(
+    state: &Mutex<AppState>,
+    location: Option<CoarseLocation>,
+    test: bool,
+) -> Result<EmergencyBroadcastReport, CommandError> {
+    let sent_at = crate::clock::now_secs();
+    let (alert_id, mut tracked, first_tier, data_dir) = {
+        let state = state.lock().unwrap();
+        device_mode::ensure_editable(&state)?;
+        let config = load_config(&state)?;
+        let alert_id = emergency_sync::alert_id(&sender_id(&state)?, sent_at);
+        if test {
+            (alert_id, None, config.trusted_contact_ids, None)
+        } else {
+            let data_dir = state.data_dir().to_path_buf();
+            let tiers = emergency_escalation::effective_tiers(
+                &config.trusted_contact_ids,
+                &emergency_escalation::load_tiers(&data_dir),
+            );
+            let first = tiers[0].contact_ids.clone();
+            let tracked = ActiveBroadcast::new(&alert_id, sent_at, location, tiers);
+            (alert_id, Some(tracked), first, Some(data_dir))
+        }
+    };
 
-    let sender_id = identity.public_id();
-    let total = config.trusted_contact_ids.len();
-    let mut sent = 0;
+    let (recipients, relay_error) =
+        notify_tier(state, sent_at, &first_tier, location, test).await?;
 
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
+    let mut tiers = Vec::new();
+    if let (Some(broadcast), Some(data_dir)) = (tracked.as_mut(), data_dir) {
+        broadcast.tiers[0].notified_at = Some(crate::clock::now_secs());
+        broadcast.tiers[0].recipients = recipients.clone();
+        emergency_escalation::save_broadcast(&data_dir, broadcast)?;
+        tiers = broadcast.tiers.clone();
+    }
 
-    for contact_id in &config.trusted_contact_ids {
-        // Skip contacts that don't exist locally
-        let contact = match state.storage.load_contact(contact_id) {
-            Ok(Some(c)) => c,
-            _ => continue,
-        };
+    Ok(EmergencyBroadcastReport {
+        alert_id,
+        recipients,
+        tiers,
+        relay_error,
+    })
+}
 
-        // Skip blocked contacts
-        if contact.is_blocked() {
-            continue;
+/// Queue the alert sent at `sent_at` for the given contacts and push it to
+/// the relay.
+///
+/// Returns the per-recipient status and, if the relay could not be
+/// reached, why.
+pub(crate) async fn notify_tier(
+    state: &Mutex<AppState>,
+    sent_at: u64,
+    contact_ids: &[String],
+    location: Option<CoarseLocation>,
+    test: bool,
+) -> Result<(Vec<RecipientDelivery>, Option<String>), CommandError> {
+    let (mut recipients, queued, envelopes, storage, relay_url, identity_handle) = {
+        let state = state.lock().unwrap();
+        let (storage, relay_url, identity_handle) = sync::push_target(&state)?;
+        let (recipients, queued) = queue_alerts(&state, sent_at, contact_ids, location, test)?;
+        let sender_id = sender_id(&state)?;
+        let envelopes = queued
+            .iter()
+            .filter_map(|update| {
+                let data = sync::encode_update(&sender_id, update).ok()?;
+                Some((update.id.clone(), data))
+            })
+            .collect::<Vec<_>>();
+        (
+            recipients,
+            queued,
+            envelopes,
+            storage,
+            relay_url,
+            identity_handle,
+        )
+    };
+
+    let mut relay_error = None;
+    if !envelopes.is_empty() {
+        match sync::push_updates(storage, &relay_url, identity_handle, envelopes).await {
+            Ok(sent_ids) => {
+                for update in queued.iter().filter(|u| sent_ids.contains(&u.id)) {
+                    if let Some(recipient) = recipients
+                        .iter_mut()
+                        .find(|r| r.contact_id == update.contact_id)
+                    {
+                        recipient.status = DeliveryStatus::Sent;
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Emergency broadcast not pushed, left queued: {}", e);
+                relay_error = Some(e.to_string());
+            }
         }
+    }
 
-        // Skip contacts without ratchet (can't encrypt)
-        let (mut ratchet, is_initiator) = match state.storage.load_ratchet_state(contact_id) {
-            Ok(Some(r)) => r,
-            _ => continue,
-        };
+    Ok((recipients, relay_error))
+}
 
-        // Create the emergency alert payload
-        let alert = EmergencyAlert {
-            sender_id: sender_id.clone(),
-            message: config.message.clone(),
-            timestamp: now,
-            location: None,
-        };
+/// Dead man's switch settings from the frontend.
+#[derive(Deserialize)]
+pub struct DeadMansSwitchInput {
+    pub enabled: bool,
+    /// Seconds between required check-ins.
+    pub interval_secs: u64,
+    /// Seconds after a missed check-in before the action runs.
+    pub grace_secs: u64,
+    pub action: SwitchAction,
+}
 
-        // Serialize the alert as JSON
-        let alert_bytes = match serde_json::to_vec(&alert) {
-            Ok(b) => b,
-            Err(_) => continue,
-        };
+/// Check-in status for the frontend.
+#[derive(Serialize)]
+pub struct CheckInStatus {
+    pub enabled: bool,
+    pub action: SwitchAction,
+    pub interval_secs: u64,
+    pub grace_secs: u64,
+    pub last_check_in: u64,
+    /// When the next check-in is due.
+    pub due_at: u64,
+    /// When the action runs without a check-in.
+    pub deadline: u64,
+    /// Whether the check-in is due.
+    pub overdue: bool,
+    /// When the action ran, if it did since the last check-in.
+    pub triggered_at: Option<u64>,
+}
 
-        // Encrypt with ratchet (indistinguishable from card update)
-        let ratchet_msg = match ratchet.encrypt(&alert_bytes) {
-            Ok(m) => m,
-            Err(_) => continue,
-        };
-        let encrypted = match serde_json::to_vec(&ratchet_msg) {
-            Ok(b) => b,
-            Err(_) => continue,
-        };
+fn check_in_status(switch: &DeadMansSwitch) -> CheckInStatus {
+    CheckInStatus {
+        enabled: switch.enabled,
+        action: switch.action,
+        interval_secs: switch.interval_secs,
+        grace_secs: switch.grace_secs,
+        last_check_in: switch.last_check_in,
+        due_at: switch.due_at(),
+        deadline: switch.deadline(),
+        overdue: switch.enabled && crate::clock::now_secs() >= switch.due_at(),
+        triggered_at: switch.triggered_at,
+    }
+}
 
-        // Save updated ratchet state
-        if state
+/// Configure the dead man's switch. Saving counts as a check-in.
+#[tauri::command]
+pub fn configure_dead_mans_switch(
+    config: DeadMansSwitchInput,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CheckInStatus, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    if config.interval_secs < dead_mans_switch::MIN_INTERVAL_SECS {
+        return Err(CommandError::Emergency(format!(
+            "Check-in interval must be at least {} seconds",
+            dead_mans_switch::MIN_INTERVAL_SECS
+        )));
+    }
+    if config.grace_secs < dead_mans_switch::MIN_GRACE_SECS {
+        return Err(CommandError::Emergency(format!(
+            "Grace period must be at least {} seconds",
+            dead_mans_switch::MIN_GRACE_SECS
+        )));
+    }
+    if config.enabled && config.action == SwitchAction::Broadcast {
+        let configured = state
             .storage
-            .save_ratchet_state(contact_id, &ratchet, is_initiator)
-            .is_err()
-        {
-            continue;
+            .load_emergency_config()
+            .map_err(|e| CommandError::Storage(e.to_string()))?
+            .is_some();
+        if !configured {
+            return Err(CommandError::Emergency(
+                "Emergency broadcast not configured".to_string(),
+            ));
         }
+    }
 
-        // Generate a random ID (uses ring internally via SymmetricKey::generate)
-        let update_id = hex::encode(&SymmetricKey::generate().as_bytes()[..16]);
-
-        // Queue for delivery (looks like a regular card update on the wire)
-        let update = PendingUpdate {
-            id: update_id,
-            contact_id: contact_id.to_string(),
-            update_type: "card_delta".to_string(),
-            payload: encrypted,
-            created_at: now,
-            retry_count: 0,
-            status: UpdateStatus::Pending,
-        };
-        if state.storage.queue_update(&update).is_ok() {
-            sent += 1;
+    let mut switch = dead_mans_switch::load(state.data_dir());
+    switch.enabled = config.enabled;
+    switch.interval_secs = config.interval_secs;
+    switch.grace_secs = config.grace_secs;
+    switch.action = config.action;
+    switch.check_in(crate::clock::now_secs());
+    dead_mans_switch::save(state.data_dir(), &switch)?;
+    Ok(check_in_status(&switch))
+}
+
+/// Confirm presence, re-arming the dead man's switch.
+#[tauri::command]
+pub fn check_in(state: State<'_, Mutex<AppState>>) -> Result<CheckInStatus, CommandError> {
+    let state = state.lock().unwrap();
+    let mut switch = dead_mans_switch::load(state.data_dir());
+    switch.check_in(crate::clock::now_secs());
+    dead_mans_switch::save(state.data_dir(), &switch)?;
+    Ok(check_in_status(&switch))
+}
+
+/// Get the dead man's switch status.
+#[tauri::command]
+pub fn get_check_in_status(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CheckInStatus, CommandError> {
+    let state = state.lock().unwrap();
+    Ok(check_in_status(&dead_mans_switch::load(state.data_dir())))
+}
+
+/// List emergency alerts received from contacts, newest first.
+#[tauri::command]
+pub fn list_emergency_alerts(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<IncomingEmergencyAlert>, CommandError> {
+    let state = state.lock().unwrap();
+    let mut alerts = emergency_sync::load_alerts(state.data_dir());
+    alerts.reverse();
+    Ok(alerts)
+}
+
+/// Acknowledge a received emergency alert. The sender is told, which stops
+/// their broadcast from escalating; the acknowledgement goes out with the
+/// next sync.
+#[tauri::command]
+pub fn acknowledge_alert(
+    alert_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let now = crate::clock::now_secs();
+    let senders: Vec<String> = emergency_sync::load_alerts(state.data_dir())
+        .into_iter()
+        .filter(|a| a.alert_id == alert_id && a.acknowledged_at.is_none())
+        .map(|a| a.contact_id)
+        .collect();
+    if !emergency_sync::acknowledge(state.data_dir(), &alert_id, now)? {
+        return Err(CommandError::Emergency(
+            "Emergency alert not found".to_string(),
+        ));
+    }
+    for contact_id in senders {
+        if let Err(e) =
+            emergency_sync::queue_acknowledgement(&state.storage, &contact_id, &alert_id, now)
+        {
+            tracing::warn!("Failed to queue emergency acknowledgement: {}", e);
         }
     }
-
-    Ok(BroadcastResultInfo { sent, total })
+    Ok(())
 }
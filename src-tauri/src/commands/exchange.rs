@@ -17,7 +17,14 @@ use vauchi_core::exchange::{
     ExchangeEvent, ExchangeQR, ExchangeSession, ExchangeState, ManualConfirmationVerifier,
 };
 
+use crate::clock;
+use crate::commands::contacts::format_hex_fingerprint;
+use crate::commands::i18n::{isolate, isolate_ltr};
+use crate::default_label;
+use crate::device_mode;
 use crate::error::CommandError;
+use crate::events::{self, AppEvent};
+use crate::milestones;
 use crate::state::AppState;
 
 /// Exchange QR data for the frontend.
@@ -25,12 +32,43 @@ use crate::state::AppState;
 pub struct ExchangeQRResponse {
     /// Base64-encoded QR data string
     pub data: String,
-    /// Display name shown in QR
+    /// Display name shown next to the QR (bidi-isolated)
     pub display_name: String,
     /// ASCII art representation of QR (for terminal/testing)
     pub qr_ascii: String,
 }
 
+/// Who a scanned QR belongs to, for the confirmation screen shown before
+/// `complete_exchange`.
+#[derive(Serialize)]
+pub struct ScannedQrPreview {
+    /// Peer display name from the QR (bidi-isolated)
+    pub display_name: String,
+    /// Peer public ID (hex-encoded)
+    pub public_id: String,
+    /// Peer fingerprint in groups of four (LTR-isolated)
+    pub fingerprint: String,
+    /// Unix time at which the QR expires
+    pub expires_at: u64,
+    /// Seconds left before the QR expires, for a countdown
+    pub expires_in_secs: u64,
+}
+
+impl ScannedQrPreview {
+    /// Preview of a scanned QR at `now`.
+    pub fn new(qr: &ExchangeQR, now: u64) -> Self {
+        let public_id = hex::encode(qr.public_key());
+        let expires_at = qr.expires_at();
+        ScannedQrPreview {
+            display_name: isolate(qr.display_name()),
+            fingerprint: isolate_ltr(&format_hex_fingerprint(&public_id)),
+            public_id,
+            expires_at,
+            expires_in_secs: expires_at.saturating_sub(now),
+        }
+    }
+}
+
 /// Result of completing an exchange.
 #[derive(Serialize)]
 pub struct ExchangeResult {
@@ -52,7 +90,14 @@ pub struct ExchangeResult {
 pub fn start_exchange(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<ExchangeQRResponse, CommandError> {
-    let mut state = state.lock().unwrap();
+    start_exchange_impl(&mut state.lock().unwrap())
+}
+
+/// [`start_exchange`] on locked state, shared with the test server.
+pub(crate) fn start_exchange_impl(
+    state: &mut AppState,
+) -> Result<ExchangeQRResponse, CommandError> {
+    device_mode::ensure_editable(state)?;
 
     if !state.has_identity() {
         return Err(CommandError::Identity(
@@ -71,7 +116,8 @@ pub fn start_exchange(
         .flatten()
         .unwrap_or_else(|| ContactCard::new(identity.display_name()));
 
-    let display_name = identity.display_name().to_string();
+    // Isolated so an RTL name does not reorder the surrounding label
+    let display_name = isolate(identity.display_name());
 
     let verifier = ManualConfirmationVerifier::new();
     let mut session = ExchangeSession::new_qr(identity, our_card, verifier);
@@ -98,14 +144,31 @@ pub fn start_exchange(
 /// Process a scanned QR code from the peer.
 ///
 /// Creates a QR ExchangeSession, applies `StartQR` to initialise it,
-/// then applies `ProcessQR` with the scanned data.
+/// then applies `ProcessQR` with the scanned data. Returns who the QR
+/// belongs to so the user can check before completing the exchange.
 #[tauri::command]
 pub fn process_scanned_qr(
     data: String,
     state: State<'_, Mutex<AppState>>,
-) -> Result<(), CommandError> {
-    let mut state = state.lock().unwrap();
+) -> Result<ScannedQrPreview, CommandError> {
+    process_scanned_qr_impl(&mut state.lock().unwrap(), &data)
+}
+
+/// [`process_scanned_qr`] on locked state, shared with the test server.
+pub(crate) fn process_scanned_qr_impl(
+    state: &mut AppState,
+    data: &str,
+) -> Result<ScannedQrPreview, CommandError> {
+    device_mode::ensure_editable(state)?;
+    process_qr_data(state, data)
+}
 
+/// Start an exchange session from the peer's QR data, however it arrived
+/// (scanned, deep link or NFC tag).
+pub(crate) fn process_qr_data(
+    state: &mut AppState,
+    data: &str,
+) -> Result<ScannedQrPreview, CommandError> {
     if !state.has_identity() {
         return Err(CommandError::Identity(
             "No identity found. Please create an identity first.".to_string(),
@@ -123,15 +186,19 @@ pub fn process_scanned_qr(
         .flatten()
         .unwrap_or_else(|| ContactCard::new(identity.display_name()));
 
-    let qr = ExchangeQR::from_data_string(&data)
+    let qr = ExchangeQR::from_data_string(data)
         .map_err(|e| CommandError::Exchange(format!("Invalid QR code: {:?}", e)))?;
 
+    // The QR was made on another device, so a wrong local clock shows up here
     if qr.is_expired() {
         return Err(CommandError::Exchange(
             "This QR code has expired. Please ask them to generate a new one.".to_string(),
-        ));
+        )
+        .or_clock_skew());
     }
 
+    let preview = ScannedQrPreview::new(&qr, clock::now_secs());
+
     let verifier = ManualConfirmationVerifier::new();
     let mut session = ExchangeSession::new_qr(identity, our_card, verifier);
 
@@ -146,7 +213,7 @@ pub fn process_scanned_qr(
 
     state.exchange_session = Some(session);
 
-    Ok(())
+    Ok(preview)
 }
 
 /// Confirm the peer has scanned our QR code.
@@ -155,8 +222,11 @@ pub fn process_scanned_qr(
 /// user confirming) that the other party has successfully scanned our QR.
 #[tauri::command]
 pub fn confirm_peer_scan(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
-    let mut state = state.lock().unwrap();
+    confirm_peer_scan_impl(&mut state.lock().unwrap())
+}
 
+/// [`confirm_peer_scan`] on locked state, shared with the test server.
+pub(crate) fn confirm_peer_scan_impl(state: &mut AppState) -> Result<(), CommandError> {
     let session = state
         .exchange_session
         .as_mut()
@@ -176,8 +246,11 @@ pub fn confirm_peer_scan(state: State<'_, Mutex<AppState>>) -> Result<(), Comman
 pub fn complete_exchange(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<ExchangeResult, CommandError> {
-    let mut state = state.lock().unwrap();
+    complete_exchange_impl(&mut state.lock().unwrap())
+}
 
+/// [`complete_exchange`] on locked state, shared with the test server.
+pub(crate) fn complete_exchange_impl(state: &mut AppState) -> Result<ExchangeResult, CommandError> {
     // Take the session out of state so we can use state.storage later
     let mut session = state
         .exchange_session
@@ -238,12 +311,20 @@ pub fn complete_exchange(
     };
 
     state
-        .storage
         .save_contact(&contact)
         .map_err(|e| CommandError::Contact(format!("Failed to save contact: {:?}", e)))?;
+    default_label::apply(&state.storage, state.data_dir(), &contact_id);
+    if let Ok(contacts) = state.storage.list_contacts() {
+        milestones::record_contact_count(state.data_dir(), contacts.len());
+    }
 
     let contact_name = contact.display_name().to_string();
 
+    events::publish(AppEvent::ContactAdded {
+        contact_id: contact_id.clone(),
+        display_name: contact_name.clone(),
+    });
+
     Ok(ExchangeResult {
         success: true,
         contact_name,
@@ -36,7 +36,11 @@ pub struct ConsentRecordInfo {
 /// Export all user data as GDPR-compliant JSON.
 #[tauri::command]
 pub fn export_gdpr_data(state: State<'_, Mutex<AppState>>) -> Result<String, CommandError> {
-    let state = state.lock().unwrap();
+    export_gdpr_data_impl(&state.lock().unwrap())
+}
+
+/// [`export_gdpr_data`] on locked state, shared with the test server.
+pub(crate) fn export_gdpr_data_impl(state: &AppState) -> Result<String, CommandError> {
     let export = vauchi_core::api::export_all_data(&state.storage)
         .map_err(|e| CommandError::Privacy(format!("Export failed: {}", e)))?;
 
@@ -49,7 +53,13 @@ pub fn export_gdpr_data(state: State<'_, Mutex<AppState>>) -> Result<String, Com
 pub fn schedule_account_deletion(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<DeletionInfo, CommandError> {
-    let state = state.lock().unwrap();
+    schedule_account_deletion_impl(&state.lock().unwrap())
+}
+
+/// [`schedule_account_deletion`] on locked state, shared with the test server.
+pub(crate) fn schedule_account_deletion_impl(
+    state: &AppState,
+) -> Result<DeletionInfo, CommandError> {
     let manager = vauchi_core::api::DeletionManager::new(&state.storage);
 
     manager
@@ -66,7 +76,11 @@ pub fn schedule_account_deletion(
 /// Cancel a scheduled account deletion.
 #[tauri::command]
 pub fn cancel_account_deletion(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    cancel_account_deletion_impl(&state.lock().unwrap())
+}
+
+/// [`cancel_account_deletion`] on locked state, shared with the test server.
+pub(crate) fn cancel_account_deletion_impl(state: &AppState) -> Result<(), CommandError> {
     let manager = vauchi_core::api::DeletionManager::new(&state.storage);
     manager
         .cancel_deletion()
@@ -76,7 +90,11 @@ pub fn cancel_account_deletion(state: State<'_, Mutex<AppState>>) -> Result<(),
 /// Get current deletion state.
 #[tauri::command]
 pub fn get_deletion_state(state: State<'_, Mutex<AppState>>) -> Result<DeletionInfo, CommandError> {
-    let state = state.lock().unwrap();
+    get_deletion_state_impl(&state.lock().unwrap())
+}
+
+/// [`get_deletion_state`] on locked state, shared with the test server.
+pub(crate) fn get_deletion_state_impl(state: &AppState) -> Result<DeletionInfo, CommandError> {
     let manager = vauchi_core::api::DeletionManager::new(&state.storage);
     let deletion_state = manager
         .deletion_state()
@@ -91,8 +109,12 @@ pub fn grant_consent(
     consent_type: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
-    let ct = parse_consent_type(&consent_type)?;
+    grant_consent_impl(&state.lock().unwrap(), &consent_type)
+}
+
+/// [`grant_consent`] on locked state, shared with the test server.
+pub(crate) fn grant_consent_impl(state: &AppState, consent_type: &str) -> Result<(), CommandError> {
+    let ct = parse_consent_type(consent_type)?;
     let manager = vauchi_core::api::ConsentManager::new(&state.storage);
     manager
         .grant(ct)
@@ -105,8 +127,15 @@ pub fn revoke_consent(
     consent_type: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
-    let ct = parse_consent_type(&consent_type)?;
+    revoke_consent_impl(&state.lock().unwrap(), &consent_type)
+}
+
+/// [`revoke_consent`] on locked state, shared with the test server.
+pub(crate) fn revoke_consent_impl(
+    state: &AppState,
+    consent_type: &str,
+) -> Result<(), CommandError> {
+    let ct = parse_consent_type(consent_type)?;
     let manager = vauchi_core::api::ConsentManager::new(&state.storage);
     manager
         .revoke(ct)
@@ -118,7 +147,13 @@ pub fn revoke_consent(
 pub fn get_consent_records(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Vec<ConsentRecordInfo>, CommandError> {
-    let state = state.lock().unwrap();
+    get_consent_records_impl(&state.lock().unwrap())
+}
+
+/// [`get_consent_records`] on locked state, shared with the test server.
+pub(crate) fn get_consent_records_impl(
+    state: &AppState,
+) -> Result<Vec<ConsentRecordInfo>, CommandError> {
     let manager = vauchi_core::api::ConsentManager::new(&state.storage);
     let records = manager
         .export_consent_log_with_version()
@@ -191,7 +226,7 @@ fn create_shred_relay_client(
     let mut client = RelayClient::new(transport, config, identity_id.to_string());
     client
         .connect()
-        .map_err(|e| CommandError::Network(format!("Failed to connect to relay: {}", e)))?;
+        .map_err(|e| CommandError::Relay(format!("Failed to connect to relay: {}", e)))?;
     Ok(client)
 }
 
@@ -216,10 +251,7 @@ pub fn execute_account_deletion(
             scheduled_at,
             execute_at,
         } => {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
+            let now = crate::clock::now_secs();
             if now < execute_at {
                 return Err(CommandError::Privacy(
                     "Grace period has not elapsed yet".to_string(),
@@ -318,10 +350,7 @@ fn deletion_state_to_info(state: &vauchi_core::storage::DeletionState) -> Deleti
             scheduled_at,
             execute_at,
         } => {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
+            let now = crate::clock::now_secs();
             let remaining = execute_at.saturating_sub(now);
             DeletionInfo {
                 state: "scheduled".to_string(),
@@ -4,15 +4,106 @@
 
 //! Help Commands
 //!
-//! Handles in-app help and FAQ for the desktop app.
+//! Handles in-app help and FAQ for the desktop app, and the what's-new
+//! release notes.
+//!
+//! Context help maps app screens to FAQ categories and keywords, so the
+//! FAQ set follows the core help content without pinning FAQ IDs.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
 
 use serde::Serialize;
+use tauri::{AppHandle, State};
 use vauchi_core::help::{
     get_faq_by_id, get_faq_by_id_localized, get_faqs, get_faqs_by_category,
     get_faqs_by_category_localized, get_faqs_localized, search_faqs, search_faqs_localized,
     HelpCategory,
 };
-use vauchi_core::i18n::Locale;
+use vauchi_core::i18n::{get_locale_info, Locale};
+
+use crate::commands::i18n::{parse_locale, resolve_locale};
+use crate::desktop_strings;
+use crate::error::CommandError;
+use crate::help_feedback::{self, UnansweredSearch};
+use crate::release_notes::{self, ReleaseEntry};
+use crate::state::AppState;
+
+/// Minimum share of query trigrams an FAQ must contain to match fuzzily.
+const FUZZY_THRESHOLD: f64 = 0.6;
+
+/// Weight of answer matches relative to question matches.
+const FUZZY_ANSWER_WEIGHT: f64 = 0.8;
+
+/// Queries with fewer trigrams than this skip fuzzy matching.
+const MIN_FUZZY_TRIGRAMS: usize = 3;
+
+/// Most fuzzy matches appended to a search.
+const MAX_FUZZY_RESULTS: usize = 10;
+
+/// Most FAQs shown in a context help panel.
+const MAX_CONTEXT_FAQS: usize = 5;
+
+/// Help curated for one app screen. Its title and walkthrough steps are
+/// the desktop strings `help.<id>.title` and `help.<id>.step1` onwards.
+struct ScreenHelp {
+    id: &'static str,
+    /// FAQs in these categories are candidates.
+    categories: &'static [HelpCategory],
+    /// A candidate is shown if its question or answer mentions one of these.
+    keywords: &'static [&'static str],
+    steps: usize,
+}
+
+/// Screens with a "?" help panel.
+const SCREEN_HELP: &[ScreenHelp] = &[
+    ScreenHelp {
+        id: "exchange",
+        categories: &[HelpCategory::GettingStarted, HelpCategory::Contacts],
+        keywords: &["exchange", "qr", "scan", "meet"],
+        steps: 4,
+    },
+    ScreenHelp {
+        id: "device-link",
+        categories: &[HelpCategory::GettingStarted, HelpCategory::Features],
+        keywords: &["device", "link", "sync"],
+        steps: 4,
+    },
+    ScreenHelp {
+        id: "sync",
+        categories: &[HelpCategory::Updates, HelpCategory::Features],
+        keywords: &["sync", "relay", "update", "offline"],
+        steps: 3,
+    },
+    ScreenHelp {
+        id: "visibility",
+        categories: &[HelpCategory::Privacy, HelpCategory::Contacts],
+        keywords: &["visib", "label", "hide", "share", "field"],
+        steps: 4,
+    },
+];
+
+/// Context help for a screen.
+#[derive(Serialize)]
+pub struct ContextHelp {
+    pub screen_id: String,
+    pub title: String,
+    pub faqs: Vec<FaqInfo>,
+    pub walkthrough: Vec<String>,
+}
+
+/// FAQs for a screen from the given FAQ list, in list order.
+fn context_faqs(screen: &ScreenHelp, faqs: &[vauchi_core::help::FaqItem]) -> Vec<FaqInfo> {
+    faqs.iter()
+        .filter(|faq| screen.categories.contains(&faq.category))
+        .filter(|faq| {
+            let text = format!("{} {}", faq.question, faq.answer).to_lowercase();
+            screen.keywords.iter().any(|k| text.contains(k))
+        })
+        .take(MAX_CONTEXT_FAQS)
+        .map(FaqInfo::from)
+        .collect()
+}
 
 /// FAQ item for the frontend.
 #[derive(Serialize)]
@@ -105,23 +196,94 @@ pub fn get_faq(faq_id: String) -> Option<FaqInfo> {
 
 /// Search FAQs by query.
 #[tauri::command]
-pub fn search_help(query: String) -> Vec<FaqInfo> {
-    search_faqs(&query).iter().map(FaqInfo::from).collect()
+pub fn search_help(query: String, state: State<'_, Mutex<AppState>>) -> Vec<FaqInfo> {
+    let exact: Vec<FaqInfo> = search_faqs(&query).iter().map(FaqInfo::from).collect();
+    let results = with_fuzzy_matches(&query, exact, &get_faqs());
+    if results.is_empty() {
+        note_unanswered(&query, &state);
+    }
+    results
 }
 
-fn string_to_locale(code: &str) -> Locale {
-    match code.to_lowercase().as_str() {
-        "de" => Locale::German,
-        "fr" => Locale::French,
-        "es" => Locale::Spanish,
-        _ => Locale::English,
+/// Trigrams of `text` lowercased with everything but letters and digits
+/// removed, so word splits and punctuation do not matter.
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let chars: Vec<char> = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+    chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// Share of the query trigrams found in `text` (0.0 to 1.0).
+fn trigram_containment(query: &HashSet<[char; 3]>, text: &str) -> f64 {
+    if query.is_empty() {
+        return 0.0;
+    }
+    let text = trigrams(text);
+    query.intersection(&text).count() as f64 / query.len() as f64
+}
+
+/// Fuzzy match score of an FAQ; question matches weigh more than answer matches.
+fn fuzzy_score(query: &HashSet<[char; 3]>, faq: &vauchi_core::help::FaqItem) -> f64 {
+    let question = trigram_containment(query, &faq.question);
+    let answer = trigram_containment(query, &faq.answer) * FUZZY_ANSWER_WEIGHT;
+    question.max(answer)
+}
+
+/// Append FAQs that fuzzily match `query` (typos, split or joined words)
+/// to the substring matches, best first.
+fn with_fuzzy_matches(
+    query: &str,
+    mut results: Vec<FaqInfo>,
+    faqs: &[vauchi_core::help::FaqItem],
+) -> Vec<FaqInfo> {
+    let query = trigrams(query);
+    if query.len() < MIN_FUZZY_TRIGRAMS {
+        return results;
+    }
+    let mut scored: Vec<(f64, &vauchi_core::help::FaqItem)> = faqs
+        .iter()
+        .filter(|faq| !results.iter().any(|r| r.id == faq.id))
+        .map(|faq| (fuzzy_score(&query, faq), faq))
+        .filter(|(score, _)| *score >= FUZZY_THRESHOLD)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    results.extend(
+        scored
+            .into_iter()
+            .take(MAX_FUZZY_RESULTS)
+            .map(|(_, faq)| FaqInfo::from(faq)),
+    );
+    results
+}
+
+/// Record a search without results for `get_top_unanswered_searches`.
+fn note_unanswered(query: &str, state: &Mutex<AppState>) {
+    let state = state.lock().unwrap();
+    if let Err(e) = help_feedback::record_unanswered(state.data_dir(), query) {
+        tracing::warn!("Failed to record unanswered help search: {}", e);
     }
 }
 
+/// Whether FAQs are translated for `locale` (not the English fallback).
+pub(crate) fn has_localized_content(locale: Locale) -> bool {
+    if locale == Locale::English {
+        return true;
+    }
+    let english = get_faqs_localized(Locale::English);
+    let localized = get_faqs_localized(locale);
+    english
+        .iter()
+        .zip(localized.iter())
+        .any(|(en, loc)| en.question != loc.question)
+}
+
 /// Get all FAQ items in the specified locale.
 #[tauri::command]
 pub fn get_all_faqs_localized(locale_code: String) -> Vec<FaqInfo> {
-    get_faqs_localized(string_to_locale(&locale_code))
+    get_faqs_localized(parse_locale(&locale_code))
         .iter()
         .map(FaqInfo::from)
         .collect()
@@ -131,7 +293,7 @@ pub fn get_all_faqs_localized(locale_code: String) -> Vec<FaqInfo> {
 #[tauri::command]
 pub fn get_category_faqs_localized(category: String, locale_code: String) -> Vec<FaqInfo> {
     if let Some(cat) = string_to_category(&category) {
-        get_faqs_by_category_localized(cat, string_to_locale(&locale_code))
+        get_faqs_by_category_localized(cat, parse_locale(&locale_code))
             .iter()
             .map(FaqInfo::from)
             .collect()
@@ -143,14 +305,218 @@ pub fn get_category_faqs_localized(category: String, locale_code: String) -> Vec
 /// Get a specific FAQ by ID in the specified locale.
 #[tauri::command]
 pub fn get_faq_localized(faq_id: String, locale_code: String) -> Option<FaqInfo> {
-    get_faq_by_id_localized(&faq_id, string_to_locale(&locale_code)).map(|f| FaqInfo::from(&f))
+    get_faq_by_id_localized(&faq_id, parse_locale(&locale_code)).map(|f| FaqInfo::from(&f))
 }
 
 /// Search FAQs by query in the specified locale.
 #[tauri::command]
-pub fn search_help_localized(query: String, locale_code: String) -> Vec<FaqInfo> {
-    search_faqs_localized(&query, string_to_locale(&locale_code))
+pub fn search_help_localized(
+    query: String,
+    locale_code: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Vec<FaqInfo> {
+    let locale = parse_locale(&locale_code);
+    let exact: Vec<FaqInfo> = search_faqs_localized(&query, locale)
         .iter()
         .map(FaqInfo::from)
-        .collect()
+        .collect();
+    let results = with_fuzzy_matches(&query, exact, &get_faqs_localized(locale));
+    if results.is_empty() {
+        note_unanswered(&query, &state);
+    }
+    results
+}
+
+/// Record whether an FAQ answered the user's question, with an optional comment.
+#[tauri::command]
+pub fn submit_help_feedback(
+    faq_id: String,
+    helpful: bool,
+    comment: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if get_faq_by_id(&faq_id).is_none() {
+        return Err(CommandError::Validation(format!("Unknown FAQ: {}", faq_id)));
+    }
+    let state = state.lock().unwrap();
+    help_feedback::record_feedback(state.data_dir(), &faq_id, helpful, comment.as_deref())
+        .map_err(|e| CommandError::Storage(format!("Failed to save help feedback: {}", e)))
+}
+
+/// Get the most frequent help searches that returned no results (default 10).
+#[tauri::command]
+pub fn get_top_unanswered_searches(
+    limit: Option<usize>,
+    state: State<'_, Mutex<AppState>>,
+) -> Vec<UnansweredSearch> {
+    let state = state.lock().unwrap();
+    help_feedback::top_unanswered(state.data_dir(), limit.unwrap_or(10))
+}
+
+/// Get the FAQs and walkthrough steps for an app screen
+/// (`exchange`, `device-link`, `sync` or `visibility`).
+///
+/// FAQs use `locale_code`, or the current app locale when omitted.
+#[tauri::command]
+pub fn get_help_for_context(
+    screen_id: String,
+    locale_code: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ContextHelp, CommandError> {
+    let screen = SCREEN_HELP
+        .iter()
+        .find(|s| s.id == screen_id)
+        .ok_or_else(|| CommandError::Validation(format!("No help for screen: {}", screen_id)))?;
+    let locale = resolve_locale(locale_code, &state);
+    let code = get_locale_info(locale).code;
+    let faqs = get_faqs_localized(locale);
+
+    Ok(ContextHelp {
+        title: desktop_strings::get(code, &format!("help.{}.title", screen.id)),
+        faqs: context_faqs(screen, &faqs),
+        walkthrough: (1..=screen.steps)
+            .map(|n| desktop_strings::get(code, &format!("help.{}.step{}", screen.id, n)))
+            .collect(),
+        screen_id,
+    })
+}
+
+/// Release notes since a version.
+#[derive(Serialize)]
+pub struct WhatsNew {
+    pub current_version: String,
+    pub last_seen_version: Option<String>,
+    /// Whether there are notes the user has not seen; show the dialog once.
+    pub should_show: bool,
+    /// Newest first.
+    pub releases: Vec<ReleaseEntry>,
+}
+
+/// Get the release notes after `since_version` (default: the last version
+/// whose notes were seen) up to the running version, in `locale_code`
+/// (default: the app locale).
+#[tauri::command]
+pub fn get_whats_new(
+    since_version: Option<String>,
+    locale_code: Option<String>,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> WhatsNew {
+    let state = state.lock().unwrap();
+    let current_version = app.package_info().version.to_string();
+    let last_seen_version =
+        release_notes::load_seen(state.data_dir()).and_then(|s| s.last_seen_version);
+    let locale = locale_code.unwrap_or_else(|| state.locale_code().to_string());
+
+    let since = since_version.as_deref().or(last_seen_version.as_deref());
+    let releases = release_notes::whats_new(
+        &release_notes::load(state.data_dir()),
+        since,
+        &current_version,
+        &locale,
+    );
+    let should_show = last_seen_version
+        .as_deref()
+        .is_some_and(|seen| release_notes::compare_versions(&current_version, seen).is_gt())
+        && !releases.is_empty();
+
+    WhatsNew {
+        current_version,
+        last_seen_version,
+        should_show,
+        releases,
+    }
+}
+
+/// Record that the running version's notes were seen.
+#[tauri::command]
+pub fn mark_whats_new_seen(
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    release_notes::mark_seen(state.data_dir(), &app.package_info().version.to_string())
+        .map_err(|e| CommandError::Config(format!("Failed to save what's new state: {}", e)))
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private screen help table
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn faq(id: &str, question: &str, answer: &str) -> vauchi_core::help::FaqItem {
+        let mut item = get_faqs()[0].clone();
+        item.id = id.to_string();
+        item.question = question.to_string();
+        item.answer = answer.to_string();
+        item
+    }
+
+    #[test]
+    fn test_fuzzy_search_tolerates_typos_and_split_words() {
+        let faqs = vec![
+            faq(
+                "verify",
+                "How does fingerprint verification work?",
+                "Compare the numbers.",
+            ),
+            faq(
+                "backup",
+                "How do I back up my identity?",
+                "Export a backup file.",
+            ),
+        ];
+        let results = with_fuzzy_matches("finger print verfication", vec![], &faqs);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "verify");
+    }
+
+    #[test]
+    fn test_fuzzy_search_keeps_exact_matches_first_without_duplicates() {
+        let faqs = vec![faq(
+            "backup",
+            "How do I back up my identity?",
+            "Export a backup.",
+        )];
+        let exact = vec![FaqInfo::from(&faqs[0])];
+        let results = with_fuzzy_matches("backup", exact, &faqs);
+        assert_eq!(results.len(), 1);
+
+        assert!(with_fuzzy_matches("ab", vec![], &faqs).is_empty());
+    }
+
+    #[test]
+    fn test_screen_help_ids_unique_with_walkthroughs() {
+        for (i, screen) in SCREEN_HELP.iter().enumerate() {
+            assert!(screen.steps > 0, "{} has no steps", screen.id);
+            assert!(!screen.keywords.is_empty(), "{} has no keywords", screen.id);
+            assert!(SCREEN_HELP[i + 1..].iter().all(|s| s.id != screen.id));
+        }
+    }
+
+    #[test]
+    fn test_screen_help_texts_are_bundled() {
+        for screen in SCREEN_HELP {
+            let title = format!("help.{}.title", screen.id);
+            assert_ne!(desktop_strings::get("en", &title), title);
+            for n in 1..=screen.steps + 1 {
+                let step = format!("help.{}.step{}", screen.id, n);
+                let bundled = desktop_strings::get("en", &step) != step;
+                assert_eq!(bundled, n <= screen.steps, "{}", step);
+            }
+        }
+    }
+
+    #[test]
+    fn test_context_faqs_match_category_and_keyword() {
+        let faqs = get_faqs();
+        for screen in SCREEN_HELP {
+            let selected = context_faqs(screen, &faqs);
+            assert!(selected.len() <= MAX_CONTEXT_FAQS);
+            for faq in &selected {
+                let category = string_to_category(&faq.category).unwrap();
+                assert!(screen.categories.contains(&category));
+            }
+        }
+    }
 }
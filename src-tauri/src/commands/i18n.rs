@@ -5,67 +5,564 @@
 //! Internationalization Commands
 //!
 //! Handles localization for the desktop app.
+//!
+//! The current locale is detected from the OS on first start and persisted
+//! once the user picks one. Commands taking an optional `locale_code` fall
+//! back to it, so the frontend does not need to pass a code on every call.
+//!
+//! Plurals use CLDR categories: `get_localized_plural` looks up
+//! `<key>.<category>` (e.g. `sync.updated.one`), falling back to
+//! `<key>.other`, and passes `count` as an argument. Numbers and dates are
+//! formatted with per-language separators and field order.
+//!
+//! `localize_error` turns a `CommandError` code into a translated message
+//! (the `error.<code>` desktop string), falling back to English.
 
 use serde::Serialize;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
 use vauchi_core::i18n::{
     get_all_strings, get_available_locales, get_locale_info, get_string, get_string_with_args,
     Locale,
 };
 
+use crate::desktop_strings;
+use crate::error::CommandError;
+use crate::state::AppState;
+
+/// Event emitted when the current locale changes.
+pub const LOCALE_CHANGED_EVENT: &str = "locale://changed";
+
 /// Locale information for the frontend.
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct LocaleInfo {
     pub code: String,
     pub name: String,
     pub english_name: String,
     pub is_rtl: bool,
+    /// Layout direction: `ltr` or `rtl`.
+    pub direction: String,
 }
 
+/// All strings of a locale together with its layout direction.
+#[derive(Serialize)]
+pub struct LocaleBundle {
+    pub code: String,
+    /// Layout direction: `ltr` or `rtl`.
+    pub direction: String,
+    pub strings: HashMap<String, String>,
+}
+
+/// Unicode LEFT-TO-RIGHT ISOLATE.
+const LRI: char = '\u{2066}';
+
+/// Unicode FIRST STRONG ISOLATE.
+const FSI: char = '\u{2068}';
+
+/// Unicode POP DIRECTIONAL ISOLATE.
+const PDI: char = '\u{2069}';
+
 /// Get all available locales.
 #[tauri::command]
 pub fn get_locales() -> Vec<LocaleInfo> {
     get_available_locales()
         .into_iter()
-        .map(|locale| {
-            let info = get_locale_info(locale);
-            LocaleInfo {
-                code: info.code.to_string(),
-                name: info.name.to_string(),
-                english_name: info.english_name.to_string(),
-                is_rtl: info.is_rtl,
-            }
-        })
+        .map(locale_info)
         .collect()
 }
 
+fn locale_info(locale: Locale) -> LocaleInfo {
+    let info = get_locale_info(locale);
+    LocaleInfo {
+        code: info.code.to_string(),
+        name: info.name.to_string(),
+        english_name: info.english_name.to_string(),
+        is_rtl: info.is_rtl,
+        direction: direction(info.is_rtl).to_string(),
+    }
+}
+
+fn direction(is_rtl: bool) -> &'static str {
+    if is_rtl {
+        "rtl"
+    } else {
+        "ltr"
+    }
+}
+
+/// Get the layout direction (`ltr` or `rtl`) for a locale, or the current one.
+#[tauri::command]
+pub fn get_layout_direction(
+    locale_code: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> String {
+    let locale = resolve_locale(locale_code, &state);
+    direction(get_locale_info(locale).is_rtl).to_string()
+}
+
+/// Get all strings of a locale with its layout direction.
+#[tauri::command]
+pub fn get_locale_bundle(
+    locale_code: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> LocaleBundle {
+    let locale = resolve_locale(locale_code, &state);
+    let info = get_locale_info(locale);
+    LocaleBundle {
+        code: info.code.to_string(),
+        direction: direction(info.is_rtl).to_string(),
+        strings: get_all_strings(locale),
+    }
+}
+
+/// Wrap text that must read left-to-right (hex fingerprints, codes) in an
+/// LTR isolate, so RTL surroundings do not reorder its groups.
+pub fn isolate_ltr(text: &str) -> String {
+    format!("{}{}{}", LRI, text, PDI)
+}
+
+/// Wrap user-provided text (names) in a first-strong isolate, so its own
+/// direction does not leak into adjacent labels.
+pub fn isolate(text: &str) -> String {
+    format!("{}{}{}", FSI, text, PDI)
+}
+
+/// Get the locales a feature has translated content for.
+///
+/// `feature` is `ui` (all bundled locales), `help` (FAQ content) or `aha`
+/// (milestone celebrations). Help and aha content fall back to English
+/// for untranslated locales, so those are left out.
+#[tauri::command]
+pub fn get_supported_locales_for(feature: String) -> Result<Vec<LocaleInfo>, CommandError> {
+    let supported: fn(Locale) -> bool = match feature.as_str() {
+        "ui" => |_| true,
+        "help" => crate::commands::help::has_localized_content,
+        "aha" => crate::commands::aha::has_localized_content,
+        other => {
+            return Err(CommandError::Validation(format!(
+                "Unknown feature: {}",
+                other
+            )))
+        }
+    };
+    Ok(get_available_locales()
+        .into_iter()
+        .filter(|locale| supported(*locale))
+        .map(locale_info)
+        .collect())
+}
+
+/// Get the current locale (persisted choice, or detected from the OS).
+#[tauri::command]
+pub fn get_current_locale(state: State<'_, Mutex<AppState>>) -> LocaleInfo {
+    let state = state.lock().unwrap();
+    locale_info(parse_locale(state.locale_code()))
+}
+
+/// Set and persist the current locale, emitting `locale://changed`.
+#[tauri::command]
+pub fn set_current_locale(
+    locale_code: String,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<LocaleInfo, CommandError> {
+    let locale = Locale::from_code(&locale_code)
+        .ok_or_else(|| CommandError::Validation(format!("Unsupported locale: {}", locale_code)))?;
+    let info = locale_info(locale);
+
+    state
+        .lock()
+        .unwrap()
+        .set_locale_code(&info.code)
+        .map_err(|e| CommandError::Config(e.to_string()))?;
+
+    if let Err(e) = app.emit(LOCALE_CHANGED_EVENT, info.clone()) {
+        tracing::warn!("Failed to emit locale change: {}", e);
+    }
+    Ok(info)
+}
+
 /// Get a localized string.
 #[tauri::command]
-pub fn get_localized_string(locale_code: String, key: String) -> String {
-    let locale = parse_locale(&locale_code);
+pub fn get_localized_string(
+    locale_code: Option<String>,
+    key: String,
+    state: State<'_, Mutex<AppState>>,
+) -> String {
+    let locale = resolve_locale(locale_code, &state);
     get_string(locale, &key)
 }
 
 /// Get a localized string with arguments.
 #[tauri::command]
 pub fn get_localized_string_with_args(
-    locale_code: String,
+    locale_code: Option<String>,
     key: String,
     args: HashMap<String, String>,
+    state: State<'_, Mutex<AppState>>,
 ) -> String {
-    let locale = parse_locale(&locale_code);
+    let locale = resolve_locale(locale_code, &state);
     let args_vec: Vec<(&str, &str)> = args.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
     get_string_with_args(locale, &key, &args_vec)
 }
 
 /// Get all localized strings for a locale.
 #[tauri::command]
-pub fn get_locale_strings(locale_code: String) -> HashMap<String, String> {
-    let locale = parse_locale(&locale_code);
+pub fn get_locale_strings(
+    locale_code: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> HashMap<String, String> {
+    let locale = resolve_locale(locale_code, &state);
     get_all_strings(locale)
 }
 
-/// Parse a locale code to a Locale enum.
-fn parse_locale(code: &str) -> Locale {
-    Locale::from_code(code).unwrap_or(Locale::English)
+/// Get the user-facing message for a `CommandError` code.
+///
+/// Uses the `error.<code>` desktop string of the locale, or the current
+/// one, falling back to English.
+#[tauri::command]
+pub fn localize_error(
+    code: String,
+    locale_code: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    let locale = resolve_locale(locale_code, &state);
+    crate::error::localized_message(get_locale_info(locale).code, &code)
+        .ok_or_else(|| CommandError::Validation(format!("Unknown error code: {}", code)))
+}
+
+/// Parse a locale code to a Locale enum, falling back to English.
+pub(crate) fn parse_locale(code: &str) -> Locale {
+    Locale::from_code(code)
+        .or_else(|| Locale::from_code(&code.to_lowercase()))
+        .unwrap_or(Locale::English)
+}
+
+/// Reload bundled locales with the overrides from
+/// `<data_dir>/locales-override/`, then emit `locale://changed` so the UI
+/// refetches its strings. Returns the override files applied.
+#[tauri::command]
+pub fn reload_locales(
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<String>, CommandError> {
+    let state = state.lock().unwrap();
+    let resource_dir = app
+        .path()
+        .resource_dir()
+        .map(|d| d.join("locales"))
+        .unwrap_or_else(|_| state.data_dir().join("locales"));
+
+    let applied = crate::locale_overrides::init(&resource_dir, state.data_dir())
+        .map_err(|e| CommandError::Config(e.to_string()))?;
+
+    let info = locale_info(parse_locale(state.locale_code()));
+    if let Err(e) = app.emit(LOCALE_CHANGED_EVENT, info) {
+        tracing::warn!("Failed to emit locale change: {}", e);
+    }
+    Ok(applied)
+}
+
+/// CLDR plural category for a cardinal count.
+///
+/// Covers the rule families of the languages the app ships; unknown
+/// languages use the English rule.
+pub fn plural_category(language: &str, count: u64) -> &'static str {
+    let (n10, n100) = (count % 10, count % 100);
+    match language {
+        // No plural distinction
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "tr" | "fa" => "other",
+        // 0 and 1 are singular
+        "fr" | "hi" | "bn" => {
+            if count <= 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "ru" | "uk" | "be" => {
+            if n10 == 1 && n100 != 11 {
+                "one"
+            } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        // Same as Russian, but without a separate "many"
+        "sr" | "hr" | "bs" => {
+            if n10 == 1 && n100 != 11 {
+                "one"
+            } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                "few"
+            } else {
+                "other"
+            }
+        }
+        "pl" => {
+            if count == 1 {
+                "one"
+            } else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "cs" | "sk" => match count {
+            1 => "one",
+            2..=4 => "few",
+            _ => "other",
+        },
+        "ar" => match count {
+            0 => "zero",
+            1 => "one",
+            2 => "two",
+            _ if (3..=10).contains(&n100) => "few",
+            _ if (11..=99).contains(&n100) => "many",
+            _ => "other",
+        },
+        "he" => match count {
+            1 => "one",
+            2 => "two",
+            _ => "other",
+        },
+        _ => {
+            if count == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    }
+}
+
+/// Language part of a locale code (`pt-BR` -> `pt`).
+fn language_of(locale: Locale) -> String {
+    let code = get_locale_info(locale).code;
+    code.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Get a pluralized string for `count`.
+///
+/// Looks up `<key>.<category>`, then `<key>.other`, then `key`, with
+/// `count` available as an argument. Core's locale files come first, then
+/// the strings bundled with the desktop app.
+#[tauri::command]
+pub fn get_localized_plural(
+    locale_code: Option<String>,
+    key: String,
+    count: u64,
+    state: State<'_, Mutex<AppState>>,
+) -> String {
+    let locale = resolve_locale(locale_code, &state);
+    localized_plural(locale, &key, count)
+}
+
+pub(crate) fn localized_plural(locale: Locale, key: &str, count: u64) -> String {
+    let count_str = count.to_string();
+    let args = [("count", count_str.as_str())];
+    let category = plural_category(&language_of(locale), count);
+    let code = get_locale_info(locale).code;
+
+    for candidate in [
+        format!("{}.{}", key, category),
+        format!("{}.other", key),
+        key.to_string(),
+    ] {
+        // Missing keys come back unchanged
+        let value = get_string_with_args(locale, &candidate, &args);
+        if value != candidate {
+            return value;
+        }
+        let value = desktop_strings::get(code, &candidate);
+        if value != candidate {
+            return value.replace("{count}", &count_str);
+        }
+    }
+    key.to_string()
+}
+
+/// Decimal and grouping separators for a language.
+fn number_separators(language: &str) -> (char, char) {
+    match language {
+        "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" => (',', '.'),
+        "fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "nb" | "fi" | "hu" => (',', '\u{202f}'),
+        _ => ('.', ','),
+    }
+}
+
+/// Format a number with locale separators and a fixed number of decimals.
+pub fn format_number_for(language: &str, value: f64, decimals: usize) -> String {
+    let (decimal_sep, group_sep) = number_separators(language);
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((i, f)) => (i.to_string(), Some(f.to_string())),
+        None => (formatted, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(group_sep);
+        }
+        grouped.push(c);
+    }
+
+    let mut out = String::new();
+    if value < 0.0 && formatted_is_nonzero(&int_part, frac_part.as_deref()) {
+        out.push('-');
+    }
+    out.push_str(&grouped);
+    if let Some(frac) = frac_part {
+        out.push(decimal_sep);
+        out.push_str(&frac);
+    }
+    out
+}
+
+fn formatted_is_nonzero(int_part: &str, frac_part: Option<&str>) -> bool {
+    int_part
+        .chars()
+        .chain(frac_part.unwrap_or_default().chars())
+        .any(|c| c != '0')
+}
+
+/// Format a Unix timestamp as a local date in the language's field order.
+pub fn format_date_for(language: &str, timestamp: u64) -> String {
+    use chrono::{Local, TimeZone};
+
+    match Local.timestamp_opt(timestamp as i64, 0).single() {
+        Some(date) => format_calendar_date(language, date.date_naive()),
+        None => String::new(),
+    }
+}
+
+/// Format a calendar date in the language's field order.
+fn format_calendar_date(language: &str, date: chrono::NaiveDate) -> String {
+    use chrono::Datelike;
+
+    let (d, m, y) = (date.day(), date.month(), date.year());
+    match language {
+        "en" => format!("{}/{}/{}", m, d, y),
+        "de" | "ru" | "uk" | "pl" | "cs" | "sk" | "tr" | "fi" | "nb" | "da" => {
+            format!("{:02}.{:02}.{}", d, m, y)
+        }
+        "nl" => format!("{}-{}-{}", d, m, y),
+        "ja" | "zh" | "ko" | "sv" | "hu" | "lt" => format!("{}-{:02}-{:02}", y, m, d),
+        _ => format!("{:02}/{:02}/{}", d, m, y),
+    }
+}
+
+/// Format a number for the locale.
+#[tauri::command]
+pub fn format_number(
+    locale_code: Option<String>,
+    value: f64,
+    decimals: Option<usize>,
+    state: State<'_, Mutex<AppState>>,
+) -> String {
+    let locale = resolve_locale(locale_code, &state);
+    format_number_for(&language_of(locale), value, decimals.unwrap_or(0))
+}
+
+/// Format a Unix timestamp as a local date for the locale.
+#[tauri::command]
+pub fn format_date(
+    locale_code: Option<String>,
+    timestamp: u64,
+    state: State<'_, Mutex<AppState>>,
+) -> String {
+    let locale = resolve_locale(locale_code, &state);
+    format_date_for(&language_of(locale), timestamp)
+}
+
+/// Use the given locale code, or the current locale if none was passed.
+pub(crate) fn resolve_locale(locale_code: Option<String>, state: &Mutex<AppState>) -> Locale {
+    match locale_code {
+        Some(code) => parse_locale(&code),
+        None => parse_locale(state.lock().unwrap().locale_code()),
+    }
+}
+
+/// Map an OS locale string (`de-CH`, `fr_FR.UTF-8`, `pt`) to a supported
+/// locale code, trying the full tag before the language alone.
+pub fn detect_locale_code(os_locale: Option<&str>) -> String {
+    let Some(raw) = os_locale else {
+        return "en".to_string();
+    };
+    let tag = raw.split('.').next().unwrap_or_default().replace('_', "-");
+    let language = tag.split('-').next().unwrap_or_default();
+    Locale::from_code(&tag)
+        .or_else(|| Locale::from_code(&tag.to_lowercase()))
+        .or_else(|| Locale::from_code(&language.to_lowercase()))
+        .map(|locale| get_locale_info(locale).code.to_string())
+        .unwrap_or_else(|| "en".to_string())
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private OS locale mapping
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_locale_code_uses_language_part() {
+        assert_eq!(detect_locale_code(Some("de-CH")), "de");
+        assert_eq!(detect_locale_code(Some("fr_FR.UTF-8")), "fr");
+    }
+
+    #[test]
+    fn test_detect_locale_code_falls_back_to_english() {
+        assert_eq!(detect_locale_code(None), "en");
+        assert_eq!(detect_locale_code(Some("xx-YY")), "en");
+        assert_eq!(detect_locale_code(Some("C")), "en");
+    }
+
+    #[test]
+    fn test_bidi_isolates_wrap_text() {
+        assert_eq!(isolate_ltr("AB12 CD34"), "\u{2066}AB12 CD34\u{2069}");
+        assert_eq!(isolate("مريم"), "\u{2068}مريم\u{2069}");
+        assert_eq!(direction(true), "rtl");
+        assert_eq!(direction(false), "ltr");
+    }
+
+    #[test]
+    fn test_plural_category_rules() {
+        assert_eq!(plural_category("en", 1), "one");
+        assert_eq!(plural_category("en", 0), "other");
+        assert_eq!(plural_category("de", 3), "other");
+        assert_eq!(plural_category("fr", 0), "one");
+        assert_eq!(plural_category("fr", 2), "other");
+        assert_eq!(plural_category("ru", 21), "one");
+        assert_eq!(plural_category("ru", 22), "few");
+        assert_eq!(plural_category("ru", 12), "many");
+        assert_eq!(plural_category("pl", 5), "many");
+        assert_eq!(plural_category("hr", 21), "one");
+        assert_eq!(plural_category("sr", 3), "few");
+        assert_eq!(plural_category("bs", 5), "other");
+        assert_eq!(plural_category("ar", 2), "two");
+        assert_eq!(plural_category("ja", 1), "other");
+    }
+
+    #[test]
+    fn test_format_number_separators() {
+        assert_eq!(format_number_for("en", 1234567.891, 2), "1,234,567.89");
+        assert_eq!(format_number_for("de", 1234567.891, 2), "1.234.567,89");
+        assert_eq!(format_number_for("fr", 1234.5, 1), "1\u{202f}234,5");
+        assert_eq!(format_number_for("en", -42.0, 0), "-42");
+        assert_eq!(format_number_for("en", -0.001, 0), "0");
+        assert_eq!(format_number_for("en", 999.0, 0), "999");
+    }
+
+    #[test]
+    fn test_format_date_field_order() {
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        assert_eq!(format_calendar_date("en", date), "3/4/2026");
+        assert_eq!(format_calendar_date("de", date), "04.03.2026");
+        assert_eq!(format_calendar_date("fr", date), "04/03/2026");
+        assert_eq!(format_calendar_date("ja", date), "2026-03-04");
+    }
 }
@@ -9,6 +9,7 @@ use std::sync::Mutex;
 use serde::Serialize;
 use tauri::State;
 
+use crate::device_mode;
 use crate::error::CommandError;
 use crate::state::AppState;
 
@@ -66,6 +67,7 @@ pub fn update_display_name(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<IdentityInfo, CommandError> {
     let mut state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     state
         .update_display_name(&name)
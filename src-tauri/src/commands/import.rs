@@ -0,0 +1,198 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Import Commands
+//!
+//! Commands for importing contacts from files chosen in the UI, or from
+//! another Vauchi data directory (see `profile_import`).
+//! Dropped and OS-opened files are handled in `file_import`.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tauri::State;
+use vauchi_core::{Contact, ContactCard, ContactField, FieldType};
+
+use crate::deleted_contacts;
+use crate::device_mode;
+use crate::error::CommandError;
+use crate::file_import::{parse_vcards, ImportedContact};
+use crate::profile_import::{self, ProfileImportReport};
+use crate::secret::SecretString;
+use crate::state::AppState;
+use crate::storage_worker;
+
+/// Parse vCard text into contact previews.
+#[tauri::command]
+pub fn parse_vcard(data: String) -> Result<Vec<ImportedContact>, CommandError> {
+    let contacts = parse_vcards(&data);
+    if contacts.is_empty() {
+        return Err(CommandError::Validation(
+            "No contacts found in vCard".to_string(),
+        ));
+    }
+    Ok(contacts)
+}
+
+/// Add parsed vCard contacts to the contact list, once the user has
+/// confirmed the preview.
+///
+/// Returns how many were added. Contacts whose card is already in the list
+/// are skipped, so importing the same file twice adds nothing.
+#[tauri::command]
+pub fn import_vcard_contacts(
+    contacts: Vec<ImportedContact>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<usize, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    if state.identity.is_none() {
+        return Err(CommandError::Identity("No identity found".to_string()));
+    }
+
+    let (new, _) = split_known(&state, contacts)?;
+    for card in &new {
+        state.save_contact(&Contact::from_import(to_card(card)))?;
+    }
+    Ok(new.len())
+}
+
+/// Split imported contacts into those not in the contact list yet and the
+/// number already there (or repeated in the import).
+pub(crate) fn split_known(
+    state: &AppState,
+    contacts: Vec<ImportedContact>,
+) -> Result<(Vec<ImportedContact>, usize), CommandError> {
+    let mut known: HashSet<CardKey> = state
+        .cached_contacts()?
+        .iter()
+        .map(|c| card_key(c.card()))
+        .collect();
+    let total = contacts.len();
+    let new: Vec<ImportedContact> = contacts
+        .into_iter()
+        .filter(|c| known.insert(card_key(&to_card(c))))
+        .collect();
+    let known = total - new.len();
+    Ok((new, known))
+}
+
+/// What makes two cards the same contact: the name and the field values,
+/// ignoring case, order and labels.
+type CardKey = (String, Vec<(String, String)>);
+
+fn card_key(card: &ContactCard) -> CardKey {
+    let mut fields: Vec<(String, String)> = card
+        .fields()
+        .iter()
+        .map(|f| {
+            (
+                format!("{:?}", f.field_type()),
+                f.value().trim().to_lowercase(),
+            )
+        })
+        .collect();
+    fields.sort();
+    (card.display_name().trim().to_lowercase(), fields)
+}
+
+/// Build a card from an imported contact. Fields the card rejects are left
+/// out.
+fn to_card(contact: &ImportedContact) -> ContactCard {
+    let mut card = ContactCard::new(&contact.display_name);
+    for field in &contact.fields {
+        let field_type = match field.field_type.as_str() {
+            "email" => FieldType::Email,
+            "phone" => FieldType::Phone,
+            "website" => FieldType::Website,
+            "address" => FieldType::Address,
+            "birthday" => FieldType::Birthday,
+            _ => FieldType::Custom,
+        };
+        let field = ContactField::new(field_type, &field.label, &field.value);
+        if let Err(e) = card.add_field(field) {
+            tracing::debug!("Skipping imported field: {}", e);
+        }
+    }
+    card
+}
+
+/// Merge contacts, labels and validations from another Vauchi data
+/// directory of the same identity into this profile.
+///
+/// `password` is the other profile's backup password, needed only when its
+/// `.backup-password` file was not copied along.
+#[tauri::command]
+pub fn import_from_data_dir(
+    path: String,
+    password: Option<SecretString>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ProfileImportReport, CommandError> {
+    let source_dir = PathBuf::from(path);
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+
+    let same_dir = match (source_dir.canonicalize(), state.data_dir().canonicalize()) {
+        (Ok(source), Ok(current)) => source == current,
+        _ => false,
+    };
+    if same_dir {
+        return Err(CommandError::Validation(
+            "This is the current profile's data directory".to_string(),
+        ));
+    }
+
+    let (source, source_identity) =
+        profile_import::open_source(&source_dir, password.as_ref().map(|p| p.expose()))
+            .map_err(|e| CommandError::Storage(e.to_string()))?;
+    if source_identity.public_id() != identity.public_id() {
+        return Err(CommandError::Validation(
+            "The profile belongs to a different identity".to_string(),
+        ));
+    }
+
+    let my_id = hex::encode(identity.signing_public_key());
+    let deleted = deleted_contacts::load(state.data_dir());
+    let report = storage_worker::in_transaction(&state.storage, |storage| {
+        profile_import::merge(&source, storage, &my_id, &deleted)
+    })?;
+    state.invalidate_contact_cache();
+    Ok(report)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private vCard to card mapping
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imported_contact_becomes_card() {
+        let contacts = parse_vcards(
+            "BEGIN:VCARD\nFN:Alice\nTEL;TYPE=work:+41 44 123 45 67\nBDAY:1990-01-01\nEND:VCARD",
+        );
+        let card = to_card(&contacts[0]);
+        assert_eq!(card.display_name(), "Alice");
+        assert_eq!(card.fields().len(), 2);
+        assert_eq!(card.fields()[0].field_type(), FieldType::Phone);
+        assert_eq!(card.fields()[0].value(), "+41 44 123 45 67");
+        assert_eq!(card.fields()[1].field_type(), FieldType::Birthday);
+    }
+
+    #[test]
+    fn test_card_key_ignores_case_order_and_labels() {
+        let contacts = parse_vcards(
+            "BEGIN:VCARD\nFN:Alice\nEMAIL:alice@example.com\nTEL:+41 44 123 45 67\nEND:VCARD\n\
+             BEGIN:VCARD\nFN:alice\nTEL;TYPE=work:+41 44 123 45 67\nEMAIL:Alice@Example.com\nEND:VCARD\n\
+             BEGIN:VCARD\nFN:Alice\nEMAIL:alice@work.example\nEND:VCARD",
+        );
+        let keys: Vec<CardKey> = contacts.iter().map(|c| card_key(&to_card(c))).collect();
+        assert_eq!(keys[0], keys[1]);
+        assert_ne!(keys[0], keys[2]);
+    }
+}
@@ -5,14 +5,28 @@
 //! Visibility Labels Commands
 //!
 //! Commands for managing visibility labels.
+//!
+//! A contact's effective label fields are the union of the visible fields
+//! of every label they are in and of those labels' ancestors (see
+//! `label_tree`). Per-contact field overrides take precedence over the
+//! labels. Label rules only take effect on a contact once applied to its
+//! per-contact visibility rules.
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Mutex;
 
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
+use vauchi_core::Storage;
+
+use crate::default_label::{self, DefaultLabelPolicy, DefaultLabelSettings};
+use crate::device_mode;
 use crate::error::CommandError;
+use crate::label_tree::{self, LabelTree};
+use crate::milestones::{self, Milestone};
 use crate::state::AppState;
+use crate::storage_worker;
 
 /// Visibility label info for the frontend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,11 +50,90 @@ pub struct LabelDetail {
     pub modified_at: u64,
 }
 
+/// What one label member sees under its labels and under its current rules.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberVisibility {
+    pub contact_id: String,
+    pub display_name: String,
+    /// Fields visible through the contact's labels and overrides.
+    pub label_field_ids: Vec<String>,
+    /// Fields the contact's current visibility rules let it see.
+    pub current_field_ids: Vec<String>,
+    /// Whether the current rules already match the labels.
+    pub in_sync: bool,
+}
+
+/// Union of the visible fields of all labels containing `contact_id`,
+/// including fields inherited from parent labels.
+fn label_fields_for(
+    storage: &Storage,
+    tree: &LabelTree,
+    contact_id: &str,
+) -> Result<HashSet<String>, CommandError> {
+    let labels = storage
+        .get_labels_for_contact(contact_id)
+        .map_err(|e| CommandError::Storage(format!("Failed to get labels for contact: {:?}", e)))?;
+    let mut fields: HashSet<String> = labels
+        .iter()
+        .flat_map(|l| l.visible_fields().iter().cloned())
+        .collect();
+    let ancestors: HashSet<String> = labels.iter().flat_map(|l| tree.ancestors(l.id())).collect();
+    for ancestor_id in ancestors {
+        // A parent deleted outside this app no longer contributes fields
+        if let Ok(ancestor) = storage.load_label(&ancestor_id) {
+            fields.extend(ancestor.visible_fields().iter().cloned());
+        }
+    }
+    Ok(fields)
+}
+
+/// A contact's per-contact field overrides, as field ID → visible.
+pub(crate) fn contact_overrides(
+    storage: &Storage,
+    contact_id: &str,
+) -> Result<HashMap<String, bool>, CommandError> {
+    let overrides = storage
+        .load_contact_overrides(contact_id)
+        .map_err(|e| CommandError::Storage(format!("Failed to load contact overrides: {:?}", e)))?;
+    Ok(overrides.into_iter().collect())
+}
+
+/// Fields `contact_id` should see: its label fields, with its per-contact
+/// overrides taking precedence.
+pub(crate) fn effective_fields_for(
+    storage: &Storage,
+    tree: &LabelTree,
+    contact_id: &str,
+) -> Result<HashSet<String>, CommandError> {
+    let mut fields = label_fields_for(storage, tree, contact_id)?;
+    for (field_id, visible) in contact_overrides(storage, contact_id)? {
+        if visible {
+            fields.insert(field_id);
+        } else {
+            fields.remove(&field_id);
+        }
+    }
+    Ok(fields)
+}
+
+/// Own card field IDs, in card order.
+fn own_field_ids(storage: &Storage) -> Result<Vec<String>, CommandError> {
+    let card = storage
+        .load_own_card()
+        .map_err(|e| CommandError::Card(format!("Failed to load card: {:?}", e)))?;
+    Ok(card
+        .map(|c| c.fields().iter().map(|f| f.id().to_string()).collect())
+        .unwrap_or_default())
+}
+
 /// List all visibility labels.
 #[tauri::command]
 pub fn list_labels(state: State<'_, Mutex<AppState>>) -> Result<Vec<LabelInfo>, CommandError> {
-    let state = state.lock().unwrap();
+    list_labels_impl(&state.lock().unwrap())
+}
 
+/// [`list_labels`] on locked state, shared with the test server.
+pub(crate) fn list_labels_impl(state: &AppState) -> Result<Vec<LabelInfo>, CommandError> {
     let labels = state
         .storage
         .load_all_labels()
@@ -65,12 +158,18 @@ pub fn create_label(
     name: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<LabelInfo, CommandError> {
-    let state = state.lock().unwrap();
+    create_label_impl(&state.lock().unwrap(), &name)
+}
+
+/// [`create_label`] on locked state, shared with the test server.
+pub(crate) fn create_label_impl(state: &AppState, name: &str) -> Result<LabelInfo, CommandError> {
+    device_mode::ensure_editable(state)?;
 
     let label = state
         .storage
-        .create_label(&name)
+        .create_label(name)
         .map_err(|e| CommandError::Storage(format!("Failed to create label: {:?}", e)))?;
+    milestones::record(state.data_dir(), Milestone::FirstLabelCreated);
 
     Ok(LabelInfo {
         id: label.id().to_string(),
@@ -88,11 +187,17 @@ pub fn get_label(
     label_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<LabelDetail, CommandError> {
-    let state = state.lock().unwrap();
+    get_label_impl(&state.lock().unwrap(), &label_id)
+}
 
+/// [`get_label`] on locked state, shared with the test server.
+pub(crate) fn get_label_impl(
+    state: &AppState,
+    label_id: &str,
+) -> Result<LabelDetail, CommandError> {
     let label = state
         .storage
-        .load_label(&label_id)
+        .load_label(label_id)
         .map_err(|e| CommandError::Storage(format!("Failed to load label: {:?}", e)))?;
 
     Ok(LabelDetail {
@@ -112,11 +217,20 @@ pub fn rename_label(
     new_name: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    rename_label_impl(&state.lock().unwrap(), &label_id, &new_name)
+}
+
+/// [`rename_label`] on locked state, shared with the test server.
+pub(crate) fn rename_label_impl(
+    state: &AppState,
+    label_id: &str,
+    new_name: &str,
+) -> Result<(), CommandError> {
+    device_mode::ensure_editable(state)?;
 
     state
         .storage
-        .rename_label(&label_id, &new_name)
+        .rename_label(label_id, new_name)
         .map_err(|e| CommandError::Storage(format!("Failed to rename label: {:?}", e)))
 }
 
@@ -126,12 +240,30 @@ pub fn delete_label(
     label_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    delete_label_impl(&state.lock().unwrap(), &label_id)
+}
+
+/// [`delete_label`] on locked state, shared with the test server.
+pub(crate) fn delete_label_impl(state: &AppState, label_id: &str) -> Result<(), CommandError> {
+    device_mode::ensure_editable(state)?;
 
     state
         .storage
-        .delete_label(&label_id)
-        .map_err(|e| CommandError::Storage(format!("Failed to delete label: {:?}", e)))
+        .delete_label(label_id)
+        .map_err(|e| CommandError::Storage(format!("Failed to delete label: {:?}", e)))?;
+
+    let mut defaults = default_label::load(state.data_dir());
+    if defaults.label_id.as_deref() == Some(label_id) {
+        defaults = DefaultLabelSettings::default();
+        default_label::save(state.data_dir(), &defaults).map_err(|e| {
+            CommandError::Config(format!("Failed to save default label settings: {}", e))
+        })?;
+    }
+
+    let mut tree = label_tree::load(state.data_dir());
+    tree.remove_label(label_id);
+    label_tree::save(state.data_dir(), &tree)
+        .map_err(|e| CommandError::Config(format!("Failed to save label tree: {}", e)))
 }
 
 /// Add a contact to a label.
@@ -141,11 +273,20 @@ pub fn add_contact_to_label(
     contact_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    add_contact_to_label_impl(&state.lock().unwrap(), &label_id, &contact_id)
+}
+
+/// [`add_contact_to_label`] on locked state, shared with the test server.
+pub(crate) fn add_contact_to_label_impl(
+    state: &AppState,
+    label_id: &str,
+    contact_id: &str,
+) -> Result<(), CommandError> {
+    device_mode::ensure_editable(state)?;
 
     state
         .storage
-        .add_contact_to_label(&label_id, &contact_id)
+        .add_contact_to_label(label_id, contact_id)
         .map_err(|e| CommandError::Storage(format!("Failed to add contact to label: {:?}", e)))
 }
 
@@ -156,11 +297,20 @@ pub fn remove_contact_from_label(
     contact_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    remove_contact_from_label_impl(&state.lock().unwrap(), &label_id, &contact_id)
+}
+
+/// [`remove_contact_from_label`] on locked state, shared with the test server.
+pub(crate) fn remove_contact_from_label_impl(
+    state: &AppState,
+    label_id: &str,
+    contact_id: &str,
+) -> Result<(), CommandError> {
+    device_mode::ensure_editable(state)?;
 
     state
         .storage
-        .remove_contact_from_label(&label_id, &contact_id)
+        .remove_contact_from_label(label_id, contact_id)
         .map_err(|e| CommandError::Storage(format!("Failed to remove contact from label: {:?}", e)))
 }
 
@@ -198,11 +348,21 @@ pub fn set_label_field_visibility(
     is_visible: bool,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    set_label_field_visibility_impl(&state.lock().unwrap(), &label_id, &field_id, is_visible)
+}
+
+/// [`set_label_field_visibility`] on locked state, shared with the test server.
+pub(crate) fn set_label_field_visibility_impl(
+    state: &AppState,
+    label_id: &str,
+    field_id: &str,
+    is_visible: bool,
+) -> Result<(), CommandError> {
+    device_mode::ensure_editable(state)?;
 
     state
         .storage
-        .set_label_field_visibility(&label_id, &field_id, is_visible)
+        .set_label_field_visibility(label_id, field_id, is_visible)
         .map_err(|e| CommandError::Storage(format!("Failed to set field visibility: {:?}", e)))
 }
 
@@ -215,6 +375,7 @@ pub fn set_contact_field_override(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     state
         .storage
@@ -230,6 +391,7 @@ pub fn remove_contact_field_override(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
     let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
 
     state
         .storage
@@ -237,6 +399,199 @@ pub fn remove_contact_field_override(
         .map_err(|e| CommandError::Storage(format!("Failed to remove contact override: {:?}", e)))
 }
 
+/// Preview, for every member of a label, the fields it sees through its
+/// labels and overrides next to the fields its current visibility rules
+/// allow.
+#[tauri::command]
+pub fn preview_label_visibility(
+    label_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<MemberVisibility>, CommandError> {
+    let state = state.lock().unwrap();
+
+    let label = state
+        .storage
+        .load_label(&label_id)
+        .map_err(|e| CommandError::Storage(format!("Failed to load label: {:?}", e)))?;
+    let field_ids = own_field_ids(&state.storage)?;
+    let tree = label_tree::load(state.data_dir());
+
+    let mut members = Vec::new();
+    for contact_id in label.contacts() {
+        let Some(contact) = state.cached_contact(contact_id)? else {
+            continue;
+        };
+        let allowed = effective_fields_for(&state.storage, &tree, contact_id)?;
+        let rules = contact.visibility_rules();
+        let label_field_ids: Vec<String> = field_ids
+            .iter()
+            .filter(|id| allowed.contains(*id))
+            .cloned()
+            .collect();
+        let current_field_ids: Vec<String> = field_ids
+            .iter()
+            .filter(|id| rules.can_see(id, contact_id))
+            .cloned()
+            .collect();
+        members.push(MemberVisibility {
+            contact_id: contact_id.to_string(),
+            display_name: contact.display_name().to_string(),
+            in_sync: label_field_ids == current_field_ids,
+            label_field_ids,
+            current_field_ids,
+        });
+    }
+    members.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    Ok(members)
+}
+
+/// Rewrite the visibility rules of every member of a label to match its
+/// labels, keeping its per-contact overrides, in one transaction. Returns
+/// how many contacts changed.
+#[tauri::command]
+pub fn apply_label_defaults_to_members(
+    label_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<u32, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+
+    let label = state
+        .storage
+        .load_label(&label_id)
+        .map_err(|e| CommandError::Storage(format!("Failed to load label: {:?}", e)))?;
+    let tree = label_tree::load(state.data_dir());
+
+    let updated = storage_worker::in_transaction(&state.storage, |storage| {
+        let field_ids = own_field_ids(storage)?;
+        let mut updated = 0;
+        for contact_id in label.contacts() {
+            let Some(mut contact) = storage.load_contact(contact_id)? else {
+                continue;
+            };
+            let allowed = effective_fields_for(storage, &tree, contact_id)?;
+            let mut changed = false;
+            for field_id in &field_ids {
+                let visible = allowed.contains(field_id);
+                let rules = contact.visibility_rules_mut();
+                if rules.can_see(field_id, contact_id) != visible {
+                    if visible {
+                        rules.set_everyone(field_id);
+                    } else {
+                        rules.set_nobody(field_id);
+                    }
+                    changed = true;
+                }
+            }
+            if changed {
+                storage.save_contact(&contact)?;
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    })?;
+
+    state.invalidate_contact_cache();
+    Ok(updated)
+}
+
+/// Get the label hierarchy as a child label ID → parent label ID map.
+#[tauri::command]
+pub fn get_label_parents(state: State<'_, Mutex<AppState>>) -> BTreeMap<String, String> {
+    let state = state.lock().unwrap();
+    label_tree::load(state.data_dir()).parents
+}
+
+/// Move a label under `new_parent`, or to the top level when `None`.
+/// The label then inherits the visible fields of its new ancestors.
+#[tauri::command]
+pub fn move_label(
+    label_id: String,
+    new_parent: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+
+    for id in std::iter::once(&label_id).chain(new_parent.as_ref()) {
+        state
+            .storage
+            .load_label(id)
+            .map_err(|e| CommandError::Storage(format!("Failed to load label: {:?}", e)))?;
+    }
+
+    let mut tree = label_tree::load(state.data_dir());
+    tree.move_label(&label_id, new_parent.as_deref())
+        .map_err(CommandError::Validation)?;
+    label_tree::save(state.data_dir(), &tree)
+        .map_err(|e| CommandError::Config(format!("Failed to save label tree: {}", e)))
+}
+
+/// Get how new contacts are labelled.
+#[tauri::command]
+pub fn get_default_label_settings(state: State<'_, Mutex<AppState>>) -> DefaultLabelSettings {
+    let state = state.lock().unwrap();
+    default_label::load(state.data_dir())
+}
+
+/// Set how new contacts are labelled. `Assign` needs an existing label.
+#[tauri::command]
+pub fn set_default_label_settings(
+    settings: DefaultLabelSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+
+    if settings.policy == DefaultLabelPolicy::Assign {
+        let label_id = settings.label_id.as_deref().ok_or_else(|| {
+            CommandError::Validation("Choose a label for new contacts".to_string())
+        })?;
+        state
+            .storage
+            .load_label(label_id)
+            .map_err(|e| CommandError::Storage(format!("Failed to load label: {:?}", e)))?;
+    }
+
+    default_label::save(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save default label settings: {}", e)))
+}
+
+/// Contact without any label, so outside the visibility scheme.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnlabeledContact {
+    pub id: String,
+    pub display_name: String,
+}
+
+/// List contacts that are in no label, sorted by name.
+#[tauri::command]
+pub fn get_unlabeled_contacts(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<UnlabeledContact>, CommandError> {
+    let state = state.lock().unwrap();
+
+    let labels = state
+        .storage
+        .load_all_labels()
+        .map_err(|e| CommandError::Storage(format!("Failed to load labels: {:?}", e)))?;
+    let labelled: HashSet<&str> = labels
+        .iter()
+        .flat_map(|l| l.contacts().iter().map(String::as_str))
+        .collect();
+
+    let mut unlabeled: Vec<UnlabeledContact> = state
+        .cached_contacts()?
+        .into_iter()
+        .filter(|c| !labelled.contains(c.id()))
+        .map(|c| UnlabeledContact {
+            id: c.id().to_string(),
+            display_name: c.display_name().to_string(),
+        })
+        .collect();
+    unlabeled.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    Ok(unlabeled)
+}
+
 /// Get suggested default labels.
 #[tauri::command]
 pub fn get_suggested_labels() -> Vec<String> {
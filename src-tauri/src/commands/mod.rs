@@ -6,16 +6,23 @@
 //!
 //! IPC commands exposed to the frontend.
 
+pub mod accessibility;
 pub mod actions;
+pub mod address_book;
 pub mod aha;
+pub mod app_update;
 pub mod auth;
 pub mod backup;
 pub mod card;
+pub mod carddav;
+pub mod clipboard;
 pub mod contacts;
 pub mod content;
 pub mod decoy;
+pub mod deep_link;
 pub mod delivery;
 pub mod devices;
+pub mod diagnostics;
 pub mod duress;
 pub mod emergency;
 pub mod exchange;
@@ -23,10 +30,22 @@ pub mod gdpr;
 pub mod help;
 pub mod i18n;
 pub mod identity;
+pub mod import;
 pub mod labels;
+pub mod nfc;
+pub mod notifications;
+pub mod offline_sync;
+pub mod onboarding;
+pub mod print;
 pub mod recovery;
+pub mod share_links;
+pub mod storage;
 pub mod sync;
 pub mod theme;
 pub mod tor;
+pub mod troubleshoot;
+pub mod trust_graph;
 pub mod validation;
 pub mod visibility;
+pub mod webhooks;
+pub mod window;
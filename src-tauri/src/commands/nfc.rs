@@ -0,0 +1,83 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! NFC Commands
+//!
+//! Tap instead of scan: write our exchange QR payload to an NFC tag, or
+//! read the peer's from one, through a PC/SC reader.
+
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::commands::exchange::{process_qr_data, ScannedQrPreview};
+use crate::deep_link::DeepLink;
+use crate::error::CommandError;
+use crate::error_stats;
+use crate::nfc::{self, NfcCapability};
+use crate::state::AppState;
+
+/// Whether an NFC reader is available, and whether a tag is on it.
+#[tauri::command]
+pub async fn get_nfc_capability() -> Result<NfcCapability, CommandError> {
+    error_stats::track("get_nfc_capability", async {
+        tauri::async_runtime::spawn_blocking(nfc::capability)
+            .await
+            .map_err(|e| CommandError::Exchange(format!("NFC check failed: {}", e)))
+    })
+    .await
+}
+
+/// Write the QR payload of the exchange started with `start_exchange` to
+/// the tag on the reader, waiting briefly for one to be put on it.
+#[tauri::command]
+pub async fn write_exchange_to_nfc(state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    error_stats::track("write_exchange_to_nfc", async {
+        let data = {
+            let state = state.lock().unwrap();
+            state
+                .exchange_session
+                .as_ref()
+                .and_then(|session| session.qr())
+                .map(|qr| qr.to_data_string())
+                .ok_or_else(|| {
+                    CommandError::Exchange("Start an exchange before writing a tag".to_string())
+                })?
+        }; // Lock released before the tag wait
+
+        let uri = format!("vauchi://exchange/{}", data);
+        tauri::async_runtime::spawn_blocking(move || nfc::write_uri(&uri))
+            .await
+            .map_err(|e| CommandError::Exchange(format!("NFC write failed: {}", e)))?
+            .map_err(CommandError::Exchange)
+    })
+    .await
+}
+
+/// Read the peer's exchange payload from the tag on the reader and process
+/// it as if its QR code had been scanned.
+#[tauri::command]
+pub async fn read_exchange_from_nfc(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ScannedQrPreview, CommandError> {
+    error_stats::track("read_exchange_from_nfc", async {
+        let uri = tauri::async_runtime::spawn_blocking(nfc::read_uri)
+            .await
+            .map_err(|e| CommandError::Exchange(format!("NFC read failed: {}", e)))?
+            .map_err(CommandError::Exchange)?;
+
+        let data = match DeepLink::parse(&uri)? {
+            DeepLink::Exchange(data) => data,
+            _ => {
+                return Err(CommandError::Exchange(
+                    "The tag holds no exchange code".to_string(),
+                ))
+            }
+        };
+
+        let mut state = state.lock().unwrap();
+        process_qr_data(&mut state, &data)
+    })
+    .await
+}
@@ -0,0 +1,101 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Notification Commands
+//!
+//! Per-event preferences for OS notifications shown while the window is
+//! hidden, quiet hours, review of notifications missed during them, and
+//! the weekly digest of network changes.
+
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::clock;
+use crate::commands::i18n::resolve_locale;
+use crate::digest::{self, DigestSettings, WeeklyDigest};
+use crate::error::CommandError;
+use crate::notifications::{self, MissedNotification, NotificationSettings, MINUTES_PER_DAY};
+use crate::state::AppState;
+
+/// Get the notification preferences.
+#[tauri::command]
+pub fn get_notification_settings(state: State<'_, Mutex<AppState>>) -> NotificationSettings {
+    let state = state.lock().unwrap();
+    notifications::load_settings(state.data_dir())
+}
+
+/// Save the notification preferences.
+#[tauri::command]
+pub fn set_notification_settings(
+    settings: NotificationSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if let Some(quiet) = settings.quiet_hours {
+        if quiet.start_minute >= MINUTES_PER_DAY || quiet.end_minute >= MINUTES_PER_DAY {
+            return Err(CommandError::Validation(
+                "Quiet hours must be between 00:00 and 23:59".to_string(),
+            ));
+        }
+    }
+
+    let state = state.lock().unwrap();
+    notifications::save_settings(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save notification settings: {}", e)))
+}
+
+/// Get notifications suppressed during quiet hours, oldest first.
+///
+/// Pass `clear: true` to empty the queue after reading it.
+#[tauri::command]
+pub fn get_missed_notifications(
+    clear: Option<bool>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<MissedNotification>, CommandError> {
+    let state = state.lock().unwrap();
+    let missed = notifications::load_missed(state.data_dir());
+    if clear.unwrap_or(false) {
+        notifications::clear_missed(state.data_dir())?;
+    }
+    Ok(missed)
+}
+
+/// Get the digest of the last seven days' network changes.
+#[tauri::command]
+pub fn get_weekly_digest(
+    locale_code: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> WeeklyDigest {
+    let locale = resolve_locale(locale_code, &state);
+    let state = state.lock().unwrap();
+    digest::build(
+        &digest::load_log(state.data_dir()),
+        clock::now_secs(),
+        locale,
+        &digest::contact_names(&state),
+    )
+}
+
+/// Get the weekly digest notification preferences.
+#[tauri::command]
+pub fn get_digest_settings(state: State<'_, Mutex<AppState>>) -> DigestSettings {
+    let state = state.lock().unwrap();
+    digest::load_settings(state.data_dir())
+}
+
+/// Save the weekly digest notification preferences.
+#[tauri::command]
+pub fn set_digest_settings(
+    settings: DigestSettings,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if settings.weekday > 6 {
+        return Err(CommandError::Validation(
+            "Weekday must be between 0 (Monday) and 6 (Sunday)".to_string(),
+        ));
+    }
+    let state = state.lock().unwrap();
+    digest::save_settings(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save digest settings: {}", e)))
+}
@@ -0,0 +1,246 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Offline Sync Commands
+//!
+//! Sync without a relay: pending card updates are written to a file as the
+//! same encrypted envelopes the relay would carry, and carried to the
+//! contact by USB stick or a local file share. Importing the file runs the
+//! envelopes addressed to us through the normal sync pipeline (signature,
+//! revocation and replay checks).
+//!
+//! Exported updates stay queued, so a later relay sync still delivers them;
+//! the recipient drops the duplicate as a replay. Contact exchanges need a
+//! reply over the relay and are not carried.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use vauchi_core::exchange::EncryptedExchangeMessage;
+use vauchi_core::network::simple_message::{decode_simple_message, SimplePayload};
+
+use crate::clock;
+use crate::commands::sync::{encode_update, process_received, ReceivedMessages};
+use crate::error::CommandError;
+use crate::state::AppState;
+use crate::storage_worker;
+
+/// Marks a file as an offline update file.
+const FORMAT: &str = "vauchi-offline-updates";
+
+/// Current file version.
+const VERSION: u32 = 1;
+
+/// Pending updates as written to disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct OfflineUpdateFile {
+    format: String,
+    version: u32,
+    sender_id: String,
+    created_at: u64,
+    /// Base64 relay envelopes.
+    envelopes: Vec<String>,
+}
+
+/// Result of an export.
+#[derive(Debug, Serialize)]
+pub struct OfflineExportResult {
+    pub path: String,
+    pub updates_exported: u32,
+    /// Contacts with at least one exported update.
+    pub contacts: u32,
+}
+
+/// Result of an import.
+#[derive(Debug, Serialize)]
+pub struct OfflineImportResult {
+    pub cards_updated: u32,
+    /// Envelopes for someone else, exchanges, or unreadable ones.
+    pub skipped: u32,
+}
+
+/// Split a file's envelopes into card updates addressed to `my_id`, as
+/// `(sender_id, ciphertext)`, and the number skipped.
+fn inbound_updates(file: &OfflineUpdateFile, my_id: &str) -> (Vec<(String, Vec<u8>)>, u32) {
+    let mut updates = Vec::new();
+    let mut skipped = 0;
+    for encoded in &file.envelopes {
+        let envelope = STANDARD
+            .decode(encoded)
+            .ok()
+            .and_then(|data| decode_simple_message(&data).ok());
+        match envelope.map(|e| e.payload) {
+            Some(SimplePayload::EncryptedUpdate(update))
+                if update.recipient_id == my_id
+                    && EncryptedExchangeMessage::from_bytes(&update.ciphertext).is_err() =>
+            {
+                updates.push((update.sender_id, update.ciphertext));
+            }
+            _ => skipped += 1,
+        }
+    }
+    (updates, skipped)
+}
+
+/// Write pending updates to `path` for carrying to contacts offline.
+///
+/// `contact_ids` limits the file to those contacts, e.g. the one it is
+/// handed to; by default updates for all contacts are included.
+#[tauri::command]
+pub fn export_pending_updates(
+    path: String,
+    contact_ids: Option<Vec<String>>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<OfflineExportResult, CommandError> {
+    let state = state.lock().unwrap();
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+    let sender_id = identity.public_id();
+
+    let mut envelopes = Vec::new();
+    let mut contacts = 0;
+    for contact in state.cached_contacts()? {
+        if contact_ids
+            .as_ref()
+            .is_some_and(|ids| !ids.iter().any(|id| id == contact.id()))
+        {
+            continue;
+        }
+        let pending = state
+            .storage
+            .get_pending_updates(contact.id())
+            .map_err(CommandError::from)?;
+        if pending.is_empty() {
+            continue;
+        }
+        for update in &pending {
+            envelopes.push(STANDARD.encode(encode_update(&sender_id, update)?));
+        }
+        contacts += 1;
+    }
+
+    let file = OfflineUpdateFile {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        sender_id,
+        created_at: clock::now_secs(),
+        envelopes,
+    };
+    let path = PathBuf::from(path);
+    std::fs::write(&path, serde_json::to_string(&file)?)?;
+
+    Ok(OfflineExportResult {
+        path: path.to_string_lossy().to_string(),
+        updates_exported: file.envelopes.len() as u32,
+        contacts,
+    })
+}
+
+/// Apply the updates addressed to us from an offline update file.
+#[tauri::command]
+pub fn import_received_updates(
+    path: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<OfflineImportResult, CommandError> {
+    let content = std::fs::read_to_string(PathBuf::from(path))?;
+    let file: OfflineUpdateFile = serde_json::from_str(&content)
+        .map_err(|_| CommandError::Validation("Not an offline update file".to_string()))?;
+    if file.format != FORMAT {
+        return Err(CommandError::Validation(
+            "Not an offline update file".to_string(),
+        ));
+    }
+    if file.version > VERSION {
+        return Err(CommandError::Validation(format!(
+            "Offline update file version {} is newer than this app supports",
+            file.version
+        )));
+    }
+
+    let state = state.lock().unwrap();
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+
+    let (card_updates, skipped) = inbound_updates(&file, &identity.public_id());
+    if card_updates.is_empty() {
+        return Ok(OfflineImportResult {
+            cards_updated: 0,
+            skipped,
+        });
+    }
+
+    let received = ReceivedMessages {
+        encrypted_exchange: Vec::new(),
+        card_updates,
+        device_sync_messages: Vec::new(),
+    };
+    let processed = storage_worker::in_transaction(&state.storage, |storage| {
+        process_received(identity, storage, state.data_dir(), received)
+    })?;
+    state.invalidate_contact_cache();
+
+    Ok(OfflineImportResult {
+        cards_updated: processed.cards_updated,
+        skipped,
+    })
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private file format
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vauchi_core::network::simple_message::{
+        create_simple_envelope, encode_simple_message, SimpleEncryptedUpdate,
+    };
+
+    fn envelope(recipient_id: &str, ciphertext: Vec<u8>) -> String {
+        let update = SimpleEncryptedUpdate {
+            recipient_id: recipient_id.to_string(),
+            sender_id: "alice".to_string(),
+            ciphertext,
+        };
+        let envelope = create_simple_envelope(SimplePayload::EncryptedUpdate(update));
+        STANDARD.encode(encode_simple_message(&envelope).unwrap())
+    }
+
+    #[test]
+    fn test_only_updates_for_us_are_taken() {
+        let file = OfflineUpdateFile {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            sender_id: "alice".to_string(),
+            created_at: 0,
+            envelopes: vec![
+                envelope("bob", vec![1, 2, 3]),
+                envelope("carol", vec![4, 5, 6]),
+                "not base64!".to_string(),
+            ],
+        };
+        let (updates, skipped) = inbound_updates(&file, "bob");
+        assert_eq!(updates, vec![("alice".to_string(), vec![1, 2, 3])]);
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn test_file_round_trips() {
+        let file = OfflineUpdateFile {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            sender_id: "alice".to_string(),
+            created_at: 42,
+            envelopes: vec![envelope("bob", vec![7])],
+        };
+        let parsed: OfflineUpdateFile =
+            serde_json::from_str(&serde_json::to_string(&file).unwrap()).unwrap();
+        assert_eq!(parsed.envelopes, file.envelopes);
+        assert_eq!(parsed.sender_id, "alice");
+    }
+}
@@ -0,0 +1,176 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Onboarding Commands
+//!
+//! Tracks the guided tour for new users: create identity → add field →
+//! first exchange → first sync. Progress is stored in `onboarding.json`
+//! next to the aha tracker. Steps may be completed in any order; the tour
+//! points at the first one not yet done.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::CommandError;
+use crate::state::AppState;
+
+/// Tour steps in order, with their titles.
+const STEPS: [(&str, &str); 4] = [
+    ("create_identity", "Create your identity"),
+    ("add_field", "Add a field to your card"),
+    ("first_exchange", "Exchange with your first contact"),
+    ("first_sync", "Sync your first update"),
+];
+
+/// Persisted tour progress.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct OnboardingProgress {
+    completed: Vec<String>,
+    skipped: bool,
+}
+
+/// A tour step for the frontend.
+#[derive(Serialize)]
+pub struct OnboardingStepInfo {
+    pub id: String,
+    pub title: String,
+    pub completed: bool,
+}
+
+/// Tour state for the frontend.
+#[derive(Serialize)]
+pub struct OnboardingState {
+    pub steps: Vec<OnboardingStepInfo>,
+    /// First step not yet completed, unless the tour is skipped or finished.
+    pub current_step: Option<String>,
+    pub skipped: bool,
+    /// All steps are completed.
+    pub finished: bool,
+}
+
+fn progress_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("onboarding.json")
+}
+
+fn load_progress(data_dir: &Path) -> OnboardingProgress {
+    std::fs::read_to_string(progress_path(data_dir))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_progress(data_dir: &Path, progress: &OnboardingProgress) -> Result<(), CommandError> {
+    std::fs::write(
+        progress_path(data_dir),
+        serde_json::to_string_pretty(progress)?,
+    )?;
+    Ok(())
+}
+
+fn state_from(progress: &OnboardingProgress) -> OnboardingState {
+    let steps: Vec<OnboardingStepInfo> = STEPS
+        .iter()
+        .map(|(id, title)| OnboardingStepInfo {
+            id: id.to_string(),
+            title: title.to_string(),
+            completed: progress.completed.iter().any(|c| c == id),
+        })
+        .collect();
+    let next = steps.iter().find(|s| !s.completed).map(|s| s.id.clone());
+    OnboardingState {
+        finished: next.is_none(),
+        current_step: next.filter(|_| !progress.skipped),
+        skipped: progress.skipped,
+        steps,
+    }
+}
+
+/// Get the guided tour state.
+#[tauri::command]
+pub fn get_onboarding_state(state: State<'_, Mutex<AppState>>) -> OnboardingState {
+    let state = state.lock().unwrap();
+    state_from(&load_progress(state.data_dir()))
+}
+
+/// Mark a tour step as completed. Completing a step twice is a no-op.
+#[tauri::command]
+pub fn complete_onboarding_step(
+    step: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<OnboardingState, CommandError> {
+    if !STEPS.iter().any(|(id, _)| *id == step) {
+        return Err(CommandError::Validation(format!(
+            "Unknown onboarding step: {}",
+            step
+        )));
+    }
+    let state = state.lock().unwrap();
+    let mut progress = load_progress(state.data_dir());
+    if !progress.completed.contains(&step) {
+        progress.completed.push(step);
+        save_progress(state.data_dir(), &progress)?;
+    }
+    Ok(state_from(&progress))
+}
+
+/// Skip the rest of the guided tour. Completed steps are kept.
+#[tauri::command]
+pub fn skip_onboarding(state: State<'_, Mutex<AppState>>) -> Result<OnboardingState, CommandError> {
+    let state = state.lock().unwrap();
+    let mut progress = load_progress(state.data_dir());
+    progress.skipped = true;
+    save_progress(state.data_dir(), &progress)?;
+    Ok(state_from(&progress))
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private progress persistence
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_new_tour_starts_at_first_step() {
+        let temp = TempDir::new().unwrap();
+        let state = state_from(&load_progress(temp.path()));
+        assert_eq!(state.current_step.as_deref(), Some("create_identity"));
+        assert!(!state.finished);
+        assert!(!state.skipped);
+    }
+
+    #[test]
+    fn test_out_of_order_completion_points_at_first_open_step() {
+        let temp = TempDir::new().unwrap();
+        let progress = OnboardingProgress {
+            completed: vec!["create_identity".to_string(), "first_exchange".to_string()],
+            skipped: false,
+        };
+        save_progress(temp.path(), &progress).unwrap();
+
+        let state = state_from(&load_progress(temp.path()));
+        assert_eq!(state.current_step.as_deref(), Some("add_field"));
+        assert!(state.steps[2].completed);
+    }
+
+    #[test]
+    fn test_skipped_and_finished() {
+        let skipped = state_from(&OnboardingProgress {
+            completed: vec![],
+            skipped: true,
+        });
+        assert!(skipped.current_step.is_none());
+        assert!(!skipped.finished);
+
+        let done = state_from(&OnboardingProgress {
+            completed: STEPS.iter().map(|(id, _)| id.to_string()).collect(),
+            skipped: false,
+        });
+        assert!(done.finished);
+        assert!(done.current_step.is_none());
+    }
+}
@@ -0,0 +1,241 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Print Sheet Commands
+//!
+//! Builds a self-contained HTML page for printing: the own card with its
+//! identity fingerprint (`own_card`), or one contact's details
+//! (`contact`). Styles and the QR SVG are inline and the page is sized
+//! with `@page` for A4 or Letter, so the frontend only has to print it.
+//!
+//! The own card's QR holds a share link (`vauchi://share/...`, see
+//! `share_links`) that the frontend created for the sheet, so scanning it
+//! with Vauchi opens the card. Without a link the sheet has no QR and only
+//! the fingerprint to compare. Labels are bundled desktop strings
+//! (`desktop_strings`) in the requested or current locale.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::State;
+use vauchi_core::i18n::get_locale_info;
+use vauchi_core::{AuthMode, ContactField};
+
+use crate::commands::contacts::format_hex_fingerprint;
+use crate::commands::devices::generate_qr_svg;
+use crate::commands::i18n::resolve_locale;
+use crate::desktop_strings;
+use crate::error::CommandError;
+use crate::share_links;
+use crate::state::AppState;
+use crate::xml;
+
+/// A printable document.
+#[derive(Serialize)]
+pub struct PrintSheet {
+    pub title: String,
+    /// Complete HTML document.
+    pub html: String,
+}
+
+/// Paper sizes a sheet can be laid out for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Paper {
+    A4,
+    Letter,
+}
+
+impl Paper {
+    fn parse(value: Option<&str>) -> Result<Self, CommandError> {
+        match value.unwrap_or("a4") {
+            "a4" => Ok(Paper::A4),
+            "letter" => Ok(Paper::Letter),
+            other => Err(CommandError::Validation(format!(
+                "Unknown paper size: {}",
+                other
+            ))),
+        }
+    }
+
+    fn css_size(self) -> &'static str {
+        match self {
+            Paper::A4 => "A4",
+            Paper::Letter => "letter",
+        }
+    }
+}
+
+/// Rows of a field table.
+fn field_rows(fields: &[ContactField]) -> String {
+    fields
+        .iter()
+        .map(|f| {
+            format!(
+                "<tr><th>{}</th><td dir=\"auto\">{}</td></tr>",
+                xml::escape(f.label()),
+                xml::escape(f.value())
+            )
+        })
+        .collect()
+}
+
+/// Wrap sheet content in a complete, printable HTML document.
+fn document(title: &str, body: &str, paper: Paper) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title><style>\
+         @page {{ size: {size}; margin: 20mm; }}\
+         body {{ font-family: sans-serif; color: #000; background: #fff; margin: 0; }}\
+         h1 {{ font-size: 24pt; margin: 0 0 8mm; }}\
+         table {{ border-collapse: collapse; width: 100%; font-size: 12pt; }}\
+         th {{ text-align: start; width: 35%; padding: 2mm 4mm 2mm 0; vertical-align: top; }}\
+         td {{ padding: 2mm 0; word-break: break-word; }}\
+         tr {{ border-bottom: 0.2mm solid #ccc; }}\
+         .qr {{ width: 60mm; height: 60mm; margin: 10mm 0 4mm; }}\
+         .fingerprint {{ font-family: monospace; font-size: 11pt; }}\
+         .note {{ font-size: 9pt; color: #555; margin-top: 8mm; }}\
+         </style></head><body>{body}</body></html>",
+        title = xml::escape(title),
+        size = paper.css_size(),
+        body = body
+    )
+}
+
+fn fingerprint_block(label: &str, fingerprint_hex: &str) -> String {
+    format!(
+        "<p>{}<br><span class=\"fingerprint\" dir=\"ltr\">{}</span></p>",
+        xml::escape(label),
+        xml::escape(&format_hex_fingerprint(fingerprint_hex))
+    )
+}
+
+/// Generate a printable sheet.
+///
+/// `kind` is `own_card` (with an optional `share_link` for the QR) or
+/// `contact` (with `contact_id`); `paper` is `a4` (default) or `letter`.
+/// `locale_code` defaults to the current locale.
+#[tauri::command]
+pub fn generate_print_sheet(
+    kind: String,
+    contact_id: Option<String>,
+    share_link: Option<String>,
+    paper: Option<String>,
+    locale_code: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<PrintSheet, CommandError> {
+    let paper = Paper::parse(paper.as_deref())?;
+    let locale = get_locale_info(resolve_locale(locale_code, &state)).code;
+    let text = |key: &str| desktop_strings::get(locale, key);
+    let state = state.lock().unwrap();
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+
+    let (title, body) = match kind.as_str() {
+        "own_card" => {
+            let card = state
+                .get_card()
+                .map_err(|e| CommandError::Card(e.to_string()))?;
+            let name = card
+                .as_ref()
+                .map(|c| c.display_name().to_string())
+                .or_else(|| state.display_name().map(str::to_string))
+                .unwrap_or_default();
+            let fields = card
+                .as_ref()
+                .map(|c| field_rows(c.fields()))
+                .unwrap_or_default();
+            let fingerprint = hex::encode(identity.signing_keypair().public_key().as_bytes());
+            let (qr, note) = match share_link {
+                Some(link) => {
+                    share_links::parse_link(&link).map_err(CommandError::Validation)?;
+                    (
+                        format!("<div class=\"qr\">{}</div>", generate_qr_svg(link.trim())?),
+                        text("print.scan_note"),
+                    )
+                }
+                None => (String::new(), text("print.compare_note")),
+            };
+            let body = format!(
+                "<h1 dir=\"auto\">{}</h1><table>{}</table>{}{}<p class=\"note\">{}</p>",
+                xml::escape(&name),
+                fields,
+                qr,
+                fingerprint_block(&text("print.identity_fingerprint"), &fingerprint),
+                xml::escape(&note)
+            );
+            (name, body)
+        }
+        "contact" => {
+            let id = contact_id.ok_or_else(|| {
+                CommandError::Validation("A contact is required for a contact sheet".to_string())
+            })?;
+            // Real contacts are not printed in duress mode
+            let contact = if state.auth_mode == AuthMode::Duress {
+                None
+            } else {
+                state.cached_contact(&id)?
+            };
+            let contact =
+                contact.ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
+            let verified = if contact.is_fingerprint_verified() {
+                text("print.verified")
+            } else {
+                text("print.not_verified")
+            };
+            let body = format!(
+                "<h1 dir=\"auto\">{}</h1><table>{}</table>{}<p class=\"note\">{}</p>",
+                xml::escape(contact.display_name()),
+                field_rows(contact.card().fields()),
+                fingerprint_block(
+                    &text("print.fingerprint"),
+                    &hex::encode(contact.public_key())
+                ),
+                xml::escape(&verified)
+            );
+            (contact.display_name().to_string(), body)
+        }
+        other => {
+            return Err(CommandError::Validation(format!(
+                "Unknown print sheet kind: {}",
+                other
+            )))
+        }
+    };
+
+    Ok(PrintSheet {
+        html: document(&title, &body, paper),
+        title,
+    })
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private field rows and page layout
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vauchi_core::contact_card::FieldType;
+
+    #[test]
+    fn test_fields_are_escaped() {
+        let fields = vec![ContactField::new(
+            FieldType::Custom,
+            "<b>note</b>",
+            "Tom & \"Jerry\"",
+        )];
+        let rows = field_rows(&fields);
+        assert!(rows.contains("&lt;b&gt;note&lt;/b&gt;"));
+        assert!(rows.contains("Tom &amp; &quot;Jerry&quot;"));
+        assert!(!rows.contains("<b>"));
+    }
+
+    #[test]
+    fn test_document_sets_paper_size() {
+        let a4 = document("Alice", "<p>x</p>", Paper::parse(None).unwrap());
+        assert!(a4.starts_with("<!DOCTYPE html>"));
+        assert!(a4.contains("size: A4"));
+        let letter = document("Alice", "", Paper::parse(Some("letter")).unwrap());
+        assert!(letter.contains("size: letter"));
+        assert!(Paper::parse(Some("a3")).is_err());
+    }
+}
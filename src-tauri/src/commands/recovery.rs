@@ -11,20 +11,47 @@ use std::sync::Mutex;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::Serialize;
 use tauri::State;
-use vauchi_core::recovery::{RecoveryClaim, RecoverySettings, RecoveryVoucher};
+use vauchi_core::recovery::{RecoveryClaim, RecoveryVoucher};
 
+use crate::clock;
+use crate::commands::devices::MultipartQRFrame;
+use crate::commands::sync;
+use crate::device_mode;
 use crate::error::CommandError;
+use crate::error_stats;
+use crate::recovery_drill::{self, RecoveryDrillReport, Trustee};
+use crate::recovery_policy::{self, RecoveryPolicy};
+use crate::recovery_qr::{self, RecoveryQrKind};
+use crate::recovery_session::{self, IncomingRecoveryClaim, RecoveryMessage, RecoverySession};
 use crate::state::AppState;
 
 /// Recovery status for the frontend.
 #[derive(Serialize)]
-#[allow(dead_code)]
 pub struct RecoveryStatus {
     pub in_progress: bool,
     pub voucher_count: usize,
     pub threshold: u32,
     pub old_pk: Option<String>,
     pub new_pk: Option<String>,
+    /// The claim to hand to former contacts, base64 encoded.
+    pub claim: Option<String>,
+    pub vouchers: Vec<VoucherInfo>,
+    /// Whether enough vouchers were collected to finalize.
+    pub ready: bool,
+    pub finalized_at: Option<u64>,
+}
+
+/// Result of finalizing a recovery.
+#[derive(Serialize)]
+pub struct FinalizeRecoveryResult {
+    /// The recovery proof, base64 encoded, for contacts not reachable here.
+    pub proof: String,
+    /// Contacts the proof was sent to through the relay.
+    pub contacts_notified: usize,
+    /// Contacts the proof is queued for (delivered on the next sync).
+    pub contacts_queued: usize,
+    /// Why the relay could not be reached, if it could not.
+    pub relay_error: Option<String>,
 }
 
 /// Recovery settings for the frontend.
@@ -37,7 +64,6 @@ pub struct RecoverySettingsInfo {
 
 /// Voucher info for display.
 #[derive(Serialize)]
-#[allow(dead_code)]
 pub struct VoucherInfo {
     pub voucher_pk: String,
     pub timestamp: u64,
@@ -57,17 +83,39 @@ pub struct VerificationInfo {
 pub fn get_recovery_settings(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<RecoverySettingsInfo, CommandError> {
-    let settings = RecoverySettings::default();
-    let trusted_count = {
-        let state = state.lock().unwrap();
-        let contacts = state.storage.list_contacts().unwrap_or_default();
-        contacts.iter().filter(|c| c.is_recovery_trusted()).count() as u32
+    let state = state.lock().unwrap();
+    Ok(recovery_settings_info(&state))
+}
+
+/// Current recovery settings with the trusted contact count.
+pub(crate) fn recovery_settings_info(state: &AppState) -> RecoverySettingsInfo {
+    let policy = recovery_policy::load(state.data_dir());
+    let contacts = state.storage.list_contacts().unwrap_or_default();
+    RecoverySettingsInfo {
+        recovery_threshold: policy.recovery_threshold,
+        verification_threshold: policy.verification_threshold,
+        trusted_contact_count: contacts.iter().filter(|c| c.is_recovery_trusted()).count() as u32,
+    }
+}
+
+/// Set the recovery and verification thresholds.
+///
+/// The settings are kept with the identity and included in backups.
+#[tauri::command]
+pub fn set_recovery_settings(
+    recovery_threshold: u32,
+    verification_threshold: u32,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RecoverySettingsInfo, CommandError> {
+    let policy = RecoveryPolicy {
+        recovery_threshold,
+        verification_threshold,
     };
-    Ok(RecoverySettingsInfo {
-        recovery_threshold: settings.recovery_threshold(),
-        verification_threshold: settings.verification_threshold(),
-        trusted_contact_count: trusted_count,
-    })
+    policy.validate().map_err(CommandError::Validation)?;
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    recovery_policy::save(state.data_dir(), &policy)?;
+    Ok(recovery_settings_info(&state))
 }
 
 /// Create a recovery claim for a lost identity.
@@ -76,15 +124,28 @@ pub fn create_recovery_claim(
     old_pk_hex: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<String, CommandError> {
-    let state = state.lock().unwrap();
+    create_recovery_claim_impl(&state.lock().unwrap(), &old_pk_hex)
+}
 
+/// [`create_recovery_claim`] on locked state, shared with the test server.
+pub(crate) fn create_recovery_claim_impl(
+    state: &AppState,
+    old_pk_hex: &str,
+) -> Result<String, CommandError> {
+    device_mode::ensure_editable(state)?;
+    let claim = new_claim(state, old_pk_hex)?;
+    Ok(BASE64.encode(claim.to_bytes()))
+}
+
+/// Build a claim that the lost key `old_pk_hex` is replaced by ours.
+fn new_claim(state: &AppState, old_pk_hex: &str) -> Result<RecoveryClaim, CommandError> {
     let identity = state
         .identity
         .as_ref()
         .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
 
     // Parse old public key
-    let old_pk_bytes = hex::decode(&old_pk_hex)?;
+    let old_pk_bytes = hex::decode(old_pk_hex)?;
 
     if old_pk_bytes.len() != 32 {
         return Err(CommandError::Validation(
@@ -104,10 +165,7 @@ pub fn create_recovery_claim(
         ));
     }
 
-    let claim = RecoveryClaim::new(&old_pk, new_pk);
-    let claim_b64 = BASE64.encode(claim.to_bytes());
-
-    Ok(claim_b64)
+    Ok(RecoveryClaim::new(&old_pk, new_pk))
 }
 
 /// Create a voucher for someone's recovery claim.
@@ -116,7 +174,15 @@ pub fn create_recovery_voucher(
     claim_b64: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<String, CommandError> {
-    let state = state.lock().unwrap();
+    create_recovery_voucher_impl(&state.lock().unwrap(), &claim_b64)
+}
+
+/// [`create_recovery_voucher`] on locked state, shared with the test server.
+pub(crate) fn create_recovery_voucher_impl(
+    state: &AppState,
+    claim_b64: &str,
+) -> Result<String, CommandError> {
+    device_mode::ensure_editable(state)?;
 
     let identity = state
         .identity
@@ -124,18 +190,18 @@ pub fn create_recovery_voucher(
         .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
 
     // Parse claim
-    let claim_bytes = BASE64.decode(&claim_b64)?;
+    let claim_bytes = BASE64.decode(claim_b64)?;
 
     let claim = RecoveryClaim::from_bytes(&claim_bytes)
-        .map_err(|e| CommandError::Backup(format!("Invalid claim: {:?}", e)))?;
+        .map_err(|e| CommandError::Recovery(format!("Invalid claim: {:?}", e)))?;
 
     if claim.is_expired() {
-        return Err(CommandError::Validation("Claim has expired".to_string()));
+        return Err(CommandError::Recovery("Claim has expired".to_string()).or_clock_skew());
     }
 
     // Create voucher
     let voucher = RecoveryVoucher::create_from_claim(&claim, identity.signing_keypair())
-        .map_err(|e| CommandError::Backup(format!("Failed to create voucher: {:?}", e)))?;
+        .map_err(|e| CommandError::Recovery(format!("Failed to create voucher: {:?}", e)))?;
 
     let voucher_b64 = BASE64.encode(voucher.to_bytes());
     Ok(voucher_b64)
@@ -153,7 +219,7 @@ pub fn check_recovery_claim(
     let claim_bytes = BASE64.decode(&claim_b64)?;
 
     let claim = RecoveryClaim::from_bytes(&claim_bytes)
-        .map_err(|e| CommandError::Backup(format!("Invalid claim: {:?}", e)))?;
+        .map_err(|e| CommandError::Recovery(format!("Invalid claim: {:?}", e)))?;
 
     let old_pk_hex = hex::encode(claim.old_pk());
 
@@ -184,11 +250,15 @@ pub fn parse_recovery_claim(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<ClaimInfo, CommandError> {
     let state = state.lock().unwrap();
+    claim_info(&state, &claim_b64)
+}
 
-    let claim_bytes = BASE64.decode(&claim_b64)?;
+/// Decode a claim and look up whose lost key it replaces.
+pub(crate) fn claim_info(state: &AppState, claim_b64: &str) -> Result<ClaimInfo, CommandError> {
+    let claim_bytes = BASE64.decode(claim_b64)?;
 
     let claim = RecoveryClaim::from_bytes(&claim_bytes)
-        .map_err(|e| CommandError::Backup(format!("Invalid claim: {:?}", e)))?;
+        .map_err(|e| CommandError::Recovery(format!("Invalid claim: {:?}", e)))?;
 
     let old_pk_hex = hex::encode(claim.old_pk());
     let new_pk_hex = hex::encode(claim.new_pk());
@@ -208,3 +278,315 @@ pub fn parse_recovery_claim(
         contact_name,
     })
 }
+
+fn recovery_status(session: Option<&RecoverySession>, threshold: u32) -> RecoveryStatus {
+    RecoveryStatus {
+        in_progress: session.is_some_and(|s| s.finalized_at.is_none()),
+        voucher_count: session.map_or(0, |s| s.vouchers.len()),
+        threshold,
+        old_pk: session.map(|s| s.old_pk.clone()),
+        new_pk: session.map(|s| s.new_pk.clone()),
+        claim: session.map(|s| s.claim.clone()),
+        vouchers: session
+            .map(|s| {
+                s.vouchers
+                    .iter()
+                    .map(|v| VoucherInfo {
+                        voucher_pk: v.voucher_pk.clone(),
+                        timestamp: v.timestamp,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        ready: session.is_some_and(|s| s.is_complete(threshold)),
+        finalized_at: session.and_then(|s| s.finalized_at),
+    }
+}
+
+/// Vouchers needed to recover.
+fn recovery_threshold(state: &AppState) -> u32 {
+    recovery_policy::load(state.data_dir()).recovery_threshold
+}
+
+/// Start recovering the lost identity `old_pk` onto the current identity.
+///
+/// Replaces any recovery in progress. The returned status carries the claim
+/// to hand to former contacts, whose vouchers are then added with
+/// `add_received_voucher`.
+#[tauri::command]
+pub fn start_recovery(
+    old_pk: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RecoveryStatus, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let claim = new_claim(&state, old_pk.trim())?;
+    let session = RecoverySession::new(
+        hex::encode(claim.old_pk()),
+        hex::encode(claim.new_pk()),
+        BASE64.encode(claim.to_bytes()),
+        clock::now_secs(),
+    );
+    recovery_session::save(state.data_dir(), &session)?;
+    Ok(recovery_status(Some(&session), recovery_threshold(&state)))
+}
+
+/// Add a voucher received from a former contact to the recovery in progress.
+#[tauri::command]
+pub fn add_received_voucher(
+    voucher_b64: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RecoveryStatus, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let mut session = recovery_session::load(state.data_dir())
+        .filter(|s| s.finalized_at.is_none())
+        .ok_or_else(|| CommandError::Recovery("No recovery in progress".to_string()))?;
+    session
+        .add_voucher(&voucher_b64)
+        .map_err(|e| CommandError::Recovery(e.to_string()))?;
+    recovery_session::save(state.data_dir(), &session)?;
+    Ok(recovery_status(Some(&session), recovery_threshold(&state)))
+}
+
+/// Get the progress of the current recovery: vouchers collected vs the
+/// threshold.
+#[tauri::command]
+pub fn get_recovery_progress(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RecoveryStatus, CommandError> {
+    let state = state.lock().unwrap();
+    let session = recovery_session::load(state.data_dir());
+    Ok(recovery_status(
+        session.as_ref(),
+        recovery_threshold(&state),
+    ))
+}
+
+/// Finalize the recovery once enough vouchers are collected.
+///
+/// The recovery proof is sent to every contact that is not blocked,
+/// including those who only know the lost identity, and pushed to the
+/// relay right away; contacts that cannot be reached now get it on the
+/// next sync. The proof is also returned for sharing by hand.
+#[tauri::command]
+pub async fn finalize_recovery(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<FinalizeRecoveryResult, CommandError> {
+    error_stats::track("finalize_recovery", async {
+        let (proof_b64, queued, envelopes, storage, relay_url, identity_handle) = {
+            let state = state.lock().unwrap();
+            let (storage, relay_url, identity_handle) = sync::push_target(&state)?;
+            let identity = state
+                .identity
+                .as_ref()
+                .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+            let mut session = recovery_session::load(state.data_dir())
+                .filter(|s| s.finalized_at.is_none())
+                .ok_or_else(|| CommandError::Recovery("No recovery in progress".to_string()))?;
+            let threshold = recovery_threshold(&state);
+            if !session.is_complete(threshold) {
+                return Err(CommandError::Recovery(format!(
+                    "{} of {} vouchers collected",
+                    session.vouchers.len(),
+                    threshold
+                )));
+            }
+
+            let proof = session.proof();
+            let proof_b64 = BASE64.encode(serde_json::to_vec(&proof)?);
+            let sender_id = identity.public_id();
+            let mut queued = Vec::new();
+            let mut envelopes = Vec::new();
+            for contact in state.storage.list_contacts()? {
+                if contact.is_blocked() {
+                    continue;
+                }
+                match recovery_session::queue_proof(&state.storage, contact.id(), &proof) {
+                    Ok(update) => {
+                        if let Ok(data) = sync::encode_update(&sender_id, &update) {
+                            envelopes.push((update.id.clone(), data));
+                        }
+                        queued.push(update.id);
+                    }
+                    Err(e) => tracing::warn!("Failed to queue recovery proof: {}", e),
+                }
+            }
+
+            session.finalized_at = Some(clock::now_secs());
+            recovery_session::save(state.data_dir(), &session)?;
+
+            (
+                proof_b64,
+                queued,
+                envelopes,
+                storage,
+                relay_url,
+                identity_handle,
+            )
+        };
+
+        let mut contacts_notified = 0;
+        let mut relay_error = None;
+        if !envelopes.is_empty() {
+            match sync::push_updates(storage, &relay_url, identity_handle, envelopes).await {
+                Ok(sent_ids) => contacts_notified = sent_ids.len(),
+                Err(e) => {
+                    tracing::warn!("Recovery proof not pushed, left queued: {}", e);
+                    relay_error = Some(e.to_string());
+                }
+            }
+        }
+
+        Ok(FinalizeRecoveryResult {
+            proof: proof_b64,
+            contacts_notified,
+            contacts_queued: queued.len() - contacts_notified,
+            relay_error,
+        })
+    })
+    .await
+}
+
+/// Send the claim of the recovery in progress to a contact, asking them to
+/// vouch. The claim goes out with the next sync.
+#[tauri::command]
+pub fn send_recovery_claim(
+    contact_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let session = recovery_session::load(state.data_dir())
+        .filter(|s| s.finalized_at.is_none())
+        .ok_or_else(|| CommandError::Recovery("No recovery in progress".to_string()))?;
+    let message = RecoveryMessage::Claim {
+        claim: session.claim,
+    };
+    recovery_session::queue_message(&state.storage, &contact_id, &message)
+        .map_err(CommandError::Contact)?
+        .ok_or_else(|| {
+            CommandError::Contact("No secure session with this contact yet".to_string())
+        })?;
+    Ok(())
+}
+
+/// List received recovery claims about known contacts that have not
+/// expired, newest first.
+#[tauri::command]
+pub fn list_recovery_claims(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<IncomingRecoveryClaim>, CommandError> {
+    let state = state.lock().unwrap();
+    let mut claims = recovery_session::load_claims(state.data_dir());
+    claims.reverse();
+    Ok(claims)
+}
+
+/// Progress of scanning a recovery QR sequence.
+#[derive(Serialize)]
+pub struct RecoveryScanResult {
+    pub kind: RecoveryQrKind,
+    pub frames_received: usize,
+    pub total_frames: usize,
+    /// The scanned claim or voucher, base64 encoded, once complete.
+    pub payload: Option<String>,
+    /// A complete claim, ready to vouch for with `create_recovery_voucher`.
+    pub claim: Option<ClaimInfo>,
+    /// A complete voucher was added to the recovery in progress.
+    pub status: Option<RecoveryStatus>,
+}
+
+/// Render a claim or voucher as a QR code sequence for handing over in
+/// person. With `png`, each frame also carries a rasterized PNG.
+#[tauri::command]
+pub fn generate_recovery_qr(
+    kind: RecoveryQrKind,
+    data: String,
+    png: Option<bool>,
+) -> Result<Vec<MultipartQRFrame>, CommandError> {
+    BASE64.decode(data.trim())?;
+    let frames = recovery_qr::encode_frames(kind, data.trim());
+    let total_frames = frames.len();
+    frames
+        .into_iter()
+        .enumerate()
+        .map(|(i, frame_data)| {
+            MultipartQRFrame::render(i + 1, total_frames, frame_data, png.unwrap_or(false))
+        })
+        .collect()
+}
+
+/// Add a scanned recovery QR frame.
+///
+/// Once every frame of a claim is scanned, the claim is returned for
+/// review; a complete voucher is added to the recovery in progress.
+#[tauri::command]
+pub fn scan_recovery_qr(
+    frame_data: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RecoveryScanResult, CommandError> {
+    let mut state = state.lock().unwrap();
+    recovery_qr::add_frame(&mut state.pending_recovery_scan, &frame_data)
+        .map_err(CommandError::Validation)?;
+    let Some(scan) = state.pending_recovery_scan.clone() else {
+        return Err(CommandError::Validation(
+            "Not a recovery QR code".to_string(),
+        ));
+    };
+    let mut result = RecoveryScanResult {
+        kind: scan.kind,
+        frames_received: scan.frames_received(),
+        total_frames: scan.total_frames,
+        payload: None,
+        claim: None,
+        status: None,
+    };
+    let Some(payload) = scan.payload() else {
+        return Ok(result);
+    };
+    state.pending_recovery_scan = None;
+
+    match scan.kind {
+        RecoveryQrKind::Claim => result.claim = Some(claim_info(&state, &payload)?),
+        RecoveryQrKind::Voucher => {
+            let mut session = recovery_session::load(state.data_dir())
+                .filter(|s| s.finalized_at.is_none())
+                .ok_or_else(|| CommandError::Recovery("No recovery in progress".to_string()))?;
+            session
+                .add_voucher(&payload)
+                .map_err(|e| CommandError::Recovery(e.to_string()))?;
+            recovery_session::save(state.data_dir(), &session)?;
+            result.status = Some(recovery_status(Some(&session), recovery_threshold(&state)));
+        }
+    }
+    result.payload = Some(payload);
+    Ok(result)
+}
+
+/// Rehearse a recovery with the configured trustees and threshold.
+///
+/// Uses throwaway keys only; neither the identity nor any recovery in
+/// progress is touched.
+#[tauri::command]
+pub fn run_recovery_drill(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RecoveryDrillReport, CommandError> {
+    let state = state.lock().unwrap();
+    let trustees: Vec<Trustee> = state
+        .storage
+        .list_contacts()?
+        .iter()
+        .filter(|c| c.is_recovery_trusted())
+        .map(|c| Trustee {
+            contact_id: c.id().to_string(),
+            display_name: c.display_name().to_string(),
+            blocked: c.is_blocked(),
+        })
+        .collect();
+    Ok(recovery_drill::run(
+        &trustees,
+        recovery_threshold(&state),
+        clock::now_secs(),
+    ))
+}
@@ -0,0 +1,235 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Share Link Commands
+//!
+//! Create, list and revoke one-way share links of selected card fields,
+//! and open links received from others. Links are published to and
+//! fetched from the relay, through the Tor proxy in Tor mode. Builds
+//! without the `share-links` feature can list links but not make or open
+//! them.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::clock;
+use crate::commands::app_update;
+use crate::commands::devices::generate_qr_svg;
+use crate::device_mode;
+use crate::error::CommandError;
+use crate::error_stats;
+use crate::share_links::{self, ShareLinkRecord, SharedCard, SharedField};
+use crate::state::AppState;
+
+/// A created share link.
+#[derive(Serialize)]
+pub struct ShareLinkInfo {
+    pub id: String,
+    pub link: String,
+    pub qr_svg: String,
+    pub expires_at: Option<u64>,
+}
+
+/// A share link for the list, with its state at the time of listing.
+#[derive(Serialize)]
+pub struct ShareLinkSummary {
+    pub id: String,
+    pub field_ids: Vec<String>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+    pub revoked_at: Option<u64>,
+    /// Not revoked and not expired.
+    pub active: bool,
+}
+
+/// Create a link sharing `field_ids` of our card with someone who is not a
+/// contact. `expiry` is in seconds from now; without it the link does not
+/// expire. The link is shown once; only its ID is kept.
+#[tauri::command]
+pub async fn create_share_link(
+    field_ids: Vec<String>,
+    expiry: Option<u64>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ShareLinkInfo, CommandError> {
+    error_stats::track("create_share_link", async {
+        if field_ids.is_empty() {
+            return Err(CommandError::Validation(
+                "Choose at least one field to share".to_string(),
+            ));
+        }
+        if expiry == Some(0) {
+            return Err(CommandError::Validation(
+                "Expiry must be in the future".to_string(),
+            ));
+        }
+
+        let now = clock::now_secs();
+        let id = share_links::new_id();
+        let delete_token = share_links::new_delete_token();
+        let expires_at = expiry.map(|secs| now.saturating_add(secs));
+        let (blob, link, relay_url, proxy) = {
+            let state = state.lock().unwrap();
+            device_mode::ensure_editable(&state)?;
+            let identity = state
+                .identity_handle()
+                .map_err(|e| CommandError::Identity(e.to_string()))?;
+            let card = state
+                .get_card()
+                .map_err(|e| CommandError::Card(e.to_string()))?
+                .ok_or_else(|| CommandError::Card("No card to share from".to_string()))?;
+
+            let mut fields = Vec::new();
+            for field_id in &field_ids {
+                let field = card
+                    .fields()
+                    .iter()
+                    .find(|f| f.id() == field_id)
+                    .ok_or_else(|| CommandError::Card(format!("Field not found: {}", field_id)))?;
+                fields.push(SharedField {
+                    label: field.label().to_string(),
+                    field_type: format!("{:?}", field.field_type()),
+                    value: field.value().to_string(),
+                });
+            }
+
+            let shared = share_links::shared_card(
+                id.clone(),
+                card.display_name().to_string(),
+                &identity,
+                fields,
+                expires_at,
+            );
+            let (blob, link) = share_links::seal(&shared, &identity).map_err(CommandError::Card)?;
+            let proxy = app_update::http_proxy(&state)?;
+            (blob, link, state.relay_url().to_string(), proxy)
+        };
+
+        share_links::publish(
+            &relay_url,
+            proxy.as_ref(),
+            &id,
+            &delete_token,
+            expires_at,
+            blob,
+        )
+        .await
+        .map_err(CommandError::Network)?;
+        let qr_svg = generate_qr_svg(&link)?;
+
+        let state = state.lock().unwrap();
+        let mut registry = share_links::load(state.data_dir());
+        registry.links.push(ShareLinkRecord {
+            id: id.clone(),
+            field_ids,
+            created_at: now,
+            expires_at,
+            revoked_at: None,
+            delete_token: Some(delete_token),
+        });
+        share_links::save(state.data_dir(), &registry)
+            .map_err(|e| CommandError::Config(format!("Failed to save share links: {}", e)))?;
+
+        Ok(ShareLinkInfo {
+            id,
+            link,
+            qr_svg,
+            expires_at,
+        })
+    })
+    .await
+}
+
+/// List created share links, newest first.
+#[tauri::command]
+pub fn list_share_links(state: State<'_, Mutex<AppState>>) -> Vec<ShareLinkSummary> {
+    let state = state.lock().unwrap();
+    let now = clock::now_secs();
+    share_links::load(state.data_dir())
+        .links
+        .into_iter()
+        .rev()
+        .map(|record| ShareLinkSummary {
+            active: record.is_active(now),
+            id: record.id,
+            field_ids: record.field_ids,
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+            revoked_at: record.revoked_at,
+        })
+        .collect()
+}
+
+/// Revoke a share link: delete it from the relay so it no longer opens.
+#[tauri::command]
+pub async fn revoke_share_link(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    error_stats::track("revoke_share_link", async {
+        let (delete_token, relay_url, proxy) = {
+            let state = state.lock().unwrap();
+            device_mode::ensure_editable(&state)?;
+            let registry = share_links::load(state.data_dir());
+            let record =
+                registry.links.iter().find(|r| r.id == id).ok_or_else(|| {
+                    CommandError::Validation(format!("Share link not found: {}", id))
+                })?;
+            (
+                record.delete_token.clone(),
+                state.relay_url().to_string(),
+                app_update::http_proxy(&state)?,
+            )
+        };
+
+        // Links made before publishing to the relay have nothing to delete
+        if let Some(token) = delete_token {
+            share_links::delete(&relay_url, proxy.as_ref(), &id, &token)
+                .await
+                .map_err(CommandError::Network)?;
+        }
+
+        let state = state.lock().unwrap();
+        let mut registry = share_links::load(state.data_dir());
+        registry.revoke(&id, clock::now_secs());
+        share_links::save(state.data_dir(), &registry)
+            .map_err(|e| CommandError::Config(format!("Failed to save share links: {}", e)))
+    })
+    .await
+}
+
+/// Open a share link someone sent us: fetch it from the relay and check
+/// its signature. Revoked and expired links are refused.
+#[tauri::command]
+pub async fn open_share_link(
+    link: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<SharedCard, CommandError> {
+    error_stats::track("open_share_link", async {
+        let (id, key) = share_links::parse_link(&link).map_err(CommandError::Validation)?;
+        let (relay_url, proxy) = {
+            let state = state.lock().unwrap();
+            (
+                state.relay_url().to_string(),
+                app_update::http_proxy(&state)?,
+            )
+        };
+
+        let blob = share_links::fetch(&relay_url, proxy.as_ref(), &id)
+            .await
+            .map_err(CommandError::Network)?
+            .ok_or_else(|| {
+                CommandError::Validation("This share link was revoked or has expired".to_string())
+            })?;
+        let card = share_links::open(&id, &key, &blob).map_err(CommandError::Validation)?;
+        if card.is_expired(clock::now_secs()) {
+            return Err(
+                CommandError::Validation("This share link has expired".to_string()).or_clock_skew(),
+            );
+        }
+        Ok(card)
+    })
+    .await
+}
@@ -0,0 +1,210 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Storage Commands
+//!
+//! Where the profile's data lives, moving it elsewhere, how much space it
+//! takes, checking, repairing and compacting the database, and rotating
+//! its key or moving it into the OS keychain.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::background;
+use crate::data_location::{self, MigrationReport};
+use crate::db_integrity::{self, IntegrityReport, RepairReport, UndoReport};
+use crate::error::CommandError;
+use crate::error_stats;
+use crate::key_rotation::{self, RotationReport};
+use crate::keychain_migration::{self, KeychainMigration};
+use crate::state::AppState;
+use crate::storage_usage::{self, CompactionReport, StorageUsage};
+
+/// The data dir in use and how it was chosen.
+#[derive(Serialize)]
+pub struct DataDirInfo {
+    pub path: String,
+    /// Set through `VAUCHI_DATA_DIR`, which the app cannot change.
+    pub from_env: bool,
+    pub is_default: bool,
+}
+
+/// Get the data directory in use.
+#[tauri::command]
+pub fn get_data_dir(state: State<'_, Mutex<AppState>>) -> DataDirInfo {
+    let state = state.lock().unwrap();
+    DataDirInfo {
+        path: state.data_dir().to_string_lossy().to_string(),
+        from_env: data_location::env_override().is_some(),
+        is_default: state.data_dir() == data_location::default_data_dir(),
+    }
+}
+
+/// Move the database, keys, settings and caches to `new_path` (a new or
+/// empty directory), verify the copy, and restart the app from there. The
+/// old directory is removed on that start.
+///
+/// Background tasks are stopped first, and the app state stays locked
+/// from the copy until the app exits, so nothing writes to the old
+/// directory after it was copied. If the move fails, the tasks stay
+/// stopped until the next start.
+#[tauri::command]
+pub async fn migrate_data_dir(
+    new_path: String,
+    app: AppHandle,
+) -> Result<MigrationReport, CommandError> {
+    error_stats::track("migrate_data_dir", async {
+        if data_location::env_override().is_some() {
+            return Err(CommandError::Validation(format!(
+                "The data directory is set by {}; change it there",
+                data_location::ENV_VAR
+            )));
+        }
+
+        let new_path = PathBuf::from(new_path);
+        {
+            let state = app.state::<Mutex<AppState>>();
+            let state = state.lock().unwrap();
+            data_location::validate_target(state.data_dir(), &new_path)?;
+        }
+
+        background::stop_all().await;
+
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let state = handle.state::<Mutex<AppState>>();
+            let mut state = state.lock().unwrap();
+            let report = state
+                .stop_storage_worker()
+                .map_err(|e| CommandError::Storage(e.to_string()))
+                .and_then(|()| {
+                    data_location::migrate(
+                        &data_location::config_dir(),
+                        state.data_dir(),
+                        &new_path,
+                    )
+                });
+            if report.is_err() {
+                tracing::warn!("Background tasks stay stopped until the app restarts");
+                return report;
+            }
+            // Every subsystem holds the old path; start over from the new
+            // one, which removes the old directory. Until then the state
+            // stays locked so no command writes there.
+            handle.request_restart();
+            std::mem::forget(state);
+            report
+        })
+        .await
+        .map_err(|e| CommandError::Storage(format!("Data directory move failed: {}", e)))?
+    })
+    .await
+}
+
+/// Our signing key (hex), which own validations are stored under.
+fn my_id(state: &AppState) -> Option<String> {
+    state
+        .identity
+        .as_ref()
+        .map(|identity| hex::encode(identity.signing_public_key()))
+}
+
+/// Run SQLite's integrity checks and the app's own consistency checks.
+#[tauri::command]
+pub fn check_database_integrity(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<IntegrityReport, CommandError> {
+    let state = state.lock().unwrap();
+    db_integrity::check(state.data_dir(), &state.storage, my_id(&state).as_deref())
+}
+
+/// Fix what `check_database_integrity` finds, keeping a copy of anything
+/// removed in the quarantine folder. `dry_run` (default: true) only
+/// reports what would be fixed.
+#[tauri::command]
+pub fn repair_database(
+    dry_run: Option<bool>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RepairReport, CommandError> {
+    let state = state.lock().unwrap();
+    let dry_run = dry_run.unwrap_or(true);
+    let report = db_integrity::repair(
+        state.data_dir(),
+        &state.storage,
+        my_id(&state).as_deref(),
+        dry_run,
+    )?;
+    if !dry_run {
+        state.invalidate_contact_cache();
+    }
+    Ok(report)
+}
+
+/// Put back what a repair removed, from the quarantine file named in its
+/// report.
+#[tauri::command]
+pub fn undo_database_repair(
+    quarantine_file: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<UndoReport, CommandError> {
+    let state = state.lock().unwrap();
+    let report = db_integrity::undo_repair(
+        state.data_dir(),
+        &state.storage,
+        &PathBuf::from(quarantine_file),
+    )?;
+    state.invalidate_contact_cache();
+    Ok(report)
+}
+
+/// Get the disk space used by the database (per table), caches and logs.
+#[tauri::command]
+pub fn get_storage_usage(state: State<'_, Mutex<AppState>>) -> Result<StorageUsage, CommandError> {
+    let state = state.lock().unwrap();
+    storage_usage::usage(state.data_dir())
+}
+
+/// Give the space of deleted data back to the disk. With `purge_caches`,
+/// also delete caches that are not needed to run, including the content
+/// version history, so content updates can no longer be rolled back.
+/// Refused while a sync is running.
+#[tauri::command]
+pub fn compact_database(
+    purge_caches: Option<bool>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<CompactionReport, CommandError> {
+    // Held while compacting so no new storage worker starts; stopping the
+    // running one is refused while a sync uses it
+    let mut state = state.lock().unwrap();
+    state
+        .stop_storage_worker()
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    storage_usage::compact(state.data_dir(), purge_caches.unwrap_or(false))
+}
+
+/// Re-encrypt the database under a new storage key and replace the old
+/// key, e.g. after the device may have been compromised. The new key is
+/// kept in the OS keychain if it works, otherwise in file key storage.
+/// Refused while a sync is running.
+#[tauri::command]
+pub fn rotate_storage_key(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<RotationReport, CommandError> {
+    let mut state = state.lock().unwrap();
+    key_rotation::rotate(&mut state)
+}
+
+/// Move a storage key kept in file key storage into the OS keychain and
+/// shred the `.fallback-key` file. Also runs on every start.
+#[tauri::command]
+pub fn migrate_to_keychain(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<KeychainMigration, CommandError> {
+    // Held so no rotation changes the key meanwhile
+    let state = state.lock().unwrap();
+    keychain_migration::migrate(state.data_dir())
+}
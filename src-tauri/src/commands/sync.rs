@@ -7,13 +7,14 @@
 //! Handles synchronization with the relay server using async WebSocket I/O.
 //! Storage is scoped so it never lives across `.await` boundaries (it is `!Send`).
 
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use futures_util::{SinkExt, StreamExt};
 use serde::Serialize;
 use tauri::State;
-use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
 
 use vauchi_core::crypto::ratchet::DoubleRatchetState;
 use vauchi_core::exchange::{EncryptedExchangeMessage, X3DHKeyPair};
@@ -25,10 +26,27 @@ use vauchi_core::network::simple_message::{
 use vauchi_core::sync::{
     build_device_sync_envelopes, process_card_updates, DeviceSyncOrchestrator, SyncItem,
 };
-use vauchi_core::{Contact, ContactCard, Identity, IdentityBackup, Storage};
-
+use vauchi_core::{Contact, ContactCard, Identity, PendingUpdate, Storage};
+
+use crate::clock;
+use crate::clock_skew;
+use crate::commands::troubleshoot::clock_skew_secs;
+use crate::default_label;
+use crate::deleted_contacts;
+use crate::device_mode::{self, DeviceMode};
+use crate::digest;
 use crate::error::CommandError;
+use crate::error_stats;
+use crate::events::{self, AppEvent};
+use crate::metrics;
+use crate::milestones;
+use crate::mock_relay::{self, MockConnection, MockRelay};
+use crate::ratchet_messages;
 use crate::state::AppState;
+use crate::storage_worker::{self, StorageHandle};
+use crate::sync_history::{self, SyncRun};
+use crate::unread;
+use crate::validation_sync;
 
 /// Exchange response data: (recipient_id, exchange_key).
 type ExchangeResponses = Vec<(String, [u8; 32])>;
@@ -37,6 +55,42 @@ type ExchangeResponses = Vec<(String, [u8; 32])>;
 type WsStream =
     tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
 
+/// Connection to the relay: a real WebSocket, or the in-process mock
+/// relay when `VAUCHI_MOCK_RELAY=1`.
+enum RelaySocket {
+    Ws(Box<WsStream>),
+    Mock(MockConnection),
+}
+
+impl RelaySocket {
+    async fn send(&mut self, msg: Message) -> Result<(), WsError> {
+        match self {
+            RelaySocket::Ws(ws) => ws.send(msg).await,
+            RelaySocket::Mock(mock) => {
+                if let Message::Binary(data) = msg {
+                    mock.send(&data);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Next incoming message. The mock returns `None` once drained, which
+    /// sync treats like the receive timeout.
+    async fn next(&mut self) -> Option<Result<Message, WsError>> {
+        match self {
+            RelaySocket::Ws(ws) => ws.next().await,
+            RelaySocket::Mock(mock) => mock.recv().map(|data| Ok(Message::Binary(data))),
+        }
+    }
+
+    async fn close(&mut self) {
+        if let RelaySocket::Ws(ws) = self {
+            let _ = ws.close(None).await;
+        }
+    }
+}
+
 /// Result of a sync operation.
 #[derive(Serialize)]
 pub struct SyncResult {
@@ -57,31 +111,53 @@ pub struct SyncResult {
 pub struct SyncStatus {
     /// Number of pending outbound updates.
     pub pending_updates: u32,
-    /// Last sync timestamp (Unix seconds), if available.
+    /// When the last successful sync ended (Unix seconds), if any.
     pub last_sync: Option<u64>,
+    /// The latest sync run, successful or not.
+    pub last_run: Option<SyncRun>,
     /// Whether currently syncing.
     pub is_syncing: bool,
 }
 
+/// Default number of runs `get_sync_history` returns.
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+
 /// Connect to relay server via async WebSocket with timeout.
-async fn connect_to_relay(relay_url: &str) -> Result<WsStream, CommandError> {
-    let (ws_stream, _) = tokio::time::timeout(
+async fn connect_to_relay(relay_url: &str) -> Result<RelaySocket, CommandError> {
+    if mock_relay::is_enabled() {
+        return Ok(RelaySocket::Mock(MockRelay::shared().connect()));
+    }
+
+    let (ws_stream, response) = tokio::time::timeout(
         Duration::from_secs(5),
         tokio_tungstenite::connect_async(relay_url),
     )
     .await
     .map_err(|_| CommandError::Network("Connection timed out".to_string()))?
-    .map_err(|e| CommandError::Network(format!("WebSocket connection failed: {}", e)))?;
+    .map_err(|e| CommandError::Relay(format!("WebSocket connection failed: {}", e)))?;
+
+    // The handshake's Date header tells us whether our clock is off
+    if let Some(skew) = response
+        .headers()
+        .get("date")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|date| clock_skew_secs(date, clock::now_secs()))
+    {
+        clock_skew::record(skew, "relay");
+    }
 
-    Ok(ws_stream)
+    Ok(RelaySocket::Ws(Box::new(ws_stream)))
 }
 
 /// Send authenticated handshake to relay.
 async fn send_handshake(
-    socket: &mut WsStream,
+    socket: &mut RelaySocket,
     identity: &Identity,
     device_id: Option<&str>,
 ) -> Result<(), CommandError> {
+    if let RelaySocket::Mock(mock) = socket {
+        mock.register(identity.public_id(), device_id.map(|s| s.to_string()));
+    }
     let handshake = create_signed_handshake(identity, device_id.map(|s| s.to_string()));
     let envelope = create_simple_envelope(SimplePayload::Handshake(handshake));
     let data = encode_simple_message(&envelope)
@@ -89,22 +165,28 @@ async fn send_handshake(
     socket
         .send(Message::Binary(data))
         .await
-        .map_err(|e| CommandError::Network(format!("Send error: {}", e)))?;
+        .map_err(|e| CommandError::Relay(format!("Send error: {}", e)))?;
     Ok(())
 }
 
-/// Received messages from relay.
-struct ReceivedMessages {
-    encrypted_exchange: Vec<Vec<u8>>,
-    card_updates: Vec<(String, Vec<u8>)>,
-    device_sync_messages: Vec<SimpleDeviceSyncMessage>,
+/// Received messages from relay (or an offline update file).
+pub(crate) struct ReceivedMessages {
+    pub(crate) encrypted_exchange: Vec<Vec<u8>>,
+    pub(crate) card_updates: Vec<(String, Vec<u8>)>,
+    pub(crate) device_sync_messages: Vec<SimpleDeviceSyncMessage>,
 }
 
 /// Receive pending messages from relay with timeout.
-async fn receive_pending(socket: &mut WsStream) -> Result<ReceivedMessages, CommandError> {
+///
+/// Returns the messages and their acknowledgements, which must only be
+/// sent once the messages are stored: the relay drops acked messages.
+async fn receive_pending(
+    socket: &mut RelaySocket,
+) -> Result<(ReceivedMessages, Vec<Vec<u8>>), CommandError> {
     let mut encrypted_exchange = Vec::new();
     let mut card_updates = Vec::new();
     let mut device_sync_messages = Vec::new();
+    let mut acks = Vec::new();
 
     loop {
         // Use timeout to detect when no more messages are pending
@@ -126,13 +208,12 @@ async fn receive_pending(socket: &mut WsStream) -> Result<ReceivedMessages, Comm
                                 card_updates.push((update.sender_id, update.ciphertext));
                             }
 
-                            // Send acknowledgment
                             let ack = create_simple_ack(
                                 &envelope.message_id,
                                 SimpleAckStatus::ReceivedByRecipient,
                             );
                             if let Ok(ack_data) = encode_simple_message(&ack) {
-                                let _ = socket.send(Message::Binary(ack_data)).await;
+                                acks.push(ack_data);
                             }
                         }
                         SimplePayload::DeviceSyncMessage(msg) => {
@@ -140,10 +221,9 @@ async fn receive_pending(socket: &mut WsStream) -> Result<ReceivedMessages, Comm
                             let version = msg.version;
                             device_sync_messages.push(msg);
 
-                            // Send device sync ack
                             let ack = create_device_sync_ack(&envelope.message_id, version);
                             if let Ok(ack_data) = encode_simple_message(&ack) {
-                                let _ = socket.send(Message::Binary(ack_data)).await;
+                                acks.push(ack_data);
                             }
                         }
                         _ => {}
@@ -158,11 +238,14 @@ async fn receive_pending(socket: &mut WsStream) -> Result<ReceivedMessages, Comm
         }
     }
 
-    Ok(ReceivedMessages {
-        encrypted_exchange,
-        card_updates,
-        device_sync_messages,
-    })
+    Ok((
+        ReceivedMessages {
+            encrypted_exchange,
+            card_updates,
+            device_sync_messages,
+        },
+        acks,
+    ))
 }
 
 /// Process encrypted exchange messages (sync — no await, Storage-safe).
@@ -171,6 +254,7 @@ async fn receive_pending(socket: &mut WsStream) -> Result<ReceivedMessages, Comm
 fn process_exchanges_sync(
     identity: &Identity,
     storage: &Storage,
+    data_dir: &std::path::Path,
     encrypted_data: Vec<Vec<u8>>,
 ) -> Result<(u32, ExchangeResponses), CommandError> {
     let mut added = 0u32;
@@ -204,6 +288,13 @@ fn process_exchanges_sync(
         let contact = Contact::from_exchange(payload.identity_key, card, shared_secret.clone());
         let contact_id = contact.id().to_string();
         storage.save_contact(&contact).map_err(CommandError::from)?;
+        default_label::apply(storage, data_dir, &contact_id);
+        // Notifications and webhooks only for contacts that were stored
+        let event = AppEvent::ContactAdded {
+            contact_id: contact_id.clone(),
+            display_name: payload.display_name.clone(),
+        };
+        storage_worker::after_commit(move || events::publish(event));
 
         // Initialize ratchet
         let ratchet_dh = X3DHKeyPair::from_bytes(our_x3dh.secret_bytes());
@@ -214,6 +305,15 @@ fn process_exchanges_sync(
         responses.push((public_id, payload.exchange_key));
     }
 
+    if added > 0 {
+        if let Ok(contacts) = storage.list_contacts() {
+            let (data_dir, count) = (data_dir.to_path_buf(), contacts.len());
+            storage_worker::after_commit(move || {
+                milestones::record_contact_count(&data_dir, count)
+            });
+        }
+    }
+
     Ok((added, responses))
 }
 
@@ -253,7 +353,7 @@ async fn send_exchange_response(
         .map_err(|e| CommandError::Network(e.to_string()))?;
 
     tokio::time::sleep(Duration::from_millis(100)).await;
-    let _ = socket.close(None).await;
+    socket.close().await;
 
     Ok(())
 }
@@ -277,14 +377,7 @@ fn collect_pending_updates_data(
             .map_err(CommandError::from)?;
 
         for update in pending {
-            let msg = SimpleEncryptedUpdate {
-                recipient_id: contact.id().to_string(),
-                sender_id: our_id.clone(),
-                ciphertext: update.payload,
-            };
-
-            let envelope = create_simple_envelope(SimplePayload::EncryptedUpdate(msg));
-            if let Ok(data) = encode_simple_message(&envelope) {
+            if let Ok(data) = encode_update(&our_id, &update) {
                 result.push((update.id, data));
             }
         }
@@ -293,10 +386,87 @@ fn collect_pending_updates_data(
     Ok(result)
 }
 
+/// Encode a pending update as a relay envelope.
+pub(crate) fn encode_update(
+    sender_id: &str,
+    update: &PendingUpdate,
+) -> Result<Vec<u8>, CommandError> {
+    let msg = SimpleEncryptedUpdate {
+        recipient_id: update.contact_id.clone(),
+        sender_id: sender_id.to_string(),
+        ciphertext: update.payload.clone(),
+    };
+    let envelope = create_simple_envelope(SimplePayload::EncryptedUpdate(msg));
+    encode_simple_message(&envelope).map_err(|e| CommandError::Network(e.to_string()))
+}
+
+/// What `push_updates` needs, taken while holding the app state lock.
+///
+/// Refused on a mirror, which sends nothing of its own.
+pub(crate) fn push_target(
+    state: &AppState,
+) -> Result<(StorageHandle, String, Arc<Identity>), CommandError> {
+    device_mode::ensure_editable(state)?;
+    let identity = state
+        .identity_handle()
+        .map_err(|e| CommandError::Identity(e.to_string()))?;
+    let storage = state
+        .storage_handle()
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    Ok((storage, state.relay_url().to_string(), identity))
+}
+
+/// Push already queued updates to the relay right away, outside a full sync.
+///
+/// `updates` are `(update_id, envelope)` pairs from `encode_update`. Updates
+/// handed to the relay are removed from the pending queue; the rest stay
+/// queued for the next sync. Returns the IDs of the pushed updates.
+pub(crate) async fn push_updates(
+    storage: StorageHandle,
+    relay_url: &str,
+    identity: Arc<Identity>,
+    updates: Vec<(String, Vec<u8>)>,
+) -> Result<Vec<String>, CommandError> {
+    let device_id_hex = hex::encode(identity.device_id());
+
+    let mut socket = connect_to_relay(relay_url).await?;
+    send_handshake(&mut socket, &identity, Some(&device_id_hex)).await?;
+
+    let mut sent_ids = Vec::new();
+    for (update_id, data) in updates {
+        if socket.send(Message::Binary(data)).await.is_ok() {
+            sent_ids.push(update_id);
+        }
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    socket.close().await;
+
+    if !sent_ids.is_empty() {
+        delete_sent_updates(&storage, sent_ids.clone()).await?;
+    }
+    Ok(sent_ids)
+}
+
+/// Remove updates handed to the relay from the pending queue.
+async fn delete_sent_updates(
+    storage: &StorageHandle,
+    sent_ids: Vec<String>,
+) -> Result<(), CommandError> {
+    storage
+        .transaction(move |storage| {
+            for id in &sent_ids {
+                let _ = storage.delete_pending_update(id);
+            }
+            Ok(())
+        })
+        .await
+}
+
 /// Process incoming device sync messages from other devices.
 fn process_device_sync_messages(
     identity: &Identity,
     storage: &Storage,
+    data_dir: &std::path::Path,
     messages: Vec<SimpleDeviceSyncMessage>,
 ) -> Result<u32, CommandError> {
     if messages.is_empty() {
@@ -311,8 +481,13 @@ fn process_device_sync_messages(
 
     let mut orchestrator =
         DeviceSyncOrchestrator::new(storage, identity.create_device_info(), registry.clone());
+    let primary_id = registry
+        .all_devices()
+        .first()
+        .map(|device| hex::encode(device.device_id));
 
     let mut processed = 0u32;
+    let mut batch = SyncBatch::default();
 
     for msg in messages {
         // Parse sender device ID
@@ -339,6 +514,21 @@ fn process_device_sync_messages(
             Err(_) => continue,
         };
 
+        // Device scopes are ours rather than core's
+        if let Some(accepted) = device_mode::accept(
+            data_dir,
+            &msg.sender_device_id,
+            primary_id.as_deref(),
+            &plaintext,
+        ) {
+            match accepted {
+                Ok(true) => processed += 1,
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to apply device scopes: {}", e),
+            }
+            continue;
+        }
+
         // Parse SyncItems
         let items: Vec<SyncItem> = match serde_json::from_slice(&plaintext) {
             Ok(items) => items,
@@ -353,7 +543,7 @@ fn process_device_sync_messages(
 
         // Apply the items
         for item in &applied {
-            let _ = apply_sync_item(storage, item);
+            let _ = batch.apply(storage, item);
         }
 
         if !applied.is_empty() {
@@ -361,134 +551,289 @@ fn process_device_sync_messages(
         }
     }
 
+    let removed: Vec<String> = batch.removed.iter().cloned().collect();
+    batch.flush(storage)?;
+    let data_dir = data_dir.to_path_buf();
+    storage_worker::after_commit(move || deleted_contacts::record(&data_dir, &removed));
     Ok(processed)
 }
 
-/// Apply a single sync item to local storage.
-fn apply_sync_item(storage: &Storage, item: &SyncItem) -> Result<(), CommandError> {
-    match item {
-        SyncItem::ContactAdded { contact_data, .. } => {
-            if let Ok(contact) = contact_data.to_contact() {
-                storage.save_contact(&contact).map_err(CommandError::from)?;
-            }
-        }
-        SyncItem::ContactRemoved { contact_id, .. } => {
-            storage
-                .delete_contact(contact_id)
-                .map_err(CommandError::from)?;
+/// Writes from device sync items, coalesced so each contact and the own
+/// card are saved at most once per sync however many items touch them.
+#[derive(Default)]
+struct SyncBatch {
+    contacts: HashMap<String, Contact>,
+    removed: HashSet<String>,
+    own_card: Option<ContactCard>,
+}
+
+impl SyncBatch {
+    /// The contact as changed so far in this batch.
+    fn contact(
+        &mut self,
+        storage: &Storage,
+        id: &str,
+    ) -> Result<Option<&mut Contact>, CommandError> {
+        if self.removed.contains(id) {
+            return Ok(None);
         }
-        SyncItem::CardUpdated {
-            field_label,
-            new_value,
-            ..
-        } => {
-            if let Ok(Some(mut card)) = storage.load_own_card() {
-                if card.update_field_value(field_label, new_value).is_ok() {
-                    storage.save_own_card(&card).map_err(CommandError::from)?;
+        if !self.contacts.contains_key(id) {
+            match storage.load_contact(id)? {
+                Some(contact) => {
+                    self.contacts.insert(id.to_string(), contact);
                 }
+                None => return Ok(None),
             }
         }
-        SyncItem::VisibilityChanged {
-            contact_id,
-            field_label,
-            is_visible,
-            ..
-        } => {
-            if let Some(mut contact) = storage
-                .load_contact(contact_id)
-                .map_err(CommandError::from)?
-            {
-                if *is_visible {
-                    contact.visibility_rules_mut().set_everyone(field_label);
-                } else {
-                    contact.visibility_rules_mut().set_nobody(field_label);
+        Ok(self.contacts.get_mut(id))
+    }
+
+    /// Apply a single sync item to the batch.
+    fn apply(&mut self, storage: &Storage, item: &SyncItem) -> Result<(), CommandError> {
+        match item {
+            SyncItem::ContactAdded { contact_data, .. } => {
+                if let Ok(contact) = contact_data.to_contact() {
+                    let id = contact.id().to_string();
+                    self.removed.remove(&id);
+                    self.contacts.insert(id, contact);
+                }
+            }
+            SyncItem::ContactRemoved { contact_id, .. } => {
+                self.contacts.remove(contact_id);
+                self.removed.insert(contact_id.clone());
+            }
+            SyncItem::CardUpdated {
+                field_label,
+                new_value,
+                ..
+            } => {
+                if self.own_card.is_none() {
+                    self.own_card = storage.load_own_card().ok().flatten();
+                }
+                if let Some(card) = self.own_card.as_mut() {
+                    let _ = card.update_field_value(field_label, new_value);
+                }
+            }
+            SyncItem::VisibilityChanged {
+                contact_id,
+                field_label,
+                is_visible,
+                ..
+            } => {
+                if let Some(contact) = self.contact(storage, contact_id)? {
+                    if *is_visible {
+                        contact.visibility_rules_mut().set_everyone(field_label);
+                    } else {
+                        contact.visibility_rules_mut().set_nobody(field_label);
+                    }
                 }
-                storage.save_contact(&contact).map_err(CommandError::from)?;
+            }
+            SyncItem::LabelChange { .. } => {
+                // Label changes are handled by the label manager during full sync
+            }
+            _ => {
+                // Unknown sync items are silently ignored for forward compatibility
             }
         }
-        SyncItem::LabelChange { .. } => {
-            // Label changes are handled by the label manager during full sync
+        Ok(())
+    }
+
+    /// Write the batch to storage.
+    fn flush(self, storage: &Storage) -> Result<(), CommandError> {
+        for contact_id in &self.removed {
+            storage.delete_contact(contact_id)?;
+        }
+        for contact in self.contacts.values() {
+            storage.save_contact(contact)?;
         }
-        _ => {
-            // Unknown sync items are silently ignored for forward compatibility
+        if let Some(card) = &self.own_card {
+            storage.save_own_card(card)?;
         }
+        Ok(())
     }
-    Ok(())
+}
+
+/// Outcome of processing the messages received in a sync.
+pub(crate) struct ProcessedMessages {
+    contacts_added: u32,
+    exchange_responses: ExchangeResponses,
+    pub(crate) cards_updated: u32,
+    device_synced: u32,
+    device_envelopes: Vec<Vec<u8>>,
+    pending_to_send: Vec<(String, Vec<u8>)>,
+}
+
+/// Apply received messages to storage and collect what goes out.
+///
+/// Runs inside one storage transaction (see `do_sync_async`), so a sync
+/// after a long time offline commits once rather than once per message.
+pub(crate) fn process_received(
+    identity: &Identity,
+    storage: &Storage,
+    data_dir: &std::path::Path,
+    received: ReceivedMessages,
+) -> Result<ProcessedMessages, CommandError> {
+    // Process exchange messages
+    let (added, responses) =
+        process_exchanges_sync(identity, storage, data_dir, received.encrypted_exchange)?;
+
+    // Hand validations, emergency alerts and recovery messages from
+    // contacts to their features; the rest are card updates
+    let (card_updates, messages) =
+        ratchet_messages::take_inbound(identity, storage, data_dir, received.card_updates);
+    let validations_received = messages.validations;
+    if validations_received > 0 {
+        tracing::info!("Stored {} received field validations", validations_received);
+        let data_dir = data_dir.to_path_buf();
+        storage_worker::after_commit(move || {
+            digest::record(
+                &data_dir,
+                digest::DigestChange::ValidationsReceived {
+                    count: validations_received as u32,
+                },
+                clock::now_secs(),
+            )
+        });
+    }
+
+    if messages.alerts > 0 {
+        tracing::warn!("Received {} emergency alerts", messages.alerts);
+    }
+
+    // Process card updates (core's secure pipeline)
+    let senders: Vec<String> = card_updates
+        .iter()
+        .map(|(sender_id, _)| sender_id.clone())
+        .collect();
+    let card_result = process_card_updates(identity, storage, card_updates)
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+
+    // Flag updated contacts as unread
+    if card_result.processed > 0 {
+        let changed: Vec<String> = senders
+            .into_iter()
+            .filter(|id| matches!(storage.load_contact(id), Ok(Some(_))))
+            .collect();
+        let data_dir = data_dir.to_path_buf();
+        storage_worker::after_commit(move || {
+            if let Err(e) = unread::mark_changed(&data_dir, &changed) {
+                tracing::warn!("Failed to record unread contacts: {}", e);
+            }
+            for contact_id in &changed {
+                digest::record(
+                    &data_dir,
+                    digest::DigestChange::CardChanged {
+                        contact_id: contact_id.clone(),
+                    },
+                    clock::now_secs(),
+                );
+            }
+            events::publish(AppEvent::UnreadChanged {
+                total: unread::total(&data_dir),
+            });
+        });
+    }
+
+    // Process device sync messages
+    let device_synced =
+        process_device_sync_messages(identity, storage, data_dir, received.device_sync_messages)?;
+
+    // Build device sync envelopes for outbound
+    let mut device_envelopes = build_device_sync_envelopes(identity, storage).unwrap_or_default();
+    match device_mode::envelopes(identity, storage, data_dir) {
+        Ok(envelopes) => device_envelopes.extend(envelopes),
+        Err(e) => tracing::warn!("Failed to send device scopes: {}", e),
+    }
+
+    // Queue our new validations for the contacts they are about
+    if let Err(e) = validation_sync::queue_outbound(identity, storage, data_dir) {
+        tracing::warn!("Failed to queue field validations: {}", e);
+    }
+
+    // Collect pending update data
+    let pending = collect_pending_updates_data(identity, storage)?;
+
+    Ok(ProcessedMessages {
+        contacts_added: added,
+        exchange_responses: responses,
+        cards_updated: card_result.processed,
+        device_synced,
+        device_envelopes,
+        pending_to_send: pending,
+    })
 }
 
 /// Perform a fully async sync with the relay server.
 ///
-/// Storage is created in scoped blocks and dropped before any `.await` boundaries
-/// because `Storage` is `!Send` (contains `RefCell`).
+/// `Storage` is `!Send` (contains `RefCell`), so storage work runs on the
+/// storage worker between `.await` points. The identity comes from
+/// `AppState::identity_handle`, so the backup KDF does not run on every sync.
+/// A `mirror` device only receives: nothing queued on it is sent.
 async fn do_sync_async(
     data_dir: &std::path::Path,
+    storage: StorageHandle,
     relay_url: &str,
-    backup_password: &str,
+    identity: Arc<Identity>,
+    mirror: bool,
 ) -> Result<SyncResult, CommandError> {
-    // ── Phase 1: Reconstruct identity (Storage scoped, no await) ──
-    let (identity, device_id_hex) = {
-        let storage =
-            AppState::open_storage(data_dir).map_err(|e| CommandError::Storage(e.to_string()))?;
-        let (backup_data, _name) = storage
-            .load_identity()
-            .map_err(CommandError::from)?
-            .ok_or_else(|| CommandError::Identity("No identity found in storage".to_string()))?;
-        let backup = IdentityBackup::new(backup_data);
-        let identity = Identity::import_backup(&backup, backup_password)
-            .map_err(|e| CommandError::Identity(format!("Failed to import identity: {:?}", e)))?;
-        let device_id_hex = hex::encode(identity.device_id());
-        (identity, device_id_hex)
-        // storage dropped here
-    };
+    let _total = metrics::Timer::start("sync:total");
+
+    // ── Phase 1: Identity (already imported) ──
+    events::publish(AppEvent::SyncProgress {
+        phase: "identity".to_string(),
+    });
+    let device_id_hex = hex::encode(identity.device_id());
 
     // ── Phase 2: Connect and receive messages (async, no Storage) ──
-    let mut socket = connect_to_relay(relay_url).await?;
-    send_handshake(&mut socket, &identity, Some(&device_id_hex)).await?;
-    tokio::time::sleep(Duration::from_millis(100)).await;
-    let received = receive_pending(&mut socket).await?;
+    let (mut socket, received, acks) = {
+        let _timer = metrics::Timer::start("sync:receive");
+        events::publish(AppEvent::SyncProgress {
+            phase: "receive".to_string(),
+        });
+        let mut socket = connect_to_relay(relay_url).await?;
+        send_handshake(&mut socket, &identity, Some(&device_id_hex)).await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let (received, acks) = receive_pending(&mut socket).await?;
+        (socket, received, acks)
+    };
 
-    // ── Phase 3: Process received messages (Storage scoped, no await) ──
-    let (
+    // ── Phase 3: Process received messages (storage worker, one transaction) ──
+    // Nothing is acked before the transaction commits, so the relay
+    // delivers the messages again if it rolls back.
+    let ProcessedMessages {
         contacts_added,
         exchange_responses,
         cards_updated,
         device_synced,
         device_envelopes,
         pending_to_send,
-    ) = {
-        let storage =
-            AppState::open_storage(data_dir).map_err(|e| CommandError::Storage(e.to_string()))?;
-
-        // Process exchange messages
-        let (added, responses) =
-            process_exchanges_sync(&identity, &storage, received.encrypted_exchange)?;
-
-        // Process card updates (core's secure pipeline)
-        let card_result = process_card_updates(&identity, &storage, received.card_updates)
-            .map_err(|e| CommandError::Storage(e.to_string()))?;
-
-        // Process device sync messages
-        let device_synced =
-            process_device_sync_messages(&identity, &storage, received.device_sync_messages)?;
-
-        // Build device sync envelopes for outbound
-        let device_envelopes = build_device_sync_envelopes(&identity, &storage).unwrap_or_default();
-
-        // Collect pending update data
-        let pending = collect_pending_updates_data(&identity, &storage)?;
-
-        (
-            added,
-            responses,
-            card_result.processed,
-            device_synced,
-            device_envelopes,
-            pending,
-        )
-        // storage dropped here
+    } = {
+        let _timer = metrics::Timer::start("sync:process");
+        events::publish(AppEvent::SyncProgress {
+            phase: "process".to_string(),
+        });
+        let identity = Arc::clone(&identity);
+        let data_dir = data_dir.to_path_buf();
+        storage
+            .transaction(move |storage| process_received(&identity, storage, &data_dir, received))
+            .await?
     };
+    for ack in acks {
+        let _ = socket.send(Message::Binary(ack)).await;
+    }
 
     // ── Phase 4: Send outbound data (async, no Storage) ──
+    let send_timer = metrics::Timer::start("sync:send");
+    events::publish(AppEvent::SyncProgress {
+        phase: "send".to_string(),
+    });
+
+    // A mirror leaves its queue as it is
+    let (exchange_responses, device_envelopes, pending_to_send) = if mirror {
+        (Vec::new(), Vec::new(), Vec::new())
+    } else {
+        (exchange_responses, device_envelopes, pending_to_send)
+    };
 
     // Send exchange responses (each opens its own connection)
     for (recipient_id, exchange_key) in &exchange_responses {
@@ -513,17 +858,18 @@ async fn do_sync_async(
         }
     }
 
-    // ── Phase 5: Cleanup sent updates (Storage scoped, no await) ──
+    drop(send_timer);
+
+    // ── Phase 5: Cleanup sent updates (storage worker) ──
     if !sent_ids.is_empty() {
-        let storage =
-            AppState::open_storage(data_dir).map_err(|e| CommandError::Storage(e.to_string()))?;
-        for id in &sent_ids {
-            let _ = storage.delete_pending_update(id);
-        }
-        // storage dropped here
+        let _timer = metrics::Timer::start("sync:cleanup");
+        events::publish(AppEvent::SyncProgress {
+            phase: "cleanup".to_string(),
+        });
+        delete_sent_updates(&storage, sent_ids).await?;
     }
 
-    let _ = socket.close(None).await;
+    socket.close().await;
 
     Ok(SyncResult {
         contacts_added,
@@ -540,8 +886,13 @@ async fn do_sync_async(
 /// Fully async — no blocking I/O on the Tauri command thread.
 #[tauri::command]
 pub async fn sync(state: State<'_, Mutex<AppState>>) -> Result<SyncResult, CommandError> {
+    error_stats::track("sync", sync_impl(&state)).await
+}
+
+/// [`sync`] on the app state, shared with the emergency escalation loop.
+pub(crate) async fn sync_impl(state: &Mutex<AppState>) -> Result<SyncResult, CommandError> {
     // Extract what we need from state (hold lock briefly, then release)
-    let (data_dir, relay_url, backup_password) = {
+    let (data_dir, storage, relay_url, identity, mirror) = {
         let state_guard = state.lock().unwrap();
 
         if state_guard.identity.is_none() {
@@ -550,31 +901,92 @@ pub async fn sync(state: State<'_, Mutex<AppState>>) -> Result<SyncResult, Comma
             ));
         }
 
-        let backup_password = state_guard
-            .backup_password()
+        let identity = {
+            let _timer = metrics::Timer::start("sync:identity");
+            state_guard
+                .identity_handle()
+                .map_err(|e| CommandError::Identity(e.to_string()))?
+        };
+
+        let storage = state_guard
+            .storage_handle()
             .map_err(|e| CommandError::Storage(e.to_string()))?;
 
         (
             state_guard.data_dir().to_path_buf(),
+            storage,
             state_guard.relay_url().to_string(),
-            backup_password,
+            identity,
+            device_mode::current(&state_guard) == DeviceMode::Mirror,
         )
     };
     // Mutex lock released here — UI thread is now unblocked
 
+    events::publish(AppEvent::SyncStarted);
+
     // Run fully async sync (no spawn_blocking needed)
-    do_sync_async(&data_dir, &relay_url, &backup_password).await
+    let started_at = clock::now_secs();
+    let result = do_sync_async(&data_dir, storage, &relay_url, identity, mirror).await;
+
+    let run = match &result {
+        Ok(r) => SyncRun {
+            started_at,
+            finished_at: clock::now_secs(),
+            success: r.success,
+            contacts_added: r.contacts_added,
+            cards_updated: r.cards_updated,
+            updates_sent: r.updates_sent,
+            error: r.error.clone(),
+        },
+        Err(e) => SyncRun {
+            started_at,
+            finished_at: clock::now_secs(),
+            success: false,
+            contacts_added: 0,
+            cards_updated: 0,
+            updates_sent: 0,
+            error: Some(e.to_string()),
+        },
+    };
+    if let Err(e) = sync_history::record(&data_dir, run) {
+        tracing::warn!("Failed to record sync history: {}", e);
+    }
+
+    events::publish(match &result {
+        Ok(r) => AppEvent::SyncCompleted {
+            success: r.success,
+            contacts_added: r.contacts_added,
+            cards_updated: r.cards_updated,
+            updates_sent: r.updates_sent,
+            error: r.error.clone(),
+        },
+        Err(e) => AppEvent::SyncCompleted {
+            success: false,
+            contacts_added: 0,
+            cards_updated: 0,
+            updates_sent: 0,
+            error: Some(e.to_string()),
+        },
+    });
+
+    // Sync writes contacts through its own Storage handles
+    state.lock().unwrap().invalidate_contact_cache();
+
+    result
 }
 
 /// Get the current sync status.
 #[tauri::command]
 pub fn get_sync_status(state: State<'_, Mutex<AppState>>) -> Result<SyncStatus, CommandError> {
     let state = state.lock().unwrap();
+    let last_sync = sync_history::last_success(state.data_dir());
+    let last_run = sync_history::last(state.data_dir());
 
     if state.identity.is_none() {
         return Ok(SyncStatus {
             pending_updates: 0,
-            last_sync: None,
+            last_sync,
+            last_run,
             is_syncing: false,
         });
     }
@@ -593,11 +1005,24 @@ pub fn get_sync_status(state: State<'_, Mutex<AppState>>) -> Result<SyncStatus,
 
     Ok(SyncStatus {
         pending_updates: total_pending,
-        last_sync: None,
+        last_sync,
+        last_run,
         is_syncing: false,
     })
 }
 
+/// Get the last `limit` sync runs (default 20), newest first.
+#[tauri::command]
+pub fn get_sync_history(limit: Option<usize>, state: State<'_, Mutex<AppState>>) -> Vec<SyncRun> {
+    let state = state.lock().unwrap();
+    sync_history::recent(
+        state.data_dir(),
+        limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .min(sync_history::MAX_RUNS),
+    )
+}
+
 /// Get the current relay URL.
 #[tauri::command]
 pub fn get_relay_url(state: State<'_, Mutex<AppState>>) -> Result<String, CommandError> {
@@ -5,25 +5,77 @@
 //! Theme Commands
 //!
 //! Handles theme management for the desktop app.
+//!
+//! Bundled themes are embedded at compile time. User themes are JSON files
+//! in `<data_dir>/themes/` with the same shape as `ThemeInfo`; they are
+//! validated strictly (colors must be hex) because the frontend injects
+//! the colors into a stylesheet.
+//!
+//! The active theme is stored in `active_theme.json`. Without an explicit
+//! theme the app follows the OS dark/light setting, which is tracked from
+//! window theme events and announced as `theme://system-changed`.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, Theme as WindowTheme, Window};
 use vauchi_core::theme::{load_themes_from_json, Theme, ThemeColors, ThemeMode};
 
+use crate::error::CommandError;
+use crate::state::AppState;
+
 /// Themes embedded at compile time from the themes repo.
 const THEMES_JSON: &[u8] = include_bytes!("../../../../themes/themes.json");
 
+/// User theme directory name under the data dir.
+const USER_THEMES_DIR: &str = "themes";
+
+/// Theme files larger than this are rejected.
+const MAX_THEME_FILE_BYTES: u64 = 64 * 1024;
+
+/// Longest accepted theme ID or name.
+const MAX_THEME_TEXT_LEN: usize = 64;
+
+/// Minimum WCAG AA contrast ratio for normal-size text.
+const MIN_TEXT_CONTRAST: f64 = 4.5;
+
+/// Text/background color pairs checked in custom themes.
+const CONTRAST_PAIRS: [(&str, &str); 5] = [
+    ("text_primary", "bg_primary"),
+    ("text_primary", "bg_secondary"),
+    ("text_primary", "bg_tertiary"),
+    ("text_secondary", "bg_primary"),
+    ("text_secondary", "bg_secondary"),
+];
+
+/// Active theme settings file name under the data dir.
+const ACTIVE_THEME_FILE: &str = "active_theme.json";
+
+/// Event emitted when the OS switches between dark and light mode.
+pub const SYSTEM_THEME_CHANGED_EVENT: &str = "theme://system-changed";
+
+/// ID of the built-in high-contrast theme.
+pub const HIGH_CONTRAST_THEME_ID: &str = "high-contrast";
+
+/// Last known OS appearance.
+static SYSTEM_DARK: AtomicBool = AtomicBool::new(false);
+
 /// Theme information for the frontend.
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct ThemeInfo {
     pub id: String,
     pub name: String,
     pub mode: String,
     pub author: Option<String>,
     pub colors: ThemeColorsInfo,
+    /// Whether the theme comes from the user themes directory.
+    pub is_user: bool,
 }
 
 /// Theme colors for the frontend.
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ThemeColorsInfo {
     pub bg_primary: String,
     pub bg_secondary: String,
@@ -49,6 +101,7 @@ impl From<&Theme> for ThemeInfo {
             },
             author: theme.author.clone(),
             colors: ThemeColorsInfo::from(&theme.colors),
+            is_user: false,
         }
     }
 }
@@ -75,19 +128,529 @@ fn load_themes() -> Vec<Theme> {
     load_themes_from_json(THEMES_JSON).unwrap_or_default()
 }
 
-/// Get all available themes.
+/// On-disk format of a user theme.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserThemeFile {
+    id: String,
+    name: String,
+    mode: String,
+    #[serde(default)]
+    author: Option<String>,
+    colors: ThemeColorsInfo,
+}
+
+impl From<UserThemeFile> for ThemeInfo {
+    fn from(file: UserThemeFile) -> Self {
+        ThemeInfo {
+            id: file.id,
+            name: file.name,
+            mode: file.mode,
+            author: file.author,
+            colors: file.colors,
+            is_user: true,
+        }
+    }
+}
+
+/// Path of the user themes directory.
+pub fn user_themes_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(USER_THEMES_DIR)
+}
+
+/// Whether `value` is a `#rgb`, `#rrggbb` or `#rrggbbaa` color.
+pub(crate) fn is_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+impl ThemeColorsInfo {
+    /// All colors with their field names.
+    pub(crate) fn entries(&self) -> [(&'static str, &str); 11] {
+        [
+            ("bg_primary", &self.bg_primary),
+            ("bg_secondary", &self.bg_secondary),
+            ("bg_tertiary", &self.bg_tertiary),
+            ("text_primary", &self.text_primary),
+            ("text_secondary", &self.text_secondary),
+            ("accent", &self.accent),
+            ("accent_dark", &self.accent_dark),
+            ("success", &self.success),
+            ("error", &self.error),
+            ("warning", &self.warning),
+            ("border", &self.border),
+        ]
+    }
+
+    /// Color value by field name.
+    fn get(&self, field: &str) -> Option<&str> {
+        self.entries()
+            .into_iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Parse a hex color into RGB, ignoring any alpha channel.
+fn parse_hex_rgb(value: &str) -> Option<[u8; 3]> {
+    if !is_hex_color(value) {
+        return None;
+    }
+    let hex = &value[1..];
+    let channel = |i: usize, len: usize| {
+        let digits = &hex[i * len..i * len + len];
+        let v = u8::from_str_radix(digits, 16).ok()?;
+        Some(if len == 1 { v * 17 } else { v })
+    };
+    let len = if hex.len() == 3 { 1 } else { 2 };
+    Some([channel(0, len)?, channel(1, len)?, channel(2, len)?])
+}
+
+/// WCAG relative luminance of an sRGB color.
+fn relative_luminance(rgb: [u8; 3]) -> f64 {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(rgb[0]) + 0.7152 * linear(rgb[1]) + 0.0722 * linear(rgb[2])
+}
+
+/// WCAG contrast ratio between two colors (1.0 to 21.0).
+pub(crate) fn contrast_ratio(a: [u8; 3], b: [u8; 3]) -> f64 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    let (light, dark) = if la > lb { (la, lb) } else { (lb, la) };
+    (light + 0.05) / (dark + 0.05)
+}
+
+/// Check text/background pairs against WCAG AA.
+fn check_contrast(colors: &ThemeColorsInfo) -> Result<(), String> {
+    for (text, background) in CONTRAST_PAIRS {
+        let rgb = |field| colors.get(field).and_then(parse_hex_rgb);
+        let (Some(fg), Some(bg)) = (rgb(text), rgb(background)) else {
+            return Err(format!("Invalid color in {} / {}", text, background));
+        };
+        let ratio = contrast_ratio(fg, bg);
+        if ratio < MIN_TEXT_CONTRAST {
+            return Err(format!(
+                "Contrast of {} on {} is {:.2}:1, below the required {}:1",
+                text, background, ratio, MIN_TEXT_CONTRAST
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Validate a user theme, returning why it is rejected.
+fn validate_user_theme(theme: &UserThemeFile) -> Result<(), String> {
+    if theme.id.is_empty()
+        || theme.id.len() > MAX_THEME_TEXT_LEN
+        || !theme
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err("Theme id must be 1-64 characters of a-z, 0-9 and '-'".to_string());
+    }
+    if is_builtin_theme_id(&theme.id) {
+        return Err(format!(
+            "Theme id '{}' is used by a bundled theme",
+            theme.id
+        ));
+    }
+    if theme.name.trim().is_empty() || theme.name.chars().count() > MAX_THEME_TEXT_LEN {
+        return Err("Theme name must be 1-64 characters".to_string());
+    }
+    if theme.mode != "light" && theme.mode != "dark" {
+        return Err("Theme mode must be 'light' or 'dark'".to_string());
+    }
+    for (field, value) in theme.colors.entries() {
+        if !is_hex_color(value) {
+            return Err(format!("Color '{}' is not a hex color: {}", field, value));
+        }
+    }
+    Ok(())
+}
+
+/// Read and validate a user theme file.
+fn read_user_theme(path: &Path) -> Result<UserThemeFile, String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("Failed to read theme: {}", e))?;
+    if meta.len() > MAX_THEME_FILE_BYTES {
+        return Err("Theme file is too large".to_string());
+    }
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read theme: {}", e))?;
+    let theme: UserThemeFile =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid theme file: {}", e))?;
+    validate_user_theme(&theme)?;
+    Ok(theme)
+}
+
+/// Load valid user themes, sorted by ID. Invalid files are skipped with a warning.
+pub fn load_user_themes(data_dir: &Path) -> Vec<ThemeInfo> {
+    let Ok(entries) = std::fs::read_dir(user_themes_dir(data_dir)) else {
+        return Vec::new();
+    };
+    let mut themes: Vec<ThemeInfo> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| match read_user_theme(&path) {
+            Ok(theme) => Some(ThemeInfo::from(theme)),
+            Err(e) => {
+                tracing::warn!("Skipping user theme {:?}: {}", path.file_name(), e);
+                None
+            }
+        })
+        .collect();
+    themes.sort_by(|a, b| a.id.cmp(&b.id));
+    themes
+}
+
+/// High-contrast theme (AAA contrast for all text) used by the accessibility setting.
+fn high_contrast_theme() -> ThemeInfo {
+    let color = |c: &str| c.to_string();
+    ThemeInfo {
+        id: HIGH_CONTRAST_THEME_ID.to_string(),
+        name: "High Contrast".to_string(),
+        mode: "dark".to_string(),
+        author: None,
+        colors: ThemeColorsInfo {
+            bg_primary: color("#000000"),
+            bg_secondary: color("#0a0a0a"),
+            bg_tertiary: color("#1a1a1a"),
+            text_primary: color("#ffffff"),
+            text_secondary: color("#e6e6e6"),
+            accent: color("#ffd700"),
+            accent_dark: color("#ffea70"),
+            success: color("#4dff4d"),
+            error: color("#ff6b6b"),
+            warning: color("#ffb84d"),
+            border: color("#ffffff"),
+        },
+        is_user: false,
+    }
+}
+
+/// Whether `id` belongs to a built-in theme.
+fn is_builtin_theme_id(id: &str) -> bool {
+    id == HIGH_CONTRAST_THEME_ID || load_themes().iter().any(|t| t.id == id)
+}
+
+/// Bundled themes followed by user themes.
+fn all_themes(data_dir: &Path) -> Vec<ThemeInfo> {
+    let mut themes: Vec<ThemeInfo> = load_themes().iter().map(ThemeInfo::from).collect();
+    themes.push(high_contrast_theme());
+    themes.extend(load_user_themes(data_dir));
+    themes
+}
+
+/// Get all available themes (bundled and user themes).
 #[tauri::command]
-pub fn get_available_themes() -> Vec<ThemeInfo> {
-    load_themes().iter().map(ThemeInfo::from).collect()
+pub fn get_available_themes(state: State<'_, Mutex<AppState>>) -> Vec<ThemeInfo> {
+    let state = state.lock().unwrap();
+    all_themes(state.data_dir())
 }
 
 /// Get a specific theme by ID.
 #[tauri::command]
-pub fn get_theme(theme_id: String) -> Option<ThemeInfo> {
-    load_themes()
-        .iter()
+pub fn get_theme(theme_id: String, state: State<'_, Mutex<AppState>>) -> Option<ThemeInfo> {
+    let state = state.lock().unwrap();
+    all_themes(state.data_dir())
+        .into_iter()
         .find(|t| t.id == theme_id)
-        .map(ThemeInfo::from)
+}
+
+/// Validate a theme file and copy it into the user themes directory.
+///
+/// Importing a theme with the ID of an existing user theme replaces it.
+#[tauri::command]
+pub fn import_theme_file(
+    path: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ThemeInfo, CommandError> {
+    let theme = read_user_theme(Path::new(&path)).map_err(CommandError::Validation)?;
+
+    let state = state.lock().unwrap();
+    write_user_theme(state.data_dir(), &theme)?;
+    Ok(ThemeInfo::from(theme))
+}
+
+/// Write a user theme to `<data_dir>/themes/<id>.json`.
+fn write_user_theme(data_dir: &Path, theme: &UserThemeFile) -> Result<(), CommandError> {
+    let dir = user_themes_dir(data_dir);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join(format!("{}.json", theme.id)),
+        serde_json::to_string_pretty(theme)?,
+    )?;
+    Ok(())
+}
+
+/// Colors to change in a custom theme; unset fields keep the base color.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeColorOverrides {
+    pub bg_primary: Option<String>,
+    pub bg_secondary: Option<String>,
+    pub bg_tertiary: Option<String>,
+    pub text_primary: Option<String>,
+    pub text_secondary: Option<String>,
+    pub accent: Option<String>,
+    pub accent_dark: Option<String>,
+    pub success: Option<String>,
+    pub error: Option<String>,
+    pub warning: Option<String>,
+    pub border: Option<String>,
+}
+
+impl ThemeColorOverrides {
+    /// Apply the set overrides to `colors`.
+    fn apply(self, colors: &mut ThemeColorsInfo) {
+        let pairs = [
+            (self.bg_primary, &mut colors.bg_primary),
+            (self.bg_secondary, &mut colors.bg_secondary),
+            (self.bg_tertiary, &mut colors.bg_tertiary),
+            (self.text_primary, &mut colors.text_primary),
+            (self.text_secondary, &mut colors.text_secondary),
+            (self.accent, &mut colors.accent),
+            (self.accent_dark, &mut colors.accent_dark),
+            (self.success, &mut colors.success),
+            (self.error, &mut colors.error),
+            (self.warning, &mut colors.warning),
+            (self.border, &mut colors.border),
+        ];
+        for (value, target) in pairs {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+    }
+}
+
+/// Name, mode and colors for a new custom theme.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CustomThemeOverrides {
+    /// Display name; defaults to "<base name> (custom)".
+    pub name: Option<String>,
+    /// `light` or `dark`; defaults to the base theme's mode.
+    pub mode: Option<String>,
+    pub colors: ThemeColorOverrides,
+}
+
+/// Theme ID derived from a name, unique among existing themes.
+fn custom_theme_id(name: &str, existing: &[ThemeInfo]) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(MAX_THEME_TEXT_LEN - 4);
+    let slug = match slug.trim_end_matches('-') {
+        "" => "custom".to_string(),
+        s => s.to_string(),
+    };
+
+    let taken = |id: &str| existing.iter().any(|t| t.id == id);
+    if !taken(&slug) {
+        return slug;
+    }
+    let mut n = 2;
+    loop {
+        let id = format!("{}-{}", slug, n);
+        if !taken(&id) {
+            return id;
+        }
+        n += 1;
+    }
+}
+
+/// Validate a custom theme and save it as a user theme.
+fn save_custom_theme(data_dir: &Path, theme: UserThemeFile) -> Result<ThemeInfo, CommandError> {
+    validate_user_theme(&theme).map_err(CommandError::Validation)?;
+    check_contrast(&theme.colors).map_err(CommandError::Validation)?;
+    write_user_theme(data_dir, &theme)?;
+    Ok(ThemeInfo::from(theme))
+}
+
+/// Create a user theme from a bundled or user theme with some colors changed.
+///
+/// Colors must be hex and text/background pairs must meet WCAG AA contrast.
+#[tauri::command]
+pub fn create_custom_theme(
+    base_id: String,
+    overrides: CustomThemeOverrides,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ThemeInfo, CommandError> {
+    let state = state.lock().unwrap();
+    let data_dir = state.data_dir();
+    let themes = all_themes(data_dir);
+    let base = themes
+        .iter()
+        .find(|t| t.id == base_id)
+        .ok_or_else(|| CommandError::Validation(format!("Unknown theme: {}", base_id)))?;
+
+    let name = overrides
+        .name
+        .map(|n| n.trim().to_string())
+        .unwrap_or_else(|| format!("{} (custom)", base.name));
+    let mut colors = base.colors.clone();
+    overrides.colors.apply(&mut colors);
+
+    let theme = UserThemeFile {
+        id: custom_theme_id(&name, &themes),
+        mode: overrides.mode.unwrap_or_else(|| base.mode.clone()),
+        author: None,
+        name,
+        colors,
+    };
+    save_custom_theme(data_dir, theme)
+}
+
+/// Replace the colors of an existing user theme.
+#[tauri::command]
+pub fn update_custom_theme(
+    id: String,
+    colors: ThemeColorsInfo,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ThemeInfo, CommandError> {
+    let state = state.lock().unwrap();
+    let data_dir = state.data_dir();
+    let mut theme = load_user_themes(data_dir)
+        .into_iter()
+        .find(|t| t.id == id)
+        .map(|t| UserThemeFile {
+            id: t.id,
+            name: t.name,
+            mode: t.mode,
+            author: t.author,
+            colors: t.colors,
+        })
+        .ok_or_else(|| CommandError::Validation(format!("Unknown user theme: {}", id)))?;
+    theme.colors = colors;
+    save_custom_theme(data_dir, theme)
+}
+
+/// Persisted theme choice. `theme_id: None` follows the OS appearance.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActiveThemeSettings {
+    pub theme_id: Option<String>,
+}
+
+/// Active theme for the frontend.
+#[derive(Serialize, Clone)]
+pub struct ActiveTheme {
+    /// Theme to apply (the explicit choice or the system default).
+    pub theme_id: String,
+    /// Whether the theme follows the OS dark/light setting.
+    pub follow_system: bool,
+    /// Current OS appearance.
+    pub system_dark: bool,
+}
+
+fn active_theme_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(ACTIVE_THEME_FILE)
+}
+
+/// Load the active theme settings, falling back to following the system.
+pub fn load_active_settings(data_dir: &Path) -> ActiveThemeSettings {
+    std::fs::read_to_string(active_theme_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the active theme settings.
+fn save_active_settings(data_dir: &Path, settings: &ActiveThemeSettings) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(settings)?;
+    std::fs::write(active_theme_path(data_dir), json)
+}
+
+/// Resolve the theme to apply. The high-contrast accessibility setting wins;
+/// a chosen theme that no longer exists (e.g. a deleted user theme) falls
+/// back to the system default.
+fn resolve_active_theme(data_dir: &Path, system_dark: bool) -> ActiveTheme {
+    if crate::accessibility::load(data_dir).high_contrast {
+        return ActiveTheme {
+            theme_id: HIGH_CONTRAST_THEME_ID.to_string(),
+            follow_system: false,
+            system_dark,
+        };
+    }
+    let settings = load_active_settings(data_dir);
+    let chosen = settings
+        .theme_id
+        .filter(|id| all_themes(data_dir).iter().any(|t| &t.id == id));
+    ActiveTheme {
+        follow_system: chosen.is_none(),
+        theme_id: chosen.unwrap_or_else(|| get_default_theme_id(system_dark)),
+        system_dark,
+    }
+}
+
+/// Whether the OS is currently in dark mode.
+fn system_dark() -> bool {
+    SYSTEM_DARK.load(Ordering::Relaxed)
+}
+
+/// Record the initial OS appearance from the main window.
+pub fn init_system_theme(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if let Ok(theme) = window.theme() {
+            SYSTEM_DARK.store(theme == WindowTheme::Dark, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Handle an OS theme change reported by a window.
+pub fn handle_system_theme_changed(window: &Window, theme: &WindowTheme) {
+    let dark = *theme == WindowTheme::Dark;
+    // Every window reports the change; only announce it once
+    if SYSTEM_DARK.swap(dark, Ordering::Relaxed) == dark {
+        return;
+    }
+    // The frontend reads the theme itself once the app state is ready
+    let Some(state) = window.try_state::<Mutex<AppState>>() else {
+        return;
+    };
+    let active = resolve_active_theme(state.lock().unwrap().data_dir(), dark);
+    if let Err(e) = window.app_handle().emit(SYSTEM_THEME_CHANGED_EVENT, active) {
+        tracing::warn!("Failed to emit system theme change: {}", e);
+    }
+}
+
+/// Get the active theme.
+#[tauri::command]
+pub fn get_active_theme(state: State<'_, Mutex<AppState>>) -> ActiveTheme {
+    let state = state.lock().unwrap();
+    resolve_active_theme(state.data_dir(), system_dark())
+}
+
+/// Set the active theme, or follow the system with `None`.
+#[tauri::command]
+pub fn set_active_theme(
+    theme_id: Option<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<ActiveTheme, CommandError> {
+    let state = state.lock().unwrap();
+    let data_dir = state.data_dir();
+    if let Some(id) = &theme_id {
+        if !all_themes(data_dir).iter().any(|t| &t.id == id) {
+            return Err(CommandError::Validation(format!("Unknown theme: {}", id)));
+        }
+    }
+    save_active_settings(data_dir, &ActiveThemeSettings { theme_id })
+        .map_err(|e| CommandError::Config(format!("Failed to save theme: {}", e)))?;
+    Ok(resolve_active_theme(data_dir, system_dark()))
 }
 
 /// Get the default theme ID based on system preference.
@@ -99,3 +662,156 @@ pub fn get_default_theme_id(prefer_dark: bool) -> String {
         "default-light".to_string()
     }
 }
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private user theme validation
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn theme_json(id: &str, accent: &str) -> String {
+        format!(
+            r##"{{"id":"{id}","name":"Ocean","mode":"dark","colors":{{"bg_primary":"#001122","bg_secondary":"#112233","bg_tertiary":"#223344","text_primary":"#ffffff","text_secondary":"#cccccc","accent":"{accent}","accent_dark":"#0055aa","success":"#00aa00","error":"#aa0000","warning":"#aaaa00","border":"#333"}}}}"##
+        )
+    }
+
+    #[test]
+    fn test_is_hex_color() {
+        assert!(is_hex_color("#fff"));
+        assert!(is_hex_color("#A1B2C3"));
+        assert!(is_hex_color("#a1b2c3d4"));
+        assert!(!is_hex_color("red"));
+        assert!(!is_hex_color("#12345"));
+        assert!(!is_hex_color("#fff; } body { x"));
+    }
+
+    #[test]
+    fn test_user_themes_loaded_and_invalid_skipped() {
+        let temp = TempDir::new().unwrap();
+        let dir = user_themes_dir(temp.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ocean.json"), theme_json("ocean", "#0088ff")).unwrap();
+        std::fs::write(
+            dir.join("evil.json"),
+            theme_json("evil", "red; } :root { --x: url(x)"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let themes = load_user_themes(temp.path());
+        assert_eq!(themes.len(), 1);
+        assert_eq!(themes[0].id, "ocean");
+        assert!(themes[0].is_user);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let ratio = contrast_ratio([0, 0, 0], [255, 255, 255]);
+        assert!((ratio - 21.0).abs() < 0.01);
+        assert!((contrast_ratio([0x77, 0x77, 0x77], [255, 255, 255]) - 4.48).abs() < 0.01);
+        assert_eq!(parse_hex_rgb("#fff"), Some([255, 255, 255]));
+        assert_eq!(parse_hex_rgb("#10203040"), Some([0x10, 0x20, 0x30]));
+    }
+
+    #[test]
+    fn test_check_contrast_rejects_low_contrast_text() {
+        let theme: UserThemeFile = serde_json::from_str(&theme_json("ocean", "#0088ff")).unwrap();
+        assert!(check_contrast(&theme.colors).is_ok());
+
+        let mut colors = theme.colors;
+        colors.text_secondary = "#334455".to_string();
+        let err = check_contrast(&colors).unwrap_err();
+        assert!(err.contains("text_secondary on bg_primary"));
+    }
+
+    #[test]
+    fn test_custom_theme_id_is_unique_slug() {
+        let temp = TempDir::new().unwrap();
+        let dir = user_themes_dir(temp.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ocean.json"), theme_json("ocean", "#0088ff")).unwrap();
+        let existing = load_user_themes(temp.path());
+
+        assert_eq!(custom_theme_id("My Theme!", &existing), "my-theme");
+        assert_eq!(custom_theme_id("Ocean", &existing), "ocean-2");
+        assert_eq!(custom_theme_id("???", &existing), "custom");
+    }
+
+    #[test]
+    fn test_save_custom_theme_validates_before_writing() {
+        let temp = TempDir::new().unwrap();
+        let mut theme: UserThemeFile =
+            serde_json::from_str(&theme_json("ocean", "#0088ff")).unwrap();
+        theme.colors.text_primary = "#002233".to_string();
+        assert!(save_custom_theme(temp.path(), theme).is_err());
+        assert!(load_user_themes(temp.path()).is_empty());
+
+        let theme: UserThemeFile = serde_json::from_str(&theme_json("ocean", "#0088ff")).unwrap();
+        save_custom_theme(temp.path(), theme).unwrap();
+        assert_eq!(load_user_themes(temp.path()).len(), 1);
+    }
+
+    #[test]
+    fn test_active_theme_follows_system_by_default() {
+        let temp = TempDir::new().unwrap();
+        let active = resolve_active_theme(temp.path(), true);
+        assert!(active.follow_system);
+        assert_eq!(active.theme_id, "default-dark");
+    }
+
+    #[test]
+    fn test_active_theme_missing_choice_falls_back() {
+        let temp = TempDir::new().unwrap();
+        let settings = ActiveThemeSettings {
+            theme_id: Some("deleted-theme".to_string()),
+        };
+        save_active_settings(temp.path(), &settings).unwrap();
+        assert_eq!(load_active_settings(temp.path()), settings);
+
+        let active = resolve_active_theme(temp.path(), false);
+        assert!(active.follow_system);
+        assert_eq!(active.theme_id, "default-light");
+    }
+
+    #[test]
+    fn test_high_contrast_overrides_choice_and_meets_contrast() {
+        let temp = TempDir::new().unwrap();
+        let settings = crate::accessibility::AccessibilitySettings {
+            high_contrast: true,
+            ..Default::default()
+        };
+        crate::accessibility::save(temp.path(), &settings).unwrap();
+
+        let active = resolve_active_theme(temp.path(), false);
+        assert_eq!(active.theme_id, HIGH_CONTRAST_THEME_ID);
+        assert!(check_contrast(&high_contrast_theme().colors).is_ok());
+        assert!(is_builtin_theme_id(HIGH_CONTRAST_THEME_ID));
+    }
+
+    #[test]
+    fn test_active_theme_user_choice() {
+        let temp = TempDir::new().unwrap();
+        let dir = user_themes_dir(temp.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ocean.json"), theme_json("ocean", "#0088ff")).unwrap();
+        save_active_settings(
+            temp.path(),
+            &ActiveThemeSettings {
+                theme_id: Some("ocean".to_string()),
+            },
+        )
+        .unwrap();
+
+        let active = resolve_active_theme(temp.path(), true);
+        assert!(!active.follow_system);
+        assert_eq!(active.theme_id, "ocean");
+    }
+
+    #[test]
+    fn test_read_user_theme_rejects_bad_id() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("t.json");
+        std::fs::write(&path, theme_json("Bad Id", "#0088ff")).unwrap();
+        assert!(read_user_theme(&path).is_err());
+    }
+}
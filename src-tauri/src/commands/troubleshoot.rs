@@ -0,0 +1,423 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Troubleshooting Commands
+//!
+//! Guided checks for common problems ("sync not working", "device link
+//! fails"). Each check runs against the real state — identity, relay URL,
+//! relay reachability, clock skew against the relay's `Date` header and the
+//! outbound queue — and reports a finding with a suggested fix and the
+//! context help screen (see `get_help_for_context`) that explains it.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use vauchi_core::storage::DeliveryStatus;
+
+use crate::clock;
+use crate::clock_skew;
+use crate::error::CommandError;
+use crate::error_stats;
+use crate::mock_relay;
+use crate::state::AppState;
+
+/// Relay connection attempts give up after this long.
+const RELAY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Relay round trips slower than this are reported as a warning.
+const SLOW_RELAY: Duration = Duration::from_secs(2);
+
+/// Clock differences beyond this break signature and expiry checks.
+pub(crate) const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+    /// Not run because an earlier check failed.
+    Skipped,
+}
+
+/// One step of a troubleshooter run.
+#[derive(Debug, Serialize)]
+pub struct TroubleshootStep {
+    pub check: String,
+    pub status: CheckStatus,
+    pub finding: String,
+    pub suggestion: Option<String>,
+    /// Screen ID for `get_help_for_context`.
+    pub help_screen: Option<String>,
+}
+
+/// Result of a troubleshooter run.
+#[derive(Debug, Serialize)]
+pub struct TroubleshootReport {
+    pub kind: String,
+    pub steps: Vec<TroubleshootStep>,
+    /// Whether any check found an error.
+    pub has_errors: bool,
+}
+
+impl TroubleshootStep {
+    fn new(check: &str, status: CheckStatus, finding: String) -> Self {
+        Self {
+            check: check.to_string(),
+            status,
+            finding,
+            suggestion: None,
+            help_screen: None,
+        }
+    }
+
+    fn suggest(mut self, suggestion: &str, help_screen: &str) -> Self {
+        self.suggestion = Some(suggestion.to_string());
+        self.help_screen = Some(help_screen.to_string());
+        self
+    }
+}
+
+/// Problems the troubleshooter knows about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TroubleshootKind {
+    Sync,
+    DeviceLink,
+}
+
+impl TroubleshootKind {
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "sync" => Some(Self::Sync),
+            "device_link" | "device-link" => Some(Self::DeviceLink),
+            _ => None,
+        }
+    }
+
+    fn help_screen(self) -> &'static str {
+        match self {
+            Self::Sync => "sync",
+            Self::DeviceLink => "device-link",
+        }
+    }
+}
+
+/// Relay reachability probe result.
+struct RelayProbe {
+    round_trip: Duration,
+    /// Relay time minus local time, from the handshake `Date` header.
+    clock_skew: Option<i64>,
+}
+
+/// Seconds the server clock is ahead of `local_now`, from an HTTP `Date` header.
+pub(crate) fn clock_skew_secs(date_header: &str, local_now: u64) -> Option<i64> {
+    let server = DateTime::parse_from_rfc2822(date_header.trim()).ok()?;
+    Some(server.timestamp() - local_now as i64)
+}
+
+/// Open (and drop) a WebSocket connection to the relay.
+async fn probe_relay(relay_url: &str) -> Result<RelayProbe, String> {
+    let start = Instant::now();
+    let (_, response) =
+        tokio::time::timeout(RELAY_TIMEOUT, tokio_tungstenite::connect_async(relay_url))
+            .await
+            .map_err(|_| "Connection timed out".to_string())?
+            .map_err(|e| e.to_string())?;
+    let round_trip = start.elapsed();
+
+    let clock_skew = response
+        .headers()
+        .get("date")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|date| clock_skew_secs(date, clock::now_secs()));
+    if let Some(skew) = clock_skew {
+        clock_skew::record(skew, "relay");
+    }
+    Ok(RelayProbe {
+        round_trip,
+        clock_skew,
+    })
+}
+
+/// Check the relay URL; returns it when it can be connected to.
+fn check_relay_url(relay_url: &str, help: &str) -> (TroubleshootStep, bool) {
+    let valid = url::Url::parse(relay_url)
+        .map(|u| matches!(u.scheme(), "ws" | "wss") && u.host().is_some())
+        .unwrap_or(false);
+    if valid {
+        let status = if relay_url.starts_with("ws://") {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        };
+        let mut step =
+            TroubleshootStep::new("relay_url", status, format!("Relay URL is {}", relay_url));
+        if status == CheckStatus::Warning {
+            step = step.suggest(
+                "Use a wss:// relay URL so the connection is encrypted.",
+                help,
+            );
+        }
+        (step, true)
+    } else {
+        let step = TroubleshootStep::new(
+            "relay_url",
+            CheckStatus::Error,
+            format!("Relay URL is not a WebSocket URL: {}", relay_url),
+        )
+        .suggest("Set a relay URL starting with wss:// in Settings.", help);
+        (step, false)
+    }
+}
+
+/// Steps for the relay connection and the clock, run without holding the state lock.
+async fn check_relay(relay_url: Option<&str>, help: &str) -> Vec<TroubleshootStep> {
+    let Some(relay_url) = relay_url else {
+        return vec![
+            TroubleshootStep::new(
+                "relay_reachable",
+                CheckStatus::Skipped,
+                "Skipped because the relay URL is invalid".to_string(),
+            ),
+            TroubleshootStep::new(
+                "clock_skew",
+                CheckStatus::Skipped,
+                "Skipped because the relay could not be contacted".to_string(),
+            ),
+        ];
+    };
+    if mock_relay::is_enabled() {
+        return vec![TroubleshootStep::new(
+            "relay_reachable",
+            CheckStatus::Ok,
+            "Using the in-process mock relay".to_string(),
+        )];
+    }
+
+    match probe_relay(relay_url).await {
+        Ok(probe) => {
+            let ms = probe.round_trip.as_millis();
+            let reachable = if probe.round_trip > SLOW_RELAY {
+                TroubleshootStep::new(
+                    "relay_reachable",
+                    CheckStatus::Warning,
+                    format!("Relay answered slowly ({} ms)", ms),
+                )
+                .suggest("Check your network connection; sync may time out.", help)
+            } else {
+                TroubleshootStep::new(
+                    "relay_reachable",
+                    CheckStatus::Ok,
+                    format!("Relay answered in {} ms", ms),
+                )
+            };
+            vec![reachable, clock_step(probe.clock_skew, help)]
+        }
+        Err(e) => vec![
+            TroubleshootStep::new(
+                "relay_reachable",
+                CheckStatus::Error,
+                format!("Could not connect to the relay: {}", e),
+            )
+            .suggest(
+                "Check your internet connection, firewall or proxy, and the relay URL.",
+                help,
+            ),
+            TroubleshootStep::new(
+                "clock_skew",
+                CheckStatus::Skipped,
+                "Skipped because the relay could not be contacted".to_string(),
+            ),
+        ],
+    }
+}
+
+fn clock_step(skew: Option<i64>, help: &str) -> TroubleshootStep {
+    match skew {
+        None => TroubleshootStep::new(
+            "clock_skew",
+            CheckStatus::Skipped,
+            "The relay did not report its time".to_string(),
+        ),
+        Some(skew) if skew.abs() > MAX_CLOCK_SKEW_SECS => TroubleshootStep::new(
+            "clock_skew",
+            CheckStatus::Error,
+            format!("Your clock is off by {} seconds", skew.abs()),
+        )
+        .suggest(
+            "Turn on automatic date and time in your system settings.",
+            help,
+        ),
+        Some(skew) => TroubleshootStep::new(
+            "clock_skew",
+            CheckStatus::Ok,
+            format!("Clock is within {} seconds of the relay", skew.abs()),
+        ),
+    }
+}
+
+/// Identity step. Joining as a new device needs no identity.
+fn identity_step(kind: TroubleshootKind, has_identity: bool, help: &str) -> TroubleshootStep {
+    match (has_identity, kind) {
+        (true, _) => TroubleshootStep::new(
+            "identity",
+            CheckStatus::Ok,
+            "An identity is set up on this device".to_string(),
+        ),
+        (false, TroubleshootKind::DeviceLink) => TroubleshootStep::new(
+            "identity",
+            CheckStatus::Warning,
+            "No identity on this device, so it can only join another device".to_string(),
+        )
+        .suggest(
+            "Start the link on the device that has your identity and scan its QR code here.",
+            help,
+        ),
+        (false, TroubleshootKind::Sync) => TroubleshootStep::new(
+            "identity",
+            CheckStatus::Error,
+            "No identity on this device; there is nothing to sync".to_string(),
+        )
+        .suggest(
+            "Create an identity or link this device to an existing one.",
+            help,
+        ),
+    }
+}
+
+/// Outbound queue step from pending updates and failed deliveries.
+fn queue_step(pending: usize, failed: usize, help: &str) -> TroubleshootStep {
+    if failed > 0 {
+        TroubleshootStep::new(
+            "outbound_queue",
+            CheckStatus::Warning,
+            format!("{} updates waiting, {} deliveries failed", pending, failed),
+        )
+        .suggest(
+            "Failed deliveries are retried automatically; sync to retry now.",
+            help,
+        )
+    } else if pending > 0 {
+        TroubleshootStep::new(
+            "outbound_queue",
+            CheckStatus::Warning,
+            format!("{} updates waiting to be sent", pending),
+        )
+        .suggest("Run a sync while online to send them.", help)
+    } else {
+        TroubleshootStep::new(
+            "outbound_queue",
+            CheckStatus::Ok,
+            "No updates waiting to be sent".to_string(),
+        )
+    }
+}
+
+/// Run the checks for a problem (`sync` or `device_link`) and return the
+/// findings in order.
+#[tauri::command]
+pub async fn run_troubleshooter(
+    kind: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<TroubleshootReport, CommandError> {
+    error_stats::track("run_troubleshooter", async {
+        let parsed = TroubleshootKind::parse(&kind)
+            .ok_or_else(|| CommandError::Validation(format!("Unknown troubleshooter: {}", kind)))?;
+        let help = parsed.help_screen();
+
+        // Gather local state first; the relay probe must not hold the lock
+        let (mut steps, relay_url, queue) = {
+            let state = state.lock().unwrap();
+            let has_identity = state.identity.is_some();
+            let (url_step, url_ok) = check_relay_url(state.relay_url(), help);
+            let queue = if parsed == TroubleshootKind::Sync && has_identity {
+                let pending: usize = state
+                    .storage
+                    .list_contacts()?
+                    .iter()
+                    .map(|c| {
+                        state
+                            .storage
+                            .get_pending_updates(c.id())
+                            .map(|p| p.len())
+                            .unwrap_or(0)
+                    })
+                    .sum();
+                let failed = state
+                    .storage
+                    .count_deliveries_by_status(&DeliveryStatus::Failed {
+                        reason: String::new(),
+                    })?;
+                Some(queue_step(pending, failed, help))
+            } else {
+                None
+            };
+            (
+                vec![identity_step(parsed, has_identity, help), url_step],
+                url_ok.then(|| state.relay_url().to_string()),
+                queue,
+            )
+        };
+
+        steps.extend(check_relay(relay_url.as_deref(), help).await);
+        steps.extend(queue);
+
+        Ok(TroubleshootReport {
+            kind,
+            has_errors: steps.iter().any(|s| s.status == CheckStatus::Error),
+            steps,
+        })
+    })
+    .await
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private check helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_skew_from_date_header() {
+        // 2026-01-01T00:00:00Z
+        let now = 1_767_225_600;
+        let header = "Thu, 01 Jan 2026 00:10:00 GMT";
+        assert_eq!(clock_skew_secs(header, now), Some(600));
+        assert_eq!(clock_skew_secs("not a date", now), None);
+
+        assert_eq!(clock_step(Some(600), "sync").status, CheckStatus::Error);
+        assert_eq!(clock_step(Some(-30), "sync").status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn test_relay_url_check() {
+        assert!(check_relay_url("wss://relay.vauchi.app", "sync").1);
+        let (step, ok) = check_relay_url("ws://localhost:8080", "sync");
+        assert!(ok);
+        assert_eq!(step.status, CheckStatus::Warning);
+        let (step, ok) = check_relay_url("https://relay.vauchi.app", "sync");
+        assert!(!ok);
+        assert_eq!(step.status, CheckStatus::Error);
+        assert_eq!(step.help_screen.as_deref(), Some("sync"));
+    }
+
+    #[test]
+    fn test_identity_step_depends_on_kind() {
+        let link = identity_step(TroubleshootKind::DeviceLink, false, "device-link");
+        assert_eq!(link.status, CheckStatus::Warning);
+        let sync = identity_step(TroubleshootKind::Sync, false, "sync");
+        assert_eq!(sync.status, CheckStatus::Error);
+    }
+
+    #[test]
+    fn test_queue_step() {
+        assert_eq!(queue_step(0, 0, "sync").status, CheckStatus::Ok);
+        let step = queue_step(3, 1, "sync");
+        assert_eq!(step.status, CheckStatus::Warning);
+        assert!(step.finding.contains("1 deliveries failed"));
+    }
+}
@@ -0,0 +1,78 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Trust Graph Commands
+//!
+//! Web-of-trust data for the frontend to render (see `trust_graph`), and
+//! mutual contact indicators for a single contact (see
+//! `mutual_indicators`).
+
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::error::CommandError;
+use crate::mutual_indicators::{self, MutualIndicators};
+use crate::recovery_policy;
+use crate::reverification;
+use crate::state::AppState;
+use crate::trust_graph::{GraphOwner, TrustGraph};
+
+/// Get the user's web of trust: contacts as nodes, validations, recovery
+/// vouching and fingerprint verification as edges.
+#[tauri::command]
+pub fn get_trust_graph(state: State<'_, Mutex<AppState>>) -> Result<TrustGraph, CommandError> {
+    let state = state.lock().unwrap();
+
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+
+    let own_field_ids: Vec<String> = state
+        .storage
+        .load_own_card()?
+        .map(|card| card.fields().iter().map(|f| f.id().to_string()).collect())
+        .unwrap_or_default();
+    let public_id = identity.public_id();
+    let validator_id = hex::encode(identity.signing_public_key());
+    let owner = GraphOwner {
+        public_id: &public_id,
+        validator_id: &validator_id,
+        display_name: state.display_name().unwrap_or(""),
+        own_field_ids: &own_field_ids,
+    };
+
+    let contacts = state.cached_contacts()?;
+    Ok(state.trust_graph().graph(&state.storage, &owner, &contacts))
+}
+
+/// Estimate how many of our contacts also know a contact, from local
+/// validations and recovery vouchers only.
+#[tauri::command]
+pub fn get_mutual_indicators(
+    contact_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<MutualIndicators, CommandError> {
+    let state = state.lock().unwrap();
+
+    let contact = state
+        .cached_contact(&contact_id)?
+        .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
+    let contacts = state.cached_contacts()?;
+    let voucher_ids = reverification::load(state.data_dir())
+        .into_iter()
+        .find(|entry| entry.contact_id == contact_id)
+        .map(|entry| entry.mutual_voucher_ids)
+        .unwrap_or_default();
+    let threshold = recovery_policy::load(state.data_dir()).verification_threshold;
+
+    Ok(mutual_indicators::indicators(
+        &state.storage,
+        &contact,
+        &contacts,
+        &voucher_ids,
+        threshold,
+    ))
+}
@@ -5,6 +5,11 @@
 //! Field Validation Commands
 //!
 //! Tauri IPC commands for crowd-sourced field validation.
+//!
+//! Validations are shared with the validated contact on the next sync and
+//! validations received from contacts are stored alongside (see
+//! `validation_sync`). Users can also ask a contact to validate one of
+//! their own fields; the contact accepts or declines the request.
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
@@ -13,8 +18,11 @@ use serde::Serialize;
 use tauri::State;
 use vauchi_core::{ProfileValidation, ValidationStatus};
 
+use crate::device_mode;
 use crate::error::CommandError;
 use crate::state::AppState;
+use crate::validation_freshness::{self, ValidationFreshness};
+use crate::validation_sync::{self, IncomingValidationRequest};
 
 /// Validation status information for the frontend.
 #[derive(Serialize, Clone, Debug)]
@@ -29,6 +37,9 @@ pub struct ValidationStatusInfo {
     pub validated_by_me: bool,
     /// Human-readable display text (e.g. "Verified by Bob and 2 others").
     pub display_text: String,
+    /// Number of validations older than the freshness limit. Only half of
+    /// them count towards the trust level.
+    pub stale_count: usize,
 }
 
 /// A single validation record for the frontend.
@@ -42,6 +53,8 @@ pub struct FieldValidationInfo {
 }
 
 /// Validate a contact's field (sign an attestation that the field value is correct).
+///
+/// The validation is sent to the contact on the next sync.
 #[tauri::command]
 pub fn validate_contact_field(
     contact_id: String,
@@ -49,8 +62,27 @@ pub fn validate_contact_field(
     field_value: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<FieldValidationInfo, CommandError> {
-    let state = state.lock().unwrap();
+    validate_contact_field_impl(&state.lock().unwrap(), &contact_id, &field_id, &field_value)
+}
 
+/// [`validate_contact_field`] on locked state, shared with the test server.
+pub(crate) fn validate_contact_field_impl(
+    state: &AppState,
+    contact_id: &str,
+    field_id: &str,
+    field_value: &str,
+) -> Result<FieldValidationInfo, CommandError> {
+    device_mode::ensure_editable(state)?;
+    create_validation(state, contact_id, field_id, field_value)
+}
+
+/// Sign and store a validation of a contact's field.
+fn create_validation(
+    state: &AppState,
+    contact_id: &str,
+    field_id: &str,
+    field_value: &str,
+) -> Result<FieldValidationInfo, CommandError> {
     let identity = state
         .identity
         .as_ref()
@@ -59,7 +91,7 @@ pub fn validate_contact_field(
     // Check sybil resistance — don't allow duplicate validations
     let existing = state
         .storage
-        .load_validations_for_field(&contact_id, &field_id)
+        .load_validations_for_field(contact_id, field_id)
         .map_err(|e| CommandError::Storage(e.to_string()))?;
 
     let my_id = hex::encode(identity.signing_public_key());
@@ -75,22 +107,26 @@ pub fn validate_contact_field(
     }
 
     // Create signed validation
-    let validation =
-        ProfileValidation::create_signed(identity, &field_id, &field_value, &contact_id);
+    let validation = ProfileValidation::create_signed(identity, field_id, field_value, contact_id);
 
     // Store it
     state
         .storage
         .save_validation(&validation)
         .map_err(|e| CommandError::Storage(e.to_string()))?;
+    state.trust_graph().invalidate(contact_id);
 
-    Ok(FieldValidationInfo {
+    Ok(validation_info(&validation))
+}
+
+pub(crate) fn validation_info(validation: &ProfileValidation) -> FieldValidationInfo {
+    FieldValidationInfo {
         contact_id: validation.contact_id().unwrap_or("").to_string(),
         field_name: validation.field_name().unwrap_or("").to_string(),
         field_value: validation.field_value().to_string(),
         validator_id: validation.validator_id().to_string(),
         validated_at: validation.validated_at(),
-    })
+    }
 }
 
 /// Get the validation status for a specific contact field.
@@ -102,42 +138,147 @@ pub fn get_field_validation_status(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<ValidationStatusInfo, CommandError> {
     let state = state.lock().unwrap();
-
+    let context = StatusContext::load(&state);
     let validations = state
         .storage
         .load_validations_for_field(&contact_id, &field_id)
         .map_err(|e| CommandError::Storage(e.to_string()))?;
+    Ok(field_status(&context, validations, &field_value))
+}
 
-    let my_id = state
-        .identity
-        .as_ref()
-        .map(|i| hex::encode(i.signing_public_key()));
+/// Validation status of one field of a contact's card.
+#[derive(Serialize, Clone, Debug)]
+pub struct FieldValidationSummary {
+    pub field_id: String,
+    pub field_label: String,
+    pub status: ValidationStatusInfo,
+}
 
-    let blocked = HashSet::new(); // TODO: load blocked contacts when blocking is implemented
+/// Get the validation status of every field on a contact's card at once.
+///
+/// Saves the contact page one IPC call per field. The contact's
+/// validations are read in one storage query, and the blocked set, names
+/// and freshness settings are loaded once for all fields.
+#[tauri::command]
+pub fn get_contact_validation_summary(
+    contact_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<FieldValidationSummary>, CommandError> {
+    let state = state.lock().unwrap();
+
+    let contact = state
+        .cached_contact(&contact_id)?
+        .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
+    let context = StatusContext::load(&state);
+    let mut by_field = validations_by_field(&state, &contact_id)?;
+
+    Ok(contact
+        .card()
+        .fields()
+        .iter()
+        .map(|field| {
+            let validations = by_field.remove(field.id()).unwrap_or_default();
+            FieldValidationSummary {
+                field_id: field.id().to_string(),
+                field_label: field.label().to_string(),
+                status: field_status(&context, validations, field.value()),
+            }
+        })
+        .collect())
+}
 
+/// All validations of a contact's fields, keyed by field ID.
+fn validations_by_field(
+    state: &AppState,
+    contact_id: &str,
+) -> Result<HashMap<String, Vec<ProfileValidation>>, CommandError> {
+    let validations = state
+        .storage
+        .load_validations_for_contact(contact_id)
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    let mut by_field: HashMap<String, Vec<ProfileValidation>> = HashMap::new();
+    for validation in validations {
+        if let Some(field) = validation.field_name() {
+            by_field
+                .entry(field.to_string())
+                .or_default()
+                .push(validation);
+        }
+    }
+    Ok(by_field)
+}
+
+/// Everything a status computation needs besides the field's validations.
+struct StatusContext {
+    my_id: Option<String>,
+    blocked: HashSet<String>,
+    known_names: HashMap<String, String>,
+    freshness: ValidationFreshness,
+    now: u64,
+}
+
+impl StatusContext {
+    fn load(state: &AppState) -> Self {
+        StatusContext {
+            my_id: state
+                .identity
+                .as_ref()
+                .map(|i| hex::encode(i.signing_public_key())),
+            blocked: blocked_validator_ids(state),
+            known_names: build_known_names_map(state),
+            freshness: validation_freshness::load(state.data_dir()),
+            now: crate::clock::now_secs(),
+        }
+    }
+}
+
+/// Compute the validation status of one field from its validations.
+fn field_status(
+    context: &StatusContext,
+    validations: Vec<ProfileValidation>,
+    field_value: &str,
+) -> ValidationStatusInfo {
+    let my_id = context.my_id.as_deref();
     let status =
-        ValidationStatus::from_validations(&validations, &field_value, my_id.as_deref(), &blocked);
+        ValidationStatus::from_validations(&validations, field_value, my_id, &context.blocked);
 
-    // Build known names map from contacts for display
-    let known_names = build_known_names_map(&state);
+    // Stale validations carry less weight in the trust level
+    let mut weighted = validations;
+    let stale_count =
+        validation_freshness::weigh_for_trust(&mut weighted, &context.freshness, context.now);
+    let trust_level =
+        ValidationStatus::from_validations(&weighted, field_value, my_id, &context.blocked)
+            .trust_level;
 
-    Ok(ValidationStatusInfo {
+    ValidationStatusInfo {
         count: status.count,
-        trust_level: status.trust_level.label().to_string(),
-        color: status.trust_level.color().to_string(),
+        trust_level: trust_level.label().to_string(),
+        color: trust_level.color().to_string(),
         validated_by_me: status.validated_by_me,
-        display_text: status.display(&known_names),
-    })
+        display_text: status.display(&context.known_names),
+        stale_count,
+    }
 }
 
 /// Revoke the current user's validation of a field.
+///
+/// The contact is sent a revocation on the next sync.
 #[tauri::command]
 pub fn revoke_field_validation(
     contact_id: String,
     field_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, CommandError> {
-    let state = state.lock().unwrap();
+    revoke_field_validation_impl(&state.lock().unwrap(), &contact_id, &field_id)
+}
+
+/// [`revoke_field_validation`] on locked state, shared with the test server.
+pub(crate) fn revoke_field_validation_impl(
+    state: &AppState,
+    contact_id: &str,
+    field_id: &str,
+) -> Result<bool, CommandError> {
+    device_mode::ensure_editable(state)?;
 
     let identity = state
         .identity
@@ -146,10 +287,21 @@ pub fn revoke_field_validation(
 
     let my_id = hex::encode(identity.signing_public_key());
 
-    state
+    let deleted = state
         .storage
-        .delete_validation(&contact_id, &field_id, &my_id)
-        .map_err(|e| CommandError::Storage(e.to_string()))
+        .delete_validation(contact_id, field_id, &my_id)
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+
+    // Tell the contact so the validation stops counting on their side too
+    if deleted {
+        state.trust_graph().invalidate(contact_id);
+        if let Err(e) =
+            validation_sync::queue_revocation(identity, &state.storage, contact_id, field_id)
+        {
+            tracing::warn!("Failed to queue validation revocation: {}", e);
+        }
+    }
+    Ok(deleted)
 }
 
 /// Get the validation count for a specific field.
@@ -174,8 +326,13 @@ pub fn get_field_validation_count(
 pub fn list_my_validations(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Vec<FieldValidationInfo>, CommandError> {
-    let state = state.lock().unwrap();
+    list_my_validations_impl(&state.lock().unwrap())
+}
 
+/// [`list_my_validations`] on locked state, shared with the test server.
+pub(crate) fn list_my_validations_impl(
+    state: &AppState,
+) -> Result<Vec<FieldValidationInfo>, CommandError> {
     let identity = state
         .identity
         .as_ref()
@@ -188,23 +345,333 @@ pub fn list_my_validations(
         .load_validations_by_validator(&my_id)
         .map_err(|e| CommandError::Storage(e.to_string()))?;
 
-    Ok(validations
+    Ok(validations.iter().map(validation_info).collect())
+}
+
+/// Get the validation freshness settings.
+#[tauri::command]
+pub fn get_validation_freshness(state: State<'_, Mutex<AppState>>) -> ValidationFreshness {
+    let state = state.lock().unwrap();
+    validation_freshness::load(state.data_dir())
+}
+
+/// Save the validation freshness settings.
+#[tauri::command]
+pub fn set_validation_freshness(
+    settings: ValidationFreshness,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    if settings.max_age_months == 0 {
+        return Err(CommandError::Validation(
+            "Freshness must be at least one month".to_string(),
+        ));
+    }
+    let state = state.lock().unwrap();
+    validation_freshness::save(state.data_dir(), &settings)
+        .map_err(|e| CommandError::Config(format!("Failed to save validation freshness: {}", e)))
+}
+
+/// List the current user's validations that are stale and worth re-confirming,
+/// oldest first.
+#[tauri::command]
+pub fn list_stale_validations(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<FieldValidationInfo>, CommandError> {
+    let state = state.lock().unwrap();
+
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+
+    let my_id = hex::encode(identity.signing_public_key());
+    let freshness = validation_freshness::load(state.data_dir());
+    let now = crate::clock::now_secs();
+
+    let mut stale: Vec<FieldValidationInfo> = state
+        .storage
+        .load_validations_by_validator(&my_id)
+        .map_err(|e| CommandError::Storage(e.to_string()))?
+        .iter()
+        .filter(|v| freshness.is_stale(v.validated_at(), now))
+        .map(validation_info)
+        .collect();
+    stale.sort_by_key(|v| v.validated_at);
+    Ok(stale)
+}
+
+/// Re-sign the current user's validation of a field with a new timestamp,
+/// for the value the contact's card shows now.
+///
+/// The refreshed validation replaces the old one here and, after the next
+/// sync, on the contact's side. Fails if the field is no longer on the
+/// card; the old validation is then kept.
+#[tauri::command]
+pub fn refresh_validation(
+    contact_id: String,
+    field_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<FieldValidationInfo, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    refresh_own_validation(&state, &contact_id, &field_id)
+}
+
+fn refresh_own_validation(
+    state: &AppState,
+    contact_id: &str,
+    field_id: &str,
+) -> Result<FieldValidationInfo, CommandError> {
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+
+    let my_id = hex::encode(identity.signing_public_key());
+    let validated = state
+        .storage
+        .load_validations_for_field(contact_id, field_id)
+        .map_err(|e| CommandError::Storage(e.to_string()))?
+        .iter()
+        .any(|v| v.validator_id() == my_id);
+    if !validated {
+        return Err(CommandError::Validation(
+            "You have not validated this field".to_string(),
+        ));
+    }
+
+    let contact = state
+        .cached_contact(contact_id)?
+        .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
+    let current_value = contact
+        .card()
+        .fields()
+        .iter()
+        .find(|f| f.id() == field_id)
+        .map(|f| f.value().to_string())
+        .ok_or_else(|| {
+            CommandError::Validation("The field is no longer on the contact's card".to_string())
+        })?;
+
+    state
+        .storage
+        .delete_validation(contact_id, field_id, &my_id)
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    create_validation(state, contact_id, field_id, &current_value)
+}
+
+/// Ask a contact to validate one of your own card fields.
+///
+/// The request is signed, encrypted with the contact's session and sent on
+/// the next sync. The contact must be able to see the field. Returns the
+/// request ID.
+#[tauri::command]
+pub fn request_field_validation(
+    contact_id: String,
+    field_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<String, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let identity = state
+        .identity
+        .as_ref()
+        .ok_or_else(|| CommandError::Identity("No identity found".to_string()))?;
+
+    let contact = state
+        .cached_contact(&contact_id)?
+        .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
+    if contact.is_blocked() {
+        return Err(CommandError::Contact("Contact is blocked".to_string()));
+    }
+
+    let card = state
+        .storage
+        .load_own_card()?
+        .ok_or_else(|| CommandError::Card("No card found".to_string()))?;
+    let field = card
+        .fields()
         .iter()
-        .map(|v| FieldValidationInfo {
-            contact_id: v.contact_id().unwrap_or("").to_string(),
-            field_name: v.field_name().unwrap_or("").to_string(),
-            field_value: v.field_value().to_string(),
-            validator_id: v.validator_id().to_string(),
-            validated_at: v.validated_at(),
+        .find(|f| f.id() == field_id)
+        .ok_or_else(|| CommandError::Card("Field not found".to_string()))?;
+
+    if !contact.visibility_rules().can_see(&field_id, &contact_id) {
+        return Err(CommandError::Validation(
+            "This contact cannot see the field".to_string(),
+        ));
+    }
+
+    validation_sync::queue_request(
+        identity,
+        &state.storage,
+        &contact_id,
+        &field_id,
+        field.label(),
+        field.value(),
+    )
+    .map_err(CommandError::Network)
+}
+
+/// List requests from contacts to validate their fields, oldest first.
+#[tauri::command]
+pub fn list_incoming_validation_requests(
+    state: State<'_, Mutex<AppState>>,
+) -> Vec<IncomingValidationRequest> {
+    let state = state.lock().unwrap();
+    validation_sync::load_requests(state.data_dir())
+}
+
+/// Accept a validation request: validate the field and remove the request.
+///
+/// Fails if the contact's card no longer shows the requested value, so only
+/// what the user can actually see gets vouched for.
+#[tauri::command]
+pub fn accept_validation_request(
+    request_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<FieldValidationInfo, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+
+    let request = validation_sync::load_requests(state.data_dir())
+        .into_iter()
+        .find(|r| r.request_id == request_id)
+        .ok_or_else(|| CommandError::Validation("Validation request not found".to_string()))?;
+
+    let contact = state
+        .cached_contact(&request.contact_id)?
+        .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
+    let matches_card = contact
+        .card()
+        .fields()
+        .iter()
+        .any(|f| f.id() == request.field_id && f.value() == request.field_value);
+    if !matches_card {
+        return Err(CommandError::Validation(
+            "The requested value does not match the contact's card".to_string(),
+        ));
+    }
+
+    let info = create_validation(
+        &state,
+        &request.contact_id,
+        &request.field_id,
+        &request.field_value,
+    )?;
+    validation_sync::take_request(state.data_dir(), &request_id)?;
+    Ok(info)
+}
+
+/// Decline a validation request. The contact is not notified.
+#[tauri::command]
+pub fn decline_validation_request(
+    request_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    validation_sync::take_request(state.data_dir(), &request_id)?
+        .map(|_| ())
+        .ok_or_else(|| CommandError::Validation("Validation request not found".to_string()))
+}
+
+/// Result of re-checking a stored validation's signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// Signed by the validator's known key.
+    Valid,
+    /// The signature does not match the record; it was altered.
+    Tampered,
+    /// The validator is neither us nor a known contact.
+    UnknownValidator,
+}
+
+/// A stored validation with its signature check.
+#[derive(Serialize, Clone, Debug)]
+pub struct VerifiedValidationInfo {
+    pub validation: FieldValidationInfo,
+    pub signature: SignatureStatus,
+}
+
+/// Re-verify the signatures of all stored validations of a contact's fields.
+///
+/// Each signature is checked against the public key we know for the
+/// validator (our own, or the one from the key exchange), never against key
+/// material carried in the record itself.
+#[tauri::command]
+pub fn verify_validation_signatures(
+    contact_id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<VerifiedValidationInfo>, CommandError> {
+    let state = state.lock().unwrap();
+
+    let contact = state
+        .cached_contact(&contact_id)?
+        .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
+    let known_keys = known_validator_keys(&state);
+
+    let mut result = Vec::new();
+    for field in contact.card().fields() {
+        let validations = state
+            .storage
+            .load_validations_for_field(&contact_id, field.id())
+            .map_err(|e| CommandError::Storage(e.to_string()))?;
+        result.extend(validations.iter().map(|v| VerifiedValidationInfo {
+            validation: validation_info(v),
+            signature: check_signature(v, &known_keys),
+        }));
+    }
+    Ok(result)
+}
+
+/// Public keys of everyone whose validations we can check, by validator ID.
+fn known_validator_keys(state: &AppState) -> HashMap<String, [u8; 32]> {
+    let mut keys = HashMap::new();
+    if let Some(identity) = state.identity.as_ref() {
+        let key = *identity.signing_public_key();
+        keys.insert(hex::encode(key), key);
+    }
+    if let Ok(contacts) = state.cached_contacts() {
+        for contact in contacts {
+            let key = *contact.public_key();
+            keys.insert(hex::encode(key), key);
+        }
+    }
+    keys
+}
+
+/// Check a validation's signature against the validator's known key.
+fn check_signature(
+    validation: &ProfileValidation,
+    known_keys: &HashMap<String, [u8; 32]>,
+) -> SignatureStatus {
+    match known_keys.get(validation.validator_id()) {
+        None => SignatureStatus::UnknownValidator,
+        Some(key) if validation.verify(key) => SignatureStatus::Valid,
+        Some(_) => SignatureStatus::Tampered,
+    }
+}
+
+/// Validator IDs (public keys) of blocked contacts, excluded from trust levels.
+fn blocked_validator_ids(state: &AppState) -> HashSet<String> {
+    state
+        .cached_contacts()
+        .map(|contacts| {
+            contacts
+                .iter()
+                .filter(|c| c.is_blocked())
+                .map(|c| hex::encode(c.public_key()))
+                .collect()
         })
-        .collect())
+        .unwrap_or_default()
 }
 
 /// Build a map of validator_id -> display_name from known contacts.
 fn build_known_names_map(state: &AppState) -> HashMap<String, String> {
     let mut names = HashMap::new();
 
-    if let Ok(contacts) = state.storage.list_contacts() {
+    if let Ok(contacts) = state.cached_contacts() {
         for contact in contacts {
             let pk_hex = hex::encode(contact.public_key());
             names.insert(pk_hex, contact.display_name().to_string());
@@ -224,7 +691,7 @@ fn build_known_names_map(state: &AppState) -> HashMap<String, String> {
 mod tests {
     use super::*;
     use tempfile::TempDir;
-    use vauchi_core::TrustLevel;
+    use vauchi_core::{Contact, ContactCard, ContactField, FieldType, SymmetricKey, TrustLevel};
 
     fn create_test_state() -> (AppState, TempDir) {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -398,4 +865,165 @@ mod tests {
         // No contacts added, so map should be empty
         assert!(names.is_empty());
     }
+
+    // @scenario: field_validation:User validates a contact field
+    #[test]
+    fn test_create_validation_rejects_duplicate() {
+        let (state, _temp) = create_state_with_identity();
+        create_validation(&state, "contact-1", "email", "bob@example.com").unwrap();
+        assert!(matches!(
+            create_validation(&state, "contact-1", "email", "bob@example.com"),
+            Err(CommandError::Validation(_))
+        ));
+    }
+
+    // @scenario: field_validation:Validation trust levels
+    #[test]
+    fn test_blocked_validator_ids_lists_only_blocked_contacts() {
+        let (state, _temp) = create_state_with_identity();
+        let mut blocked = Contact::from_exchange(
+            [7u8; 32],
+            ContactCard::new("Mallory"),
+            SymmetricKey::generate(),
+        );
+        blocked.block();
+        let friend =
+            Contact::from_exchange([8u8; 32], ContactCard::new("Bob"), SymmetricKey::generate());
+        state.save_contact(&blocked).unwrap();
+        state.save_contact(&friend).unwrap();
+
+        let ids = blocked_validator_ids(&state);
+        assert_eq!(ids.len(), 1);
+        assert!(ids.contains(&hex::encode([7u8; 32])));
+    }
+
+    // @scenario: field_validation:Validation trust levels
+    #[test]
+    fn test_blocked_validators_excluded_from_trust_level() {
+        let (state, _temp) = create_state_with_identity();
+        let identity = state.identity.as_ref().unwrap();
+        let validator_id = hex::encode(identity.signing_public_key());
+
+        let validation =
+            ProfileValidation::create_signed(identity, "email", "bob@example.com", "contact-1");
+        state.storage.save_validation(&validation).unwrap();
+        let validations = state
+            .storage
+            .load_validations_for_field("contact-1", "email")
+            .unwrap();
+
+        let status = ValidationStatus::from_validations(
+            &validations,
+            "bob@example.com",
+            None,
+            &HashSet::new(),
+        );
+        assert_eq!(status.trust_level, TrustLevel::LowConfidence);
+
+        let blocked: HashSet<String> = [validator_id].into_iter().collect();
+        let status =
+            ValidationStatus::from_validations(&validations, "bob@example.com", None, &blocked);
+        assert_eq!(status.trust_level, TrustLevel::Unverified);
+    }
+
+    // @scenario: field_validation:Validation trust levels
+    #[test]
+    fn test_field_status_counts_own_validation() {
+        let (state, _temp) = create_state_with_identity();
+        let identity = state.identity.as_ref().unwrap();
+        let validation =
+            ProfileValidation::create_signed(identity, "email", "bob@example.com", "contact-1");
+        state.storage.save_validation(&validation).unwrap();
+
+        let context = StatusContext::load(&state);
+        let mut by_field = validations_by_field(&state, "contact-1").unwrap();
+        let status = field_status(
+            &context,
+            by_field.remove("email").unwrap_or_default(),
+            "bob@example.com",
+        );
+        assert_eq!(status.count, 1);
+        assert!(status.validated_by_me);
+        assert_eq!(status.stale_count, 0);
+
+        let other = field_status(&context, by_field.remove("phone").unwrap_or_default(), "+1");
+        assert_eq!(other.count, 0);
+    }
+
+    #[test]
+    fn test_validations_grouped_by_field_of_one_contact() {
+        let (state, _temp) = create_state_with_identity();
+        let identity = state.identity.as_ref().unwrap();
+        for (field, value, contact) in [
+            ("email", "bob@example.com", "contact-1"),
+            ("phone", "+41 44 123 45 67", "contact-1"),
+            ("email", "carol@example.com", "contact-2"),
+        ] {
+            let validation = ProfileValidation::create_signed(identity, field, value, contact);
+            state.storage.save_validation(&validation).unwrap();
+        }
+
+        let by_field = validations_by_field(&state, "contact-1").unwrap();
+        assert_eq!(by_field.len(), 2);
+        assert_eq!(by_field["email"].len(), 1);
+        assert_eq!(by_field["email"][0].field_value(), "bob@example.com");
+        assert_eq!(by_field["phone"].len(), 1);
+    }
+
+    // @scenario: field_validation:User validates a contact field
+    #[test]
+    fn test_check_signature_against_known_keys() {
+        let (state, _temp) = create_state_with_identity();
+        let identity = state.identity.as_ref().unwrap();
+        let validation =
+            ProfileValidation::create_signed(identity, "email", "bob@example.com", "contact-1");
+
+        let known = known_validator_keys(&state);
+        assert_eq!(check_signature(&validation, &known), SignatureStatus::Valid);
+        assert_eq!(
+            check_signature(&validation, &HashMap::new()),
+            SignatureStatus::UnknownValidator
+        );
+
+        // Our ID mapped to a different key: the signature cannot match
+        let wrong: HashMap<String, [u8; 32]> = [(validation.validator_id().to_string(), [9u8; 32])]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            check_signature(&validation, &wrong),
+            SignatureStatus::Tampered
+        );
+    }
+
+    #[test]
+    fn test_refresh_requires_and_replaces_own_validation() {
+        let (state, _temp) = create_state_with_identity();
+        let mut card = ContactCard::new("Bob");
+        card.add_field(ContactField::new(
+            FieldType::Email,
+            "email",
+            "bob@example.com",
+        ))
+        .unwrap();
+        let bob = Contact::from_exchange([8u8; 32], card, SymmetricKey::generate());
+        let field_id = bob.card().fields()[0].id().to_string();
+        state.save_contact(&bob).unwrap();
+
+        assert!(matches!(
+            refresh_own_validation(&state, bob.id(), &field_id),
+            Err(CommandError::Validation(_))
+        ));
+
+        // Validated before Bob changed his address
+        create_validation(&state, bob.id(), &field_id, "old@example.com").unwrap();
+        let refreshed = refresh_own_validation(&state, bob.id(), &field_id).unwrap();
+        assert_eq!(refreshed.field_value, "bob@example.com");
+        assert_eq!(
+            state
+                .storage
+                .count_validations_for_field(bob.id(), &field_id)
+                .unwrap(),
+            1
+        );
+    }
 }
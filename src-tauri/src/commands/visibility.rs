@@ -4,17 +4,22 @@
 
 //! Visibility Commands
 //!
-//! Commands for managing contact card field visibility.
+//! Commands for managing contact card field visibility, and an audit of
+//! risky visibility configurations.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use vauchi_core::contact::FieldVisibility;
+use vauchi_core::contact_card::FieldType;
 
+use crate::commands::labels;
+use crate::device_mode;
 use crate::error::CommandError;
 use crate::state::AppState;
+use crate::storage_worker;
 
 /// Visibility level for a field (frontend-friendly).
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,12 +58,17 @@ pub fn get_visibility_rules(
     contact_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Vec<FieldVisibilityInfo>, CommandError> {
-    let state = state.lock().unwrap();
+    get_visibility_rules_impl(&state.lock().unwrap(), &contact_id)
+}
 
+/// [`get_visibility_rules`] on locked state, shared with the test server.
+pub(crate) fn get_visibility_rules_impl(
+    state: &AppState,
+    contact_id: &str,
+) -> Result<Vec<FieldVisibilityInfo>, CommandError> {
     // Load the specific contact
     let contact = state
-        .storage
-        .load_contact(&contact_id)?
+        .cached_contact(contact_id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     let rules = contact.visibility_rules();
@@ -69,7 +79,7 @@ pub fn get_visibility_rules(
         for field in card.fields() {
             let field_id = field.id().to_string();
             let visibility = rules.get(&field_id);
-            let can_see = rules.can_see(&field_id, &contact_id);
+            let can_see = rules.can_see(&field_id, contact_id);
 
             result.push(FieldVisibilityInfo {
                 field_id,
@@ -92,27 +102,35 @@ pub fn set_field_visibility(
     visibility: VisibilityLevel,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<(), CommandError> {
-    let state = state.lock().unwrap();
+    set_field_visibility_impl(&state.lock().unwrap(), &contact_id, &field_id, visibility)
+}
+
+/// [`set_field_visibility`] on locked state, shared with the test server.
+pub(crate) fn set_field_visibility_impl(
+    state: &AppState,
+    contact_id: &str,
+    field_id: &str,
+    visibility: VisibilityLevel,
+) -> Result<(), CommandError> {
+    device_mode::ensure_editable(state)?;
 
     // Load the contact
     let mut contact = state
-        .storage
-        .load_contact(&contact_id)?
+        .cached_contact(contact_id)?
         .ok_or_else(|| CommandError::Contact("Contact not found".to_string()))?;
 
     // Update visibility rules
     let rules = contact.visibility_rules_mut();
     match visibility {
-        VisibilityLevel::Everyone => rules.set_everyone(&field_id),
-        VisibilityLevel::Nobody => rules.set_nobody(&field_id),
+        VisibilityLevel::Everyone => rules.set_everyone(field_id),
+        VisibilityLevel::Nobody => rules.set_nobody(field_id),
         VisibilityLevel::Contacts { ids } => {
-            rules.set_contacts(&field_id, ids.into_iter().collect::<HashSet<_>>())
+            rules.set_contacts(field_id, ids.into_iter().collect::<HashSet<_>>())
         }
     }
 
     // Save the updated contact
     state
-        .storage
         .save_contact(&contact)
         .map_err(|e| CommandError::Contact(format!("Failed to save contact: {:?}", e)))?;
 
@@ -126,7 +144,7 @@ pub fn get_contacts_for_visibility(
 ) -> Result<Vec<ContactOption>, CommandError> {
     let state = state.lock().unwrap();
 
-    let contacts = state.storage.list_contacts()?;
+    let contacts = state.cached_contacts()?;
 
     Ok(contacts
         .into_iter()
@@ -158,15 +176,21 @@ pub fn get_field_viewers(
     field_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Vec<ContactFieldVisibility>, CommandError> {
-    let state = state.lock().unwrap();
+    get_field_viewers_impl(&state.lock().unwrap(), &field_id)
+}
 
-    let contacts = state.storage.list_contacts()?;
+/// [`get_field_viewers`] on locked state, shared with the test server.
+pub(crate) fn get_field_viewers_impl(
+    state: &AppState,
+    field_id: &str,
+) -> Result<Vec<ContactFieldVisibility>, CommandError> {
+    let contacts = state.cached_contacts()?;
 
     let mut result = Vec::new();
     for contact in contacts {
         let contact_id = contact.id().to_string();
         let rules = contact.visibility_rules();
-        let can_see = rules.can_see(&field_id, &contact_id);
+        let can_see = rules.can_see(field_id, &contact_id);
 
         result.push(ContactFieldVisibility {
             contact_id,
@@ -177,3 +201,226 @@ pub fn get_field_viewers(
 
     Ok(result)
 }
+
+/// Make a field visible to exactly `contact_ids` and hidden from every
+/// other contact, in one transaction. Returns how many contacts changed.
+#[tauri::command]
+pub fn set_field_viewers(
+    field_id: String,
+    contact_ids: Vec<String>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<u32, CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+
+    let viewers: HashSet<String> = contact_ids.into_iter().collect();
+    let updated = storage_worker::in_transaction(&state.storage, |storage| {
+        let contacts = storage.list_contacts()?;
+        if let Some(unknown) = viewers
+            .iter()
+            .find(|id| !contacts.iter().any(|c| c.id() == id.as_str()))
+        {
+            return Err(CommandError::Contact(format!(
+                "Contact not found: {}",
+                unknown
+            )));
+        }
+
+        let mut updated = 0;
+        for mut contact in contacts {
+            let contact_id = contact.id().to_string();
+            let visible = viewers.contains(&contact_id);
+            let rules = contact.visibility_rules_mut();
+            if rules.can_see(&field_id, &contact_id) == visible {
+                continue;
+            }
+            if visible {
+                rules.set_everyone(&field_id);
+            } else {
+                rules.set_nobody(&field_id);
+            }
+            storage.save_contact(&contact)?;
+            updated += 1;
+        }
+        Ok(updated)
+    })?;
+
+    state.invalidate_contact_cache();
+    Ok(updated)
+}
+
+/// A field that contacts' rules leave visible to everyone.
+#[derive(Serialize)]
+pub struct PublicField {
+    pub field_id: String,
+    pub field_label: String,
+    /// Contacts whose rules for this field are `Everyone`.
+    pub contact_count: u32,
+}
+
+/// An unverified contact that can see sensitive fields.
+#[derive(Serialize)]
+pub struct UnverifiedViewer {
+    pub contact_id: String,
+    pub display_name: String,
+    pub field_ids: Vec<String>,
+}
+
+/// A label rule for a field that is no longer on the card.
+#[derive(Serialize)]
+pub struct OrphanedLabelField {
+    pub label_id: String,
+    pub label_name: String,
+    pub field_id: String,
+}
+
+/// A contact's override or rule for a field that is no longer on the card.
+#[derive(Serialize)]
+pub struct OrphanedContactField {
+    pub contact_id: String,
+    pub display_name: String,
+    pub field_id: String,
+    /// Whether it is a per-contact override rather than a visibility rule.
+    pub is_override: bool,
+}
+
+/// Risky visibility configurations.
+#[derive(Serialize)]
+pub struct VisibilityAudit {
+    pub public_fields: Vec<PublicField>,
+    pub unverified_sensitive_viewers: Vec<UnverifiedViewer>,
+    pub orphaned_label_fields: Vec<OrphanedLabelField>,
+    pub orphaned_contact_fields: Vec<OrphanedContactField>,
+}
+
+/// Scan fields, contacts' rules and overrides, and labels for risky
+/// configurations: fields visible to everyone, address or phone fields
+/// visible to contacts whose fingerprint is unverified, and label rules,
+/// contact rules and overrides for deleted fields. A per-contact override
+/// takes precedence over the contact's rule.
+#[tauri::command]
+pub fn audit_visibility(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<VisibilityAudit, CommandError> {
+    let state = state.lock().unwrap();
+
+    let card = state
+        .get_card()
+        .map_err(|e| CommandError::Card(e.to_string()))?;
+    let fields = card.as_ref().map(|c| c.fields()).unwrap_or_default();
+    let contacts = state.cached_contacts()?;
+    let overrides: HashMap<String, HashMap<String, bool>> = contacts
+        .iter()
+        .map(|c| {
+            Ok((
+                c.id().to_string(),
+                labels::contact_overrides(&state.storage, c.id())?,
+            ))
+        })
+        .collect::<Result<_, CommandError>>()?;
+    let override_for = |contact_id: &str, field_id: &str| {
+        overrides
+            .get(contact_id)
+            .and_then(|o| o.get(field_id))
+            .copied()
+    };
+
+    let public_fields = fields
+        .iter()
+        .filter_map(|field| {
+            let field_id = field.id().to_string();
+            let contact_count = contacts
+                .iter()
+                .filter(|c| {
+                    override_for(c.id(), &field_id) != Some(false)
+                        && matches!(
+                            c.visibility_rules().get(&field_id),
+                            FieldVisibility::Everyone
+                        )
+                })
+                .count() as u32;
+            (contact_count > 0).then(|| PublicField {
+                field_id,
+                field_label: field.label().to_string(),
+                contact_count,
+            })
+        })
+        .collect();
+
+    let mut unverified_sensitive_viewers: Vec<UnverifiedViewer> = contacts
+        .iter()
+        .filter(|c| !c.is_fingerprint_verified())
+        .filter_map(|contact| {
+            let rules = contact.visibility_rules();
+            let field_ids: Vec<String> = fields
+                .iter()
+                // Where someone lives or how to reach them
+                .filter(|f| matches!(f.field_type(), FieldType::Address | FieldType::Phone))
+                .map(|f| f.id().to_string())
+                .filter(|id| {
+                    override_for(contact.id(), id)
+                        .unwrap_or_else(|| rules.can_see(id, contact.id()))
+                })
+                .collect();
+            (!field_ids.is_empty()).then(|| UnverifiedViewer {
+                contact_id: contact.id().to_string(),
+                display_name: contact.display_name().to_string(),
+                field_ids,
+            })
+        })
+        .collect();
+    unverified_sensitive_viewers.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+    let field_ids: HashSet<String> = fields.iter().map(|f| f.id().to_string()).collect();
+    let labels = state
+        .storage
+        .load_all_labels()
+        .map_err(|e| CommandError::Storage(format!("Failed to load labels: {:?}", e)))?;
+    let orphaned_label_fields = labels
+        .iter()
+        .flat_map(|label| {
+            label
+                .visible_fields()
+                .iter()
+                .filter(|id| !field_ids.contains(*id))
+                .map(|id| OrphanedLabelField {
+                    label_id: label.id().to_string(),
+                    label_name: label.name().to_string(),
+                    field_id: id.clone(),
+                })
+        })
+        .collect();
+
+    let mut orphaned_contact_fields = Vec::new();
+    for contact in &contacts {
+        let contact_overrides = overrides.get(contact.id());
+        let orphaned_overrides = contact_overrides
+            .into_iter()
+            .flat_map(|o| o.keys())
+            .filter(|id| !field_ids.contains(*id))
+            .map(|id| (id.clone(), true));
+        let orphaned_rules = contact
+            .visibility_rules()
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|id| !field_ids.contains(*id))
+            .map(|id| (id.clone(), false));
+        for (field_id, is_override) in orphaned_overrides.chain(orphaned_rules) {
+            orphaned_contact_fields.push(OrphanedContactField {
+                contact_id: contact.id().to_string(),
+                display_name: contact.display_name().to_string(),
+                field_id,
+                is_override,
+            });
+        }
+    }
+    orphaned_contact_fields
+        .sort_by(|a, b| (&a.display_name, &a.field_id).cmp(&(&b.display_name, &b.field_id)));
+
+    Ok(VisibilityAudit {
+        public_fields,
+        unverified_sensitive_viewers,
+        orphaned_label_fields,
+        orphaned_contact_fields,
+    })
+}
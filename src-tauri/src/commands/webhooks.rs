@@ -0,0 +1,151 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Webhook Commands
+//!
+//! Commands for managing outbound webhooks for local automations. Signing
+//! secrets are generated when a webhook is added; scripts use them to
+//! verify the payload signature.
+
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::clock;
+use crate::commands::app_update;
+use crate::device_mode;
+use crate::error::CommandError;
+use crate::error_stats;
+use crate::events::AppEvent;
+use crate::state::AppState;
+use crate::webhooks::{self, Webhook, WebhookConfig, WebhookEvent};
+
+fn load(state: &AppState) -> Result<WebhookConfig, CommandError> {
+    webhooks::load(state.data_dir())
+        .map_err(|e| CommandError::Storage(format!("Failed to load webhooks: {}", e)))
+}
+
+fn save(state: &AppState, config: &WebhookConfig) -> Result<(), CommandError> {
+    webhooks::save(state.data_dir(), config)
+        .map_err(|e| CommandError::Config(format!("Failed to save webhooks: {}", e)))
+}
+
+/// Get all webhooks and the remote target policy.
+#[tauri::command]
+pub fn get_webhooks(state: State<'_, Mutex<AppState>>) -> Result<WebhookConfig, CommandError> {
+    let state = state.lock().unwrap();
+    load(&state)
+}
+
+/// Add a webhook for `events`, with a generated signing secret.
+#[tauri::command]
+pub fn add_webhook(
+    url: String,
+    events: Vec<WebhookEvent>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Webhook, CommandError> {
+    if events.is_empty() {
+        return Err(CommandError::Validation(
+            "Select at least one event".to_string(),
+        ));
+    }
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let mut config = load(&state)?;
+    webhooks::validate_url(&url, config.allow_remote).map_err(CommandError::Validation)?;
+
+    let webhook = Webhook {
+        id: webhooks::random_token(),
+        url,
+        events,
+        secret: webhooks::random_token(),
+        enabled: true,
+    };
+    config.webhooks.push(webhook.clone());
+    save(&state, &config)?;
+    Ok(webhook)
+}
+
+/// Remove a webhook.
+#[tauri::command]
+pub fn remove_webhook(id: String, state: State<'_, Mutex<AppState>>) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let mut config = load(&state)?;
+    let before = config.webhooks.len();
+    config.webhooks.retain(|w| w.id != id);
+    if config.webhooks.len() == before {
+        return Err(CommandError::Config("Webhook not found".to_string()));
+    }
+    save(&state, &config)
+}
+
+/// Pause or resume a webhook.
+#[tauri::command]
+pub fn set_webhook_enabled(
+    id: String,
+    enabled: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let mut config = load(&state)?;
+    let webhook = config
+        .webhooks
+        .iter_mut()
+        .find(|w| w.id == id)
+        .ok_or_else(|| CommandError::Config("Webhook not found".to_string()))?;
+    webhook.enabled = enabled;
+    save(&state, &config)
+}
+
+/// Allow or forbid webhook URLs outside `localhost`. Remote webhooks stop
+/// firing while forbidden.
+#[tauri::command]
+pub fn set_webhooks_allow_remote(
+    allow: bool,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    device_mode::ensure_editable(&state)?;
+    let mut config = load(&state)?;
+    config.allow_remote = allow;
+    save(&state, &config)
+}
+
+/// Send a signed `sync_finished` sample payload to a webhook now.
+#[tauri::command]
+pub async fn test_webhook(
+    id: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    error_stats::track("test_webhook", async {
+        let (config, http_proxy) = {
+            let state = state.lock().unwrap();
+            (load(&state)?, app_update::http_proxy(&state))
+        };
+        let webhook = config
+            .webhooks
+            .iter()
+            .find(|w| w.id == id)
+            .ok_or_else(|| CommandError::Config("Webhook not found".to_string()))?;
+        webhooks::validate_url(&webhook.url, config.allow_remote)
+            .map_err(CommandError::Validation)?;
+        let proxy =
+            webhooks::delivery_proxy(&webhook.url, &http_proxy).map_err(CommandError::Privacy)?;
+
+        let sample = AppEvent::SyncCompleted {
+            success: true,
+            contacts_added: 0,
+            cards_updated: 0,
+            updates_sent: 0,
+            error: None,
+        };
+        let body = webhooks::payload(WebhookEvent::SyncFinished, &sample, clock::now_secs());
+        webhooks::deliver(webhook, body, proxy.as_ref())
+            .await
+            .map_err(|e| CommandError::Network(format!("Webhook delivery failed: {}", e)))
+    })
+    .await
+}
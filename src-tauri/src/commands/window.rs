@@ -0,0 +1,161 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Window Commands
+//!
+//! Close and minimize behavior of the main window, and the always-on-top
+//! QR window used to show an exchange or device-link QR on another screen.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::error::CommandError;
+use crate::error_stats;
+use crate::state::AppState;
+use crate::window_behavior::{self, WindowBehavior};
+
+/// Get the window close/minimize behavior.
+#[tauri::command]
+pub fn get_window_behavior(state: State<'_, Mutex<AppState>>) -> WindowBehavior {
+    let state = state.lock().unwrap();
+    window_behavior::load(state.data_dir())
+}
+
+/// Save the window close/minimize behavior.
+#[tauri::command]
+pub fn set_window_behavior(
+    behavior: WindowBehavior,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    let state = state.lock().unwrap();
+    window_behavior::save(state.data_dir(), &behavior)
+        .map_err(|e| CommandError::Config(format!("Failed to save window behavior: {}", e)))
+}
+
+/// Label of the secondary QR window.
+const QR_WINDOW_LABEL: &str = "qr";
+
+/// Event sent to an already open QR window when the kind changes.
+const QR_WINDOW_KIND_EVENT: &str = "qr-window-kind";
+
+/// QR shown in the secondary window.
+#[derive(Serialize)]
+pub struct QrWindowContent {
+    /// `exchange` or `device_link`.
+    pub kind: String,
+    /// QR data string to render.
+    pub data: String,
+}
+
+/// Current QR data for a window kind, from the flow already in progress.
+fn current_qr_data(state: &AppState, kind: &str) -> Result<String, CommandError> {
+    match kind {
+        "exchange" => state
+            .exchange_session
+            .as_ref()
+            .and_then(|session| session.qr())
+            .map(|qr| qr.to_data_string())
+            .ok_or_else(|| CommandError::Exchange("No exchange in progress".to_string())),
+        "device_link" => state
+            .pending_device_link_qr
+            .as_ref()
+            .map(|qr| qr.expose().to_string())
+            .ok_or_else(|| CommandError::Device("No device link in progress".to_string())),
+        other => Err(CommandError::Validation(format!(
+            "Unknown QR window kind: {}",
+            other
+        ))),
+    }
+}
+
+/// Open a small frameless, always-on-top window showing the current
+/// exchange (`kind = "exchange"`) or device-link (`kind = "device_link"`) QR.
+///
+/// The flow must already be started in the main window; the QR window only
+/// displays it. Calling again while the window is open switches its kind.
+#[tauri::command]
+pub async fn show_qr_window(
+    kind: String,
+    app: AppHandle,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), CommandError> {
+    error_stats::track("show_qr_window", async {
+        // Fail early if there is nothing to show
+        current_qr_data(&state.lock().unwrap(), &kind)?;
+
+        if let Some(window) = app.get_webview_window(QR_WINDOW_LABEL) {
+            window
+                .emit(QR_WINDOW_KIND_EVENT, &kind)
+                .map_err(|e| CommandError::Config(e.to_string()))?;
+            let _ = window.show();
+            let _ = window.set_focus();
+            return Ok(());
+        }
+
+        // Match the main window's UI scale, growing the fixed window to fit
+        let scale = crate::accessibility::load_ui_scale(state.lock().unwrap().data_dir());
+        let url = format!("index.html?window=qr&kind={}", kind);
+        let window = WebviewWindowBuilder::new(&app, QR_WINDOW_LABEL, WebviewUrl::App(url.into()))
+            .title("Vauchi QR")
+            .inner_size(320.0 * scale, 360.0 * scale)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .build()
+            .map_err(|e| CommandError::Config(format!("Failed to open QR window: {}", e)))?;
+        if let Err(e) = window.set_zoom(scale) {
+            tracing::warn!("Failed to set QR window zoom: {}", e);
+        }
+
+        Ok(())
+    })
+    .await
+}
+
+/// Get the QR for the secondary window.
+#[tauri::command]
+pub fn get_qr_window_content(
+    kind: String,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<QrWindowContent, CommandError> {
+    let state = state.lock().unwrap();
+    let data = current_qr_data(&state, &kind)?;
+    Ok(QrWindowContent { kind, data })
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private current_qr_data helper
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_qr_data_requires_flow_in_progress() {
+        let temp = TempDir::new().unwrap();
+        let state = AppState::new(temp.path()).unwrap();
+        assert!(matches!(
+            current_qr_data(&state, "exchange"),
+            Err(CommandError::Exchange(_))
+        ));
+        assert!(matches!(
+            current_qr_data(&state, "device_link"),
+            Err(CommandError::Device(_))
+        ));
+    }
+
+    #[test]
+    fn test_qr_data_device_link_and_unknown_kind() {
+        let temp = TempDir::new().unwrap();
+        let mut state = AppState::new(temp.path()).unwrap();
+        state.pending_device_link_qr = Some("link-data".into());
+        assert_eq!(current_qr_data(&state, "device_link").unwrap(), "link-data");
+        assert!(matches!(
+            current_qr_data(&state, "contact"),
+            Err(CommandError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! In-Memory Contact Cache
+//!
+//! Keeps decrypted contacts in memory so read-heavy commands (visibility
+//! rules, field viewers, validation name lookups) don't hit SQLite and
+//! re-decrypt every contact on each call.
+//!
+//! The cache is owned by `AppState` and invalidated whenever contacts are
+//! saved, deleted, or replaced by a relay sync.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use vauchi_core::Contact;
+
+/// Cache of contacts keyed by contact ID.
+///
+/// Uses interior mutability so read paths that only hold `&AppState` can
+/// still populate the cache on a miss.
+#[derive(Default)]
+pub struct ContactCache {
+    /// Individually loaded contacts, keyed by contact ID.
+    by_id: RefCell<HashMap<String, Contact>>,
+    /// Full contact list in storage order, if it has been loaded.
+    all: RefCell<Option<Vec<Contact>>>,
+}
+
+impl ContactCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a cached contact by ID.
+    pub fn get(&self, id: &str) -> Option<Contact> {
+        if let Some(contact) = self.by_id.borrow().get(id) {
+            return Some(contact.clone());
+        }
+        self.all
+            .borrow()
+            .as_ref()
+            .and_then(|all| all.iter().find(|c| c.id() == id).cloned())
+    }
+
+    /// Get the cached full contact list, if loaded.
+    pub fn list(&self) -> Option<Vec<Contact>> {
+        self.all.borrow().clone()
+    }
+
+    /// Store a single contact (after a load or a write-through save).
+    ///
+    /// The full list is dropped because the contact's position in storage
+    /// order may have changed.
+    pub fn put(&self, contact: &Contact) {
+        self.by_id
+            .borrow_mut()
+            .insert(contact.id().to_string(), contact.clone());
+        *self.all.borrow_mut() = None;
+    }
+
+    /// Store the full contact list, replacing any individual entries.
+    pub fn fill(&self, contacts: &[Contact]) {
+        let mut by_id = self.by_id.borrow_mut();
+        by_id.clear();
+        for contact in contacts {
+            by_id.insert(contact.id().to_string(), contact.clone());
+        }
+        *self.all.borrow_mut() = Some(contacts.to_vec());
+    }
+
+    /// Drop a single contact from the cache.
+    pub fn invalidate(&self, id: &str) {
+        self.by_id.borrow_mut().remove(id);
+        *self.all.borrow_mut() = None;
+    }
+
+    /// Drop everything (e.g. after a sync wrote to storage directly).
+    pub fn clear(&self) {
+        self.by_id.borrow_mut().clear();
+        *self.all.borrow_mut() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vauchi_core::{ContactCard, SymmetricKey};
+
+    fn contact(key_byte: u8, name: &str) -> Contact {
+        Contact::from_exchange(
+            [key_byte; 32],
+            ContactCard::new(name),
+            SymmetricKey::generate(),
+        )
+    }
+
+    #[test]
+    fn test_empty_cache_misses() {
+        let cache = ContactCache::new();
+        assert!(cache.get("missing").is_none());
+        assert!(cache.list().is_none());
+    }
+
+    #[test]
+    fn test_fill_serves_list_and_single_lookups() {
+        let cache = ContactCache::new();
+        let alice = contact(1, "Alice");
+        let bob = contact(2, "Bob");
+        cache.fill(&[alice.clone(), bob.clone()]);
+
+        assert_eq!(cache.list().map(|l| l.len()), Some(2));
+        assert_eq!(
+            cache.get(bob.id()).map(|c| c.display_name().to_string()),
+            Some("Bob".to_string())
+        );
+    }
+
+    #[test]
+    fn test_put_drops_full_list() {
+        let cache = ContactCache::new();
+        let alice = contact(1, "Alice");
+        cache.fill(std::slice::from_ref(&alice));
+
+        cache.put(&contact(2, "Bob"));
+
+        assert!(cache.list().is_none(), "List must be reloaded after a save");
+        assert!(cache.get(alice.id()).is_some());
+    }
+
+    #[test]
+    fn test_invalidate_removes_entry() {
+        let cache = ContactCache::new();
+        let alice = contact(1, "Alice");
+        cache.fill(std::slice::from_ref(&alice));
+
+        cache.invalidate(alice.id());
+
+        assert!(cache.get(alice.id()).is_none());
+        assert!(cache.list().is_none());
+    }
+
+    #[test]
+    fn test_clear_removes_everything() {
+        let cache = ContactCache::new();
+        cache.put(&contact(1, "Alice"));
+        cache.fill(&[contact(2, "Bob")]);
+
+        cache.clear();
+
+        assert!(cache.list().is_none());
+        assert!(cache.get(&hex::encode([2u8; 32])).is_none());
+    }
+}
@@ -0,0 +1,227 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Offline Content Bundles
+//!
+//! Air-gapped machines receive content updates as a single JSON file:
+//!
+//! ```json
+//! { "version": 1, "files": { "locales/de.json": "<base64>" }, "signature": "<base64>" }
+//! ```
+//!
+//! `signature` is the publisher's Ed25519 signature over the JSON encoding
+//! of `files` (keys sorted), checked against the same pinned key as remote
+//! content. File paths are relative to the content cache and must belong
+//! to a known content type. Types in the bundle are snapshotted first so
+//! an import can be rolled back like a remote update.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use base64::Engine;
+use serde::Deserialize;
+
+use crate::content_signing::{self, VerificationStatus};
+use crate::content_versions::{self, CONTENT_TYPES};
+
+/// Largest bundle file accepted.
+const MAX_BUNDLE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Supported bundle format version.
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContentBundle {
+    version: u32,
+    files: BTreeMap<String, String>,
+    signature: String,
+}
+
+/// Why a bundle was refused.
+#[derive(Debug)]
+pub enum BundleError {
+    Io(std::io::Error),
+    Invalid(String),
+    Signature(VerificationStatus),
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleError::Io(e) => write!(f, "{}", e),
+            BundleError::Invalid(reason) => write!(f, "Invalid content bundle: {}", reason),
+            BundleError::Signature(status) => {
+                write!(f, "Content bundle signature not verified: {:?}", status)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for BundleError {
+    fn from(e: std::io::Error) -> Self {
+        BundleError::Io(e)
+    }
+}
+
+/// Content type a bundle path belongs to, if it is a safe relative path
+/// under a known type.
+fn bundle_path_type(path: &str) -> Option<&'static str> {
+    let path = Path::new(path);
+    if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    let first = path.components().next()?.as_os_str().to_str()?;
+    CONTENT_TYPES
+        .iter()
+        .copied()
+        .find(|content_type| content_versions::names_type(first, content_type))
+}
+
+/// Parse, check and decode a bundle. Returns files by relative path and the
+/// content types they belong to.
+fn decode_bundle(
+    bytes: &[u8],
+    verify: impl Fn(&[u8], &str) -> VerificationStatus,
+) -> Result<(Vec<(PathBuf, Vec<u8>)>, Vec<&'static str>), BundleError> {
+    let bundle: ContentBundle =
+        serde_json::from_slice(bytes).map_err(|e| BundleError::Invalid(e.to_string()))?;
+    if bundle.version != BUNDLE_VERSION {
+        return Err(BundleError::Invalid(format!(
+            "unsupported version {}",
+            bundle.version
+        )));
+    }
+
+    let signed =
+        serde_json::to_vec(&bundle.files).map_err(|e| BundleError::Invalid(e.to_string()))?;
+    let status = verify(&signed, &bundle.signature);
+    if !status.is_verified() {
+        return Err(BundleError::Signature(status));
+    }
+
+    let mut files = Vec::new();
+    let mut types: Vec<&'static str> = Vec::new();
+    for (path, content) in &bundle.files {
+        let content_type = bundle_path_type(path)
+            .ok_or_else(|| BundleError::Invalid(format!("unexpected path {}", path)))?;
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(content)
+            .map_err(|e| BundleError::Invalid(format!("{}: {}", path, e)))?;
+        if !types.contains(&content_type) {
+            types.push(content_type);
+        }
+        files.push((PathBuf::from(path), data));
+    }
+    Ok((files, types))
+}
+
+/// Import a signed content bundle into the content cache. Returns the
+/// content types updated.
+pub fn import(data_dir: &Path, bundle_path: &Path, now: u64) -> Result<Vec<String>, BundleError> {
+    if std::fs::metadata(bundle_path)?.len() > MAX_BUNDLE_BYTES {
+        return Err(BundleError::Invalid("file too large".to_string()));
+    }
+    let bytes = std::fs::read(bundle_path)?;
+    let (files, types) = decode_bundle(&bytes, content_signing::verify_pinned)?;
+    write_files(data_dir, &files, &types, now)?;
+    Ok(types.iter().map(|t| t.to_string()).collect())
+}
+
+fn write_files(
+    data_dir: &Path,
+    files: &[(PathBuf, Vec<u8>)],
+    types: &[&str],
+    now: u64,
+) -> std::io::Result<()> {
+    for content_type in types {
+        content_versions::snapshot(data_dir, content_type, now)?;
+    }
+    let root = content_versions::content_dir(data_dir);
+    for (path, data) in files {
+        let target = root.join(path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, data)?;
+    }
+    Ok(())
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the private bundle path checks and decoding
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn bundle(files: &[(&str, &str)]) -> Vec<u8> {
+        let files: BTreeMap<String, String> = files
+            .iter()
+            .map(|(p, c)| {
+                (
+                    p.to_string(),
+                    base64::engine::general_purpose::STANDARD.encode(c),
+                )
+            })
+            .collect();
+        serde_json::to_vec(&serde_json::json!({
+            "version": 1,
+            "files": files,
+            "signature": "sig",
+        }))
+        .unwrap()
+    }
+
+    fn accept(_: &[u8], _: &str) -> VerificationStatus {
+        VerificationStatus::Verified
+    }
+
+    fn reject(_: &[u8], _: &str) -> VerificationStatus {
+        VerificationStatus::InvalidSignature
+    }
+
+    #[test]
+    fn test_bundle_path_type_rejects_unsafe_paths() {
+        assert_eq!(bundle_path_type("locales/de.json"), Some("locales"));
+        assert_eq!(bundle_path_type("networks.json"), Some("networks"));
+        assert_eq!(bundle_path_type("../locales/de.json"), None);
+        assert_eq!(bundle_path_type("/etc/passwd"), None);
+        assert_eq!(bundle_path_type("settings.json"), None);
+        assert_eq!(bundle_path_type("help_feedback.json"), None);
+    }
+
+    #[test]
+    fn test_decode_requires_signature_and_known_paths() {
+        let good = bundle(&[("locales/de.json", "{}"), ("themes/dark.json", "{}")]);
+        let (files, types) = decode_bundle(&good, accept).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(types, vec!["locales", "themes"]);
+
+        assert!(matches!(
+            decode_bundle(&good, reject),
+            Err(BundleError::Signature(VerificationStatus::InvalidSignature))
+        ));
+        assert!(matches!(
+            decode_bundle(&bundle(&[("../x", "{}")]), accept),
+            Err(BundleError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_files_snapshots_previous_content() {
+        let temp = TempDir::new().unwrap();
+        let root = content_versions::content_dir(temp.path());
+        std::fs::create_dir_all(root.join("locales")).unwrap();
+        std::fs::write(root.join("locales/de.json"), "old").unwrap();
+
+        let (files, types) = decode_bundle(&bundle(&[("locales/de.json", "new")]), accept).unwrap();
+        write_files(temp.path(), &files, &types, 10).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(root.join("locales/de.json")).unwrap(),
+            "new"
+        );
+        assert_eq!(content_versions::list(temp.path(), "locales").len(), 1);
+    }
+}
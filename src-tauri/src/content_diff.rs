@@ -0,0 +1,272 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Content Update Diff
+//!
+//! Compares two content cache directories (current and a staged update)
+//! and summarizes the changes per content type. Items are JSON objects
+//! carrying a string `id` (networks, themes, help articles); locales are
+//! compared key by key after flattening nested objects.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::content_versions;
+
+/// What an update would change.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ContentDiff {
+    /// IDs of social networks added.
+    pub networks_added: Vec<String>,
+    /// IDs of social networks removed.
+    pub networks_removed: Vec<String>,
+    /// Number of locale strings added, removed or changed.
+    pub locale_keys_changed: usize,
+    /// Locales with at least one changed string.
+    pub locales_changed: Vec<String>,
+    /// IDs of themes added or changed.
+    pub themes_updated: Vec<String>,
+    /// IDs of help articles added.
+    pub help_added: Vec<String>,
+    /// IDs of help articles removed.
+    pub help_removed: Vec<String>,
+}
+
+impl ContentDiff {
+    /// Whether the update changes nothing.
+    pub fn is_empty(&self) -> bool {
+        *self == ContentDiff::default()
+    }
+}
+
+/// Read every JSON file under `dir` with a path component named after
+/// `content_type`, keyed by relative path.
+fn read_type_json(root: &Path, content_type: &str) -> BTreeMap<String, Value> {
+    fn walk(root: &Path, dir: &Path, content_type: &str, out: &mut BTreeMap<String, Value>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(root, &path, content_type, out);
+                continue;
+            }
+            let Some(relative) = path
+                .strip_prefix(root)
+                .ok()
+                .and_then(|r| r.to_str())
+                .map(|r| r.replace('\\', "/"))
+            else {
+                continue;
+            };
+            if !relative
+                .split('/')
+                .any(|c| content_versions::names_type(c, content_type))
+            {
+                continue;
+            }
+            let value = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok());
+            if let Some(value) = value {
+                out.insert(relative, value);
+            }
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    walk(root, root, content_type, &mut files);
+    files
+}
+
+/// Objects with a string `id`, anywhere in the values.
+fn items_by_id<'a>(values: impl Iterator<Item = &'a Value>) -> BTreeMap<String, &'a Value> {
+    fn collect<'a>(value: &'a Value, out: &mut BTreeMap<String, &'a Value>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(id)) = map.get("id") {
+                    out.insert(id.clone(), value);
+                    return;
+                }
+                map.values().for_each(|v| collect(v, out));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect(v, out)),
+            _ => {}
+        }
+    }
+
+    let mut items = BTreeMap::new();
+    values.for_each(|v| collect(v, &mut items));
+    items
+}
+
+/// Flatten nested objects into dotted keys.
+fn flatten(value: &Value, prefix: &str, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(v, &key, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.clone());
+        }
+    }
+}
+
+/// Locale code from a locale file path (`locales/de.json` -> `de`).
+fn locale_code(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.strip_suffix(".json").unwrap_or(name).to_string()
+}
+
+fn added_and_removed(
+    before: &BTreeMap<String, &Value>,
+    after: &BTreeMap<String, &Value>,
+) -> (Vec<String>, Vec<String>) {
+    let added = after
+        .keys()
+        .filter(|id| !before.contains_key(*id))
+        .cloned()
+        .collect();
+    let removed = before
+        .keys()
+        .filter(|id| !after.contains_key(*id))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+/// Summarize the differences between the `current` and `staged` content dirs.
+pub fn diff(current: &Path, staged: &Path) -> ContentDiff {
+    let mut result = ContentDiff::default();
+
+    let before = read_type_json(current, "networks");
+    let after = read_type_json(staged, "networks");
+    let (added, removed) =
+        added_and_removed(&items_by_id(before.values()), &items_by_id(after.values()));
+    result.networks_added = added;
+    result.networks_removed = removed;
+
+    let before = read_type_json(current, "themes");
+    let after = read_type_json(staged, "themes");
+    let (before, after) = (items_by_id(before.values()), items_by_id(after.values()));
+    result.themes_updated = after
+        .iter()
+        .filter(|(id, theme)| before.get(*id) != Some(theme))
+        .map(|(id, _)| id.clone())
+        .collect();
+
+    let before = read_type_json(current, "help");
+    let after = read_type_json(staged, "help");
+    let (added, removed) =
+        added_and_removed(&items_by_id(before.values()), &items_by_id(after.values()));
+    result.help_added = added;
+    result.help_removed = removed;
+
+    let before = read_type_json(current, "locales");
+    let after = read_type_json(staged, "locales");
+    let paths: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    for path in paths {
+        let mut old = BTreeMap::new();
+        let mut new = BTreeMap::new();
+        if let Some(value) = before.get(path) {
+            flatten(value, "", &mut old);
+        }
+        if let Some(value) = after.get(path) {
+            flatten(value, "", &mut new);
+        }
+        let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        let changed = keys.iter().filter(|k| old.get(**k) != new.get(**k)).count();
+        if changed > 0 {
+            result.locale_keys_changed += changed;
+            result.locales_changed.push(locale_code(path));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str, value: Value) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_diff_reports_changes_per_type() {
+        let current = TempDir::new().unwrap();
+        let staged = TempDir::new().unwrap();
+
+        write(
+            current.path(),
+            "networks.json",
+            serde_json::json!([{"id": "twitter"}, {"id": "myspace"}]),
+        );
+        write(
+            staged.path(),
+            "networks.json",
+            serde_json::json!([{"id": "twitter"}, {"id": "bluesky"}]),
+        );
+        write(
+            current.path(),
+            "locales/de.json",
+            serde_json::json!({"a": "x", "nested": {"b": "y"}}),
+        );
+        write(
+            staged.path(),
+            "locales/de.json",
+            serde_json::json!({"a": "x", "nested": {"b": "z"}, "c": "new"}),
+        );
+        write(
+            staged.path(),
+            "themes/dark.json",
+            serde_json::json!({"id": "dark", "colors": {}}),
+        );
+        write(
+            current.path(),
+            "help/faq.json",
+            serde_json::json!({"faqs": [{"id": "old-faq"}]}),
+        );
+        write(
+            staged.path(),
+            "help/faq.json",
+            serde_json::json!({"faqs": [{"id": "new-faq"}]}),
+        );
+
+        let result = diff(current.path(), staged.path());
+        assert_eq!(result.networks_added, vec!["bluesky"]);
+        assert_eq!(result.networks_removed, vec!["myspace"]);
+        assert_eq!(result.locale_keys_changed, 2);
+        assert_eq!(result.locales_changed, vec!["de"]);
+        assert_eq!(result.themes_updated, vec!["dark"]);
+        assert_eq!(result.help_added, vec!["new-faq"]);
+        assert_eq!(result.help_removed, vec!["old-faq"]);
+    }
+
+    #[test]
+    fn test_identical_dirs_have_empty_diff() {
+        let current = TempDir::new().unwrap();
+        write(
+            current.path(),
+            "themes/dark.json",
+            serde_json::json!({"id": "dark"}),
+        );
+        assert!(diff(current.path(), current.path()).is_empty());
+    }
+}
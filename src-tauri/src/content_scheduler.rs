@@ -0,0 +1,151 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Scheduled Content Update Checks
+//!
+//! Checks for content updates every `check_interval_secs`, counted from the
+//! last check (manual or scheduled). Found updates are announced with
+//! `content://updates-available`, or applied right away when `auto_apply`
+//! is set and announced with `content://updated`.
+//!
+//! Scheduled checks are skipped while Tor mode is enabled, so content
+//! requests are only made over Tor when the user asks for them; manual
+//! checks go through the Tor proxy.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter};
+
+use crate::background;
+use crate::clock;
+use crate::commands::content::{self, LAST_CHECK_FILE};
+use crate::state::AppState;
+
+/// Event sent when scheduled checks found updates that were not applied.
+pub const UPDATES_AVAILABLE_EVENT: &str = "content://updates-available";
+
+/// Event sent after scheduled checks applied updates.
+pub const UPDATED_EVENT: &str = "content://updated";
+
+/// Delay after startup before the first check.
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// Time of the last update check, if any.
+fn last_check(data_dir: &Path) -> Option<u64> {
+    std::fs::read_to_string(data_dir.join(LAST_CHECK_FILE))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Seconds until the next check is due.
+fn secs_until_due(last_check: Option<u64>, interval_secs: u64, now: u64) -> u64 {
+    match last_check {
+        Some(last) => last.saturating_add(interval_secs).saturating_sub(now),
+        None => 0,
+    }
+}
+
+/// Whether Tor mode is enabled.
+fn tor_enabled(data_dir: &Path) -> bool {
+    AppState::open_storage(data_dir)
+        .ok()
+        .and_then(|storage| storage.load_or_create_tor_config().ok())
+        .is_some_and(|config| config.enabled)
+}
+
+/// Run one scheduled check if it is due. Returns seconds to wait before
+/// the next attempt.
+async fn run_once(app: &AppHandle, data_dir: &Path) -> u64 {
+    // Re-read each time so changed settings apply immediately
+    let settings = match content::read_content_settings(data_dir) {
+        Ok(settings) => settings,
+        Err(e) => {
+            tracing::warn!("Failed to read content settings: {}", e);
+            return STARTUP_DELAY.as_secs();
+        }
+    };
+    let interval = settings.check_interval_secs.max(STARTUP_DELAY.as_secs());
+    if !settings.enabled {
+        return interval;
+    }
+
+    let wait = secs_until_due(last_check(data_dir), interval, clock::now_secs());
+    if wait > 0 {
+        return wait;
+    }
+    if tor_enabled(data_dir) {
+        tracing::info!("Skipping scheduled content check while Tor mode is enabled");
+        return interval;
+    }
+
+    let status = match content::check_updates(data_dir, &settings, None).await {
+        Ok(status) => status,
+        Err(e) => {
+            tracing::warn!("Scheduled content check failed: {}", e);
+            return interval;
+        }
+    };
+    if !status.has_updates {
+        return interval;
+    }
+
+    if !settings.auto_apply {
+        if let Err(e) = app.emit(UPDATES_AVAILABLE_EVENT, &status) {
+            tracing::warn!("Failed to emit content update event: {}", e);
+        }
+        return interval;
+    }
+
+    match content::apply_updates(data_dir, &settings, None).await {
+        Ok(result) if !result.applied.is_empty() => {
+            tracing::info!("Applied content updates: {}", result.applied.join(", "));
+            if let Err(e) = app.emit(UPDATED_EVENT, &result) {
+                tracing::warn!("Failed to emit content update event: {}", e);
+            }
+        }
+        Ok(result) => {
+            if let Some(error) = result.error {
+                tracing::warn!("Scheduled content update not applied: {}", error);
+            }
+        }
+        Err(e) => tracing::warn!("Scheduled content update failed: {}", e),
+    }
+    interval
+}
+
+/// Start the background check loop.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    background::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            let wait = run_once(&app, &data_dir).await;
+            tokio::time::sleep(Duration::from_secs(wait.max(1))).await;
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private scheduling helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_secs_until_due() {
+        assert_eq!(secs_until_due(None, 3600, 1000), 0);
+        assert_eq!(secs_until_due(Some(1000), 3600, 1600), 3000);
+        assert_eq!(secs_until_due(Some(1000), 3600, 5000), 0);
+    }
+
+    #[test]
+    fn test_last_check_reads_timestamp_file() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(last_check(temp.path()), None);
+        std::fs::write(temp.path().join(LAST_CHECK_FILE), "1234\n").unwrap();
+        assert_eq!(last_check(temp.path()), Some(1234));
+    }
+}
@@ -0,0 +1,239 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Content Signing
+//!
+//! Remote content (networks, locales, themes, help) is only applied if the
+//! content server's `manifest.json` carries a valid Ed25519 signature by the
+//! publisher. The signature is a detached, base64-encoded file next to the
+//! manifest (`manifest.json.sig`).
+//!
+//! The publisher key is pinned at build time through `VAUCHI_CONTENT_PUBKEY`
+//! (the hex-encoded Ed25519 public key whose private half signs the
+//! content server's manifests at release time). Builds without it verify
+//! nothing and apply no remote content. Updates are applied to a staging copy
+//! of the content cache first, and only taken if the manifest core applied
+//! is the one that was verified, so the server cannot swap it between the
+//! check and the download. In Tor mode the requests go through the Tor
+//! proxy.
+
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::Serialize;
+use url::Url;
+
+/// Publisher public key (Ed25519, hex), pinned at build time.
+const PUBLISHER_KEY: Option<&str> = option_env!("VAUCHI_CONTENT_PUBKEY");
+
+/// Manifest file name on the content server.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Detached signature file name on the content server.
+const SIGNATURE_FILE: &str = "manifest.json.sig";
+
+/// Largest manifest accepted for verification.
+const MAX_MANIFEST_BYTES: usize = 1024 * 1024;
+
+/// Timeout for each manifest request.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Outcome of verifying the content manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    /// Signed by the pinned publisher key.
+    Verified,
+    /// The server has no signature for the manifest.
+    Unsigned,
+    /// The signature does not match the manifest or the pinned key.
+    InvalidSignature,
+    /// The manifest or signature could not be downloaded.
+    FetchFailed,
+    /// The content applied is not what the verified manifest describes.
+    Mismatch,
+    /// This build has no publisher key to verify against.
+    NoPublisherKey,
+}
+
+impl VerificationStatus {
+    /// Whether content may be applied.
+    pub fn is_verified(self) -> bool {
+        self == VerificationStatus::Verified
+    }
+}
+
+/// Verify a manifest against a base64 detached signature.
+pub fn verify_manifest(public_key: &[u8], manifest: &[u8], signature: &str) -> VerificationStatus {
+    let Ok(signature) = base64::engine::general_purpose::STANDARD.decode(signature.trim()) else {
+        return VerificationStatus::InvalidSignature;
+    };
+    match UnparsedPublicKey::new(&ED25519, public_key).verify(manifest, &signature) {
+        Ok(()) => VerificationStatus::Verified,
+        Err(_) => VerificationStatus::InvalidSignature,
+    }
+}
+
+/// The pinned publisher key, if this build has a valid one.
+fn publisher_key() -> Option<[u8; 32]> {
+    let key = hex::decode(PUBLISHER_KEY?.trim()).ok()?;
+    key.try_into().ok()
+}
+
+/// Verify a detached signature against the pinned publisher key.
+pub fn verify_pinned(message: &[u8], signature: &str) -> VerificationStatus {
+    match publisher_key() {
+        Some(key) => verify_manifest(&key, message, signature),
+        None => VerificationStatus::NoPublisherKey,
+    }
+}
+
+/// Join a file name onto the content base URL.
+fn content_file_url(content_url: &str, file: &str) -> String {
+    format!("{}/{}", content_url.trim_end_matches('/'), file)
+}
+
+/// Download the manifest and its signature from `content_url`, through
+/// `proxy` if set, and verify them against the pinned publisher key.
+/// Returns the verified manifest.
+pub async fn fetch_verified(
+    content_url: &str,
+    proxy: Option<&Url>,
+) -> Result<Vec<u8>, VerificationStatus> {
+    if publisher_key().is_none() {
+        return Err(VerificationStatus::NoPublisherKey);
+    }
+    let mut builder = reqwest::Client::builder().timeout(FETCH_TIMEOUT);
+    if let Some(proxy) = proxy {
+        let proxy =
+            reqwest::Proxy::all(proxy.as_str()).map_err(|_| VerificationStatus::FetchFailed)?;
+        builder = builder.proxy(proxy);
+    }
+    let client = builder
+        .build()
+        .map_err(|_| VerificationStatus::FetchFailed)?;
+
+    let manifest = match client
+        .get(content_file_url(content_url, MANIFEST_FILE))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+    {
+        Ok(response) => match response.bytes().await {
+            Ok(bytes) if bytes.len() <= MAX_MANIFEST_BYTES => bytes,
+            _ => return Err(VerificationStatus::FetchFailed),
+        },
+        Err(_) => return Err(VerificationStatus::FetchFailed),
+    };
+
+    let signature = match client
+        .get(content_file_url(content_url, SIGNATURE_FILE))
+        .send()
+        .await
+    {
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+            return Err(VerificationStatus::Unsigned);
+        }
+        Ok(response) if response.status().is_success() => match response.text().await {
+            Ok(text) => text,
+            Err(_) => return Err(VerificationStatus::FetchFailed),
+        },
+        _ => return Err(VerificationStatus::FetchFailed),
+    };
+
+    match verify_pinned(&manifest, &signature) {
+        VerificationStatus::Verified => Ok(manifest.to_vec()),
+        status => Err(status),
+    }
+}
+
+/// Whether the manifest core cached under `content_root` while applying
+/// an update is the verified one. Compared as JSON, as core may store it
+/// re-encoded.
+pub fn applied_manifest_matches(content_root: &Path, verified: &[u8]) -> bool {
+    let Ok(applied) = std::fs::read(content_root.join(MANIFEST_FILE)) else {
+        return false;
+    };
+    match (
+        serde_json::from_slice::<serde_json::Value>(&applied),
+        serde_json::from_slice::<serde_json::Value>(verified),
+    ) {
+        (Ok(applied), Ok(verified)) => applied == verified,
+        _ => false,
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the private content URL and file names
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn signed(manifest: &[u8]) -> (Vec<u8>, String) {
+        let pair = Ed25519KeyPair::from_seed_unchecked(&[7u8; 32]).unwrap();
+        let signature = base64::engine::general_purpose::STANDARD.encode(pair.sign(manifest));
+        (pair.public_key().as_ref().to_vec(), signature)
+    }
+
+    #[test]
+    fn test_valid_signature_verifies() {
+        let (key, signature) = signed(b"{\"version\":1}");
+        assert_eq!(
+            verify_manifest(&key, b"{\"version\":1}", &format!("{}\n", signature)),
+            VerificationStatus::Verified
+        );
+    }
+
+    #[test]
+    fn test_tampered_manifest_and_garbage_signature_rejected() {
+        let (key, signature) = signed(b"{\"version\":1}");
+        assert_eq!(
+            verify_manifest(&key, b"{\"version\":2}", &signature),
+            VerificationStatus::InvalidSignature
+        );
+        assert_eq!(
+            verify_manifest(&key, b"{\"version\":1}", "not base64!"),
+            VerificationStatus::InvalidSignature
+        );
+        assert_eq!(
+            verify_manifest(&[0u8; 32], b"{\"version\":1}", &signature),
+            VerificationStatus::InvalidSignature
+        );
+    }
+
+    #[test]
+    fn test_applied_manifest_must_match_the_verified_one() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let verified = br#"{"version":1,"files":{"locales":"abc"}}"#;
+        assert!(!applied_manifest_matches(temp.path(), verified));
+
+        std::fs::write(
+            temp.path().join(MANIFEST_FILE),
+            r#"{ "files": { "locales": "abc" }, "version": 1 }"#,
+        )
+        .unwrap();
+        assert!(applied_manifest_matches(temp.path(), verified));
+
+        std::fs::write(
+            temp.path().join(MANIFEST_FILE),
+            r#"{"version":1,"files":{"locales":"evil"}}"#,
+        )
+        .unwrap();
+        assert!(!applied_manifest_matches(temp.path(), verified));
+    }
+
+    #[test]
+    fn test_content_file_url_joins_once() {
+        assert_eq!(
+            content_file_url("https://vauchi.app/app-files/", MANIFEST_FILE),
+            "https://vauchi.app/app-files/manifest.json"
+        );
+        assert_eq!(
+            content_file_url("https://vauchi.app/app-files", SIGNATURE_FILE),
+            "https://vauchi.app/app-files/manifest.json.sig"
+        );
+    }
+}
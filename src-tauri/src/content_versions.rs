@@ -0,0 +1,419 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Content Version History
+//!
+//! Core caches downloaded content under `<data_dir>/content/`, keeping each
+//! content type in files or directories named after it (`networks.json`,
+//! `locales/`, `themes/`, `help/`). Before updates are applied, the current
+//! files of every type are copied to
+//! `<data_dir>/content_versions/<type>/<version>/`; snapshots of types the
+//! update did not touch are dropped again. A bad remote push can then be
+//! reverted locally with `rollback`.
+//!
+//! Earlier versions let core cache straight into the data dir;
+//! [`migrate_legacy_cache`] moves such a cache into `content/`.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Core content cache directory name under the data dir.
+const CONTENT_DIR: &str = "content";
+
+/// Version history directory name under the data dir.
+const VERSIONS_DIR: &str = "content_versions";
+
+/// Content type names, as used by core's cache.
+pub const CONTENT_TYPES: &[&str] = &["networks", "locales", "themes", "help"];
+
+/// Versions kept per content type.
+const MAX_VERSIONS: usize = 5;
+
+/// Files core keeps next to the content types in its cache.
+const CACHE_FILES: &[&str] = &["manifest.json", "manifest.json.sig"];
+
+/// A saved content version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContentVersionInfo {
+    pub content_type: String,
+    /// Version ID (creation time in seconds, with a suffix if needed).
+    pub version: String,
+    pub created_at: u64,
+    pub size_bytes: u64,
+}
+
+/// Core content cache directory.
+pub fn content_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONTENT_DIR)
+}
+
+/// Version history directory.
+pub fn versions_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(VERSIONS_DIR)
+}
+
+fn type_versions_dir(data_dir: &Path, content_type: &str) -> PathBuf {
+    versions_dir(data_dir).join(content_type)
+}
+
+/// Whether a path component is named after a content type, with or
+/// without extensions (`locales`, `networks.json`).
+pub fn names_type(component: &str, content_type: &str) -> bool {
+    component.split('.').next() == Some(content_type)
+}
+
+/// Files (relative to `root`) belonging to a content type: those with a
+/// path component named after it.
+fn type_files(root: &Path, content_type: &str) -> Vec<PathBuf> {
+    fn walk(root: &Path, dir: &Path, content_type: &str, out: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(root, &path, content_type, out);
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let belongs = relative.components().any(|c| {
+                c.as_os_str()
+                    .to_str()
+                    .is_some_and(|name| names_type(name, content_type))
+            });
+            if belongs {
+                out.push(relative.to_path_buf());
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(root, root, content_type, &mut files);
+    files.sort();
+    files
+}
+
+fn copy_files(from: &Path, to: &Path, files: &[PathBuf]) -> std::io::Result<u64> {
+    let mut size = 0;
+    for file in files {
+        let target = to.join(file);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        size += std::fs::copy(from.join(file), target)?;
+    }
+    Ok(size)
+}
+
+/// Copy a directory tree. A missing source copies nothing.
+pub fn copy_tree(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    let Ok(entries) = std::fs::read_dir(from) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Move a content cache core kept directly in the data dir into
+/// `content/`. Does nothing once `content/` exists. Only top-level entries
+/// named after a content type, and core's manifest, are moved.
+pub fn migrate_legacy_cache(data_dir: &Path) -> std::io::Result<()> {
+    let target = content_dir(data_dir);
+    if target.exists() {
+        return Ok(());
+    }
+    let Ok(entries) = std::fs::read_dir(data_dir) else {
+        return Ok(());
+    };
+    let legacy: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name().to_str().is_some_and(|name| {
+                CACHE_FILES.contains(&name) || CONTENT_TYPES.iter().any(|t| names_type(name, t))
+            })
+        })
+        .map(|e| e.path())
+        .collect();
+    if legacy.is_empty() {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&target)?;
+    for path in legacy {
+        if let Some(name) = path.file_name() {
+            std::fs::rename(&path, target.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the current files of a content type into a new version. Returns
+/// `None` if there is nothing cached for the type.
+pub fn snapshot(data_dir: &Path, content_type: &str, now: u64) -> std::io::Result<Option<String>> {
+    let version = save_version(data_dir, content_type, now)?;
+    prune(data_dir, content_type)?;
+    Ok(version)
+}
+
+fn save_version(data_dir: &Path, content_type: &str, now: u64) -> std::io::Result<Option<String>> {
+    let root = content_dir(data_dir);
+    let files = type_files(&root, content_type);
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let versions_dir = type_versions_dir(data_dir, content_type);
+    let mut version = now.to_string();
+    let mut suffix = 2;
+    while versions_dir.join(&version).exists() {
+        version = format!("{}-{}", now, suffix);
+        suffix += 1;
+    }
+    copy_files(&root, &versions_dir.join(&version), &files)?;
+    Ok(Some(version))
+}
+
+/// Delete a saved version.
+pub fn discard(data_dir: &Path, content_type: &str, version: &str) -> std::io::Result<()> {
+    std::fs::remove_dir_all(type_versions_dir(data_dir, content_type).join(version))
+}
+
+/// Keep only the newest `MAX_VERSIONS` versions of a content type.
+fn prune(data_dir: &Path, content_type: &str) -> std::io::Result<()> {
+    let versions = list(data_dir, content_type);
+    for old in versions.iter().skip(MAX_VERSIONS) {
+        discard(data_dir, content_type, &old.version)?;
+    }
+    Ok(())
+}
+
+/// Parse the creation time out of a version ID.
+fn version_created_at(version: &str) -> Option<u64> {
+    version.split('-').next()?.parse().ok()
+}
+
+/// Saved versions of a content type, newest first.
+pub fn list(data_dir: &Path, content_type: &str) -> Vec<ContentVersionInfo> {
+    let versions_dir = type_versions_dir(data_dir, content_type);
+    let Ok(entries) = std::fs::read_dir(&versions_dir) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<ContentVersionInfo> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let version = e.file_name().to_str()?.to_string();
+            let created_at = version_created_at(&version)?;
+            let dir = versions_dir.join(&version);
+            let size_bytes = type_files(&dir, content_type)
+                .iter()
+                .filter_map(|f| std::fs::metadata(dir.join(f)).ok())
+                .map(|m| m.len())
+                .sum();
+            Some(ContentVersionInfo {
+                content_type: content_type.to_string(),
+                version,
+                created_at,
+                size_bytes,
+            })
+        })
+        .collect();
+    versions.sort_by(|a, b| {
+        (b.created_at, b.version.len(), &b.version).cmp(&(
+            a.created_at,
+            a.version.len(),
+            &a.version,
+        ))
+    });
+    versions
+}
+
+/// Replace the current files of a content type with a saved version. The
+/// current files are saved as a new version first, so a rollback can be
+/// undone.
+pub fn rollback(
+    data_dir: &Path,
+    content_type: &str,
+    version: &str,
+    now: u64,
+) -> std::io::Result<()> {
+    if type_files(
+        &type_versions_dir(data_dir, content_type).join(version),
+        content_type,
+    )
+    .is_empty()
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Content version not found",
+        ));
+    }
+
+    // Pruned only after copying, which could otherwise remove the source
+    save_version(data_dir, content_type, now)?;
+    restore(data_dir, content_type, version)?;
+    prune(data_dir, content_type)
+}
+
+/// Replace the current files of a content type with a saved version,
+/// without saving the current ones.
+fn restore(data_dir: &Path, content_type: &str, version: &str) -> std::io::Result<()> {
+    let source = type_versions_dir(data_dir, content_type).join(version);
+    let files = type_files(&source, content_type);
+    remove(data_dir, content_type)?;
+    copy_files(&source, &content_dir(data_dir), &files)?;
+    Ok(())
+}
+
+/// Delete the current files of a content type, falling back to the bundled
+/// content.
+fn remove(data_dir: &Path, content_type: &str) -> std::io::Result<()> {
+    let root = content_dir(data_dir);
+    for current in type_files(&root, content_type) {
+        std::fs::remove_file(root.join(current))?;
+    }
+    Ok(())
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private file matching on a temp data dir
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(data_dir: &Path, relative: &str, content: &str) {
+        let path = content_dir(data_dir).join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn read(data_dir: &Path, relative: &str) -> String {
+        std::fs::read_to_string(content_dir(data_dir).join(relative)).unwrap()
+    }
+
+    #[test]
+    fn test_type_files_match_path_components() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "networks.json", "[]");
+        write(temp.path(), "locales/de.json", "{}");
+        write(temp.path(), "manifest.json", "{}");
+
+        let root = content_dir(temp.path());
+        assert_eq!(
+            type_files(&root, "networks"),
+            vec![PathBuf::from("networks.json")]
+        );
+        assert_eq!(
+            type_files(&root, "locales"),
+            vec![PathBuf::from("locales").join("de.json")]
+        );
+        assert!(type_files(&root, "themes").is_empty());
+    }
+
+    #[test]
+    fn test_type_files_do_not_match_other_names_with_the_type_as_prefix() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "help/faq.json", "{}");
+        write(temp.path(), "help_feedback.json", "{}");
+        write(temp.path(), "helpers/x.json", "{}");
+
+        assert_eq!(
+            type_files(&content_dir(temp.path()), "help"),
+            vec![PathBuf::from("help").join("faq.json")]
+        );
+    }
+
+    #[test]
+    fn test_legacy_cache_is_moved_into_the_content_dir() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("locales")).unwrap();
+        std::fs::write(temp.path().join("locales/de.json"), "{}").unwrap();
+        std::fs::write(temp.path().join("networks.json"), "[]").unwrap();
+        std::fs::write(temp.path().join("manifest.json"), "{}").unwrap();
+        std::fs::write(temp.path().join("locale.txt"), "de").unwrap();
+        std::fs::write(temp.path().join("help_feedback.json"), "[]").unwrap();
+
+        migrate_legacy_cache(temp.path()).unwrap();
+
+        assert_eq!(read(temp.path(), "locales/de.json"), "{}");
+        assert_eq!(read(temp.path(), "networks.json"), "[]");
+        assert_eq!(read(temp.path(), "manifest.json"), "{}");
+        assert!(!temp.path().join("networks.json").exists());
+        // Desktop files stay where they are
+        assert!(temp.path().join("locale.txt").exists());
+        assert!(temp.path().join("help_feedback.json").exists());
+
+        // Done once; later files in the data dir are left alone
+        std::fs::write(temp.path().join("networks.json"), "new").unwrap();
+        migrate_legacy_cache(temp.path()).unwrap();
+        assert_eq!(read(temp.path(), "networks.json"), "[]");
+    }
+
+    #[test]
+    fn test_snapshot_and_rollback_restore_previous_files() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "locales/de.json", "old");
+        let version = snapshot(temp.path(), "locales", 100).unwrap().unwrap();
+        assert_eq!(version, "100");
+
+        write(temp.path(), "locales/de.json", "bad");
+        write(temp.path(), "locales/fr.json", "new");
+        rollback(temp.path(), "locales", &version, 200).unwrap();
+
+        assert_eq!(read(temp.path(), "locales/de.json"), "old");
+        assert!(!content_dir(temp.path()).join("locales/fr.json").exists());
+
+        // The bad version was saved and can be restored again
+        let versions: Vec<String> = list(temp.path(), "locales")
+            .into_iter()
+            .map(|v| v.version)
+            .collect();
+        assert_eq!(versions, vec!["200", "100"]);
+    }
+
+    #[test]
+    fn test_snapshot_prunes_old_versions() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "themes/dark.json", "{}");
+        for now in 0..(MAX_VERSIONS as u64 + 2) {
+            snapshot(temp.path(), "themes", now).unwrap();
+        }
+        let versions = list(temp.path(), "themes");
+        assert_eq!(versions.len(), MAX_VERSIONS);
+        assert_eq!(versions[0].created_at, MAX_VERSIONS as u64 + 1);
+    }
+
+    #[test]
+    fn test_nothing_cached_means_no_snapshot() {
+        let temp = TempDir::new().unwrap();
+        assert!(snapshot(temp.path(), "help", 1).unwrap().is_none());
+        assert!(rollback(temp.path(), "help", "1", 2).is_err());
+    }
+
+    #[test]
+    fn test_restore_and_remove_do_not_save_versions() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "themes/dark.json", "old");
+        let version = snapshot(temp.path(), "themes", 1).unwrap().unwrap();
+        write(temp.path(), "themes/dark.json", "new");
+
+        restore(temp.path(), "themes", &version).unwrap();
+        assert_eq!(read(temp.path(), "themes/dark.json"), "old");
+        assert_eq!(list(temp.path(), "themes").len(), 1);
+
+        remove(temp.path(), "themes").unwrap();
+        assert!(type_files(&content_dir(temp.path()), "themes").is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Crash Reports
+//!
+//! A panic hook that writes each panic (from command threads, background
+//! sync tasks, or the main thread) to `<data_dir>/crashes/` as a JSON
+//! report. Reports stay local — nothing is uploaded. Users can list them
+//! and attach them to bug reports deliberately, or delete them.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::logging::redact;
+
+/// Crash report directory name under the data dir.
+const CRASH_DIR: &str = "crashes";
+
+/// Maximum number of crash reports kept; oldest are pruned.
+const MAX_CRASH_REPORTS: usize = 20;
+
+/// A single captured panic.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CrashReport {
+    /// Report ID (also the file stem).
+    pub id: String,
+    /// Unix timestamp (seconds) of the panic.
+    pub timestamp: u64,
+    pub app_version: String,
+    /// Name of the panicking thread, if any.
+    pub thread: String,
+    /// Redacted panic message.
+    pub message: String,
+    /// Source location (`file:line:column`).
+    pub location: String,
+    pub backtrace: String,
+}
+
+/// Path of the crash report directory for a data dir.
+pub fn crash_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(CRASH_DIR)
+}
+
+/// Install the panic hook. The previous hook still runs afterwards.
+pub fn install(data_dir: &Path) {
+    let dir = crash_dir(data_dir);
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+
+        let report = new_report(&message, location, backtrace);
+        tracing::error!("Panic in thread '{}': {}", report.thread, report.message);
+        if let Err(e) = write_report(&dir, &report) {
+            tracing::error!("Failed to write crash report: {}", e);
+        }
+
+        previous(info);
+    }));
+}
+
+fn new_report(message: &str, location: String, backtrace: String) -> CrashReport {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    CrashReport {
+        id: format!("crash-{}-{:09}", now.as_secs(), now.subsec_nanos()),
+        timestamp: now.as_secs(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("unnamed")
+            .to_string(),
+        message: redact(message),
+        location,
+        backtrace,
+    }
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let json = serde_json::to_string_pretty(report)?;
+    std::fs::write(dir.join(format!("{}.json", report.id)), json)?;
+
+    // Prune oldest reports beyond the cap
+    let reports = list_reports(dir);
+    for old in reports.iter().skip(MAX_CRASH_REPORTS) {
+        let _ = std::fs::remove_file(dir.join(format!("{}.json", old.id)));
+    }
+    Ok(())
+}
+
+/// Load all crash reports, newest first.
+pub fn list_reports(dir: &Path) -> Vec<CrashReport> {
+    let mut reports: Vec<CrashReport> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|p| std::fs::read_to_string(p).ok())
+                .filter_map(|json| serde_json::from_str(&json).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort_by(|a: &CrashReport, b| b.id.cmp(&a.id));
+    reports
+}
+
+/// Delete the given reports, or all reports when `ids` is `None`.
+///
+/// Returns the number of reports deleted. IDs that don't look like report
+/// IDs are ignored so a caller can't delete arbitrary files.
+pub fn delete_reports(dir: &Path, ids: Option<&[String]>) -> usize {
+    let targets: Vec<String> = match ids {
+        Some(ids) => ids
+            .iter()
+            .filter(|id| {
+                id.starts_with("crash-")
+                    && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            })
+            .cloned()
+            .collect(),
+        None => list_reports(dir).into_iter().map(|r| r.id).collect(),
+    };
+
+    targets
+        .iter()
+        .filter(|id| std::fs::remove_file(dir.join(format!("{}.json", id))).is_ok())
+        .count()
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private crash report persistence
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_is_written_and_listed() {
+        let temp = TempDir::new().unwrap();
+        let dir = crash_dir(temp.path());
+        let report = new_report("boom", "src/lib.rs:1:1".to_string(), String::new());
+
+        write_report(&dir, &report).unwrap();
+
+        let reports = list_reports(&dir);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].message, "boom");
+        assert_eq!(reports[0].id, report.id);
+    }
+
+    #[test]
+    fn test_report_message_is_redacted() {
+        let report = new_report("bad email bob@example.com", String::new(), String::new());
+        assert_eq!(report.message, "bad email [email]");
+    }
+
+    #[test]
+    fn test_delete_selected_and_all() {
+        let temp = TempDir::new().unwrap();
+        let dir = crash_dir(temp.path());
+        let mut first = new_report("a", String::new(), String::new());
+        first.id = "crash-1-000000001".to_string();
+        let mut second = new_report("b", String::new(), String::new());
+        second.id = "crash-2-000000002".to_string();
+        write_report(&dir, &first).unwrap();
+        write_report(&dir, &second).unwrap();
+
+        assert_eq!(delete_reports(&dir, Some(&[first.id.clone()])), 1);
+        assert_eq!(list_reports(&dir).len(), 1);
+
+        assert_eq!(delete_reports(&dir, None), 1);
+        assert!(list_reports(&dir).is_empty());
+    }
+
+    #[test]
+    fn test_delete_ignores_path_traversal() {
+        let temp = TempDir::new().unwrap();
+        let dir = crash_dir(temp.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(temp.path().join("keep.json"), "{}").unwrap();
+
+        let deleted = delete_reports(&dir, Some(&["../keep".to_string()]));
+
+        assert_eq!(deleted, 0);
+        assert!(temp.path().join("keep.json").exists());
+    }
+}
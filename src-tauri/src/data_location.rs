@@ -0,0 +1,435 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Data Location
+//!
+//! Where the profile lives: `VAUCHI_DATA_DIR` if set, else the directory
+//! chosen in the app (kept in `data_location.json` in the config dir, so it
+//! survives moving the data), else `<system data dir>/vauchi`.
+//!
+//! `migrate` copies the profile (database, keys, settings, caches) to a new
+//! directory, e.g. on an encrypted volume, checks every copied file against
+//! its source by SHA-256 and opens the copied database with the storage key
+//! before pointing the app there. The keychain entry is scoped to the data
+//! dir path, so the storage key is saved for the new path too. The old
+//! directory and its keychain entry are removed on the next start, once the
+//! app runs from the new location; key files in it are overwritten before
+//! they are deleted.
+//!
+//! The copy needs the storage worker stopped, so nothing writes to the
+//! database meanwhile; the WAL is checkpointed into it first.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ring::digest;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use vauchi_core::Storage;
+
+#[cfg(feature = "secure-storage")]
+use vauchi_core::storage::secure::{PlatformKeyring, SecureStorage};
+
+use crate::error::CommandError;
+use crate::keychain_migration;
+use crate::profile_import;
+
+#[cfg(feature = "secure-storage")]
+use crate::state::AppState;
+
+/// Environment variable overriding the data dir.
+pub const ENV_VAR: &str = "VAUCHI_DATA_DIR";
+
+/// Pointer file name under the config dir.
+const LOCATION_FILE: &str = "data_location.json";
+
+/// How long the checkpoint waits for a lock on the database.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Key name of the storage key, as saved by `AppState`.
+#[cfg(feature = "secure-storage")]
+const STORAGE_KEY_NAME: &str = "storage_key";
+
+/// The data dir chosen in the app.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataLocation {
+    /// `None` for the default location.
+    pub data_dir: Option<PathBuf>,
+    /// Directory moved away from, removed on the next start.
+    pub previous_data_dir: Option<PathBuf>,
+}
+
+/// What a migration copied.
+#[derive(Debug, Serialize)]
+pub struct MigrationReport {
+    pub data_dir: String,
+    pub files_copied: u64,
+    pub bytes_copied: u64,
+}
+
+/// Directory holding the pointer file.
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("vauchi")
+}
+
+/// The data dir used when none is chosen.
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("vauchi")
+}
+
+/// `VAUCHI_DATA_DIR`, if set.
+pub fn env_override() -> Option<PathBuf> {
+    std::env::var(ENV_VAR)
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+}
+
+fn location_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(LOCATION_FILE)
+}
+
+/// Load the pointer, falling back to the default location.
+pub fn load(config_dir: &Path) -> DataLocation {
+    std::fs::read_to_string(location_path(config_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Save the pointer, replacing the old one atomically.
+fn save(config_dir: &Path, location: &DataLocation) -> std::io::Result<()> {
+    std::fs::create_dir_all(config_dir)?;
+    let tmp = config_dir.join(format!("{}.tmp", LOCATION_FILE));
+    std::fs::write(&tmp, serde_json::to_string_pretty(location)?)?;
+    std::fs::rename(tmp, location_path(config_dir))
+}
+
+/// The data dir to run from.
+pub fn resolve(config_dir: &Path) -> PathBuf {
+    env_override()
+        .or(load(config_dir).data_dir)
+        .unwrap_or_else(default_data_dir)
+}
+
+/// Remove the directory a migration moved away from, once the app runs
+/// from the new one.
+pub fn finish_migration(config_dir: &Path, data_dir: &Path) {
+    let mut location = load(config_dir);
+    let Some(previous) = location.previous_data_dir.clone() else {
+        return;
+    };
+    if location.data_dir.as_deref() != Some(data_dir) || previous == data_dir {
+        return;
+    }
+
+    #[cfg(feature = "secure-storage")]
+    {
+        let keyring = PlatformKeyring::new(&AppState::keyring_service_name(&previous));
+        let _ = keyring.delete_key(STORAGE_KEY_NAME);
+    }
+    if let Err(e) = shred_keys(&previous) {
+        tracing::warn!("Failed to wipe keys in old data directory: {}", e);
+        return;
+    }
+    match std::fs::remove_dir_all(&previous) {
+        Ok(()) => tracing::info!("Removed old data directory {}", previous.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            tracing::warn!("Failed to remove old data directory: {}", e);
+            return;
+        }
+    }
+    location.previous_data_dir = None;
+    if let Err(e) = save(config_dir, &location) {
+        tracing::warn!("Failed to save data location: {}", e);
+    }
+}
+
+/// Overwrite and delete the key files in `data_dir`: the fallback key and
+/// the file key storage it protects.
+fn shred_keys(data_dir: &Path) -> std::io::Result<()> {
+    let key_dir = data_dir.join("keys");
+    if key_dir.is_dir() {
+        for entry in std::fs::read_dir(&key_dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                keychain_migration::shred(&entry.path())?;
+            }
+        }
+    }
+    match keychain_migration::shred(&data_dir.join(".fallback-key")) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Move everything in the WAL into the database file, so the copy holds
+/// every committed write.
+fn checkpoint(data_dir: &Path) -> Result<(), CommandError> {
+    let db_path = data_dir.join("vauchi.db");
+    if !db_path.exists() {
+        return Ok(());
+    }
+    let sqlite_error =
+        |e: rusqlite::Error| CommandError::Storage(format!("Database checkpoint failed: {}", e));
+    let conn = Connection::open(db_path).map_err(sqlite_error)?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(sqlite_error)?;
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+        .map_err(sqlite_error)
+}
+
+/// The target must be a new or empty directory outside the current one.
+pub fn validate_target(from: &Path, to: &Path) -> Result<(), CommandError> {
+    if !to.is_absolute() {
+        return Err(CommandError::Validation(
+            "Choose an absolute path for the data directory".to_string(),
+        ));
+    }
+    let from = from.canonicalize().unwrap_or_else(|_| from.to_path_buf());
+    // The target may not exist yet; compare through its nearest existing parent
+    let existing = to.ancestors().find(|p| p.exists()).unwrap_or(to);
+    let resolved = existing
+        .canonicalize()
+        .unwrap_or_else(|_| existing.to_path_buf())
+        .join(to.strip_prefix(existing).unwrap_or(Path::new("")));
+    if resolved.starts_with(&from) || from.starts_with(&resolved) {
+        return Err(CommandError::Validation(
+            "The new data directory may not be inside the current one or contain it".to_string(),
+        ));
+    }
+    if to.exists() {
+        let empty = std::fs::read_dir(to)
+            .map_err(|e| CommandError::Storage(format!("Cannot read {}: {}", to.display(), e)))?
+            .next()
+            .is_none();
+        if !empty {
+            return Err(CommandError::Validation(
+                "The new data directory must be empty".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Copy all files under `from` to `to`; returns their relative paths.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        std::fs::create_dir_all(to.join(&relative))?;
+        for entry in std::fs::read_dir(from.join(&relative))? {
+            let entry = entry?;
+            let path = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else {
+                std::fs::copy(from.join(&path), to.join(&path))?;
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<digest::Digest> {
+    let mut file = std::fs::File::open(path)?;
+    let mut context = digest::Context::new(&digest::SHA256);
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            return Ok(context.finish());
+        }
+        context.update(&buf[..n]);
+    }
+}
+
+/// Check each copied file against its source; returns the bytes compared.
+fn verify_copy(from: &Path, to: &Path, files: &[PathBuf]) -> Result<u64, CommandError> {
+    let mut bytes = 0;
+    for file in files {
+        let (source, copy) = (from.join(file), to.join(file));
+        let matches = sha256_file(&source)?.as_ref() == sha256_file(&copy)?.as_ref();
+        if !matches {
+            return Err(CommandError::Storage(format!(
+                "Copy of {} does not match the original",
+                file.display()
+            )));
+        }
+        bytes += std::fs::metadata(&copy)?.len();
+    }
+    Ok(bytes)
+}
+
+/// Open the copied database with the storage key and compare its identity.
+fn verify_database(from: &Path, to: &Path) -> Result<(), CommandError> {
+    let db_path = to.join("vauchi.db");
+    if !db_path.exists() {
+        return Ok(());
+    }
+    let key =
+        profile_import::source_storage_key(to).map_err(|e| CommandError::Storage(e.to_string()))?;
+    let copied = Storage::open(&db_path, key)
+        .map_err(|e| CommandError::Storage(format!("Copied database does not open: {}", e)))?;
+    let key = profile_import::source_storage_key(from)
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    let source = Storage::open(&from.join("vauchi.db"), key)?;
+    if copied.load_identity()? != source.load_identity()? {
+        return Err(CommandError::Storage(
+            "Copied database does not match the original".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Make the storage key of `from` available to the profile at `to`.
+#[cfg(feature = "secure-storage")]
+fn copy_storage_key(from: &Path, to: &Path) -> Result<(), CommandError> {
+    if !from.join("vauchi.db").exists() {
+        return Ok(());
+    }
+    let key = profile_import::source_storage_key(from)
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    let keyring = PlatformKeyring::new(&AppState::keyring_service_name(to));
+    // Without a working keychain the copied `keys/` folder holds the key
+    if let Err(e) = keyring.save_key(STORAGE_KEY_NAME, key.as_bytes()) {
+        tracing::warn!("Storage key not saved to keychain: {}", e);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "secure-storage"))]
+fn copy_storage_key(_from: &Path, _to: &Path) -> Result<(), CommandError> {
+    Ok(())
+}
+
+fn copy_and_verify(from: &Path, to: &Path) -> Result<MigrationReport, CommandError> {
+    checkpoint(from)?;
+    let files = copy_dir(from, to)?;
+    let bytes_copied = verify_copy(from, to, &files)?;
+    copy_storage_key(from, to)?;
+    verify_database(from, to)?;
+    Ok(MigrationReport {
+        data_dir: to.to_string_lossy().to_string(),
+        files_copied: files.len() as u64,
+        bytes_copied,
+    })
+}
+
+/// Copy the profile at `from` to the empty or new directory `to`, verify
+/// the copy and point the app at it from the next start. The storage
+/// worker must be stopped, so nothing writes to `from` meanwhile. A failed
+/// copy is removed again.
+pub fn migrate(config_dir: &Path, from: &Path, to: &Path) -> Result<MigrationReport, CommandError> {
+    validate_target(from, to)?;
+    let existed = to.exists();
+
+    let report = match copy_and_verify(from, to) {
+        Ok(report) => report,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(to);
+            if existed {
+                let _ = std::fs::create_dir_all(to);
+            }
+            return Err(e);
+        }
+    };
+
+    let location = DataLocation {
+        data_dir: Some(to.to_path_buf()),
+        previous_data_dir: Some(from.to_path_buf()),
+    };
+    save(config_dir, &location)
+        .map_err(|e| CommandError::Config(format!("Failed to save data location: {}", e)))?;
+    tracing::info!(
+        "Copied {} files to {}; the app runs from there after a restart",
+        report.files_copied,
+        to.display()
+    );
+    Ok(report)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private copy and target checks
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_target_must_be_empty_and_outside() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("from");
+        std::fs::create_dir_all(&from).unwrap();
+
+        assert!(validate_target(&from, Path::new("relative")).is_err());
+        assert!(validate_target(&from, &from.join("inner")).is_err());
+        assert!(validate_target(&from, temp.path()).is_err());
+
+        let to = temp.path().join("to");
+        assert!(validate_target(&from, &to).is_ok());
+        std::fs::create_dir_all(&to).unwrap();
+        assert!(validate_target(&from, &to).is_ok());
+        std::fs::write(to.join("file"), b"x").unwrap();
+        assert!(validate_target(&from, &to).is_err());
+    }
+
+    #[test]
+    fn test_copy_is_verified() {
+        let temp = TempDir::new().unwrap();
+        let (from, to) = (temp.path().join("from"), temp.path().join("to"));
+        std::fs::create_dir_all(from.join("content/help")).unwrap();
+        std::fs::write(from.join("locale.txt"), b"de").unwrap();
+        std::fs::write(from.join("content/help/faq.json"), b"{}").unwrap();
+
+        let files = copy_dir(&from, &to).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(verify_copy(&from, &to, &files).unwrap(), 4);
+
+        std::fs::write(to.join("locale.txt"), b"fr").unwrap();
+        assert!(verify_copy(&from, &to, &files).is_err());
+    }
+
+    #[test]
+    fn test_migrate_points_to_new_dir_and_cleans_up_old() {
+        let temp = TempDir::new().unwrap();
+        let config = temp.path().join("config");
+        let (from, to) = (temp.path().join("from"), temp.path().join("to"));
+        drop(crate::state::AppState::new(&from).unwrap());
+
+        let report = migrate(&config, &from, &to).unwrap();
+        assert!(report.files_copied > 0);
+        assert_eq!(load(&config).data_dir.as_deref(), Some(to.as_path()));
+        assert!(from.exists());
+
+        // Not yet running from the new dir
+        finish_migration(&config, &from);
+        assert!(from.exists());
+
+        finish_migration(&config, &to);
+        assert!(!from.exists());
+        assert_eq!(load(&config).previous_data_dir, None);
+        crate::state::AppState::new(&to).unwrap();
+    }
+
+    #[test]
+    fn test_shred_keys_removes_key_files() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("keys")).unwrap();
+        std::fs::write(temp.path().join("keys").join("storage_key"), [1u8; 48]).unwrap();
+        std::fs::write(temp.path().join(".fallback-key"), [2u8; 32]).unwrap();
+
+        shred_keys(temp.path()).unwrap();
+        assert!(!temp.path().join(".fallback-key").exists());
+        assert!(!temp.path().join("keys").join("storage_key").exists());
+        // Nothing left to wipe is fine
+        shred_keys(temp.path()).unwrap();
+    }
+}
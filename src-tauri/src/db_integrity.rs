@@ -0,0 +1,492 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Database Integrity
+//!
+//! Checks the SQLite database itself (`PRAGMA integrity_check` and
+//! `foreign_key_check`) and what the app expects of its contents: every
+//! contact has readable ratchet state, and label members and our own
+//! validations refer to existing contacts.
+//!
+//! `repair` removes orphaned label memberships and validations through
+//! core, after copying them to `<data_dir>/quarantine/`; `undo_repair`
+//! puts them back from that file. Damaged pages, dangling rows and
+//! contacts without ratchet state cannot be fixed here; they are reported
+//! with what to do instead.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use vauchi_core::{ProfileValidation, Storage};
+
+use crate::clock;
+use crate::error::CommandError;
+
+/// Quarantine directory name under the data dir.
+const QUARANTINE_DIR: &str = "quarantine";
+
+/// What kind of inconsistency was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// SQLite reports damaged pages or indexes.
+    Corruption,
+    /// A row refers to a row that no longer exists.
+    DanglingRow,
+    MissingRatchet,
+    UnreadableRatchet,
+    OrphanedLabelMember,
+    OrphanedValidation,
+}
+
+/// How `repair` fixes an issue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Fix {
+    RemoveLabelMember {
+        label_id: String,
+        contact_id: String,
+    },
+    DeleteValidation {
+        contact_id: String,
+        field: String,
+        validator_id: String,
+    },
+}
+
+/// One inconsistency.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub kind: IssueKind,
+    pub detail: String,
+    /// What to do when `repair` cannot fix it.
+    pub suggestion: Option<String>,
+    #[serde(skip)]
+    pub fix: Option<Fix>,
+    pub repairable: bool,
+}
+
+impl IntegrityIssue {
+    fn new(kind: IssueKind, detail: String, fix: Option<Fix>) -> Self {
+        Self {
+            kind,
+            detail,
+            suggestion: None,
+            repairable: fix.is_some(),
+            fix,
+        }
+    }
+
+    fn suggest(mut self, suggestion: &str) -> Self {
+        self.suggestion = Some(suggestion.to_string());
+        self
+    }
+}
+
+/// Result of a check.
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: u64,
+    /// Whether SQLite found no damage.
+    pub sqlite_ok: bool,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+/// Result of a repair.
+#[derive(Debug, Serialize)]
+pub struct RepairReport {
+    pub dry_run: bool,
+    /// Fixed, or to be fixed on a dry run.
+    pub repaired: Vec<IntegrityIssue>,
+    /// Issues that need the user.
+    pub remaining: Vec<IntegrityIssue>,
+    /// Copy of everything removed.
+    pub quarantine_file: Option<String>,
+}
+
+/// Result of undoing a repair.
+#[derive(Debug, Serialize)]
+pub struct UndoReport {
+    pub restored: usize,
+    /// Entries that could not be put back.
+    pub failed: Vec<String>,
+}
+
+/// Quarantined copy of a fixed issue, with what is needed to undo it.
+#[derive(Serialize, Deserialize)]
+struct QuarantineEntry {
+    detail: String,
+    fix: Fix,
+    /// The deleted validation.
+    #[serde(default)]
+    validation: Option<ProfileValidation>,
+}
+
+fn db_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("vauchi.db")
+}
+
+fn quarantine_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(QUARANTINE_DIR)
+}
+
+/// Damage and dangling rows, as SQLite sees them.
+fn sqlite_issues(conn: &Connection) -> rusqlite::Result<Vec<IntegrityIssue>> {
+    let mut issues = Vec::new();
+
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let lines = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    if lines != ["ok"] {
+        issues.extend(lines.into_iter().map(|line| {
+            IntegrityIssue::new(IssueKind::Corruption, line, None)
+                .suggest("Restore a backup; the database file is damaged")
+        }));
+    }
+
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(2)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    // Rows are core's to remove; only report them
+    for (table, parent) in rows {
+        let detail = format!("A row in {} refers to a missing {} row", table, parent);
+        issues.push(
+            IntegrityIssue::new(IssueKind::DanglingRow, detail, None)
+                .suggest("Restore a backup, or report the issue if it keeps coming back"),
+        );
+    }
+
+    Ok(issues)
+}
+
+/// Contacts without ratchet state, and label members and validations of
+/// contacts that no longer exist. `my_id` is our signing key (hex).
+fn app_issues(storage: &Storage, my_id: Option<&str>) -> Result<Vec<IntegrityIssue>, CommandError> {
+    let mut issues = Vec::new();
+
+    let contacts = storage.list_contacts()?;
+    for contact in &contacts {
+        match storage.load_ratchet_state(contact.id()) {
+            Ok(Some(_)) => {}
+            Ok(None) => issues.push(
+                IntegrityIssue::new(
+                    IssueKind::MissingRatchet,
+                    format!("{} has no session state", contact.display_name()),
+                    None,
+                )
+                .suggest("Exchange with this contact again to resume updates"),
+            ),
+            Err(e) => issues.push(
+                IntegrityIssue::new(
+                    IssueKind::UnreadableRatchet,
+                    format!(
+                        "Session state of {} cannot be read: {}",
+                        contact.display_name(),
+                        e
+                    ),
+                    None,
+                )
+                .suggest("Exchange with this contact again, or restore a backup"),
+            ),
+        }
+    }
+    let contact_ids: HashSet<&str> = contacts.iter().map(|c| c.id()).collect();
+
+    let labels = storage
+        .load_all_labels()
+        .map_err(|e| CommandError::Storage(format!("Failed to load labels: {}", e)))?;
+    for label in &labels {
+        for contact_id in label.contacts() {
+            if !contact_ids.contains(contact_id.as_str()) {
+                issues.push(IntegrityIssue::new(
+                    IssueKind::OrphanedLabelMember,
+                    format!("Label {} lists a deleted contact", label.name()),
+                    Some(Fix::RemoveLabelMember {
+                        label_id: label.id().to_string(),
+                        contact_id: contact_id.clone(),
+                    }),
+                ));
+            }
+        }
+    }
+
+    if let Some(my_id) = my_id {
+        for validation in storage.load_validations_by_validator(my_id)? {
+            let (Some(contact_id), Some(field)) =
+                (validation.contact_id(), validation.field_name())
+            else {
+                continue;
+            };
+            if !contact_ids.contains(contact_id) {
+                issues.push(IntegrityIssue::new(
+                    IssueKind::OrphanedValidation,
+                    format!("A validation of {} refers to a deleted contact", field),
+                    Some(Fix::DeleteValidation {
+                        contact_id: contact_id.to_string(),
+                        field: field.to_string(),
+                        validator_id: my_id.to_string(),
+                    }),
+                ));
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Check the database in `data_dir`, opened by `storage`.
+pub fn check(
+    data_dir: &Path,
+    storage: &Storage,
+    my_id: Option<&str>,
+) -> Result<IntegrityReport, CommandError> {
+    let conn = Connection::open(db_path(data_dir)).map_err(sqlite_error)?;
+    let mut issues = sqlite_issues(&conn).map_err(sqlite_error)?;
+    let sqlite_ok = !issues.iter().any(|i| i.kind == IssueKind::Corruption);
+    // Reading through damaged pages may fail or mislead
+    if sqlite_ok {
+        issues.extend(app_issues(storage, my_id)?);
+    }
+    Ok(IntegrityReport {
+        checked_at: clock::now_secs(),
+        sqlite_ok,
+        issues,
+    })
+}
+
+fn sqlite_error(e: rusqlite::Error) -> CommandError {
+    CommandError::Storage(format!("Database check failed: {}", e))
+}
+
+fn apply(storage: &Storage, fix: &Fix) -> Result<(), CommandError> {
+    match fix {
+        Fix::RemoveLabelMember {
+            label_id,
+            contact_id,
+        } => {
+            storage
+                .remove_contact_from_label(label_id, contact_id)
+                .map_err(|e| {
+                    CommandError::Storage(format!("Failed to remove label member: {}", e))
+                })?;
+        }
+        Fix::DeleteValidation {
+            contact_id,
+            field,
+            validator_id,
+        } => {
+            storage.delete_validation(contact_id, field, validator_id)?;
+        }
+    }
+    Ok(())
+}
+
+/// The validation a [`Fix::DeleteValidation`] removes, so it can be put
+/// back.
+fn quarantined_validation(
+    storage: &Storage,
+    fix: &Fix,
+) -> Result<Option<ProfileValidation>, CommandError> {
+    let Fix::DeleteValidation {
+        contact_id,
+        field,
+        validator_id,
+    } = fix
+    else {
+        return Ok(None);
+    };
+    Ok(storage
+        .load_validations_by_validator(validator_id)?
+        .into_iter()
+        .find(|v| {
+            v.contact_id() == Some(contact_id.as_str()) && v.field_name() == Some(field.as_str())
+        }))
+}
+
+/// Fix what `check` finds, copying everything removed to the quarantine
+/// directory first. With `dry_run`, only report what would be fixed.
+pub fn repair(
+    data_dir: &Path,
+    storage: &Storage,
+    my_id: Option<&str>,
+    dry_run: bool,
+) -> Result<RepairReport, CommandError> {
+    let report = check(data_dir, storage, my_id)?;
+    let (repaired, remaining): (Vec<_>, Vec<_>) = report
+        .issues
+        .into_iter()
+        .partition(|issue| issue.fix.is_some());
+    if dry_run || repaired.is_empty() {
+        return Ok(RepairReport {
+            dry_run,
+            repaired,
+            remaining,
+            quarantine_file: None,
+        });
+    }
+
+    let entries = repaired
+        .iter()
+        .filter_map(|issue| issue.fix.as_ref().map(|fix| (issue, fix)))
+        .map(|(issue, fix)| {
+            Ok(QuarantineEntry {
+                detail: issue.detail.clone(),
+                fix: fix.clone(),
+                validation: quarantined_validation(storage, fix)?,
+            })
+        })
+        .collect::<Result<Vec<_>, CommandError>>()?;
+
+    let dir = quarantine_dir(data_dir);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("repair-{}.json", clock::now_secs()));
+    std::fs::write(&path, serde_json::to_string_pretty(&entries)?)?;
+
+    for entry in &entries {
+        apply(storage, &entry.fix)?;
+    }
+    tracing::info!(
+        "Repaired {} database issue(s); {} left",
+        entries.len(),
+        remaining.len()
+    );
+
+    Ok(RepairReport {
+        dry_run,
+        repaired,
+        remaining,
+        quarantine_file: Some(path.to_string_lossy().to_string()),
+    })
+}
+
+/// Put back what a repair removed, from the quarantine file it wrote.
+/// Only files in the quarantine directory are read.
+pub fn undo_repair(
+    data_dir: &Path,
+    storage: &Storage,
+    quarantine_file: &Path,
+) -> Result<UndoReport, CommandError> {
+    let in_quarantine = quarantine_file
+        .canonicalize()
+        .ok()
+        .zip(quarantine_dir(data_dir).canonicalize().ok())
+        .is_some_and(|(file, dir)| file.parent() == Some(dir.as_path()));
+    if !in_quarantine {
+        return Err(CommandError::Validation(
+            "Not a quarantine file of this profile".to_string(),
+        ));
+    }
+    let entries: Vec<QuarantineEntry> =
+        serde_json::from_str(&std::fs::read_to_string(quarantine_file)?)
+            .map_err(|e| CommandError::Validation(format!("Quarantine file is damaged: {}", e)))?;
+
+    let mut report = UndoReport {
+        restored: 0,
+        failed: Vec::new(),
+    };
+    for entry in entries {
+        let result = match &entry.fix {
+            Fix::RemoveLabelMember {
+                label_id,
+                contact_id,
+            } => storage
+                .add_contact_to_label(label_id, contact_id)
+                .map_err(|e| e.to_string()),
+            Fix::DeleteValidation { .. } => match &entry.validation {
+                Some(validation) => storage
+                    .save_validation(validation)
+                    .map_err(|e| e.to_string()),
+                None => Err("the validation was not saved".to_string()),
+            },
+        };
+        match result {
+            Ok(()) => report.restored += 1,
+            Err(e) => report.failed.push(format!("{}: {}", entry.detail, e)),
+        }
+    }
+    tracing::info!(
+        "Restored {} quarantined item(s); {} failed",
+        report.restored,
+        report.failed.len()
+    );
+    Ok(report)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private SQLite checks on a scratch database
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn dangling_db(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE parent (id TEXT PRIMARY KEY);
+             CREATE TABLE child (parent_id TEXT REFERENCES parent(id), data BLOB);
+             INSERT INTO parent VALUES ('a');
+             INSERT INTO child VALUES ('a', x'01');
+             INSERT INTO child VALUES ('gone', x'ff');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_dangling_rows_are_found() {
+        let temp = TempDir::new().unwrap();
+        let conn = dangling_db(&temp.path().join("test.db"));
+
+        let issues = sqlite_issues(&conn).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::DanglingRow);
+        assert!(!issues[0].repairable);
+        assert!(issues[0].suggestion.is_some());
+    }
+
+    #[test]
+    fn test_repair_can_be_undone() {
+        let temp = TempDir::new().unwrap();
+        let state = crate::state::AppState::new(temp.path()).unwrap();
+        let me = vauchi_core::Identity::create("Me");
+        let my_id = hex::encode(me.signing_public_key());
+        let label = state.storage.create_label("Friends").unwrap();
+        state
+            .storage
+            .add_contact_to_label(label.id(), "gone")
+            .unwrap();
+        let validation = ProfileValidation::create_signed(&me, "email", "x@example.com", "gone");
+        state.storage.save_validation(&validation).unwrap();
+
+        let report = repair(temp.path(), &state.storage, Some(&my_id), false).unwrap();
+        assert_eq!(report.repaired.len(), 2);
+        let consistent = check(temp.path(), &state.storage, Some(&my_id)).unwrap();
+        assert!(consistent.issues.is_empty());
+
+        let file = PathBuf::from(report.quarantine_file.unwrap());
+        let undo = undo_repair(temp.path(), &state.storage, &file).unwrap();
+        assert_eq!(undo.restored, 2);
+        assert!(undo.failed.is_empty());
+        let restored = check(temp.path(), &state.storage, Some(&my_id)).unwrap();
+        assert_eq!(restored.issues.len(), 2);
+
+        let outside = temp.path().join("repair.json");
+        std::fs::copy(&file, &outside).unwrap();
+        assert!(undo_repair(temp.path(), &state.storage, &outside).is_err());
+    }
+
+    #[test]
+    fn test_fresh_database_is_consistent() {
+        let temp = TempDir::new().unwrap();
+        let state = crate::state::AppState::new(temp.path()).unwrap();
+        let report = check(temp.path(), &state.storage, None).unwrap();
+        assert!(report.sqlite_ok);
+        assert!(report.issues.is_empty());
+    }
+}
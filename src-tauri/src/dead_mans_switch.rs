@@ -0,0 +1,322 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Dead Man's Switch
+//!
+//! While armed, the user has to check in every `interval_secs`. Once a
+//! check-in is due, `emergency://check-in-due` and an OS notification
+//! (shown even while the window is hidden) remind them; if they still
+//! have not checked in when the grace period is over, the configured action
+//! runs once (an emergency broadcast, or scheduling account deletion with
+//! its usual grace period) and `emergency://switch-triggered` is sent. The
+//! next check-in re-arms the switch.
+//!
+//! The switch state lives in `dead_mans_switch.json` and is checked by a
+//! background loop. The grace period always counts from the reminder, so
+//! a deadline missed while the app was closed only prompts for a check-in
+//! on the next start; the action runs if the grace period passes again.
+//! A read-only mirror device never runs the switch.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::background;
+use crate::clock;
+use crate::commands::emergency;
+use crate::device_mode::{self, DeviceMode};
+use crate::events::{self, AppEvent};
+use crate::state::AppState;
+
+/// Switch state file name under the data dir.
+const SWITCH_FILE: &str = "dead_mans_switch.json";
+
+/// Event sent when a check-in is due.
+pub const CHECK_IN_DUE_EVENT: &str = "emergency://check-in-due";
+
+/// Event sent after the switch ran its action.
+pub const TRIGGERED_EVENT: &str = "emergency://switch-triggered";
+
+/// Shortest allowed check-in interval.
+pub const MIN_INTERVAL_SECS: u64 = 3600;
+
+/// Shortest grace period between the reminder and the action, so the user
+/// has time to see it.
+pub const MIN_GRACE_SECS: u64 = 3600;
+
+/// Longest the background loop sleeps, so changed settings apply soon.
+const MAX_POLL_SECS: u64 = 60;
+
+/// What happens when the user misses a check-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchAction {
+    /// Send the configured emergency broadcast.
+    Broadcast,
+    /// Schedule account deletion.
+    ScheduleDeletion,
+}
+
+/// Dead man's switch configuration and state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadMansSwitch {
+    pub enabled: bool,
+    /// Seconds between required check-ins.
+    pub interval_secs: u64,
+    /// Seconds after a missed check-in before the action runs.
+    pub grace_secs: u64,
+    pub action: SwitchAction,
+    pub last_check_in: u64,
+    /// When the action ran; cleared by the next check-in.
+    pub triggered_at: Option<u64>,
+    /// When the due reminder was sent for the current period.
+    #[serde(default)]
+    pub reminded_at: Option<u64>,
+}
+
+impl Default for DeadMansSwitch {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 7 * 24 * 3600,
+            grace_secs: 24 * 3600,
+            action: SwitchAction::Broadcast,
+            last_check_in: 0,
+            triggered_at: None,
+            reminded_at: None,
+        }
+    }
+}
+
+impl DeadMansSwitch {
+    /// When the next check-in is due.
+    pub fn due_at(&self) -> u64 {
+        self.last_check_in.saturating_add(self.interval_secs)
+    }
+
+    /// When the action runs without a check-in: a grace period after the
+    /// due time, and never sooner than a grace period after the reminder.
+    /// Grace periods saved before the minimum existed are raised to it.
+    pub fn deadline(&self) -> u64 {
+        let grace = self.grace_secs.max(MIN_GRACE_SECS);
+        let after_due = self.due_at().saturating_add(grace);
+        match self.reminded_at {
+            Some(reminded_at) => after_due.max(reminded_at.saturating_add(grace)),
+            None => after_due,
+        }
+    }
+
+    /// Record that the user is present, re-arming the switch.
+    pub fn check_in(&mut self, now: u64) {
+        self.last_check_in = now;
+        self.triggered_at = None;
+        self.reminded_at = None;
+    }
+}
+
+fn switch_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SWITCH_FILE)
+}
+
+/// Load the switch state, disabled by default.
+pub fn load(data_dir: &Path) -> DeadMansSwitch {
+    std::fs::read_to_string(switch_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Save the switch state.
+pub fn save(data_dir: &Path, switch: &DeadMansSwitch) -> std::io::Result<()> {
+    std::fs::write(switch_path(data_dir), serde_json::to_string(switch)?)
+}
+
+/// Next step for an armed switch.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    /// Nothing to do for this many seconds.
+    Wait(u64),
+    /// Remind the user to check in.
+    Remind,
+    /// Run the action.
+    Trigger,
+}
+
+fn next_step(switch: &DeadMansSwitch, now: u64) -> Step {
+    if !switch.enabled || switch.triggered_at.is_some() {
+        return Step::Wait(MAX_POLL_SECS);
+    }
+    if now < switch.due_at() {
+        return Step::Wait((switch.due_at() - now).min(MAX_POLL_SECS));
+    }
+    // Never act without having asked first
+    if switch.reminded_at.is_none() {
+        return Step::Remind;
+    }
+    if now >= switch.deadline() {
+        return Step::Trigger;
+    }
+    Step::Wait((switch.deadline() - now).min(MAX_POLL_SECS))
+}
+
+/// Run the configured action.
+async fn run_action(app: &AppHandle, action: SwitchAction) -> Result<(), String> {
+    let state = app.state::<Mutex<AppState>>();
+    match action {
+        SwitchAction::Broadcast => {
+            let report = emergency::broadcast(&state, None, false)
+                .await
+                .map_err(|e| e.to_string())?;
+            tracing::warn!(
+                "Dead man's switch sent emergency broadcast to {} contacts",
+                report.recipients.len()
+            );
+        }
+        SwitchAction::ScheduleDeletion => {
+            let state = state.lock().unwrap();
+            vauchi_core::api::DeletionManager::new(&state.storage)
+                .schedule_deletion()
+                .map_err(|e| e.to_string())?;
+            tracing::warn!("Dead man's switch scheduled account deletion");
+        }
+    }
+    Ok(())
+}
+
+/// Advance the switch once. Returns seconds to wait before the next attempt.
+async fn run_once(app: &AppHandle, data_dir: &Path) -> u64 {
+    // A mirror acts for nobody; the switch runs on the other devices
+    let mirror = {
+        let state = app.state::<Mutex<AppState>>();
+        let state = state.lock().unwrap();
+        device_mode::current(&state) == DeviceMode::Mirror
+    };
+    if mirror {
+        return MAX_POLL_SECS;
+    }
+    let mut switch = load(data_dir);
+    let now = clock::now_secs();
+    match next_step(&switch, now) {
+        Step::Wait(secs) => return secs,
+        Step::Remind => {
+            switch.reminded_at = Some(now);
+            let deadline = switch.deadline();
+            if let Err(e) = app.emit(CHECK_IN_DUE_EVENT, deadline) {
+                tracing::warn!("Failed to emit check-in event: {}", e);
+            }
+            // The window is usually hidden in the tray
+            events::publish(AppEvent::CheckInDue { deadline });
+        }
+        Step::Trigger => {
+            if let Err(e) = run_action(app, switch.action).await {
+                tracing::warn!("Dead man's switch action failed: {}", e);
+                return MAX_POLL_SECS;
+            }
+            switch.triggered_at = Some(now);
+            if let Err(e) = app.emit(TRIGGERED_EVENT, switch.action) {
+                tracing::warn!("Failed to emit switch event: {}", e);
+            }
+        }
+    }
+    // A check-in may have happened meanwhile; it wins
+    let current = load(data_dir);
+    if current.last_check_in == switch.last_check_in {
+        if let Err(e) = save(data_dir, &switch) {
+            tracing::warn!("Failed to save dead man's switch: {}", e);
+        }
+    }
+    1
+}
+
+/// Start the background loop.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    background::spawn(async move {
+        loop {
+            let wait = run_once(&app, &data_dir).await;
+            tokio::time::sleep(Duration::from_secs(wait.max(1))).await;
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private step logic
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn armed(last_check_in: u64) -> DeadMansSwitch {
+        DeadMansSwitch {
+            enabled: true,
+            interval_secs: 10_000,
+            grace_secs: MIN_GRACE_SECS,
+            last_check_in,
+            ..DeadMansSwitch::default()
+        }
+    }
+
+    #[test]
+    fn test_steps_follow_due_time_and_deadline() {
+        let switch = armed(0);
+        assert_eq!(next_step(&switch, 500), Step::Wait(MAX_POLL_SECS));
+        assert_eq!(next_step(&switch, 9_990), Step::Wait(10));
+        assert_eq!(next_step(&switch, 10_000), Step::Remind);
+
+        let reminded = DeadMansSwitch {
+            reminded_at: Some(10_000),
+            ..armed(0)
+        };
+        assert_eq!(next_step(&reminded, 13_550), Step::Wait(50));
+        assert_eq!(next_step(&reminded, 13_600), Step::Trigger);
+    }
+
+    #[test]
+    fn test_missed_deadline_asks_before_acting() {
+        // The app was closed through the whole grace period
+        let mut switch = armed(0);
+        assert_eq!(next_step(&switch, 50_000), Step::Remind);
+
+        switch.reminded_at = Some(50_000);
+        assert_eq!(switch.deadline(), 53_600);
+        assert_eq!(next_step(&switch, 53_550), Step::Wait(50));
+        assert_eq!(next_step(&switch, 53_600), Step::Trigger);
+    }
+
+    #[test]
+    fn test_short_grace_is_raised_to_minimum() {
+        let switch = DeadMansSwitch {
+            grace_secs: 0,
+            reminded_at: Some(10_000),
+            ..armed(0)
+        };
+        assert_eq!(switch.deadline(), 10_000 + MIN_GRACE_SECS);
+    }
+
+    #[test]
+    fn test_disabled_or_triggered_switch_waits() {
+        let disabled = DeadMansSwitch {
+            enabled: false,
+            ..armed(0)
+        };
+        assert_eq!(next_step(&disabled, 5000), Step::Wait(MAX_POLL_SECS));
+
+        let mut triggered = armed(0);
+        triggered.triggered_at = Some(13_600);
+        assert_eq!(next_step(&triggered, 50_000), Step::Wait(MAX_POLL_SECS));
+
+        triggered.check_in(50_000);
+        assert_eq!(triggered.triggered_at, None);
+        assert_eq!(triggered.due_at(), 60_000);
+    }
+
+    #[test]
+    fn test_load_defaults_and_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert!(!load(temp.path()).enabled);
+        save(temp.path(), &armed(42)).unwrap();
+        assert_eq!(load(temp.path()), armed(42));
+    }
+}
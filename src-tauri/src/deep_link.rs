@@ -0,0 +1,213 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Deep Link Dispatcher
+//!
+//! Parses `vauchi://` URLs and routes them into the matching pending flow:
+//!
+//! - `vauchi://exchange/<qr-data>` — contact exchange QR payload
+//! - `vauchi://recovery-claim/<claim-b64>` — recovery claim to vouch for
+//! - `vauchi://device-link/<link-data>` — device link QR payload
+//! - `vauchi://share/<id>#<key>` — one-way share of card fields
+//!
+//! The parsed link is stored in `AppState` (so a cold-start link survives
+//! until the frontend is ready) and a `deep-link` event is emitted so a
+//! running UI can navigate immediately. Links that arrive while the state
+//! is still loading are queued and dispatched once it is managed.
+
+use std::sync::Mutex;
+
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::CommandError;
+use crate::state::AppState;
+
+/// URL scheme registered with the OS.
+pub const SCHEME: &str = "vauchi";
+
+/// Event emitted to the frontend when a deep link arrives.
+pub const DEEP_LINK_EVENT: &str = "deep-link";
+
+/// Links that arrived before the app state was managed.
+static PENDING: Mutex<Vec<DeepLink>> = Mutex::new(Vec::new());
+
+/// A parsed `vauchi://` link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "payload", rename_all = "snake_case")]
+pub enum DeepLink {
+    /// Contact exchange QR data (feed to `process_scanned_qr`).
+    Exchange(String),
+    /// Base64 recovery claim (feed to `parse_recovery_claim`).
+    RecoveryClaim(String),
+    /// Device link QR data (feed to `join_device`).
+    DeviceLink(String),
+    /// Share link ID and key as `<id>#<key>` (feed to `open_share_link`).
+    Share(String),
+}
+
+impl DeepLink {
+    /// Parse a `vauchi://<kind>/<payload>` URL.
+    pub fn parse(raw: &str) -> Result<Self, CommandError> {
+        let url = url::Url::parse(raw.trim())
+            .map_err(|e| CommandError::Validation(format!("Invalid link: {}", e)))?;
+
+        if url.scheme() != SCHEME {
+            return Err(CommandError::Validation(format!(
+                "Unsupported link scheme '{}'",
+                url.scheme()
+            )));
+        }
+
+        let kind = url.host_str().unwrap_or_default();
+        let payload = percent_decode_str(url.path().trim_start_matches('/'))
+            .decode_utf8()
+            .map_err(|_| CommandError::Validation("Link payload is not valid UTF-8".to_string()))?
+            .to_string();
+
+        if payload.is_empty() {
+            return Err(CommandError::Validation("Link has no payload".to_string()));
+        }
+
+        match kind {
+            "exchange" => Ok(DeepLink::Exchange(payload)),
+            "recovery-claim" => Ok(DeepLink::RecoveryClaim(payload)),
+            "device-link" => Ok(DeepLink::DeviceLink(payload)),
+            // The key is in the fragment
+            "share" => Ok(DeepLink::Share(format!(
+                "{}#{}",
+                payload,
+                url.fragment().unwrap_or_default()
+            ))),
+            other => Err(CommandError::Validation(format!(
+                "Unknown link type '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Frontend route that handles this link.
+    pub fn route(&self) -> &'static str {
+        match self {
+            DeepLink::Exchange(_) => "exchange",
+            DeepLink::RecoveryClaim(_) => "recovery",
+            DeepLink::DeviceLink(_) => "devices",
+            DeepLink::Share(_) => "share",
+        }
+    }
+}
+
+/// Navigation event payload sent to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeepLinkEvent {
+    /// Frontend route to navigate to.
+    pub route: String,
+    /// The parsed link.
+    pub link: DeepLink,
+}
+
+/// Handle an incoming URL: store it as pending and notify the frontend.
+///
+/// Invalid links are dropped with a warning — a malformed link from another
+/// app must never crash or block the UI.
+pub fn dispatch(app: &AppHandle, raw: &str) {
+    match DeepLink::parse(raw) {
+        Ok(link) => dispatch_link(app, link),
+        Err(e) => tracing::warn!("Ignoring deep link: {}", e),
+    }
+}
+
+/// Store an already-parsed link as pending and notify the frontend.
+pub fn dispatch_link(app: &AppHandle, link: DeepLink) {
+    {
+        let mut pending = PENDING.lock().unwrap();
+        match app.try_state::<Mutex<AppState>>() {
+            Some(state) => state.lock().unwrap().pending_deep_link = Some(link.clone()),
+            None => {
+                pending.push(link);
+                return;
+            }
+        }
+    }
+
+    crate::tray::show_window(app);
+
+    let event = DeepLinkEvent {
+        route: link.route().to_string(),
+        link,
+    };
+    if let Err(e) = app.emit(DEEP_LINK_EVENT, event) {
+        tracing::warn!("Failed to emit deep link event: {}", e);
+    }
+}
+
+/// Dispatch the links that arrived before the app state was managed.
+pub fn handle_pending(app: &AppHandle) {
+    let links = std::mem::take(&mut *PENDING.lock().unwrap());
+    for link in links {
+        dispatch_link(app, link);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exchange_link() {
+        let link = DeepLink::parse("vauchi://exchange/WBEX-abc123").unwrap();
+        assert_eq!(link, DeepLink::Exchange("WBEX-abc123".to_string()));
+        assert_eq!(link.route(), "exchange");
+    }
+
+    #[test]
+    fn test_parse_recovery_claim_link_decodes_percent_escapes() {
+        let link = DeepLink::parse("vauchi://recovery-claim/YWJj%2Bx%3D%3D").unwrap();
+        assert_eq!(link, DeepLink::RecoveryClaim("YWJj+x==".to_string()));
+        assert_eq!(link.route(), "recovery");
+    }
+
+    #[test]
+    fn test_parse_device_link() {
+        let link = DeepLink::parse("vauchi://device-link/WBDL-xyz").unwrap();
+        assert_eq!(link, DeepLink::DeviceLink("WBDL-xyz".to_string()));
+        assert_eq!(link.route(), "devices");
+    }
+
+    #[test]
+    fn test_parse_share_link() {
+        let link = DeepLink::parse("vauchi://share/abc123#a2V5").unwrap();
+        assert_eq!(link, DeepLink::Share("abc123#a2V5".to_string()));
+        assert_eq!(link.route(), "share");
+    }
+
+    #[test]
+    fn test_parse_rejects_other_scheme() {
+        assert!(DeepLink::parse("https://exchange/abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_kind() {
+        assert!(DeepLink::parse("vauchi://settings/abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_empty_payload() {
+        assert!(DeepLink::parse("vauchi://exchange/").is_err());
+        assert!(DeepLink::parse("vauchi://exchange").is_err());
+    }
+
+    #[test]
+    fn test_event_serializes_kind_and_payload() {
+        let event = DeepLinkEvent {
+            route: "exchange".to_string(),
+            link: DeepLink::Exchange("data".to_string()),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["route"], "exchange");
+        assert_eq!(json["link"]["kind"], "exchange");
+        assert_eq!(json["link"]["payload"], "data");
+    }
+}
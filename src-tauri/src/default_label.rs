@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Default Label
+//!
+//! What happens to the labels of a contact added by an exchange or by an
+//! incoming exchange on sync, kept in `default_label.json`: nothing, put it
+//! in a chosen label, or leave it for the frontend to prompt (the contact
+//! then shows up in `get_unlabeled_contacts` until labelled).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use vauchi_core::Storage;
+
+use crate::settings_file;
+
+/// Settings file for the default label policy.
+const SETTINGS_FILE: &str = "default_label.json";
+
+/// How new contacts get a label.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefaultLabelPolicy {
+    /// Leave new contacts unlabelled.
+    #[default]
+    None,
+    /// Add new contacts to `label_id`.
+    Assign,
+    /// Leave new contacts unlabelled and ask the user.
+    Prompt,
+}
+
+/// Default label settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DefaultLabelSettings {
+    pub policy: DefaultLabelPolicy,
+    /// Label new contacts go into with `Assign`.
+    pub label_id: Option<String>,
+}
+
+/// The default label policy.
+pub fn load(data_dir: &Path) -> DefaultLabelSettings {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Change the default label policy.
+pub fn save(data_dir: &Path, settings: &DefaultLabelSettings) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, settings)
+}
+
+/// Label a newly added contact according to the settings. Failures are
+/// logged; the contact stays unlabelled and is listed as such.
+pub fn apply(storage: &Storage, data_dir: &Path, contact_id: &str) {
+    let settings = load(data_dir);
+    let (DefaultLabelPolicy::Assign, Some(label_id)) = (settings.policy, &settings.label_id) else {
+        return;
+    };
+    if let Err(e) = storage.add_contact_to_label(label_id, contact_id) {
+        tracing::warn!("Failed to apply default label: {:?}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_roundtrip_and_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load(temp.path()).policy, DefaultLabelPolicy::None);
+        let settings = DefaultLabelSettings {
+            policy: DefaultLabelPolicy::Assign,
+            label_id: Some("work".to_string()),
+        };
+        save(temp.path(), &settings).unwrap();
+        assert_eq!(load(temp.path()), settings);
+    }
+
+    #[test]
+    fn test_policy_serializes_snake_case() {
+        let json = serde_json::to_string(&DefaultLabelPolicy::Prompt).unwrap();
+        assert_eq!(json, "\"prompt\"");
+    }
+}
@@ -0,0 +1,68 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Deleted Contacts
+//!
+//! Remembers the IDs of contacts deleted from this profile, here or on a
+//! linked device, so importing an older profile (`profile_import`) does
+//! not bring them back. Stored in `deleted_contacts.json` in the data dir.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::clock;
+
+/// Deleted contacts file name under the data dir.
+const DELETED_FILE: &str = "deleted_contacts.json";
+
+fn deleted_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(DELETED_FILE)
+}
+
+/// Deleted contact IDs → Unix seconds they were deleted.
+pub fn load(data_dir: &Path) -> BTreeMap<String, u64> {
+    std::fs::read_to_string(deleted_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Remember that the contacts were deleted.
+pub fn record(data_dir: &Path, contact_ids: &[String]) {
+    let mut deleted = load(data_dir);
+    let now = clock::now_secs();
+    let before = deleted.len();
+    for id in contact_ids {
+        deleted.entry(id.clone()).or_insert(now);
+    }
+    if deleted.len() == before {
+        return;
+    }
+    let result = serde_json::to_string(&deleted)
+        .map_err(std::io::Error::from)
+        .and_then(|json| std::fs::write(deleted_path(data_dir), json));
+    if let Err(e) = result {
+        tracing::warn!("Failed to save deleted contacts: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_deletions_are_kept_with_first_time() {
+        let temp = TempDir::new().unwrap();
+        assert!(load(temp.path()).is_empty());
+
+        record(temp.path(), &["alice".to_string()]);
+        let first = load(temp.path())["alice"];
+        record(temp.path(), &["alice".to_string(), "bob".to_string()]);
+
+        let deleted = load(temp.path());
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(deleted["alice"], first);
+    }
+}
@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Desktop Strings
+//!
+//! Texts the backend renders itself (milestone titles, print sheets, help
+//! walkthroughs, error messages) and that core's locale files do not have.
+//! They are bundled in `resources/strings.json`, keyed by locale code and
+//! then by flat keys such as `print.fingerprint`, with English as the
+//! fallback.
+
+use std::collections::BTreeMap;
+
+/// Strings bundled with this build.
+const STRINGS: &str = include_str!("../resources/strings.json");
+
+/// Fallback locale.
+const FALLBACK_LOCALE: &str = "en";
+
+fn bundled() -> BTreeMap<String, BTreeMap<String, String>> {
+    serde_json::from_str(STRINGS).expect("bundled desktop strings should parse")
+}
+
+/// The string `key` in the locale `locale_code`, in English if the locale
+/// does not have it, or the key itself if no locale does.
+pub fn get(locale_code: &str, key: &str) -> String {
+    let strings = bundled();
+    [locale_code, FALLBACK_LOCALE]
+        .iter()
+        .find_map(|code| strings.get(*code)?.get(key).cloned())
+        .unwrap_or_else(|| key.to_string())
+}
+
+// INLINE_TEST_REQUIRED: tests read the crate-private bundled strings
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_locale_has_every_english_key() {
+        let strings = bundled();
+        let english = &strings[FALLBACK_LOCALE];
+        for (locale, texts) in &strings {
+            for key in english.keys() {
+                assert!(texts.contains_key(key), "{} is missing {}", locale, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_falls_back_to_english_then_key() {
+        assert_eq!(get("de", "print.fingerprint"), "Fingerabdruck");
+        assert_eq!(get("xx", "print.fingerprint"), "Fingerprint");
+        assert_eq!(get("de", "no.such.key"), "no.such.key");
+    }
+}
@@ -0,0 +1,352 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Device Mode
+//!
+//! A linked desktop can run as a read-only mirror, e.g. a shared
+//! living-room machine. Sync still applies everything the other devices
+//! send, but a mirror sends nothing of its own: commands that change data
+//! or queue messages are refused, immediate pushes (emergency alerts, the
+//! recovery proof) go through `sync::push_target`, which refuses too, and
+//! the dead man's switch does not run.
+//!
+//! Modes are scopes of the device registry: a map from device ID to mode,
+//! kept in the encrypted settings and shared with the other linked devices
+//! over device sync, newest change winning. Only a full device can change
+//! it, for itself or any other device, so a mirror cannot lift its own
+//! restriction, and scopes sent by a device that is a mirror are ignored.
+//! The primary device cannot be a mirror.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use vauchi_core::network::simple_message::{
+    create_simple_envelope, encode_simple_message, SimpleDeviceSyncMessage, SimplePayload,
+};
+use vauchi_core::sync::DeviceSyncOrchestrator;
+use vauchi_core::{Identity, Storage};
+
+use crate::clock;
+use crate::error::CommandError;
+use crate::secure_settings::{self, DEVICE_SCOPES};
+use crate::state::AppState;
+use crate::storage_worker;
+
+/// Settings file older versions kept this device's mode in.
+const LEGACY_SETTINGS_FILE: &str = "device_mode.json";
+
+/// How a device takes part in sync.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceMode {
+    /// Edits and sends changes.
+    #[default]
+    Full,
+    /// Applies incoming changes only.
+    Mirror,
+}
+
+/// Modes of the linked devices, as shared between them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceScopes {
+    /// Modes by device ID (hex); devices not listed are full.
+    devices: BTreeMap<String, DeviceMode>,
+    /// Unix seconds of the last change; the newest change wins.
+    updated_at: u64,
+}
+
+impl DeviceScopes {
+    fn mode(&self, device_id: &str) -> DeviceMode {
+        self.devices.get(device_id).copied().unwrap_or_default()
+    }
+
+    fn set(&mut self, device_id: &str, mode: DeviceMode, now: u64) {
+        match mode {
+            DeviceMode::Full => self.devices.remove(device_id),
+            DeviceMode::Mirror => self.devices.insert(device_id.to_string(), mode),
+        };
+        // Never behind a change already seen
+        self.updated_at = now.max(self.updated_at + 1);
+    }
+
+    /// Take `incoming` from the device `sender_id` if it is newer and the
+    /// sender may change scopes. Returns whether it was taken.
+    fn merge(&mut self, incoming: DeviceScopes, sender_id: &str) -> bool {
+        if self.mode(sender_id) == DeviceMode::Mirror || incoming.updated_at <= self.updated_at {
+            return false;
+        }
+        *self = incoming;
+        true
+    }
+}
+
+/// Wire form of the scopes in a device sync message.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename = "vauchi.device-scopes.v1", deny_unknown_fields)]
+struct ScopesMessage {
+    scopes: DeviceScopes,
+}
+
+/// The stored scopes.
+pub fn scopes(data_dir: &Path) -> Result<DeviceScopes, CommandError> {
+    Ok(secure_settings::get(data_dir, &DEVICE_SCOPES)?.unwrap_or_default())
+}
+
+fn save(data_dir: &Path, scopes: &DeviceScopes) -> Result<(), CommandError> {
+    secure_settings::set(data_dir, &DEVICE_SCOPES, Some(scopes))
+}
+
+/// The mode of `device_id`.
+pub fn mode(data_dir: &Path, device_id: &str) -> Result<DeviceMode, CommandError> {
+    Ok(scopes(data_dir)?.mode(device_id))
+}
+
+/// This device's ID (hex); `None` without an identity.
+fn own_device_id(state: &AppState) -> Option<String> {
+    state
+        .identity
+        .as_ref()
+        .map(|identity| hex::encode(identity.device_info().device_id()))
+}
+
+/// The mode of this device; full without an identity. If the scopes
+/// cannot be read, the device is treated as a mirror, so it sends nothing.
+pub fn current(state: &AppState) -> DeviceMode {
+    let Some(device_id) = own_device_id(state) else {
+        return DeviceMode::Full;
+    };
+    mode(state.data_dir(), &device_id).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read device scopes: {}", e);
+        DeviceMode::Mirror
+    })
+}
+
+/// Refuse an edit on a mirror device.
+pub fn ensure_editable(state: &AppState) -> Result<(), CommandError> {
+    let Some(device_id) = own_device_id(state) else {
+        return Ok(());
+    };
+    match mode(state.data_dir(), &device_id)? {
+        DeviceMode::Full => Ok(()),
+        DeviceMode::Mirror => Err(CommandError::Device(
+            "This device is a read-only mirror; make changes on another device".to_string(),
+        )),
+    }
+}
+
+/// Set the mode of the linked device `device_id` from this device, which
+/// must be a full device. The change reaches the other devices with the
+/// next sync.
+pub fn set_mode(state: &AppState, device_id: &str, mode: DeviceMode) -> Result<(), CommandError> {
+    ensure_editable(state)?;
+    let registry = state
+        .storage
+        .load_device_registry()
+        .map_err(|e| CommandError::Storage(format!("Failed to load device registry: {:?}", e)))?
+        .ok_or_else(|| CommandError::Device("No device registry found".to_string()))?;
+    let index = registry
+        .all_devices()
+        .iter()
+        .position(|d| hex::encode(d.device_id) == device_id && d.is_active())
+        .ok_or_else(|| CommandError::Device("Device not found".to_string()))?;
+    if mode == DeviceMode::Mirror && index == 0 {
+        return Err(CommandError::Device(
+            "The primary device cannot be a read-only mirror".to_string(),
+        ));
+    }
+
+    let mut scopes = scopes(state.data_dir())?;
+    scopes.set(device_id, mode, clock::now_secs());
+    save(state.data_dir(), &scopes)
+}
+
+/// Encrypted device sync envelopes carrying the scopes to every other
+/// active device. Empty on a mirror and while no scope was ever set.
+pub(crate) fn envelopes(
+    identity: &Identity,
+    storage: &Storage,
+    data_dir: &Path,
+) -> Result<Vec<Vec<u8>>, CommandError> {
+    let own_id = hex::encode(identity.device_id());
+    let scopes = scopes(data_dir)?;
+    if scopes.updated_at == 0 || scopes.mode(&own_id) == DeviceMode::Mirror {
+        return Ok(Vec::new());
+    }
+    let Some(registry) = storage.load_device_registry()? else {
+        return Ok(Vec::new());
+    };
+    let plaintext = serde_json::to_vec(&ScopesMessage { scopes })?;
+    let orchestrator =
+        DeviceSyncOrchestrator::new(storage, identity.create_device_info(), registry.clone());
+
+    let mut envelopes = Vec::new();
+    for device in registry.all_devices() {
+        if !device.is_active() || hex::encode(device.device_id) == own_id {
+            continue;
+        }
+        let encrypted_payload = orchestrator
+            .encrypt_for_device(&device.exchange_public_key, &plaintext)
+            .map_err(|e| CommandError::Device(format!("Failed to encrypt scopes: {:?}", e)))?;
+        let message = SimpleDeviceSyncMessage {
+            sender_device_id: own_id.clone(),
+            target_device_id: hex::encode(device.device_id),
+            encrypted_payload,
+            version: 0,
+        };
+        let envelope = create_simple_envelope(SimplePayload::DeviceSyncMessage(message));
+        envelopes.push(
+            encode_simple_message(&envelope).map_err(|e| CommandError::Network(e.to_string()))?,
+        );
+    }
+    Ok(envelopes)
+}
+
+/// Take scopes received from the device `sender_id` (hex). Returns `None`
+/// if `plaintext` is not a scopes message, so it can be read as sync
+/// items instead. `primary_id` is the primary device, which stays full
+/// whatever the sender says.
+///
+/// The scopes are saved once the sync transaction commits.
+pub(crate) fn accept(
+    data_dir: &Path,
+    sender_id: &str,
+    primary_id: Option<&str>,
+    plaintext: &[u8],
+) -> Option<Result<bool, CommandError>> {
+    let ScopesMessage { mut scopes } = serde_json::from_slice(plaintext).ok()?;
+    if let Some(primary_id) = primary_id {
+        scopes.devices.remove(primary_id);
+    }
+    let mut current = match self::scopes(data_dir) {
+        Ok(current) => current,
+        Err(e) => return Some(Err(e)),
+    };
+    if !current.merge(scopes, sender_id) {
+        return Some(Ok(false));
+    }
+    let data_dir = data_dir.to_path_buf();
+    storage_worker::after_commit(move || {
+        if let Err(e) = save(&data_dir, &current) {
+            tracing::warn!("Failed to save device scopes: {}", e);
+        }
+    });
+    Some(Ok(true))
+}
+
+fn legacy_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(LEGACY_SETTINGS_FILE)
+}
+
+/// Move the modes older versions kept in `device_mode.json` into the
+/// shared scopes, keeping mirrors mirrors.
+pub fn migrate_legacy(data_dir: &Path) -> Result<(), CommandError> {
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    struct LegacyModes {
+        devices: BTreeMap<String, DeviceMode>,
+    }
+
+    let path = legacy_path(data_dir);
+    let Ok(json) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let legacy: LegacyModes = serde_json::from_str(&json).unwrap_or_default();
+    let mut scopes = scopes(data_dir)?;
+    let now = clock::now_secs();
+    for (device_id, mode) in legacy.devices {
+        scopes.set(&device_id, mode, now);
+    }
+    save(data_dir, &scopes)?;
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private scope merging and settings
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn scopes_with(device_id: &str, mode: DeviceMode, now: u64) -> DeviceScopes {
+        let mut scopes = DeviceScopes::default();
+        scopes.set(device_id, mode, now);
+        scopes
+    }
+
+    #[test]
+    fn test_newest_change_from_a_full_device_wins() {
+        let mut scopes = scopes_with("bb", DeviceMode::Mirror, 100);
+        assert_eq!(scopes.mode("bb"), DeviceMode::Mirror);
+        assert_eq!(scopes.mode("cc"), DeviceMode::Full);
+
+        // Older changes lose
+        assert!(!scopes.merge(scopes_with("cc", DeviceMode::Mirror, 50), "aa"));
+        // The mirror cannot lift its own restriction
+        assert!(!scopes.merge(scopes_with("bb", DeviceMode::Full, 200), "bb"));
+        assert_eq!(scopes.mode("bb"), DeviceMode::Mirror);
+
+        assert!(scopes.merge(scopes_with("bb", DeviceMode::Full, 200), "aa"));
+        assert_eq!(scopes.mode("bb"), DeviceMode::Full);
+        assert!(scopes.devices.is_empty());
+    }
+
+    #[test]
+    fn test_accept_ignores_other_payloads_and_keeps_the_primary_full() {
+        let temp = TempDir::new().unwrap();
+        let _state = AppState::new(temp.path()).unwrap();
+        assert!(accept(temp.path(), "aa", None, b"[]").is_none());
+
+        let mut incoming = scopes_with("bb", DeviceMode::Mirror, 100);
+        incoming.set("00", DeviceMode::Mirror, 100);
+        let plaintext = serde_json::to_vec(&ScopesMessage { scopes: incoming }).unwrap();
+        assert!(matches!(
+            accept(temp.path(), "aa", Some("00"), &plaintext),
+            Some(Ok(true))
+        ));
+        let saved = scopes(temp.path()).unwrap();
+        assert_eq!(saved.mode("bb"), DeviceMode::Mirror);
+        assert_eq!(saved.mode("00"), DeviceMode::Full);
+    }
+
+    #[test]
+    fn test_mirror_refuses_edits() {
+        let temp = TempDir::new().unwrap();
+        let mut state = AppState::new(temp.path()).unwrap();
+        assert!(ensure_editable(&state).is_ok());
+
+        state.create_identity("Alice Smith").unwrap();
+        let device_id = hex::encode(state.identity.as_ref().unwrap().device_info().device_id());
+        save(
+            temp.path(),
+            &scopes_with(&device_id, DeviceMode::Mirror, 100),
+        )
+        .unwrap();
+        assert!(matches!(
+            ensure_editable(&state),
+            Err(CommandError::Device(_))
+        ));
+        assert!(matches!(
+            crate::commands::sync::push_target(&state),
+            Err(CommandError::Device(_))
+        ));
+        // Not even its own mode
+        assert!(matches!(
+            set_mode(&state, &device_id, DeviceMode::Full),
+            Err(CommandError::Device(_))
+        ));
+    }
+
+    #[test]
+    fn test_legacy_modes_are_migrated() {
+        let temp = TempDir::new().unwrap();
+        let _state = AppState::new(temp.path()).unwrap();
+        std::fs::write(legacy_path(temp.path()), r#"{"devices":{"bb":"mirror"}}"#).unwrap();
+
+        migrate_legacy(temp.path()).unwrap();
+        assert_eq!(mode(temp.path(), "bb").unwrap(), DeviceMode::Mirror);
+        assert!(!legacy_path(temp.path()).exists());
+    }
+}
@@ -0,0 +1,424 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Weekly Digest
+//!
+//! Keeps a short log of network changes in `digest_log.json` (contacts
+//! added, contacts whose card changed, validations received) and sums up
+//! the last seven days into a localized report. Entries older than
+//! [`RETENTION_SECS`] are dropped as new ones come in.
+//!
+//! The log sits outside the encrypted database, so it holds contact IDs
+//! only; names are looked up when the digest is built, and contacts deleted
+//! since are left out.
+//!
+//! If enabled in `digest_settings.json`, the digest is also shown as one OS
+//! notification on the chosen weekday, at most once a week. It follows the
+//! notification settings: nothing is shown when notifications are off, and
+//! during quiet hours it is queued with the other missed notifications.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{Datelike, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast::error::RecvError;
+use vauchi_core::i18n::{get_locale_info, Locale};
+
+use crate::background;
+use crate::clock;
+use crate::commands::i18n::{localized_plural, parse_locale};
+use crate::desktop_strings;
+use crate::events::{self, AppEvent};
+use crate::notifications::{self, MissedNotification};
+use crate::settings_file;
+use crate::state::AppState;
+
+/// Log file name under the data dir.
+const LOG_FILE: &str = "digest_log.json";
+
+/// Settings file for the digest schedule.
+const SETTINGS_FILE: &str = "digest_settings.json";
+
+/// A week, the span a digest covers.
+pub const WEEK_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// How long log entries are kept.
+pub const RETENTION_SECS: u64 = 5 * WEEK_SECS;
+
+/// How often the notification schedule is checked.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Serializes read-modify-writes of the log between the event listener,
+/// sync and the notification check.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Something that changed in the network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DigestChange {
+    ContactAdded { contact_id: String },
+    CardChanged { contact_id: String },
+    ValidationsReceived { count: u32 },
+}
+
+/// A logged change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestEntry {
+    pub timestamp: u64,
+    pub change: DigestChange,
+}
+
+/// The change log and when the digest was last notified.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestLog {
+    pub entries: Vec<DigestEntry>,
+    pub last_notified: Option<u64>,
+}
+
+/// Digest notification preferences.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestSettings {
+    /// Show the digest as an OS notification.
+    pub notify: bool,
+    /// Day to notify on, 0 = Monday … 6 = Sunday (local time).
+    pub weekday: u8,
+}
+
+/// A summary of one week.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WeeklyDigest {
+    /// Unix seconds the digest starts at.
+    pub since: u64,
+    pub until: u64,
+    /// Names of contacts added and not deleted since, oldest first.
+    pub new_contacts: Vec<String>,
+    /// Contacts whose card changed.
+    pub contacts_changed: u32,
+    pub validations_received: u32,
+    pub title: String,
+    /// One localized line per kind of change; empty for a quiet week.
+    pub lines: Vec<String>,
+}
+
+fn log_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(LOG_FILE)
+}
+
+/// Load the log, empty if missing or unreadable.
+pub fn load_log(data_dir: &Path) -> DigestLog {
+    std::fs::read_to_string(log_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_log(data_dir: &Path, log: &DigestLog) -> std::io::Result<()> {
+    std::fs::write(log_path(data_dir), serde_json::to_string(log)?)
+}
+
+/// The digest schedule.
+pub fn load_settings(data_dir: &Path) -> DigestSettings {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Change the digest schedule.
+pub fn save_settings(data_dir: &Path, settings: &DigestSettings) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, settings)
+}
+
+/// Log a change at `now`, dropping entries past retention.
+pub fn record(data_dir: &Path, change: DigestChange, now: u64) {
+    let _guard = LOG_LOCK.lock().unwrap();
+    let mut log = load_log(data_dir);
+    log.entries
+        .retain(|e| now.saturating_sub(e.timestamp) < RETENTION_SECS);
+    log.entries.push(DigestEntry {
+        timestamp: now,
+        change,
+    });
+    if let Err(e) = save_log(data_dir, &log) {
+        tracing::warn!("Failed to save digest log: {}", e);
+    }
+}
+
+/// Current contact names by ID, to resolve logged contact IDs.
+pub fn contact_names(state: &AppState) -> HashMap<String, String> {
+    state
+        .cached_contacts()
+        .unwrap_or_default()
+        .iter()
+        .map(|c| (c.id().to_string(), c.display_name().to_string()))
+        .collect()
+}
+
+/// Sum up the week before `now`, naming contacts from `names`.
+pub fn build(
+    log: &DigestLog,
+    now: u64,
+    locale: Locale,
+    names: &HashMap<String, String>,
+) -> WeeklyDigest {
+    let since = now.saturating_sub(WEEK_SECS);
+    let mut new_contacts = Vec::new();
+    let mut changed = HashSet::new();
+    let mut validations_received = 0u32;
+    for entry in log.entries.iter().filter(|e| e.timestamp >= since) {
+        match &entry.change {
+            DigestChange::ContactAdded { contact_id } => {
+                if let Some(name) = names.get(contact_id) {
+                    new_contacts.push(name.clone());
+                }
+            }
+            DigestChange::CardChanged { contact_id } => {
+                changed.insert(contact_id.as_str());
+            }
+            DigestChange::ValidationsReceived { count } => validations_received += count,
+        }
+    }
+
+    let mut lines = Vec::new();
+    if !new_contacts.is_empty() {
+        lines.push(localized_plural(
+            locale,
+            "digest.new_contacts",
+            new_contacts.len() as u64,
+        ));
+    }
+    if !changed.is_empty() {
+        lines.push(localized_plural(
+            locale,
+            "digest.contacts_changed",
+            changed.len() as u64,
+        ));
+    }
+    if validations_received > 0 {
+        lines.push(localized_plural(
+            locale,
+            "digest.validations_received",
+            validations_received as u64,
+        ));
+    }
+    let title = desktop_strings::get(get_locale_info(locale).code, "digest.title");
+
+    WeeklyDigest {
+        since,
+        until: now,
+        contacts_changed: changed.len() as u32,
+        new_contacts,
+        validations_received,
+        title,
+        lines,
+    }
+}
+
+/// Whether the digest notification is due at `now`: on the chosen local
+/// weekday, and not within the last six days.
+fn notification_due(settings: &DigestSettings, log: &DigestLog, now: u64) -> bool {
+    if !settings.notify {
+        return false;
+    }
+    let Some(local) = Local.timestamp_opt(now as i64, 0).single() else {
+        return false;
+    };
+    if local.weekday().num_days_from_monday() != u32::from(settings.weekday) {
+        return false;
+    }
+    log.last_notified.map_or(true, |last| {
+        now.saturating_sub(last) >= WEEK_SECS - 24 * 60 * 60
+    })
+}
+
+/// Show the digest notification if due.
+fn notify_if_due(app: &AppHandle, data_dir: &Path) {
+    let now = clock::now_secs();
+    let notification_settings = notifications::load_settings(data_dir);
+    if !notification_settings.enabled {
+        return;
+    }
+    let (locale, names) = {
+        let state = app.state::<Mutex<AppState>>();
+        let state = state.lock().unwrap();
+        (parse_locale(state.locale_code()), contact_names(&state))
+    };
+    let digest = {
+        let _guard = LOG_LOCK.lock().unwrap();
+        let mut log = load_log(data_dir);
+        if !notification_due(&load_settings(data_dir), &log, now) {
+            return;
+        }
+        let digest = build(&log, now, locale, &names);
+        log.last_notified = Some(now);
+        if let Err(e) = save_log(data_dir, &log) {
+            tracing::warn!("Failed to save digest log: {}", e);
+        }
+        digest
+    };
+    // A quiet week is not worth a notification
+    if digest.lines.is_empty() {
+        return;
+    }
+    let body = digest.lines.join("\n");
+    if notifications::in_quiet_hours(&notification_settings, now) {
+        let missed = MissedNotification {
+            timestamp: now,
+            title: digest.title,
+            body,
+        };
+        if let Err(e) = notifications::queue_missed(data_dir, missed) {
+            tracing::warn!("Failed to queue missed notification: {}", e);
+        }
+    } else if let Err(e) = app
+        .notification()
+        .builder()
+        .title(digest.title)
+        .body(body)
+        .show()
+    {
+        tracing::warn!("Failed to show digest notification: {}", e);
+    }
+}
+
+/// Rewrite the log without contact names stored by earlier versions.
+fn scrub_names(data_dir: &Path) {
+    let _guard = LOG_LOCK.lock().unwrap();
+    let Ok(raw) = std::fs::read_to_string(log_path(data_dir)) else {
+        return;
+    };
+    if !raw.contains("\"display_name\"") {
+        return;
+    }
+    if let Err(e) = save_log(data_dir, &load_log(data_dir)) {
+        tracing::warn!("Failed to save digest log: {}", e);
+    }
+}
+
+/// Start logging added contacts and checking the notification schedule.
+/// Card changes and validations are logged by sync directly.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    scrub_names(&data_dir);
+    let mut rx = events::subscribe();
+    let log_dir = data_dir.clone();
+    background::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(AppEvent::ContactAdded { contact_id, .. }) => record(
+                    &log_dir,
+                    DigestChange::ContactAdded { contact_id },
+                    clock::now_secs(),
+                ),
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+    background::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            notify_if_due(&app, &data_dir);
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private log handling and scheduling
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn test_digest_counts_the_last_week() {
+        let temp = TempDir::new().unwrap();
+        let changed = |id: &str| DigestChange::CardChanged {
+            contact_id: id.to_string(),
+        };
+        record(temp.path(), changed("old"), NOW - WEEK_SECS - 1);
+        let added = |id: &str| DigestChange::ContactAdded {
+            contact_id: id.to_string(),
+        };
+        record(temp.path(), added("a"), NOW - 10);
+        record(temp.path(), added("deleted"), NOW - 9);
+        record(temp.path(), changed("a"), NOW - 5);
+        record(temp.path(), changed("a"), NOW - 4);
+        record(temp.path(), changed("b"), NOW - 3);
+        record(
+            temp.path(),
+            DigestChange::ValidationsReceived { count: 2 },
+            NOW,
+        );
+
+        let names = HashMap::from([("a".to_string(), "Alice".to_string())]);
+        let digest = build(&load_log(temp.path()), NOW, Locale::English, &names);
+        assert_eq!(digest.new_contacts, vec!["Alice"]);
+        assert_eq!(digest.contacts_changed, 2);
+        assert_eq!(digest.validations_received, 2);
+        assert_eq!(digest.title, "Your week in Vauchi");
+        assert_eq!(
+            digest.lines,
+            vec![
+                "1 new contact",
+                "2 contacts changed their cards",
+                "2 field validations received"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scrub_drops_names_from_old_logs() {
+        let temp = TempDir::new().unwrap();
+        let old = r#"{"entries":[{"timestamp":1,"change":{"type":"contact_added","contact_id":"a","display_name":"Alice"}}]}"#;
+        std::fs::write(log_path(temp.path()), old).unwrap();
+
+        scrub_names(temp.path());
+
+        let raw = std::fs::read_to_string(log_path(temp.path())).unwrap();
+        assert!(!raw.contains("Alice"));
+        assert_eq!(
+            load_log(temp.path()).entries[0].change,
+            DigestChange::ContactAdded {
+                contact_id: "a".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_old_entries_are_dropped() {
+        let temp = TempDir::new().unwrap();
+        let change = DigestChange::ValidationsReceived { count: 1 };
+        record(temp.path(), change.clone(), NOW - RETENTION_SECS);
+        record(temp.path(), change, NOW);
+        assert_eq!(load_log(temp.path()).entries.len(), 1);
+    }
+
+    #[test]
+    fn test_notification_once_a_week_on_the_chosen_day() {
+        let weekday = Local
+            .timestamp_opt(NOW as i64, 0)
+            .unwrap()
+            .weekday()
+            .num_days_from_monday() as u8;
+        let mut settings = DigestSettings {
+            notify: true,
+            weekday,
+        };
+        let mut log = DigestLog::default();
+        assert!(notification_due(&settings, &log, NOW));
+
+        log.last_notified = Some(NOW - 60 * 60);
+        assert!(!notification_due(&settings, &log, NOW));
+
+        log.last_notified = None;
+        settings.weekday = (weekday + 1) % 7;
+        assert!(!notification_due(&settings, &log, NOW));
+    }
+}
@@ -0,0 +1,357 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Emergency Escalation Tiers
+//!
+//! Trusted contacts can be ordered in tiers (`emergency_tiers.json`; core's
+//! emergency config only holds the flat contact list). A broadcast notifies
+//! the first tier right away; each later tier is notified once its
+//! `escalate_after_mins` have passed since the previous tier without any
+//! recipient acknowledging the alert. Without tiers, all trusted contacts
+//! form a single tier.
+//!
+//! The running broadcast is kept in `emergency_broadcast.json` so
+//! escalation survives restarts; a background loop advances it. Before
+//! notifying a later tier, the loop syncs, so acknowledgements waiting on
+//! the relay stop the escalation.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::background;
+use crate::clock;
+use crate::commands::emergency::{self, RecipientDelivery};
+use crate::commands::sync;
+use crate::emergency_sync::CoarseLocation;
+use crate::state::AppState;
+
+/// Tier configuration file name under the data dir.
+const TIERS_FILE: &str = "emergency_tiers.json";
+
+/// Running broadcast file name under the data dir.
+const BROADCAST_FILE: &str = "emergency_broadcast.json";
+
+/// How often the background loop looks for due tiers.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A group of trusted contacts notified together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmergencyTier {
+    pub contact_ids: Vec<String>,
+    /// Minutes after the previous tier without acknowledgement before this
+    /// tier is notified. Ignored for the first tier.
+    pub escalate_after_mins: u32,
+}
+
+/// Progress of one tier of a running broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TierProgress {
+    pub contact_ids: Vec<String>,
+    pub escalate_after_mins: u32,
+    /// When the tier was notified, if it was.
+    pub notified_at: Option<u64>,
+    pub recipients: Vec<RecipientDelivery>,
+}
+
+/// An acknowledgement of our alert.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    pub contact_id: String,
+    pub acknowledged_at: u64,
+}
+
+/// A running broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveBroadcast {
+    pub alert_id: String,
+    pub started_at: u64,
+    pub location: Option<CoarseLocation>,
+    pub tiers: Vec<TierProgress>,
+    pub acknowledgements: Vec<Acknowledgement>,
+}
+
+impl ActiveBroadcast {
+    /// Start tracking a broadcast over the given tiers.
+    pub fn new(
+        alert_id: &str,
+        started_at: u64,
+        location: Option<CoarseLocation>,
+        tiers: Vec<EmergencyTier>,
+    ) -> Self {
+        Self {
+            alert_id: alert_id.to_string(),
+            started_at,
+            location,
+            tiers: tiers
+                .into_iter()
+                .map(|tier| TierProgress {
+                    contact_ids: tier.contact_ids,
+                    escalate_after_mins: tier.escalate_after_mins,
+                    notified_at: None,
+                    recipients: Vec::new(),
+                })
+                .collect(),
+            acknowledgements: Vec::new(),
+        }
+    }
+
+    /// Index of the tier to notify now, if any.
+    pub fn due_tier(&self, now: u64) -> Option<usize> {
+        if !self.acknowledgements.is_empty() {
+            return None;
+        }
+        let next = self.tiers.iter().position(|t| t.notified_at.is_none())?;
+        if next == 0 {
+            return Some(0);
+        }
+        let previous = self.tiers[next - 1].notified_at?;
+        let delay = u64::from(self.tiers[next].escalate_after_mins) * 60;
+        (now >= previous.saturating_add(delay)).then_some(next)
+    }
+}
+
+fn tiers_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(TIERS_FILE)
+}
+
+/// Configured tiers; empty if none.
+pub fn load_tiers(data_dir: &Path) -> Vec<EmergencyTier> {
+    std::fs::read_to_string(tiers_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Save the tiers; an empty list removes them.
+pub fn save_tiers(data_dir: &Path, tiers: &[EmergencyTier]) -> std::io::Result<()> {
+    if tiers.is_empty() {
+        return match std::fs::remove_file(tiers_path(data_dir)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    std::fs::write(tiers_path(data_dir), serde_json::to_string(tiers)?)
+}
+
+/// The tiers to broadcast over: the configured ones, limited to trusted
+/// contacts, or all trusted contacts as a single tier.
+pub fn effective_tiers(
+    trusted_contact_ids: &[String],
+    tiers: &[EmergencyTier],
+) -> Vec<EmergencyTier> {
+    let tiers: Vec<EmergencyTier> = tiers
+        .iter()
+        .map(|tier| EmergencyTier {
+            contact_ids: tier
+                .contact_ids
+                .iter()
+                .filter(|id| trusted_contact_ids.contains(id))
+                .cloned()
+                .collect(),
+            escalate_after_mins: tier.escalate_after_mins,
+        })
+        .filter(|tier| !tier.contact_ids.is_empty())
+        .collect();
+    if tiers.is_empty() {
+        return vec![EmergencyTier {
+            contact_ids: trusted_contact_ids.to_vec(),
+            escalate_after_mins: 0,
+        }];
+    }
+    tiers
+}
+
+fn broadcast_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(BROADCAST_FILE)
+}
+
+/// The running broadcast, if any.
+pub fn load_broadcast(data_dir: &Path) -> Option<ActiveBroadcast> {
+    std::fs::read_to_string(broadcast_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// Save the running broadcast.
+pub fn save_broadcast(data_dir: &Path, broadcast: &ActiveBroadcast) -> std::io::Result<()> {
+    std::fs::write(broadcast_path(data_dir), serde_json::to_string(broadcast)?)
+}
+
+/// Stop tracking the running broadcast, ending escalation.
+pub fn clear_broadcast(data_dir: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(broadcast_path(data_dir)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Record a contact's acknowledgement of our running broadcast. Ignored
+/// for other alerts or contacts we did not notify.
+pub fn record_acknowledgement(
+    data_dir: &Path,
+    alert_id: &str,
+    contact_id: &str,
+    acknowledged_at: u64,
+) -> std::io::Result<()> {
+    let Some(mut broadcast) = load_broadcast(data_dir) else {
+        return Ok(());
+    };
+    let notified = broadcast
+        .tiers
+        .iter()
+        .any(|t| t.notified_at.is_some() && t.contact_ids.iter().any(|id| id == contact_id));
+    if broadcast.alert_id != alert_id
+        || !notified
+        || broadcast
+            .acknowledgements
+            .iter()
+            .any(|a| a.contact_id == contact_id)
+    {
+        return Ok(());
+    }
+    broadcast.acknowledgements.push(Acknowledgement {
+        contact_id: contact_id.to_string(),
+        acknowledged_at,
+    });
+    save_broadcast(data_dir, &broadcast)
+}
+
+/// Notify the next tier if it is due and nobody has acknowledged yet.
+async fn run_once(app: &AppHandle, data_dir: &Path) {
+    let Some(mut broadcast) = load_broadcast(data_dir) else {
+        return;
+    };
+    let Some(mut index) = broadcast.due_tier(clock::now_secs()) else {
+        return;
+    };
+    let state = app.state::<Mutex<AppState>>();
+    if index > 0 {
+        // Pick up acknowledgements from the relay first
+        if let Err(e) = sync::sync_impl(&state).await {
+            tracing::warn!("Sync before emergency escalation failed: {}", e);
+        }
+        let Some(current) = load_broadcast(data_dir) else {
+            return;
+        };
+        let Some(due) = current.due_tier(clock::now_secs()) else {
+            return;
+        };
+        broadcast = current;
+        index = due;
+    }
+    let tier = &broadcast.tiers[index];
+    let notified = emergency::notify_tier(
+        &state,
+        broadcast.started_at,
+        &tier.contact_ids,
+        broadcast.location,
+        false,
+    )
+    .await;
+    let recipients = match notified {
+        Ok((recipients, _)) => recipients,
+        Err(e) => {
+            tracing::warn!("Emergency escalation failed: {}", e);
+            return;
+        }
+    };
+    tracing::warn!("Emergency broadcast escalated to tier {}", index + 1);
+
+    // Re-read: acknowledgements may have arrived meanwhile
+    let Some(mut current) = load_broadcast(data_dir) else {
+        return;
+    };
+    if current.alert_id != broadcast.alert_id {
+        return;
+    }
+    current.tiers[index].notified_at = Some(clock::now_secs());
+    current.tiers[index].recipients = recipients;
+    if let Err(e) = save_broadcast(data_dir, &current) {
+        tracing::warn!("Failed to save emergency broadcast: {}", e);
+    }
+}
+
+/// Start the background escalation loop.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    background::spawn(async move {
+        loop {
+            run_once(&app, &data_dir).await;
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tier(ids: &[&str], mins: u32) -> EmergencyTier {
+        EmergencyTier {
+            contact_ids: ids.iter().map(|id| id.to_string()).collect(),
+            escalate_after_mins: mins,
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_effective_tiers_fall_back_to_single_tier() {
+        let trusted = ids(&["a", "b"]);
+        assert_eq!(effective_tiers(&trusted, &[]), vec![tier(&["a", "b"], 0)]);
+        assert_eq!(
+            effective_tiers(&trusted, &[tier(&["a", "gone"], 0), tier(&["gone"], 5)]),
+            vec![tier(&["a"], 0)]
+        );
+    }
+
+    #[test]
+    fn test_later_tiers_wait_for_delay_and_stop_on_acknowledgement() {
+        let mut broadcast =
+            ActiveBroadcast::new("a1", 0, None, vec![tier(&["a"], 0), tier(&["b"], 10)]);
+        assert_eq!(broadcast.due_tier(0), Some(0));
+
+        broadcast.tiers[0].notified_at = Some(100);
+        assert_eq!(broadcast.due_tier(100 + 599), None);
+        assert_eq!(broadcast.due_tier(100 + 600), Some(1));
+
+        broadcast.acknowledgements.push(Acknowledgement {
+            contact_id: "a".to_string(),
+            acknowledged_at: 200,
+        });
+        assert_eq!(broadcast.due_tier(10_000), None);
+    }
+
+    #[test]
+    fn test_acknowledgements_only_count_for_notified_contacts() {
+        let temp = TempDir::new().unwrap();
+        let mut broadcast =
+            ActiveBroadcast::new("a1", 0, None, vec![tier(&["a"], 0), tier(&["b"], 10)]);
+        broadcast.tiers[0].notified_at = Some(1);
+        save_broadcast(temp.path(), &broadcast).unwrap();
+
+        record_acknowledgement(temp.path(), "other", "a", 5).unwrap();
+        record_acknowledgement(temp.path(), "a1", "b", 5).unwrap();
+        assert!(load_broadcast(temp.path())
+            .unwrap()
+            .acknowledgements
+            .is_empty());
+
+        record_acknowledgement(temp.path(), "a1", "a", 5).unwrap();
+        record_acknowledgement(temp.path(), "a1", "a", 6).unwrap();
+        assert_eq!(
+            load_broadcast(temp.path()).unwrap().acknowledgements,
+            vec![Acknowledgement {
+                contact_id: "a".to_string(),
+                acknowledged_at: 5,
+            }]
+        );
+    }
+}
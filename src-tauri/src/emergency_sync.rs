@@ -0,0 +1,389 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Emergency Alert Sync
+//!
+//! Emergency alerts travel like any other update: encrypted with the
+//! recipient's ratchet and queued as a pending `card_delta`, so they are
+//! indistinguishable from card updates on the wire. Triggering a broadcast
+//! pushes the queued alerts to the relay right away instead of waiting for
+//! the next sync; alerts that cannot be pushed stay queued.
+//!
+//! Real alerts carry core's `EmergencyAlert`, so every Vauchi client can
+//! read them and desktop reads theirs. An alert is identified by its
+//! sender and timestamp, which all tiers of a broadcast share. Test alerts
+//! and acknowledgements have no core type; they are tagged desktop
+//! messages other clients ignore, so a test never alarms anyone.
+//!
+//! Inbound: during sync, `ratchet_messages` decrypts incoming updates and
+//! hands emergency messages to [`receive`]. Alerts are kept in
+//! `emergency_alerts.json` until acknowledged and announced on the event
+//! bus; `start` forwards them to the frontend as `emergency://alert`.
+//! Acknowledging an alert sends an acknowledgement back to its sender,
+//! which stops their broadcast from escalating further.
+
+use std::path::{Path, PathBuf};
+
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+use vauchi_core::network::EmergencyAlert;
+use vauchi_core::{Contact, PendingUpdate, Storage};
+
+use crate::background;
+use crate::clock;
+use crate::emergency_escalation;
+use crate::events::{self, AppEvent};
+use crate::ratchet_messages;
+use crate::storage_worker;
+
+/// Received alerts file name under the data dir.
+const ALERTS_FILE: &str = "emergency_alerts.json";
+
+/// Event sent to the frontend when an alert arrives.
+pub const ALERT_EVENT: &str = "emergency://alert";
+
+/// Decimal places kept of a shared location (about 1 km).
+const LOCATION_PRECISION: i32 = 2;
+
+/// A location rounded so it only reveals the area, not the address.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CoarseLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl CoarseLocation {
+    /// Round a precise location down to the shared precision.
+    pub fn coarse(self) -> Self {
+        let factor = 10f64.powi(LOCATION_PRECISION);
+        Self {
+            latitude: (self.latitude * factor).round() / factor,
+            longitude: (self.longitude * factor).round() / factor,
+        }
+    }
+
+    /// The `latitude,longitude` form of core's alert location.
+    fn to_wire(self) -> String {
+        format!("{},{}", self.latitude, self.longitude)
+    }
+
+    fn from_wire(location: &str) -> Option<Self> {
+        let (latitude, longitude) = location.split_once(',')?;
+        Some(Self {
+            latitude: latitude.trim().parse().ok()?,
+            longitude: longitude.trim().parse().ok()?,
+        })
+        .map(Self::coarse)
+    }
+}
+
+/// Desktop messages without a core type, tagged so they cannot be mistaken
+/// for an alert or a card delta.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", deny_unknown_fields)]
+enum DesktopMessage {
+    /// A test of the sender's setup; recipients must not treat it as real.
+    #[serde(rename = "vauchi.emergency-test.v1")]
+    Test {
+        message: String,
+        location: Option<CoarseLocation>,
+        sent_at: u64,
+    },
+    /// The sender saw the recipient's alert.
+    #[serde(rename = "vauchi.emergency-ack.v1")]
+    Acknowledgement {
+        alert_id: String,
+        acknowledged_at: u64,
+    },
+}
+
+/// Plaintext of an emergency update.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum EmergencyMessage {
+    Desktop(DesktopMessage),
+    Alert(EmergencyAlert),
+}
+
+/// An emergency alert received from a contact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncomingEmergencyAlert {
+    pub alert_id: String,
+    pub contact_id: String,
+    pub display_name: String,
+    pub message: String,
+    pub location: Option<CoarseLocation>,
+    pub sent_at: u64,
+    pub received_at: u64,
+    /// A test by the sender; nothing is wrong.
+    pub test: bool,
+    pub acknowledged_at: Option<u64>,
+}
+
+/// The ID of the alert `sender_id` sent at `sent_at`, shared by all
+/// recipients and tiers of one broadcast.
+pub fn alert_id(sender_id: &str, sent_at: u64) -> String {
+    let hash = digest(&SHA256, format!("{}:{}", sender_id, sent_at).as_bytes());
+    hex::encode(&hash.as_ref()[..16])
+}
+
+/// Encrypt an alert for a contact and queue it as a pending update. Test
+/// alerts go through the same pipeline but are marked as tests.
+///
+/// Returns the queued update, or `None` if there is no secure session with
+/// the contact yet.
+pub fn queue_alert(
+    storage: &Storage,
+    contact_id: &str,
+    sender_id: &str,
+    sent_at: u64,
+    message: &str,
+    location: Option<CoarseLocation>,
+    test: bool,
+) -> Result<Option<PendingUpdate>, String> {
+    let location = location.map(CoarseLocation::coarse);
+    let plaintext = if test {
+        serde_json::to_vec(&DesktopMessage::Test {
+            message: message.to_string(),
+            location,
+            sent_at,
+        })
+    } else {
+        serde_json::to_vec(&EmergencyAlert {
+            sender_id: sender_id.to_string(),
+            message: message.to_string(),
+            timestamp: sent_at,
+            location: location.map(CoarseLocation::to_wire),
+        })
+    }
+    .map_err(|e| e.to_string())?;
+    ratchet_messages::queue(storage, contact_id, &plaintext)
+}
+
+/// Queue an acknowledgement of a contact's alert, telling them help is on
+/// the way so their broadcast stops escalating.
+pub fn queue_acknowledgement(
+    storage: &Storage,
+    contact_id: &str,
+    alert_id: &str,
+    acknowledged_at: u64,
+) -> Result<Option<PendingUpdate>, String> {
+    let ack = DesktopMessage::Acknowledgement {
+        alert_id: alert_id.to_string(),
+        acknowledged_at,
+    };
+    let plaintext = serde_json::to_vec(&ack).map_err(|e| e.to_string())?;
+    ratchet_messages::queue(storage, contact_id, &plaintext)
+}
+
+fn alerts_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(ALERTS_FILE)
+}
+
+/// Received alerts, oldest first.
+pub fn load_alerts(data_dir: &Path) -> Vec<IncomingEmergencyAlert> {
+    std::fs::read_to_string(alerts_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_alerts(data_dir: &Path, alerts: &[IncomingEmergencyAlert]) -> std::io::Result<()> {
+    std::fs::write(alerts_path(data_dir), serde_json::to_string(alerts)?)
+}
+
+/// Store a received alert. Returns `false` if it was already stored.
+fn add_alert(data_dir: &Path, alert: IncomingEmergencyAlert) -> std::io::Result<bool> {
+    let mut alerts = load_alerts(data_dir);
+    if alerts
+        .iter()
+        .any(|a| a.alert_id == alert.alert_id && a.contact_id == alert.contact_id)
+    {
+        return Ok(false);
+    }
+    alerts.push(alert);
+    save_alerts(data_dir, &alerts)?;
+    Ok(true)
+}
+
+/// Mark an alert as acknowledged. Returns `false` if there is no such alert.
+pub fn acknowledge(data_dir: &Path, alert_id: &str, now: u64) -> std::io::Result<bool> {
+    let mut alerts = load_alerts(data_dir);
+    let mut found = false;
+    for alert in alerts.iter_mut().filter(|a| a.alert_id == alert_id) {
+        alert.acknowledged_at.get_or_insert(now);
+        found = true;
+    }
+    if found {
+        save_alerts(data_dir, &alerts)?;
+    }
+    Ok(found)
+}
+
+/// Decode a decrypted update as an emergency message.
+pub(crate) fn decode_message(plaintext: &[u8]) -> Option<EmergencyMessage> {
+    serde_json::from_slice(plaintext).ok()
+}
+
+/// Handle an emergency message from `contact`: store and announce a new
+/// alert, or record an acknowledgement. Returns whether it was an alert.
+pub(crate) fn receive(data_dir: &Path, contact: &Contact, message: EmergencyMessage) -> bool {
+    let sender_id = contact.id().to_string();
+    let (alert_id, message, location, sent_at, test) = match message {
+        EmergencyMessage::Alert(alert) => (
+            alert_id(&alert.sender_id, alert.timestamp),
+            alert.message,
+            alert
+                .location
+                .as_deref()
+                .and_then(CoarseLocation::from_wire),
+            alert.timestamp,
+            false,
+        ),
+        EmergencyMessage::Desktop(DesktopMessage::Test {
+            message,
+            location,
+            sent_at,
+        }) => (
+            alert_id(&sender_id, sent_at),
+            message,
+            location.map(CoarseLocation::coarse),
+            sent_at,
+            true,
+        ),
+        EmergencyMessage::Desktop(DesktopMessage::Acknowledgement {
+            alert_id,
+            acknowledged_at,
+        }) => {
+            let data_dir = data_dir.to_path_buf();
+            storage_worker::after_commit(move || {
+                if let Err(e) = emergency_escalation::record_acknowledgement(
+                    &data_dir,
+                    &alert_id,
+                    &sender_id,
+                    acknowledged_at,
+                ) {
+                    tracing::warn!("Failed to record emergency acknowledgement: {}", e);
+                }
+            });
+            return false;
+        }
+    };
+    let alert = IncomingEmergencyAlert {
+        alert_id,
+        contact_id: sender_id,
+        display_name: contact.display_name().to_string(),
+        message,
+        location,
+        sent_at,
+        received_at: clock::now_secs(),
+        test,
+        acknowledged_at: None,
+    };
+    let data_dir = data_dir.to_path_buf();
+    storage_worker::after_commit(move || match add_alert(&data_dir, alert.clone()) {
+        Ok(true) => events::publish(AppEvent::EmergencyAlertReceived {
+            alert_id: alert.alert_id,
+            contact_id: alert.contact_id,
+            display_name: alert.display_name,
+            message: alert.message,
+            test: alert.test,
+        }),
+        Ok(false) => {}
+        Err(e) => tracing::warn!("Failed to store emergency alert: {}", e),
+    });
+    true
+}
+
+/// Forward received alerts from the event bus to the frontend.
+pub fn start(app: AppHandle) {
+    let mut rx = events::subscribe();
+    background::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            if let AppEvent::EmergencyAlertReceived { .. } = event {
+                if let Err(e) = app.emit(ALERT_EVENT, &event) {
+                    tracing::warn!("Failed to emit emergency alert event: {}", e);
+                }
+            }
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private wire format
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coarse_location_rounds_to_area() {
+        let precise = CoarseLocation {
+            latitude: 47.376_887,
+            longitude: 8.541_694,
+        };
+        assert_eq!(
+            precise.coarse(),
+            CoarseLocation {
+                latitude: 47.38,
+                longitude: 8.54,
+            }
+        );
+    }
+
+    #[test]
+    fn test_location_survives_the_core_alert() {
+        let location = CoarseLocation {
+            latitude: 47.38,
+            longitude: -8.54,
+        };
+        assert_eq!(
+            CoarseLocation::from_wire(&location.to_wire()),
+            Some(location)
+        );
+        assert_eq!(CoarseLocation::from_wire("somewhere"), None);
+    }
+
+    #[test]
+    fn test_core_alert_is_read_as_real_alert() {
+        let alert = EmergencyAlert {
+            sender_id: "bob".to_string(),
+            message: "help".to_string(),
+            timestamp: 1,
+            location: None,
+        };
+        let json = serde_json::to_vec(&alert).unwrap();
+        assert!(matches!(
+            serde_json::from_slice(&json).unwrap(),
+            EmergencyMessage::Alert(a) if a.message == "help"
+        ));
+    }
+
+    #[test]
+    fn test_desktop_messages_are_tagged() {
+        let test = DesktopMessage::Test {
+            message: "help".to_string(),
+            location: None,
+            sent_at: 1,
+        };
+        let json = serde_json::to_value(&test).unwrap();
+        assert_eq!(json["kind"], "vauchi.emergency-test.v1");
+        assert!(matches!(
+            serde_json::from_value(json).unwrap(),
+            EmergencyMessage::Desktop(decoded) if decoded == test
+        ));
+    }
+
+    #[test]
+    fn test_alert_id_is_shared_by_a_broadcast() {
+        assert_eq!(alert_id("bob", 1), alert_id("bob", 1));
+        assert_ne!(alert_id("bob", 1), alert_id("bob", 2));
+        assert_ne!(alert_id("bob", 1), alert_id("carol", 1));
+    }
+}
@@ -0,0 +1,341 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Environment Checks
+//!
+//! Sanity checks of the machine the app runs on: whether the OS keychain
+//! keeps keys, the webview (WebKitGTK on Linux) version, the system clock
+//! against an NTP server, and whether the bundled locale files are there.
+//! A wrong clock in particular makes QR codes look expired and signatures
+//! fail in ways that are hard to trace back.
+//!
+//! The checks run once on first launch; the report is kept in
+//! `environment_report.json` and can be refreshed on demand. The NTP query
+//! contacts a third party, so it only runs on a refresh, only when the
+//! user turned it on in `clock_check.json` and never in Tor mode (see
+//! [`clock_skew::ntp_allowed`]); the first-launch run makes no network
+//! requests.
+
+use std::net::UdpSocket;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::clock_skew;
+use crate::commands::troubleshoot::{CheckStatus, MAX_CLOCK_SKEW_SECS};
+
+/// Report file name under the data dir.
+const REPORT_FILE: &str = "environment_report.json";
+
+/// NTP server queried for the clock check.
+const NTP_SERVER: &str = "pool.ntp.org:123";
+
+/// NTP queries give up after this long.
+const NTP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
+/// Oldest WebKitGTK release known to render the app correctly.
+#[cfg(target_os = "linux")]
+const MIN_WEBKITGTK: (u32, u32) = (2, 40);
+
+/// Result of one check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentCheck {
+    pub check: String,
+    pub status: CheckStatus,
+    pub finding: String,
+    pub suggestion: Option<String>,
+}
+
+impl EnvironmentCheck {
+    fn new(check: &str, status: CheckStatus, finding: String) -> Self {
+        Self {
+            check: check.to_string(),
+            status,
+            finding,
+            suggestion: None,
+        }
+    }
+
+    fn suggest(mut self, suggestion: &str) -> Self {
+        self.suggestion = Some(suggestion.to_string());
+        self
+    }
+}
+
+/// All checks of one run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentReport {
+    /// Unix seconds the checks ran at (local clock).
+    pub checked_at: u64,
+    pub checks: Vec<EnvironmentCheck>,
+    /// Whether any check found an error.
+    pub has_errors: bool,
+}
+
+fn report_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(REPORT_FILE)
+}
+
+/// The last saved report, if the checks ever ran.
+pub fn load(data_dir: &Path) -> Option<EnvironmentReport> {
+    std::fs::read_to_string(report_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+fn save(data_dir: &Path, report: &EnvironmentReport) -> std::io::Result<()> {
+    std::fs::write(report_path(data_dir), serde_json::to_string_pretty(report)?)
+}
+
+/// Seconds the NTP server's clock is ahead of ours.
+pub fn ntp_offset_secs() -> Result<i64, String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+    socket
+        .set_read_timeout(Some(NTP_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    // SNTP v4 client request: LI 0, version 4, mode 3
+    let mut packet = [0u8; 48];
+    packet[0] = 0x23;
+    socket
+        .send_to(&packet, NTP_SERVER)
+        .map_err(|e| e.to_string())?;
+    let sent = clock::now_secs();
+    let (len, _) = socket.recv_from(&mut packet).map_err(|e| e.to_string())?;
+    server_offset(&packet[..len], sent, clock::now_secs())
+}
+
+/// Offset from an SNTP reply's transmit timestamp, against the midpoint of
+/// our send and receive times.
+fn server_offset(reply: &[u8], sent: u64, received: u64) -> Result<i64, String> {
+    let seconds = reply
+        .get(40..44)
+        .ok_or_else(|| "Short NTP reply".to_string())?;
+    let ntp_secs = u64::from(u32::from_be_bytes([
+        seconds[0], seconds[1], seconds[2], seconds[3],
+    ]));
+    if ntp_secs < NTP_UNIX_OFFSET {
+        return Err("Invalid NTP timestamp".to_string());
+    }
+    let local = (sent + received) / 2;
+    Ok((ntp_secs - NTP_UNIX_OFFSET) as i64 - local as i64)
+}
+
+/// Clock check from an NTP offset, or `None` when NTP queries are off.
+fn clock_check(offset: Option<Result<i64, String>>) -> EnvironmentCheck {
+    let Some(offset) = offset else {
+        return EnvironmentCheck::new(
+            "clock",
+            CheckStatus::Skipped,
+            "Network time check is off".to_string(),
+        );
+    };
+    match offset {
+        Err(e) => EnvironmentCheck::new(
+            "clock",
+            CheckStatus::Skipped,
+            format!("Could not reach a time server: {}", e),
+        ),
+        Ok(skew) if skew.abs() > MAX_CLOCK_SKEW_SECS => EnvironmentCheck::new(
+            "clock",
+            CheckStatus::Error,
+            format!("Your clock is off by {} seconds", skew.abs()),
+        )
+        .suggest("Turn on automatic date and time in your system settings."),
+        Ok(skew) => EnvironmentCheck::new(
+            "clock",
+            CheckStatus::Ok,
+            format!("Clock is within {} seconds of network time", skew.abs()),
+        ),
+    }
+}
+
+/// Whether the OS keychain stores and returns a probe key.
+#[cfg(feature = "secure-storage")]
+fn keychain_check(data_dir: &Path) -> EnvironmentCheck {
+    use vauchi_core::storage::secure::{PlatformKeyring, SecureStorage};
+
+    const PROBE: &str = "environment_probe";
+    let keyring = PlatformKeyring::new(&crate::state::AppState::keyring_service_name(data_dir));
+    let probe = [0x5a_u8; 32];
+    let works = keyring.save_key(PROBE, &probe).is_ok()
+        && matches!(keyring.load_key(PROBE), Ok(Some(bytes)) if bytes == probe);
+    let _ = keyring.delete_key(PROBE);
+
+    if works {
+        EnvironmentCheck::new(
+            "keychain",
+            CheckStatus::Ok,
+            "OS keychain is available".to_string(),
+        )
+    } else {
+        EnvironmentCheck::new(
+            "keychain",
+            CheckStatus::Warning,
+            "OS keychain does not keep keys; the storage key is kept in an encrypted file"
+                .to_string(),
+        )
+        .suggest("On Linux, install and unlock a Secret Service provider such as GNOME Keyring.")
+    }
+}
+
+#[cfg(not(feature = "secure-storage"))]
+fn keychain_check(_data_dir: &Path) -> EnvironmentCheck {
+    EnvironmentCheck::new(
+        "keychain",
+        CheckStatus::Ok,
+        "Built without OS keychain support; the storage key is kept in an encrypted file"
+            .to_string(),
+    )
+}
+
+/// Parse the major and minor version of a version string like `2.44.1`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn major_minor(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+fn webview_check(version: Result<String, String>) -> EnvironmentCheck {
+    let version = match version {
+        Ok(version) => version,
+        Err(e) => {
+            return EnvironmentCheck::new(
+                "webview",
+                CheckStatus::Error,
+                format!("Could not determine the webview version: {}", e),
+            )
+            .suggest("Install or update the system webview (WebKitGTK, WebView2 or WebKit).")
+        }
+    };
+    #[cfg(target_os = "linux")]
+    if major_minor(&version).is_some_and(|v| v < MIN_WEBKITGTK) {
+        return EnvironmentCheck::new(
+            "webview",
+            CheckStatus::Warning,
+            format!(
+                "WebKitGTK {} is older than {}.{}",
+                version, MIN_WEBKITGTK.0, MIN_WEBKITGTK.1
+            ),
+        )
+        .suggest("Update WebKitGTK through your distribution's package manager.");
+    }
+    EnvironmentCheck::new("webview", CheckStatus::Ok, format!("Webview {}", version))
+}
+
+fn locales_check(locales_dir: &Path) -> EnvironmentCheck {
+    let count = std::fs::read_dir(locales_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                .count()
+        })
+        .unwrap_or(0);
+    if count == 0 {
+        EnvironmentCheck::new(
+            "locales",
+            CheckStatus::Error,
+            format!("No locale files found in {}", locales_dir.display()),
+        )
+        .suggest("Reinstall the app; the interface falls back to untranslated keys.")
+    } else {
+        EnvironmentCheck::new(
+            "locales",
+            CheckStatus::Ok,
+            format!("{} locale files found", count),
+        )
+    }
+}
+
+/// Run all checks and save the report. With `query_ntp`, blocks on an NTP
+/// query for the clock check.
+pub fn run(data_dir: &Path, locales_dir: &Path, query_ntp: bool) -> EnvironmentReport {
+    let offset = query_ntp.then(ntp_offset_secs);
+    if let Some(Ok(skew)) = offset {
+        clock_skew::record(skew, "ntp");
+    }
+    let checks = vec![
+        keychain_check(data_dir),
+        webview_check(tauri::webview_version().map_err(|e| e.to_string())),
+        clock_check(offset),
+        locales_check(locales_dir),
+    ];
+    let report = EnvironmentReport {
+        checked_at: clock::now_secs(),
+        has_errors: checks.iter().any(|c| c.status == CheckStatus::Error),
+        checks,
+    };
+    if let Err(e) = save(data_dir, &report) {
+        tracing::warn!("Failed to save environment report: {}", e);
+    }
+    for check in report.checks.iter().filter(|c| c.status != CheckStatus::Ok) {
+        tracing::warn!("Environment check {}: {}", check.check, check.finding);
+    }
+    report
+}
+
+/// Run the checks in the background if they never ran (first launch),
+/// without the NTP query.
+pub fn start(data_dir: PathBuf, locales_dir: PathBuf) {
+    if load(&data_dir).is_some() {
+        return;
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        run(&data_dir, &locales_dir, false);
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private check evaluation
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_server_offset_from_reply() {
+        let now = 1_700_000_000u64;
+        let mut reply = [0u8; 48];
+        let server = (now + NTP_UNIX_OFFSET + 600) as u32;
+        reply[40..44].copy_from_slice(&server.to_be_bytes());
+        assert_eq!(server_offset(&reply, now, now), Ok(600));
+        assert!(server_offset(&reply[..40], now, now).is_err());
+    }
+
+    #[test]
+    fn test_clock_check_thresholds() {
+        assert_eq!(clock_check(Some(Ok(10))).status, CheckStatus::Ok);
+        assert_eq!(clock_check(Some(Ok(-3600))).status, CheckStatus::Error);
+        assert_eq!(
+            clock_check(Some(Err("offline".to_string()))).status,
+            CheckStatus::Skipped
+        );
+        assert_eq!(clock_check(None).status, CheckStatus::Skipped);
+    }
+
+    #[test]
+    fn test_locales_check_counts_json_files() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(locales_check(temp.path()).status, CheckStatus::Error);
+        std::fs::write(temp.path().join("en.json"), "{}").unwrap();
+        assert_eq!(locales_check(temp.path()).status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn test_webview_version_parsing() {
+        assert_eq!(major_minor("2.44.1"), Some((2, 44)));
+        assert_eq!(major_minor("120"), Some((120, 0)));
+        assert_eq!(major_minor("unknown"), None);
+        assert_eq!(
+            webview_check(Err("missing".to_string())).status,
+            CheckStatus::Error
+        );
+    }
+}
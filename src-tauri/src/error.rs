@@ -9,15 +9,17 @@
 
 use std::fmt;
 
-use serde::Serialize;
+use crate::clock_skew;
+use crate::desktop_strings;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 
 /// Error type for all Tauri commands.
 ///
 /// Each variant maps to a category of failure that can occur across the
-/// desktop app's IPC surface. Implements `Serialize` so Tauri can send
-/// structured error data to the frontend.
-#[derive(Debug, Serialize)]
-#[serde(tag = "kind", content = "message")]
+/// desktop app's IPC surface. Serializes as
+/// `{ "kind", "code", "message", "i18n_key" }` so Tauri can send structured
+/// error data to the frontend.
+#[derive(Debug)]
 pub enum CommandError {
     /// Database or storage layer failures.
     Storage(String),
@@ -33,7 +35,7 @@ pub enum CommandError {
     Backup(String),
     /// Configuration errors (relay URL, content settings, serialization).
     Config(String),
-    /// Network errors (WebSocket, timeout, relay connection).
+    /// Network errors (WebSocket, timeout, message encoding).
     Network(String),
     /// Input validation failures (empty fields, bad format, weak password).
     Validation(String),
@@ -45,6 +47,148 @@ pub enum CommandError {
     Emergency(String),
     /// GDPR/privacy operation failures.
     Privacy(String),
+    /// Relay server unreachable or rejecting the connection.
+    Relay(String),
+    /// Identity recovery failures (claims, vouchers, recovery session).
+    Recovery(String),
+    /// Content update failures (fetch, verification, bundles, rollback).
+    Content(String),
+    /// Something looked expired because the system clock is wrong.
+    ClockSkew(String),
+}
+
+/// Stable error codes. Codes are part of the IPC contract: never rename or
+/// reuse one. Each has an `error.<code>` desktop string.
+const ERROR_CODES: &[&str] = &[
+    "storage_error",
+    "identity_error",
+    "exchange_error",
+    "contact_error",
+    "card_error",
+    "backup_error",
+    "config_error",
+    "network_error",
+    "validation_error",
+    "device_error",
+    "auth_error",
+    "emergency_error",
+    "privacy_error",
+    "relay_error",
+    "recovery_error",
+    "content_error",
+    "clock_skew_error",
+];
+
+/// Translation key for an error code.
+pub fn i18n_key_for(code: &str) -> String {
+    format!("error.{}", code)
+}
+
+/// Text for an error code in `locale_code`, `None` for unknown codes.
+pub fn localized_message(locale_code: &str, code: &str) -> Option<String> {
+    ERROR_CODES
+        .contains(&code)
+        .then(|| desktop_strings::get(locale_code, &i18n_key_for(code)))
+}
+
+impl CommandError {
+    /// Stable, machine-readable code, as sent in `code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::Storage(_) => "storage_error",
+            CommandError::Identity(_) => "identity_error",
+            CommandError::Exchange(_) => "exchange_error",
+            CommandError::Contact(_) => "contact_error",
+            CommandError::Card(_) => "card_error",
+            CommandError::Backup(_) => "backup_error",
+            CommandError::Config(_) => "config_error",
+            CommandError::Network(_) => "network_error",
+            CommandError::Validation(_) => "validation_error",
+            CommandError::Device(_) => "device_error",
+            CommandError::Auth(_) => "auth_error",
+            CommandError::Emergency(_) => "emergency_error",
+            CommandError::Privacy(_) => "privacy_error",
+            CommandError::Relay(_) => "relay_error",
+            CommandError::Recovery(_) => "recovery_error",
+            CommandError::Content(_) => "content_error",
+            CommandError::ClockSkew(_) => "clock_skew_error",
+        }
+    }
+
+    /// Translation key for this error, as sent in `i18n_key`.
+    pub fn i18n_key(&self) -> String {
+        i18n_key_for(self.code())
+    }
+
+    /// Name of the variant, as sent in `kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CommandError::Storage(_) => "Storage",
+            CommandError::Identity(_) => "Identity",
+            CommandError::Exchange(_) => "Exchange",
+            CommandError::Contact(_) => "Contact",
+            CommandError::Card(_) => "Card",
+            CommandError::Backup(_) => "Backup",
+            CommandError::Config(_) => "Config",
+            CommandError::Network(_) => "Network",
+            CommandError::Validation(_) => "Validation",
+            CommandError::Device(_) => "Device",
+            CommandError::Auth(_) => "Auth",
+            CommandError::Emergency(_) => "Emergency",
+            CommandError::Privacy(_) => "Privacy",
+            CommandError::Relay(_) => "Relay",
+            CommandError::Recovery(_) => "Recovery",
+            CommandError::Content(_) => "Content",
+            CommandError::ClockSkew(_) => "ClockSkew",
+        }
+    }
+
+    /// This expiry error, or a `ClockSkew` error when the system clock is
+    /// known to be off, since then things only look expired.
+    pub fn or_clock_skew(self) -> Self {
+        match clock_skew::excessive_skew() {
+            Some(skew) => CommandError::ClockSkew(format!(
+                "Your clock is off by {} seconds, so this looks expired. Correct the system \
+                 time and try again.",
+                skew.abs()
+            )),
+            None => self,
+        }
+    }
+
+    /// The error message without the kind prefix.
+    pub fn message(&self) -> &str {
+        match self {
+            CommandError::Storage(msg)
+            | CommandError::Identity(msg)
+            | CommandError::Exchange(msg)
+            | CommandError::Contact(msg)
+            | CommandError::Card(msg)
+            | CommandError::Backup(msg)
+            | CommandError::Config(msg)
+            | CommandError::Network(msg)
+            | CommandError::Validation(msg)
+            | CommandError::Device(msg)
+            | CommandError::Auth(msg)
+            | CommandError::Emergency(msg)
+            | CommandError::Privacy(msg)
+            | CommandError::Relay(msg)
+            | CommandError::Recovery(msg)
+            | CommandError::Content(msg)
+            | CommandError::ClockSkew(msg) => msg,
+        }
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("CommandError", 4)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", self.message())?;
+        error.serialize_field("i18n_key", &self.i18n_key())?;
+        error.end()
+    }
 }
 
 impl fmt::Display for CommandError {
@@ -63,6 +207,10 @@ impl fmt::Display for CommandError {
             CommandError::Auth(msg) => write!(f, "Auth error: {}", msg),
             CommandError::Emergency(msg) => write!(f, "Emergency error: {}", msg),
             CommandError::Privacy(msg) => write!(f, "Privacy error: {}", msg),
+            CommandError::Relay(msg) => write!(f, "Relay error: {}", msg),
+            CommandError::Recovery(msg) => write!(f, "Recovery error: {}", msg),
+            CommandError::Content(msg) => write!(f, "Content error: {}", msg),
+            CommandError::ClockSkew(msg) => write!(f, "Clock skew error: {}", msg),
         }
     }
 }
@@ -210,6 +358,34 @@ mod tests {
         assert_eq!(display, "Privacy error: export failed");
     }
 
+    #[test]
+    fn test_display_relay_error_includes_kind_and_message() {
+        let err = CommandError::Relay("connection refused".to_string());
+        let display = format!("{}", err);
+        assert_eq!(display, "Relay error: connection refused");
+    }
+
+    #[test]
+    fn test_display_recovery_error_includes_kind_and_message() {
+        let err = CommandError::Recovery("no recovery in progress".to_string());
+        let display = format!("{}", err);
+        assert_eq!(display, "Recovery error: no recovery in progress");
+    }
+
+    #[test]
+    fn test_display_content_error_includes_kind_and_message() {
+        let err = CommandError::Content("bad signature".to_string());
+        let display = format!("{}", err);
+        assert_eq!(display, "Content error: bad signature");
+    }
+
+    #[test]
+    fn test_display_clock_skew_error_includes_kind_and_message() {
+        let err = CommandError::ClockSkew("clock is off".to_string());
+        let display = format!("{}", err);
+        assert_eq!(display, "Clock skew error: clock is off");
+    }
+
     // === All variants produce distinct display strings ===
 
     #[test]
@@ -228,6 +404,10 @@ mod tests {
             CommandError::Auth("x".into()),
             CommandError::Emergency("x".into()),
             CommandError::Privacy("x".into()),
+            CommandError::Relay("x".into()),
+            CommandError::Recovery("x".into()),
+            CommandError::Content("x".into()),
+            CommandError::ClockSkew("x".into()),
         ];
 
         let displays: Vec<String> = variants.iter().map(|v| format!("{}", v)).collect();
@@ -267,6 +447,10 @@ mod tests {
             ("Auth", CommandError::Auth("a".into())),
             ("Emergency", CommandError::Emergency("a".into())),
             ("Privacy", CommandError::Privacy("a".into())),
+            ("Relay", CommandError::Relay("a".into())),
+            ("Recovery", CommandError::Recovery("a".into())),
+            ("Content", CommandError::Content("a".into())),
+            ("ClockSkew", CommandError::ClockSkew("a".into())),
         ];
 
         for (expected_kind, err) in variants {
@@ -282,6 +466,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_serialize_includes_stable_code() {
+        let err = CommandError::Network("timeout".to_string());
+        let parsed = serde_json::to_value(&err).expect("serialization must succeed");
+        assert_eq!(parsed["code"], "network_error");
+        assert_eq!(parsed["message"], "timeout");
+        assert_eq!(parsed["i18n_key"], "error.network_error");
+    }
+
+    #[test]
+    fn test_every_code_has_a_default_message() {
+        let variants: Vec<CommandError> = vec![
+            CommandError::Storage("x".into()),
+            CommandError::Identity("x".into()),
+            CommandError::Exchange("x".into()),
+            CommandError::Contact("x".into()),
+            CommandError::Card("x".into()),
+            CommandError::Backup("x".into()),
+            CommandError::Config("x".into()),
+            CommandError::Network("x".into()),
+            CommandError::Validation("x".into()),
+            CommandError::Device("x".into()),
+            CommandError::Auth("x".into()),
+            CommandError::Emergency("x".into()),
+            CommandError::Privacy("x".into()),
+            CommandError::Relay("x".into()),
+            CommandError::Recovery("x".into()),
+            CommandError::Content("x".into()),
+            CommandError::ClockSkew("x".into()),
+        ];
+        for err in &variants {
+            assert_ne!(
+                localized_message("en", err.code()),
+                Some(err.i18n_key()),
+                "Code {} must have a bundled message",
+                err.code()
+            );
+        }
+        assert_eq!(ERROR_CODES.len(), variants.len());
+        assert_eq!(localized_message("en", "no_such_code"), None);
+        assert_ne!(
+            localized_message("de", "network_error"),
+            localized_message("en", "network_error")
+        );
+        assert_eq!(i18n_key_for("relay_error"), "error.relay_error");
+    }
+
     // === From conversion tests ===
 
     #[test]
@@ -0,0 +1,243 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Error Statistics
+//!
+//! Counts `CommandError` kinds per command in hourly buckets tagged with
+//! the app version, so a spike of one kind after an update shows up in
+//! `get_error_stats` and the diagnostics bundle. Only counts are kept, never
+//! messages. Stored in `error_stats.json` in the data dir and pruned to the
+//! last `RETENTION_HOURS`.
+//!
+//! Commands report their errors by awaiting their result through `track`,
+//! so an error is counted where the command returns it, on any thread.
+//! Counts are kept in memory and written at most every `SAVE_INTERVAL`,
+//! and once more when the app exits.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::error::CommandError;
+use crate::metrics;
+
+/// Statistics file name under the data dir.
+const STATS_FILE: &str = "error_stats.json";
+
+/// Hours of statistics kept.
+const RETENTION_HOURS: u64 = 30 * 24;
+
+const SECONDS_PER_HOUR: u64 = 3600;
+
+/// How often new counts are written to disk.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Errors of one kind from one command within one hour.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBucket {
+    /// Start of the hour (Unix seconds).
+    pub hour: u64,
+    pub app_version: String,
+    pub command: String,
+    pub kind: String,
+    pub count: u64,
+}
+
+/// Error statistics for the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorStats {
+    /// Buckets oldest first.
+    pub buckets: Vec<ErrorBucket>,
+    pub total_by_kind: BTreeMap<String, u64>,
+    pub total_by_command: BTreeMap<String, u64>,
+}
+
+struct Store {
+    data_dir: PathBuf,
+    buckets: Vec<ErrorBucket>,
+    /// Counts not yet written to disk.
+    dirty: bool,
+}
+
+fn store() -> &'static OnceLock<Mutex<Store>> {
+    static STORE: OnceLock<Mutex<Store>> = OnceLock::new();
+    &STORE
+}
+
+fn stats_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STATS_FILE)
+}
+
+fn load(data_dir: &Path) -> Vec<ErrorBucket> {
+    std::fs::read_to_string(stats_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Load saved statistics and start saving new counts periodically. Errors
+/// before this are not counted.
+pub fn init(data_dir: &Path) {
+    let _ = store().set(Mutex::new(Store {
+        data_dir: data_dir.to_path_buf(),
+        buckets: load(data_dir),
+        dirty: false,
+    }));
+    tauri::async_runtime::spawn(async {
+        loop {
+            tokio::time::sleep(SAVE_INTERVAL).await;
+            save();
+        }
+    });
+}
+
+/// Add one error to `buckets`, dropping buckets older than the retention.
+fn add(buckets: &mut Vec<ErrorBucket>, command: &str, kind: &str, now: u64) {
+    let hour = now - now % SECONDS_PER_HOUR;
+    let cutoff = hour.saturating_sub(RETENTION_HOURS * SECONDS_PER_HOUR);
+    buckets.retain(|b| b.hour >= cutoff);
+
+    let version = env!("CARGO_PKG_VERSION");
+    match buckets.iter_mut().find(|b| {
+        b.hour == hour && b.command == command && b.kind == kind && b.app_version == version
+    }) {
+        Some(bucket) => bucket.count += 1,
+        None => buckets.push(ErrorBucket {
+            hour,
+            app_version: version.to_string(),
+            command: command.to_string(),
+            kind: kind.to_string(),
+            count: 1,
+        }),
+    }
+}
+
+/// Count an error of `kind` from `command`.
+pub fn record(command: &str, kind: &str) {
+    let Some(store) = store().get() else {
+        return;
+    };
+    let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+    add(&mut store.buckets, command, kind, clock::now_secs());
+    store.dirty = true;
+}
+
+/// Write counts recorded since the last save.
+pub fn save() {
+    let Some(store) = store().get() else {
+        return;
+    };
+    let mut store = store.lock().unwrap_or_else(|e| e.into_inner());
+    if !store.dirty {
+        return;
+    }
+    let json = match serde_json::to_string(&store.buckets) {
+        Ok(json) => json,
+        Err(_) => return,
+    };
+    match std::fs::write(stats_path(&store.data_dir), json) {
+        Ok(()) => store.dirty = false,
+        Err(e) => tracing::warn!("Failed to save error statistics: {}", e),
+    }
+}
+
+/// Await a command's result, counting its error and timing it to
+/// completion for `get_performance_metrics`.
+pub async fn track<T>(
+    command: &str,
+    result: impl Future<Output = Result<T, CommandError>>,
+) -> Result<T, CommandError> {
+    let start = Instant::now();
+    let result = result.await;
+    metrics::record_completion(command, start.elapsed());
+    if let Err(e) = &result {
+        record(command, e.kind());
+    }
+    result
+}
+
+/// Statistics for the last `hours`, or everything kept.
+pub fn stats(hours: Option<u64>) -> ErrorStats {
+    let buckets = store()
+        .get()
+        .map(|store| {
+            store
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .buckets
+                .clone()
+        })
+        .unwrap_or_default();
+    summarize(buckets, hours, clock::now_secs())
+}
+
+fn summarize(mut buckets: Vec<ErrorBucket>, hours: Option<u64>, now: u64) -> ErrorStats {
+    if let Some(hours) = hours {
+        let cutoff = now.saturating_sub(hours * SECONDS_PER_HOUR);
+        buckets.retain(|b| b.hour + SECONDS_PER_HOUR > cutoff);
+    }
+    buckets.sort_by_key(|b| b.hour);
+    let mut total_by_kind = BTreeMap::new();
+    let mut total_by_command = BTreeMap::new();
+    for bucket in &buckets {
+        *total_by_kind.entry(bucket.kind.clone()).or_default() += bucket.count;
+        *total_by_command.entry(bucket.command.clone()).or_default() += bucket.count;
+    }
+    ErrorStats {
+        buckets,
+        total_by_kind,
+        total_by_command,
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private bucketing with fixed timestamps
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_share_hourly_buckets() {
+        let mut buckets = Vec::new();
+        add(&mut buckets, "sync", "Network", 7200);
+        add(&mut buckets, "sync", "Network", 7300);
+        add(&mut buckets, "sync", "Relay", 7300);
+        add(&mut buckets, "sync", "Network", 10800);
+
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].hour, 7200);
+        assert_eq!(buckets[0].count, 2);
+        assert_eq!(buckets[2].hour, 10800);
+    }
+
+    #[test]
+    fn test_old_buckets_are_pruned() {
+        let mut buckets = Vec::new();
+        add(&mut buckets, "sync", "Network", 0);
+        let later = (RETENTION_HOURS + 2) * SECONDS_PER_HOUR;
+        add(&mut buckets, "sync", "Network", later);
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].hour, later);
+    }
+
+    #[test]
+    fn test_summary_totals_and_window() {
+        let mut buckets = Vec::new();
+        add(&mut buckets, "sync", "Network", 0);
+        add(&mut buckets, "add_field", "Card", 5 * SECONDS_PER_HOUR);
+        add(&mut buckets, "sync", "Network", 5 * SECONDS_PER_HOUR);
+
+        let all = summarize(buckets.clone(), None, 5 * SECONDS_PER_HOUR);
+        assert_eq!(all.total_by_kind["Network"], 2);
+        assert_eq!(all.total_by_command["add_field"], 1);
+
+        let recent = summarize(buckets, Some(1), 5 * SECONDS_PER_HOUR + 60);
+        assert_eq!(recent.buckets.len(), 2);
+        assert_eq!(recent.total_by_kind["Network"], 1);
+    }
+}
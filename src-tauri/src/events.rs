@@ -0,0 +1,126 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Internal Event Bus
+//!
+//! A process-wide broadcast channel for things that happen asynchronously:
+//! sync progress, contacts added, devices linked, emergency alerts,
+//! recovery claims, check-in reminders. Producers call [`publish`]; consumers (the E2E test
+//! server's `/events` socket) call [`subscribe`]. Publishing with no
+//! subscribers is a no-op.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before the slowest one starts lagging.
+const EVENT_BUFFER: usize = 256;
+
+/// An application event.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AppEvent {
+    /// A relay sync started.
+    SyncStarted,
+    /// A sync phase started (`identity`, `receive`, `process`, `send`, `cleanup`).
+    SyncProgress { phase: String },
+    /// A sync finished.
+    SyncCompleted {
+        success: bool,
+        contacts_added: u32,
+        cards_updated: u32,
+        updates_sent: u32,
+        error: Option<String>,
+    },
+    /// A contact was added (via exchange or an incoming exchange on sync).
+    ContactAdded {
+        contact_id: String,
+        display_name: String,
+    },
+    /// A burst of own card edits settled and updates were queued.
+    CardUpdated { contacts_notified: u32 },
+    /// A device link completed. `role` is `initiator` or `joiner`.
+    DeviceLinked { role: String, device_count: usize },
+    /// The number of contacts with unseen changes changed.
+    UnreadChanged { total: u32 },
+    /// A contact's emergency alert arrived.
+    EmergencyAlertReceived {
+        alert_id: String,
+        contact_id: String,
+        display_name: String,
+        message: String,
+        /// A test by the sender; nothing is wrong.
+        test: bool,
+    },
+    /// The system clock differs from the relay's or network time by more
+    /// than the allowed skew. `source` is `relay` or `ntp`.
+    ClockSkewDetected { skew_secs: i64, source: String },
+    /// Someone claims to have recovered a known contact's identity and
+    /// asks for a voucher.
+    RecoveryClaimReceived {
+        contact_id: String,
+        contact_name: String,
+        expires_at: u64,
+    },
+    /// The dead man's switch wants a check-in; its action runs at `deadline`.
+    CheckInDue { deadline: u64 },
+}
+
+fn sender() -> &'static broadcast::Sender<AppEvent> {
+    static SENDER: OnceLock<broadcast::Sender<AppEvent>> = OnceLock::new();
+    SENDER.get_or_init(|| broadcast::channel(EVENT_BUFFER).0)
+}
+
+/// Publish an event to all current subscribers.
+///
+/// Inside a storage transaction the event is held until it commits.
+pub fn publish(event: AppEvent) {
+    crate::storage_worker::after_commit(move || {
+        // Err only means nobody is listening
+        let _ = sender().send(event);
+    });
+}
+
+/// Subscribe to events published from now on.
+pub fn subscribe() -> broadcast::Receiver<AppEvent> {
+    sender().subscribe()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscriber_receives_published_events() {
+        let mut rx = subscribe();
+        publish(AppEvent::SyncStarted);
+        // Other tests may publish concurrently; look for ours
+        let mut seen = false;
+        while let Ok(event) = rx.try_recv() {
+            if event == AppEvent::SyncStarted {
+                seen = true;
+            }
+        }
+        assert!(seen);
+    }
+
+    #[test]
+    fn test_publish_without_subscribers_is_noop() {
+        publish(AppEvent::SyncProgress {
+            phase: "receive".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_event_serializes_with_type_tag() {
+        let json = serde_json::to_value(AppEvent::ContactAdded {
+            contact_id: "abc".to_string(),
+            display_name: "Alice".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json["type"], "contact_added");
+        assert_eq!(json["display_name"], "Alice");
+    }
+}
@@ -0,0 +1,443 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! File Import
+//!
+//! Handles files handed to the app by the OS (`.vauchi` file association)
+//! or dropped onto the window. Each file is classified by content:
+//!
+//! - vCard (`BEGIN:VCARD`) — parsed into a preview of the contacts not in
+//!   the list yet; the frontend asks before `import_vcard_contacts` adds them
+//! - exchange invite (`.vauchi` containing exchange QR data) — routed like
+//!   a `vauchi://exchange/` deep link
+//! - encrypted identity backup (`.vauchi` containing a backup envelope or
+//!   bare base64) — inspected
+//!
+//! The parsed result is emitted as a `file-import` event for the frontend.
+//! Files that arrive before the app state is loaded wait in a queue until
+//! [`handle_pending`] runs.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use vauchi_core::exchange::ExchangeQR;
+
+use crate::commands::backup::{inspect_backup_data, BackupInspection};
+use crate::commands::import;
+use crate::deep_link::DeepLink;
+use crate::state::AppState;
+
+/// Event emitted to the frontend with the parsed file.
+pub const FILE_IMPORT_EVENT: &str = "file-import";
+
+/// Files larger than this are rejected without being read.
+const MAX_IMPORT_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Files handed over before the app state was managed.
+static PENDING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// A single field parsed from a vCard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedField {
+    pub field_type: String,
+    pub label: String,
+    pub value: String,
+}
+
+/// A contact parsed from a vCard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedContact {
+    pub display_name: String,
+    pub fields: Vec<ImportedField>,
+}
+
+/// Result of classifying and parsing an imported file.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileImport {
+    /// One or more contacts from a vCard file, not added yet.
+    Vcard {
+        file_name: String,
+        /// Contacts not in the contact list yet.
+        contacts: Vec<ImportedContact>,
+        /// How many of the file's contacts are already in the list.
+        known: usize,
+    },
+    /// An encrypted identity backup (password needed to restore).
+    Backup {
+        file_name: String,
+        /// Base64 backup data, ready for `import_backup`.
+        data: String,
+        inspection: BackupInspection,
+    },
+    /// A contact exchange invite, routed through the deep link flow.
+    Invite { file_name: String },
+    /// The file could not be imported.
+    Unsupported { file_name: String, reason: String },
+}
+
+/// Read, classify and parse a file.
+pub fn import_file(path: &Path) -> FileImport {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.len() > MAX_IMPORT_FILE_BYTES => {
+            return FileImport::Unsupported {
+                file_name,
+                reason: "File is too large to import".to_string(),
+            };
+        }
+        Ok(meta) if !meta.is_file() => {
+            return FileImport::Unsupported {
+                file_name,
+                reason: "Not a regular file".to_string(),
+            };
+        }
+        Err(e) => {
+            return FileImport::Unsupported {
+                file_name,
+                reason: format!("Failed to read file: {}", e),
+            };
+        }
+        Ok(_) => {}
+    }
+
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            return FileImport::Unsupported {
+                file_name,
+                reason: format!("Failed to read file: {}", e),
+            };
+        }
+    };
+
+    classify(file_name, content.trim())
+}
+
+/// Classify file content and parse it.
+fn classify(file_name: String, content: &str) -> FileImport {
+    if content
+        .get(..11)
+        .is_some_and(|p| p.eq_ignore_ascii_case("BEGIN:VCARD"))
+    {
+        let contacts = parse_vcards(content);
+        if contacts.is_empty() {
+            return FileImport::Unsupported {
+                file_name,
+                reason: "vCard contains no contacts".to_string(),
+            };
+        }
+        return FileImport::Vcard {
+            file_name,
+            contacts,
+            known: 0,
+        };
+    }
+
+    if ExchangeQR::from_data_string(content).is_ok() {
+        return FileImport::Invite { file_name };
+    }
+
+    let inspection = inspect_backup_data(content);
+    if inspection.valid {
+        return FileImport::Backup {
+            file_name,
+            data: content.to_string(),
+            inspection,
+        };
+    }
+
+    FileImport::Unsupported {
+        file_name,
+        reason: "Unrecognized file format".to_string(),
+    }
+}
+
+/// Import a file and emit the result to the frontend.
+///
+/// vCard contacts are only previewed. Exchange invites are dispatched as
+/// deep links so they land in the same pending-exchange flow as
+/// `vauchi://exchange/` URLs.
+pub fn handle_path(app: &AppHandle, path: &Path) {
+    let state = {
+        let mut pending = PENDING.lock().unwrap();
+        match app.try_state::<Mutex<AppState>>() {
+            Some(state) => state,
+            None => {
+                pending.push(path.to_path_buf());
+                return;
+            }
+        }
+    };
+    let mut result = import_file(path);
+
+    if let FileImport::Vcard {
+        file_name,
+        contacts,
+        known,
+    } = &mut result
+    {
+        let outcome = import::split_known(&state.lock().unwrap(), std::mem::take(contacts));
+        match outcome {
+            Ok((new, count)) => {
+                *contacts = new;
+                *known = count;
+            }
+            Err(e) => {
+                let file_name = std::mem::take(file_name);
+                result = FileImport::Unsupported {
+                    file_name,
+                    reason: e.to_string(),
+                };
+            }
+        }
+    }
+
+    if let FileImport::Invite { .. } = result {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            let link = DeepLink::Exchange(content.trim().to_string());
+            crate::deep_link::dispatch_link(app, link);
+        }
+    }
+
+    if let Err(e) = app.emit(FILE_IMPORT_EVENT, &result) {
+        tracing::warn!("Failed to emit file import event: {}", e);
+    }
+}
+
+/// Handle the files that arrived before the app state was managed.
+pub fn handle_pending(app: &AppHandle) {
+    let paths = std::mem::take(&mut *PENDING.lock().unwrap());
+    for path in paths {
+        handle_path(app, &path);
+    }
+}
+
+/// Pick file paths out of launch arguments (Linux/Windows file association).
+pub fn paths_from_args(args: impl Iterator<Item = String>) -> Vec<std::path::PathBuf> {
+    args.skip(1)
+        .filter(|a| !a.starts_with('-'))
+        .map(std::path::PathBuf::from)
+        .filter(|p| p.is_file())
+        .collect()
+}
+
+/// Parse all vCards in a file.
+///
+/// Supports the properties Vauchi cards map to: FN/N, TEL, EMAIL, URL, ADR,
+/// BDAY. Unknown properties are skipped.
+pub fn parse_vcards(content: &str) -> Vec<ImportedContact> {
+    let mut contacts = Vec::new();
+    let mut current: Option<ImportedContact> = None;
+    let mut fallback_name = String::new();
+
+    for line in unfold_lines(content) {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = head.split(';');
+        let name = parts.next().unwrap_or_default().to_ascii_uppercase();
+        // Strip group prefixes like "item1.TEL"
+        let name = name.rsplit('.').next().unwrap_or_default().to_string();
+        let label = parts
+            .filter_map(|p| {
+                let (k, v) = p.split_once('=')?;
+                k.eq_ignore_ascii_case("TYPE").then(|| v.to_lowercase())
+            })
+            .next()
+            .unwrap_or_default();
+        let value = unescape(value.trim());
+
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some(ImportedContact {
+                    display_name: String::new(),
+                    fields: Vec::new(),
+                });
+                fallback_name.clear();
+            }
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(mut contact) = current.take() {
+                    if contact.display_name.is_empty() {
+                        contact.display_name = fallback_name.clone();
+                    }
+                    if !contact.display_name.is_empty() || !contact.fields.is_empty() {
+                        contacts.push(contact);
+                    }
+                }
+            }
+            _ => {
+                let Some(contact) = current.as_mut() else {
+                    continue;
+                };
+                let field_type = match name.as_str() {
+                    "FN" => {
+                        contact.display_name = value;
+                        continue;
+                    }
+                    "N" => {
+                        // N:Family;Given;Additional;Prefix;Suffix
+                        let parts: Vec<&str> = value.split(';').collect();
+                        let given = parts.get(1).copied().unwrap_or_default();
+                        let family = parts.first().copied().unwrap_or_default();
+                        fallback_name = format!("{} {}", given, family).trim().to_string();
+                        continue;
+                    }
+                    "TEL" => "phone",
+                    "EMAIL" => "email",
+                    "URL" => "website",
+                    "BDAY" => "birthday",
+                    "ADR" => "address",
+                    _ => continue,
+                };
+                let value = if field_type == "address" {
+                    value
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                } else {
+                    value
+                };
+                if value.is_empty() {
+                    continue;
+                }
+                let label = if label.is_empty() {
+                    field_type.to_string()
+                } else {
+                    label
+                };
+                contact.fields.push(ImportedField {
+                    field_type: field_type.to_string(),
+                    label,
+                    value,
+                });
+            }
+        }
+    }
+
+    contacts
+}
+
+/// Join RFC 6350 folded lines (continuations start with a space or tab).
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if let Some(cont) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(cont);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+    lines
+}
+
+/// Undo vCard text escaping (`\n`, `\,`, `\;`, `\\`).
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private vCard parsing and file classification
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE_VCARD: &str = "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Alice Smith\r\nTEL;TYPE=work:+41 44 123 45 67\r\nEMAIL:alice@example.com\r\nADR;TYPE=home:;;Main St 1;Zurich;;8000;Switzerland\r\nEND:VCARD\r\n";
+
+    #[test]
+    fn test_parse_single_vcard() {
+        let contacts = parse_vcards(ALICE_VCARD);
+        assert_eq!(contacts.len(), 1);
+        let alice = &contacts[0];
+        assert_eq!(alice.display_name, "Alice Smith");
+        assert_eq!(alice.fields.len(), 3);
+        assert_eq!(alice.fields[0].field_type, "phone");
+        assert_eq!(alice.fields[0].label, "work");
+        assert_eq!(alice.fields[1].label, "email");
+        assert_eq!(
+            alice.fields[2].value,
+            "Main St 1, Zurich, 8000, Switzerland"
+        );
+    }
+
+    #[test]
+    fn test_parse_multiple_vcards_and_n_fallback() {
+        let content = format!(
+            "{}BEGIN:VCARD\nVERSION:3.0\nN:Jones;Bob;;;\nURL:https://bob.dev\nEND:VCARD\n",
+            ALICE_VCARD
+        );
+        let contacts = parse_vcards(&content);
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[1].display_name, "Bob Jones");
+        assert_eq!(contacts[1].fields[0].field_type, "website");
+    }
+
+    #[test]
+    fn test_parse_folded_and_escaped_lines() {
+        let content = "BEGIN:VCARD\nFN:Carol\\, PhD\nEMAIL:carol@exam\n ple.com\nEND:VCARD";
+        let contacts = parse_vcards(content);
+        assert_eq!(contacts[0].display_name, "Carol, PhD");
+        assert_eq!(contacts[0].fields[0].value, "carol@example.com");
+    }
+
+    #[test]
+    fn test_classify_vcard() {
+        match classify("alice.vcf".to_string(), ALICE_VCARD.trim()) {
+            FileImport::Vcard { contacts, .. } => assert_eq!(contacts.len(), 1),
+            other => panic!("Expected Vcard, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_classify_garbage_is_unsupported() {
+        match classify("notes.txt".to_string(), "hello world!") {
+            FileImport::Unsupported { .. } => {}
+            other => panic!("Expected Unsupported, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_classify_backup_envelope() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        use crate::backup_envelope::BackupEnvelope;
+        use crate::recovery_policy::RecoveryPolicy;
+
+        let identity = STANDARD.encode([7u8; 96]);
+        let data = BackupEnvelope::new(identity, Some(RecoveryPolicy::default())).encode();
+        match classify("backup.vauchi".to_string(), &data) {
+            FileImport::Backup { inspection, .. } => assert_eq!(inspection.size_bytes, 96),
+            other => panic!("Expected Backup, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_import_file_missing_is_unsupported() {
+        let result = import_file(Path::new("/nonexistent/backup.vauchi"));
+        assert!(matches!(result, FileImport::Unsupported { .. }));
+    }
+}
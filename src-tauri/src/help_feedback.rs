@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Help Feedback
+//!
+//! Local record of "was this helpful?" answers on FAQs and of help searches
+//! that found nothing, so gaps in the help content can be spotted. Nothing
+//! leaves the device unless the user includes the feedback in a
+//! diagnostics bundle. Comments and queries are redacted (see
+//! `logging::redact`) before they are written.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::logging;
+
+/// Feedback file name under the data dir.
+const FEEDBACK_FILE: &str = "help_feedback.json";
+
+/// Unanswered search file name under the data dir.
+const UNANSWERED_FILE: &str = "help_unanswered_searches.json";
+
+/// Oldest feedback entries are dropped beyond this many.
+const MAX_FEEDBACK: usize = 500;
+
+/// Least recently searched queries are dropped beyond this many.
+const MAX_UNANSWERED: usize = 200;
+
+/// Longest stored comment, in characters.
+pub const MAX_COMMENT_CHARS: usize = 1000;
+
+/// Longest stored search query, in characters.
+const MAX_QUERY_CHARS: usize = 100;
+
+/// Feedback on one FAQ.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelpFeedback {
+    pub faq_id: String,
+    pub helpful: bool,
+    pub comment: Option<String>,
+    pub timestamp: u64,
+}
+
+/// A search query that returned no FAQs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnansweredSearch {
+    pub query: String,
+    pub count: u32,
+    pub last_searched: u64,
+}
+
+fn feedback_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(FEEDBACK_FILE)
+}
+
+fn unanswered_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(UNANSWERED_FILE)
+}
+
+/// Recorded feedback, oldest first.
+pub fn load_feedback(data_dir: &Path) -> Vec<HelpFeedback> {
+    std::fs::read_to_string(feedback_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Record feedback on an FAQ, keeping at most `MAX_FEEDBACK` entries.
+pub fn record_feedback(
+    data_dir: &Path,
+    faq_id: &str,
+    helpful: bool,
+    comment: Option<&str>,
+) -> std::io::Result<()> {
+    let comment = comment
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(|c| logging::redact(&c.chars().take(MAX_COMMENT_CHARS).collect::<String>()));
+
+    let mut feedback = load_feedback(data_dir);
+    feedback.push(HelpFeedback {
+        faq_id: faq_id.to_string(),
+        helpful,
+        comment,
+        timestamp: clock::now_secs(),
+    });
+    let excess = feedback.len().saturating_sub(MAX_FEEDBACK);
+    feedback.drain(..excess);
+    std::fs::write(feedback_path(data_dir), serde_json::to_string(&feedback)?)
+}
+
+fn load_unanswered(data_dir: &Path) -> HashMap<String, UnansweredSearch> {
+    std::fs::read_to_string(unanswered_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Count a search that returned nothing. Queries are grouped
+/// case-insensitively; blank queries are ignored.
+pub fn record_unanswered(data_dir: &Path, query: &str) -> std::io::Result<()> {
+    let query: String = query
+        .trim()
+        .to_lowercase()
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect();
+    if query.is_empty() {
+        return Ok(());
+    }
+    let query = logging::redact(&query);
+
+    let mut searches = load_unanswered(data_dir);
+    let now = clock::now_secs();
+    let entry = searches
+        .entry(query.clone())
+        .or_insert_with(|| UnansweredSearch {
+            query,
+            count: 0,
+            last_searched: now,
+        });
+    entry.count += 1;
+    entry.last_searched = now;
+
+    while searches.len() > MAX_UNANSWERED {
+        let oldest = searches
+            .values()
+            .min_by_key(|s| s.last_searched)
+            .map(|s| s.query.clone());
+        match oldest {
+            Some(query) => searches.remove(&query),
+            None => break,
+        };
+    }
+    std::fs::write(unanswered_path(data_dir), serde_json::to_string(&searches)?)
+}
+
+/// Most frequent unanswered searches, most recent first among equal counts.
+pub fn top_unanswered(data_dir: &Path, limit: usize) -> Vec<UnansweredSearch> {
+    let mut searches: Vec<UnansweredSearch> = load_unanswered(data_dir).into_values().collect();
+    searches.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.last_searched.cmp(&a.last_searched))
+    });
+    searches.truncate(limit);
+    searches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_feedback_recorded_and_redacted() {
+        let temp = TempDir::new().unwrap();
+        record_feedback(temp.path(), "faq-1", false, Some(" mail me at a@b.com ")).unwrap();
+        record_feedback(temp.path(), "faq-2", true, Some("   ")).unwrap();
+
+        let feedback = load_feedback(temp.path());
+        assert_eq!(feedback.len(), 2);
+        assert_eq!(feedback[0].comment.as_deref(), Some("mail me at [email]"));
+        assert!(!feedback[0].helpful);
+        assert!(feedback[1].comment.is_none());
+    }
+
+    #[test]
+    fn test_unanswered_searches_grouped_and_ranked() {
+        let temp = TempDir::new().unwrap();
+        record_unanswered(temp.path(), "Backup Codes").unwrap();
+        record_unanswered(temp.path(), "backup codes ").unwrap();
+        record_unanswered(temp.path(), "nfc").unwrap();
+        record_unanswered(temp.path(), "  ").unwrap();
+
+        let top = top_unanswered(temp.path(), 10);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].query, "backup codes");
+        assert_eq!(top[0].count, 2);
+
+        assert_eq!(top_unanswered(temp.path(), 1).len(), 1);
+    }
+}
@@ -0,0 +1,254 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Issue Reports
+//!
+//! A zip the user can attach to a bug report, written to
+//! `<data_dir>/issue_reports/`:
+//!
+//! - `report.json`: app version, OS, environment report, clock skew, error
+//!   counts, crash reports and, if chosen, a configuration summary
+//!
+//! and, if the user chooses to include logs:
+//!
+//! - `recent_errors.json`: recent warnings and errors from the log
+//! - `sync_journal.json`: recent log entries of the sync modules
+//! - `logs.json`: recent log entries (the log file keeps INFO and above)
+//!
+//! Log lines and crash messages are redacted when written. The
+//! configuration summary is an allowlist of settings; keys, card fields and
+//! contact data are never read here. Only the newest [`MAX_REPORTS`]
+//! reports are kept.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::clock_skew::SkewMeasurement;
+use crate::crash::CrashReport;
+use crate::environment::EnvironmentReport;
+use crate::error_stats::ErrorBucket;
+use crate::logging::{self, LogEntry};
+
+/// Report directory name under the data dir.
+const REPORT_DIR: &str = "issue_reports";
+
+/// Number of warnings and errors included.
+const ERROR_LOG_LIMIT: usize = 200;
+
+/// Number of sync log entries included.
+const SYNC_LOG_LIMIT: usize = 500;
+
+/// Number of log entries included with `include_logs`.
+const FULL_LOG_LIMIT: usize = 2000;
+
+/// Number of reports kept in the report dir.
+pub const MAX_REPORTS: usize = 5;
+
+/// Settings worth knowing when reproducing a bug.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigSummary {
+    pub locale: String,
+    pub relay_url: String,
+    pub tor_enabled: bool,
+    pub ntp_enabled: bool,
+    pub app_updates_enabled: bool,
+}
+
+/// The `report.json` part of an issue report.
+#[derive(Debug, Serialize)]
+pub struct IssueReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub generated_at: u64,
+    pub has_identity: bool,
+    /// `None` if the environment checks never ran.
+    pub environment: Option<EnvironmentReport>,
+    pub clock_skew: Option<SkewMeasurement>,
+    /// Error counts per command and kind, hourly.
+    pub error_stats: Vec<ErrorBucket>,
+    pub crash_reports: Vec<CrashReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<ConfigSummary>,
+}
+
+/// Directory issue reports are written to.
+pub fn report_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(REPORT_DIR)
+}
+
+/// Whether a log target belongs to one of the sync modules.
+fn is_sync_target(target: &str) -> bool {
+    target.split("::").any(|part| part.contains("sync"))
+}
+
+/// Recent log entries of the sync modules, newest last.
+fn sync_journal(data_dir: &Path) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = logging::recent_entries(data_dir, "info", FULL_LOG_LIMIT)
+        .into_iter()
+        .filter(|e| is_sync_target(&e.target))
+        .collect();
+    let skip = entries.len().saturating_sub(SYNC_LOG_LIMIT);
+    entries.split_off(skip)
+}
+
+fn add_json<T: Serialize>(
+    zip: &mut ZipWriter<std::fs::File>,
+    name: &str,
+    value: &T,
+) -> std::io::Result<()> {
+    zip.start_file(name, SimpleFileOptions::default())?;
+    zip.write_all(serde_json::to_string_pretty(value)?.as_bytes())
+}
+
+/// Write `report`, and the log excerpts if `include_logs`, to a new zip in
+/// the report dir, then remove all but the newest [`MAX_REPORTS`].
+/// Returns the zip's path.
+pub fn write(
+    data_dir: &Path,
+    report: &IssueReport,
+    include_logs: bool,
+) -> std::io::Result<PathBuf> {
+    let dir = report_dir(data_dir);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("vauchi-issue-{}.zip", report.generated_at));
+
+    let mut zip = ZipWriter::new(std::fs::File::create(&path)?);
+    add_json(&mut zip, "report.json", report)?;
+    if include_logs {
+        add_json(
+            &mut zip,
+            "recent_errors.json",
+            &logging::recent_entries(data_dir, "warn", ERROR_LOG_LIMIT),
+        )?;
+        add_json(&mut zip, "sync_journal.json", &sync_journal(data_dir))?;
+        add_json(
+            &mut zip,
+            "logs.json",
+            &logging::recent_entries(data_dir, "info", FULL_LOG_LIMIT),
+        )?;
+    }
+    zip.finish()?;
+    prune(&dir);
+    Ok(path)
+}
+
+/// Generation time of a report, from its file name.
+fn report_time(path: &Path) -> Option<u64> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("vauchi-issue-")?
+        .strip_suffix(".zip")?
+        .parse()
+        .ok()
+}
+
+/// Remove all but the newest [`MAX_REPORTS`] reports in `dir`.
+fn prune(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut reports: Vec<(u64, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|p| Some((report_time(&p)?, p)))
+        .collect();
+    reports.sort();
+    let excess = reports.len().saturating_sub(MAX_REPORTS);
+    for (_, path) in reports.drain(..excess) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!("Failed to remove old issue report: {}", e);
+        }
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private sync target filter
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn report(config: Option<ConfigSummary>) -> IssueReport {
+        IssueReport {
+            app_version: "0.1.0".to_string(),
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            generated_at: 1_700_000_000,
+            has_identity: true,
+            environment: None,
+            clock_skew: None,
+            error_stats: Vec::new(),
+            crash_reports: Vec::new(),
+            config,
+        }
+    }
+
+    fn entry_names(path: &Path) -> Vec<String> {
+        let archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_sync_targets() {
+        assert!(is_sync_target("vauchi_desktop_lib::commands::sync"));
+        assert!(is_sync_target("vauchi_desktop_lib::emergency_sync"));
+        assert!(!is_sync_target("vauchi_desktop_lib::commands::card"));
+    }
+
+    #[test]
+    fn test_logs_are_optional() {
+        let temp = TempDir::new().unwrap();
+        let path = write(temp.path(), &report(None), false).unwrap();
+        assert!(path.starts_with(report_dir(temp.path())));
+        assert_eq!(entry_names(&path), vec!["report.json"]);
+
+        std::fs::remove_file(&path).unwrap();
+        let path = write(temp.path(), &report(None), true).unwrap();
+        assert_eq!(
+            entry_names(&path),
+            vec![
+                "logs.json",
+                "recent_errors.json",
+                "report.json",
+                "sync_journal.json"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_the_newest_reports_are_kept() {
+        let temp = TempDir::new().unwrap();
+        let mut paths = Vec::new();
+        for i in 0..MAX_REPORTS + 2 {
+            let mut report = report(None);
+            report.generated_at += i as u64;
+            paths.push(write(temp.path(), &report, false).unwrap());
+        }
+        assert!(!paths[0].exists());
+        assert!(!paths[1].exists());
+        assert!(paths[2..].iter().all(|p| p.exists()));
+    }
+
+    #[test]
+    fn test_config_only_when_chosen() {
+        let json = serde_json::to_value(report(None)).unwrap();
+        assert!(json.get("config").is_none());
+
+        let config = ConfigSummary {
+            locale: "en".to_string(),
+            relay_url: "wss://relay.example".to_string(),
+            tor_enabled: false,
+            ntp_enabled: false,
+            app_updates_enabled: false,
+        };
+        let json = serde_json::to_value(report(Some(config))).unwrap();
+        assert_eq!(json["config"]["locale"], "en");
+    }
+}
@@ -0,0 +1,286 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Storage Key Rotation
+//!
+//! Core re-encrypts every stored value under a new key in one
+//! transaction; the encrypted settings are re-keyed after it. The storage
+//! worker is stopped first, and rotation is refused while a sync or push
+//! still holds it, so nothing writes with the old key behind the lock.
+//! Afterwards the WAL is checkpointed and the database vacuumed with
+//! `secure_delete` on, so no page or WAL frame keeps old ciphertext.
+//!
+//! The new key goes to the OS keychain if it keeps keys, otherwise to file
+//! key storage, and the old key is deleted from the other one, so rotating
+//! also moves an install between the two.
+//!
+//! The new key is saved as `storage_key_next` before core commits and
+//! promoted to `storage_key` after. If the app stops in between, `recover`
+//! keeps whichever of the two opens the database.
+
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::Connection;
+use serde::Serialize;
+use vauchi_core::storage::secure::{FileKeyStorage, SecureStorage};
+use vauchi_core::{Storage, SymmetricKey};
+
+#[cfg(feature = "secure-storage")]
+use vauchi_core::storage::secure::PlatformKeyring;
+
+use crate::error::CommandError;
+use crate::secure_settings;
+use crate::state::{self, AppState};
+
+/// Key name of the storage key, as saved by `AppState`.
+pub(crate) const KEY_NAME: &str = "storage_key";
+
+/// Key name of a new key not yet promoted.
+const NEXT_KEY_NAME: &str = "storage_key_next";
+
+/// How long to wait for other connections to finish writing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the storage key is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStorage {
+    Keychain,
+    File,
+}
+
+/// Result of a rotation.
+#[derive(Debug, Serialize)]
+pub struct RotationReport {
+    pub values_reencrypted: u64,
+    pub key_storage: KeyStorage,
+}
+
+fn to_key(bytes: &[u8]) -> Option<SymmetricKey> {
+    let arr: [u8; 32] = bytes.try_into().ok()?;
+    Some(SymmetricKey::from_bytes(arr))
+}
+
+fn key_error(e: impl std::fmt::Display) -> CommandError {
+    CommandError::Storage(format!("Storage key error: {}", e))
+}
+
+fn file_store(data_dir: &Path) -> Result<FileKeyStorage, CommandError> {
+    let fallback = state::load_or_generate_fallback_key(data_dir).map_err(key_error)?;
+    Ok(FileKeyStorage::new(data_dir.join("keys"), fallback))
+}
+
+/// File key storage, if this install ever used it.
+pub(crate) fn existing_file_store(data_dir: &Path) -> Option<FileKeyStorage> {
+    data_dir
+        .join(".fallback-key")
+        .exists()
+        .then(|| file_store(data_dir).ok())
+        .flatten()
+}
+
+#[cfg(feature = "secure-storage")]
+pub(crate) fn keychain(data_dir: &Path) -> PlatformKeyring {
+    PlatformKeyring::new(&AppState::keyring_service_name(data_dir))
+}
+
+/// Save `key` as `name` in the keychain if it keeps it, else in file
+/// key storage.
+pub(crate) fn save_key(
+    data_dir: &Path,
+    name: &str,
+    key: &SymmetricKey,
+) -> Result<KeyStorage, CommandError> {
+    #[cfg(feature = "secure-storage")]
+    {
+        let keyring = keychain(data_dir);
+        if keyring.save_key(name, key.as_bytes()).is_ok()
+            && keyring.load_key(name).ok().flatten().as_deref() == Some(key.as_bytes())
+        {
+            return Ok(KeyStorage::Keychain);
+        }
+    }
+    file_store(data_dir)?
+        .save_key(name, key.as_bytes())
+        .map_err(key_error)?;
+    Ok(KeyStorage::File)
+}
+
+/// Load `name` from the keychain or file key storage.
+fn load_key(data_dir: &Path, name: &str) -> Option<SymmetricKey> {
+    #[cfg(feature = "secure-storage")]
+    {
+        let key = keychain(data_dir)
+            .load_key(name)
+            .ok()
+            .flatten()
+            .and_then(|bytes| to_key(&bytes));
+        if key.is_some() {
+            return key;
+        }
+    }
+    existing_file_store(data_dir)?
+        .load_key(name)
+        .ok()
+        .flatten()
+        .and_then(|bytes| to_key(&bytes))
+}
+
+/// Delete `name` from every key store but `keep`.
+pub(crate) fn delete_key(data_dir: &Path, name: &str, keep: Option<KeyStorage>) {
+    #[cfg(feature = "secure-storage")]
+    {
+        if keep != Some(KeyStorage::Keychain) {
+            let _ = keychain(data_dir).delete_key(name);
+        }
+    }
+    if keep != Some(KeyStorage::File) {
+        if let Some(store) = existing_file_store(data_dir) {
+            let _ = store.delete_key(name);
+        }
+    }
+}
+
+fn sqlite_error(e: rusqlite::Error) -> CommandError {
+    CommandError::Storage(format!("Key rotation failed: {}", e))
+}
+
+/// Whether core reads the identity in `data_dir` with `key`. `false` if
+/// there is no identity to prove it.
+pub(crate) fn opens_with(data_dir: &Path, key: &SymmetricKey) -> Result<bool, CommandError> {
+    let storage = Storage::open(
+        &data_dir.join("vauchi.db"),
+        SymmetricKey::from_bytes(*key.as_bytes()),
+    )?;
+    Ok(matches!(storage.load_identity(), Ok(Some(_))))
+}
+
+/// Checkpoint the WAL and rebuild the database with `secure_delete` on,
+/// overwriting freed pages and WAL frames that held old values. Needs the
+/// storage worker stopped.
+pub(crate) fn scrub(data_dir: &Path) -> Result<(), CommandError> {
+    let conn = Connection::open(data_dir.join("vauchi.db")).map_err(sqlite_error)?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(sqlite_error)?;
+    conn.execute_batch(
+        "PRAGMA secure_delete = ON;
+         PRAGMA wal_checkpoint(TRUNCATE);
+         VACUUM;
+         PRAGMA wal_checkpoint(TRUNCATE);",
+    )
+    .map_err(sqlite_error)
+}
+
+/// Re-encrypt the database under a new key, store that key in place of the
+/// old one and reopen storage. Fails while a sync or push is running.
+pub fn rotate(state: &mut AppState) -> Result<RotationReport, CommandError> {
+    state
+        .stop_storage_worker()
+        .map_err(|e| CommandError::Storage(e.to_string()))?;
+    let data_dir = state.data_dir().to_path_buf();
+    let old = AppState::load_or_create_storage_key(&data_dir).map_err(key_error)?;
+    let new = SymmetricKey::generate();
+
+    save_key(&data_dir, NEXT_KEY_NAME, &new)?;
+    let values_reencrypted = match state.storage.rekey(&new) {
+        Ok(count) => count,
+        Err(e) => {
+            delete_key(&data_dir, NEXT_KEY_NAME, None);
+            return Err(e.into());
+        }
+    };
+    secure_settings::rekey(&data_dir, &old, &new)?;
+
+    let key_storage = save_key(&data_dir, KEY_NAME, &new)?;
+    delete_key(&data_dir, KEY_NAME, Some(key_storage));
+    delete_key(&data_dir, NEXT_KEY_NAME, None);
+    state
+        .reopen_storage()
+        .map_err(|e| CommandError::Storage(format!("Failed to reopen storage: {}", e)))?;
+    if let Err(e) = scrub(&data_dir) {
+        tracing::warn!("Old ciphertext may remain in free pages: {}", e);
+    }
+    tracing::info!(
+        "Rotated storage key ({} values re-encrypted, key in {:?})",
+        values_reencrypted,
+        key_storage
+    );
+    Ok(RotationReport {
+        values_reencrypted,
+        key_storage,
+    })
+}
+
+/// Finish a rotation that stopped between committing and storing the new
+/// key. Call before storage is opened.
+pub fn recover(data_dir: &Path) {
+    let Some(next) = load_key(data_dir, NEXT_KEY_NAME) else {
+        return;
+    };
+    match opens_with(data_dir, &next) {
+        Ok(true) => {
+            // Core committed; the settings may not have been re-keyed yet
+            if let Some(old) = load_key(data_dir, KEY_NAME) {
+                if let Err(e) = secure_settings::rekey(data_dir, &old, &next) {
+                    tracing::error!("Failed to re-key encrypted settings: {}", e);
+                    return;
+                }
+            }
+            match save_key(data_dir, KEY_NAME, &next) {
+                Ok(key_storage) => {
+                    delete_key(data_dir, KEY_NAME, Some(key_storage));
+                    tracing::info!("Finished an interrupted storage key rotation");
+                }
+                Err(e) => {
+                    tracing::error!("Failed to finish storage key rotation: {}", e);
+                    return;
+                }
+            }
+        }
+        Ok(false) => tracing::info!("Discarded the key of a rotation that did not commit"),
+        Err(e) => {
+            tracing::error!("Failed to check storage key rotation: {}", e);
+            return;
+        }
+    }
+    delete_key(data_dir, NEXT_KEY_NAME, None);
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private rotation on a scratch profile
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rotated_profile_reopens() {
+        let temp = TempDir::new().unwrap();
+        let mut state = AppState::new(temp.path()).unwrap();
+        state.create_identity("Alice Smith").unwrap();
+        let before = state.storage.load_identity().unwrap();
+
+        let old = AppState::load_or_create_storage_key(temp.path()).unwrap();
+        rotate(&mut state).unwrap();
+        assert_eq!(state.storage.load_identity().unwrap(), before);
+        assert!(!opens_with(temp.path(), &old).unwrap());
+        assert!(load_key(temp.path(), NEXT_KEY_NAME).is_none());
+        drop(state);
+
+        let state = AppState::new(temp.path()).unwrap();
+        assert_eq!(state.display_name(), Some("Alice Smith"));
+    }
+
+    #[tokio::test]
+    async fn test_rotation_waits_for_running_sync() {
+        let temp = TempDir::new().unwrap();
+        let mut state = AppState::new(temp.path()).unwrap();
+        state.create_identity("Alice Smith").unwrap();
+        let sync = state.storage_handle().unwrap();
+
+        assert!(rotate(&mut state).is_err());
+        assert!(sync.run(|_| ()).await.is_ok());
+        drop(sync);
+        assert!(rotate(&mut state).is_ok());
+    }
+}
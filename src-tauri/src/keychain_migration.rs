@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Keychain Migration
+//!
+//! Installs that ran without a working OS keychain (or were built without
+//! `secure-storage`) keep the storage key in file key storage, encrypted
+//! with `.fallback-key` next to it. Once the keychain works, `migrate`
+//! moves the key there, deletes the file copy and shreds `.fallback-key`
+//! if no other key depends on it.
+//!
+//! If the keychain already holds a different key, the one that opens the
+//! database is kept as the storage key and the other is moved to the
+//! keychain under a quarantine name instead of being deleted. If neither
+//! can be shown to open it, both are left as they are.
+//!
+//! It runs on every start, before storage is opened, and on demand through
+//! `migrate_to_keychain`.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::CommandError;
+
+/// Outcome of a migration attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(not(feature = "secure-storage"), allow(dead_code))]
+pub enum KeychainMigration {
+    /// The key was moved to the keychain.
+    Migrated,
+    /// No key on disk; nothing to do.
+    AlreadyInKeychain,
+    /// The keychain does not keep keys, or the app was built without
+    /// keychain support; the key stays on disk.
+    KeychainUnavailable,
+}
+
+/// Overwrite a file with random bytes, flush it and delete it.
+pub(crate) fn shred(path: &Path) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let len = std::fs::metadata(path)?.len() as usize;
+    let mut noise = Vec::with_capacity(len);
+    while noise.len() < len {
+        noise.extend_from_slice(vauchi_core::SymmetricKey::generate().as_bytes());
+    }
+    noise.truncate(len);
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    file.write_all(&noise)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)
+}
+
+/// Drop the file key storage once it holds no keys.
+#[cfg(feature = "secure-storage")]
+fn remove_empty_file_storage(data_dir: &Path) -> std::io::Result<()> {
+    let key_dir = data_dir.join("keys");
+    if key_dir.exists() {
+        if std::fs::read_dir(&key_dir)?.next().is_some() {
+            tracing::info!("Other keys still use .fallback-key; keeping it");
+            return Ok(());
+        }
+        std::fs::remove_dir(&key_dir)?;
+    }
+    shred(&data_dir.join(".fallback-key"))
+}
+
+/// Keep `bytes` in the keychain under a quarantine name, so a key thought
+/// unused can still be recovered.
+#[cfg(feature = "secure-storage")]
+fn quarantine(data_dir: &Path, bytes: &[u8]) -> Result<(), CommandError> {
+    use vauchi_core::storage::secure::SecureStorage;
+
+    let name = format!(
+        "{}_quarantined_{}",
+        crate::key_rotation::KEY_NAME,
+        crate::clock::now_secs()
+    );
+    crate::key_rotation::keychain(data_dir)
+        .save_key(&name, bytes)
+        .map_err(|e| CommandError::Storage(format!("Failed to quarantine key: {}", e)))?;
+    tracing::warn!("Quarantined an unused storage key as {}", name);
+    Ok(())
+}
+
+#[cfg(feature = "secure-storage")]
+fn to_key(bytes: &[u8]) -> Result<vauchi_core::SymmetricKey, CommandError> {
+    let key: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| CommandError::Storage("Invalid storage key length".to_string()))?;
+    Ok(vauchi_core::SymmetricKey::from_bytes(key))
+}
+
+/// Move the storage key from file key storage into the OS keychain.
+#[cfg(feature = "secure-storage")]
+pub fn migrate(data_dir: &Path) -> Result<KeychainMigration, CommandError> {
+    use vauchi_core::storage::secure::SecureStorage;
+
+    use crate::key_rotation::{self, KeyStorage, KEY_NAME};
+
+    let Some(file_store) = key_rotation::existing_file_store(data_dir) else {
+        return Ok(KeychainMigration::AlreadyInKeychain);
+    };
+    let file_key = file_store
+        .load_key(KEY_NAME)
+        .map_err(|e| CommandError::Storage(format!("Failed to read key file: {}", e)))?;
+
+    let Some(bytes) = file_key else {
+        remove_empty_file_storage(data_dir)?;
+        return Ok(KeychainMigration::AlreadyInKeychain);
+    };
+    let in_keychain = key_rotation::keychain(data_dir)
+        .load_key(KEY_NAME)
+        .ok()
+        .flatten();
+    match in_keychain {
+        Some(existing) if existing != bytes => {
+            let file_opens = key_rotation::opens_with(data_dir, &to_key(&bytes)?)?;
+            let keychain_opens = key_rotation::opens_with(data_dir, &to_key(&existing)?)?;
+            match (file_opens, keychain_opens) {
+                // The file copy is unused
+                (false, true) => quarantine(data_dir, &bytes)?,
+                // The keychain key is unused; the file key is the live one
+                (true, false) => {
+                    quarantine(data_dir, &existing)?;
+                    if key_rotation::save_key(data_dir, KEY_NAME, &to_key(&bytes)?)?
+                        != KeyStorage::Keychain
+                    {
+                        return Ok(KeychainMigration::KeychainUnavailable);
+                    }
+                }
+                _ => {
+                    return Err(CommandError::Storage(
+                        "The keychain and the key file hold different storage keys; kept both"
+                            .to_string(),
+                    ))
+                }
+            }
+        }
+        Some(_) => {}
+        None => {
+            if key_rotation::save_key(data_dir, KEY_NAME, &to_key(&bytes)?)? != KeyStorage::Keychain
+            {
+                return Ok(KeychainMigration::KeychainUnavailable);
+            }
+        }
+    }
+    file_store
+        .delete_key(KEY_NAME)
+        .map_err(|e| CommandError::Storage(format!("Failed to delete key file: {}", e)))?;
+
+    remove_empty_file_storage(data_dir)?;
+    tracing::info!("Moved the storage key into the OS keychain");
+    Ok(KeychainMigration::Migrated)
+}
+
+/// Without keychain support the key stays in file key storage.
+#[cfg(not(feature = "secure-storage"))]
+pub fn migrate(_data_dir: &Path) -> Result<KeychainMigration, CommandError> {
+    Ok(KeychainMigration::KeychainUnavailable)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private file shredding
+#[cfg(all(test, feature = "secure-storage"))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fallback_key_is_shredded_only_when_unused() {
+        let temp = TempDir::new().unwrap();
+        let fallback = temp.path().join(".fallback-key");
+        std::fs::write(&fallback, [7u8; 32]).unwrap();
+        std::fs::create_dir_all(temp.path().join("keys")).unwrap();
+        std::fs::write(temp.path().join("keys/other"), b"x").unwrap();
+
+        remove_empty_file_storage(temp.path()).unwrap();
+        assert!(fallback.exists());
+
+        std::fs::remove_file(temp.path().join("keys/other")).unwrap();
+        remove_empty_file_storage(temp.path()).unwrap();
+        assert!(!fallback.exists());
+        assert!(!temp.path().join("keys").exists());
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Label Tree
+//!
+//! Parent-child relations between visibility labels (e.g. Work → Team A),
+//! kept in `label_tree.json` as a child → parent map since core labels are
+//! flat. A label inherits the visible fields of all its ancestors, so a
+//! rule set on Work applies to everyone in Team A as well.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Tree file name under the data dir.
+const TREE_FILE: &str = "label_tree.json";
+
+/// Child label ID → parent label ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LabelTree {
+    pub parents: BTreeMap<String, String>,
+}
+
+impl LabelTree {
+    /// Ancestors of `label_id`, nearest first.
+    pub fn ancestors(&self, label_id: &str) -> Vec<String> {
+        let mut ancestors = Vec::new();
+        let mut seen = HashSet::from([label_id.to_string()]);
+        let mut current = label_id;
+        while let Some(parent) = self.parents.get(current) {
+            // A hand-edited file could still contain a loop
+            if !seen.insert(parent.clone()) {
+                break;
+            }
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// Move `label_id` under `new_parent`, or to the top level with `None`.
+    pub fn move_label(&mut self, label_id: &str, new_parent: Option<&str>) -> Result<(), String> {
+        match new_parent {
+            None => {
+                self.parents.remove(label_id);
+            }
+            Some(parent) => {
+                if parent == label_id || self.ancestors(parent).iter().any(|a| a == label_id) {
+                    return Err("A label cannot be moved under itself or its own sublabel".into());
+                }
+                self.parents
+                    .insert(label_id.to_string(), parent.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop a deleted label, moving its children up to its parent.
+    pub fn remove_label(&mut self, label_id: &str) {
+        let parent = self.parents.remove(label_id);
+        for child_parent in self.parents.values_mut() {
+            if child_parent == label_id {
+                match &parent {
+                    Some(parent) => child_parent.clone_from(parent),
+                    None => child_parent.clear(),
+                }
+            }
+        }
+        self.parents.retain(|_, parent| !parent.is_empty());
+    }
+}
+
+fn tree_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(TREE_FILE)
+}
+
+/// Load the tree, empty if missing or unreadable.
+pub fn load(data_dir: &Path) -> LabelTree {
+    std::fs::read_to_string(tree_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Save the tree.
+pub fn save(data_dir: &Path, tree: &LabelTree) -> std::io::Result<()> {
+    std::fs::write(tree_path(data_dir), serde_json::to_string_pretty(tree)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tree() -> LabelTree {
+        let mut tree = LabelTree::default();
+        tree.move_label("team-a", Some("work")).unwrap();
+        tree.move_label("work", Some("all")).unwrap();
+        tree
+    }
+
+    #[test]
+    fn test_ancestors_nearest_first() {
+        assert_eq!(tree().ancestors("team-a"), vec!["work", "all"]);
+        assert!(tree().ancestors("all").is_empty());
+    }
+
+    #[test]
+    fn test_cycles_are_rejected() {
+        let mut tree = tree();
+        assert!(tree.move_label("all", Some("team-a")).is_err());
+        assert!(tree.move_label("work", Some("work")).is_err());
+        assert!(tree.move_label("team-a", None).is_ok());
+        assert!(tree.move_label("all", Some("team-a")).is_ok());
+    }
+
+    #[test]
+    fn test_removing_a_label_reparents_children() {
+        let mut tree = tree();
+        tree.remove_label("work");
+        assert_eq!(tree.ancestors("team-a"), vec!["all"]);
+        tree.remove_label("all");
+        assert!(tree.parents.is_empty());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load(temp.path()), LabelTree::default());
+        save(temp.path(), &tree()).unwrap();
+        assert_eq!(load(temp.path()), tree());
+    }
+}
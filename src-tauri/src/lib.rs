@@ -6,18 +6,85 @@
 //!
 //! Tauri-based desktop application for Vauchi.
 
+mod accessibility;
+mod address_book;
+mod background;
+mod backup_envelope;
+mod card_propagation;
+mod carddav;
+mod clipboard;
+mod clock;
+mod clock_skew;
 mod commands;
+mod contact_cache;
+mod content_bundle;
+mod content_diff;
+mod content_scheduler;
+mod content_signing;
+mod content_versions;
+mod crash;
+mod data_location;
+mod db_integrity;
+mod dead_mans_switch;
+mod deep_link;
+mod default_label;
+mod deleted_contacts;
+mod desktop_strings;
+mod device_mode;
+mod digest;
+mod emergency_escalation;
+mod emergency_sync;
+mod environment;
 pub mod error;
+mod error_stats;
+mod events;
+mod file_import;
+mod help_feedback;
+mod issue_report;
+mod key_rotation;
+mod keychain_migration;
+mod label_tree;
+mod locale_overrides;
+mod logging;
+mod metrics;
+mod milestones;
+mod mock_relay;
+mod mutual_indicators;
+mod nfc;
+mod notifications;
+mod profile_import;
+mod ratchet_messages;
+mod recovery_drill;
+mod recovery_policy;
+mod recovery_qr;
+mod recovery_session;
 mod relay;
+mod release_notes;
+mod reverification;
+mod secret;
+mod secure_settings;
+mod settings_file;
+mod share_links;
+mod startup;
 mod state;
+mod storage_usage;
+mod storage_worker;
+mod sync_history;
 #[cfg(debug_assertions)]
 mod test_server;
 mod tray;
+mod trust_graph;
+mod unread;
+mod validation_freshness;
+mod validation_sync;
+mod webhooks;
+mod window_behavior;
+mod xml;
 
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 
 use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
 
 use state::AppState;
 
@@ -26,246 +93,590 @@ use state::AppState;
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // Must come first: a second launch hands its arguments to this
+        // instance and exits. With the deep-link feature its vauchi://
+        // URLs arrive through on_open_url.
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            tray::show_window(app);
+            for path in file_import::paths_from_args(args.into_iter()) {
+                file_import::handle_path(app, &path);
+            }
+        }))
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .setup(|app| {
+            startup::begin();
+
             // Resolve data directory
-            // Priority: VAUCHI_DATA_DIR env var > system data dir
-            let data_dir = std::env::var("VAUCHI_DATA_DIR")
-                .ok()
-                .filter(|s| !s.is_empty())
-                .map(PathBuf::from)
-                .unwrap_or_else(|| {
-                    dirs::data_dir()
-                        .unwrap_or_else(|| PathBuf::from("."))
-                        .join("vauchi")
-                });
+            // Priority: VAUCHI_DATA_DIR env var > chosen in the app > system data dir
+            let location_dir = data_location::config_dir();
+            let data_dir = data_location::resolve(&location_dir);
 
-            // Initialize i18n from bundled resource files
+            startup::phase("logging", || {
+                logging::init(&data_dir);
+                crash::install(&data_dir);
+            });
+            tracing::info!("Starting Vauchi {}", env!("CARGO_PKG_VERSION"));
+            // Remove the old data directory after a move
+            data_location::finish_migration(&location_dir, &data_dir);
+            if mock_relay::is_enabled() {
+                tracing::warn!("VAUCHI_MOCK_RELAY is set: relay traffic stays in-process");
+            }
+
+            // Non-critical initialization runs after the window is shown;
+            // each part announces itself on startup://ready
             let resource_dir = app
                 .path()
                 .resource_dir()
                 .map(|d| d.join("locales"))
                 .unwrap_or_else(|_| data_dir.join("locales"));
-            if let Err(e) = vauchi_core::i18n::init(&resource_dir) {
-                eprintln!(
-                    "Warning: Failed to load locale files from {:?}: {}",
-                    resource_dir, e
-                );
+            // A fresh install has no release notes to catch up on
+            release_notes::init(&data_dir, &app.package_info().version.to_string());
+
+            // Sanity-check keychain, webview, clock and locales on first launch
+            environment::start(data_dir.clone(), resource_dir.clone());
+            let handle = app.handle().clone();
+            let background_dir = data_dir.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                // Initialize i18n from bundled resource files
+                // Overrides in <data_dir>/locales-override/ are merged on top
+                startup::background_phase("i18n", || {
+                    match locale_overrides::init(&resource_dir, &background_dir) {
+                        Ok(applied) if !applied.is_empty() => {
+                            tracing::info!("Applied locale overrides: {}", applied.join(", "));
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            tracing::warn!(
+                                "Failed to load locale files from {:?}: {}",
+                                resource_dir,
+                                e
+                            );
+                        }
+                    }
+                });
+                startup::ready(&handle, "i18n");
+
+                // Scan user themes so invalid files are reported early
+                startup::background_phase("themes", || {
+                    let user_themes = commands::theme::load_user_themes(&background_dir);
+                    if !user_themes.is_empty() {
+                        tracing::info!("Loaded {} user theme(s)", user_themes.len());
+                    }
+                });
+                startup::ready(&handle, "themes");
+            });
+
+            // Command errors are counted from here on
+            error_stats::init(&data_dir);
+
+            // QR rendering reads the accessibility settings without state access
+            accessibility::init(&data_dir);
+            // Restore the saved UI scale before the window is shown
+            accessibility::apply_ui_scale(app.handle(), accessibility::load_ui_scale(&data_dir));
+
+            // Track the OS dark/light setting for theme://system-changed
+            commands::theme::init_system_theme(app.handle());
+
+            // Route vauchi:// links into pending flows
+            // Linux and Windows dev builds need runtime registration of the scheme
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            if let Err(e) = app.deep_link().register_all() {
+                tracing::warn!("Failed to register deep link scheme: {}", e);
             }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    deep_link::dispatch(&handle, url.as_str());
+                }
+            });
+
+            // Initialize app state (storage key from the keychain, identity)
+            // after the window is shown; commands that need it wait for
+            // "state" on startup://ready. Everything that reads the state
+            // starts once it is managed.
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                // Finish a storage key rotation cut short and move a
+                // file-stored key into the keychain first
+                let app_state = startup::background_phase("state", || {
+                    key_rotation::recover(&data_dir);
+                    // Move a file-stored key into the keychain once it works
+                    if let Err(e) = keychain_migration::migrate(&data_dir) {
+                        tracing::warn!("Failed to move storage key to keychain: {}", e);
+                    }
+                    let app_state = AppState::new(&data_dir);
+                    // Move credentials out of plain settings files
+                    secure_settings::migrate(&data_dir);
+                    if let Err(e) = share_links::drop_legacy_links(&data_dir) {
+                        tracing::warn!("Failed to drop stored share links: {}", e);
+                    }
+                    if let Err(e) = content_versions::migrate_legacy_cache(&data_dir) {
+                        tracing::warn!("Failed to move content cache: {}", e);
+                    }
+                    app_state
+                });
+                let app_state = match app_state {
+                    Ok(app_state) => app_state,
+                    Err(e) => {
+                        tracing::error!("Failed to initialize app state: {:#}", e);
+                        handle.exit(1);
+                        return;
+                    }
+                };
+                handle.manage(Mutex::new(app_state));
+
+                // OS notifications for events while the window is hidden
+                notifications::start(handle.clone(), data_dir.clone());
+
+                // Log network changes and show the weekly digest if enabled
+                digest::start(handle.clone(), data_dir.clone());
+
+                // Forward clock skew warnings; query NTP if enabled outside Tor mode
+                clock_skew::start(handle.clone());
 
-            // Initialize app state
-            let app_state = AppState::new(&data_dir).expect("Failed to initialize app state");
-
-            // D-C2: Test HTTP server (debug builds only)
-            // Only enable in debug builds to prevent exposure in release binaries
-            #[cfg(debug_assertions)]
-            {
-                // Start test HTTP server if VAUCHI_TEST_PORT is set
-                // The test server gets its own AppState instance pointing to the same data dir
-                if let Ok(port_str) = std::env::var("VAUCHI_TEST_PORT") {
-                    if let Ok(port) = port_str.parse::<u16>() {
-                        let data_dir_clone = data_dir.clone();
-                        std::thread::spawn(move || {
-                            // Create a separate AppState for the test server
-                            // Both instances share the same SQLite database (with proper locking)
-                            match AppState::new(&data_dir_clone) {
-                                Ok(test_state) => {
-                                    let test_state = Arc::new(Mutex::new(test_state));
-                                    match test_server::start_test_server(test_state, port) {
-                                        Ok(actual_port) => {
-                                            println!("Test server started on port {}", actual_port);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Failed to start test server: {}", e);
-                                        }
-                                    }
+                // Check for content updates on the configured interval
+                content_scheduler::start(handle.clone(), data_dir.clone());
+
+                // Forward contacts' emergency alerts to the frontend
+                emergency_sync::start(handle.clone());
+
+                // Forward recovery claims about contacts to the frontend
+                recovery_session::start(handle.clone());
+
+                // Notify later emergency tiers while nobody acknowledges
+                emergency_escalation::start(handle.clone(), data_dir.clone());
+
+                // Run the dead man's switch action when a check-in is missed
+                dead_mans_switch::start(handle.clone(), data_dir.clone());
+
+                // Wipe device link keys once their QR has expired
+                commands::devices::start_link_expiry(&handle);
+
+                // Queue card updates once a burst of edits has settled
+                card_propagation::start(handle.clone(), data_dir.clone());
+
+                // Serve contacts to local mail clients if the user enabled it
+                carddav::start(handle.clone(), data_dir.clone());
+
+                // Send selected events to the user's local automation webhooks
+                webhooks::start(handle.clone(), data_dir.clone());
+
+                // D-C2: Test HTTP server (debug builds only)
+                // Only enable in debug builds to prevent exposure in release binaries
+                #[cfg(debug_assertions)]
+                {
+                    // Start test HTTP server if VAUCHI_TEST_PORT is set
+                    // It shares the managed AppState with the Tauri commands
+                    if let Ok(port_str) = std::env::var("VAUCHI_TEST_PORT") {
+                        if let Ok(port) = port_str.parse::<u16>() {
+                            match test_server::start_test_server(handle.clone(), port) {
+                                Ok(actual_port) => {
+                                    println!("Test server started on port {}", actual_port);
                                 }
                                 Err(e) => {
-                                    eprintln!("Failed to create test state: {}", e);
+                                    tracing::error!("Failed to start test server: {}", e);
                                 }
                             }
-                        });
+                        }
                     }
                 }
-            }
 
-            app.manage(Mutex::new(app_state));
+                // Set up system tray
+                if let Err(e) = startup::background_phase("tray", || tray::setup(&handle)) {
+                    tracing::warn!("Failed to set up system tray: {}", e);
+                    // Non-fatal — app works without tray
+                }
 
-            // Set up system tray
-            if let Err(e) = tray::setup(app.handle()) {
-                eprintln!("Warning: Failed to set up system tray: {}", e);
-                // Non-fatal — app works without tray
-            }
+                // Links that arrived while the state was loading, then the
+                // link that launched the app (cold start)
+                deep_link::handle_pending(&handle);
+                if let Ok(Some(urls)) = handle.deep_link().get_current() {
+                    for url in urls {
+                        deep_link::dispatch(&handle, url.as_str());
+                    }
+                }
+
+                // Files dropped or opened while the state was loading, then
+                // .vauchi / .vcf files passed on the command line (file association)
+                file_import::handle_pending(&handle);
+                for path in file_import::paths_from_args(std::env::args()) {
+                    file_import::handle_path(&handle, &path);
+                }
 
+                startup::ready(&handle, "state");
+            });
+
+            startup::finish_setup();
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            commands::identity::has_identity,
-            commands::identity::create_identity,
-            commands::identity::get_identity_info,
-            commands::identity::update_display_name,
-            commands::card::get_card,
-            commands::card::add_field,
-            commands::card::remove_field,
-            commands::card::update_field,
-            commands::contacts::list_contacts,
-            commands::contacts::list_contacts_paginated,
-            commands::contacts::search_contacts,
-            commands::contacts::get_contact,
-            commands::contacts::remove_contact,
-            commands::contacts::get_contact_fingerprint,
-            commands::contacts::verify_contact,
-            commands::contacts::trust_contact,
-            commands::contacts::untrust_contact,
-            commands::contacts::trusted_contact_count,
-            commands::contacts::hide_contact,
-            commands::contacts::unhide_contact,
-            commands::contacts::list_hidden_contacts,
-            commands::contacts::find_duplicates,
-            commands::contacts::dismiss_duplicate,
-            commands::contacts::undismiss_duplicate,
-            commands::contacts::merge_contacts,
-            commands::contacts::get_contact_limit,
-            commands::contacts::set_contact_limit,
-            commands::exchange::start_exchange,
-            commands::exchange::process_scanned_qr,
-            commands::exchange::confirm_peer_scan,
-            commands::exchange::complete_exchange,
-            commands::backup::export_backup,
-            commands::backup::import_backup,
-            commands::backup::check_password_strength,
-            commands::visibility::get_visibility_rules,
-            commands::visibility::set_field_visibility,
-            commands::visibility::get_contacts_for_visibility,
-            commands::visibility::get_field_viewers,
-            commands::labels::list_labels,
-            commands::labels::create_label,
-            commands::labels::get_label,
-            commands::labels::rename_label,
-            commands::labels::delete_label,
-            commands::labels::add_contact_to_label,
-            commands::labels::remove_contact_from_label,
-            commands::labels::get_labels_for_contact,
-            commands::labels::set_label_field_visibility,
-            commands::labels::set_contact_field_override,
-            commands::labels::remove_contact_field_override,
-            commands::labels::get_suggested_labels,
-            commands::devices::list_devices,
-            commands::devices::get_current_device,
-            commands::devices::generate_device_link,
-            commands::devices::generate_device_link_qr,
-            commands::devices::join_device,
-            commands::devices::finish_join_device,
-            commands::devices::get_join_confirmation_code,
-            commands::devices::complete_device_link,
-            commands::devices::prepare_device_confirmation,
-            commands::devices::confirm_device_link_approved,
-            commands::devices::deny_device_link,
-            commands::devices::revoke_device,
-            commands::devices::generate_multipart_qr,
-            commands::devices::relay_listen_for_request,
-            commands::devices::relay_send_response,
-            commands::devices::relay_join_via_relay,
-            commands::recovery::get_recovery_settings,
-            commands::recovery::create_recovery_claim,
-            commands::recovery::create_recovery_voucher,
-            commands::recovery::check_recovery_claim,
-            commands::recovery::parse_recovery_claim,
-            commands::actions::open_contact_field,
-            commands::actions::get_field_action,
-            commands::actions::get_secondary_actions,
-            commands::actions::get_directions_url,
-            commands::sync::sync,
-            commands::sync::get_sync_status,
-            commands::sync::get_relay_url,
-            commands::sync::set_relay_url,
-            commands::content::check_content_updates,
-            commands::content::apply_content_updates,
-            commands::content::get_content_settings,
-            commands::content::set_content_updates_enabled,
-            commands::content::set_content_url,
-            commands::content::get_social_networks,
-            // Theme commands
-            commands::theme::get_available_themes,
-            commands::theme::get_theme,
-            commands::theme::get_default_theme_id,
-            // i18n commands
-            commands::i18n::get_locales,
-            commands::i18n::get_localized_string,
-            commands::i18n::get_localized_string_with_args,
-            commands::i18n::get_locale_strings,
-            // Help commands
-            commands::help::get_help_categories,
-            commands::help::get_all_faqs,
-            commands::help::get_category_faqs,
-            commands::help::get_faq,
-            commands::help::search_help,
-            commands::help::get_all_faqs_localized,
-            commands::help::get_category_faqs_localized,
-            commands::help::get_faq_localized,
-            commands::help::search_help_localized,
-            // Aha moment commands
-            commands::aha::check_aha_moment,
-            commands::aha::check_aha_moment_with_context,
-            commands::aha::check_aha_moment_localized,
-            // Validation commands
-            commands::validation::validate_contact_field,
-            commands::validation::get_field_validation_status,
-            commands::validation::revoke_field_validation,
-            commands::validation::get_field_validation_count,
-            commands::validation::list_my_validations,
-            // GDPR commands
-            commands::gdpr::export_gdpr_data,
-            commands::gdpr::schedule_account_deletion,
-            commands::gdpr::cancel_account_deletion,
-            commands::gdpr::get_deletion_state,
-            commands::gdpr::grant_consent,
-            commands::gdpr::revoke_consent,
-            commands::gdpr::get_consent_records,
-            commands::gdpr::execute_account_deletion,
-            commands::gdpr::panic_shred,
-            // Emergency broadcast commands
-            commands::emergency::get_emergency_config,
-            commands::emergency::save_emergency_config,
-            commands::emergency::delete_emergency_config,
-            commands::emergency::send_emergency_broadcast,
-            // Auth & duress commands
-            commands::auth::get_auth_mode,
-            commands::auth::setup_app_password,
-            commands::auth::authenticate,
-            commands::auth::setup_duress_pin,
-            commands::auth::disable_duress,
-            commands::auth::get_duress_status,
-            commands::auth::get_duress_settings,
-            commands::auth::save_duress_settings,
-            // Duress commands
-            commands::duress::enable_duress_password,
-            commands::duress::get_duress_config,
-            commands::duress::disable_duress_password,
-            commands::duress::test_duress_auth,
-            // Decoy contact commands
-            commands::decoy::list_decoy_contacts,
-            commands::decoy::add_decoy_contact,
-            commands::decoy::remove_decoy_contact,
-            commands::decoy::clear_decoy_contacts,
-            // Delivery commands
-            commands::delivery::get_delivery_status,
-            commands::delivery::list_delivery_records,
-            commands::delivery::process_delivery_retries,
-            commands::delivery::run_delivery_cleanup,
-            commands::delivery::translate_delivery_failure,
-            // Tor commands
-            commands::tor::get_tor_config,
-            commands::tor::save_tor_config,
-        ])
-        .on_window_event(|window, event| {
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                // Hide to tray instead of quitting
-                let _ = window.hide();
-                api.prevent_close();
+        .invoke_handler({
+            let handler = tauri::generate_handler![
+                commands::identity::has_identity,
+                commands::identity::create_identity,
+                commands::identity::get_identity_info,
+                commands::identity::update_display_name,
+                commands::card::get_card,
+                commands::card::add_field,
+                commands::card::remove_field,
+                commands::card::update_field,
+                commands::card::get_card_update_settings,
+                commands::card::set_card_update_settings,
+                commands::contacts::list_contacts,
+                commands::contacts::list_contacts_paginated,
+                commands::contacts::search_contacts,
+                commands::contacts::get_contact,
+                commands::contacts::remove_contact,
+                commands::contacts::get_contact_fingerprint,
+                commands::contacts::verify_contact,
+                commands::contacts::list_contacts_needing_reverification,
+                commands::contacts::trust_contact,
+                commands::contacts::untrust_contact,
+                commands::contacts::trusted_contact_count,
+                commands::contacts::hide_contact,
+                commands::contacts::unhide_contact,
+                commands::contacts::list_hidden_contacts,
+                commands::contacts::find_duplicates,
+                commands::contacts::dismiss_duplicate,
+                commands::contacts::undismiss_duplicate,
+                commands::contacts::merge_contacts,
+                commands::contacts::get_contact_limit,
+                commands::contacts::set_contact_limit,
+                commands::contacts::get_unread_counts,
+                commands::contacts::mark_contact_seen,
+                commands::exchange::start_exchange,
+                commands::exchange::process_scanned_qr,
+                commands::exchange::confirm_peer_scan,
+                commands::exchange::complete_exchange,
+                commands::nfc::get_nfc_capability,
+                commands::nfc::write_exchange_to_nfc,
+                commands::nfc::read_exchange_from_nfc,
+                commands::backup::export_backup,
+                commands::backup::import_backup,
+                commands::backup::check_password_strength,
+                commands::visibility::get_visibility_rules,
+                commands::visibility::set_field_visibility,
+                commands::visibility::get_contacts_for_visibility,
+                commands::visibility::get_field_viewers,
+                commands::visibility::set_field_viewers,
+                commands::visibility::audit_visibility,
+                commands::labels::list_labels,
+                commands::labels::create_label,
+                commands::labels::get_label,
+                commands::labels::rename_label,
+                commands::labels::delete_label,
+                commands::labels::add_contact_to_label,
+                commands::labels::remove_contact_from_label,
+                commands::labels::get_labels_for_contact,
+                commands::labels::set_label_field_visibility,
+                commands::labels::set_contact_field_override,
+                commands::labels::remove_contact_field_override,
+                commands::labels::preview_label_visibility,
+                commands::labels::apply_label_defaults_to_members,
+                commands::labels::get_label_parents,
+                commands::labels::move_label,
+                commands::labels::get_default_label_settings,
+                commands::labels::set_default_label_settings,
+                commands::labels::get_unlabeled_contacts,
+                commands::labels::get_suggested_labels,
+                commands::devices::list_devices,
+                commands::devices::get_current_device,
+                commands::devices::get_device_mode,
+                commands::devices::set_device_mode,
+                commands::devices::generate_device_link,
+                commands::devices::generate_device_link_qr,
+                commands::devices::join_device,
+                commands::devices::finish_join_device,
+                commands::devices::get_join_confirmation_code,
+                commands::devices::complete_device_link,
+                commands::devices::prepare_device_confirmation,
+                commands::devices::confirm_device_link_approved,
+                commands::devices::deny_device_link,
+                commands::devices::revoke_device,
+                commands::devices::generate_multipart_qr,
+                commands::devices::relay_listen_for_request,
+                commands::devices::relay_send_response,
+                commands::devices::relay_join_via_relay,
+                commands::recovery::get_recovery_settings,
+                commands::recovery::set_recovery_settings,
+                commands::recovery::create_recovery_claim,
+                commands::recovery::create_recovery_voucher,
+                commands::recovery::check_recovery_claim,
+                commands::recovery::parse_recovery_claim,
+                commands::recovery::start_recovery,
+                commands::recovery::add_received_voucher,
+                commands::recovery::get_recovery_progress,
+                commands::recovery::finalize_recovery,
+                commands::recovery::send_recovery_claim,
+                commands::recovery::list_recovery_claims,
+                commands::recovery::generate_recovery_qr,
+                commands::recovery::scan_recovery_qr,
+                commands::recovery::run_recovery_drill,
+                commands::actions::open_contact_field,
+                commands::actions::get_field_action,
+                commands::actions::get_secondary_actions,
+                commands::actions::get_directions_url,
+                commands::sync::sync,
+                commands::sync::get_sync_status,
+                commands::sync::get_sync_history,
+                commands::sync::get_relay_url,
+                commands::sync::set_relay_url,
+                commands::offline_sync::export_pending_updates,
+                commands::offline_sync::import_received_updates,
+                commands::content::check_content_updates,
+                commands::content::apply_content_updates,
+                commands::content::preview_content_updates,
+                commands::content::get_content_settings,
+                commands::content::set_content_updates_enabled,
+                commands::content::set_content_url,
+                commands::content::set_content_mirrors,
+                commands::content::set_content_type_enabled,
+                commands::content::import_content_bundle,
+                commands::content::set_content_check_interval,
+                commands::content::set_content_auto_apply,
+                commands::content::list_content_versions,
+                commands::content::rollback_content,
+                commands::content::get_social_networks,
+                // App update commands
+                commands::app_update::get_app_update_settings,
+                commands::app_update::set_app_update_settings,
+                commands::app_update::check_app_update,
+                commands::app_update::download_app_update,
+                // Theme commands
+                commands::theme::get_available_themes,
+                commands::theme::get_theme,
+                commands::theme::get_default_theme_id,
+                commands::theme::import_theme_file,
+                commands::theme::create_custom_theme,
+                commands::theme::update_custom_theme,
+                commands::theme::get_active_theme,
+                commands::theme::set_active_theme,
+                // i18n commands
+                commands::i18n::get_locales,
+                commands::i18n::get_localized_string,
+                commands::i18n::get_localized_string_with_args,
+                commands::i18n::get_locale_strings,
+                commands::i18n::get_current_locale,
+                commands::i18n::set_current_locale,
+                commands::i18n::get_localized_plural,
+                commands::i18n::format_number,
+                commands::i18n::format_date,
+                commands::i18n::reload_locales,
+                commands::i18n::get_supported_locales_for,
+                commands::i18n::get_layout_direction,
+                commands::i18n::get_locale_bundle,
+                commands::i18n::localize_error,
+                // Help commands
+                commands::help::get_help_categories,
+                commands::help::get_all_faqs,
+                commands::help::get_category_faqs,
+                commands::help::get_faq,
+                commands::help::search_help,
+                commands::help::get_all_faqs_localized,
+                commands::help::get_category_faqs_localized,
+                commands::help::get_faq_localized,
+                commands::help::search_help_localized,
+                commands::help::get_help_for_context,
+                commands::help::submit_help_feedback,
+                commands::help::get_top_unanswered_searches,
+                commands::help::get_whats_new,
+                commands::help::mark_whats_new_seen,
+                commands::troubleshoot::run_troubleshooter,
+                // Onboarding commands
+                commands::onboarding::get_onboarding_state,
+                commands::onboarding::complete_onboarding_step,
+                commands::onboarding::skip_onboarding,
+                // Aha moment commands
+                commands::aha::check_aha_moment,
+                commands::aha::check_aha_moment_with_context,
+                commands::aha::check_aha_moment_localized,
+                commands::aha::take_aha_moments,
+                commands::aha::get_aha_progress,
+                commands::aha::reset_aha_moments,
+                // Validation commands
+                commands::validation::validate_contact_field,
+                commands::validation::get_field_validation_status,
+                commands::validation::get_contact_validation_summary,
+                commands::validation::verify_validation_signatures,
+                commands::trust_graph::get_trust_graph,
+                commands::trust_graph::get_mutual_indicators,
+                commands::validation::revoke_field_validation,
+                commands::validation::get_field_validation_count,
+                commands::validation::list_my_validations,
+                commands::validation::get_validation_freshness,
+                commands::validation::set_validation_freshness,
+                commands::validation::list_stale_validations,
+                commands::validation::refresh_validation,
+                commands::validation::request_field_validation,
+                commands::validation::list_incoming_validation_requests,
+                commands::validation::accept_validation_request,
+                commands::validation::decline_validation_request,
+                // GDPR commands
+                commands::gdpr::export_gdpr_data,
+                commands::gdpr::schedule_account_deletion,
+                commands::gdpr::cancel_account_deletion,
+                commands::gdpr::get_deletion_state,
+                commands::gdpr::grant_consent,
+                commands::gdpr::revoke_consent,
+                commands::gdpr::get_consent_records,
+                commands::gdpr::execute_account_deletion,
+                commands::gdpr::panic_shred,
+                // Emergency broadcast commands
+                commands::emergency::get_emergency_config,
+                commands::emergency::save_emergency_config,
+                commands::emergency::delete_emergency_config,
+                commands::emergency::send_emergency_broadcast,
+                commands::emergency::trigger_emergency_broadcast,
+                commands::emergency::test_emergency_broadcast,
+                commands::emergency::get_emergency_broadcast_status,
+                commands::emergency::cancel_emergency_broadcast,
+                commands::emergency::configure_dead_mans_switch,
+                commands::emergency::check_in,
+                commands::emergency::get_check_in_status,
+                commands::emergency::list_emergency_alerts,
+                commands::emergency::acknowledge_alert,
+                // Auth & duress commands
+                commands::auth::get_auth_mode,
+                commands::auth::setup_app_password,
+                commands::auth::authenticate,
+                commands::auth::setup_duress_pin,
+                commands::auth::disable_duress,
+                commands::auth::get_duress_status,
+                commands::auth::get_duress_settings,
+                commands::auth::save_duress_settings,
+                // Duress commands
+                commands::duress::enable_duress_password,
+                commands::duress::get_duress_config,
+                commands::duress::disable_duress_password,
+                commands::duress::test_duress_auth,
+                // Decoy contact commands
+                commands::decoy::list_decoy_contacts,
+                commands::decoy::add_decoy_contact,
+                commands::decoy::remove_decoy_contact,
+                commands::decoy::clear_decoy_contacts,
+                // Delivery commands
+                commands::delivery::get_delivery_status,
+                commands::delivery::list_delivery_records,
+                commands::delivery::process_delivery_retries,
+                commands::delivery::run_delivery_cleanup,
+                commands::delivery::translate_delivery_failure,
+                // Tor commands
+                commands::tor::get_tor_config,
+                commands::tor::save_tor_config,
+                // Deep link commands
+                commands::deep_link::take_pending_deep_link,
+                // Share link commands
+                commands::share_links::create_share_link,
+                commands::share_links::list_share_links,
+                commands::share_links::revoke_share_link,
+                commands::share_links::open_share_link,
+                // Diagnostics commands
+                commands::diagnostics::get_recent_logs,
+                commands::diagnostics::export_diagnostics_bundle,
+                commands::diagnostics::create_issue_report,
+                commands::diagnostics::list_crash_reports,
+                commands::diagnostics::delete_crash_reports,
+                commands::diagnostics::get_performance_metrics,
+                commands::diagnostics::get_startup_report,
+                commands::diagnostics::get_error_stats,
+                commands::diagnostics::get_environment_report,
+                commands::diagnostics::get_clock_skew_status,
+                commands::diagnostics::set_clock_check_settings,
+                // Notification commands
+                commands::notifications::get_notification_settings,
+                commands::notifications::set_notification_settings,
+                commands::notifications::get_missed_notifications,
+                commands::notifications::get_weekly_digest,
+                commands::notifications::get_digest_settings,
+                commands::notifications::set_digest_settings,
+                // Accessibility commands
+                commands::accessibility::get_accessibility_settings,
+                commands::accessibility::set_accessibility_settings,
+                commands::accessibility::get_ui_scale,
+                commands::accessibility::set_ui_scale,
+                // Window commands
+                commands::window::get_window_behavior,
+                commands::window::set_window_behavior,
+                commands::window::show_qr_window,
+                commands::window::get_qr_window_content,
+                // Import commands
+                commands::backup::inspect_backup,
+                commands::import::parse_vcard,
+                commands::import::import_from_data_dir,
+                commands::import::import_vcard_contacts,
+                // Storage commands
+                commands::storage::get_data_dir,
+                commands::storage::migrate_data_dir,
+                commands::storage::check_database_integrity,
+                commands::storage::repair_database,
+                commands::storage::undo_database_repair,
+                commands::storage::get_storage_usage,
+                commands::storage::compact_database,
+                commands::storage::rotate_storage_key,
+                commands::storage::migrate_to_keychain,
+                // Address book commands
+                commands::address_book::get_address_book_target,
+                commands::address_book::get_address_book_consent,
+                commands::address_book::set_address_book_consent,
+                commands::address_book::export_to_address_book,
+                // CardDAV commands
+                commands::carddav::get_carddav_settings,
+                commands::carddav::set_carddav_enabled,
+                commands::carddav::regenerate_carddav_password,
+                // Webhook commands
+                commands::webhooks::get_webhooks,
+                commands::webhooks::add_webhook,
+                commands::webhooks::remove_webhook,
+                commands::webhooks::set_webhook_enabled,
+                commands::webhooks::set_webhooks_allow_remote,
+                commands::webhooks::test_webhook,
+                // Clipboard commands
+                commands::clipboard::copy_to_clipboard,
+                commands::clipboard::get_clipboard_settings,
+                commands::clipboard::set_clipboard_settings,
+                // Print commands
+                commands::print::generate_print_sheet,
+            ];
+            // Time every command for get_performance_metrics
+            move |invoke: tauri::ipc::Invoke| {
+                let command = invoke.message.command().to_string();
+                let start = std::time::Instant::now();
+                let handled = handler(invoke);
+                metrics::record_dispatch(&command, start.elapsed());
+                handled
+            }
+        })
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::CloseRequested { .. } | tauri::WindowEvent::Resized(_) => {
+                // Hide to tray or quit, per the user's window behavior
+                window_behavior::handle_event(window, event);
             }
+            tauri::WindowEvent::ThemeChanged(theme) => {
+                commands::theme::handle_system_theme_changed(window, theme);
+            }
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                for path in paths {
+                    file_import::handle_path(window.app_handle(), path);
+                }
+            }
+            _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // macOS delivers associated files as an Opened event
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = &event {
+                for path in urls.iter().filter_map(|u| u.to_file_path().ok()) {
+                    file_import::handle_path(_app, &path);
+                }
+            }
+            if let tauri::RunEvent::Exit = event {
+                error_stats::save();
+            }
+        });
 }
@@ -0,0 +1,172 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Locale Overrides
+//!
+//! JSON files in `<data_dir>/locales-override/` (named like the bundled
+//! ones, e.g. `de.json`) are deep-merged over the bundled locale files
+//! before `vauchi_core::i18n::init`. The merged set is written to
+//! `<data_dir>/locales-merged/`, so community translations and
+//! terminology fixes apply without rebuilding the app.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// Override directory name under the data dir.
+pub const OVERRIDE_DIR: &str = "locales-override";
+
+/// Merged output directory name under the data dir.
+const MERGED_DIR: &str = "locales-merged";
+
+/// JSON files in a directory, sorted by name.
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Recursively merge `overlay` into `base`; overlay values win.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Write bundled locales merged with overrides into `merged_dir`.
+///
+/// Returns the file names of the overrides that were applied; files that
+/// are not valid JSON objects are skipped.
+fn build_merged(
+    resource_dir: &Path,
+    override_dir: &Path,
+    merged_dir: &Path,
+) -> Result<Vec<String>> {
+    if merged_dir.exists() {
+        std::fs::remove_dir_all(merged_dir).context("Failed to clear merged locales")?;
+    }
+    std::fs::create_dir_all(merged_dir).context("Failed to create merged locales dir")?;
+
+    for file in json_files(resource_dir) {
+        if let Some(name) = file.file_name() {
+            std::fs::copy(&file, merged_dir.join(name)).context("Failed to copy locale file")?;
+        }
+    }
+
+    let mut applied = Vec::new();
+    for file in json_files(override_dir) {
+        let Some(name) = file.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let overlay: Value = match std::fs::read_to_string(&file)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| serde_json::from_str(&s).map_err(anyhow::Error::from))
+        {
+            Ok(value @ Value::Object(_)) => value,
+            Ok(_) => {
+                tracing::warn!("Skipping locale override {}: not a JSON object", name);
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!("Skipping locale override {}: {}", name, e);
+                continue;
+            }
+        };
+
+        let target = merged_dir.join(&name);
+        let mut base = std::fs::read_to_string(&target)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        merge(&mut base, overlay);
+        std::fs::write(&target, serde_json::to_string_pretty(&base)?)
+            .context("Failed to write merged locale")?;
+        applied.push(name);
+    }
+
+    Ok(applied)
+}
+
+/// Initialize i18n from the bundled locales plus any overrides.
+///
+/// Returns the override files applied. Without overrides the bundled
+/// directory is loaded directly.
+pub fn init(resource_dir: &Path, data_dir: &Path) -> Result<Vec<String>> {
+    let override_dir = data_dir.join(OVERRIDE_DIR);
+    if json_files(&override_dir).is_empty() {
+        vauchi_core::i18n::init(resource_dir)
+            .map_err(|e| anyhow::anyhow!("Failed to load locales: {}", e))?;
+        return Ok(Vec::new());
+    }
+
+    let merged_dir = data_dir.join(MERGED_DIR);
+    let applied = build_merged(resource_dir, &override_dir, &merged_dir)?;
+    vauchi_core::i18n::init(&merged_dir)
+        .map_err(|e| anyhow::anyhow!("Failed to load merged locales: {}", e))?;
+    Ok(applied)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private merge helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merge_overrides_nested_keys() {
+        let mut base = json!({"app": {"title": "Vauchi", "tagline": "Hi"}, "ok": "OK"});
+        merge(
+            &mut base,
+            json!({"app": {"tagline": "Hallo"}, "new": "Neu"}),
+        );
+        assert_eq!(
+            base,
+            json!({"app": {"title": "Vauchi", "tagline": "Hallo"}, "ok": "OK", "new": "Neu"})
+        );
+    }
+
+    #[test]
+    fn test_build_merged_applies_valid_overrides_only() {
+        let temp = TempDir::new().unwrap();
+        let resources = temp.path().join("resources");
+        let overrides = temp.path().join(OVERRIDE_DIR);
+        let merged = temp.path().join(MERGED_DIR);
+        std::fs::create_dir_all(&resources).unwrap();
+        std::fs::create_dir_all(&overrides).unwrap();
+        std::fs::write(resources.join("de.json"), r#"{"a":"A","b":"B"}"#).unwrap();
+        std::fs::write(resources.join("en.json"), r#"{"a":"A"}"#).unwrap();
+        std::fs::write(overrides.join("de.json"), r#"{"b":"Bee"}"#).unwrap();
+        std::fs::write(overrides.join("fr.json"), "not json").unwrap();
+
+        let applied = build_merged(&resources, &overrides, &merged).unwrap();
+        assert_eq!(applied, vec!["de.json"]);
+
+        let de: Value =
+            serde_json::from_str(&std::fs::read_to_string(merged.join("de.json")).unwrap())
+                .unwrap();
+        assert_eq!(de, json!({"a": "A", "b": "Bee"}));
+        assert!(merged.join("en.json").exists());
+        assert!(!merged.join("fr.json").exists());
+    }
+}
@@ -0,0 +1,551 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Logging
+//!
+//! `tracing`-based logging to daily-rotated JSON files under
+//! `<data_dir>/logs/`. Info and above is written to disk, which is what the
+//! log viewer and support bundles read. Every field is redacted before the
+//! line is serialized: email addresses, phone numbers, long hex strings
+//! (keys, IDs), long base64 strings (ciphertext, QR payloads) and the
+//! display names of the user and their contacts are replaced with
+//! placeholders, so log files can be attached to support requests without
+//! leaking contact data.
+//!
+//! Names are only known once loaded; `AppState` hands them over with
+//! [`remember_names`] as it loads the identity and contacts.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Log directory name under the data dir.
+const LOG_DIR: &str = "logs";
+
+/// Log file name prefix (files are `vauchi.YYYY-MM-DD.log`).
+const LOG_FILE_PREFIX: &str = "vauchi";
+
+/// Log file name suffix.
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Number of daily log files kept on disk.
+const MAX_LOG_FILES: usize = 7;
+
+/// Hex runs at least this long are treated as keys or IDs.
+const MIN_REDACTED_HEX_LEN: usize = 32;
+
+/// Base64 runs at least this long are treated as ciphertext or payloads.
+const MIN_REDACTED_BASE64_LEN: usize = 40;
+
+/// Digit counts treated as phone numbers (E.164 allows at most 15).
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 7..=15;
+
+/// Names and name parts shorter than this are left alone; they would hit
+/// ordinary words.
+const MIN_REDACTED_NAME_LEN: usize = 3;
+
+/// Display names (and their parts) redacted as `[name]`.
+static NAMES: RwLock<BTreeSet<String>> = RwLock::new(BTreeSet::new());
+
+/// Path of the log directory for a data dir.
+pub fn log_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(LOG_DIR)
+}
+
+/// Install the global subscriber.
+///
+/// Logs at INFO and above go to the rotating file; debug builds also
+/// mirror them to stderr. Calling this twice is harmless.
+pub fn init(data_dir: &Path) {
+    let dir = log_dir(data_dir);
+    let appender = match std::fs::create_dir_all(&dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_FILE_PREFIX)
+                .filename_suffix(LOG_FILE_SUFFIX)
+                .max_log_files(MAX_LOG_FILES)
+                .build(&dir)
+                .map_err(|e| e.to_string())
+        }) {
+        Ok(appender) => Some(appender),
+        Err(e) => {
+            eprintln!("Warning: File logging disabled: {}", e);
+            None
+        }
+    };
+
+    let file_layer = appender.map(|appender| {
+        tracing_subscriber::fmt::layer()
+            .event_format(RedactedJson)
+            .with_ansi(false)
+            .with_writer(appender)
+            .with_filter(LevelFilter::INFO)
+    });
+
+    let stderr_layer = cfg!(debug_assertions).then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(io::stderr)
+            .with_filter(LevelFilter::INFO)
+    });
+
+    let _ = tracing_subscriber::registry()
+        .with(file_layer)
+        .with(stderr_layer)
+        .try_init();
+}
+
+/// Formats events as JSON lines (`timestamp`, `level`, `fields`,
+/// `target`), redacting each field value before it is serialized.
+struct RedactedJson;
+
+impl<S, N> FormatEvent<S, N> for RedactedJson
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut fields = RedactedFields::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let line = serde_json::json!({
+            "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            "level": metadata.level().as_str(),
+            "fields": fields.0,
+            "target": metadata.target(),
+        });
+        writeln!(writer, "{}", line)
+    }
+}
+
+/// Event fields with text values redacted; numbers and booleans are kept.
+#[derive(Default)]
+struct RedactedFields(Map<String, Value>);
+
+impl RedactedFields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for RedactedFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::String(redact(value)));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::String(redact(&format!("{:?}", value))));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+}
+
+/// Redact these display names from now on, with each of their words.
+pub fn remember_names<'a>(names: impl IntoIterator<Item = &'a str>) {
+    let mut known = NAMES.write().unwrap_or_else(|e| e.into_inner());
+    for name in names {
+        let name = name.trim();
+        for part in std::iter::once(name).chain(name.split_whitespace()) {
+            if part.chars().count() >= MIN_REDACTED_NAME_LEN {
+                known.insert(part.to_string());
+            }
+        }
+    }
+}
+
+/// Replace emails, phone numbers, long hex and long base64 tokens and
+/// known names with placeholders.
+pub fn redact(text: &str) -> String {
+    let names = NAMES.read().unwrap_or_else(|e| e.into_inner());
+    redact_names(&redact_phones(&redact_tokens(text)), &names)
+}
+
+fn redact_tokens(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut token = String::new();
+
+    for c in text.chars() {
+        if is_token_char(c) {
+            token.push(c);
+        } else {
+            out.push_str(&redact_token(&token));
+            token.clear();
+            out.push(c);
+        }
+    }
+    out.push_str(&redact_token(&token));
+    out
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '+' | '/' | '=' | '-' | '_')
+}
+
+fn redact_token(token: &str) -> String {
+    if let Some((local, domain)) = token.split_once('@') {
+        if !local.is_empty() && domain.contains('.') && !domain.starts_with('.') {
+            return "[email]".to_string();
+        }
+    }
+    if token.len() >= MIN_REDACTED_HEX_LEN && token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return "[hex]".to_string();
+    }
+    if token.len() >= MIN_REDACTED_BASE64_LEN
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '-' | '_'))
+        && token.chars().any(|c| c.is_ascii_digit())
+    {
+        return "[data]".to_string();
+    }
+    token.to_string()
+}
+
+/// Replace runs of digits and phone separators (space, `-`, `.`,
+/// parentheses, a leading `+`) that stand alone and hold a phone number's
+/// worth of digits. ISO dates are kept.
+fn redact_phones(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let standalone = i == 0 || !chars[i - 1].is_alphanumeric();
+        if standalone && (c == '+' || c == '(' || c.is_ascii_digit()) {
+            let mut digits = 0;
+            let mut end = i;
+            let mut j = i;
+            while j < chars.len()
+                && (chars[j].is_ascii_digit()
+                    || matches!(chars[j], ' ' | '-' | '.' | '(' | ')')
+                    || (j == i && chars[j] == '+'))
+            {
+                if chars[j].is_ascii_digit() {
+                    digits += 1;
+                    end = j + 1;
+                }
+                j += 1;
+            }
+            let run: String = chars[i..end].iter().collect();
+            let bounded = end == chars.len() || !chars[end].is_alphanumeric();
+            if bounded && PHONE_DIGITS.contains(&digits) && !is_iso_date(&run) {
+                out.push_str("[phone]");
+                i = end;
+                continue;
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+fn is_iso_date(run: &str) -> bool {
+    let bytes = run.as_bytes();
+    bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit())
+}
+
+/// Replace whole-word occurrences of known names; at each word the longest
+/// name wins, so a full name is replaced once rather than part by part.
+fn redact_names(text: &str, names: &BTreeSet<String>) -> String {
+    let Some(longest) = names.iter().map(String::len).max() else {
+        return text.to_string();
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut prev: Option<char> = None;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        if !prev.is_some_and(char::is_alphanumeric) {
+            if let Some(len) = name_at(&text[i..], names, longest) {
+                out.push_str("[name]");
+                prev = text[..i + len].chars().next_back();
+                i += len;
+                continue;
+            }
+        }
+        out.push(c);
+        prev = Some(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+/// Length of the longest known name that starts `text` and ends at a word
+/// boundary.
+fn name_at(text: &str, names: &BTreeSet<String>, longest: usize) -> Option<usize> {
+    let mut ends: Vec<usize> = text
+        .char_indices()
+        .skip(1)
+        .take_while(|&(end, _)| end <= longest)
+        .filter(|&(_, c)| !c.is_alphanumeric())
+        .map(|(end, _)| end)
+        .collect();
+    if text.len() <= longest {
+        ends.push(text.len());
+    }
+    ends.into_iter()
+        .rev()
+        .find(|&end| names.contains(&text[..end]))
+}
+
+/// A parsed log line for the frontend.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// Raw JSON line written by the fmt layer.
+#[derive(Deserialize)]
+struct RawLogLine {
+    timestamp: String,
+    level: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    fields: RawFields,
+}
+
+#[derive(Deserialize, Default)]
+struct RawFields {
+    #[serde(default)]
+    message: String,
+}
+
+/// Numeric severity for filtering (higher is more severe).
+fn severity(level: &str) -> u8 {
+    match level.to_ascii_uppercase().as_str() {
+        "TRACE" => 0,
+        "DEBUG" => 1,
+        "INFO" => 2,
+        "WARN" => 3,
+        "ERROR" => 4,
+        _ => 2,
+    }
+}
+
+/// Log files in the log dir, oldest first.
+pub fn log_files(data_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(log_dir(data_dir))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Date-stamped names sort chronologically
+    files.sort();
+    files
+}
+
+/// Read the most recent log entries at or above `min_level`, newest last.
+pub fn recent_entries(data_dir: &Path, min_level: &str, limit: usize) -> Vec<LogEntry> {
+    let min = severity(min_level);
+    let mut entries: Vec<LogEntry> = Vec::new();
+
+    for path in log_files(data_dir).iter().rev() {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let mut file_entries: Vec<LogEntry> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<RawLogLine>(line).ok())
+            .filter(|raw| severity(&raw.level) >= min)
+            .map(|raw| LogEntry {
+                timestamp: raw.timestamp,
+                level: raw.level,
+                target: raw.target,
+                message: raw.fields.message,
+            })
+            .collect();
+        file_entries.append(&mut entries);
+        entries = file_entries;
+        if entries.len() >= limit {
+            break;
+        }
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    entries.split_off(skip)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private redaction and log parsing helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_redact_email() {
+        assert_eq!(redact("sent to alice@example.com ok"), "sent to [email] ok");
+    }
+
+    #[test]
+    fn test_redact_long_hex() {
+        let key = "ab".repeat(32);
+        assert_eq!(redact(&format!("contact {}", key)), "contact [hex]");
+    }
+
+    #[test]
+    fn test_redact_base64_payload() {
+        let payload = "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVoxMjM0NTY3ODkw";
+        assert_eq!(redact(&format!("\"{}\"", payload)), "\"[data]\"");
+    }
+
+    #[test]
+    fn test_redact_keeps_ordinary_text() {
+        let text = "Warning: Failed to set up system tray: no display";
+        assert_eq!(redact(text), text);
+    }
+
+    #[test]
+    fn test_redact_phone_numbers() {
+        assert_eq!(redact("call +41 79 123 45 67 now"), "call [phone] now");
+        assert_eq!(redact("(555) 123-4567."), "[phone].");
+        assert_eq!(redact("tel:0791234567"), "tel:[phone]");
+        // Timestamps, dates and small numbers stay readable
+        let text = "2026-01-01T00:00:00Z synced 2026-01-02, 3 of 12 contacts";
+        assert_eq!(redact(text), text);
+    }
+
+    #[test]
+    fn test_redact_known_names() {
+        let names: BTreeSet<String> = ["Alice Smith", "Alice", "Smith", "Bob"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            redact_names("Alice Smith and Bob, not Bobby; Alice", &names),
+            "[name] and [name], not Bobby; [name]"
+        );
+
+        remember_names(["Zoë Q"]);
+        assert_eq!(redact("from Zoë"), "from [name]");
+        // Too short to redact on its own
+        assert_eq!(redact("Q"), "Q");
+    }
+
+    /// Shared buffer the test subscriber writes to.
+    #[derive(Clone, Default)]
+    struct Buffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fields_are_redacted_before_serialization() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(RedactedJson)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(
+                contact = "bob@example.com",
+                count = 3,
+                "sent to alice@example.com"
+            );
+        });
+
+        let line = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(!line.contains("example.com"));
+        let raw: RawLogLine = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(raw.level, "WARN");
+        assert_eq!(raw.fields.message, "sent to [email]");
+        let json: Value = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(json["fields"]["contact"], "[email]");
+        assert_eq!(json["fields"]["count"], 3);
+    }
+
+    #[test]
+    fn test_recent_entries_filters_and_limits() {
+        let temp = TempDir::new().unwrap();
+        let dir = log_dir(temp.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        let line = |level: &str, msg: &str| {
+            format!(
+                "{{\"timestamp\":\"2026-01-01T00:00:00Z\",\"level\":\"{}\",\"fields\":{{\"message\":\"{}\"}},\"target\":\"t\"}}\n",
+                level, msg
+            )
+        };
+        std::fs::write(
+            dir.join("vauchi.2026-01-01.log"),
+            line("INFO", "a") + &line("WARN", "b"),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("vauchi.2026-01-02.log"),
+            line("ERROR", "c") + &line("INFO", "d"),
+        )
+        .unwrap();
+
+        let warn: Vec<String> = recent_entries(temp.path(), "warn", 10)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(warn, vec!["b", "c"]);
+
+        let last_two: Vec<String> = recent_entries(temp.path(), "info", 2)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(last_two, vec!["c", "d"]);
+    }
+}
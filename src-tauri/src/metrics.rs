@@ -0,0 +1,223 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Performance Metrics
+//!
+//! In-process timing of Tauri commands and sync phases. Each metric
+//! keeps a bounded window of recent samples and reports count and
+//! percentiles, so slowdowns (e.g. listing contacts once there are
+//! hundreds) can be measured rather than guessed at. Nothing leaves the
+//! device.
+//!
+//! The invoke handler times every command until it returns. An async
+//! command returns once dispatched, so it is timed to completion by
+//! `error_stats::track` instead, and its dispatch times are not kept.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Samples kept per metric.
+const MAX_SAMPLES: usize = 512;
+
+/// Recent samples for one metric.
+#[derive(Default)]
+struct Samples {
+    /// Total number of samples ever recorded.
+    count: u64,
+    /// Most recent durations, in microseconds.
+    recent: VecDeque<u64>,
+}
+
+/// Samples by metric name.
+#[derive(Default)]
+struct Registry {
+    metrics: HashMap<String, Samples>,
+    /// Async commands seen completing; their dispatch times are ignored.
+    async_commands: HashSet<String>,
+}
+
+impl Registry {
+    fn record(&mut self, name: &str, elapsed: Duration) {
+        let samples = self.metrics.entry(name.to_string()).or_default();
+        samples.count += 1;
+        if samples.recent.len() == MAX_SAMPLES {
+            samples.recent.pop_front();
+        }
+        samples.recent.push_back(elapsed.as_micros() as u64);
+    }
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// Record one duration for a metric.
+pub fn record(name: &str, elapsed: Duration) {
+    let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    registry.record(name, elapsed);
+}
+
+/// Record the time the invoke handler took for `command`, unless it is an
+/// async command, which only dispatched in that time.
+pub fn record_dispatch(command: &str, elapsed: Duration) {
+    let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    if !registry.async_commands.contains(command) {
+        registry.record(command, elapsed);
+    }
+}
+
+/// Record the time async `command` took to complete. The first time,
+/// its dispatch time, if already recorded, is dropped.
+pub fn record_completion(command: &str, elapsed: Duration) {
+    let mut registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    if registry.async_commands.insert(command.to_string()) {
+        registry.metrics.remove(command);
+    }
+    registry.record(command, elapsed);
+}
+
+/// Records the time from `start` until drop, including early returns.
+pub struct Timer {
+    name: &'static str,
+    start: Instant,
+}
+
+impl Timer {
+    /// Start timing a metric.
+    pub fn start(name: &'static str) -> Self {
+        Self {
+            name,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        record(self.name, self.start.elapsed());
+    }
+}
+
+/// Summary of one metric for the frontend. Durations are in microseconds.
+#[derive(Serialize, Clone, Debug)]
+pub struct MetricSummary {
+    pub name: String,
+    /// Total samples recorded since startup.
+    pub count: u64,
+    /// Samples the percentiles are computed from.
+    pub window: usize,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Summaries of all metrics, sorted by name.
+pub fn snapshot() -> Vec<MetricSummary> {
+    let registry = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let mut summaries: Vec<MetricSummary> = registry
+        .metrics
+        .iter()
+        .map(|(name, samples)| {
+            let mut sorted: Vec<u64> = samples.recent.iter().copied().collect();
+            sorted.sort_unstable();
+            MetricSummary {
+                name: name.clone(),
+                count: samples.count,
+                window: sorted.len(),
+                p50_us: percentile(&sorted, 50),
+                p90_us: percentile(&sorted, 90),
+                p99_us: percentile(&sorted, 99),
+                max_us: sorted.last().copied().unwrap_or(0),
+            }
+        })
+        .collect();
+    summaries.sort_by(|a, b| a.name.cmp(&b.name));
+    summaries
+}
+
+/// Drop all recorded samples.
+pub fn reset() {
+    registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .metrics
+        .clear();
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private metrics registry
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 50), 50);
+        assert_eq!(percentile(&sorted, 90), 90);
+        assert_eq!(percentile(&sorted, 99), 99);
+        assert_eq!(percentile(&[], 50), 0);
+        assert_eq!(percentile(&[7], 99), 7);
+    }
+
+    #[test]
+    fn test_record_and_snapshot() {
+        record("test:record", Duration::from_micros(10));
+        record("test:record", Duration::from_micros(30));
+
+        let summary = snapshot()
+            .into_iter()
+            .find(|m| m.name == "test:record")
+            .unwrap();
+        assert!(summary.count >= 2);
+        assert!(summary.max_us >= 30);
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        for i in 0..(MAX_SAMPLES as u64 + 10) {
+            record("test:bounded", Duration::from_micros(i));
+        }
+        let summary = snapshot()
+            .into_iter()
+            .find(|m| m.name == "test:bounded")
+            .unwrap();
+        assert_eq!(summary.window, MAX_SAMPLES);
+    }
+
+    #[test]
+    fn test_async_commands_keep_only_completion_times() {
+        record_dispatch("test_async", Duration::from_micros(1));
+        record_completion("test_async", Duration::from_micros(500));
+        record_dispatch("test_async", Duration::from_micros(1));
+
+        let summary = snapshot()
+            .into_iter()
+            .find(|m| m.name == "test_async")
+            .unwrap();
+        assert_eq!(summary.count, 1);
+        assert_eq!(summary.max_us, 500);
+    }
+
+    #[test]
+    fn test_timer_records_on_drop() {
+        {
+            let _timer = Timer::start("test:timer");
+        }
+        assert!(snapshot().iter().any(|m| m.name == "test:timer"));
+    }
+}
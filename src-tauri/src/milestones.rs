@@ -0,0 +1,329 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Desktop Milestones
+//!
+//! Aha moments beyond core's `AhaMomentType`, reached from desktop data:
+//! the tenth contact, the first label, the first linked device and the
+//! first exported backup. Commands call [`record`] when one is reached; the
+//! frontend picks reached-but-unshown moments up with `take_aha_moments`.
+//!
+//! Each milestone is reached once. They are tracked in the same
+//! `aha_tracker.json` as core's aha moments, under a `desktop` key that
+//! core's tracker never sees; the tracker also keeps when each aha moment
+//! (core or desktop) was first shown. Trackers from `aha_desktop.json`,
+//! used by earlier versions, are moved over on the next save.
+//!
+//! Titles and messages are bundled desktop strings (`desktop_strings`)
+//! under `aha.<milestone>.title` and `aha.<milestone>.message`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use vauchi_core::aha_moments::AhaMomentTracker;
+
+use crate::desktop_strings;
+
+/// Tracker file name under the data dir, shared with core's tracker.
+const TRACKER_FILE: &str = "aha_tracker.json";
+
+/// Key of the desktop tracker in [`TRACKER_FILE`].
+const DESKTOP_KEY: &str = "desktop";
+
+/// Separate tracker file of earlier versions.
+const LEGACY_TRACKER_FILE: &str = "aha_desktop.json";
+
+/// Contacts needed for [`Milestone::TenContactsReached`].
+pub const TEN_CONTACTS: usize = 10;
+
+/// A desktop milestone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Milestone {
+    TenContactsReached,
+    FirstLabelCreated,
+    FirstDeviceLinked,
+    FirstBackupExported,
+}
+
+impl Milestone {
+    /// All desktop milestones.
+    pub const ALL: [Milestone; 4] = [
+        Milestone::TenContactsReached,
+        Milestone::FirstLabelCreated,
+        Milestone::FirstDeviceLinked,
+        Milestone::FirstBackupExported,
+    ];
+
+    /// Stable name, as sent in `moment_type`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Milestone::TenContactsReached => "ten_contacts_reached",
+            Milestone::FirstLabelCreated => "first_label_created",
+            Milestone::FirstDeviceLinked => "first_device_linked",
+            Milestone::FirstBackupExported => "first_backup_exported",
+        }
+    }
+
+    /// Title and message in the locale `locale_code`, or in English if
+    /// the locale has no texts.
+    pub fn texts(self, locale_code: &str) -> (String, String) {
+        (
+            desktop_strings::get(locale_code, &format!("aha.{}.title", self.as_str())),
+            desktop_strings::get(locale_code, &format!("aha.{}.message", self.as_str())),
+        )
+    }
+}
+
+/// Reached milestones, which of them were shown, and when aha moments
+/// were first shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MilestoneTracker {
+    pub reached: Vec<Milestone>,
+    pub shown: Vec<Milestone>,
+    /// Aha moment name → Unix seconds it was first shown.
+    pub seen_at: BTreeMap<String, u64>,
+}
+
+impl MilestoneTracker {
+    /// Reached milestones not shown yet, marking them shown at `now`.
+    pub fn take_pending(&mut self, now: u64) -> Vec<Milestone> {
+        let pending: Vec<Milestone> = self
+            .reached
+            .iter()
+            .filter(|m| !self.shown.contains(m))
+            .copied()
+            .collect();
+        for milestone in &pending {
+            self.seen_at
+                .entry(milestone.as_str().to_string())
+                .or_insert(now);
+        }
+        self.shown.extend(&pending);
+        pending
+    }
+}
+
+fn tracker_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(TRACKER_FILE)
+}
+
+/// The tracker file as a JSON object, empty if missing or unreadable.
+fn read_file(data_dir: &Path) -> Map<String, Value> {
+    std::fs::read_to_string(tracker_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn write_file(data_dir: &Path, file: Map<String, Value>) -> std::io::Result<()> {
+    std::fs::write(tracker_path(data_dir), Value::Object(file).to_string())
+}
+
+/// Load core's aha moment tracker, empty if missing or unreadable.
+pub fn load_core(data_dir: &Path) -> AhaMomentTracker {
+    let mut file = read_file(data_dir);
+    file.remove(DESKTOP_KEY);
+    AhaMomentTracker::from_json(&Value::Object(file).to_string()).unwrap_or_default()
+}
+
+/// Save core's aha moment tracker, keeping the desktop tracker.
+pub fn save_core(data_dir: &Path, tracker: &AhaMomentTracker) -> std::io::Result<()> {
+    let json = tracker
+        .to_json()
+        .map_err(|e| std::io::Error::other(format!("{:?}", e)))?;
+    let mut file: Map<String, Value> = serde_json::from_str(&json)?;
+    if let Some(desktop) = read_file(data_dir).remove(DESKTOP_KEY) {
+        file.insert(DESKTOP_KEY.to_string(), desktop);
+    }
+    write_file(data_dir, file)
+}
+
+/// Load the desktop tracker, empty if missing or unreadable.
+pub fn load(data_dir: &Path) -> MilestoneTracker {
+    match read_file(data_dir).remove(DESKTOP_KEY) {
+        Some(desktop) => serde_json::from_value(desktop).ok(),
+        None => std::fs::read_to_string(data_dir.join(LEGACY_TRACKER_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok()),
+    }
+    .unwrap_or_default()
+}
+
+/// Save the desktop tracker, keeping core's tracker.
+pub fn save(data_dir: &Path, tracker: &MilestoneTracker) -> std::io::Result<()> {
+    let mut file = read_file(data_dir);
+    file.insert(DESKTOP_KEY.to_string(), serde_json::to_value(tracker)?);
+    write_file(data_dir, file)?;
+    match std::fs::remove_file(data_dir.join(LEGACY_TRACKER_FILE)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Mark `milestone` reached if it was not already.
+pub fn record(data_dir: &Path, milestone: Milestone) {
+    let mut tracker = load(data_dir);
+    if tracker.reached.contains(&milestone) {
+        return;
+    }
+    tracker.reached.push(milestone);
+    if let Err(e) = save(data_dir, &tracker) {
+        tracing::warn!("Failed to save milestones: {}", e);
+    }
+}
+
+/// Remember when the aha moment `moment_type` was first shown.
+pub fn mark_seen(data_dir: &Path, moment_type: &str, now: u64) {
+    let mut tracker = load(data_dir);
+    if tracker.seen_at.contains_key(moment_type) {
+        return;
+    }
+    tracker.seen_at.insert(moment_type.to_string(), now);
+    if let Err(e) = save(data_dir, &tracker) {
+        tracing::warn!("Failed to save milestones: {}", e);
+    }
+}
+
+/// Forget which aha moments (core and desktop) were shown and when, so
+/// they are shown again. Reached milestones stay reached.
+pub fn reset(data_dir: &Path) -> std::io::Result<()> {
+    save_core(data_dir, &AhaMomentTracker::default())?;
+    let mut tracker = load(data_dir);
+    tracker.shown.clear();
+    tracker.seen_at.clear();
+    save(data_dir, &tracker)
+}
+
+/// Record [`Milestone::TenContactsReached`] once `contact_count` gets there.
+pub fn record_contact_count(data_dir: &Path, contact_count: usize) {
+    if contact_count >= TEN_CONTACTS {
+        record(data_dir, Milestone::TenContactsReached);
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private tracker persistence
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use vauchi_core::aha_moments::AhaMomentType;
+
+    #[test]
+    fn test_milestone_is_shown_once() {
+        let temp = TempDir::new().unwrap();
+        record(temp.path(), Milestone::FirstLabelCreated);
+        record(temp.path(), Milestone::FirstLabelCreated);
+
+        let mut tracker = load(temp.path());
+        assert_eq!(
+            tracker.take_pending(100),
+            vec![Milestone::FirstLabelCreated]
+        );
+        assert!(tracker.take_pending(200).is_empty());
+        assert_eq!(tracker.seen_at.get("first_label_created"), Some(&100));
+    }
+
+    #[test]
+    fn test_seen_time_is_kept_and_reset() {
+        let temp = TempDir::new().unwrap();
+        reset(temp.path()).unwrap();
+        mark_seen(temp.path(), "first_edit", 100);
+        mark_seen(temp.path(), "first_edit", 200);
+        assert_eq!(load(temp.path()).seen_at.get("first_edit"), Some(&100));
+
+        record(temp.path(), Milestone::FirstLabelCreated);
+        record(temp.path(), Milestone::FirstBackupExported);
+        let mut tracker = load(temp.path());
+        tracker.take_pending(300);
+        save(temp.path(), &tracker).unwrap();
+
+        // Reached milestones survive and are shown again
+        reset(temp.path()).unwrap();
+        let mut tracker = load(temp.path());
+        assert!(tracker.seen_at.is_empty());
+        assert_eq!(
+            tracker.take_pending(400),
+            vec![Milestone::FirstLabelCreated, Milestone::FirstBackupExported]
+        );
+    }
+
+    #[test]
+    fn test_ten_contacts_threshold() {
+        let temp = TempDir::new().unwrap();
+        record_contact_count(temp.path(), TEN_CONTACTS - 1);
+        assert!(load(temp.path()).reached.is_empty());
+        record_contact_count(temp.path(), TEN_CONTACTS);
+        assert_eq!(
+            load(temp.path()).reached,
+            vec![Milestone::TenContactsReached]
+        );
+    }
+
+    #[test]
+    fn test_shares_the_file_with_core_tracker() {
+        let temp = TempDir::new().unwrap();
+        let mut core = load_core(temp.path());
+        core.try_trigger(AhaMomentType::FirstEdit);
+        save_core(temp.path(), &core).unwrap();
+        record(temp.path(), Milestone::FirstDeviceLinked);
+
+        assert!(load_core(temp.path()).has_seen(AhaMomentType::FirstEdit));
+        save_core(temp.path(), &load_core(temp.path())).unwrap();
+        assert_eq!(
+            load(temp.path()).reached,
+            vec![Milestone::FirstDeviceLinked]
+        );
+    }
+
+    #[test]
+    fn test_legacy_tracker_is_moved_over() {
+        let temp = TempDir::new().unwrap();
+        let legacy = temp.path().join(LEGACY_TRACKER_FILE);
+        std::fs::write(&legacy, r#"{"reached":["first_label_created"]}"#).unwrap();
+        assert_eq!(
+            load(temp.path()).reached,
+            vec![Milestone::FirstLabelCreated]
+        );
+
+        record(temp.path(), Milestone::FirstBackupExported);
+        assert!(!legacy.exists());
+        assert_eq!(
+            load(temp.path()).reached,
+            vec![Milestone::FirstLabelCreated, Milestone::FirstBackupExported]
+        );
+    }
+
+    #[test]
+    fn test_every_milestone_has_texts() {
+        for milestone in Milestone::ALL {
+            let (title, message) = milestone.texts("en");
+            assert!(
+                !title.starts_with("aha."),
+                "{} has no title",
+                milestone.as_str()
+            );
+            assert!(
+                !message.starts_with("aha."),
+                "{} has no message",
+                milestone.as_str()
+            );
+        }
+        let (german, _) = Milestone::FirstLabelCreated.texts("de");
+        assert_eq!(german, "Erstes Label erstellt");
+        let (fallback, _) = Milestone::FirstLabelCreated.texts("xx");
+        assert_eq!(fallback, "First label created");
+    }
+
+    #[test]
+    fn test_names_match_serde() {
+        for milestone in Milestone::ALL {
+            let json = serde_json::to_string(&milestone).unwrap();
+            assert_eq!(json, format!("\"{}\"", milestone.as_str()));
+        }
+    }
+}
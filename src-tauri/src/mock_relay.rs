@@ -0,0 +1,195 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Mock Relay
+//!
+//! In-process loopback relay used when `VAUCHI_MOCK_RELAY=1` (debug builds
+//! only). Instead of opening a WebSocket, sync gets a `MockConnection` to
+//! the process-wide [`MockRelay::shared`], which stores outbound envelopes
+//! in per-identity mailboxes. Any other `AppState` in the same process that
+//! syncs afterwards receives them, so exchange and sync E2E tests run
+//! without a live relay server. Unit tests make their own `MockRelay`, so
+//! their mailboxes do not mix.
+//!
+//! Routing mirrors the relay: encrypted updates go to `recipient_id`,
+//! device sync messages go to the sender's other devices. Acks and
+//! handshakes are accepted and dropped.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use vauchi_core::network::simple_message::{decode_simple_message, SimplePayload};
+
+/// Environment variable that enables the mock relay.
+const MOCK_RELAY_ENV: &str = "VAUCHI_MOCK_RELAY";
+
+/// A message waiting in a mailbox.
+struct Queued {
+    /// Device that must not receive this message (the sending device).
+    skip_device: Option<String>,
+    data: Vec<u8>,
+}
+
+/// Mailboxes keyed by identity public ID.
+type Mailboxes = Arc<Mutex<HashMap<String, VecDeque<Queued>>>>;
+
+/// An in-process relay: a set of mailboxes its connections share.
+#[derive(Clone, Default)]
+pub struct MockRelay {
+    mailboxes: Mailboxes,
+}
+
+impl MockRelay {
+    /// A relay with empty mailboxes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The relay every `AppState` in the process syncs with.
+    pub fn shared() -> &'static MockRelay {
+        static SHARED: OnceLock<MockRelay> = OnceLock::new();
+        SHARED.get_or_init(MockRelay::new)
+    }
+
+    /// Open a new, unregistered connection.
+    pub fn connect(&self) -> MockConnection {
+        MockConnection {
+            mailboxes: Arc::clone(&self.mailboxes),
+            identity_id: None,
+            device_id: None,
+        }
+    }
+}
+
+/// Whether the mock relay replaces the network transport.
+pub fn is_enabled() -> bool {
+    cfg!(debug_assertions) && std::env::var(MOCK_RELAY_ENV).is_ok_and(|v| v == "1")
+}
+
+/// A client connection to the in-process relay.
+///
+/// Like a relay socket, it only receives after `register` (the handshake).
+pub struct MockConnection {
+    mailboxes: Mailboxes,
+    identity_id: Option<String>,
+    device_id: Option<String>,
+}
+
+impl MockConnection {
+    /// Identify the connection (equivalent of the relay handshake).
+    pub fn register(&mut self, identity_id: String, device_id: Option<String>) {
+        self.identity_id = Some(identity_id);
+        self.device_id = device_id;
+    }
+
+    /// Route an encoded envelope into the recipient's mailbox.
+    pub fn send(&mut self, data: &[u8]) {
+        let Ok(envelope) = decode_simple_message(data) else {
+            return;
+        };
+        let (recipient, skip_device) = match envelope.payload {
+            SimplePayload::EncryptedUpdate(update) => (update.recipient_id, None),
+            SimplePayload::DeviceSyncMessage(_) => match &self.identity_id {
+                Some(id) => (id.clone(), self.device_id.clone()),
+                None => return,
+            },
+            _ => return,
+        };
+
+        self.mailboxes
+            .lock()
+            .unwrap()
+            .entry(recipient)
+            .or_default()
+            .push_back(Queued {
+                skip_device,
+                data: data.to_vec(),
+            });
+    }
+
+    /// Take the next message for this connection, or `None` when the
+    /// mailbox is drained.
+    pub fn recv(&mut self) -> Option<Vec<u8>> {
+        let identity_id = self.identity_id.as_ref()?;
+        let mut mailboxes = self.mailboxes.lock().unwrap();
+        let queue = mailboxes.get_mut(identity_id)?;
+        let index = queue
+            .iter()
+            .position(|m| m.skip_device.is_none() || m.skip_device != self.device_id)?;
+        queue.remove(index).map(|m| m.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vauchi_core::network::simple_message::{
+        create_simple_envelope, encode_simple_message, SimpleEncryptedUpdate,
+    };
+
+    fn update(sender: &str, recipient: &str, ciphertext: &[u8]) -> Vec<u8> {
+        let envelope =
+            create_simple_envelope(SimplePayload::EncryptedUpdate(SimpleEncryptedUpdate {
+                recipient_id: recipient.to_string(),
+                sender_id: sender.to_string(),
+                ciphertext: ciphertext.to_vec(),
+            }));
+        encode_simple_message(&envelope).unwrap()
+    }
+
+    #[test]
+    fn test_update_delivered_to_recipient_only() {
+        let relay = MockRelay::new();
+        let mut alice = relay.connect();
+        alice.register("alice".to_string(), None);
+        let mut bob = relay.connect();
+        bob.register("bob".to_string(), None);
+
+        let data = update("alice", "bob", b"hello");
+        alice.send(&data);
+
+        assert!(alice.recv().is_none());
+        assert_eq!(bob.recv(), Some(data));
+        assert!(bob.recv().is_none());
+    }
+
+    #[test]
+    fn test_messages_wait_until_recipient_connects() {
+        let relay = MockRelay::new();
+        let mut alice = relay.connect();
+        alice.register("alice".to_string(), None);
+        let one = update("alice", "bob", b"one");
+        let two = update("alice", "bob", b"two");
+        alice.send(&one);
+        alice.send(&two);
+
+        let mut bob = relay.connect();
+        assert!(
+            bob.recv().is_none(),
+            "Unregistered connection receives nothing"
+        );
+        bob.register("bob".to_string(), None);
+        assert_eq!(bob.recv(), Some(one));
+        assert_eq!(bob.recv(), Some(two));
+    }
+
+    #[test]
+    fn test_garbage_is_dropped() {
+        let mut alice = MockRelay::new().connect();
+        alice.register("alice".to_string(), None);
+        alice.send(b"not an envelope");
+        assert!(alice.recv().is_none());
+    }
+
+    #[test]
+    fn test_relays_do_not_share_mailboxes() {
+        let mut alice = MockRelay::new().connect();
+        alice.register("alice".to_string(), None);
+        alice.send(&update("alice", "bob", b"hello"));
+
+        let mut bob = MockRelay::new().connect();
+        bob.register("bob".to_string(), None);
+        assert!(bob.recv().is_none());
+    }
+}
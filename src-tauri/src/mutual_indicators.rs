@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Mutual Contact Indicators
+//!
+//! An estimate of how many of the user's contacts also know a contact,
+//! to help judge whether a new contact is who they claim to be. It only
+//! uses data already on this device, so nothing is asked of the relay or
+//! of other contacts:
+//!
+//! - our contacts who validated the contact's fields;
+//! - our contacts whose fields the contact validated;
+//! - our contacts who vouched for a recovery of the contact's identity
+//!   that is still awaiting re-verification.
+//!
+//! Contacts are matched by ID and public key; display names are only
+//! returned for display. Blocked contacts do not count. Confidence is high
+//! once the number of mutual contacts meets the verification threshold of
+//! the recovery policy.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use vauchi_core::{Contact, Storage};
+
+/// How much the mutual contacts say about the contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MutualConfidence {
+    None,
+    Low,
+    High,
+}
+
+/// One of our contacts that also knows the contact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MutualContact {
+    pub contact_id: String,
+    pub display_name: String,
+}
+
+impl MutualContact {
+    fn of(contact: &Contact) -> Self {
+        Self {
+            contact_id: contact.id().to_string(),
+            display_name: contact.display_name().to_string(),
+        }
+    }
+}
+
+/// Mutual connections with a contact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MutualIndicators {
+    pub contact_id: String,
+    /// Our contacts who validated the contact's fields.
+    pub shared_validators: Vec<MutualContact>,
+    /// Our contacts whose fields the contact validated.
+    pub validated_contacts: Vec<MutualContact>,
+    /// Our contacts who vouched for the contact's recovery.
+    pub shared_vouchers: Vec<MutualContact>,
+    /// Distinct contacts across the three lists.
+    pub estimated_mutual: usize,
+    pub confidence: MutualConfidence,
+}
+
+/// Hex keys of everyone who validated any of `owner`'s fields.
+fn validators_of(storage: &Storage, owner: &Contact) -> HashSet<String> {
+    owner
+        .card()
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            storage
+                .load_validations_for_field(owner.id(), field.id())
+                .ok()
+        })
+        .flatten()
+        .map(|v| v.validator_id().to_string())
+        .collect()
+}
+
+/// Compute the indicators for `contact` against the user's `contacts`.
+/// `voucher_ids` are the IDs of our contacts who vouched for its recovery.
+pub fn indicators(
+    storage: &Storage,
+    contact: &Contact,
+    contacts: &[Contact],
+    voucher_ids: &[String],
+    threshold: u32,
+) -> MutualIndicators {
+    let contact_key = hex::encode(contact.public_key());
+    let contact_validators = validators_of(storage, contact);
+    let others: Vec<&Contact> = contacts
+        .iter()
+        .filter(|c| c.id() != contact.id() && !c.is_blocked())
+        .collect();
+
+    let mut shared_validators = Vec::new();
+    let mut validated_contacts = Vec::new();
+    let mut shared_vouchers = Vec::new();
+    for other in &others {
+        if contact_validators.contains(&hex::encode(other.public_key())) {
+            shared_validators.push(MutualContact::of(other));
+        }
+        if validators_of(storage, other).contains(&contact_key) {
+            validated_contacts.push(MutualContact::of(other));
+        }
+        if voucher_ids.iter().any(|id| id == other.id()) {
+            shared_vouchers.push(MutualContact::of(other));
+        }
+    }
+
+    let estimated_mutual = shared_validators
+        .iter()
+        .chain(&validated_contacts)
+        .chain(&shared_vouchers)
+        .map(|c| c.contact_id.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+    let confidence = match estimated_mutual {
+        0 => MutualConfidence::None,
+        n if n >= threshold as usize => MutualConfidence::High,
+        _ => MutualConfidence::Low,
+    };
+
+    MutualIndicators {
+        contact_id: contact.id().to_string(),
+        shared_validators,
+        validated_contacts,
+        shared_vouchers,
+        estimated_mutual,
+        confidence,
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private validation lookups on scratch storage
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use vauchi_core::{
+        ContactCard, ContactField, FieldType, Identity, ProfileValidation, SymmetricKey,
+    };
+
+    use crate::state::AppState;
+
+    fn contact(key: [u8; 32], name: &str) -> Contact {
+        let mut card = ContactCard::new(name);
+        card.add_field(ContactField::new(
+            FieldType::Email,
+            "email",
+            "x@example.com",
+        ))
+        .unwrap();
+        Contact::from_exchange(key, card, SymmetricKey::generate())
+    }
+
+    #[test]
+    fn test_counts_validators_and_vouchers_once() {
+        let temp = TempDir::new().unwrap();
+        let state = AppState::new(temp.path()).unwrap();
+        let bob = Identity::create("Bob");
+        let carol = Identity::create("Carol");
+        let dave = contact([9; 32], "Dave");
+        let bob_contact = contact(*bob.signing_public_key(), "Bob");
+        // Same name as Bob, but a different contact who vouched
+        let other_bob = contact([7; 32], "Bob");
+        let contacts = vec![
+            bob_contact.clone(),
+            contact(*carol.signing_public_key(), "Carol"),
+            other_bob.clone(),
+            dave.clone(),
+        ];
+        let field_id = dave.card().fields()[0].id().to_string();
+
+        assert_eq!(
+            indicators(&state.storage, &dave, &contacts, &[], 2).confidence,
+            MutualConfidence::None
+        );
+
+        // Bob validated Dave and vouched for him; Carol validated Dave
+        for validator in [&bob, &carol] {
+            let validation =
+                ProfileValidation::create_signed(validator, &field_id, "x@example.com", dave.id());
+            state.storage.save_validation(&validation).unwrap();
+        }
+        let vouchers = vec![bob_contact.id().to_string(), "unknown".to_string()];
+        let result = indicators(&state.storage, &dave, &contacts, &vouchers, 3);
+
+        let names = |list: &[MutualContact]| {
+            list.iter()
+                .map(|c| c.display_name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&result.shared_validators), vec!["Bob", "Carol"]);
+        assert!(result.validated_contacts.is_empty());
+        assert_eq!(
+            result.shared_vouchers,
+            vec![MutualContact::of(&bob_contact)]
+        );
+        assert_eq!(result.estimated_mutual, 2);
+        assert_eq!(result.confidence, MutualConfidence::Low);
+
+        // The other Bob is counted separately, not merged by name
+        let vouchers = vec![bob_contact.id().to_string(), other_bob.id().to_string()];
+        let result = indicators(&state.storage, &dave, &contacts, &vouchers, 3);
+        assert_eq!(result.estimated_mutual, 3);
+        assert_eq!(result.confidence, MutualConfidence::High);
+    }
+}
@@ -0,0 +1,384 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! NFC Tags
+//!
+//! Writes and reads exchange payloads on NFC Forum Type 2 tags (NTAG213/
+//! 215/216) through a PC/SC reader such as the ACR122U, on Linux (pcscd)
+//! and Windows. The payload is stored as an NDEF URI record
+//! (`vauchi://exchange/<qr-data>`), so phones that tap the tag open Vauchi
+//! too.
+//!
+//! Tags are accessed with the PC/SC pseudo-APDUs for storage cards:
+//! READ BINARY (`FF B0`) and UPDATE BINARY (`FF D6`), four-byte pages,
+//! user data from page 4.
+
+// Only the tag encoding is used where PC/SC is not supported
+#![cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+
+use serde::Serialize;
+
+/// Page holding the capability container.
+const CC_PAGE: u8 = 3;
+
+/// First page of user data.
+const DATA_PAGE: u8 = 4;
+
+/// Bytes per page.
+const PAGE_SIZE: usize = 4;
+
+/// NDEF message TLV tag.
+const TLV_NDEF: u8 = 0x03;
+
+/// Terminator TLV tag.
+const TLV_TERMINATOR: u8 = 0xFE;
+
+/// NULL TLV tag (padding).
+const TLV_NULL: u8 = 0x00;
+
+/// Magic byte of an NDEF capability container.
+const CC_MAGIC: u8 = 0xE1;
+
+/// NDEF record header flags.
+const FLAG_MB: u8 = 0x80;
+const FLAG_ME: u8 = 0x40;
+const FLAG_SR: u8 = 0x10;
+const FLAG_IL: u8 = 0x08;
+
+/// TNF of NFC Forum well-known types.
+const TNF_WELL_KNOWN: u8 = 0x01;
+
+/// Whether this machine can read and write NFC tags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NfcCapability {
+    /// Built with PC/SC support for this platform.
+    pub supported: bool,
+    /// Name of the first PC/SC reader found.
+    pub reader: Option<String>,
+    /// Whether a tag is on the reader right now.
+    pub tag_present: bool,
+    /// Why NFC is unavailable, if it is.
+    pub reason: Option<String>,
+}
+
+/// Encode `uri` as an NDEF message holding one URI record, wrapped in an
+/// NDEF TLV and terminated.
+pub fn encode_uri_tlv(uri: &str) -> Vec<u8> {
+    // Identifier code 0x00: no URI prefix abbreviation
+    let mut payload = Vec::with_capacity(uri.len() + 1);
+    payload.push(0x00);
+    payload.extend_from_slice(uri.as_bytes());
+
+    let mut record = Vec::with_capacity(payload.len() + 7);
+    if payload.len() < 256 {
+        record.push(FLAG_MB | FLAG_ME | FLAG_SR | TNF_WELL_KNOWN);
+        record.push(1);
+        record.push(payload.len() as u8);
+    } else {
+        record.push(FLAG_MB | FLAG_ME | TNF_WELL_KNOWN);
+        record.push(1);
+        record.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    }
+    record.push(b'U');
+    record.extend_from_slice(&payload);
+
+    let mut tlv = Vec::with_capacity(record.len() + 5);
+    tlv.push(TLV_NDEF);
+    if record.len() < 0xFF {
+        tlv.push(record.len() as u8);
+    } else {
+        tlv.push(0xFF);
+        tlv.extend_from_slice(&(record.len() as u16).to_be_bytes());
+    }
+    tlv.extend_from_slice(&record);
+    tlv.push(TLV_TERMINATOR);
+    tlv
+}
+
+/// Read a TLV length at `pos`, returning it and the position after it.
+fn tlv_length(data: &[u8], pos: usize) -> Option<(usize, usize)> {
+    match *data.get(pos)? {
+        0xFF => {
+            let bytes = data.get(pos + 1..pos + 3)?;
+            Some((u16::from_be_bytes([bytes[0], bytes[1]]) as usize, pos + 3))
+        }
+        len => Some((len as usize, pos + 1)),
+    }
+}
+
+/// The NDEF message in a tag's user data, if there is one.
+pub fn find_ndef_message(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 0;
+    while pos < data.len() {
+        match data[pos] {
+            TLV_NULL => pos += 1,
+            TLV_TERMINATOR => return None,
+            tag => {
+                let (len, start) = tlv_length(data, pos + 1)?;
+                if tag == TLV_NDEF {
+                    return data.get(start..start + len);
+                }
+                pos = start + len;
+            }
+        }
+    }
+    None
+}
+
+/// The URI of the first record of an NDEF message, if it is a URI record.
+pub fn decode_uri_record(message: &[u8]) -> Option<String> {
+    let header = *message.first()?;
+    if header & 0x07 != TNF_WELL_KNOWN {
+        return None;
+    }
+    let type_len = *message.get(1)? as usize;
+    let (payload_len, mut pos) = if header & FLAG_SR != 0 {
+        (*message.get(2)? as usize, 3)
+    } else {
+        let bytes = message.get(2..6)?;
+        (
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize,
+            6,
+        )
+    };
+    let id_len = if header & FLAG_IL != 0 {
+        let len = *message.get(pos)? as usize;
+        pos += 1;
+        len
+    } else {
+        0
+    };
+    if message.get(pos..pos + type_len)? != b"U" {
+        return None;
+    }
+    pos += type_len + id_len;
+    let payload = message.get(pos..pos + payload_len)?;
+    // Only unabbreviated URIs are written by Vauchi
+    match payload.split_first()? {
+        (0x00, uri) => String::from_utf8(uri.to_vec()).ok(),
+        _ => None,
+    }
+}
+
+/// User data capacity in bytes from a capability container page, if the
+/// tag is NDEF formatted.
+pub fn capacity_from_cc(cc: &[u8]) -> Option<usize> {
+    (cc.len() >= PAGE_SIZE && cc[0] == CC_MAGIC).then(|| cc[2] as usize * 8)
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+mod reader {
+    use std::ffi::CString;
+    use std::time::{Duration, Instant};
+
+    use pcsc::{Card, Context, Protocols, ReaderState, Scope, ShareMode, State};
+
+    use super::*;
+
+    /// How long to wait for a tag to be put on the reader.
+    const TAG_WAIT: Duration = Duration::from_secs(15);
+
+    /// Success status word.
+    const SW_OK: [u8; 2] = [0x90, 0x00];
+
+    fn first_reader(ctx: &Context) -> Result<CString, String> {
+        let mut buf = [0u8; 2048];
+        let mut readers = ctx.list_readers(&mut buf).map_err(describe)?;
+        readers
+            .next()
+            .map(|r| r.to_owned())
+            .ok_or_else(|| "No NFC reader connected".to_string())
+    }
+
+    fn describe(e: pcsc::Error) -> String {
+        match e {
+            pcsc::Error::NoService | pcsc::Error::ServiceStopped => {
+                "The smart card service is not running (pcscd on Linux)".to_string()
+            }
+            pcsc::Error::NoReadersAvailable => "No NFC reader connected".to_string(),
+            pcsc::Error::RemovedCard | pcsc::Error::NoSmartcard => {
+                "The tag was removed from the reader".to_string()
+            }
+            pcsc::Error::Timeout => "No tag was put on the reader".to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    fn tag_present(ctx: &Context, reader: &CString) -> bool {
+        let mut states = [ReaderState::new(reader.clone(), State::UNAWARE)];
+        ctx.get_status_change(Duration::ZERO, &mut states).is_ok()
+            && states[0].event_state().contains(State::PRESENT)
+    }
+
+    pub fn capability() -> NfcCapability {
+        let unavailable = |reason: String| NfcCapability {
+            supported: true,
+            reader: None,
+            tag_present: false,
+            reason: Some(reason),
+        };
+        let ctx = match Context::establish(Scope::User) {
+            Ok(ctx) => ctx,
+            Err(e) => return unavailable(describe(e)),
+        };
+        match first_reader(&ctx) {
+            Ok(reader) => NfcCapability {
+                supported: true,
+                tag_present: tag_present(&ctx, &reader),
+                reader: Some(reader.to_string_lossy().to_string()),
+                reason: None,
+            },
+            Err(reason) => unavailable(reason),
+        }
+    }
+
+    /// Wait for a tag on the first reader and connect to it.
+    fn connect() -> Result<Card, String> {
+        let ctx = Context::establish(Scope::User).map_err(describe)?;
+        let reader = first_reader(&ctx)?;
+        let deadline = Instant::now() + TAG_WAIT;
+        let mut states = [ReaderState::new(reader.clone(), State::UNAWARE)];
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            ctx.get_status_change(left, &mut states).map_err(describe)?;
+            if states[0].event_state().contains(State::PRESENT) {
+                break;
+            }
+            if left.is_zero() {
+                return Err(describe(pcsc::Error::Timeout));
+            }
+            states[0].sync_current_state();
+        }
+        ctx.connect(&reader, ShareMode::Shared, Protocols::ANY)
+            .map_err(describe)
+    }
+
+    fn transmit(card: &Card, apdu: &[u8]) -> Result<Vec<u8>, String> {
+        let mut buf = [0u8; pcsc::MAX_BUFFER_SIZE];
+        let response = card.transmit(apdu, &mut buf).map_err(describe)?;
+        match response.split_last_chunk::<2>() {
+            Some((data, sw)) if *sw == SW_OK => Ok(data.to_vec()),
+            _ => Err("The tag rejected the command; is it an NTAG tag?".to_string()),
+        }
+    }
+
+    /// READ BINARY returns four pages (16 bytes) from `page`.
+    fn read_pages(card: &Card, page: u8) -> Result<Vec<u8>, String> {
+        transmit(card, &[0xFF, 0xB0, 0x00, page, 0x10])
+    }
+
+    fn write_page(card: &Card, page: u8, data: &[u8]) -> Result<(), String> {
+        let mut apdu = vec![0xFF, 0xD6, 0x00, page, PAGE_SIZE as u8];
+        apdu.extend_from_slice(data);
+        transmit(card, &apdu).map(|_| ())
+    }
+
+    fn capacity(card: &Card) -> Result<usize, String> {
+        let cc = read_pages(card, CC_PAGE)?;
+        capacity_from_cc(&cc).ok_or_else(|| "The tag is not NDEF formatted".to_string())
+    }
+
+    pub fn write_uri(uri: &str) -> Result<(), String> {
+        let card = connect()?;
+        let mut tlv = encode_uri_tlv(uri);
+        if tlv.len() > capacity(&card)? {
+            return Err(format!(
+                "The tag is too small; {} bytes are needed (use an NTAG215 or NTAG216)",
+                tlv.len()
+            ));
+        }
+        tlv.resize(tlv.len().div_ceil(PAGE_SIZE) * PAGE_SIZE, 0);
+        for (i, chunk) in tlv.chunks(PAGE_SIZE).enumerate() {
+            write_page(&card, DATA_PAGE + i as u8, chunk)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_uri() -> Result<String, String> {
+        let card = connect()?;
+        let capacity = capacity(&card)?;
+        let mut data = Vec::with_capacity(capacity);
+        let mut page = DATA_PAGE;
+        while data.len() < capacity {
+            data.extend(read_pages(&card, page)?);
+            // Stop once the whole NDEF message is in
+            if find_ndef_message(&data).is_some() {
+                break;
+            }
+            page += 4;
+        }
+        let message = find_ndef_message(&data).ok_or_else(|| "The tag is empty".to_string())?;
+        decode_uri_record(message).ok_or_else(|| "The tag holds no Vauchi link".to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod reader {
+    use super::NfcCapability;
+
+    const UNSUPPORTED: &str = "NFC readers are supported on Linux and Windows";
+
+    pub fn capability() -> NfcCapability {
+        NfcCapability {
+            supported: false,
+            reader: None,
+            tag_present: false,
+            reason: Some(UNSUPPORTED.to_string()),
+        }
+    }
+
+    pub fn write_uri(_uri: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn read_uri() -> Result<String, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+pub use reader::{capability, read_uri, write_uri};
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private NDEF encoding
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_uri_round_trips() {
+        let uri = "vauchi://exchange/abc";
+        let tlv = encode_uri_tlv(uri);
+        assert_eq!(tlv[0], TLV_NDEF);
+        assert_eq!(*tlv.last().unwrap(), TLV_TERMINATOR);
+        let message = find_ndef_message(&tlv).unwrap();
+        assert_eq!(decode_uri_record(message).as_deref(), Some(uri));
+    }
+
+    #[test]
+    fn test_long_uri_uses_long_lengths() {
+        let uri = format!("vauchi://exchange/{}", "A".repeat(400));
+        let tlv = encode_uri_tlv(&uri);
+        assert_eq!(tlv[1], 0xFF);
+        // Lock control TLV and padding before the message are skipped
+        let mut data = vec![0x01, 0x03, 0xA0, 0x10, 0x44, TLV_NULL];
+        data.extend_from_slice(&tlv);
+        let message = find_ndef_message(&data).unwrap();
+        assert_eq!(decode_uri_record(message), Some(uri));
+    }
+
+    #[test]
+    fn test_empty_or_foreign_tags() {
+        assert_eq!(find_ndef_message(&[TLV_TERMINATOR, 0, 0]), None);
+        assert_eq!(find_ndef_message(&[0, 0, 0]), None);
+        // Text record ("T"), not a URI
+        let text = [0xD1, 0x01, 0x03, b'T', 0x02, b'e', b'n'];
+        assert_eq!(decode_uri_record(&text), None);
+    }
+
+    #[test]
+    fn test_capacity_from_cc() {
+        // NTAG215: 496 bytes of user data
+        assert_eq!(capacity_from_cc(&[0xE1, 0x10, 0x3E, 0x00]), Some(496));
+        assert_eq!(capacity_from_cc(&[0x00, 0x00, 0x00, 0x00]), None);
+    }
+}
@@ -0,0 +1,458 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! OS Notifications
+//!
+//! Listens on the internal event bus and shows an OS notification when
+//! a sync updates contact cards, a contact is added or a device link
+//! completes — but only while the main window is hidden, so the user is
+//! not notified about things already on screen. Each event kind can be
+//! switched off in the notification settings
+//! (`notification_settings.json` in the data dir).
+//!
+//! During quiet hours notifications are not shown but queued in
+//! `missed_notifications.json`, to be reviewed later.
+//!
+//! A recovery claim about a contact asks the user to call them before
+//! vouching. A contact's emergency alert and the dead man's switch
+//! check-in reminder are always shown right away, regardless of the
+//! window, the settings and quiet hours. Test alerts follow the normal
+//! rules.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast::error::RecvError;
+use vauchi_core::i18n::{get_locale_info, Locale};
+
+use crate::background;
+use crate::clock;
+use crate::commands::i18n::{localized_plural, parse_locale};
+use crate::desktop_strings;
+use crate::events::{self, AppEvent};
+use crate::settings_file;
+use crate::state::AppState;
+
+/// Settings file for the notification preferences.
+const SETTINGS_FILE: &str = "notification_settings.json";
+
+/// Queue of notifications suppressed during quiet hours.
+const MISSED_FILE: &str = "missed_notifications.json";
+
+/// Oldest missed notifications are dropped beyond this many.
+const MAX_MISSED: usize = 100;
+
+/// Minutes in a day; quiet hour bounds are minutes after local midnight.
+pub const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Daily quiet hours window in local time. `start > end` wraps midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Start, in minutes after local midnight.
+    pub start_minute: u16,
+    /// End (exclusive), in minutes after local midnight.
+    pub end_minute: u16,
+}
+
+impl QuietHours {
+    /// Whether `minute` (after local midnight) falls inside the window.
+    pub fn contains(&self, minute: u16) -> bool {
+        if self.start_minute <= self.end_minute {
+            minute >= self.start_minute && minute < self.end_minute
+        } else {
+            minute >= self.start_minute || minute < self.end_minute
+        }
+    }
+}
+
+/// A notification held back during quiet hours.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissedNotification {
+    /// Unix timestamp of the event.
+    pub timestamp: u64,
+    pub title: String,
+    pub body: String,
+}
+
+/// Per-event notification preferences.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Master switch.
+    pub enabled: bool,
+    /// Notify when sync applies contact card updates.
+    pub card_updates: bool,
+    /// Notify when a contact is added.
+    pub contact_added: bool,
+    /// Notify when a device link completes.
+    pub device_linked: bool,
+    /// Suppress (and queue) notifications during these hours.
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            card_updates: true,
+            contact_added: true,
+            device_linked: true,
+            quiet_hours: None,
+        }
+    }
+}
+
+/// The notification preferences.
+pub fn load_settings(data_dir: &Path) -> NotificationSettings {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Change the notification preferences.
+pub fn save_settings(data_dir: &Path, settings: &NotificationSettings) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, settings)
+}
+
+fn missed_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(MISSED_FILE)
+}
+
+/// Notifications queued during quiet hours, oldest first.
+pub fn load_missed(data_dir: &Path) -> Vec<MissedNotification> {
+    std::fs::read_to_string(missed_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Append a missed notification, keeping at most `MAX_MISSED`.
+pub(crate) fn queue_missed(data_dir: &Path, missed: MissedNotification) -> std::io::Result<()> {
+    let mut queue = load_missed(data_dir);
+    queue.push(missed);
+    let excess = queue.len().saturating_sub(MAX_MISSED);
+    queue.drain(..excess);
+    std::fs::write(missed_path(data_dir), serde_json::to_string(&queue)?)
+}
+
+/// Remove all missed notifications.
+pub fn clear_missed(data_dir: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(missed_path(data_dir)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Whether quiet hours are active at Unix time `now` (local time).
+pub(crate) fn in_quiet_hours(settings: &NotificationSettings, now: u64) -> bool {
+    let Some(quiet) = settings.quiet_hours else {
+        return false;
+    };
+    match Local.timestamp_opt(now as i64, 0).single() {
+        Some(local) => quiet.contains((local.hour() * 60 + local.minute()) as u16),
+        None => false,
+    }
+}
+
+/// Whether an event must be shown right away, whatever the settings.
+fn is_urgent(event: &AppEvent) -> bool {
+    matches!(
+        event,
+        AppEvent::EmergencyAlertReceived { test: false, .. } | AppEvent::CheckInDue { .. }
+    )
+}
+
+/// Title and body for an event, if the settings allow notifying about it.
+fn notification_for(
+    event: &AppEvent,
+    settings: &NotificationSettings,
+    locale: Locale,
+) -> Option<(String, String)> {
+    if let AppEvent::EmergencyAlertReceived {
+        display_name,
+        message,
+        test: false,
+        ..
+    } = event
+    {
+        return Some((
+            format!("Emergency alert from {}", display_name),
+            message.clone(),
+        ));
+    }
+    if let AppEvent::CheckInDue { deadline } = event {
+        return Some((
+            "Check in now".to_string(),
+            format!(
+                "Open Vauchi and check in before {}, or your dead man's switch will run.",
+                format_local_date(*deadline)
+            ),
+        ));
+    }
+    if !settings.enabled {
+        return None;
+    }
+    match event {
+        AppEvent::SyncCompleted { cards_updated, .. }
+            if settings.card_updates && *cards_updated > 0 =>
+        {
+            let title = desktop_strings::get(
+                get_locale_info(locale).code,
+                "notifications.cards_updated.title",
+            );
+            let body =
+                localized_plural(locale, "notifications.cards_updated", *cards_updated as u64);
+            Some((title, body))
+        }
+        AppEvent::ContactAdded { display_name, .. } if settings.contact_added => Some((
+            "New contact".to_string(),
+            format!("{} was added to your contacts", display_name),
+        )),
+        AppEvent::DeviceLinked { device_count, .. } if settings.device_linked => Some((
+            "Device linked".to_string(),
+            format!("You now have {} linked devices", device_count),
+        )),
+        AppEvent::RecoveryClaimReceived {
+            contact_name,
+            expires_at,
+            ..
+        } => Some((
+            "Recovery request".to_string(),
+            format!(
+                "Someone claims to be {} on a new device. Call them to verify before vouching \
+                 (expires {}).",
+                contact_name,
+                format_local_date(*expires_at)
+            ),
+        )),
+        AppEvent::EmergencyAlertReceived { display_name, .. } => Some((
+            "Test alert".to_string(),
+            format!("{} tested their emergency alerts", display_name),
+        )),
+        _ => None,
+    }
+}
+
+/// A Unix time as a local date and time.
+fn format_local_date(timestamp: u64) -> String {
+    match Local.timestamp_opt(timestamp as i64, 0).single() {
+        Some(local) => local.format("%Y-%m-%d %H:%M").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Whether the main window is hidden or minimized.
+fn window_hidden(app: &AppHandle) -> bool {
+    match app.get_webview_window("main") {
+        Some(window) => {
+            !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false)
+        }
+        None => true,
+    }
+}
+
+/// Start forwarding events to OS notifications.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    let mut rx = events::subscribe();
+    background::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let urgent = is_urgent(&event);
+            if !urgent && !window_hidden(&app) {
+                continue;
+            }
+            // Re-read each time so changed settings apply immediately
+            let settings = load_settings(&data_dir);
+            let locale = app
+                .try_state::<Mutex<AppState>>()
+                .map_or(Locale::English, |state| {
+                    parse_locale(state.lock().unwrap().locale_code())
+                });
+            let Some((title, body)) = notification_for(&event, &settings, locale) else {
+                continue;
+            };
+            let now = clock::now_secs();
+            if !urgent && in_quiet_hours(&settings, now) {
+                let missed = MissedNotification {
+                    timestamp: now,
+                    title,
+                    body,
+                };
+                if let Err(e) = queue_missed(&data_dir, missed) {
+                    tracing::warn!("Failed to queue missed notification: {}", e);
+                }
+            } else if let Err(e) = app.notification().builder().title(title).body(body).show() {
+                tracing::warn!("Failed to show notification: {}", e);
+            }
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private event-to-notification mapping
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sync_completed(cards_updated: u32) -> AppEvent {
+        AppEvent::SyncCompleted {
+            success: true,
+            contacts_added: 0,
+            cards_updated,
+            updates_sent: 0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_settings_default_when_missing() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load_settings(temp.path()), NotificationSettings::default());
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let settings = NotificationSettings {
+            card_updates: false,
+            ..Default::default()
+        };
+        save_settings(temp.path(), &settings).unwrap();
+        assert_eq!(load_settings(temp.path()), settings);
+    }
+
+    #[test]
+    fn test_card_updates_notify_only_when_cards_changed() {
+        let settings = NotificationSettings::default();
+        assert!(notification_for(&sync_completed(0), &settings, Locale::English).is_none());
+        let (_, body) = notification_for(&sync_completed(3), &settings, Locale::English).unwrap();
+        assert_eq!(body, "3 contacts updated their cards");
+    }
+
+    #[test]
+    fn test_disabled_event_kinds_are_skipped() {
+        let event = AppEvent::ContactAdded {
+            contact_id: "c1".to_string(),
+            display_name: "Alice".to_string(),
+        };
+        let off = NotificationSettings {
+            contact_added: false,
+            ..Default::default()
+        };
+        assert!(notification_for(&event, &off, Locale::English).is_none());
+
+        let master_off = NotificationSettings {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(notification_for(&event, &master_off, Locale::English).is_none());
+        assert!(
+            notification_for(&event, &NotificationSettings::default(), Locale::English).is_some()
+        );
+    }
+
+    #[test]
+    fn test_emergency_alerts_ignore_settings_but_tests_do_not() {
+        let alert = |test| AppEvent::EmergencyAlertReceived {
+            alert_id: "a1".to_string(),
+            contact_id: "c1".to_string(),
+            display_name: "Alice".to_string(),
+            message: "help".to_string(),
+            test,
+        };
+        let off = NotificationSettings {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(is_urgent(&alert(false)));
+        let (title, body) = notification_for(&alert(false), &off, Locale::English).unwrap();
+        assert_eq!(title, "Emergency alert from Alice");
+        assert_eq!(body, "help");
+
+        assert!(!is_urgent(&alert(true)));
+        assert!(notification_for(&alert(true), &off, Locale::English).is_none());
+        assert!(notification_for(
+            &alert(true),
+            &NotificationSettings::default(),
+            Locale::English
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_check_in_reminder_ignores_settings() {
+        let event = AppEvent::CheckInDue { deadline: 0 };
+        let off = NotificationSettings {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(is_urgent(&event));
+        let (title, _) = notification_for(&event, &off, Locale::English).unwrap();
+        assert_eq!(title, "Check in now");
+    }
+
+    #[test]
+    fn test_recovery_claim_names_contact() {
+        let event = AppEvent::RecoveryClaimReceived {
+            contact_id: "c1".to_string(),
+            contact_name: "Alice".to_string(),
+            expires_at: 0,
+        };
+        let (title, body) =
+            notification_for(&event, &NotificationSettings::default(), Locale::English).unwrap();
+        assert_eq!(title, "Recovery request");
+        assert!(body.contains("Alice"));
+        assert!(body.contains("Call them"));
+    }
+
+    #[test]
+    fn test_quiet_hours_contains_same_day_window() {
+        let quiet = QuietHours {
+            start_minute: 12 * 60,
+            end_minute: 13 * 60,
+        };
+        assert!(quiet.contains(12 * 60 + 30));
+        assert!(!quiet.contains(13 * 60));
+        assert!(!quiet.contains(11 * 60));
+    }
+
+    #[test]
+    fn test_quiet_hours_contains_wraps_midnight() {
+        let quiet = QuietHours {
+            start_minute: 22 * 60,
+            end_minute: 7 * 60,
+        };
+        assert!(quiet.contains(23 * 60));
+        assert!(quiet.contains(0));
+        assert!(quiet.contains(6 * 60 + 59));
+        assert!(!quiet.contains(7 * 60));
+        assert!(!quiet.contains(12 * 60));
+    }
+
+    #[test]
+    fn test_missed_queue_is_capped_and_clearable() {
+        let temp = TempDir::new().unwrap();
+        for i in 0..(MAX_MISSED as u64 + 5) {
+            let missed = MissedNotification {
+                timestamp: i,
+                title: "t".to_string(),
+                body: "b".to_string(),
+            };
+            queue_missed(temp.path(), missed).unwrap();
+        }
+        let queue = load_missed(temp.path());
+        assert_eq!(queue.len(), MAX_MISSED);
+        assert_eq!(queue[0].timestamp, 5, "Oldest entries are dropped");
+
+        clear_missed(temp.path()).unwrap();
+        assert!(load_missed(temp.path()).is_empty());
+        clear_missed(temp.path()).unwrap();
+    }
+}
@@ -0,0 +1,326 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Profile Import
+//!
+//! Reads another Vauchi data directory (e.g. copied from an old laptop)
+//! and merges its contacts, labels and own validations into the current
+//! profile. The other profile must belong to the same identity, since
+//! contacts' keys are bound to it.
+//!
+//! Its storage key is read from the copied `keys/` folder when the old
+//! installation used file key storage, or from this machine's keychain.
+//! A key that lived in another machine's keychain cannot be recovered;
+//! restore a backup instead.
+//!
+//! Nothing in the current profile is overwritten: a contact that exists in
+//! both with different cards, or a label with the same name but different
+//! fields, is kept as it is and reported as a conflict. Contacts deleted
+//! from the current profile (see `deleted_contacts`) are not brought back,
+//! and a contact's ratchet state is only taken over if the current profile
+//! has none, since the current one is the newer.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use vauchi_core::storage::secure::{FileKeyStorage, SecureStorage};
+use vauchi_core::{Identity, IdentityBackup, Storage, SymmetricKey};
+
+#[cfg(feature = "secure-storage")]
+use vauchi_core::storage::secure::PlatformKeyring;
+
+use crate::error::CommandError;
+use crate::state::{AppState, LEGACY_BACKUP_PASSWORD};
+
+/// Key name of the storage key, as saved by `AppState`.
+const STORAGE_KEY_NAME: &str = "storage_key";
+
+/// Something that exists in both profiles and was kept as it is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportConflict {
+    /// `contact` or `label`.
+    pub kind: String,
+    pub id: String,
+    pub name: String,
+    pub reason: String,
+}
+
+/// What an import merged.
+#[derive(Debug, Default, Serialize)]
+pub struct ProfileImportReport {
+    pub contacts_added: u32,
+    /// Contacts already present with the same card.
+    pub contacts_unchanged: u32,
+    /// Contacts deleted from the current profile, left out.
+    pub contacts_skipped_deleted: u32,
+    pub labels_created: u32,
+    pub label_memberships_added: u32,
+    pub validations_added: u32,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+fn key_from_bytes(bytes: &[u8]) -> Option<SymmetricKey> {
+    let arr: [u8; 32] = bytes.try_into().ok()?;
+    Some(SymmetricKey::from_bytes(arr))
+}
+
+/// The storage key of the profile in `data_dir`, without creating one.
+pub(crate) fn source_storage_key(data_dir: &Path) -> Result<SymmetricKey> {
+    let fallback_path = data_dir.join(".fallback-key");
+    if fallback_path.exists() {
+        let fallback = std::fs::read(&fallback_path).context("Failed to read fallback key")?;
+        let fallback = key_from_bytes(&fallback).context("Invalid fallback key")?;
+        let storage = FileKeyStorage::new(data_dir.join("keys"), fallback);
+        if let Ok(Some(bytes)) = storage.load_key(STORAGE_KEY_NAME) {
+            if let Some(key) = key_from_bytes(&bytes) {
+                return Ok(key);
+            }
+        }
+    }
+
+    #[cfg(feature = "secure-storage")]
+    {
+        let keyring = PlatformKeyring::new(&AppState::keyring_service_name(data_dir));
+        if let Ok(Some(bytes)) = keyring.load_key(STORAGE_KEY_NAME) {
+            if let Some(key) = key_from_bytes(&bytes) {
+                return Ok(key);
+            }
+        }
+    }
+
+    bail!(
+        "The storage key of this profile is not available here. It was kept in the keychain \
+         of the machine the profile came from; restore a backup instead."
+    )
+}
+
+/// Open the storage of the profile in `data_dir` and unlock its identity
+/// with `password`, its saved backup password or the legacy password.
+pub fn open_source(data_dir: &Path, password: Option<&str>) -> Result<(Storage, Identity)> {
+    let db_path = data_dir.join("vauchi.db");
+    if !db_path.exists() {
+        bail!("No Vauchi profile found in {}", data_dir.display());
+    }
+    let storage = Storage::open(&db_path, source_storage_key(data_dir)?)
+        .context("Failed to open the profile's storage")?;
+
+    let Some((backup, _)) = storage
+        .load_identity()
+        .context("Failed to read the profile's identity")?
+    else {
+        bail!("The profile has no identity");
+    };
+    let backup = IdentityBackup::new(backup);
+    let saved = std::fs::read_to_string(data_dir.join(".backup-password"))
+        .ok()
+        .map(|p| p.trim().to_string());
+    let identity = password
+        .into_iter()
+        .chain(saved.as_deref())
+        .chain(Some(LEGACY_BACKUP_PASSWORD))
+        .find_map(|p| Identity::import_backup(&backup, p).ok())
+        .context("Could not unlock the profile's identity; enter its backup password")?;
+    Ok((storage, identity))
+}
+
+/// Comparable form of a contact card.
+fn card_json(card: &vauchi_core::ContactCard) -> serde_json::Value {
+    serde_json::to_value(card).unwrap_or_default()
+}
+
+/// Copy the contact's ratchet state from `source` unless `target` already
+/// has one.
+fn import_ratchet(
+    source: &Storage,
+    target: &Storage,
+    contact_id: &str,
+) -> Result<(), CommandError> {
+    if target.load_ratchet_state(contact_id)?.is_some() {
+        return Ok(());
+    }
+    if let Ok(Some((ratchet, is_initiator))) = source.load_ratchet_state(contact_id) {
+        target.save_ratchet_state(contact_id, &ratchet, is_initiator)?;
+    }
+    Ok(())
+}
+
+/// Merge contacts, labels and the validations made by `my_id` from
+/// `source` into `target`, leaving out the `deleted` contacts (ID →
+/// deletion time). Call inside a transaction on `target`.
+pub fn merge(
+    source: &Storage,
+    target: &Storage,
+    my_id: &str,
+    deleted: &BTreeMap<String, u64>,
+) -> Result<ProfileImportReport, CommandError> {
+    let mut report = ProfileImportReport::default();
+
+    for contact in source.list_contacts()? {
+        match target.load_contact(contact.id())? {
+            None if deleted.contains_key(contact.id()) => {
+                report.contacts_skipped_deleted += 1;
+            }
+            Some(existing) => {
+                import_ratchet(source, target, contact.id())?;
+                if card_json(existing.card()) == card_json(contact.card()) {
+                    report.contacts_unchanged += 1;
+                } else {
+                    report.conflicts.push(ImportConflict {
+                        kind: "contact".to_string(),
+                        id: contact.id().to_string(),
+                        name: contact.display_name().to_string(),
+                        reason: "Exists with a different card; kept the current one".to_string(),
+                    });
+                }
+            }
+            None => {
+                target.save_contact(&contact)?;
+                import_ratchet(source, target, contact.id())?;
+                report.contacts_added += 1;
+            }
+        }
+    }
+
+    let mut current_labels = target
+        .load_all_labels()
+        .map_err(|e| CommandError::Storage(format!("Failed to load labels: {}", e)))?;
+    for label in source
+        .load_all_labels()
+        .map_err(|e| CommandError::Storage(format!("Failed to load labels: {}", e)))?
+    {
+        let index = match current_labels
+            .iter()
+            .position(|l| l.name().eq_ignore_ascii_case(label.name()))
+        {
+            Some(index) => {
+                let existing = &current_labels[index];
+                if existing.visible_fields() != label.visible_fields() {
+                    report.conflicts.push(ImportConflict {
+                        kind: "label".to_string(),
+                        id: existing.id().to_string(),
+                        name: existing.name().to_string(),
+                        reason: "Exists with different visible fields; kept the current ones"
+                            .to_string(),
+                    });
+                }
+                index
+            }
+            None => {
+                let created = target
+                    .create_label(label.name())
+                    .map_err(|e| CommandError::Storage(format!("Failed to create label: {}", e)))?;
+                for field_id in label.visible_fields() {
+                    target
+                        .set_label_field_visibility(created.id(), field_id, true)
+                        .map_err(|e| {
+                            CommandError::Storage(format!("Failed to set field visibility: {}", e))
+                        })?;
+                }
+                report.labels_created += 1;
+                current_labels.push(created);
+                current_labels.len() - 1
+            }
+        };
+        let target_label = &current_labels[index];
+        for contact_id in label.contacts() {
+            if target_label.contacts().contains(contact_id)
+                || target.load_contact(contact_id)?.is_none()
+            {
+                continue;
+            }
+            target
+                .add_contact_to_label(target_label.id(), contact_id)
+                .map_err(|e| {
+                    CommandError::Storage(format!("Failed to add contact to label: {}", e))
+                })?;
+            report.label_memberships_added += 1;
+        }
+    }
+
+    for validation in source.load_validations_by_validator(my_id)? {
+        let (Some(contact_id), Some(field_name)) =
+            (validation.contact_id(), validation.field_name())
+        else {
+            continue;
+        };
+        // Validations of contacts that were not imported would dangle
+        if target.load_contact(contact_id)?.is_none() {
+            continue;
+        }
+        let exists = target
+            .load_validations_for_field(contact_id, field_name)?
+            .iter()
+            .any(|v| v.validator_id() == my_id);
+        if !exists {
+            target.save_validation(&validation)?;
+            report.validations_added += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use vauchi_core::{Contact, ContactCard};
+
+    fn storage(dir: &TempDir) -> Storage {
+        Storage::open(dir.path().join("vauchi.db"), SymmetricKey::generate()).unwrap()
+    }
+
+    #[test]
+    fn test_merge_adds_new_and_reports_changed_contacts() {
+        let (source_dir, target_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let (source, target) = (storage(&source_dir), storage(&target_dir));
+
+        let alice =
+            Contact::from_exchange([1; 32], ContactCard::new("Alice"), SymmetricKey::generate());
+        let bob =
+            Contact::from_exchange([2; 32], ContactCard::new("Bob"), SymmetricKey::generate());
+        source.save_contact(&alice).unwrap();
+        source.save_contact(&bob).unwrap();
+        let old_bob = Contact::from_exchange(
+            [2; 32],
+            ContactCard::new("Robert"),
+            SymmetricKey::generate(),
+        );
+        target.save_contact(&old_bob).unwrap();
+
+        let report = merge(&source, &target, "me", &BTreeMap::new()).unwrap();
+        assert_eq!(report.contacts_added, 1);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].kind, "contact");
+        assert!(target.load_contact(alice.id()).unwrap().is_some());
+
+        let again = merge(&source, &target, "me", &BTreeMap::new()).unwrap();
+        assert_eq!(again.contacts_added, 0);
+        assert_eq!(again.contacts_unchanged, 1);
+    }
+
+    #[test]
+    fn test_merge_leaves_out_deleted_contacts() {
+        let (source_dir, target_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let (source, target) = (storage(&source_dir), storage(&target_dir));
+
+        let alice =
+            Contact::from_exchange([1; 32], ContactCard::new("Alice"), SymmetricKey::generate());
+        source.save_contact(&alice).unwrap();
+        let deleted = BTreeMap::from([(alice.id().to_string(), 100)]);
+
+        let report = merge(&source, &target, "me", &deleted).unwrap();
+        assert_eq!(report.contacts_added, 0);
+        assert_eq!(report.contacts_skipped_deleted, 1);
+        assert!(target.load_contact(alice.id()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_missing_profile_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        assert!(open_source(dir.path(), None).is_err());
+    }
+}
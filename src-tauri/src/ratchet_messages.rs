@@ -0,0 +1,209 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Ratchet Messages
+//!
+//! Desktop features send contacts their own messages over the pairwise
+//! ratchet: field validations (`validation_sync`), emergency alerts
+//! (`emergency_sync`) and recovery claims and proofs (`recovery_session`).
+//! Each is encrypted with the contact's ratchet and queued as a regular
+//! pending `card_delta`, so on the wire it looks like a card update.
+//! Recovery proofs are the exception: they are public and signed, and must
+//! reach contacts who only know the lost identity and so have no session
+//! with the sender, so they are queued unencrypted.
+//!
+//! Inbound: unencrypted recovery proofs are taken first, whoever sent
+//! them. Then, before card updates go to core, each incoming update is
+//! decrypted once with a copy of the sender's ratchet, and the plaintext
+//! is offered to each feature in turn. Only updates a feature recognizes
+//! are consumed (and the advanced ratchet saved); everything else is left
+//! untouched for the card update pipeline. Messages from blocked contacts
+//! are consumed and dropped.
+
+use std::path::Path;
+
+use vauchi_core::crypto::ratchet::RatchetMessage;
+use vauchi_core::{Identity, PendingUpdate, Storage, SymmetricKey, UpdateStatus};
+
+use crate::clock;
+use crate::emergency_sync::{self, EmergencyMessage};
+use crate::recovery_session::{self, RecoveryMessage};
+use crate::validation_sync::{self, ValidationMessage};
+
+/// A desktop message decrypted from an incoming update.
+enum Inbound {
+    Validation(ValidationMessage),
+    Emergency(EmergencyMessage),
+    Recovery(RecoveryMessage),
+}
+
+/// What [`take_inbound`] stored.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Received {
+    pub validations: usize,
+    pub alerts: usize,
+}
+
+/// Encrypt `plaintext` with the contact's ratchet and queue it as a pending
+/// update. Returns `None` if there is no secure session with the contact.
+pub fn queue(
+    storage: &Storage,
+    contact_id: &str,
+    plaintext: &[u8],
+) -> Result<Option<PendingUpdate>, String> {
+    let Some((mut ratchet, is_initiator)) = storage
+        .load_ratchet_state(contact_id)
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+
+    let encrypted = ratchet
+        .encrypt(plaintext)
+        .map_err(|e| format!("Encryption failed: {:?}", e))?;
+    let payload = serde_json::to_vec(&encrypted).map_err(|e| e.to_string())?;
+    storage
+        .save_ratchet_state(contact_id, &ratchet, is_initiator)
+        .map_err(|e| e.to_string())?;
+    queue_payload(storage, contact_id, payload).map(Some)
+}
+
+/// Queue `plaintext` unencrypted as a pending update. Only for messages
+/// that are public and signed.
+pub fn queue_unencrypted(
+    storage: &Storage,
+    contact_id: &str,
+    plaintext: &[u8],
+) -> Result<PendingUpdate, String> {
+    queue_payload(storage, contact_id, plaintext.to_vec())
+}
+
+fn queue_payload(
+    storage: &Storage,
+    contact_id: &str,
+    payload: Vec<u8>,
+) -> Result<PendingUpdate, String> {
+    let update = PendingUpdate {
+        id: hex::encode(&SymmetricKey::generate().as_bytes()[..16]),
+        contact_id: contact_id.to_string(),
+        update_type: "card_delta".to_string(),
+        payload,
+        created_at: clock::now_secs(),
+        retry_count: 0,
+        status: UpdateStatus::Pending,
+    };
+    storage.queue_update(&update).map_err(|e| e.to_string())?;
+    Ok(update)
+}
+
+/// Decode a decrypted update as one of the desktop messages.
+fn decode(plaintext: &[u8]) -> Option<Inbound> {
+    validation_sync::decode_message(plaintext)
+        .map(Inbound::Validation)
+        .or_else(|| emergency_sync::decode_message(plaintext).map(Inbound::Emergency))
+        .or_else(|| recovery_session::decode_message(plaintext).map(Inbound::Recovery))
+}
+
+/// Try to consume an incoming update as a desktop message. Returns `None`
+/// (leaving the ratchet untouched) if it is anything else.
+fn try_decrypt(storage: &Storage, sender_id: &str, ciphertext: &[u8]) -> Option<Inbound> {
+    let message: RatchetMessage = serde_json::from_slice(ciphertext).ok()?;
+    // A fresh copy; only saved if the update turns out to be ours
+    let (mut ratchet, is_initiator) = storage.load_ratchet_state(sender_id).ok()??;
+    let plaintext = ratchet.decrypt(&message).ok()?;
+    let decoded = decode(&plaintext)?;
+    storage
+        .save_ratchet_state(sender_id, &ratchet, is_initiator)
+        .ok()?;
+    Some(decoded)
+}
+
+/// Split desktop messages out of incoming card updates and hand each to its
+/// feature. Returns the remaining updates for core and what was stored.
+pub fn take_inbound(
+    identity: &Identity,
+    storage: &Storage,
+    data_dir: &Path,
+    updates: Vec<(String, Vec<u8>)>,
+) -> (Vec<(String, Vec<u8>)>, Received) {
+    let our_id = identity.public_id();
+    let mut remaining = Vec::with_capacity(updates.len());
+    let mut received = Received::default();
+    let mut revocations = validation_sync::Revocations::load(data_dir);
+
+    for (sender_id, ciphertext) in updates {
+        // The sender of a proof may be the recovered identity we do not know yet
+        if let Some(proof) = recovery_session::decode_public_proof(&ciphertext) {
+            let blocked = matches!(storage.load_contact(&sender_id), Ok(Some(c)) if c.is_blocked());
+            if !blocked {
+                recovery_session::receive(storage, data_dir, &sender_id, proof);
+            }
+            continue;
+        }
+        let contact = match storage.load_contact(&sender_id) {
+            Ok(Some(contact)) => contact,
+            _ => {
+                remaining.push((sender_id, ciphertext));
+                continue;
+            }
+        };
+        let Some(message) = try_decrypt(storage, &sender_id, &ciphertext) else {
+            remaining.push((sender_id, ciphertext));
+            continue;
+        };
+        if contact.is_blocked() {
+            continue;
+        }
+
+        match message {
+            Inbound::Validation(message) => {
+                received.validations += validation_sync::receive(
+                    storage,
+                    data_dir,
+                    &mut revocations,
+                    &our_id,
+                    &contact,
+                    message,
+                )
+            }
+            Inbound::Emergency(message) => {
+                if emergency_sync::receive(data_dir, &contact, message) {
+                    received.alerts += 1;
+                }
+            }
+            Inbound::Recovery(message) => {
+                recovery_session::receive(storage, data_dir, &sender_id, message)
+            }
+        }
+    }
+
+    revocations.save_after_commit(data_dir);
+    (remaining, received)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private message dispatch
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_message_kind_goes_to_its_feature() {
+        let claim = serde_json::to_vec(&RecoveryMessage::Claim {
+            claim: "c".to_string(),
+        })
+        .unwrap();
+        assert!(matches!(decode(&claim), Some(Inbound::Recovery(_))));
+
+        let ack = br#"{"kind":"vauchi.emergency-ack.v1","alert_id":"a","acknowledged_at":1}"#;
+        assert!(matches!(decode(ack), Some(Inbound::Emergency(_))));
+
+        let revocation =
+            br#"{"kind":"vauchi.validation-revocation.v1","field_id":"email","revoked_at":1}"#;
+        assert!(matches!(decode(revocation), Some(Inbound::Validation(_))));
+
+        // Card deltas are left for core
+        assert!(decode(br#"{"changes":[]}"#).is_none());
+        assert!(recovery_session::decode_public_proof(br#"{"changes":[]}"#).is_none());
+    }
+}
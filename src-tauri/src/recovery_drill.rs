@@ -0,0 +1,152 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Recovery Drill
+//!
+//! Rehearses a recovery without touching real keys: a throwaway "lost"
+//! and "new" identity form a claim, a throwaway key stands in for each
+//! trustee to vouch, and the vouchers go through the same checks as in a
+//! real `RecoverySession` (kept in memory, never saved). The report shows
+//! whether the configured trustees and threshold would be enough.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::Serialize;
+use vauchi_core::recovery::{RecoveryClaim, RecoveryVoucher};
+use vauchi_core::Identity;
+
+use crate::recovery_session::RecoverySession;
+
+/// A trusted contact as seen by the drill.
+#[derive(Debug, Clone)]
+pub struct Trustee {
+    pub contact_id: String,
+    pub display_name: String,
+    pub blocked: bool,
+}
+
+/// Outcome of the drill for one trustee.
+#[derive(Debug, Clone, Serialize)]
+pub struct DrillTrustee {
+    pub contact_id: String,
+    pub display_name: String,
+    /// Whether the stand-in voucher was accepted.
+    pub vouched: bool,
+    /// Why the trustee could not vouch, if they could not.
+    pub problem: Option<String>,
+}
+
+/// Result of a recovery drill.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryDrillReport {
+    pub threshold: u32,
+    pub trustees: Vec<DrillTrustee>,
+    pub vouchers_collected: usize,
+    /// Whether the recovery would have completed.
+    pub would_succeed: bool,
+    /// Problems with the current setup, for the user to fix.
+    pub issues: Vec<String>,
+}
+
+/// Run the drill for the given trustees and threshold.
+pub fn run(trustees: &[Trustee], threshold: u32, now: u64) -> RecoveryDrillReport {
+    let lost = Identity::create("Recovery drill (lost)");
+    let new = Identity::create("Recovery drill (new)");
+    let claim = RecoveryClaim::new(lost.signing_public_key(), new.signing_public_key());
+    let mut session = RecoverySession::new(
+        hex::encode(claim.old_pk()),
+        hex::encode(claim.new_pk()),
+        BASE64.encode(claim.to_bytes()),
+        now,
+    );
+
+    let mut results = Vec::with_capacity(trustees.len());
+    for trustee in trustees {
+        let problem = if trustee.blocked {
+            Some("Blocked contacts cannot vouch".to_string())
+        } else {
+            let stand_in = Identity::create(&trustee.display_name);
+            RecoveryVoucher::create_from_claim(&claim, stand_in.signing_keypair())
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|voucher| {
+                    session
+                        .add_voucher(&BASE64.encode(voucher.to_bytes()))
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .err()
+        };
+        results.push(DrillTrustee {
+            contact_id: trustee.contact_id.clone(),
+            display_name: trustee.display_name.clone(),
+            vouched: problem.is_none(),
+            problem,
+        });
+    }
+
+    let vouchers_collected = session.vouchers.len();
+    let would_succeed = session.is_complete(threshold);
+    let mut issues = Vec::new();
+    if trustees.is_empty() {
+        issues.push("No contacts are trusted for recovery".to_string());
+    } else if !would_succeed {
+        issues.push(format!(
+            "Only {} of the {} vouchers needed could be collected",
+            vouchers_collected, threshold
+        ));
+    }
+    if threshold == 1 {
+        issues.push("A single contact can vouch for a takeover of your identity".to_string());
+    }
+
+    RecoveryDrillReport {
+        threshold,
+        trustees: results,
+        vouchers_collected,
+        would_succeed,
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trustee(name: &str, blocked: bool) -> Trustee {
+        Trustee {
+            contact_id: name.to_lowercase(),
+            display_name: name.to_string(),
+            blocked,
+        }
+    }
+
+    #[test]
+    fn test_drill_succeeds_with_enough_trustees() {
+        let trustees = [trustee("Alice", false), trustee("Bob", false)];
+        let report = run(&trustees, 2, 1000);
+        assert!(report.would_succeed);
+        assert_eq!(report.vouchers_collected, 2);
+        assert!(report.trustees.iter().all(|t| t.vouched));
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_drill_reports_missing_vouchers() {
+        let trustees = [trustee("Alice", false), trustee("Bob", true)];
+        let report = run(&trustees, 2, 1000);
+        assert!(!report.would_succeed);
+        assert_eq!(report.vouchers_collected, 1);
+        assert!(report.trustees[1].problem.is_some());
+        assert_eq!(report.issues.len(), 1);
+    }
+
+    #[test]
+    fn test_drill_without_trustees() {
+        let report = run(&[], 3, 1000);
+        assert!(!report.would_succeed);
+        assert_eq!(
+            report.issues,
+            vec!["No contacts are trusted for recovery".to_string()]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Recovery Policy
+//!
+//! The user's recovery thresholds, kept in `recovery_settings.json`:
+//! how many vouchers a recovery of this identity needs, and how many
+//! mutual contacts must vouch before we trust someone else's recovery.
+//! Defaults come from core's `RecoverySettings`. Backups carry the policy
+//! in their envelope (see [`crate::backup_envelope`]).
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use vauchi_core::recovery::RecoverySettings;
+
+/// Policy file name under the data dir.
+const POLICY_FILE: &str = "recovery_settings.json";
+
+/// Highest allowed threshold.
+pub const MAX_THRESHOLD: u32 = 10;
+
+/// Recovery and verification thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryPolicy {
+    /// Vouchers needed to recover this identity.
+    pub recovery_threshold: u32,
+    /// Mutual contacts vouching needed to trust a contact's recovery.
+    pub verification_threshold: u32,
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        let settings = RecoverySettings::default();
+        Self {
+            recovery_threshold: settings.recovery_threshold(),
+            verification_threshold: settings.verification_threshold(),
+        }
+    }
+}
+
+impl RecoveryPolicy {
+    /// Check the thresholds are usable.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("Recovery threshold", self.recovery_threshold),
+            ("Verification threshold", self.verification_threshold),
+        ] {
+            if !(1..=MAX_THRESHOLD).contains(&value) {
+                return Err(format!("{} must be between 1 and {}", name, MAX_THRESHOLD));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn policy_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(POLICY_FILE)
+}
+
+/// Load the policy, falling back to the defaults.
+pub fn load(data_dir: &Path) -> RecoveryPolicy {
+    std::fs::read_to_string(policy_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Save the policy.
+pub fn save(data_dir: &Path, policy: &RecoveryPolicy) -> std::io::Result<()> {
+    std::fs::write(policy_path(data_dir), serde_json::to_string(policy)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_policy_roundtrip_and_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load(temp.path()), RecoveryPolicy::default());
+        let policy = RecoveryPolicy {
+            recovery_threshold: 5,
+            verification_threshold: 2,
+        };
+        save(temp.path(), &policy).unwrap();
+        assert_eq!(load(temp.path()), policy);
+    }
+
+    #[test]
+    fn test_validate_bounds() {
+        let policy = |r, v| RecoveryPolicy {
+            recovery_threshold: r,
+            verification_threshold: v,
+        };
+        assert!(policy(3, 2).validate().is_ok());
+        assert!(policy(0, 2).validate().is_err());
+        assert!(policy(3, MAX_THRESHOLD + 1).validate().is_err());
+    }
+}
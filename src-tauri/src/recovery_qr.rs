@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Recovery QR Transport
+//!
+//! Claims and vouchers can be handed over in person as QR code sequences,
+//! without the relay. Frames follow the device link multipart layout with
+//! their own prefix: `WBRC|frame|total|chunk` for claims and
+//! `WBRV|frame|total|chunk` for vouchers, where the chunks are slices of
+//! the base64 payload. Frames may be scanned in any order and repeats are
+//! ignored.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Base64 characters per frame; well within QR capacity.
+const CHUNK_SIZE: usize = 1500;
+
+/// What a recovery QR sequence carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryQrKind {
+    Claim,
+    Voucher,
+}
+
+impl RecoveryQrKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Claim => "WBRC",
+            Self::Voucher => "WBRV",
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "WBRC" => Some(Self::Claim),
+            "WBRV" => Some(Self::Voucher),
+            _ => None,
+        }
+    }
+}
+
+/// Split a base64 payload into frame data strings.
+pub fn encode_frames(kind: RecoveryQrKind, payload_b64: &str) -> Vec<String> {
+    let chunks: Vec<&str> = if payload_b64.is_empty() {
+        vec![""]
+    } else {
+        // Base64 is ASCII, so byte chunks are valid strings
+        payload_b64
+            .as_bytes()
+            .chunks(CHUNK_SIZE)
+            .map(|c| std::str::from_utf8(c).unwrap_or_default())
+            .collect()
+    };
+    let total = chunks.len();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("{}|{}|{}|{}", kind.prefix(), i + 1, total, chunk))
+        .collect()
+}
+
+/// A partly scanned QR sequence.
+#[derive(Debug, Clone)]
+pub struct RecoveryQrScan {
+    pub kind: RecoveryQrKind,
+    pub total_frames: usize,
+    frames: BTreeMap<usize, String>,
+}
+
+impl RecoveryQrScan {
+    /// Number of distinct frames scanned so far.
+    pub fn frames_received(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// The reassembled payload once every frame was scanned.
+    pub fn payload(&self) -> Option<String> {
+        (self.frames.len() == self.total_frames).then(|| self.frames.values().cloned().collect())
+    }
+}
+
+/// Add a scanned frame to `scan`. A frame from another sequence (different
+/// kind or frame count) starts a new scan.
+pub fn add_frame(scan: &mut Option<RecoveryQrScan>, data: &str) -> Result<(), String> {
+    let mut parts = data.trim().splitn(4, '|');
+    let kind = parts
+        .next()
+        .and_then(RecoveryQrKind::from_prefix)
+        .ok_or_else(|| "Not a recovery QR code".to_string())?;
+    let frame: usize = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| "Invalid frame number".to_string())?;
+    let total: usize = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| "Invalid frame count".to_string())?;
+    let chunk = parts
+        .next()
+        .ok_or_else(|| "Missing frame data".to_string())?;
+    if total == 0 || frame == 0 || frame > total {
+        return Err("Invalid frame number".to_string());
+    }
+
+    let current = scan
+        .as_ref()
+        .filter(|s| s.kind == kind && s.total_frames == total);
+    if current.is_none() {
+        *scan = Some(RecoveryQrScan {
+            kind,
+            total_frames: total,
+            frames: BTreeMap::new(),
+        });
+    }
+    if let Some(scan) = scan.as_mut() {
+        scan.frames
+            .entry(frame)
+            .or_insert_with(|| chunk.to_string());
+    }
+    Ok(())
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private frame reassembly
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_reassemble_in_any_order() {
+        let payload = "A".repeat(CHUNK_SIZE * 2 + 10);
+        let frames = encode_frames(RecoveryQrKind::Voucher, &payload);
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].starts_with("WBRV|1|3|"));
+
+        let mut scan = None;
+        for data in [&frames[2], &frames[0], &frames[2]] {
+            add_frame(&mut scan, data).unwrap();
+        }
+        assert_eq!(scan.as_ref().unwrap().frames_received(), 2);
+        assert_eq!(scan.as_ref().unwrap().payload(), None);
+
+        add_frame(&mut scan, &frames[1]).unwrap();
+        assert_eq!(scan.unwrap().payload(), Some(payload));
+    }
+
+    #[test]
+    fn test_frame_from_other_sequence_restarts_scan() {
+        let mut scan = None;
+        add_frame(&mut scan, "WBRV|1|2|abc").unwrap();
+        add_frame(&mut scan, "WBRC|1|1|claim").unwrap();
+        let scan = scan.unwrap();
+        assert_eq!(scan.kind, RecoveryQrKind::Claim);
+        assert_eq!(scan.payload(), Some("claim".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_frames_are_rejected() {
+        let mut scan = None;
+        assert!(add_frame(&mut scan, "WBMP|1|1|abc").is_err());
+        assert!(add_frame(&mut scan, "WBRC|3|2|abc").is_err());
+        assert!(add_frame(&mut scan, "WBRC|1|1").is_err());
+        assert!(scan.is_none());
+    }
+}
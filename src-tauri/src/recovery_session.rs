@@ -0,0 +1,545 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Recovery Session
+//!
+//! Tracks an identity recovery in progress: the claim that the lost key
+//! (`old_pk`) is replaced by the current identity's key, and the vouchers
+//! collected from former contacts. The session lives in
+//! `recovery_session.json` until it is finalized or replaced by a new one.
+//!
+//! Once enough vouchers are collected, the claim and vouchers form a
+//! recovery proof. Finalizing sends the proof to every contact, so they
+//! learn about the key change. The proof is public and signed by the
+//! vouchers, and contacts who only know the lost identity have no session
+//! with the new one, so it is sent unencrypted (see `ratchet_messages`).
+//!
+//! The claim itself can also be sent over the relay to a contact already
+//! re-added from the new identity. Incoming claims about a known contact
+//! are kept in `recovery_claims.json` and announced, prompting the user to
+//! verify the person (e.g. by calling them) before vouching; `start`
+//! forwards them to the frontend as `recovery://claim`.
+//!
+//! A received proof about a contact's lost or recovered key is checked
+//! against its vouchers, whoever relayed it; the contact then needs
+//! re-verification (see `reverification`). Incoming messages are decoded
+//! by `ratchet_messages` and handed to [`receive`].
+
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+use vauchi_core::recovery::{RecoveryClaim, RecoveryVoucher};
+use vauchi_core::{PendingUpdate, Storage};
+
+use crate::background;
+use crate::clock;
+use crate::events::{self, AppEvent};
+use crate::ratchet_messages;
+use crate::recovery_policy;
+use crate::reverification::{self, NeedsReverification};
+use crate::storage_worker;
+
+/// Session file name under the data dir.
+const SESSION_FILE: &str = "recovery_session.json";
+
+/// Received claims file name under the data dir.
+const CLAIMS_FILE: &str = "recovery_claims.json";
+
+/// Event sent to the frontend when a claim about a known contact arrives.
+pub const CLAIM_EVENT: &str = "recovery://claim";
+
+/// A voucher collected for the session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectedVoucher {
+    /// Hex public key of the voucher.
+    pub voucher_pk: String,
+    pub timestamp: u64,
+    /// The voucher, base64 encoded.
+    pub voucher: String,
+}
+
+/// A recovery in progress.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoverySession {
+    /// Hex public key of the lost identity.
+    pub old_pk: String,
+    /// Hex public key of the current identity.
+    pub new_pk: String,
+    /// The claim to hand to former contacts, base64 encoded.
+    pub claim: String,
+    pub started_at: u64,
+    pub vouchers: Vec<CollectedVoucher>,
+    pub finalized_at: Option<u64>,
+}
+
+/// Why a voucher was not added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoucherError {
+    /// Not a decodable voucher.
+    Invalid,
+    /// The voucher is for a different claim.
+    WrongClaim,
+    /// The voucher's signature does not verify.
+    BadSignature,
+    /// The lost identity cannot vouch for itself.
+    SelfVouch,
+    /// This contact already vouched.
+    Duplicate,
+}
+
+impl std::fmt::Display for VoucherError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            VoucherError::Invalid => "Invalid voucher",
+            VoucherError::WrongClaim => "Voucher is for a different recovery claim",
+            VoucherError::BadSignature => "Voucher signature is invalid",
+            VoucherError::SelfVouch => "The lost identity cannot vouch for itself",
+            VoucherError::Duplicate => "This contact already vouched",
+        };
+        f.write_str(msg)
+    }
+}
+
+/// Recovery message sent to contacts, tagged so it cannot be mistaken for
+/// a card delta.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", deny_unknown_fields)]
+pub enum RecoveryMessage {
+    /// The identity `old_pk` was recovered as `new_pk`.
+    #[serde(rename = "vauchi.recovery-proof.v1")]
+    Proof {
+        old_pk: String,
+        new_pk: String,
+        /// Claim, base64 encoded.
+        claim: String,
+        /// Vouchers, base64 encoded.
+        vouchers: Vec<String>,
+    },
+    /// The sender asks the recipient to vouch for a recovery.
+    #[serde(rename = "vauchi.recovery-claim.v1")]
+    Claim {
+        /// Claim, base64 encoded.
+        claim: String,
+    },
+}
+
+/// A received recovery claim about a known contact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncomingRecoveryClaim {
+    /// The claim, base64 encoded.
+    pub claim: String,
+    /// The known contact whose identity is claimed.
+    pub contact_id: String,
+    pub contact_name: String,
+    /// Who sent the claim.
+    pub sender_id: String,
+    pub expires_at: u64,
+    pub received_at: u64,
+}
+
+impl RecoverySession {
+    /// Start a session for a claim.
+    pub fn new(old_pk: String, new_pk: String, claim: String, now: u64) -> Self {
+        Self {
+            old_pk,
+            new_pk,
+            claim,
+            started_at: now,
+            vouchers: Vec::new(),
+            finalized_at: None,
+        }
+    }
+
+    /// Check a received voucher and add it to the session.
+    pub fn add_voucher(&mut self, voucher_b64: &str) -> Result<&CollectedVoucher, VoucherError> {
+        let bytes = BASE64
+            .decode(voucher_b64.trim())
+            .map_err(|_| VoucherError::Invalid)?;
+        let voucher = RecoveryVoucher::from_bytes(&bytes).map_err(|_| VoucherError::Invalid)?;
+        if hex::encode(voucher.old_pk()) != self.old_pk
+            || hex::encode(voucher.new_pk()) != self.new_pk
+        {
+            return Err(VoucherError::WrongClaim);
+        }
+        if !voucher.verify() {
+            return Err(VoucherError::BadSignature);
+        }
+        let voucher_pk = hex::encode(voucher.voucher_pk());
+        if voucher_pk == self.old_pk || voucher_pk == self.new_pk {
+            return Err(VoucherError::SelfVouch);
+        }
+        if self.vouchers.iter().any(|v| v.voucher_pk == voucher_pk) {
+            return Err(VoucherError::Duplicate);
+        }
+        self.vouchers.push(CollectedVoucher {
+            voucher_pk,
+            timestamp: voucher.timestamp(),
+            voucher: BASE64.encode(&bytes),
+        });
+        Ok(self.vouchers.last().expect("voucher was just added"))
+    }
+
+    /// Whether enough vouchers were collected.
+    pub fn is_complete(&self, threshold: u32) -> bool {
+        self.vouchers.len() >= threshold as usize
+    }
+
+    /// The recovery proof for contacts.
+    pub fn proof(&self) -> RecoveryMessage {
+        RecoveryMessage::Proof {
+            old_pk: self.old_pk.clone(),
+            new_pk: self.new_pk.clone(),
+            claim: self.claim.clone(),
+            vouchers: self.vouchers.iter().map(|v| v.voucher.clone()).collect(),
+        }
+    }
+}
+
+fn session_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SESSION_FILE)
+}
+
+/// The recovery in progress, if any.
+pub fn load(data_dir: &Path) -> Option<RecoverySession> {
+    std::fs::read_to_string(session_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// Save the session, replacing any previous one.
+pub fn save(data_dir: &Path, session: &RecoverySession) -> std::io::Result<()> {
+    std::fs::write(session_path(data_dir), serde_json::to_string(session)?)
+}
+
+/// Queue a recovery proof for a contact, unencrypted so contacts without
+/// a session with the recovered identity can read it.
+pub fn queue_proof(
+    storage: &Storage,
+    contact_id: &str,
+    proof: &RecoveryMessage,
+) -> Result<PendingUpdate, String> {
+    let plaintext = serde_json::to_vec(proof).map_err(|e| e.to_string())?;
+    ratchet_messages::queue_unencrypted(storage, contact_id, &plaintext)
+}
+
+/// Encrypt a recovery message (a claim) for a contact and queue it as a
+/// pending update. Returns `None` if there is no secure session with the
+/// contact.
+pub fn queue_message(
+    storage: &Storage,
+    contact_id: &str,
+    message: &RecoveryMessage,
+) -> Result<Option<PendingUpdate>, String> {
+    let plaintext = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+    ratchet_messages::queue(storage, contact_id, &plaintext)
+}
+
+fn claims_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CLAIMS_FILE)
+}
+
+/// Received claims that have not expired, oldest first.
+pub fn load_claims(data_dir: &Path) -> Vec<IncomingRecoveryClaim> {
+    let claims: Vec<IncomingRecoveryClaim> = std::fs::read_to_string(claims_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let now = clock::now_secs();
+    claims.into_iter().filter(|c| c.expires_at > now).collect()
+}
+
+/// Store a received claim. Returns `false` if it was already stored.
+fn add_claim(data_dir: &Path, claim: IncomingRecoveryClaim) -> std::io::Result<bool> {
+    let mut claims = load_claims(data_dir);
+    if claims.iter().any(|c| c.claim == claim.claim) {
+        return Ok(false);
+    }
+    claims.push(claim);
+    std::fs::write(claims_path(data_dir), serde_json::to_string(&claims)?)?;
+    Ok(true)
+}
+
+/// The known contact a claim is about, as `(contact_id, display_name)`.
+fn claimed_contact(storage: &Storage, claim: &RecoveryClaim) -> Option<(String, String)> {
+    let old_pk = hex::encode(claim.old_pk());
+    storage
+        .list_contacts()
+        .ok()?
+        .into_iter()
+        .find(|c| hex::encode(c.public_key()) == old_pk)
+        .map(|c| (c.id().to_string(), c.display_name().to_string()))
+}
+
+/// Store and announce a received claim, if it concerns a known contact and
+/// has not expired.
+fn receive_claim(storage: &Storage, data_dir: &Path, sender_id: &str, claim_b64: String) {
+    let Some(claim) = BASE64
+        .decode(&claim_b64)
+        .ok()
+        .and_then(|bytes| RecoveryClaim::from_bytes(&bytes).ok())
+    else {
+        return;
+    };
+    if claim.is_expired() {
+        return;
+    }
+    let Some((contact_id, contact_name)) = claimed_contact(storage, &claim) else {
+        return;
+    };
+    let incoming = IncomingRecoveryClaim {
+        claim: claim_b64,
+        contact_id,
+        contact_name,
+        sender_id: sender_id.to_string(),
+        expires_at: claim.expires_at(),
+        received_at: clock::now_secs(),
+    };
+    let data_dir = data_dir.to_path_buf();
+    storage_worker::after_commit(move || match add_claim(&data_dir, incoming.clone()) {
+        Ok(true) => events::publish(AppEvent::RecoveryClaimReceived {
+            contact_id: incoming.contact_id,
+            contact_name: incoming.contact_name,
+            expires_at: incoming.expires_at,
+        }),
+        Ok(false) => {}
+        Err(e) => tracing::warn!("Failed to store recovery claim: {}", e),
+    });
+}
+
+/// Check a recovery proof and, if it holds, reset the recovered contact's
+/// trust until the user confirms their new fingerprint.
+///
+/// The proof must concern a contact (by the lost or the recovered key) and
+/// its vouchers must be valid for its claim; who relayed it does not
+/// matter.
+fn receive_proof(
+    storage: &Storage,
+    data_dir: &Path,
+    sender_id: &str,
+    old_pk: String,
+    new_pk: String,
+    claim_b64: String,
+    vouchers: Vec<String>,
+) {
+    let Ok(contacts) = storage.list_contacts() else {
+        return;
+    };
+    let recovered = contacts
+        .iter()
+        .find(|c| hex::encode(c.public_key()) == new_pk);
+    let previous = contacts
+        .iter()
+        .find(|c| hex::encode(c.public_key()) == old_pk);
+    // Until the new key is added, the entry is for the lost identity
+    let Some(contact) = recovered.or(previous) else {
+        return;
+    };
+    let claim_matches = BASE64
+        .decode(&claim_b64)
+        .ok()
+        .and_then(|bytes| RecoveryClaim::from_bytes(&bytes).ok())
+        .is_some_and(|claim| {
+            hex::encode(claim.old_pk()) == old_pk && hex::encode(claim.new_pk()) == new_pk
+        });
+    if !claim_matches {
+        tracing::warn!("Ignoring invalid recovery proof from {}", sender_id);
+        return;
+    }
+
+    // Same checks as for our own recovery; invalid vouchers do not count
+    let mut session = RecoverySession::new(old_pk.clone(), new_pk.clone(), claim_b64, 0);
+    for voucher in &vouchers {
+        let _ = session.add_voucher(voucher);
+    }
+    if session.vouchers.is_empty() {
+        tracing::warn!(
+            "Ignoring recovery proof without valid vouchers from {}",
+            sender_id
+        );
+        return;
+    }
+    let (mutual_voucher_ids, mutual_vouchers): (Vec<String>, Vec<String>) = session
+        .vouchers
+        .iter()
+        .filter_map(|v| {
+            contacts
+                .iter()
+                .find(|c| hex::encode(c.public_key()) == v.voucher_pk)
+                .map(|c| (c.id().to_string(), c.display_name().to_string()))
+        })
+        .unzip();
+    let threshold = recovery_policy::load(data_dir).verification_threshold;
+
+    // Neither the old nor the new key is trusted until re-verified
+    for contact in [recovered, previous].into_iter().flatten() {
+        if !contact.is_recovery_trusted() {
+            continue;
+        }
+        if let Ok(Some(mut contact)) = storage.load_contact(contact.id()) {
+            contact.untrust_for_recovery();
+            if let Err(e) = storage.save_contact(&contact) {
+                tracing::warn!("Failed to reset recovery trust: {:?}", e);
+            }
+        }
+    }
+
+    let entry = NeedsReverification {
+        contact_id: contact.id().to_string(),
+        display_name: contact.display_name().to_string(),
+        previous_contact_id: previous.map(|c| c.id().to_string()),
+        old_pk,
+        new_pk,
+        high_confidence: mutual_vouchers.len() >= threshold as usize,
+        mutual_vouchers,
+        mutual_voucher_ids,
+        total_vouchers: session.vouchers.len(),
+        recovered_at: clock::now_secs(),
+    };
+    let data_dir = data_dir.to_path_buf();
+    storage_worker::after_commit(move || {
+        if let Err(e) = reverification::add(&data_dir, entry) {
+            tracing::warn!("Failed to store re-verification: {}", e);
+        }
+    });
+}
+
+/// Decode a decrypted update as a recovery message.
+pub(crate) fn decode_message(plaintext: &[u8]) -> Option<RecoveryMessage> {
+    serde_json::from_slice(plaintext).ok()
+}
+
+/// Decode an unencrypted update as a recovery proof. Claims are only
+/// accepted over a session.
+pub(crate) fn decode_public_proof(payload: &[u8]) -> Option<RecoveryMessage> {
+    decode_message(payload).filter(|m| matches!(m, RecoveryMessage::Proof { .. }))
+}
+
+/// Handle a recovery message from a contact: claims about known contacts
+/// are stored and announced, proofs are checked.
+pub(crate) fn receive(
+    storage: &Storage,
+    data_dir: &Path,
+    sender_id: &str,
+    message: RecoveryMessage,
+) {
+    match message {
+        RecoveryMessage::Claim { claim } => receive_claim(storage, data_dir, sender_id, claim),
+        RecoveryMessage::Proof {
+            old_pk,
+            new_pk,
+            claim,
+            vouchers,
+        } => receive_proof(
+            storage, data_dir, sender_id, old_pk, new_pk, claim, vouchers,
+        ),
+    }
+}
+
+/// Forward received claims from the event bus to the frontend.
+pub fn start(app: AppHandle) {
+    let mut rx = events::subscribe();
+    background::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            if let AppEvent::RecoveryClaimReceived { .. } = event {
+                if let Err(e) = app.emit(CLAIM_EVENT, &event) {
+                    tracing::warn!("Failed to emit recovery claim event: {}", e);
+                }
+            }
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private session persistence
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn session() -> RecoverySession {
+        RecoverySession::new(
+            hex::encode([1; 32]),
+            hex::encode([2; 32]),
+            "claim".to_string(),
+            10,
+        )
+    }
+
+    #[test]
+    fn test_garbage_voucher_is_rejected() {
+        let mut session = session();
+        assert_eq!(
+            session.add_voucher("not base64!").unwrap_err(),
+            VoucherError::Invalid
+        );
+        assert_eq!(
+            session.add_voucher(&BASE64.encode(b"nope")).unwrap_err(),
+            VoucherError::Invalid
+        );
+        assert!(session.vouchers.is_empty());
+    }
+
+    #[test]
+    fn test_completion_and_proof() {
+        let mut session = session();
+        assert!(!session.is_complete(1));
+        session.vouchers.push(CollectedVoucher {
+            voucher_pk: "aa".to_string(),
+            timestamp: 11,
+            voucher: "v1".to_string(),
+        });
+        assert!(session.is_complete(1));
+
+        let json = serde_json::to_value(session.proof()).unwrap();
+        assert_eq!(json["kind"], "vauchi.recovery-proof.v1");
+        assert_eq!(json["vouchers"], serde_json::json!(["v1"]));
+    }
+
+    #[test]
+    fn test_only_proofs_are_accepted_unencrypted() {
+        let proof = serde_json::to_vec(&session().proof()).unwrap();
+        assert_eq!(decode_public_proof(&proof), Some(session().proof()));
+
+        let claim = serde_json::to_vec(&RecoveryMessage::Claim {
+            claim: "claim".to_string(),
+        })
+        .unwrap();
+        assert!(decode_public_proof(&claim).is_none());
+    }
+
+    #[test]
+    fn test_expired_claims_are_dropped() {
+        let temp = TempDir::new().unwrap();
+        let claim = |claim: &str, expires_at| IncomingRecoveryClaim {
+            claim: claim.to_string(),
+            contact_id: "c1".to_string(),
+            contact_name: "Alice".to_string(),
+            sender_id: "s1".to_string(),
+            expires_at,
+            received_at: 0,
+        };
+        assert!(add_claim(temp.path(), claim("fresh", u64::MAX)).unwrap());
+        assert!(!add_claim(temp.path(), claim("fresh", u64::MAX)).unwrap());
+        assert!(add_claim(temp.path(), claim("old", 1)).unwrap());
+
+        let claims = load_claims(temp.path());
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].claim, "fresh");
+    }
+
+    #[test]
+    fn test_session_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert!(load(temp.path()).is_none());
+        save(temp.path(), &session()).unwrap();
+        assert_eq!(load(temp.path()), Some(session()));
+    }
+}
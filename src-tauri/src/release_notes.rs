@@ -0,0 +1,251 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Release Notes
+//!
+//! Per-version release notes, bundled with the app
+//! (`resources/release_notes.json`) and refreshable through the content
+//! subsystem as `help/release_notes.json` in the content cache (remote help
+//! content or an offline bundle). Entries from the content cache replace
+//! bundled ones of the same version.
+//!
+//! Notes are keyed by locale code, with English as the fallback. The last
+//! version whose notes the user saw is kept in `whats_new.json`, so the UI
+//! shows a what's-new dialog once per upgrade.
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::content_versions;
+
+/// Notes bundled with this build.
+const BUNDLED: &str = include_str!("../resources/release_notes.json");
+
+/// Refreshed notes, relative to the content cache.
+const CONTENT_FILE: &str = "help/release_notes.json";
+
+/// Last-seen tracker file name under the data dir.
+const SEEN_FILE: &str = "whats_new.json";
+
+/// Fallback locale for notes.
+const FALLBACK_LOCALE: &str = "en";
+
+/// Notes of one release, per locale code.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Release {
+    pub version: String,
+    pub date: Option<String>,
+    pub notes: std::collections::BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ReleaseNotesFile {
+    releases: Vec<Release>,
+}
+
+/// One release's notes in the chosen locale.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseEntry {
+    pub version: String,
+    pub date: Option<String>,
+    /// Locale the notes are in (the fallback if the chosen one is missing).
+    pub locale: String,
+    pub notes: Vec<String>,
+}
+
+/// Last version whose notes were shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WhatsNewState {
+    pub last_seen_version: Option<String>,
+}
+
+/// Compare dotted versions numerically (`0.10.0` > `0.9.1`); anything after
+/// a `-` or `+` is ignored.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parts(v: &str) -> Vec<u64> {
+        v.trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    }
+    let (a, b) = (parts(a), parts(b));
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn parse(json: &str) -> Vec<Release> {
+    serde_json::from_str::<ReleaseNotesFile>(json)
+        .map(|f| f.releases)
+        .unwrap_or_default()
+}
+
+/// Bundled releases, with refreshed ones from the content cache on top.
+pub fn load(data_dir: &Path) -> Vec<Release> {
+    let mut releases = parse(BUNDLED);
+    let refreshed =
+        std::fs::read_to_string(content_versions::content_dir(data_dir).join(CONTENT_FILE))
+            .map(|json| parse(&json))
+            .unwrap_or_default();
+    for release in refreshed {
+        releases.retain(|r| compare_versions(&r.version, &release.version).is_ne());
+        releases.push(release);
+    }
+    releases
+}
+
+/// Notes in `locale` (`de-CH`, then `de`, then English).
+fn localized(release: &Release, locale: &str) -> Option<(String, Vec<String>)> {
+    let language = locale.split(['-', '_']).next().unwrap_or(locale);
+    [locale, language, FALLBACK_LOCALE]
+        .into_iter()
+        .find_map(|code| {
+            release
+                .notes
+                .get(code)
+                .map(|notes| (code.to_string(), notes.clone()))
+        })
+}
+
+/// Releases after `since` up to `current`, newest first. Without `since`,
+/// all releases up to `current`.
+pub fn whats_new(
+    releases: &[Release],
+    since: Option<&str>,
+    current: &str,
+    locale: &str,
+) -> Vec<ReleaseEntry> {
+    let mut entries: Vec<ReleaseEntry> = releases
+        .iter()
+        .filter(|r| since.map_or(true, |s| compare_versions(&r.version, s).is_gt()))
+        .filter(|r| compare_versions(&r.version, current).is_le())
+        .filter_map(|r| {
+            let (locale, notes) = localized(r, locale)?;
+            Some(ReleaseEntry {
+                version: r.version.clone(),
+                date: r.date.clone(),
+                locale,
+                notes,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    entries
+}
+
+fn seen_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SEEN_FILE)
+}
+
+/// Load the tracker; `None` if it was never written (fresh install).
+pub fn load_seen(data_dir: &Path) -> Option<WhatsNewState> {
+    std::fs::read_to_string(seen_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// Remember `version` as the last one whose notes were seen.
+pub fn mark_seen(data_dir: &Path, version: &str) -> std::io::Result<()> {
+    let state = WhatsNewState {
+        last_seen_version: Some(version.to_string()),
+    };
+    std::fs::write(seen_path(data_dir), serde_json::to_string_pretty(&state)?)
+}
+
+/// On a fresh install, count the running version as seen, so the dialog
+/// only appears after an upgrade.
+pub fn init(data_dir: &Path, version: &str) {
+    if load_seen(data_dir).is_some() {
+        return;
+    }
+    if let Err(e) = mark_seen(data_dir, version) {
+        tracing::warn!("Failed to save what's new state: {}", e);
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private note selection
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn release(version: &str, locales: &[&str]) -> Release {
+        Release {
+            version: version.to_string(),
+            date: None,
+            notes: locales
+                .iter()
+                .map(|l| (l.to_string(), vec![format!("{} {}", version, l)]))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_bundled_notes_parse() {
+        assert!(!parse(BUNDLED).is_empty());
+    }
+
+    #[test]
+    fn test_versions_compare_numerically() {
+        assert!(compare_versions("0.10.0", "0.9.1").is_gt());
+        assert!(compare_versions("1.0", "1.0.0").is_eq());
+        assert!(compare_versions("v1.2.0-beta", "1.2.0").is_eq());
+    }
+
+    #[test]
+    fn test_whats_new_between_versions_newest_first() {
+        let releases = vec![
+            release("0.1.0", &["en"]),
+            release("0.2.0", &["en", "de"]),
+            release("0.3.0", &["en"]),
+            release("0.4.0", &["en"]),
+        ];
+        let entries = whats_new(&releases, Some("0.1.0"), "0.3.0", "de-CH");
+        let versions: Vec<&str> = entries.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, vec!["0.3.0", "0.2.0"]);
+        assert_eq!(entries[0].locale, "en");
+        assert_eq!(entries[1].locale, "de");
+        assert_eq!(whats_new(&releases, None, "0.4.0", "en").len(), 4);
+    }
+
+    #[test]
+    fn test_refreshed_notes_replace_bundled() {
+        let temp = TempDir::new().unwrap();
+        let bundled = parse(BUNDLED);
+        let version = bundled[0].version.clone();
+        let path = content_versions::content_dir(temp.path()).join(CONTENT_FILE);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let refreshed = serde_json::json!({
+            "releases": [{ "version": version, "notes": { "en": ["Fixed"] } }]
+        });
+        std::fs::write(&path, refreshed.to_string()).unwrap();
+
+        let releases = load(temp.path());
+        assert_eq!(releases.len(), bundled.len());
+        let release = releases.iter().find(|r| r.version == version).unwrap();
+        assert_eq!(release.notes["en"], vec!["Fixed"]);
+    }
+
+    #[test]
+    fn test_fresh_install_counts_as_seen() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load_seen(temp.path()), None);
+        init(temp.path(), "0.2.0");
+        init(temp.path(), "0.3.0");
+        assert_eq!(
+            load_seen(temp.path()).unwrap().last_seen_version.as_deref(),
+            Some("0.2.0")
+        );
+    }
+}
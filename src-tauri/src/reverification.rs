@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Contact Re-verification
+//!
+//! When a contact recovers their identity, their key changes and whatever
+//! we knew about the old key no longer holds. Contacts that sent a valid
+//! recovery proof are kept in `reverification.json` until the user
+//! confirms the new fingerprint with `verify_contact`. Meanwhile they are
+//! not trusted for recovery and cannot be re-trusted.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Pending re-verifications file name under the data dir.
+const REVERIFICATION_FILE: &str = "reverification.json";
+
+/// A recovered contact whose new fingerprint is not confirmed yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NeedsReverification {
+    /// The contact with the new key, or our entry for the lost identity
+    /// if we do not have the new key yet.
+    pub contact_id: String,
+    pub display_name: String,
+    /// Our entry for the lost identity, if we had one.
+    pub previous_contact_id: Option<String>,
+    /// Hex public key of the lost identity.
+    pub old_pk: String,
+    /// Hex public key of the recovered identity.
+    pub new_pk: String,
+    /// Our contacts who vouched for the recovery.
+    pub mutual_vouchers: Vec<String>,
+    /// IDs of the same contacts, in the same order.
+    #[serde(default)]
+    pub mutual_voucher_ids: Vec<String>,
+    pub total_vouchers: usize,
+    /// Whether enough of our contacts vouched to meet our verification
+    /// threshold.
+    pub high_confidence: bool,
+    pub recovered_at: u64,
+}
+
+fn reverification_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(REVERIFICATION_FILE)
+}
+
+/// Contacts waiting for re-verification, oldest first.
+pub fn load(data_dir: &Path) -> Vec<NeedsReverification> {
+    std::fs::read_to_string(reverification_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(data_dir: &Path, entries: &[NeedsReverification]) -> std::io::Result<()> {
+    std::fs::write(
+        reverification_path(data_dir),
+        serde_json::to_string(entries)?,
+    )
+}
+
+/// Record a recovered contact, replacing an earlier entry for it.
+pub fn add(data_dir: &Path, entry: NeedsReverification) -> std::io::Result<()> {
+    let mut entries = load(data_dir);
+    entries.retain(|e| e.contact_id != entry.contact_id);
+    entries.push(entry);
+    save(data_dir, &entries)
+}
+
+/// Whether the contact still needs re-verification.
+pub fn is_pending(data_dir: &Path, contact_id: &str) -> bool {
+    load(data_dir).iter().any(|e| e.contact_id == contact_id)
+}
+
+/// Mark the contact's new fingerprint as confirmed. Returns whether it was
+/// pending.
+pub fn resolve(data_dir: &Path, contact_id: &str) -> std::io::Result<bool> {
+    let mut entries = load(data_dir);
+    let before = entries.len();
+    entries.retain(|e| e.contact_id != contact_id);
+    if entries.len() == before {
+        return Ok(false);
+    }
+    save(data_dir, &entries)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(contact_id: &str, recovered_at: u64) -> NeedsReverification {
+        NeedsReverification {
+            contact_id: contact_id.to_string(),
+            display_name: "Alice".to_string(),
+            previous_contact_id: None,
+            old_pk: "aa".to_string(),
+            new_pk: "bb".to_string(),
+            mutual_vouchers: Vec::new(),
+            mutual_voucher_ids: Vec::new(),
+            total_vouchers: 3,
+            high_confidence: false,
+            recovered_at,
+        }
+    }
+
+    #[test]
+    fn test_add_replaces_and_resolve_clears() {
+        let temp = TempDir::new().unwrap();
+        add(temp.path(), entry("a", 1)).unwrap();
+        add(temp.path(), entry("a", 2)).unwrap();
+        add(temp.path(), entry("b", 3)).unwrap();
+        assert_eq!(load(temp.path()), vec![entry("a", 2), entry("b", 3)]);
+        assert!(is_pending(temp.path(), "a"));
+
+        assert!(resolve(temp.path(), "a").unwrap());
+        assert!(!resolve(temp.path(), "a").unwrap());
+        assert!(!is_pending(temp.path(), "a"));
+        assert_eq!(load(temp.path()), vec![entry("b", 3)]);
+    }
+}
@@ -0,0 +1,70 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Secrets
+//!
+//! Passwords and device link data (which carries the link key) are held in
+//! `SecretString`, which wipes its buffer when dropped and never shows its
+//! contents in `Debug` output. Seeds use `zeroize::Zeroizing` directly.
+//! Borrow the value with `expose` only where it is needed; copies made from
+//! it are not wiped.
+
+use std::fmt;
+
+use serde::Deserialize;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A string secret, wiped from memory when dropped.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Zeroize, ZeroizeOnDrop)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// Borrow the secret value.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_hides_value_and_zeroize_clears() {
+        let mut secret = SecretString::from("hunter2");
+        assert_eq!(format!("{:?}", secret), "SecretString(..)");
+        assert_eq!(secret.expose(), "hunter2");
+        secret.zeroize();
+        assert_eq!(secret.expose(), "");
+    }
+
+    #[test]
+    fn test_deserializes_from_plain_string() {
+        let secret: SecretString = serde_json::from_str("\"pass\"").unwrap();
+        assert_eq!(secret.expose(), "pass");
+    }
+}
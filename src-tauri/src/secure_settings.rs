@@ -0,0 +1,359 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Encrypted Settings
+//!
+//! Settings that work as credentials are kept in the `desktop_settings`
+//! table of the database, each value encrypted with the storage key,
+//! rather than in the plain settings files in the data dir:
+//!
+//! - the relay URL, which may carry credentials;
+//! - webhook signing secrets;
+//! - the CardDAV password;
+//! - the Tor proxy for app updates, which may carry a password;
+//! - the device scopes shared with the other linked devices, which decide
+//!   what a mirror device may do.
+//!
+//! Tor bridges are already kept in core storage. Storage key rotation
+//! re-encrypts these values with `rekey` once core has re-keyed its own.
+//!
+//! The settings are opened once per data dir, with the storage key
+//! `AppState` already loaded, and reused from then on: reading a setting
+//! never touches the keychain or opens another connection. Until they are
+//! opened, and whenever a value cannot be read, callers get an error
+//! rather than an empty value, so a credential is never silently blank.
+//!
+//! `migrate` moves values older versions wrote in plaintext into the
+//! table and removes them from the files.
+
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use vauchi_core::crypto::{decrypt, encrypt};
+use vauchi_core::SymmetricKey;
+
+use crate::carddav;
+use crate::commands::app_update;
+use crate::device_mode::{self, DeviceScopes};
+use crate::error::CommandError;
+use crate::webhooks;
+
+/// How long to wait for other connections to finish writing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Relay URL file written by older versions.
+const LEGACY_RELAY_URL_FILE: &str = "relay_url.txt";
+
+/// A named setting holding a `T`.
+pub struct Setting<T> {
+    name: &'static str,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Setting<T> {
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _value: PhantomData,
+        }
+    }
+}
+
+/// User-configured relay URL.
+pub const RELAY_URL: Setting<String> = Setting::new("relay_url");
+
+/// Webhook signing secrets by webhook ID.
+pub const WEBHOOK_SECRETS: Setting<BTreeMap<String, String>> = Setting::new("webhook_secrets");
+
+/// Password of the local CardDAV server.
+pub const CARDDAV_PASSWORD: Setting<String> = Setting::new("carddav_password");
+
+/// SOCKS proxy URL for app updates in Tor mode.
+pub const UPDATE_TOR_PROXY: Setting<String> = Setting::new("update_tor_proxy");
+
+/// Modes of the linked devices.
+pub const DEVICE_SCOPES: Setting<DeviceScopes> = Setting::new("device_scopes");
+
+fn sqlite_error(e: rusqlite::Error) -> CommandError {
+    CommandError::Storage(format!("Encrypted settings failed: {}", e))
+}
+
+/// The encrypted settings of a data dir.
+pub struct SecureSettings {
+    conn: Connection,
+    key: SymmetricKey,
+}
+
+impl SecureSettings {
+    fn open_with_key(data_dir: &Path, key: SymmetricKey) -> Result<Self, CommandError> {
+        let conn = Connection::open(data_dir.join("vauchi.db")).map_err(sqlite_error)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(sqlite_error)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS desktop_settings (
+                 name TEXT PRIMARY KEY,
+                 value BLOB NOT NULL
+             )",
+        )
+        .map_err(sqlite_error)?;
+        Ok(Self { conn, key })
+    }
+
+    /// Read a setting; `None` if it was never set.
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        setting: &Setting<T>,
+    ) -> Result<Option<T>, CommandError> {
+        let sealed: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT value FROM desktop_settings WHERE name = ?1",
+                [setting.name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)?;
+        let Some(sealed) = sealed else {
+            return Ok(None);
+        };
+        let json = decrypt(&self.key, &sealed).map_err(|e| {
+            CommandError::Storage(format!("Failed to decrypt {}: {}", setting.name, e))
+        })?;
+        Ok(Some(serde_json::from_slice(&json)?))
+    }
+
+    /// Write a setting.
+    pub fn set<T: Serialize>(&self, setting: &Setting<T>, value: &T) -> Result<(), CommandError> {
+        let json = serde_json::to_vec(value)?;
+        let sealed = encrypt(&self.key, &json).map_err(|e| {
+            CommandError::Storage(format!("Failed to encrypt {}: {}", setting.name, e))
+        })?;
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO desktop_settings (name, value) VALUES (?1, ?2)",
+                rusqlite::params![setting.name, sealed],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+
+    /// Delete a setting.
+    pub fn remove<T>(&self, setting: &Setting<T>) -> Result<(), CommandError> {
+        self.conn
+            .execute(
+                "DELETE FROM desktop_settings WHERE name = ?1",
+                [setting.name],
+            )
+            .map_err(sqlite_error)?;
+        Ok(())
+    }
+}
+
+/// Open settings by data dir.
+fn opened() -> &'static Mutex<HashMap<PathBuf, SecureSettings>> {
+    static OPENED: OnceLock<Mutex<HashMap<PathBuf, SecureSettings>>> = OnceLock::new();
+    OPENED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Open the settings of `data_dir` with the storage key the database was
+/// opened with, replacing any opened before (e.g. with a rotated key).
+pub fn open(data_dir: &Path, key: &SymmetricKey) -> Result<(), CommandError> {
+    let settings =
+        SecureSettings::open_with_key(data_dir, SymmetricKey::from_bytes(*key.as_bytes()))?;
+    opened()
+        .lock()
+        .unwrap()
+        .insert(data_dir.to_path_buf(), settings);
+    Ok(())
+}
+
+fn with_opened<R>(
+    data_dir: &Path,
+    f: impl FnOnce(&SecureSettings) -> Result<R, CommandError>,
+) -> Result<R, CommandError> {
+    let opened = opened().lock().unwrap();
+    let settings = opened
+        .get(data_dir)
+        .ok_or_else(|| CommandError::Storage("Encrypted settings are not open yet".to_string()))?;
+    f(settings)
+}
+
+/// Read a setting of `data_dir`; `None` if it was never set.
+pub fn get<T: DeserializeOwned>(
+    data_dir: &Path,
+    setting: &Setting<T>,
+) -> Result<Option<T>, CommandError> {
+    with_opened(data_dir, |settings| settings.get(setting))
+}
+
+/// Write a setting of `data_dir`, or delete it if `value` is `None`.
+pub fn set<T: Serialize>(
+    data_dir: &Path,
+    setting: &Setting<T>,
+    value: Option<&T>,
+) -> Result<(), CommandError> {
+    with_opened(data_dir, |settings| match value {
+        Some(value) => settings.set(setting, value),
+        None => settings.remove(setting),
+    })
+}
+
+/// Re-encrypt the settings of `data_dir` from `old` to `new`. Values that
+/// no longer open with `old` are left, so an interrupted rotation can run
+/// this again. Reopen the settings afterwards.
+pub(crate) fn rekey(
+    data_dir: &Path,
+    old: &SymmetricKey,
+    new: &SymmetricKey,
+) -> Result<(), CommandError> {
+    let mut settings =
+        SecureSettings::open_with_key(data_dir, SymmetricKey::from_bytes(*old.as_bytes()))?;
+    let tx = settings.conn.transaction().map_err(sqlite_error)?;
+    let values: Vec<(String, Vec<u8>)> = {
+        let mut stmt = tx
+            .prepare("SELECT name, value FROM desktop_settings")
+            .map_err(sqlite_error)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(sqlite_error)?;
+        rows.collect::<rusqlite::Result<_>>()
+            .map_err(sqlite_error)?
+    };
+    for (name, sealed) in values {
+        let Ok(json) = decrypt(old, &sealed) else {
+            continue;
+        };
+        let sealed = encrypt(new, &json)
+            .map_err(|e| CommandError::Storage(format!("Failed to encrypt {}: {}", name, e)))?;
+        tx.execute(
+            "UPDATE desktop_settings SET value = ?1 WHERE name = ?2",
+            rusqlite::params![sealed, name],
+        )
+        .map_err(sqlite_error)?;
+    }
+    tx.commit().map_err(sqlite_error)
+}
+
+/// The relay URL from `relay_url.txt`, where older versions kept it.
+pub fn legacy_relay_url(data_dir: &Path) -> Option<String> {
+    std::fs::read_to_string(data_dir.join(LEGACY_RELAY_URL_FILE))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Remove `relay_url.txt` once the URL is in the encrypted settings.
+pub fn remove_legacy_relay_url(data_dir: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(data_dir.join(LEGACY_RELAY_URL_FILE)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn migrate_relay_url(data_dir: &Path) -> Result<(), CommandError> {
+    let Some(url) = legacy_relay_url(data_dir) else {
+        return Ok(());
+    };
+    set(data_dir, &RELAY_URL, Some(&url))?;
+    remove_legacy_relay_url(data_dir)?;
+    Ok(())
+}
+
+/// Move sensitive values that older versions wrote to plain files into the
+/// encrypted settings. Call once storage is open.
+pub fn migrate(data_dir: &Path) {
+    let results = [
+        ("relay URL", migrate_relay_url(data_dir)),
+        (
+            "webhook secrets",
+            webhooks::migrate_secrets(data_dir).map_err(CommandError::from),
+        ),
+        (
+            "CardDAV password",
+            carddav::migrate_password(data_dir).map_err(CommandError::from),
+        ),
+        (
+            "update proxy",
+            app_update::migrate_tor_proxy(data_dir).map_err(CommandError::from),
+        ),
+        ("device modes", device_mode::migrate_legacy(data_dir)),
+    ];
+    for (what, result) in results {
+        if let Err(e) = result {
+            tracing::warn!("Failed to move the {} to encrypted settings: {}", what, e);
+        }
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private key handling on a scratch database
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_values_are_stored_encrypted() {
+        let temp = TempDir::new().unwrap();
+        let settings =
+            SecureSettings::open_with_key(temp.path(), SymmetricKey::from_bytes([7; 32])).unwrap();
+        assert_eq!(settings.get(&CARDDAV_PASSWORD).unwrap(), None);
+
+        settings
+            .set(&CARDDAV_PASSWORD, &"hunter2".to_string())
+            .unwrap();
+        assert_eq!(
+            settings.get(&CARDDAV_PASSWORD).unwrap().as_deref(),
+            Some("hunter2")
+        );
+        let raw = std::fs::read(temp.path().join("vauchi.db")).unwrap();
+        assert!(!raw.windows(7).any(|w| w == b"hunter2"));
+
+        let other = SecureSettings::open_with_key(temp.path(), SymmetricKey::generate()).unwrap();
+        assert!(other.get(&CARDDAV_PASSWORD).is_err());
+
+        settings.remove(&CARDDAV_PASSWORD).unwrap();
+        assert_eq!(settings.get(&CARDDAV_PASSWORD).unwrap(), None);
+    }
+
+    #[test]
+    fn test_rekey_moves_values_to_the_new_key() {
+        let temp = TempDir::new().unwrap();
+        let (old, new) = (SymmetricKey::generate(), SymmetricKey::generate());
+        let settings =
+            SecureSettings::open_with_key(temp.path(), SymmetricKey::from_bytes(*old.as_bytes()))
+                .unwrap();
+        settings
+            .set(&CARDDAV_PASSWORD, &"hunter2".to_string())
+            .unwrap();
+        drop(settings);
+
+        rekey(temp.path(), &old, &new).unwrap();
+        // Running it again leaves the re-keyed values alone
+        rekey(temp.path(), &old, &new).unwrap();
+        let settings = SecureSettings::open_with_key(temp.path(), new).unwrap();
+        assert_eq!(
+            settings.get(&CARDDAV_PASSWORD).unwrap().as_deref(),
+            Some("hunter2")
+        );
+    }
+
+    #[test]
+    fn test_unopened_settings_fail_closed() {
+        let temp = TempDir::new().unwrap();
+        assert!(get(temp.path(), &CARDDAV_PASSWORD).is_err());
+        assert!(set(temp.path(), &CARDDAV_PASSWORD, Some(&"x".to_string())).is_err());
+
+        open(temp.path(), &SymmetricKey::from_bytes([7; 32])).unwrap();
+        set(temp.path(), &CARDDAV_PASSWORD, Some(&"x".to_string())).unwrap();
+        assert_eq!(
+            get(temp.path(), &CARDDAV_PASSWORD).unwrap().as_deref(),
+            Some("x")
+        );
+    }
+}
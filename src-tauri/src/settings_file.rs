@@ -0,0 +1,72 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Settings Files
+//!
+//! Small JSON settings files kept next to the database in the data dir.
+//! A missing or unreadable file reads as the defaults, so a damaged file
+//! resets one group of settings instead of blocking startup.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Read `name` from the data dir, or the defaults if it is missing or
+/// does not parse.
+pub fn load<T: DeserializeOwned + Default>(data_dir: &Path, name: &str) -> T {
+    std::fs::read_to_string(data_dir.join(name))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Write `value` to `name` in the data dir.
+pub fn save<T: Serialize>(data_dir: &Path, name: &str, value: &T) -> std::io::Result<()> {
+    std::fs::write(data_dir.join(name), serde_json::to_string_pretty(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use tempfile::TempDir;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Sample {
+        enabled: bool,
+        count: u32,
+    }
+
+    #[test]
+    fn test_missing_file_loads_defaults() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(
+            load::<Sample>(temp.path(), "sample.json"),
+            Sample::default()
+        );
+    }
+
+    #[test]
+    fn test_unreadable_file_loads_defaults() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("sample.json"), "not json").unwrap();
+        assert_eq!(
+            load::<Sample>(temp.path(), "sample.json"),
+            Sample::default()
+        );
+    }
+
+    #[test]
+    fn test_save_then_load_round_trips() {
+        let temp = TempDir::new().unwrap();
+        let sample = Sample {
+            enabled: true,
+            count: 3,
+        };
+        save(temp.path(), "sample.json", &sample).unwrap();
+        assert_eq!(load::<Sample>(temp.path(), "sample.json"), sample);
+    }
+}
@@ -0,0 +1,523 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Share Links
+//!
+//! One-way sharing of selected card fields with someone who is not a
+//! contact yet. The fields are signed with our identity key, encrypted
+//! with a fresh key and published as a blob on the relay. The link is
+//! `vauchi://share/<id>#<key>`: the key only travels in the URL fragment,
+//! so the relay holds ciphertext it cannot read. Nothing flows back and
+//! the recipient gets no updates.
+//!
+//! The signature covers the display name and the expiry together with the
+//! signer's public key, so a link cannot be edited to claim another name
+//! or to outlive its expiry. The relay drops the blob at expiry and
+//! revoking deletes it, after which the link no longer opens. What was
+//! already opened cannot be taken back.
+//!
+//! `share_links.json` lists the links we made by ID, with the token that
+//! lets us delete the blob, but neither the key nor the shared values.
+//!
+//! The relay does not store blobs yet, so publishing, fetching and
+//! deleting them is behind the `share-links` feature, off by default.
+//! Without it those calls fail and no link can be made or opened. The
+//! relay API the feature expects:
+//!
+//! - `PUT /v1/blobs/<id>` stores the body (at most 64 KiB) under `<id>`.
+//!   `X-Vauchi-Blob-Token` sets the token needed to delete it, and the
+//!   optional `X-Vauchi-Blob-Expires` the Unix time after which the relay
+//!   drops it.
+//! - `GET /v1/blobs/<id>` returns the body, or 404 once it is gone.
+//! - `DELETE /v1/blobs/<id>` with the matching `X-Vauchi-Blob-Token`
+//!   deletes it; 404 if it is already gone.
+//!
+//! The relay URL's `ws`/`wss` scheme maps to `http`/`https`.
+
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use vauchi_core::crypto::{decrypt, encrypt};
+use vauchi_core::{Identity, SymmetricKey};
+
+/// Registry file name under the data dir.
+const REGISTRY_FILE: &str = "share_links.json";
+
+/// Link prefix; the blob ID and the key follow.
+pub const LINK_PREFIX: &str = "vauchi://share/";
+
+/// Current payload version.
+const VERSION: u32 = 1;
+
+/// A shared field as carried in the link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedField {
+    pub label: String,
+    pub field_type: String,
+    pub value: String,
+}
+
+/// What a share link carries, as signed by its creator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedCard {
+    pub version: u32,
+    pub id: String,
+    pub display_name: String,
+    pub fields: Vec<SharedField>,
+    pub expires_at: Option<u64>,
+    /// Signing public key (hex) of the creator.
+    pub public_key: String,
+}
+
+impl SharedCard {
+    /// Whether the link is past its expiry at `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|t| now >= t)
+    }
+}
+
+/// The encrypted content of a blob: the card JSON and its signature.
+#[derive(Serialize, Deserialize)]
+struct SignedCard {
+    card: String,
+    /// Base64url Ed25519 signature over `card`.
+    signature: String,
+}
+
+/// A link we created.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareLinkRecord {
+    pub id: String,
+    pub field_ids: Vec<String>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+    pub revoked_at: Option<u64>,
+    /// Token for deleting the blob from the relay; `None` for links made
+    /// before links were published there.
+    #[serde(default)]
+    pub delete_token: Option<String>,
+}
+
+impl ShareLinkRecord {
+    /// Whether the link may still be opened at `now`.
+    pub fn is_active(&self, now: u64) -> bool {
+        self.revoked_at.is_none() && self.expires_at.map_or(true, |t| now < t)
+    }
+}
+
+/// Created links, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShareLinkRegistry {
+    pub links: Vec<ShareLinkRecord>,
+}
+
+impl ShareLinkRegistry {
+    /// Mark link `id` revoked at `now`. Returns the record, or `None` if
+    /// there is no such link.
+    pub fn revoke(&mut self, id: &str, now: u64) -> Option<&ShareLinkRecord> {
+        let record = self.links.iter_mut().find(|r| r.id == id)?;
+        record.revoked_at.get_or_insert(now);
+        Some(record)
+    }
+}
+
+fn registry_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(REGISTRY_FILE)
+}
+
+/// Load the registry, empty if missing or unreadable.
+pub fn load(data_dir: &Path) -> ShareLinkRegistry {
+    std::fs::read_to_string(registry_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Save the registry.
+pub fn save(data_dir: &Path, registry: &ShareLinkRegistry) -> std::io::Result<()> {
+    std::fs::write(
+        registry_path(data_dir),
+        serde_json::to_string_pretty(registry)?,
+    )
+}
+
+/// Rewrite the registry without the plaintext links older versions kept
+/// in it.
+pub fn drop_legacy_links(data_dir: &Path) -> std::io::Result<()> {
+    let Ok(json) = std::fs::read_to_string(registry_path(data_dir)) else {
+        return Ok(());
+    };
+    let has_links = serde_json::from_str::<serde_json::Value>(&json)
+        .ok()
+        .and_then(|v| v.get("links").and_then(|l| l.as_array()).cloned())
+        .is_some_and(|links| links.iter().any(|l| l.get("link").is_some()));
+    if !has_links {
+        return Ok(());
+    }
+    save(data_dir, &load(data_dir))
+}
+
+/// A new random ID or token.
+fn random_token(len: usize) -> String {
+    hex::encode(&SymmetricKey::generate().as_bytes()[..len])
+}
+
+/// A new random link ID.
+pub fn new_id() -> String {
+    random_token(16)
+}
+
+/// A new random token for deleting a blob.
+pub fn new_delete_token() -> String {
+    random_token(32)
+}
+
+/// A shared card with version and signer set.
+pub fn shared_card(
+    id: String,
+    display_name: String,
+    identity: &Identity,
+    fields: Vec<SharedField>,
+    expires_at: Option<u64>,
+) -> SharedCard {
+    SharedCard {
+        version: VERSION,
+        id,
+        display_name,
+        fields,
+        expires_at,
+        public_key: hex::encode(identity.signing_public_key()),
+    }
+}
+
+/// Sign `card` with `identity` and encrypt it under a fresh key.
+/// Returns the blob to publish and the link that opens it.
+pub fn seal(card: &SharedCard, identity: &Identity) -> Result<(Vec<u8>, String), String> {
+    let json = serde_json::to_string(card).map_err(|e| e.to_string())?;
+    let signature = identity.signing_keypair().sign(json.as_bytes());
+    let signed = serde_json::to_vec(&SignedCard {
+        card: json,
+        signature: URL_SAFE_NO_PAD.encode(signature.as_bytes()),
+    })
+    .map_err(|e| e.to_string())?;
+
+    let key = SymmetricKey::generate();
+    let blob = encrypt(&key, &signed).map_err(|e| e.to_string())?;
+    let link = format!(
+        "{}{}#{}",
+        LINK_PREFIX,
+        card.id,
+        URL_SAFE_NO_PAD.encode(key.as_bytes())
+    );
+    Ok((blob, link))
+}
+
+/// Split a link (or its bare `<id>#<key>` payload) into blob ID and key.
+pub fn parse_link(link: &str) -> Result<(String, SymmetricKey), String> {
+    let payload = link.trim().trim_start_matches(LINK_PREFIX);
+    let invalid = || "Not a share link".to_string();
+    let (id, key) = payload.split_once('#').ok_or_else(invalid)?;
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let key: [u8; 32] = URL_SAFE_NO_PAD
+        .decode(key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(invalid)?;
+    Ok((id.to_string(), SymmetricKey::from_bytes(key)))
+}
+
+/// Decrypt blob `id` and check its signature. Expiry is left to the caller.
+pub fn open(id: &str, key: &SymmetricKey, blob: &[u8]) -> Result<SharedCard, String> {
+    let invalid = || "This share link is damaged or was tampered with".to_string();
+    let signed = decrypt(key, blob).map_err(|_| invalid())?;
+    let signed: SignedCard = serde_json::from_slice(&signed).map_err(|_| invalid())?;
+    let card: SharedCard = serde_json::from_str(&signed.card).map_err(|_| invalid())?;
+    if card.version > VERSION {
+        return Err("This share link needs a newer version of Vauchi".to_string());
+    }
+
+    let public_key = hex::decode(&card.public_key).map_err(|_| invalid())?;
+    let signature = URL_SAFE_NO_PAD
+        .decode(&signed.signature)
+        .map_err(|_| invalid())?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(signed.card.as_bytes(), &signature)
+        .map_err(|_| invalid())?;
+    if card.id != id {
+        return Err(invalid());
+    }
+    Ok(card)
+}
+
+pub use relay::{delete, fetch, publish};
+
+/// Blob storage on the relay.
+#[cfg(feature = "share-links")]
+mod relay {
+    use std::time::Duration;
+
+    use url::Url;
+
+    /// Relay path under which share blobs are stored.
+    const BLOB_PATH: &str = "v1/blobs";
+
+    /// Header carrying the token that allows deleting a blob.
+    const TOKEN_HEADER: &str = "X-Vauchi-Blob-Token";
+
+    /// Header carrying the Unix time after which the relay drops a blob.
+    const EXPIRES_HEADER: &str = "X-Vauchi-Blob-Expires";
+
+    /// Largest blob accepted from the relay.
+    const MAX_BLOB_BYTES: usize = 64 * 1024;
+
+    /// Timeout for relay blob requests.
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// URL of blob `id` on the relay at `relay_url`.
+    pub(super) fn blob_url(relay_url: &str, id: &str) -> Result<Url, String> {
+        let mut url = Url::parse(relay_url).map_err(|e| format!("Invalid relay URL: {}", e))?;
+        let scheme = match url.scheme() {
+            "wss" | "https" => "https",
+            "ws" | "http" => "http",
+            other => return Err(format!("Unsupported relay scheme '{}'", other)),
+        };
+        url.set_scheme(scheme)
+            .map_err(|_| "Invalid relay URL".to_string())?;
+        url.set_path(&format!("{}/{}", BLOB_PATH, id));
+        url.set_query(None);
+        Ok(url)
+    }
+
+    fn client(proxy: Option<&Url>) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some(proxy) = proxy {
+            builder =
+                builder.proxy(reqwest::Proxy::all(proxy.as_str()).map_err(|e| e.to_string())?);
+        }
+        builder.build().map_err(|e| e.to_string())
+    }
+
+    /// Publish `blob` as `id`, deletable with `delete_token` and dropped by the
+    /// relay after `expires_at`.
+    pub async fn publish(
+        relay_url: &str,
+        proxy: Option<&Url>,
+        id: &str,
+        delete_token: &str,
+        expires_at: Option<u64>,
+        blob: Vec<u8>,
+    ) -> Result<(), String> {
+        let mut request = client(proxy)?
+            .put(blob_url(relay_url, id)?)
+            .header(TOKEN_HEADER, delete_token)
+            .body(blob);
+        if let Some(expires_at) = expires_at {
+            request = request.header(EXPIRES_HEADER, expires_at.to_string());
+        }
+        request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to publish share link: {}", e))?;
+        Ok(())
+    }
+
+    /// Fetch blob `id`. `Ok(None)` if the relay no longer has it.
+    pub async fn fetch(
+        relay_url: &str,
+        proxy: Option<&Url>,
+        id: &str,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let response = client(proxy)?
+            .get(blob_url(relay_url, id)?)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch share link: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let bytes = response
+            .error_for_status()
+            .map_err(|e| format!("Failed to fetch share link: {}", e))?
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to fetch share link: {}", e))?;
+        if bytes.len() > MAX_BLOB_BYTES {
+            return Err("Share link content is too large".to_string());
+        }
+        Ok(Some(bytes.to_vec()))
+    }
+
+    /// Delete blob `id` from the relay. Deleting a blob that is already gone
+    /// succeeds.
+    pub async fn delete(
+        relay_url: &str,
+        proxy: Option<&Url>,
+        id: &str,
+        delete_token: &str,
+    ) -> Result<(), String> {
+        let response = client(proxy)?
+            .delete(blob_url(relay_url, id)?)
+            .header(TOKEN_HEADER, delete_token)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to delete share link: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        response
+            .error_for_status()
+            .map_err(|e| format!("Failed to delete share link: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Stand-ins while the relay does not store blobs.
+#[cfg(not(feature = "share-links"))]
+mod relay {
+    use url::Url;
+
+    fn unsupported() -> String {
+        "Share links are not available yet: the relay does not support them".to_string()
+    }
+
+    pub async fn publish(
+        _relay_url: &str,
+        _proxy: Option<&Url>,
+        _id: &str,
+        _delete_token: &str,
+        _expires_at: Option<u64>,
+        _blob: Vec<u8>,
+    ) -> Result<(), String> {
+        Err(unsupported())
+    }
+
+    pub async fn fetch(
+        _relay_url: &str,
+        _proxy: Option<&Url>,
+        _id: &str,
+    ) -> Result<Option<Vec<u8>>, String> {
+        Err(unsupported())
+    }
+
+    pub async fn delete(
+        _relay_url: &str,
+        _proxy: Option<&Url>,
+        _id: &str,
+        _delete_token: &str,
+    ) -> Result<(), String> {
+        Err(unsupported())
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private link sealing and registry
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn card(identity: &Identity, expires_at: Option<u64>) -> SharedCard {
+        shared_card(
+            "abc123".to_string(),
+            "Alice".to_string(),
+            identity,
+            vec![SharedField {
+                label: "Mobile".to_string(),
+                field_type: "Phone".to_string(),
+                value: "+41 79 000 00 00".to_string(),
+            }],
+            expires_at,
+        )
+    }
+
+    #[test]
+    fn test_sealed_link_opens_and_expires() {
+        let alice = Identity::create("Alice");
+        let (blob, link) = seal(&card(&alice, Some(200)), &alice).unwrap();
+        assert!(link.starts_with(LINK_PREFIX));
+        assert!(!String::from_utf8_lossy(&blob).contains("+41 79"));
+
+        let (id, key) = parse_link(&link).unwrap();
+        assert_eq!(id, "abc123");
+        let opened = open(&id, &key, &blob).unwrap();
+        assert_eq!(opened, card(&alice, Some(200)));
+        assert!(!opened.is_expired(100));
+        assert!(opened.is_expired(200));
+
+        assert!(parse_link("vauchi://share/abc123").is_err());
+        assert!(open("other", &key, &blob).is_err());
+        assert!(open(&id, &SymmetricKey::generate(), &blob).is_err());
+    }
+
+    #[test]
+    fn test_forged_card_is_rejected() {
+        let alice = Identity::create("Alice");
+        let (blob, link) = seal(&card(&alice, Some(200)), &alice).unwrap();
+        let (id, key) = parse_link(&link).unwrap();
+
+        // Re-encrypt with a later expiry and another name, keeping the signature
+        let signed: SignedCard = serde_json::from_slice(&decrypt(&key, &blob).unwrap()).unwrap();
+        let mut forged: SharedCard = serde_json::from_str(&signed.card).unwrap();
+        forged.display_name = "Bob".to_string();
+        forged.expires_at = None;
+        let tampered = serde_json::to_vec(&SignedCard {
+            card: serde_json::to_string(&forged).unwrap(),
+            signature: signed.signature,
+        })
+        .unwrap();
+        let tampered = encrypt(&key, &tampered).unwrap();
+        assert!(open(&id, &key, &tampered).is_err());
+    }
+
+    #[test]
+    fn test_registry_keeps_no_link() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            registry_path(temp.path()),
+            r#"{"links":[{"id":"abc","field_ids":["f1"],"created_at":0,
+                "expires_at":null,"revoked_at":null,"link":"vauchi://share/x"}]}"#,
+        )
+        .unwrap();
+        drop_legacy_links(temp.path()).unwrap();
+        let json = std::fs::read_to_string(registry_path(temp.path())).unwrap();
+        assert!(!json.contains("vauchi://share/x"));
+
+        let mut registry = load(temp.path());
+        assert_eq!(registry.links[0].delete_token, None);
+        registry.links[0].delete_token = Some("tok".to_string());
+        assert!(registry.links[0].is_active(100));
+        assert_eq!(
+            registry.revoke("abc", 100).unwrap().delete_token.as_deref(),
+            Some("tok")
+        );
+        assert!(registry.revoke("missing", 100).is_none());
+        save(temp.path(), &registry).unwrap();
+
+        let record = &load(temp.path()).links[0];
+        assert!(!record.is_active(100));
+        assert_eq!(record.delete_token.as_deref(), Some("tok"));
+        assert_eq!(record.revoked_at, Some(100));
+    }
+
+    #[cfg(feature = "share-links")]
+    #[test]
+    fn test_blob_url_uses_http_scheme() {
+        use relay::blob_url;
+
+        assert_eq!(
+            blob_url("wss://relay.vauchi.app/ws", "abc")
+                .unwrap()
+                .as_str(),
+            "https://relay.vauchi.app/v1/blobs/abc"
+        );
+        assert!(blob_url("ftp://relay", "abc").is_err());
+    }
+}
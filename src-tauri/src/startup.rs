@@ -0,0 +1,167 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Startup Timing
+//!
+//! Times each phase of app startup so regressions in time-to-window show
+//! up in `get_startup_report` instead of being guessed at. Only what the
+//! first screen needs runs in `setup`; the rest runs in the background
+//! afterwards, and `startup://ready` names each component once it is
+//! ready: `i18n`, `themes`, and `state` (storage key from the keychain and
+//! the identity, needed by most commands). Components that were ready
+//! before the frontend started listening are listed in the report.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::metrics;
+
+/// Event sent when a background component is ready.
+pub const READY_EVENT: &str = "startup://ready";
+
+/// Setup time above which a warning is logged.
+pub const SETUP_BUDGET: Duration = Duration::from_millis(500);
+
+/// One timed phase of startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupPhase {
+    pub name: String,
+    /// Milliseconds since startup began when the phase started.
+    pub started_at_ms: u64,
+    pub duration_ms: u64,
+    /// Whether the phase ran after the window was shown.
+    pub background: bool,
+}
+
+/// Startup timings for the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupReport {
+    /// Milliseconds spent in `setup`, before the window was shown.
+    pub setup_ms: Option<u64>,
+    pub setup_budget_ms: u64,
+    pub phases: Vec<StartupPhase>,
+    /// Background components ready so far.
+    pub ready: Vec<String>,
+}
+
+#[derive(Default)]
+struct Timeline {
+    started: Option<Instant>,
+    setup: Option<Duration>,
+    phases: Vec<StartupPhase>,
+    ready: Vec<String>,
+}
+
+fn timeline() -> &'static Mutex<Timeline> {
+    static TIMELINE: OnceLock<Mutex<Timeline>> = OnceLock::new();
+    TIMELINE.get_or_init(|| Mutex::new(Timeline::default()))
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Timeline> {
+    timeline().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn since_start(timeline: &Timeline, at: Instant) -> u64 {
+    timeline.started.map_or(0, |start| {
+        at.saturating_duration_since(start).as_millis() as u64
+    })
+}
+
+/// Mark the start of startup.
+pub fn begin() {
+    lock().started.get_or_insert_with(Instant::now);
+}
+
+/// Run and time a phase of `setup`.
+pub fn phase<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    timed(name, false, f)
+}
+
+/// Run and time a background phase.
+pub fn background_phase<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    timed(name, true, f)
+}
+
+fn timed<T>(name: &str, background: bool, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    metrics::record(&format!("startup:{}", name), elapsed);
+    let mut timeline = lock();
+    let started_at_ms = since_start(&timeline, start);
+    timeline.phases.push(StartupPhase {
+        name: name.to_string(),
+        started_at_ms,
+        duration_ms: elapsed.as_millis() as u64,
+        background,
+    });
+    result
+}
+
+/// Mark the end of `setup`, warning if it ran over budget.
+pub fn finish_setup() {
+    let mut timeline = lock();
+    let Some(started) = timeline.started else {
+        return;
+    };
+    let setup = started.elapsed();
+    timeline.setup = Some(setup);
+    if setup > SETUP_BUDGET {
+        tracing::warn!(
+            "Startup took {} ms, over the {} ms budget",
+            setup.as_millis(),
+            SETUP_BUDGET.as_millis()
+        );
+    }
+}
+
+/// Announce that a background component is ready.
+pub fn ready(app: &AppHandle, component: &str) {
+    lock().ready.push(component.to_string());
+    if let Err(e) = app.emit(READY_EVENT, component) {
+        tracing::warn!("Failed to emit startup event: {}", e);
+    }
+}
+
+/// Current startup timings.
+pub fn report() -> StartupReport {
+    let timeline = lock();
+    StartupReport {
+        setup_ms: timeline.setup.map(|d| d.as_millis() as u64),
+        setup_budget_ms: SETUP_BUDGET.as_millis() as u64,
+        phases: timeline.phases.clone(),
+        ready: timeline.ready.clone(),
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private startup timeline
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_are_recorded_in_order() {
+        begin();
+        let value = phase("test:setup-phase", || 7);
+        background_phase("test:background-phase", || ());
+        finish_setup();
+        assert_eq!(value, 7);
+
+        let report = report();
+        assert!(report.setup_ms.is_some());
+        let names: Vec<(&str, bool)> = report
+            .phases
+            .iter()
+            .filter(|p| p.name.starts_with("test:"))
+            .map(|p| (p.name.as_str(), p.background))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("test:setup-phase", false), ("test:background-phase", true)]
+        );
+    }
+}
@@ -6,21 +6,36 @@
 //!
 //! Manages the Vauchi storage and identity.
 
+use std::cell::{OnceCell, RefCell};
 use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use vauchi_core::exchange::{
-    DeviceLinkInitiatorRestored, DeviceLinkRequest, ExchangeSession, ManualConfirmationVerifier,
+    DeviceLinkInitiatorRestored, DeviceLinkQR, DeviceLinkRequest, ExchangeSession,
+    ManualConfirmationVerifier,
+};
+use vauchi_core::{
+    AuthMode, Contact, Identity, IdentityBackup, Storage, StorageError, SymmetricKey,
 };
-use vauchi_core::{AuthMode, Identity, IdentityBackup, Storage, SymmetricKey};
 
 #[cfg(feature = "secure-storage")]
 use vauchi_core::storage::secure::PlatformKeyring;
 
 use vauchi_core::storage::secure::{FileKeyStorage, SecureStorage};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::contact_cache::ContactCache;
+use crate::deep_link::DeepLink;
+use crate::logging;
+use crate::recovery_qr::RecoveryQrScan;
+use crate::secret::SecretString;
+use crate::secure_settings::{self, RELAY_URL};
+use crate::storage_worker::StorageHandle;
+use crate::trust_graph::TrustGraphCache;
 
 /// Legacy hardcoded password used before per-installation backup passwords.
-const LEGACY_BACKUP_PASSWORD: &str = "vauchi-local-storage";
+pub(crate) const LEGACY_BACKUP_PASSWORD: &str = "vauchi-local-storage";
 
 /// Default relay URL.
 const DEFAULT_RELAY_URL: &str = "wss://relay.vauchi.app";
@@ -41,6 +56,20 @@ pub struct SyncResult {
     pub error: Option<String>,
 }
 
+/// Pending device join state stored between steps. Wiped when dropped,
+/// since the QR data holds the link key.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct PendingJoin {
+    /// The original QR data string.
+    pub qr_data: SecretString,
+    /// The device name for this new device.
+    pub device_name: String,
+    /// Confirmation code computed after creating the request (stored because nonce is not recoverable).
+    pub confirmation_code: String,
+    /// Identity fingerprint from the QR code.
+    pub fingerprint: String,
+}
+
 /// Application state containing Vauchi storage.
 pub struct AppState {
     /// Storage instance
@@ -53,12 +82,15 @@ pub struct AppState {
     display_name: Option<String>,
     /// Relay server URL
     relay_url: String,
+    /// Current UI locale code
+    locale_code: String,
     /// Data directory for config files
     data_dir: std::path::PathBuf,
-    /// Pending device join state (JSON serialized).
-    pub pending_device_join: Option<String>,
-    /// Pending device link QR data for completing link requests.
-    pub pending_device_link_qr: Option<String>,
+    /// Pending device join state (between join and finish).
+    pub pending_device_join: Option<PendingJoin>,
+    /// Pending device link QR data for completing link requests. Holds the
+    /// link key, so it is wiped when cleared.
+    pub pending_device_link_qr: Option<SecretString>,
     /// Active exchange session (if an exchange is in progress).
     pub exchange_session: Option<ExchangeSession<ManualConfirmationVerifier>>,
     /// Active device link initiator (between prepare and confirm).
@@ -69,6 +101,18 @@ pub struct AppState {
     pub pending_sender_token: Option<String>,
     /// Current authentication mode (Normal, Duress, or Unauthenticated).
     pub auth_mode: AuthMode,
+    /// In-memory cache of decrypted contacts.
+    contact_cache: ContactCache,
+    /// Cached per-contact slices of the web-of-trust graph.
+    trust_graph: TrustGraphCache,
+    /// Deep link received but not yet handled by the frontend.
+    pub pending_deep_link: Option<DeepLink>,
+    /// Recovery QR sequence being scanned.
+    pub pending_recovery_scan: Option<RecoveryQrScan>,
+    /// Identity imported for async tasks, with the backup it came from.
+    identity_handle: RefCell<Option<(Vec<u8>, Arc<Identity>)>>,
+    /// Storage worker for async tasks, started on first use.
+    storage_handle: OnceCell<StorageHandle>,
 }
 
 /// Loads or generates a per-installation random fallback key from `data_dir/.fallback-key`.
@@ -115,13 +159,14 @@ pub(crate) fn load_or_generate_fallback_key(data_dir: &Path) -> Result<Symmetric
 ///
 /// Each installation gets a unique random password (32 random bytes, hex-encoded)
 /// instead of the old hardcoded `"vauchi-local-storage"` constant.
-fn load_or_generate_backup_password(data_dir: &Path) -> Result<String> {
+fn load_or_generate_backup_password(data_dir: &Path) -> Result<SecretString> {
     let password_path = data_dir.join(".backup-password");
 
     if password_path.exists() {
-        let content =
-            std::fs::read_to_string(&password_path).context("Failed to read backup password")?;
-        let trimmed = content.trim().to_string();
+        let content = SecretString::new(
+            std::fs::read_to_string(&password_path).context("Failed to read backup password")?,
+        );
+        let trimmed = content.expose().trim();
         if trimmed.len() != 64 {
             anyhow::bail!(
                 "Invalid backup password length ({}), expected 64 hex chars. Delete {} to regenerate.",
@@ -129,19 +174,15 @@ fn load_or_generate_backup_password(data_dir: &Path) -> Result<String> {
                 password_path.display()
             );
         }
-        return Ok(trimmed);
+        return Ok(SecretString::from(trimmed));
     }
 
     // Generate a new random password (32 random bytes, hex-encoded = 64 chars)
     let key = SymmetricKey::generate();
-    let password: String = key
-        .as_bytes()
-        .iter()
-        .map(|b| format!("{:02x}", b))
-        .collect();
+    let password = SecretString::new(hex::encode(key.as_bytes()));
 
     std::fs::create_dir_all(data_dir).context("Failed to create data directory")?;
-    std::fs::write(&password_path, &password).context("Failed to write backup password")?;
+    std::fs::write(&password_path, password.expose()).context("Failed to write backup password")?;
 
     #[cfg(unix)]
     {
@@ -155,7 +196,7 @@ fn load_or_generate_backup_password(data_dir: &Path) -> Result<String> {
 
 impl AppState {
     /// Returns the per-installation backup password.
-    pub fn backup_password(&self) -> Result<String> {
+    pub fn backup_password(&self) -> Result<SecretString> {
         load_or_generate_backup_password(&self.data_dir)
     }
 
@@ -165,7 +206,7 @@ impl AppState {
     /// (scoped to the data directory to prevent cross-instance conflicts).
     /// Verifies the keychain actually persists keys; if not (e.g. no Secret Service
     /// daemon on Linux), automatically falls back to encrypted file storage.
-    fn load_or_create_storage_key(data_dir: &Path) -> Result<SymmetricKey> {
+    pub(crate) fn load_or_create_storage_key(data_dir: &Path) -> Result<SymmetricKey> {
         const KEY_NAME: &str = "storage_key";
 
         // Try OS keychain first when secure-storage is enabled
@@ -296,7 +337,7 @@ impl AppState {
     /// Each data directory gets its own keychain entry, preventing conflicts
     /// between parallel test instances and multiple installations.
     #[cfg(feature = "secure-storage")]
-    fn keyring_service_name(data_dir: &Path) -> String {
+    pub(crate) fn keyring_service_name(data_dir: &Path) -> String {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
@@ -317,6 +358,8 @@ impl AppState {
 
         // Generate or load encryption key using SecureStorage
         let key = Self::load_or_create_storage_key(data_dir)?;
+        secure_settings::open(data_dir, &key)
+            .map_err(|e| anyhow::anyhow!("Failed to open encrypted settings: {}", e))?;
 
         let storage = Storage::open(&db_path, key).context("Failed to open storage")?;
 
@@ -325,14 +368,14 @@ impl AppState {
         let (identity, backup_data, display_name) =
             if let Ok(Some((backup, name))) = storage.load_identity() {
                 let backup_obj = IdentityBackup::new(backup.clone());
-                match Identity::import_backup(&backup_obj, &backup_password) {
+                match Identity::import_backup(&backup_obj, backup_password.expose()) {
                     Ok(id) => (Some(id), Some(backup), Some(name)),
                     Err(_) => {
                         // Try legacy hardcoded password for migration
                         match Identity::import_backup(&backup_obj, LEGACY_BACKUP_PASSWORD) {
                             Ok(id) => {
                                 // Re-export with per-installation password
-                                if let Ok(new_backup) = id.export_backup(&backup_password) {
+                                if let Ok(new_backup) = id.export_backup(backup_password.expose()) {
                                     let new_data = new_backup.as_bytes().to_vec();
                                     let _ = storage.save_identity(&new_data, &name);
                                     (Some(id), Some(new_data), Some(name))
@@ -347,16 +390,19 @@ impl AppState {
             } else {
                 (None, None, None)
             };
+        logging::remember_names(display_name.as_deref());
 
         // Load relay URL with fallback hierarchy:
-        // 1. User-configured URL (stored in config file)
+        // 1. User-configured URL (encrypted settings, or the config file
+        //    older versions wrote)
         // 2. VAUCHI_RELAY_URL environment variable
         // 3. Default: wss://relay.vauchi.app
-        let relay_config_path = data_dir.join("relay_url.txt");
-        let relay_url = std::fs::read_to_string(&relay_config_path)
-            .ok()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
+        let relay_url = secure_settings::get(data_dir, &RELAY_URL)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to read relay URL: {}", e);
+                None
+            })
+            .or_else(|| secure_settings::legacy_relay_url(data_dir))
             .or_else(|| {
                 std::env::var("VAUCHI_RELAY_URL")
                     .ok()
@@ -364,12 +410,22 @@ impl AppState {
             })
             .unwrap_or_else(|| DEFAULT_RELAY_URL.to_string());
 
+        // Load locale: user choice (config file) or the OS locale
+        let locale_code = std::fs::read_to_string(data_dir.join("locale.txt"))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| {
+                crate::commands::i18n::detect_locale_code(sys_locale::get_locale().as_deref())
+            });
+
         Ok(AppState {
             storage,
             identity,
             backup_data,
             display_name,
             relay_url,
+            locale_code,
             data_dir: data_dir.to_path_buf(),
             pending_device_join: None,
             pending_device_link_qr: None,
@@ -378,9 +434,40 @@ impl AppState {
             pending_link_request: None,
             pending_sender_token: None,
             auth_mode: AuthMode::Unauthenticated,
+            contact_cache: ContactCache::new(),
+            trust_graph: TrustGraphCache::new(),
+            pending_deep_link: None,
+            pending_recovery_scan: None,
+            identity_handle: RefCell::new(None),
+            storage_handle: OnceCell::new(),
         })
     }
 
+    /// Drop the device link in progress on this device, wiping its link key.
+    pub fn clear_pending_device_link(&mut self) {
+        self.pending_initiator = None;
+        self.pending_link_request = None;
+        self.pending_device_link_qr = None;
+    }
+
+    /// Drop device link and join state whose QR has expired, so the link
+    /// key does not outlive the link.
+    pub fn clear_expired_device_links(&mut self) {
+        let expired = |qr_data: &SecretString| {
+            DeviceLinkQR::from_data_string(qr_data.expose()).map_or(true, |qr| qr.is_expired())
+        };
+        if self.pending_device_link_qr.as_ref().is_some_and(expired) {
+            self.clear_pending_device_link();
+        }
+        if self
+            .pending_device_join
+            .as_ref()
+            .is_some_and(|join| expired(&join.qr_data))
+        {
+            self.pending_device_join = None;
+        }
+    }
+
     /// Check if identity exists.
     pub fn has_identity(&self) -> bool {
         self.identity.is_some() || self.backup_data.is_some()
@@ -391,27 +478,62 @@ impl AppState {
     /// Reconstructs the identity from the stored backup data.
     pub fn create_owned_identity(&self) -> Result<Identity> {
         if let Some(ref backup_data) = self.backup_data {
-            let password = self.backup_password()?;
-            let backup = IdentityBackup::new(backup_data.clone());
-            match Identity::import_backup(&backup, &password) {
-                Ok(id) => Ok(id),
-                Err(_) => {
-                    // Fall back to legacy password for un-migrated data
-                    Identity::import_backup(&backup, LEGACY_BACKUP_PASSWORD)
-                        .map_err(|e| anyhow::anyhow!("Failed to import identity: {:?}", e))
-                }
-            }
+            self.import_identity(backup_data)
         } else {
             anyhow::bail!("No identity backup data available")
         }
     }
 
+    /// Shared identity for async tasks such as relay sync.
+    ///
+    /// Importing a backup runs the password KDF, so the imported identity
+    /// is kept and handed out again until the stored backup changes.
+    pub fn identity_handle(&self) -> Result<Arc<Identity>> {
+        let (backup_data, _name) = self
+            .storage
+            .load_identity()
+            .map_err(|e| anyhow::anyhow!("Failed to load identity: {:?}", e))?
+            .context("No identity found in storage")?;
+        if let Some((cached_backup, identity)) = self.identity_handle.borrow().as_ref() {
+            if *cached_backup == backup_data {
+                return Ok(Arc::clone(identity));
+            }
+        }
+        let identity = Arc::new(self.import_identity(&backup_data)?);
+        *self.identity_handle.borrow_mut() = Some((backup_data, Arc::clone(&identity)));
+        Ok(identity)
+    }
+
+    /// Sendable storage access for async tasks, backed by a worker thread
+    /// that keeps its database connection open.
+    pub fn storage_handle(&self) -> Result<StorageHandle> {
+        if let Some(handle) = self.storage_handle.get() {
+            return Ok(handle.clone());
+        }
+        let handle = StorageHandle::spawn(&self.data_dir)?;
+        Ok(self.storage_handle.get_or_init(|| handle).clone())
+    }
+
+    /// Import an identity backup with the installation's password.
+    fn import_identity(&self, backup_data: &[u8]) -> Result<Identity> {
+        let password = self.backup_password()?;
+        let backup = IdentityBackup::new(backup_data.to_vec());
+        match Identity::import_backup(&backup, password.expose()) {
+            Ok(id) => Ok(id),
+            Err(_) => {
+                // Fall back to legacy password for un-migrated data
+                Identity::import_backup(&backup, LEGACY_BACKUP_PASSWORD)
+                    .map_err(|e| anyhow::anyhow!("Failed to import identity: {:?}", e))
+            }
+        }
+    }
+
     /// Create a new identity.
     pub fn create_identity(&mut self, name: &str) -> Result<()> {
         let password = self.backup_password()?;
         let identity = Identity::create(name);
         let backup = identity
-            .export_backup(&password)
+            .export_backup(password.expose())
             .map_err(|e| anyhow::anyhow!("Failed to create backup: {:?}", e))?;
         let backup_data = backup.as_bytes().to_vec();
 
@@ -422,6 +544,7 @@ impl AppState {
         self.identity = Some(identity);
         self.backup_data = Some(backup_data);
         self.display_name = Some(name.to_string());
+        logging::remember_names([name]);
         Ok(())
     }
 
@@ -450,13 +573,39 @@ impl AppState {
 
     /// Open a storage instance for the given data directory.
     /// Used for creating storage in background threads.
-    #[allow(dead_code)]
     pub fn open_storage(data_dir: &Path) -> Result<Storage> {
         let db_path = data_dir.join("vauchi.db");
         let key = Self::load_or_create_storage_key(data_dir)?;
         Storage::open(&db_path, key).context("Failed to open storage")
     }
 
+    /// Stop the storage worker so nothing writes behind the lock; the next
+    /// `storage_handle` starts a new one. Fails while a sync or push still
+    /// uses it.
+    pub fn stop_storage_worker(&mut self) -> Result<()> {
+        let Some(handle) = self.storage_handle.take() else {
+            return Ok(());
+        };
+        if let Err(handle) = handle.stop() {
+            self.storage_handle = OnceCell::from(handle);
+            anyhow::bail!("A sync is still running; try again once it has finished");
+        }
+        Ok(())
+    }
+
+    /// Reopen storage after its key changed. Call `stop_storage_worker`
+    /// first; new jobs get a fresh worker.
+    pub fn reopen_storage(&mut self) -> Result<()> {
+        let key = Self::load_or_create_storage_key(&self.data_dir)?;
+        secure_settings::open(&self.data_dir, &key)
+            .map_err(|e| anyhow::anyhow!("Failed to open encrypted settings: {}", e))?;
+        self.storage = Storage::open(&self.data_dir.join("vauchi.db"), key)
+            .context("Failed to open storage")?;
+        self.storage_handle = OnceCell::new();
+        self.invalidate_contact_cache();
+        Ok(())
+    }
+
     /// Set the relay URL.
     pub fn set_relay_url(&mut self, url: &str) -> Result<()> {
         let url = url.trim();
@@ -470,14 +619,28 @@ impl AppState {
         // Validate URL format
         url::Url::parse(url).context("Invalid URL format")?;
 
-        // Save to config file
-        let relay_config_path = self.data_dir.join("relay_url.txt");
-        std::fs::write(&relay_config_path, url).context("Failed to save relay URL")?;
+        // Save to encrypted settings, as the URL may carry credentials
+        secure_settings::set(&self.data_dir, &RELAY_URL, Some(&url.to_string()))
+            .context("Failed to save relay URL")?;
+        secure_settings::remove_legacy_relay_url(&self.data_dir)
+            .context("Failed to remove old relay URL file")?;
 
         self.relay_url = url.to_string();
         Ok(())
     }
 
+    /// Get the current locale code.
+    pub fn locale_code(&self) -> &str {
+        &self.locale_code
+    }
+
+    /// Set and persist the current locale code.
+    pub fn set_locale_code(&mut self, code: &str) -> Result<()> {
+        std::fs::write(self.data_dir.join("locale.txt"), code).context("Failed to save locale")?;
+        self.locale_code = code.to_string();
+        Ok(())
+    }
+
     /// Get the user's contact card.
     pub fn get_card(&self) -> Result<Option<vauchi_core::ContactCard>> {
         self.storage.load_own_card().context("Failed to load card")
@@ -489,6 +652,7 @@ impl AppState {
             .storage
             .list_contacts()
             .context("Failed to list contacts")?;
+        logging::remember_names(contacts.iter().map(|c| c.display_name()));
         Ok(contacts
             .into_iter()
             .map(|c| ContactInfo {
@@ -499,6 +663,65 @@ impl AppState {
             .collect())
     }
 
+    /// Load a contact, serving it from the in-memory cache when possible.
+    pub fn cached_contact(&self, id: &str) -> Result<Option<Contact>, StorageError> {
+        if let Some(contact) = self.contact_cache.get(id) {
+            return Ok(Some(contact));
+        }
+        let contact = self.storage.load_contact(id)?;
+        if let Some(ref c) = contact {
+            logging::remember_names([c.display_name()]);
+            self.contact_cache.put(c);
+        }
+        Ok(contact)
+    }
+
+    /// List all contacts, serving them from the in-memory cache when possible.
+    pub fn cached_contacts(&self) -> Result<Vec<Contact>, StorageError> {
+        if let Some(contacts) = self.contact_cache.list() {
+            return Ok(contacts);
+        }
+        let contacts = self.storage.list_contacts()?;
+        logging::remember_names(contacts.iter().map(|c| c.display_name()));
+        self.contact_cache.fill(&contacts);
+        Ok(contacts)
+    }
+
+    /// Save a contact to storage and update the cache.
+    pub fn save_contact(&self, contact: &Contact) -> Result<(), StorageError> {
+        self.storage.save_contact(contact)?;
+        logging::remember_names([contact.display_name()]);
+        self.contact_cache.put(contact);
+        self.trust_graph.invalidate(contact.id());
+        Ok(())
+    }
+
+    /// Delete a contact from storage and drop it from the cache.
+    pub fn delete_contact(&self, id: &str) -> Result<bool, StorageError> {
+        let deleted = self.storage.delete_contact(id)?;
+        self.contact_cache.invalidate(id);
+        self.trust_graph.invalidate(id);
+        crate::unread::forget(&self.data_dir, id);
+        if deleted {
+            crate::deleted_contacts::record(&self.data_dir, &[id.to_string()]);
+        }
+        Ok(deleted)
+    }
+
+    /// Drop all cached contacts.
+    ///
+    /// Call after anything writes contacts through a separate storage handle
+    /// (e.g. relay sync, which opens its own `Storage` per phase).
+    pub fn invalidate_contact_cache(&self) {
+        self.contact_cache.clear();
+        self.trust_graph.clear();
+    }
+
+    /// The web-of-trust graph cache.
+    pub fn trust_graph(&self) -> &TrustGraphCache {
+        &self.trust_graph
+    }
+
     /// Sync with relay.
     pub fn sync(&self) -> Result<SyncResult> {
         // Basic sync implementation - in real app this would use the sync manager
@@ -539,7 +762,7 @@ impl AppState {
         let password = self.backup_password()?;
         let identity = self.identity.as_mut().context("No identity to update")?;
         let backup = identity
-            .export_backup(&password)
+            .export_backup(password.expose())
             .map_err(|e| anyhow::anyhow!("Failed to export backup: {:?}", e))?;
         self.storage
             .save_identity(backup.as_bytes(), name)
@@ -575,10 +798,7 @@ impl AppState {
             .ok_or_else(|| anyhow::anyhow!("No identity found"))?;
         let device_id = identity.public_id();
 
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+        let now = crate::clock::now_secs();
 
         let mut queued = 0;
 
@@ -730,6 +950,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_identity_handle_is_reused_until_backup_changes() {
+        let (mut state, _temp) = create_test_state();
+        assert!(state.identity_handle().is_err());
+
+        state.create_identity("Alice").unwrap();
+        let first = state.identity_handle().unwrap();
+        let again = state.identity_handle().unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(first.public_id(), state.public_id().unwrap());
+
+        state.create_identity("Bob").unwrap();
+        let replaced = state.identity_handle().unwrap();
+        assert!(!Arc::ptr_eq(&first, &replaced));
+        assert_eq!(replaced.display_name(), "Bob");
+    }
+
     // === Settings Tests ===
 
     // @scenario: relay_network:Default relay configuration
@@ -0,0 +1,321 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Storage Usage
+//!
+//! Disk space used by the data dir: the database per table, the content
+//! cache and its version history, logs and crash reports, and generated
+//! issue reports.
+//!
+//! Deleted contacts and sent updates leave free pages behind in the
+//! database file; `compact` returns them to the disk with `VACUUM`. It
+//! needs the storage worker stopped, so nothing writes meanwhile. Only if
+//! the user asks for it, it also drops purgeable caches (content version
+//! history and issue reports); without the version history, content
+//! updates can no longer be rolled back to earlier versions.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::content_versions;
+use crate::crash;
+use crate::error::CommandError;
+use crate::issue_report;
+use crate::logging;
+
+/// Size of one database table with its indexes.
+#[derive(Debug, Clone, Serialize)]
+pub struct TableUsage {
+    pub name: String,
+    pub rows: u64,
+    /// `None` if this SQLite build cannot report sizes per table.
+    pub bytes: Option<u64>,
+}
+
+/// Size of the database.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseUsage {
+    /// Database file with its WAL and shared-memory files.
+    pub file_bytes: u64,
+    /// Space `VACUUM` would give back.
+    pub free_bytes: u64,
+    pub tables: Vec<TableUsage>,
+}
+
+/// Size of a directory in the data dir.
+#[derive(Debug, Clone, Serialize)]
+pub struct DirUsage {
+    pub name: String,
+    pub bytes: u64,
+    /// Whether `compact` deletes it when asked to purge caches.
+    pub purgeable: bool,
+}
+
+/// Disk space used by the data dir.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageUsage {
+    pub database: DatabaseUsage,
+    pub caches: Vec<DirUsage>,
+    pub logs: Vec<DirUsage>,
+    /// Everything in the data dir, including keys and settings.
+    pub total_bytes: u64,
+}
+
+/// Space reclaimed by `compact`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionReport {
+    pub database_bytes_before: u64,
+    pub database_bytes_after: u64,
+    pub cache_bytes_freed: u64,
+}
+
+/// How long `VACUUM` waits for a lock on the database.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn db_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("vauchi.db")
+}
+
+/// Total size of the files under `path`; 0 if it does not exist.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| dir_size(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Database file with its `-wal` and `-shm` files.
+fn db_file_bytes(data_dir: &Path) -> u64 {
+    ["", "-wal", "-shm"]
+        .iter()
+        .map(|suffix| {
+            let mut path = db_path(data_dir).into_os_string();
+            path.push(suffix);
+            dir_size(Path::new(&path))
+        })
+        .sum()
+}
+
+fn pragma(conn: &Connection, name: &str) -> rusqlite::Result<u64> {
+    conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0))
+        .map(|n| n.max(0) as u64)
+}
+
+/// Rows and bytes per table. Index sizes count towards their table.
+fn table_usage(conn: &Connection) -> rusqlite::Result<Vec<TableUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' \
+         ORDER BY name",
+    )?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    // dbstat is only there if SQLite was built with it
+    let sizes: Option<Vec<(String, i64)>> = conn
+        .prepare(
+            "SELECT COALESCE(m.tbl_name, s.name), SUM(s.pgsize) FROM dbstat s \
+             LEFT JOIN sqlite_master m ON m.name = s.name GROUP BY 1",
+        )
+        .and_then(|mut stmt| {
+            let sizes = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>();
+            sizes
+        })
+        .ok();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let rows = conn.query_row(
+                &format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")),
+                [],
+                |row| row.get::<_, i64>(0),
+            )?;
+            let bytes = sizes.as_ref().map(|sizes| {
+                sizes
+                    .iter()
+                    .find(|(table, _)| *table == name)
+                    .map_or(0, |(_, bytes)| (*bytes).max(0) as u64)
+            });
+            Ok(TableUsage {
+                name,
+                rows: rows.max(0) as u64,
+                bytes,
+            })
+        })
+        .collect()
+}
+
+fn sqlite_error(e: rusqlite::Error) -> CommandError {
+    CommandError::Storage(format!("Failed to read database usage: {}", e))
+}
+
+/// Caches: (name, path, purgeable by `compact`).
+fn cache_dirs(data_dir: &Path) -> Vec<(&'static str, PathBuf, bool)> {
+    vec![
+        ("content", content_versions::content_dir(data_dir), false),
+        (
+            "content_versions",
+            content_versions::versions_dir(data_dir),
+            true,
+        ),
+        ("issue_reports", issue_report::report_dir(data_dir), true),
+    ]
+}
+
+/// Measure the data dir.
+pub fn usage(data_dir: &Path) -> Result<StorageUsage, CommandError> {
+    let conn = Connection::open(db_path(data_dir)).map_err(sqlite_error)?;
+    let free_bytes = pragma(&conn, "freelist_count").map_err(sqlite_error)?
+        * pragma(&conn, "page_size").map_err(sqlite_error)?;
+    let database = DatabaseUsage {
+        file_bytes: db_file_bytes(data_dir),
+        free_bytes,
+        tables: table_usage(&conn).map_err(sqlite_error)?,
+    };
+
+    let caches = cache_dirs(data_dir)
+        .into_iter()
+        .map(|(name, path, purgeable)| DirUsage {
+            name: name.to_string(),
+            bytes: dir_size(&path),
+            purgeable,
+        })
+        .collect();
+    let logs = [
+        ("logs", logging::log_dir(data_dir)),
+        ("crashes", crash::crash_dir(data_dir)),
+    ]
+    .into_iter()
+    .map(|(name, path)| DirUsage {
+        name: name.to_string(),
+        bytes: dir_size(&path),
+        purgeable: false,
+    })
+    .collect();
+
+    Ok(StorageUsage {
+        database,
+        caches,
+        logs,
+        total_bytes: dir_size(data_dir),
+    })
+}
+
+/// `VACUUM` the database and, if `purge_caches`, delete purgeable caches.
+/// The storage worker must be stopped first.
+pub fn compact(data_dir: &Path, purge_caches: bool) -> Result<CompactionReport, CommandError> {
+    let compact_error =
+        |e: rusqlite::Error| CommandError::Storage(format!("Failed to compact database: {}", e));
+    let database_bytes_before = db_file_bytes(data_dir);
+    let conn = Connection::open(db_path(data_dir)).map_err(sqlite_error)?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(compact_error)?;
+    conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+        .map_err(compact_error)?;
+    drop(conn);
+
+    let mut cache_bytes_freed = 0;
+    for (name, path, purgeable) in cache_dirs(data_dir) {
+        if !purge_caches || !purgeable || !path.exists() {
+            continue;
+        }
+        let bytes = dir_size(&path);
+        match std::fs::remove_dir_all(&path) {
+            Ok(()) => cache_bytes_freed += bytes,
+            Err(e) => tracing::warn!("Failed to purge {}: {}", name, e),
+        }
+    }
+
+    let report = CompactionReport {
+        database_bytes_before,
+        database_bytes_after: db_file_bytes(data_dir),
+        cache_bytes_freed,
+    };
+    tracing::info!(
+        "Compacted database from {} to {} bytes, freed {} cache bytes",
+        report.database_bytes_before,
+        report.database_bytes_after,
+        report.cache_bytes_freed
+    );
+    Ok(report)
+}
+
+// INLINE_TEST_REQUIRED: tests measure and compact a scratch database
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn scratch_db(data_dir: &Path) {
+        let conn = Connection::open(db_path(data_dir)).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, data BLOB);
+             CREATE INDEX items_data ON items(data);",
+        )
+        .unwrap();
+        for _ in 0..200 {
+            conn.execute("INSERT INTO items (data) VALUES (zeroblob(2048))", [])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_usage_counts_tables_and_caches() {
+        let temp = TempDir::new().unwrap();
+        scratch_db(temp.path());
+        let reports = issue_report::report_dir(temp.path());
+        std::fs::create_dir_all(&reports).unwrap();
+        std::fs::write(reports.join("report.zip"), vec![0u8; 100]).unwrap();
+
+        let usage = usage(temp.path()).unwrap();
+        assert_eq!(usage.database.tables.len(), 1);
+        assert_eq!(usage.database.tables[0].rows, 200);
+        let cache = usage
+            .caches
+            .iter()
+            .find(|c| c.name == "issue_reports")
+            .unwrap();
+        assert_eq!(cache.bytes, 100);
+        assert!(usage.total_bytes >= usage.database.file_bytes + 100);
+    }
+
+    #[test]
+    fn test_compact_purges_caches_only_when_asked() {
+        let temp = TempDir::new().unwrap();
+        scratch_db(temp.path());
+        Connection::open(db_path(temp.path()))
+            .unwrap()
+            .execute("DELETE FROM items", [])
+            .unwrap();
+        assert!(usage(temp.path()).unwrap().database.free_bytes > 0);
+        let versions = content_versions::versions_dir(temp.path()).join("themes/1");
+        std::fs::create_dir_all(&versions).unwrap();
+        std::fs::write(versions.join("ocean.json"), b"{}").unwrap();
+
+        let report = compact(temp.path(), false).unwrap();
+        assert!(report.database_bytes_after < report.database_bytes_before);
+        assert_eq!(report.cache_bytes_freed, 0);
+        assert_eq!(usage(temp.path()).unwrap().database.free_bytes, 0);
+        assert!(versions.join("ocean.json").exists());
+
+        let report = compact(temp.path(), true).unwrap();
+        assert_eq!(report.cache_bytes_freed, 2);
+        assert!(!content_versions::versions_dir(temp.path()).exists());
+    }
+}
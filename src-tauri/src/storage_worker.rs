@@ -0,0 +1,298 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Storage Worker
+//!
+//! `Storage` is `!Send`, so async code cannot hold it across `.await`.
+//! Instead of reopening the database for each phase, a dedicated thread
+//! owns one `Storage` and runs jobs sent through a `StorageHandle`, which
+//! is cheap to clone and can be awaited from any task. The thread exits
+//! once every handle is dropped.
+//!
+//! Jobs that write many rows should use `transaction`, so the database
+//! commits once per job instead of once per write. Side effects outside
+//! the database (sidecar files, events) go through `after_commit`, so a
+//! rolled-back job leaves none behind.
+//!
+//! Work that must not race the worker (key rotation, compaction, moving
+//! the data dir) stops it first with `stop`, which fails while a sync or
+//! push still holds a handle.
+//!
+//! A job that panics fails on its own: its transaction is rolled back and
+//! the worker goes on with the next job. Release builds abort on panic
+//! (`panic = "abort"`), so this applies where panics unwind, in debug
+//! builds and tests.
+
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+
+use anyhow::Context;
+use tokio::sync::oneshot;
+use vauchi_core::Storage;
+
+use crate::error::CommandError;
+use crate::state::AppState;
+
+type Job = Box<dyn FnOnce(&Storage) + Send>;
+
+thread_local! {
+    /// Work waiting for the transaction running on this thread to commit;
+    /// `None` outside a transaction.
+    static AFTER_COMMIT: RefCell<Option<Vec<Box<dyn FnOnce()>>>> = const { RefCell::new(None) };
+}
+
+/// Run `f` once the transaction running on this thread commits, or right
+/// away outside one. Dropped if the transaction rolls back.
+pub fn after_commit(f: impl FnOnce() + 'static) {
+    let f: Box<dyn FnOnce()> = Box::new(f);
+    let now = AFTER_COMMIT.with(|deferred| match deferred.borrow_mut().as_mut() {
+        Some(deferred) => {
+            deferred.push(f);
+            None
+        }
+        None => Some(f),
+    });
+    if let Some(f) = now {
+        f();
+    }
+}
+
+/// Sendable handle to the storage worker thread.
+#[derive(Clone)]
+pub struct StorageHandle {
+    jobs: mpsc::Sender<Job>,
+    /// The worker thread, shared by all clones so they can be counted.
+    thread: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl StorageHandle {
+    /// Start a worker thread with its own connection to the database in
+    /// `data_dir`.
+    pub fn spawn(data_dir: &Path) -> anyhow::Result<Self> {
+        let data_dir = data_dir.to_path_buf();
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let (opened_tx, opened_rx) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("vauchi-storage".to_string())
+            .spawn(move || {
+                let storage = match AppState::open_storage(&data_dir) {
+                    Ok(storage) => {
+                        let _ = opened_tx.send(Ok(()));
+                        storage
+                    }
+                    Err(e) => {
+                        let _ = opened_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+                for job in queue {
+                    run_job(&storage, job);
+                }
+            })
+            .context("Failed to start storage worker")?;
+        opened_rx
+            .recv()
+            .context("Storage worker exited")?
+            .map_err(|e| anyhow::anyhow!(e))?;
+        Ok(Self {
+            jobs,
+            thread: Arc::new(Mutex::new(Some(thread))),
+        })
+    }
+
+    /// Whether another clone of this handle is alive, e.g. held by a sync.
+    pub fn in_use(&self) -> bool {
+        Arc::strong_count(&self.thread) > 1
+    }
+
+    /// Stop the worker once its queued jobs are done and wait for it to
+    /// close its connection. Gives the handle back if another clone is
+    /// still alive.
+    pub fn stop(self) -> Result<(), Self> {
+        if self.in_use() {
+            return Err(self);
+        }
+        let thread = self.thread.lock().unwrap().take();
+        drop(self.jobs);
+        if let Some(thread) = thread {
+            let _ = thread.join();
+        }
+        Ok(())
+    }
+
+    /// Run `job` on the worker's storage and wait for its result.
+    pub async fn run<T, F>(&self, job: F) -> Result<T, CommandError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Storage) -> T + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        self.jobs
+            .send(Box::new(move |storage| {
+                let _ = reply.send(job(storage));
+            }))
+            .map_err(|_| CommandError::Storage("Storage worker stopped".to_string()))?;
+        result
+            .await
+            .map_err(|_| CommandError::Storage("Storage worker dropped the job".to_string()))
+    }
+
+    /// Run `job` inside one transaction on the worker's storage.
+    pub async fn transaction<T, F>(&self, job: F) -> Result<T, CommandError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Storage) -> Result<T, CommandError> + Send + 'static,
+    {
+        self.run(move |storage| in_transaction(storage, job))
+            .await?
+    }
+}
+
+/// Run one job, recovering from a panic in it so the worker keeps serving
+/// the others. The job's caller sees its reply dropped.
+fn run_job(storage: &Storage, job: Job) {
+    if panic::catch_unwind(AssertUnwindSafe(|| job(storage))).is_ok() {
+        return;
+    }
+    tracing::error!("Storage job panicked; rolling back its transaction");
+    AFTER_COMMIT.with(|deferred| deferred.replace(None));
+    // Fails harmlessly if the job was not in a transaction
+    let _ = storage.rollback_transaction();
+}
+
+/// Run `f` inside one transaction, committing if it succeeds and rolling
+/// back if it fails.
+pub fn in_transaction<T>(
+    storage: &Storage,
+    f: impl FnOnce(&Storage) -> Result<T, CommandError>,
+) -> Result<T, CommandError> {
+    storage.begin_transaction().map_err(CommandError::from)?;
+    let outer = AFTER_COMMIT.with(|deferred| deferred.replace(Some(Vec::new())));
+    let result = f(storage);
+    let deferred = AFTER_COMMIT
+        .with(|deferred| deferred.replace(outer))
+        .unwrap_or_default();
+    match result {
+        Ok(value) => {
+            storage.commit_transaction().map_err(CommandError::from)?;
+            for f in deferred {
+                after_commit(f);
+            }
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback) = storage.rollback_transaction() {
+                tracing::warn!("Failed to roll back storage transaction: {}", rollback);
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_jobs_share_one_connection() {
+        let temp = TempDir::new().unwrap();
+        let handle = StorageHandle::spawn(temp.path()).unwrap();
+        let other = handle.clone();
+
+        let contacts = handle
+            .run(|storage| storage.list_contacts().map(|c| c.len()).unwrap_or(0))
+            .await
+            .unwrap();
+        assert_eq!(contacts, 0);
+
+        let identity = tokio::spawn(async move {
+            other
+                .run(|storage| storage.load_identity().ok().flatten().is_some())
+                .await
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(!identity);
+    }
+
+    #[test]
+    fn test_side_effects_wait_for_commit() {
+        use std::rc::Rc;
+
+        let temp = TempDir::new().unwrap();
+        let storage = AppState::open_storage(temp.path()).unwrap();
+        let ran = Rc::new(RefCell::new(Vec::new()));
+
+        let log = ran.clone();
+        let _ = in_transaction(&storage, move |_| {
+            after_commit(move || log.borrow_mut().push("rolled back"));
+            Err::<(), _>(CommandError::Storage("abort".to_string()))
+        });
+        let log = ran.clone();
+        in_transaction(&storage, move |_| {
+            let inner = log.clone();
+            after_commit(move || inner.borrow_mut().push("committed"));
+            assert!(log.borrow().is_empty());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(*ran.borrow(), vec!["committed"]);
+    }
+
+    #[tokio::test]
+    async fn test_worker_survives_a_panicking_job() {
+        let temp = TempDir::new().unwrap();
+        let handle = StorageHandle::spawn(temp.path()).unwrap();
+
+        let panicked = handle
+            .transaction(|_| -> Result<(), CommandError> { panic!("job failed") })
+            .await;
+        assert!(panicked.is_err());
+        assert!(handle.transaction(|_| Ok(())).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_stop_waits_for_other_handles() {
+        let temp = TempDir::new().unwrap();
+        let handle = StorageHandle::spawn(temp.path()).unwrap();
+        let sync = handle.clone();
+        assert!(handle.in_use());
+
+        let handle = handle.stop().err().unwrap();
+        assert!(sync.run(|_| ()).await.is_ok());
+        drop(sync);
+        assert!(handle.stop().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction_rolls_back() {
+        let temp = TempDir::new().unwrap();
+        let handle = StorageHandle::spawn(temp.path()).unwrap();
+
+        let result: Result<(), CommandError> = handle
+            .transaction(|storage| {
+                let card = vauchi_core::ContactCard::new("Alice");
+                let contact = vauchi_core::Contact::from_exchange(
+                    [7u8; 32],
+                    card,
+                    vauchi_core::SymmetricKey::generate(),
+                );
+                storage.save_contact(&contact)?;
+                Err(CommandError::Storage("abort".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+
+        let contacts = handle
+            .transaction(|storage| Ok(storage.list_contacts()?.len()))
+            .await
+            .unwrap();
+        assert_eq!(contacts, 0);
+    }
+}
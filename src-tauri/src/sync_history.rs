@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Sync History
+//!
+//! Records each relay sync (when it ran, what it moved, and why it failed)
+//! in `sync_history.json` in the data dir, keeping the last [`MAX_RUNS`].
+//! The latest successful run is the "last sync" shown in the sync status.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// History file name under the data dir.
+const HISTORY_FILE: &str = "sync_history.json";
+
+/// How many runs are kept.
+pub const MAX_RUNS: usize = 100;
+
+/// One sync run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncRun {
+    /// Unix seconds the run started.
+    pub started_at: u64,
+    /// Unix seconds the run ended.
+    pub finished_at: u64,
+    pub success: bool,
+    pub contacts_added: u32,
+    pub cards_updated: u32,
+    pub updates_sent: u32,
+    pub error: Option<String>,
+}
+
+/// Recorded runs, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct SyncHistory {
+    runs: Vec<SyncRun>,
+}
+
+fn history_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(HISTORY_FILE)
+}
+
+fn load(data_dir: &Path) -> SyncHistory {
+    std::fs::read_to_string(history_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Record a run, dropping the oldest past [`MAX_RUNS`].
+pub fn record(data_dir: &Path, run: SyncRun) -> std::io::Result<()> {
+    let mut history = load(data_dir);
+    history.runs.push(run);
+    let excess = history.runs.len().saturating_sub(MAX_RUNS);
+    history.runs.drain(..excess);
+    std::fs::write(history_path(data_dir), serde_json::to_string(&history)?)
+}
+
+/// The last `limit` runs, newest first.
+pub fn recent(data_dir: &Path, limit: usize) -> Vec<SyncRun> {
+    load(data_dir).runs.into_iter().rev().take(limit).collect()
+}
+
+/// The latest run, if any.
+pub fn last(data_dir: &Path) -> Option<SyncRun> {
+    load(data_dir).runs.pop()
+}
+
+/// When the latest successful run ended.
+pub fn last_success(data_dir: &Path) -> Option<u64> {
+    load(data_dir)
+        .runs
+        .iter()
+        .rev()
+        .find(|run| run.success)
+        .map(|run| run.finished_at)
+}
+
+// INLINE_TEST_REQUIRED: tests exercise the crate-private history file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn run(started_at: u64, success: bool) -> SyncRun {
+        SyncRun {
+            started_at,
+            finished_at: started_at + 2,
+            success,
+            contacts_added: 0,
+            cards_updated: 1,
+            updates_sent: 0,
+            error: (!success).then(|| "Connection timed out".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_recent_is_newest_first_and_capped() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(last(temp.path()), None);
+
+        for i in 0..(MAX_RUNS as u64 + 5) {
+            record(temp.path(), run(i, i % 2 == 0)).unwrap();
+        }
+        let recent = recent(temp.path(), 3);
+        assert_eq!(
+            recent.iter().map(|r| r.started_at).collect::<Vec<_>>(),
+            vec![104, 103, 102]
+        );
+        assert_eq!(load(temp.path()).runs.len(), MAX_RUNS);
+        assert_eq!(load(temp.path()).runs[0].started_at, 5);
+        assert_eq!(last(temp.path()), Some(run(104, true)));
+
+        record(temp.path(), run(200, false)).unwrap();
+        assert_eq!(last_success(temp.path()), Some(106));
+    }
+}
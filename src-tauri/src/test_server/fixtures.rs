@@ -0,0 +1,270 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Test Server Fixtures
+//!
+//! Routes that seed deterministic state for reproducible E2E and screenshot
+//! tests:
+//!
+//! - `POST /fixtures/contacts` `{"count": N, "seed": S}` — N fake contacts
+//! - `POST /fixtures/labels` `{"names": [...], "assign": true}` — labels,
+//!   with contacts spread round-robin across them
+//! - `POST /fixtures/pending-updates` `{"count": N}` — queued outbound updates
+//! - `POST /fixtures/clock` `{"timestamp": T}` — pin app timestamps (`null`
+//!   restores the real clock)
+//!
+//! The same seed always yields the same contact IDs, names and fields.
+
+use serde::Serialize;
+use serde_json::Value;
+use vauchi_core::{
+    Contact, ContactCard, ContactField, FieldType, PendingUpdate, SymmetricKey, UpdateStatus,
+};
+
+use crate::error::CommandError;
+use crate::state::AppState;
+
+/// Upper bound on generated items per request.
+const MAX_FIXTURE_COUNT: u64 = 5000;
+
+const FIRST_NAMES: &[&str] = &[
+    "Alice", "Bruno", "Chiara", "Dmitri", "Elena", "Farid", "Greta", "Hiro", "Ines", "Jonas",
+    "Kemal", "Lena", "Mateo", "Nadia", "Oskar", "Priya",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Anders", "Brunner", "Costa", "Dubois", "Eriksen", "Fischer", "Garcia", "Huber", "Ivanova",
+    "Jensen", "Keller", "Lopez", "Meier", "Novak", "Olsen", "Petrov", "Rossi",
+];
+
+/// A seeded contact, returned so tests can address it by ID.
+#[derive(Serialize)]
+pub struct FixtureContact {
+    pub id: String,
+    pub display_name: String,
+}
+
+/// Deterministic 32-byte value derived from a seed, index and domain tag.
+fn derive_bytes(seed: u64, index: u64, tag: u8) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    bytes[8..16].copy_from_slice(&index.to_le_bytes());
+    bytes[16] = tag;
+    // Spread the low bits so keys don't share long zero runs
+    for i in 17..32 {
+        bytes[i] = bytes[i - 17]
+            .wrapping_mul(31)
+            .wrapping_add(i as u8)
+            .wrapping_add(tag);
+    }
+    bytes
+}
+
+/// Name for the contact at `index`.
+fn fixture_name(seed: u64, index: u64) -> String {
+    let n = seed.wrapping_add(index) as usize;
+    let first = FIRST_NAMES[n % FIRST_NAMES.len()];
+    let last = LAST_NAMES[(n / FIRST_NAMES.len() + index as usize) % LAST_NAMES.len()];
+    format!("{} {}", first, last)
+}
+
+fn count_arg(body: &Value) -> Result<u64, CommandError> {
+    let count = body["count"]
+        .as_u64()
+        .ok_or_else(|| CommandError::Validation("Missing number field 'count'".to_string()))?;
+    if count > MAX_FIXTURE_COUNT {
+        return Err(CommandError::Validation(format!(
+            "count must be at most {}",
+            MAX_FIXTURE_COUNT
+        )));
+    }
+    Ok(count)
+}
+
+/// Create `count` deterministic contacts with email and phone fields.
+pub fn seed_contacts(state: &AppState, body: &Value) -> Result<Vec<FixtureContact>, CommandError> {
+    let count = count_arg(body)?;
+    let seed = body["seed"].as_u64().unwrap_or(0);
+
+    let mut created = Vec::new();
+    for index in 0..count {
+        let name = fixture_name(seed, index);
+        let mut card = ContactCard::new(&name);
+        let handle = name.to_lowercase().replace(' ', ".");
+        card.add_field(ContactField::new(
+            FieldType::Email,
+            "email",
+            &format!("{}@example.com", handle),
+        ))
+        .map_err(|e| CommandError::Card(e.to_string()))?;
+        card.add_field(ContactField::new(
+            FieldType::Phone,
+            "mobile",
+            &format!("+1 555 {:04}", index % 10_000),
+        ))
+        .map_err(|e| CommandError::Card(e.to_string()))?;
+
+        let contact = Contact::from_exchange(
+            derive_bytes(seed, index, 0x01),
+            card,
+            SymmetricKey::from_bytes(derive_bytes(seed, index, 0x02)),
+        );
+        state.save_contact(&contact)?;
+        created.push(FixtureContact {
+            id: contact.id().to_string(),
+            display_name: name,
+        });
+    }
+    Ok(created)
+}
+
+/// Create labels and optionally spread existing contacts across them.
+pub fn seed_labels(state: &AppState, body: &Value) -> Result<Vec<String>, CommandError> {
+    let names: Vec<String> = body["names"]
+        .as_array()
+        .ok_or_else(|| CommandError::Validation("Missing array field 'names'".to_string()))?
+        .iter()
+        .filter_map(|n| n.as_str().map(|s| s.to_string()))
+        .collect();
+    let assign = body["assign"].as_bool().unwrap_or(true);
+
+    let mut label_ids = Vec::new();
+    for name in &names {
+        let label = state
+            .storage
+            .create_label(name)
+            .map_err(|e| CommandError::Storage(format!("Failed to create label: {:?}", e)))?;
+        label_ids.push(label.id().to_string());
+    }
+
+    if assign && !label_ids.is_empty() {
+        let mut contacts = state.cached_contacts()?;
+        // Storage order is not guaranteed; sort so assignment is stable
+        contacts.sort_by(|a, b| a.id().cmp(b.id()));
+        for (i, contact) in contacts.iter().enumerate() {
+            let label_id = &label_ids[i % label_ids.len()];
+            state
+                .storage
+                .add_contact_to_label(label_id, contact.id())
+                .map_err(|e| CommandError::Storage(format!("Failed to add contact: {:?}", e)))?;
+        }
+    }
+
+    Ok(label_ids)
+}
+
+/// Queue `count` pending updates, spread round-robin across contacts.
+pub fn seed_pending_updates(state: &AppState, body: &Value) -> Result<Vec<String>, CommandError> {
+    let count = count_arg(body)?;
+    let update_type = body["update_type"].as_str().unwrap_or("card_delta");
+
+    let mut contacts = state.cached_contacts()?;
+    if contacts.is_empty() {
+        return Err(CommandError::Validation(
+            "Seed contacts before pending updates".to_string(),
+        ));
+    }
+    contacts.sort_by(|a, b| a.id().cmp(b.id()));
+
+    let now = crate::clock::now_secs();
+    let mut ids = Vec::new();
+    for index in 0..count {
+        let contact = &contacts[index as usize % contacts.len()];
+        let update = PendingUpdate {
+            id: format!("fixture-update-{:05}", index),
+            contact_id: contact.id().to_string(),
+            update_type: update_type.to_string(),
+            payload: derive_bytes(0, index, 0x03).to_vec(),
+            created_at: now,
+            retry_count: 0,
+            status: UpdateStatus::Pending,
+        };
+        state
+            .storage
+            .queue_update(&update)
+            .map_err(|e| CommandError::Storage(e.to_string()))?;
+        ids.push(update.id);
+    }
+    Ok(ids)
+}
+
+/// Pin or release the app clock. Returns the timestamp now in effect.
+pub fn set_clock(body: &Value) -> Result<u64, CommandError> {
+    match &body["timestamp"] {
+        Value::Null => crate::clock::set_fixed(None),
+        value => {
+            let timestamp = value.as_u64().filter(|t| *t > 0).ok_or_else(|| {
+                CommandError::Validation("timestamp must be a positive integer".to_string())
+            })?;
+            crate::clock::set_fixed(Some(timestamp));
+        }
+    }
+    Ok(crate::clock::now_secs())
+}
+
+// INLINE_TEST_REQUIRED: tests seed a temp AppState through crate-private fixtures
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_state() -> (AppState, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let mut state = AppState::new(temp.path()).unwrap();
+        state.create_identity("Tester").unwrap();
+        (state, temp)
+    }
+
+    #[test]
+    fn test_same_seed_yields_same_contacts() {
+        let (a, _ta) = test_state();
+        let (b, _tb) = test_state();
+        let body = serde_json::json!({"count": 5, "seed": 42});
+
+        let first: Vec<String> = seed_contacts(&a, &body)
+            .unwrap()
+            .into_iter()
+            .map(|c| format!("{}:{}", c.id, c.display_name))
+            .collect();
+        let second: Vec<String> = seed_contacts(&b, &body)
+            .unwrap()
+            .into_iter()
+            .map(|c| format!("{}:{}", c.id, c.display_name))
+            .collect();
+
+        assert_eq!(first, second);
+        assert_eq!(a.cached_contacts().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_count_is_bounded() {
+        let (state, _temp) = test_state();
+        let body = serde_json::json!({"count": MAX_FIXTURE_COUNT + 1});
+        assert!(seed_contacts(&state, &body).is_err());
+    }
+
+    #[test]
+    fn test_labels_are_assigned_round_robin() {
+        let (state, _temp) = test_state();
+        seed_contacts(&state, &serde_json::json!({"count": 4})).unwrap();
+
+        let ids = seed_labels(&state, &serde_json::json!({"names": ["Work", "Family"]})).unwrap();
+
+        assert_eq!(ids.len(), 2);
+        for id in &ids {
+            let label = state.storage.load_label(id).unwrap();
+            assert_eq!(label.contact_count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_pending_updates_require_contacts() {
+        let (state, _temp) = test_state();
+        assert!(seed_pending_updates(&state, &serde_json::json!({"count": 1})).is_err());
+
+        seed_contacts(&state, &serde_json::json!({"count": 2})).unwrap();
+        let ids = seed_pending_updates(&state, &serde_json::json!({"count": 3})).unwrap();
+        assert_eq!(ids.len(), 3);
+    }
+}
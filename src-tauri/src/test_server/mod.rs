@@ -6,17 +6,35 @@
 //!
 //! A simple HTTP server for E2E testing that exposes Tauri commands via REST API.
 //! Only enabled when VAUCHI_TEST_PORT environment variable is set.
+//!
+//! Requests operate on the same managed `AppState` as the Tauri commands,
+//! so in-memory flows (pending exchange sessions, device links) started
+//! through the UI are visible to the test server and vice versa.
+//!
+//! Identity, card, contacts and sync routes live here; the remaining
+//! domains are routed in `routes`. `GET /events` upgrades to a WebSocket
+//! that streams internal events (sync progress, contacts added, devices
+//! linked) as JSON, so tests can wait on them instead of polling.
 
 use std::io::{BufRead, BufReader, Read as IoRead, Write};
 use std::net::{TcpListener, TcpStream};
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 use std::thread;
 
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::{Message, WebSocket};
+
 use crate::state::AppState;
 
+mod fixtures;
+mod routes;
+
 /// Start the test HTTP server on the specified port.
 /// Returns the actual port being used.
-pub fn start_test_server(state: Arc<Mutex<AppState>>, port: u16) -> std::io::Result<u16> {
+pub fn start_test_server(app: AppHandle, port: u16) -> std::io::Result<u16> {
     let listener = TcpListener::bind(format!("127.0.0.1:{}", port))?;
     let actual_port = listener.local_addr()?.port();
 
@@ -26,15 +44,16 @@ pub fn start_test_server(state: Arc<Mutex<AppState>>, port: u16) -> std::io::Res
         for stream in listener.incoming() {
             match stream {
                 Ok(stream) => {
-                    let state = Arc::clone(&state);
+                    let app = app.clone();
                     thread::spawn(move || {
-                        if let Err(e) = handle_connection(stream, state) {
-                            eprintln!("Test server error: {}", e);
+                        let state = app.state::<Mutex<AppState>>();
+                        if let Err(e) = handle_connection(stream, state.inner()) {
+                            tracing::error!("Test server error: {}", e);
                         }
                     });
                 }
                 Err(e) => {
-                    eprintln!("Test server connection error: {}", e);
+                    tracing::error!("Test server connection error: {}", e);
                 }
             }
         }
@@ -43,7 +62,7 @@ pub fn start_test_server(state: Arc<Mutex<AppState>>, port: u16) -> std::io::Res
     Ok(actual_port)
 }
 
-fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<AppState>>) -> std::io::Result<()> {
+fn handle_connection(mut stream: TcpStream, state: &Mutex<AppState>) -> std::io::Result<()> {
     let mut buf_reader = BufReader::new(&stream);
     let mut request_line = String::new();
     buf_reader.read_line(&mut request_line)?;
@@ -58,6 +77,7 @@ fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<AppState>>) -> std:
 
     // Read headers
     let mut content_length = 0usize;
+    let mut websocket_key = None;
     loop {
         let mut line = String::new();
         buf_reader.read_line(&mut line)?;
@@ -69,6 +89,14 @@ fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<AppState>>) -> std:
                 content_length = len.trim().parse().unwrap_or(0);
             }
         }
+        if line.to_lowercase().starts_with("sec-websocket-key:") {
+            websocket_key = line.split(':').nth(1).map(|k| k.trim().to_string());
+        }
+    }
+
+    if let ("GET", "/events", Some(key)) = (method, path, websocket_key) {
+        drop(buf_reader);
+        return stream_events(stream, &key);
     }
 
     // Read body for POST requests
@@ -188,12 +216,44 @@ fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<AppState>>) -> std:
             }
         }
 
-        _ => (404, r#"{"error":"Not Found"}"#.to_string()),
+        _ => routes::route(method, path, &body, state)
+            .unwrap_or_else(|| (404, r#"{"error":"Not Found"}"#.to_string())),
     };
 
     send_json_response(&mut stream, status, &response_body)
 }
 
+/// Upgrade to a WebSocket and forward every internal event as a JSON text
+/// frame until the client disconnects.
+fn stream_events(mut stream: TcpStream, key: &str) -> std::io::Result<()> {
+    // Subscribe before the handshake completes so no event is missed
+    let mut events = crate::events::subscribe();
+
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    stream.write_all(response.as_bytes())?;
+
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+    loop {
+        let event = match events.blocking_recv() {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Test server event stream skipped {} events", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        let json = serde_json::to_string(&event).unwrap_or_default();
+        if socket.send(Message::Text(json)).is_err() {
+            // Client went away
+            break;
+        }
+    }
+    Ok(())
+}
+
 fn send_response(stream: &mut TcpStream, status: u16, message: &str) -> std::io::Result<()> {
     let response = format!(
         "HTTP/1.1 {} {}\r\nContent-Length: 0\r\n\r\n",
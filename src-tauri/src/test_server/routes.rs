@@ -0,0 +1,281 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Test Server Routes
+//!
+//! REST routes for exchange, devices, labels, visibility, validation, GDPR,
+//! auth and recovery, so E2E tests can drive the whole app without the
+//! webview. Routes call the same `*_impl` functions as the commands, so
+//! behavior and JSON match what the frontend receives over IPC; errors are
+//! serialized `CommandError`s (`{"kind": ..., "message": ...}`).
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::commands::visibility::VisibilityLevel;
+use crate::commands::{auth, devices, exchange, gdpr, labels, recovery, validation, visibility};
+use crate::error::CommandError;
+use crate::state::AppState;
+
+use super::fixtures;
+
+/// HTTP status and JSON body.
+pub type Response = (u16, String);
+
+/// Route a request to a domain handler. Returns `None` if no route matches.
+pub fn route(method: &str, path: &str, body: &str, state: &Mutex<AppState>) -> Option<Response> {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let body: Value = if body.trim().is_empty() {
+        Value::Null
+    } else {
+        match serde_json::from_str(body) {
+            Ok(v) => v,
+            Err(e) => {
+                return Some(respond::<()>(Err(CommandError::Validation(format!(
+                    "Invalid JSON: {}",
+                    e
+                )))))
+            }
+        }
+    };
+
+    let mut state = state.lock().unwrap();
+    let state = &mut *state;
+
+    let result = match (method, segments.as_slice()) {
+        // Exchange
+        ("POST", ["exchange", "start"]) => respond(exchange::start_exchange_impl(state)),
+        ("POST", ["exchange", "scan"]) => respond(
+            str_arg(&body, "data").and_then(|d| exchange::process_scanned_qr_impl(state, &d)),
+        ),
+        ("POST", ["exchange", "confirm"]) => respond(exchange::confirm_peer_scan_impl(state)),
+        ("POST", ["exchange", "complete"]) => respond(exchange::complete_exchange_impl(state)),
+
+        // Devices
+        ("GET", ["devices"]) => respond(devices::list_devices_impl(state)),
+        ("POST", ["devices", "link"]) => respond(devices::generate_device_link_impl(state)),
+        ("DELETE", ["devices", device_id]) => {
+            respond(devices::revoke_device_impl(state, device_id))
+        }
+
+        // Labels
+        ("GET", ["labels"]) => respond(labels::list_labels_impl(state)),
+        ("POST", ["labels"]) => {
+            respond(str_arg(&body, "name").and_then(|n| labels::create_label_impl(state, &n)))
+        }
+        ("GET", ["labels", id]) => respond(labels::get_label_impl(state, id)),
+        ("PUT", ["labels", id]) => {
+            respond(str_arg(&body, "name").and_then(|n| labels::rename_label_impl(state, id, &n)))
+        }
+        ("DELETE", ["labels", id]) => respond(labels::delete_label_impl(state, id)),
+        ("POST", ["labels", id, "contacts", contact_id]) => {
+            respond(labels::add_contact_to_label_impl(state, id, contact_id))
+        }
+        ("DELETE", ["labels", id, "contacts", contact_id]) => respond(
+            labels::remove_contact_from_label_impl(state, id, contact_id),
+        ),
+        ("PUT", ["labels", id, "fields", field_id]) => {
+            respond(bool_arg(&body, "visible").and_then(|visible| {
+                labels::set_label_field_visibility_impl(state, id, field_id, visible)
+            }))
+        }
+
+        // Visibility
+        ("GET", ["contacts", id, "visibility"]) => {
+            respond(visibility::get_visibility_rules_impl(state, id))
+        }
+        ("PUT", ["contacts", id, "visibility", field_id]) => respond(
+            serde_json::from_value::<VisibilityLevel>(body["visibility"].clone())
+                .map_err(|e| CommandError::Validation(format!("Invalid visibility: {}", e)))
+                .and_then(|v| visibility::set_field_visibility_impl(state, id, field_id, v)),
+        ),
+        ("GET", ["fields", field_id, "viewers"]) => {
+            respond(visibility::get_field_viewers_impl(state, field_id))
+        }
+
+        // Validation
+        ("POST", ["contacts", id, "validations"]) => respond(
+            str_arg(&body, "field_id")
+                .and_then(|f| Ok((f, str_arg(&body, "field_value")?)))
+                .and_then(|(f, v)| validation::validate_contact_field_impl(state, id, &f, &v)),
+        ),
+        ("GET", ["contacts", id, "validations", field_id]) => respond(
+            state
+                .storage
+                .load_validations_for_field(id, field_id)
+                .map(|vs| {
+                    vs.iter()
+                        .map(validation::validation_info)
+                        .collect::<Vec<_>>()
+                })
+                .map_err(|e| CommandError::Storage(e.to_string())),
+        ),
+        ("DELETE", ["contacts", id, "validations", field_id]) => respond(
+            validation::revoke_field_validation_impl(state, id, field_id),
+        ),
+        ("GET", ["validations", "mine"]) => respond(validation::list_my_validations_impl(state)),
+
+        // GDPR
+        ("GET", ["gdpr", "export"]) => respond(gdpr::export_gdpr_data_impl(state)),
+        ("GET", ["gdpr", "deletion"]) => respond(gdpr::get_deletion_state_impl(state)),
+        ("POST", ["gdpr", "deletion"]) => respond(gdpr::schedule_account_deletion_impl(state)),
+        ("DELETE", ["gdpr", "deletion"]) => respond(gdpr::cancel_account_deletion_impl(state)),
+        ("GET", ["gdpr", "consent"]) => respond(gdpr::get_consent_records_impl(state)),
+        ("POST", ["gdpr", "consent", consent_type]) => {
+            respond(gdpr::grant_consent_impl(state, consent_type))
+        }
+        ("DELETE", ["gdpr", "consent", consent_type]) => {
+            respond(gdpr::revoke_consent_impl(state, consent_type))
+        }
+
+        // Auth
+        ("GET", ["auth", "mode"]) => respond(Ok(auth::get_auth_mode_impl(state))),
+        ("POST", ["auth", "password"]) => respond(
+            str_arg(&body, "password").and_then(|p| auth::setup_app_password_impl(state, &p)),
+        ),
+        ("POST", ["auth", "authenticate"]) => {
+            respond(str_arg(&body, "pin").and_then(|p| auth::authenticate_impl(state, &p)))
+        }
+        ("GET", ["auth", "duress"]) => respond(auth::get_duress_status_impl(state)),
+        ("POST", ["auth", "duress"]) => {
+            respond(str_arg(&body, "pin").and_then(|p| auth::setup_duress_pin_impl(state, &p)))
+        }
+        ("DELETE", ["auth", "duress"]) => respond(auth::disable_duress_impl(state)),
+
+        // Recovery
+        ("GET", ["recovery", "settings"]) => respond(Ok(recovery::recovery_settings_info(state))),
+        ("POST", ["recovery", "claim"]) => respond(
+            str_arg(&body, "old_pk")
+                .and_then(|pk| recovery::create_recovery_claim_impl(state, &pk)),
+        ),
+        ("POST", ["recovery", "claim", "parse"]) => {
+            respond(str_arg(&body, "claim").and_then(|c| recovery::claim_info(state, &c)))
+        }
+        ("POST", ["recovery", "voucher"]) => respond(
+            str_arg(&body, "claim").and_then(|c| recovery::create_recovery_voucher_impl(state, &c)),
+        ),
+
+        // Deterministic fixtures
+        ("POST", ["fixtures", "contacts"]) => respond(fixtures::seed_contacts(state, &body)),
+        ("POST", ["fixtures", "labels"]) => respond(fixtures::seed_labels(state, &body)),
+        ("POST", ["fixtures", "pending-updates"]) => {
+            respond(fixtures::seed_pending_updates(state, &body))
+        }
+        ("POST", ["fixtures", "clock"]) => respond(fixtures::set_clock(&body)),
+
+        _ => return None,
+    };
+
+    Some(result)
+}
+
+/// Serialize a command result, mapping validation errors to 400.
+fn respond<T: Serialize>(result: Result<T, CommandError>) -> Response {
+    match result {
+        Ok(value) => (
+            200,
+            serde_json::to_string(&value).unwrap_or_else(|_| "null".to_string()),
+        ),
+        Err(e) => {
+            let status = if matches!(e, CommandError::Validation(_)) {
+                400
+            } else {
+                500
+            };
+            (
+                status,
+                serde_json::to_string(&e).unwrap_or_else(|_| "{}".to_string()),
+            )
+        }
+    }
+}
+
+fn str_arg(body: &Value, key: &str) -> Result<String, CommandError> {
+    body[key]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| CommandError::Validation(format!("Missing string field '{}'", key)))
+}
+
+fn bool_arg(body: &Value, key: &str) -> Result<bool, CommandError> {
+    body[key]
+        .as_bool()
+        .ok_or_else(|| CommandError::Validation(format!("Missing boolean field '{}'", key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_state() -> (Mutex<AppState>, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let mut state = AppState::new(temp.path()).unwrap();
+        state.create_identity("Tester").unwrap();
+        (Mutex::new(state), temp)
+    }
+
+    #[test]
+    fn test_unknown_route_is_none() {
+        let (state, _temp) = test_state();
+        assert!(route("GET", "/nope", "", &state).is_none());
+    }
+
+    #[test]
+    fn test_label_crud() {
+        let (state, _temp) = test_state();
+
+        let (status, body) = route("POST", "/labels", r#"{"name":"Family"}"#, &state).unwrap();
+        assert_eq!(status, 200);
+        let created: Value = serde_json::from_str(&body).unwrap();
+        let id = created["id"].as_str().unwrap().to_string();
+
+        let (status, body) = route("GET", "/labels", "", &state).unwrap();
+        assert_eq!(status, 200);
+        assert!(body.contains("Family"));
+
+        let (status, _) = route("DELETE", &format!("/labels/{}", id), "", &state).unwrap();
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn test_missing_argument_is_bad_request() {
+        let (state, _temp) = test_state();
+        let (status, body) = route("POST", "/labels", "{}", &state).unwrap();
+        assert_eq!(status, 400);
+        assert!(body.contains("Validation"));
+    }
+
+    #[test]
+    fn test_exchange_start_returns_qr_data() {
+        let (state, _temp) = test_state();
+        let (status, body) = route("POST", "/exchange/start", "", &state).unwrap();
+        assert_eq!(status, 200);
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert!(!json["data"].as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_devices_lists_current_device() {
+        let (state, _temp) = test_state();
+        let (status, body) = route("GET", "/devices", "", &state).unwrap();
+        assert_eq!(status, 200);
+        let devices: Vec<Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0]["is_current"], true);
+    }
+
+    #[test]
+    fn test_auth_mode_and_duress_status() {
+        let (state, _temp) = test_state();
+        let (status, _) = route("GET", "/auth/mode", "", &state).unwrap();
+        assert_eq!(status, 200);
+        let (status, body) = route("GET", "/auth/duress", "", &state).unwrap();
+        assert_eq!(status, 200);
+        assert!(body.contains("\"password_enabled\":false"));
+    }
+}
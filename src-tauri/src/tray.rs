@@ -3,26 +3,84 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 //! System tray icon setup and event handling.
+//!
+//! The tray follows the internal event bus: the icon gets a coloured dot
+//! while syncing or after a failed sync, and the tooltip/title show how
+//! many contacts have unseen changes (see `unread`).
+
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::Mutex;
 
 use tauri::{
     image::Image,
-    menu::{Menu, MenuItem},
+    menu::{Menu, MenuItem, PredefinedMenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Manager,
+    AppHandle, Emitter, Manager,
 };
+use tokio::sync::broadcast::error::RecvError;
+use vauchi_core::i18n::Locale;
+use vauchi_core::AuthMode;
+
+use crate::commands::i18n::{localized_plural, parse_locale};
+use crate::events::{self, AppEvent};
+use crate::state::AppState;
+
+/// Tray icon ID.
+const TRAY_ID: &str = "main";
+
+/// Event emitted to the frontend for tray quick actions (`show_qr`, `lock`).
+pub const TRAY_ACTION_EVENT: &str = "tray-action";
+
+/// Base tray icon.
+const TRAY_ICON: &[u8] = include_bytes!("../icons/tray-icon.png");
+
+/// Badge colour while syncing (RGBA).
+const SYNCING_BADGE: [u8; 4] = [0x3b, 0x82, 0xf6, 0xff];
+
+/// Badge colour after a failed sync (RGBA).
+const ERROR_BADGE: [u8; 4] = [0xef, 0x44, 0x44, 0xff];
+
+/// Sync state shown on the tray icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum TrayStatus {
+    Idle = 0,
+    Syncing = 1,
+    Error = 2,
+}
+
+static STATUS: AtomicU8 = AtomicU8::new(TrayStatus::Idle as u8);
+
+/// Contacts with unseen changes.
+static UNREAD: AtomicU32 = AtomicU32::new(0);
 
 /// Set up the system tray icon with context menu.
 ///
-/// Creates a tray icon with a "Show Vauchi" / "Quit" context menu.
+/// Creates a tray icon with "Show Vauchi", "Sync now", "Show my QR",
+/// "Lock" and "Quit" entries.
 /// Left-click toggles window visibility, right-click opens the menu.
 pub fn setup(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let show = MenuItem::with_id(app, "show", "Show Vauchi", true, None::<&str>)?;
+    let sync = MenuItem::with_id(app, "sync", "Sync now", true, None::<&str>)?;
+    let show_qr = MenuItem::with_id(app, "show_qr", "Show my QR", true, None::<&str>)?;
+    let lock = MenuItem::with_id(app, "lock", "Lock", true, None::<&str>)?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show, &quit])?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show,
+            &PredefinedMenuItem::separator(app)?,
+            &sync,
+            &show_qr,
+            &lock,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
 
-    let icon = Image::from_bytes(include_bytes!("../icons/tray-icon.png"))?;
+    let icon = Image::from_bytes(TRAY_ICON)?;
 
-    TrayIconBuilder::new()
+    TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon)
         .icon_as_template(true) // macOS: auto dark/light mode adaptation
         .tooltip("Vauchi")
@@ -30,6 +88,12 @@ pub fn setup(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         .show_menu_on_left_click(false) // left click toggles window, right click opens menu
         .on_menu_event(|app, event| match event.id.as_ref() {
             "show" => toggle_window(app),
+            "sync" => sync_now(app),
+            "show_qr" => {
+                show_window(app);
+                emit_action(app, "show_qr");
+            }
+            "lock" => lock(app),
             "quit" => app.exit(0),
             _ => {}
         })
@@ -45,6 +109,16 @@ pub fn setup(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         })
         .build(app)?;
 
+    let unread = {
+        let state = app.state::<Mutex<AppState>>();
+        let state = state.lock().unwrap();
+        crate::unread::total(state.data_dir())
+    };
+    UNREAD.store(unread, Ordering::Relaxed);
+    refresh(app);
+
+    start_status_listener(app.clone());
+
     Ok(())
 }
 
@@ -53,9 +127,192 @@ fn toggle_window(app: &AppHandle) {
         if window.is_visible().unwrap_or(false) {
             let _ = window.hide();
         } else {
-            let _ = window.unminimize();
-            let _ = window.show();
-            let _ = window.set_focus();
+            show_window(app);
+        }
+    }
+}
+
+/// Show, restore and focus the main window.
+pub fn show_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn emit_action(app: &AppHandle, action: &str) {
+    if let Err(e) = app.emit(TRAY_ACTION_EVENT, action) {
+        tracing::warn!("Failed to emit tray action: {}", e);
+    }
+}
+
+/// Run a sync in the background; progress is reflected via the event bus.
+fn sync_now(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::commands::sync::sync(app.state::<Mutex<AppState>>()).await {
+            tracing::warn!("Tray sync failed: {}", e);
+        }
+    });
+}
+
+/// Drop back to the password prompt. Only applies when an app password is set.
+fn lock(app: &AppHandle) {
+    let locked = {
+        let state = app.state::<Mutex<AppState>>();
+        let mut state = state.lock().unwrap();
+        match state.storage.load_password_config() {
+            Ok(Some(_)) => {
+                state.auth_mode = AuthMode::Unauthenticated;
+                true
+            }
+            _ => false,
+        }
+    };
+    if locked {
+        emit_action(app, "lock");
+    }
+}
+
+/// Follow sync and contact events to update the icon and unread count.
+fn start_status_listener(app: AppHandle) {
+    let mut rx = events::subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            match event {
+                AppEvent::SyncStarted => set_status(TrayStatus::Syncing),
+                AppEvent::SyncCompleted { success, .. } => set_status(if success {
+                    TrayStatus::Idle
+                } else {
+                    TrayStatus::Error
+                }),
+                AppEvent::UnreadChanged { total } => UNREAD.store(total, Ordering::Relaxed),
+                _ => continue,
+            }
+            refresh(&app);
+        }
+    });
+}
+
+fn set_status(status: TrayStatus) {
+    STATUS.store(status as u8, Ordering::Relaxed);
+}
+
+fn status() -> TrayStatus {
+    match STATUS.load(Ordering::Relaxed) {
+        1 => TrayStatus::Syncing,
+        2 => TrayStatus::Error,
+        _ => TrayStatus::Idle,
+    }
+}
+
+/// Tooltip text for a status and unread count.
+fn tooltip(status: TrayStatus, unread: u32, locale: Locale) -> String {
+    match (status, unread) {
+        (TrayStatus::Syncing, _) => "Vauchi — syncing…".to_string(),
+        (TrayStatus::Error, _) => "Vauchi — sync failed".to_string(),
+        (TrayStatus::Idle, 0) => "Vauchi".to_string(),
+        (TrayStatus::Idle, n) => localized_plural(locale, "tray.updated_contacts", n as u64),
+    }
+}
+
+/// Apply the current status and unread count to the tray icon.
+fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let status = status();
+    let unread = UNREAD.load(Ordering::Relaxed);
+
+    let badge = match status {
+        TrayStatus::Idle => None,
+        TrayStatus::Syncing => Some(SYNCING_BADGE),
+        TrayStatus::Error => Some(ERROR_BADGE),
+    };
+    let icon = match badge {
+        Some(color) => Image::from_bytes(TRAY_ICON).map(|base| badged(&base, color)),
+        None => Image::from_bytes(TRAY_ICON),
+    };
+    match icon {
+        Ok(icon) => {
+            let _ = tray.set_icon(Some(icon));
+            // Template icons are drawn monochrome on macOS, hiding the badge
+            let _ = tray.set_icon_as_template(badge.is_none());
         }
+        Err(e) => tracing::warn!("Failed to load tray icon: {}", e),
+    }
+
+    let locale = app
+        .try_state::<Mutex<AppState>>()
+        .map_or(Locale::English, |state| {
+            parse_locale(state.lock().unwrap().locale_code())
+        });
+    let _ = tray.set_tooltip(Some(tooltip(status, unread, locale)));
+    let title = (unread > 0).then(|| unread.to_string());
+    let _ = tray.set_title(title);
+}
+
+/// Copy of `base` with a filled dot in the bottom-right corner.
+fn badged(base: &Image<'_>, color: [u8; 4]) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    paint_dot(&mut rgba, width, height, color);
+    Image::new_owned(rgba, width, height)
+}
+
+/// Paint a dot of radius ~1/4 of the icon size into an RGBA buffer.
+fn paint_dot(rgba: &mut [u8], width: u32, height: u32, color: [u8; 4]) {
+    let radius = (width.min(height) as f32) / 4.0;
+    let cx = width as f32 - radius;
+    let cy = height as f32 - radius;
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private tooltip and badge helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tooltip_reflects_status_and_unread() {
+        let en = Locale::English;
+        assert_eq!(tooltip(TrayStatus::Idle, 0, en), "Vauchi");
+        assert_eq!(
+            tooltip(TrayStatus::Idle, 1, en),
+            "Vauchi — 1 updated contact"
+        );
+        assert_eq!(
+            tooltip(TrayStatus::Idle, 4, en),
+            "Vauchi — 4 updated contacts"
+        );
+        assert_eq!(tooltip(TrayStatus::Error, 4, en), "Vauchi — sync failed");
+    }
+
+    #[test]
+    fn test_paint_dot_colors_bottom_right_only() {
+        let (w, h) = (16u32, 16u32);
+        let mut rgba = vec![0u8; (w * h * 4) as usize];
+        paint_dot(&mut rgba, w, h, ERROR_BADGE);
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * w + x) * 4) as usize;
+            [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+        };
+        assert_eq!(pixel(13, 13), ERROR_BADGE);
+        assert_eq!(pixel(0, 0), [0, 0, 0, 0]);
     }
 }
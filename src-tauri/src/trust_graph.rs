@@ -0,0 +1,282 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Web-of-Trust Graph
+//!
+//! Nodes are the user and their contacts. Edges are field validations in
+//! either direction, recovery vouching (contacts trusted for recovery) and
+//! fingerprint verification, which both sides perform in person.
+//!
+//! Each contact's node and edges are cached in `AppState` and recomputed
+//! only when that contact or its validations change; a sync or a change of
+//! the user's own card fields drops the whole cache.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use serde::Serialize;
+use vauchi_core::{Contact, Storage};
+
+/// A person in the graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrustNode {
+    /// Contact ID, or the user's public ID for the self node.
+    pub id: String,
+    pub display_name: String,
+    pub is_self: bool,
+    pub verified: bool,
+    pub recovery_trusted: bool,
+    pub blocked: bool,
+}
+
+/// Kind of a trust relationship.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustEdgeKind {
+    /// `from` validated fields of `to`; the weight is the field count.
+    Validation,
+    /// `from` trusts `to` to vouch for account recovery.
+    Vouch,
+    /// Fingerprints were verified in person; undirected.
+    MutualVerification,
+}
+
+/// A relationship between two nodes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrustEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: TrustEdgeKind,
+    pub weight: usize,
+}
+
+/// The user's web of trust.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TrustGraph {
+    pub nodes: Vec<TrustNode>,
+    pub edges: Vec<TrustEdge>,
+}
+
+/// Who the graph is built for.
+pub struct GraphOwner<'a> {
+    /// Public ID, used as the self node ID and as the contact ID of our own
+    /// validated fields.
+    pub public_id: &'a str,
+    /// Hex signing key, the validator ID of our validations.
+    pub validator_id: &'a str,
+    pub display_name: &'a str,
+    /// IDs of the fields on our own card.
+    pub own_field_ids: &'a [String],
+}
+
+/// One contact's share of the graph.
+#[derive(Debug, Clone)]
+struct ContactSlice {
+    node: TrustNode,
+    edges: Vec<TrustEdge>,
+}
+
+/// Per-contact cache of graph slices.
+#[derive(Default)]
+pub struct TrustGraphCache {
+    slices: RefCell<HashMap<String, ContactSlice>>,
+    /// Own field IDs the cached slices were computed with.
+    own_field_ids: RefCell<Vec<String>>,
+}
+
+impl TrustGraphCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop a contact's slice after it or its validations changed.
+    pub fn invalidate(&self, contact_id: &str) {
+        self.slices.borrow_mut().remove(contact_id);
+    }
+
+    /// Drop everything.
+    pub fn clear(&self) {
+        self.slices.borrow_mut().clear();
+    }
+
+    /// Number of cached contact slices.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.slices.borrow().len()
+    }
+
+    /// Build the graph, recomputing only contacts without a cached slice.
+    pub fn graph(&self, storage: &Storage, owner: &GraphOwner, contacts: &[Contact]) -> TrustGraph {
+        if *self.own_field_ids.borrow() != owner.own_field_ids {
+            self.clear();
+            *self.own_field_ids.borrow_mut() = owner.own_field_ids.to_vec();
+        }
+
+        let mut graph = TrustGraph {
+            nodes: vec![TrustNode {
+                id: owner.public_id.to_string(),
+                display_name: owner.display_name.to_string(),
+                is_self: true,
+                verified: true,
+                recovery_trusted: false,
+                blocked: false,
+            }],
+            edges: Vec::new(),
+        };
+
+        let mut slices = self.slices.borrow_mut();
+        // Forget contacts that no longer exist
+        slices.retain(|id, _| contacts.iter().any(|c| c.id() == id));
+        for contact in contacts {
+            let slice = slices
+                .entry(contact.id().to_string())
+                .or_insert_with(|| compute_slice(storage, owner, contact));
+            graph.nodes.push(slice.node.clone());
+            graph.edges.extend(slice.edges.iter().cloned());
+        }
+        graph
+    }
+}
+
+/// Compute a contact's node and its edges to the user.
+fn compute_slice(storage: &Storage, owner: &GraphOwner, contact: &Contact) -> ContactSlice {
+    let contact_id = contact.id().to_string();
+    let self_id = owner.public_id.to_string();
+    let mut edges = Vec::new();
+
+    // Our validations of the contact's fields
+    let validated_by_us = contact
+        .card()
+        .fields()
+        .iter()
+        .filter(|f| {
+            storage
+                .load_validations_for_field(&contact_id, f.id())
+                .is_ok_and(|vs| vs.iter().any(|v| v.validator_id() == owner.validator_id))
+        })
+        .count();
+    if validated_by_us > 0 {
+        edges.push(TrustEdge {
+            from: self_id.clone(),
+            to: contact_id.clone(),
+            kind: TrustEdgeKind::Validation,
+            weight: validated_by_us,
+        });
+    }
+
+    // The contact's validations of our fields
+    let contact_key = hex::encode(contact.public_key());
+    let validated_by_contact = owner
+        .own_field_ids
+        .iter()
+        .filter(|field_id| {
+            storage
+                .load_validations_for_field(owner.public_id, field_id)
+                .is_ok_and(|vs| vs.iter().any(|v| v.validator_id() == contact_key))
+        })
+        .count();
+    if validated_by_contact > 0 {
+        edges.push(TrustEdge {
+            from: contact_id.clone(),
+            to: self_id.clone(),
+            kind: TrustEdgeKind::Validation,
+            weight: validated_by_contact,
+        });
+    }
+
+    if contact.is_recovery_trusted() {
+        edges.push(TrustEdge {
+            from: self_id.clone(),
+            to: contact_id.clone(),
+            kind: TrustEdgeKind::Vouch,
+            weight: 1,
+        });
+    }
+
+    if contact.is_fingerprint_verified() {
+        edges.push(TrustEdge {
+            from: self_id,
+            to: contact_id.clone(),
+            kind: TrustEdgeKind::MutualVerification,
+            weight: 1,
+        });
+    }
+
+    ContactSlice {
+        node: TrustNode {
+            id: contact_id,
+            display_name: contact.display_name().to_string(),
+            is_self: false,
+            verified: contact.is_fingerprint_verified(),
+            recovery_trusted: contact.is_recovery_trusted(),
+            blocked: contact.is_blocked(),
+        },
+        edges,
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests inspect the crate-private slice cache
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use vauchi_core::{
+        ContactCard, ContactField, FieldType, Identity, ProfileValidation, SymmetricKey,
+    };
+
+    use crate::state::AppState;
+
+    fn contact(key_byte: u8, name: &str) -> Contact {
+        let mut card = ContactCard::new(name);
+        card.add_field(ContactField::new(
+            FieldType::Email,
+            "email",
+            "x@example.com",
+        ))
+        .unwrap();
+        Contact::from_exchange([key_byte; 32], card, SymmetricKey::generate())
+    }
+
+    #[test]
+    fn test_graph_has_validation_edges_and_caches_slices() {
+        let temp = TempDir::new().unwrap();
+        let state = AppState::new(temp.path()).unwrap();
+        let me = Identity::create("Me");
+        let validator_id = hex::encode(me.signing_public_key());
+        let bob = contact(1, "Bob");
+        let field_id = bob.card().fields()[0].id().to_string();
+
+        let validation =
+            ProfileValidation::create_signed(&me, &field_id, "x@example.com", bob.id());
+        state.storage.save_validation(&validation).unwrap();
+
+        let owner = GraphOwner {
+            public_id: "me",
+            validator_id: &validator_id,
+            display_name: "Me",
+            own_field_ids: &[],
+        };
+        let cache = TrustGraphCache::new();
+        let graph = cache.graph(&state.storage, &owner, std::slice::from_ref(&bob));
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert!(graph.nodes[0].is_self);
+        assert_eq!(
+            graph.edges,
+            vec![TrustEdge {
+                from: "me".to_string(),
+                to: bob.id().to_string(),
+                kind: TrustEdgeKind::Validation,
+                weight: 1,
+            }]
+        );
+        assert_eq!(cache.len(), 1);
+
+        // Removed contacts drop out of the cache
+        let graph = cache.graph(&state.storage, &owner, &[]);
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(cache.len(), 0);
+    }
+}
@@ -0,0 +1,119 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Unread Contact Changes
+//!
+//! Remembers which contacts changed their card since the user last looked
+//! at them. Sync marks contacts whose card updates it applied; opening a
+//! contact marks it seen. Stored in `unread.json` in the data dir and used
+//! for contact list badges and the tray counter.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+
+/// Unread state file name under the data dir.
+const UNREAD_FILE: &str = "unread.json";
+
+/// Unseen changes for one contact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnreadEntry {
+    /// Number of updates applied since the contact was last seen.
+    pub count: u32,
+    /// Unix timestamp of the latest update.
+    pub last_changed: u64,
+}
+
+fn unread_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(UNREAD_FILE)
+}
+
+/// Unread entries keyed by contact ID.
+pub fn load(data_dir: &Path) -> BTreeMap<String, UnreadEntry> {
+    std::fs::read_to_string(unread_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(data_dir: &Path, unread: &BTreeMap<String, UnreadEntry>) -> std::io::Result<()> {
+    if unread.is_empty() {
+        return match std::fs::remove_file(unread_path(data_dir)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    std::fs::write(unread_path(data_dir), serde_json::to_string(unread)?)
+}
+
+/// Record an applied update for each contact ID.
+pub fn mark_changed(data_dir: &Path, contact_ids: &[String]) -> std::io::Result<()> {
+    if contact_ids.is_empty() {
+        return Ok(());
+    }
+    let now = clock::now_secs();
+    let mut unread = load(data_dir);
+    for id in contact_ids {
+        let entry = unread.entry(id.clone()).or_insert(UnreadEntry {
+            count: 0,
+            last_changed: now,
+        });
+        entry.count += 1;
+        entry.last_changed = now;
+    }
+    save(data_dir, &unread)
+}
+
+/// Clear unread state for a contact. Returns whether it had any.
+pub fn mark_seen(data_dir: &Path, contact_id: &str) -> std::io::Result<bool> {
+    let mut unread = load(data_dir);
+    let had = unread.remove(contact_id).is_some();
+    if had {
+        save(data_dir, &unread)?;
+    }
+    Ok(had)
+}
+
+/// Drop a contact's unread state (e.g. after it was removed).
+pub fn forget(data_dir: &Path, contact_id: &str) {
+    let _ = mark_seen(data_dir, contact_id);
+}
+
+/// Number of contacts with unseen changes.
+pub fn total(data_dir: &Path) -> u32 {
+    load(data_dir).len() as u32
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private unread persistence
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mark_changed_counts_per_contact() {
+        let temp = TempDir::new().unwrap();
+        mark_changed(temp.path(), &["a".to_string(), "b".to_string()]).unwrap();
+        mark_changed(temp.path(), &["a".to_string()]).unwrap();
+
+        let unread = load(temp.path());
+        assert_eq!(unread["a"].count, 2);
+        assert_eq!(unread["b"].count, 1);
+        assert_eq!(total(temp.path()), 2);
+    }
+
+    #[test]
+    fn test_mark_seen_clears_contact() {
+        let temp = TempDir::new().unwrap();
+        mark_changed(temp.path(), &["a".to_string()]).unwrap();
+
+        assert!(mark_seen(temp.path(), "a").unwrap());
+        assert!(!mark_seen(temp.path(), "a").unwrap());
+        assert_eq!(total(temp.path()), 0);
+        assert!(!unread_path(temp.path()).exists());
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Validation Freshness
+//!
+//! A validation vouches for a value at one point in time. Once it is older
+//! than the configured number of months it is stale: it still shows in the
+//! validation count, but only every second stale validation counts towards
+//! the trust level. Validators can re-sign stale validations to refresh them.
+//!
+//! Stored in `validation_freshness.json` in the data dir.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use vauchi_core::ProfileValidation;
+
+use crate::settings_file;
+
+/// Settings file for the freshness cut-off.
+const SETTINGS_FILE: &str = "validation_freshness.json";
+
+/// Default age in months after which a validation is stale.
+const DEFAULT_MAX_AGE_MONTHS: u32 = 12;
+
+/// Length of a month for freshness purposes.
+const SECONDS_PER_MONTH: u64 = 30 * 24 * 60 * 60;
+
+/// Validation freshness preferences.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationFreshness {
+    /// Months after which a validation is stale.
+    pub max_age_months: u32,
+}
+
+impl Default for ValidationFreshness {
+    fn default() -> Self {
+        ValidationFreshness {
+            max_age_months: DEFAULT_MAX_AGE_MONTHS,
+        }
+    }
+}
+
+impl ValidationFreshness {
+    /// Whether a validation made at `validated_at` is stale at `now`.
+    pub fn is_stale(&self, validated_at: u64, now: u64) -> bool {
+        now.saturating_sub(validated_at) > u64::from(self.max_age_months) * SECONDS_PER_MONTH
+    }
+}
+
+/// The freshness cut-off chosen by the user.
+pub fn load(data_dir: &Path) -> ValidationFreshness {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Change the freshness cut-off.
+pub fn save(data_dir: &Path, settings: &ValidationFreshness) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, settings)
+}
+
+/// Reduce validations to the ones counting towards the trust level: all
+/// fresh ones and half of the stale ones (rounded down). Returns the number
+/// of stale validations in the input.
+pub fn weigh_for_trust(
+    validations: &mut Vec<ProfileValidation>,
+    settings: &ValidationFreshness,
+    now: u64,
+) -> usize {
+    // Fresh first; the sort is stable so the order is otherwise kept
+    validations.sort_by_key(|v| settings.is_stale(v.validated_at(), now));
+    let stale = validations
+        .iter()
+        .filter(|v| settings.is_stale(v.validated_at(), now))
+        .count();
+    let fresh = validations.len() - stale;
+    validations.truncate(fresh + stale / 2);
+    stale
+}
+
+// INLINE_TEST_REQUIRED: tests use the private default age and month length
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_stale_after_max_age() {
+        let settings = ValidationFreshness { max_age_months: 1 };
+        let now = 10 * SECONDS_PER_MONTH;
+        assert!(!settings.is_stale(now - SECONDS_PER_MONTH, now));
+        assert!(settings.is_stale(now - SECONDS_PER_MONTH - 1, now));
+        // Clock skew into the future is not stale
+        assert!(!settings.is_stale(now + 100, now));
+    }
+
+    #[test]
+    fn test_settings_roundtrip_and_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load(temp.path()).max_age_months, DEFAULT_MAX_AGE_MONTHS);
+        save(temp.path(), &ValidationFreshness { max_age_months: 6 }).unwrap();
+        assert_eq!(load(temp.path()).max_age_months, 6);
+    }
+}
@@ -0,0 +1,803 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Validation Sync
+//!
+//! Carries signed field validations over the relay so trust levels
+//! aggregate across devices of different people, not just locally.
+//!
+//! Outbound: during sync, validations we made that were not shared yet are
+//! batched per validated contact, encrypted with that contact's ratchet and
+//! queued as a regular pending update (indistinguishable from a card
+//! update on the wire). Shared validations are remembered in
+//! `validations_shared.json`.
+//!
+//! Validation requests ("please vouch for my email") use the same channel
+//! and are queued right away by `queue_request`; received requests wait in
+//! `validation_requests.json` until the user accepts or declines them.
+//!
+//! Revoking a validation queues a revocation the same way. The recipient
+//! deletes the validation and remembers the revocation in
+//! `validations_revoked.json`, so an older copy arriving later is ignored.
+//!
+//! Like validations, requests and revocations are signed with the sender's
+//! identity key, over the message and the recipient's ID. Unsigned ones,
+//! or ones signed for someone else, are dropped on receipt.
+//!
+//! Inbound: `ratchet_messages` decrypts incoming updates and hands
+//! validation messages to [`receive`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use vauchi_core::{Contact, Identity, ProfileValidation, Storage, SymmetricKey};
+
+use crate::clock;
+use crate::ratchet_messages;
+use crate::storage_worker;
+
+/// Shared validation keys file name under the data dir.
+const SHARED_FILE: &str = "validations_shared.json";
+
+/// Incoming validation requests file name under the data dir.
+const REQUESTS_FILE: &str = "validation_requests.json";
+
+/// Received revocations file name under the data dir.
+const REVOKED_FILE: &str = "validations_revoked.json";
+
+/// Plaintext of a validation update, tagged so it cannot be mistaken for a
+/// card delta.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", deny_unknown_fields)]
+pub(crate) enum ValidationMessage {
+    /// Validations the sender made of the recipient's fields.
+    #[serde(rename = "vauchi.validations.v1")]
+    Validations { validations: Vec<ProfileValidation> },
+    /// The sender asks the recipient to validate one of the sender's fields.
+    #[serde(rename = "vauchi.validation-request.v1")]
+    Request {
+        request_id: String,
+        field_id: String,
+        field_label: String,
+        field_value: String,
+        requested_at: u64,
+        #[serde(default)]
+        signature: String,
+    },
+    /// The sender withdraws their validation of the recipient's field.
+    #[serde(rename = "vauchi.validation-revocation.v1")]
+    Revocation {
+        field_id: String,
+        revoked_at: u64,
+        #[serde(default)]
+        signature: String,
+    },
+}
+
+impl ValidationMessage {
+    /// What the signature of a request or revocation covers: its kind, the
+    /// recipient and its fields. `None` for validations, which are signed
+    /// one by one.
+    fn signed_bytes(&self, recipient_id: &str) -> Option<Vec<u8>> {
+        let signed = match self {
+            ValidationMessage::Validations { .. } => return None,
+            ValidationMessage::Request {
+                request_id,
+                field_id,
+                field_label,
+                field_value,
+                requested_at,
+                ..
+            } => serde_json::json!([
+                "vauchi.validation-request.v1",
+                recipient_id,
+                request_id,
+                field_id,
+                field_label,
+                field_value,
+                requested_at
+            ]),
+            ValidationMessage::Revocation {
+                field_id,
+                revoked_at,
+                ..
+            } => serde_json::json!([
+                "vauchi.validation-revocation.v1",
+                recipient_id,
+                field_id,
+                revoked_at
+            ]),
+        };
+        Some(signed.to_string().into_bytes())
+    }
+
+    /// Sign a request or revocation for `recipient_id`.
+    fn sign(mut self, identity: &Identity, recipient_id: &str) -> Self {
+        let Some(bytes) = self.signed_bytes(recipient_id) else {
+            return self;
+        };
+        let value = URL_SAFE_NO_PAD.encode(identity.signing_keypair().sign(&bytes).as_bytes());
+        match &mut self {
+            ValidationMessage::Request { signature, .. }
+            | ValidationMessage::Revocation { signature, .. } => *signature = value,
+            ValidationMessage::Validations { .. } => {}
+        }
+        self
+    }
+
+    /// Whether a request or revocation was signed by `sender_key` for
+    /// `recipient_id`. Validations are checked one by one instead.
+    fn is_signed_by(&self, sender_key: &[u8; 32], recipient_id: &str) -> bool {
+        let signature = match self {
+            ValidationMessage::Validations { .. } => return true,
+            ValidationMessage::Request { signature, .. }
+            | ValidationMessage::Revocation { signature, .. } => signature,
+        };
+        let bytes = self.signed_bytes(recipient_id).unwrap_or_default();
+        URL_SAFE_NO_PAD.decode(signature).is_ok_and(|signature| {
+            UnparsedPublicKey::new(&ED25519, sender_key)
+                .verify(&bytes, &signature)
+                .is_ok()
+        })
+    }
+}
+
+/// A received request to validate a contact's field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncomingValidationRequest {
+    pub request_id: String,
+    pub contact_id: String,
+    pub field_id: String,
+    pub field_label: String,
+    pub field_value: String,
+    pub requested_at: u64,
+    pub received_at: u64,
+}
+
+fn shared_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SHARED_FILE)
+}
+
+/// Stable key of a validation for the shared set.
+fn validation_key(validation: &ProfileValidation) -> String {
+    format!(
+        "{}:{}:{}",
+        validation.field_id(),
+        validation.validator_id(),
+        validation.validated_at()
+    )
+}
+
+fn load_shared(data_dir: &Path) -> BTreeSet<String> {
+    std::fs::read_to_string(shared_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_shared(data_dir: &Path, shared: &BTreeSet<String>) -> std::io::Result<()> {
+    std::fs::write(shared_path(data_dir), serde_json::to_string(shared)?)
+}
+
+/// Group not-yet-shared validations by the contact they are about.
+fn unshared_by_contact(
+    validations: Vec<ProfileValidation>,
+    shared: &BTreeSet<String>,
+) -> BTreeMap<String, Vec<ProfileValidation>> {
+    let mut grouped: BTreeMap<String, Vec<ProfileValidation>> = BTreeMap::new();
+    for validation in validations {
+        if shared.contains(&validation_key(&validation)) {
+            continue;
+        }
+        if let Some(contact_id) = validation.contact_id().map(str::to_string) {
+            grouped.entry(contact_id).or_default().push(validation);
+        }
+    }
+    grouped
+}
+
+/// Queue our unshared validations as encrypted pending updates, one per
+/// validated contact. Returns the number of validations queued.
+///
+/// Contacts that are blocked or have no ratchet session are retried on a
+/// later sync. Validations count as shared once the transaction commits.
+pub fn queue_outbound(
+    identity: &Identity,
+    storage: &Storage,
+    data_dir: &Path,
+) -> Result<usize, String> {
+    let my_id = hex::encode(identity.signing_public_key());
+    let mine = storage
+        .load_validations_by_validator(&my_id)
+        .map_err(|e| e.to_string())?;
+    let shared = load_shared(data_dir);
+    let mut newly_shared = Vec::new();
+
+    for (contact_id, validations) in unshared_by_contact(mine, &shared) {
+        match storage.load_contact(&contact_id) {
+            Ok(Some(contact)) if !contact.is_blocked() => {}
+            _ => continue,
+        }
+        let keys: Vec<String> = validations.iter().map(validation_key).collect();
+        let message = ValidationMessage::Validations { validations };
+        if queue_message(storage, &contact_id, &message).is_ok() {
+            newly_shared.extend(keys);
+        }
+    }
+
+    let queued = newly_shared.len();
+    if queued > 0 {
+        // Only shared once the pending updates are committed
+        let data_dir = data_dir.to_path_buf();
+        storage_worker::after_commit(move || {
+            let mut shared = load_shared(&data_dir);
+            shared.extend(newly_shared);
+            if let Err(e) = save_shared(&data_dir, &shared) {
+                tracing::warn!("Failed to record shared validations: {}", e);
+            }
+        });
+    }
+    Ok(queued)
+}
+
+/// Encrypt a message with the contact's ratchet and queue it for the next sync.
+fn queue_message(
+    storage: &Storage,
+    contact_id: &str,
+    message: &ValidationMessage,
+) -> Result<(), String> {
+    let plaintext = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+    ratchet_messages::queue(storage, contact_id, &plaintext)?
+        .map(|_| ())
+        .ok_or_else(|| "No secure session with this contact yet".to_string())
+}
+
+/// Queue a signed request asking a contact to validate one of our card
+/// fields. Returns the request ID.
+pub fn queue_request(
+    identity: &Identity,
+    storage: &Storage,
+    contact_id: &str,
+    field_id: &str,
+    field_label: &str,
+    field_value: &str,
+) -> Result<String, String> {
+    let request_id = hex::encode(&SymmetricKey::generate().as_bytes()[..16]);
+    let message = ValidationMessage::Request {
+        request_id: request_id.clone(),
+        field_id: field_id.to_string(),
+        field_label: field_label.to_string(),
+        field_value: field_value.to_string(),
+        requested_at: clock::now_secs(),
+        signature: String::new(),
+    }
+    .sign(identity, contact_id);
+    queue_message(storage, contact_id, &message)?;
+    Ok(request_id)
+}
+
+/// Queue a signed revocation of our validation of a contact's field.
+pub fn queue_revocation(
+    identity: &Identity,
+    storage: &Storage,
+    contact_id: &str,
+    field_id: &str,
+) -> Result<(), String> {
+    let message = ValidationMessage::Revocation {
+        field_id: field_id.to_string(),
+        revoked_at: clock::now_secs(),
+        signature: String::new(),
+    }
+    .sign(identity, contact_id);
+    queue_message(storage, contact_id, &message)
+}
+
+fn revoked_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(REVOKED_FILE)
+}
+
+/// Key of a received revocation: who revoked which of our fields.
+fn revocation_key(validator_id: &str, field_id: &str) -> String {
+    format!("{}:{}", validator_id, field_id)
+}
+
+fn load_revoked(data_dir: &Path) -> BTreeMap<String, u64> {
+    std::fs::read_to_string(revoked_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_revoked(data_dir: &Path, revoked: &BTreeMap<String, u64>) -> std::io::Result<()> {
+    std::fs::write(revoked_path(data_dir), serde_json::to_string(revoked)?)
+}
+
+/// Record a revocation in `revoked`, keeping the latest time per key.
+fn merge_revocation(revoked: &mut BTreeMap<String, u64>, key: String, revoked_at: u64) {
+    let entry = revoked.entry(key).or_insert(revoked_at);
+    *entry = (*entry).max(revoked_at);
+}
+
+/// Revocations known while handling one batch of incoming messages: the
+/// saved ones plus those received in the batch.
+pub(crate) struct Revocations {
+    known: BTreeMap<String, u64>,
+    received: BTreeMap<String, u64>,
+}
+
+impl Revocations {
+    pub(crate) fn load(data_dir: &Path) -> Self {
+        Self {
+            known: load_revoked(data_dir),
+            received: BTreeMap::new(),
+        }
+    }
+
+    fn record(&mut self, key: String, revoked_at: u64) {
+        merge_revocation(&mut self.known, key.clone(), revoked_at);
+        merge_revocation(&mut self.received, key, revoked_at);
+    }
+
+    /// Save the batch's revocations once the transaction commits, merged
+    /// into the file as it is then.
+    pub(crate) fn save_after_commit(self, data_dir: &Path) {
+        if self.received.is_empty() {
+            return;
+        }
+        let data_dir = data_dir.to_path_buf();
+        let received = self.received;
+        storage_worker::after_commit(move || {
+            let mut revoked = load_revoked(&data_dir);
+            for (key, revoked_at) in received {
+                merge_revocation(&mut revoked, key, revoked_at);
+            }
+            if let Err(e) = save_revoked(&data_dir, &revoked) {
+                tracing::warn!("Failed to store validation revocations: {}", e);
+            }
+        });
+    }
+}
+
+/// Apply a received revocation: remember it and delete the validation it
+/// covers. A validation made after the revocation is kept.
+fn apply_revocation(
+    storage: &Storage,
+    revocations: &mut Revocations,
+    our_id: &str,
+    validator_id: &str,
+    field_id: &str,
+    revoked_at: u64,
+) {
+    revocations.record(revocation_key(validator_id, field_id), revoked_at);
+
+    let covered = storage
+        .load_validations_for_field(our_id, field_id)
+        .map(|existing| {
+            existing
+                .iter()
+                .any(|v| v.validator_id() == validator_id && v.validated_at() <= revoked_at)
+        })
+        .unwrap_or(false);
+    if covered {
+        if let Err(e) = storage.delete_validation(our_id, field_id, validator_id) {
+            tracing::warn!("Failed to delete revoked validation: {}", e);
+        }
+    }
+}
+
+/// Decode a decrypted update as a validation message.
+pub(crate) fn decode_message(plaintext: &[u8]) -> Option<ValidationMessage> {
+    serde_json::from_slice(plaintext).ok()
+}
+
+fn requests_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(REQUESTS_FILE)
+}
+
+/// Pending incoming validation requests, oldest first.
+pub fn load_requests(data_dir: &Path) -> Vec<IncomingValidationRequest> {
+    std::fs::read_to_string(requests_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_requests(data_dir: &Path, requests: &[IncomingValidationRequest]) -> std::io::Result<()> {
+    std::fs::write(requests_path(data_dir), serde_json::to_string(requests)?)
+}
+
+/// Add a received request. A newer request for the same contact field
+/// replaces the older one.
+fn add_request(data_dir: &Path, request: IncomingValidationRequest) -> std::io::Result<()> {
+    let mut requests = load_requests(data_dir);
+    requests.retain(|r| !(r.contact_id == request.contact_id && r.field_id == request.field_id));
+    requests.push(request);
+    save_requests(data_dir, &requests)
+}
+
+/// Remove and return a pending request.
+pub fn take_request(
+    data_dir: &Path,
+    request_id: &str,
+) -> std::io::Result<Option<IncomingValidationRequest>> {
+    let mut requests = load_requests(data_dir);
+    let Some(index) = requests.iter().position(|r| r.request_id == request_id) else {
+        return Ok(None);
+    };
+    let request = requests.remove(index);
+    save_requests(data_dir, &requests)?;
+    Ok(Some(request))
+}
+
+/// Whether a received validation may be stored: made and signed by the
+/// sending contact, about us, not revoked, and newer than any we have from
+/// that contact (an older one is removed).
+fn accept_inbound(
+    storage: &Storage,
+    revocations: &Revocations,
+    validation: &ProfileValidation,
+    sender_key: &[u8; 32],
+    our_id: &str,
+) -> bool {
+    let sender_key_hex = hex::encode(sender_key);
+    if validation.validator_id() != sender_key_hex
+        || validation.contact_id() != Some(our_id)
+        || !validation.verify(sender_key)
+    {
+        return false;
+    }
+    let Some(field) = validation.field_name() else {
+        return false;
+    };
+    if revocations
+        .known
+        .get(&revocation_key(&sender_key_hex, field))
+        .is_some_and(|&revoked_at| validation.validated_at() <= revoked_at)
+    {
+        return false;
+    }
+    let existing = match storage.load_validations_for_field(our_id, field) {
+        Ok(existing) => existing,
+        Err(_) => return false,
+    };
+    match existing
+        .iter()
+        .find(|v| v.validator_id() == validation.validator_id())
+    {
+        None => true,
+        // A refreshed validation replaces the older one
+        Some(previous) if previous.validated_at() < validation.validated_at() => storage
+            .delete_validation(our_id, field, validation.validator_id())
+            .is_ok(),
+        Some(_) => false,
+    }
+}
+
+/// Handle a validation message from `contact`: store received validations
+/// and requests, and apply revocations. Returns the number of validations
+/// stored.
+///
+/// `revocations` carries revocations across the messages of one batch;
+/// save it with [`Revocations::save_after_commit`] after the batch.
+pub(crate) fn receive(
+    storage: &Storage,
+    data_dir: &Path,
+    revocations: &mut Revocations,
+    our_id: &str,
+    contact: &Contact,
+    message: ValidationMessage,
+) -> usize {
+    if !message.is_signed_by(contact.public_key(), our_id) {
+        tracing::warn!("Dropped an unsigned validation message");
+        return 0;
+    }
+    let sender_key_hex = hex::encode(contact.public_key());
+    let validations = match message {
+        ValidationMessage::Validations { validations } => validations,
+        ValidationMessage::Revocation {
+            field_id,
+            revoked_at,
+            ..
+        } => {
+            apply_revocation(
+                storage,
+                revocations,
+                our_id,
+                &sender_key_hex,
+                &field_id,
+                revoked_at,
+            );
+            return 0;
+        }
+        ValidationMessage::Request {
+            request_id,
+            field_id,
+            field_label,
+            field_value,
+            requested_at,
+            ..
+        } => {
+            let request = IncomingValidationRequest {
+                request_id,
+                contact_id: contact.id().to_string(),
+                field_id,
+                field_label,
+                field_value,
+                requested_at,
+                received_at: clock::now_secs(),
+            };
+            let data_dir = data_dir.to_path_buf();
+            storage_worker::after_commit(move || {
+                if let Err(e) = add_request(&data_dir, request) {
+                    tracing::warn!("Failed to store validation request: {}", e);
+                }
+            });
+            return 0;
+        }
+    };
+
+    validations
+        .iter()
+        .filter(|validation| {
+            accept_inbound(
+                storage,
+                revocations,
+                validation,
+                contact.public_key(),
+                our_id,
+            ) && storage.save_validation(validation).is_ok()
+        })
+        .count()
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private batching and decoding
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn validation(identity: &Identity, field: &str, contact: &str) -> ProfileValidation {
+        ProfileValidation::create_signed(identity, field, "value", contact)
+    }
+
+    #[test]
+    fn test_unshared_grouped_by_contact_and_shared_skipped() {
+        let identity = Identity::create("Tester");
+        let a1 = validation(&identity, "email", "contact-a");
+        let a2 = validation(&identity, "phone", "contact-a");
+        let b1 = validation(&identity, "email", "contact-b");
+        let shared: BTreeSet<String> = [validation_key(&a2)].into_iter().collect();
+
+        let grouped = unshared_by_contact(vec![a1, a2, b1], &shared);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped["contact-a"].len(), 1);
+        assert_eq!(grouped["contact-b"].len(), 1);
+    }
+
+    #[test]
+    fn test_shared_set_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_shared(temp.path()).is_empty());
+        let shared: BTreeSet<String> = ["k1".to_string()].into_iter().collect();
+        save_shared(temp.path(), &shared).unwrap();
+        assert_eq!(load_shared(temp.path()), shared);
+    }
+
+    #[test]
+    fn test_decode_message_requires_known_kind() {
+        let identity = Identity::create("Tester");
+        let message = ValidationMessage::Validations {
+            validations: vec![validation(&identity, "email", "contact-a")],
+        };
+        let bytes = serde_json::to_vec(&message).unwrap();
+        assert!(matches!(
+            decode_message(&bytes),
+            Some(ValidationMessage::Validations { validations }) if validations.len() == 1
+        ));
+
+        assert!(decode_message(br#"{"kind":"something-else","validations":[]}"#).is_none());
+        assert!(decode_message(br#"{"fields":[]}"#).is_none());
+    }
+
+    #[test]
+    fn test_requests_and_revocations_must_be_signed_for_us() {
+        let sender = Identity::create("Sender");
+        let key = sender.signing_public_key();
+        let request = ValidationMessage::Request {
+            request_id: "r1".to_string(),
+            field_id: "email".to_string(),
+            field_label: "Email".to_string(),
+            field_value: "a@example.com".to_string(),
+            requested_at: 1,
+            signature: String::new(),
+        };
+        assert!(!request.is_signed_by(key, "us"));
+
+        let signed = request.sign(&sender, "us");
+        assert!(signed.is_signed_by(key, "us"));
+        // Not for another recipient, nor from another key
+        assert!(!signed.is_signed_by(key, "someone-else"));
+        assert!(!signed.is_signed_by(Identity::create("Other").signing_public_key(), "us"));
+
+        // Editing a signed message breaks the signature
+        let mut json = serde_json::to_value(&signed).unwrap();
+        json["field_value"] = serde_json::json!("mallory@example.com");
+        let edited: ValidationMessage = serde_json::from_value(json).unwrap();
+        assert!(!edited.is_signed_by(key, "us"));
+
+        let revocation = ValidationMessage::Revocation {
+            field_id: "email".to_string(),
+            revoked_at: 1,
+            signature: String::new(),
+        }
+        .sign(&sender, "us");
+        assert!(revocation.is_signed_by(key, "us"));
+        assert!(!revocation.is_signed_by(key, "someone-else"));
+    }
+
+    fn request(id: &str, contact: &str, field: &str) -> IncomingValidationRequest {
+        IncomingValidationRequest {
+            request_id: id.to_string(),
+            contact_id: contact.to_string(),
+            field_id: field.to_string(),
+            field_label: "Email".to_string(),
+            field_value: "a@example.com".to_string(),
+            requested_at: 1,
+            received_at: 2,
+        }
+    }
+
+    #[test]
+    fn test_newer_request_for_same_field_replaces_older() {
+        let temp = TempDir::new().unwrap();
+        add_request(temp.path(), request("r1", "alice", "email")).unwrap();
+        add_request(temp.path(), request("r2", "alice", "phone")).unwrap();
+        add_request(temp.path(), request("r3", "alice", "email")).unwrap();
+
+        let ids: Vec<String> = load_requests(temp.path())
+            .into_iter()
+            .map(|r| r.request_id)
+            .collect();
+        assert_eq!(ids, vec!["r2", "r3"]);
+    }
+
+    #[test]
+    fn test_take_request_removes_it() {
+        let temp = TempDir::new().unwrap();
+        add_request(temp.path(), request("r1", "alice", "email")).unwrap();
+        assert!(take_request(temp.path(), "missing").unwrap().is_none());
+        assert_eq!(
+            take_request(temp.path(), "r1").unwrap().unwrap().contact_id,
+            "alice"
+        );
+        assert!(load_requests(temp.path()).is_empty());
+    }
+
+    fn no_revocations() -> Revocations {
+        Revocations {
+            known: BTreeMap::new(),
+            received: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_revocation_deletes_and_blocks_older_copies() {
+        let temp = TempDir::new().unwrap();
+        let state = crate::state::AppState::new(temp.path()).unwrap();
+        let our_id = "our-public-id";
+        let validator = Identity::create("Validator");
+        let validator_id = hex::encode(validator.signing_public_key());
+
+        let v = validation(&validator, "email", our_id);
+        state.storage.save_validation(&v).unwrap();
+
+        let mut revocations = Revocations::load(temp.path());
+        apply_revocation(
+            &state.storage,
+            &mut revocations,
+            our_id,
+            &validator_id,
+            "email",
+            v.validated_at(),
+        );
+        assert_eq!(
+            state
+                .storage
+                .count_validations_for_field(our_id, "email")
+                .unwrap(),
+            0
+        );
+
+        // Later messages of the same batch already see the revocation
+        let validator_key = validator.signing_public_key();
+        assert!(!accept_inbound(
+            &state.storage,
+            &revocations,
+            &v,
+            validator_key,
+            our_id
+        ));
+        assert!(accept_inbound(
+            &state.storage,
+            &no_revocations(),
+            &v,
+            validator_key,
+            our_id
+        ));
+
+        revocations.save_after_commit(temp.path());
+        let saved = Revocations::load(temp.path());
+        assert!(!accept_inbound(
+            &state.storage,
+            &saved,
+            &v,
+            validator_key,
+            our_id
+        ));
+    }
+
+    #[test]
+    fn test_revocations_of_one_batch_are_all_saved() {
+        let temp = TempDir::new().unwrap();
+        let state = crate::state::AppState::new(temp.path()).unwrap();
+        let mut revocations = Revocations::load(temp.path());
+        for field in ["email", "phone"] {
+            apply_revocation(&state.storage, &mut revocations, "us", "v", field, 10);
+        }
+        // Saved meanwhile by another batch
+        save_revoked(
+            temp.path(),
+            &[(revocation_key("w", "email"), 5)].into_iter().collect(),
+        )
+        .unwrap();
+
+        revocations.save_after_commit(temp.path());
+        let revoked = load_revoked(temp.path());
+        assert_eq!(revoked.len(), 3);
+        assert_eq!(revoked[&revocation_key("v", "phone")], 10);
+    }
+
+    #[test]
+    fn test_inbound_requires_the_senders_signature() {
+        let temp = TempDir::new().unwrap();
+        let state = crate::state::AppState::new(temp.path()).unwrap();
+        let our_id = "our-public-id";
+        let sender = Identity::create("Sender");
+        let forger = Identity::create("Forger");
+
+        // Signed by someone else, relayed by the sender
+        let forged = validation(&forger, "email", our_id);
+        assert!(!accept_inbound(
+            &state.storage,
+            &no_revocations(),
+            &forged,
+            sender.signing_public_key(),
+            our_id
+        ));
+
+        // Claims the sender as validator but carries another key's signature
+        let mut json = serde_json::to_value(&forged).unwrap();
+        json["validator_id"] = serde_json::json!(hex::encode(sender.signing_public_key()));
+        let planted: ProfileValidation = serde_json::from_value(json).unwrap();
+        assert!(!accept_inbound(
+            &state.storage,
+            &no_revocations(),
+            &planted,
+            sender.signing_public_key(),
+            our_id
+        ));
+
+        let genuine = validation(&sender, "email", our_id);
+        assert!(accept_inbound(
+            &state.storage,
+            &no_revocations(),
+            &genuine,
+            sender.signing_public_key(),
+            our_id
+        ));
+    }
+}
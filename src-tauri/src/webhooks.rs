@@ -0,0 +1,414 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Outbound Webhooks
+//!
+//! Listens on the internal event bus and POSTs a JSON payload to each
+//! configured webhook subscribed to the event: a contact was added, the own
+//! card was updated, or a sync finished. Each payload is signed with
+//! HMAC-SHA256 using the webhook's secret and sent in the
+//! `X-Vauchi-Signature: sha256=<hex>` header, so scripts can check it came
+//! from Vauchi.
+//!
+//! Only `localhost` URLs are accepted unless the user allows remote
+//! targets. Redirects are not followed, so a target cannot forward a
+//! payload to a host the policy would refuse, and remote targets are
+//! reached through the Tor proxy in Tor mode. Configuration lives in
+//! `webhooks.json` in the data dir, with the secrets in the encrypted
+//! settings.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use url::Url;
+use vauchi_core::SymmetricKey;
+
+use crate::background;
+use crate::clock;
+use crate::commands::app_update;
+use crate::error::CommandError;
+use crate::events::{self, AppEvent};
+use crate::secure_settings::{self, WEBHOOK_SECRETS};
+use crate::state::AppState;
+
+/// Configuration file name under the data dir.
+const CONFIG_FILE: &str = "webhooks.json";
+
+/// How long a webhook target may take to answer.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Header carrying the payload signature.
+pub const SIGNATURE_HEADER: &str = "X-Vauchi-Signature";
+
+/// Events a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    ContactAdded,
+    CardUpdated,
+    SyncFinished,
+}
+
+impl WebhookEvent {
+    /// The webhook event an app event fires, if any.
+    fn from_app_event(event: &AppEvent) -> Option<Self> {
+        match event {
+            AppEvent::ContactAdded { .. } => Some(WebhookEvent::ContactAdded),
+            AppEvent::CardUpdated { .. } => Some(WebhookEvent::CardUpdated),
+            AppEvent::SyncCompleted { .. } => Some(WebhookEvent::SyncFinished),
+            _ => None,
+        }
+    }
+}
+
+/// A configured webhook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// HMAC key for the payload signature. Kept out of `webhooks.json`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub secret: String,
+    pub enabled: bool,
+}
+
+/// All webhooks and whether non-local targets are allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub webhooks: Vec<Webhook>,
+    /// Allow URLs outside `localhost`.
+    pub allow_remote: bool,
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONFIG_FILE)
+}
+
+fn load_file(data_dir: &Path) -> WebhookConfig {
+    std::fs::read_to_string(config_path(data_dir))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Load the configuration, falling back to none if missing. Fails if a
+/// secret cannot be read, rather than leaving it empty.
+pub fn load(data_dir: &Path) -> std::io::Result<WebhookConfig> {
+    let mut config = load_file(data_dir);
+    if config.webhooks.is_empty() {
+        return Ok(config);
+    }
+    let secrets = secure_settings::get(data_dir, &WEBHOOK_SECRETS)
+        .map_err(std::io::Error::other)?
+        .unwrap_or_default();
+    for webhook in &mut config.webhooks {
+        // Older versions kept the secret in the file
+        if webhook.secret.is_empty() {
+            webhook.secret = secrets.get(&webhook.id).cloned().ok_or_else(|| {
+                std::io::Error::other(format!("No secret stored for webhook {}", webhook.id))
+            })?;
+        }
+    }
+    Ok(config)
+}
+
+/// Persist the configuration, with the secrets in the encrypted settings.
+pub fn save(data_dir: &Path, config: &WebhookConfig) -> std::io::Result<()> {
+    if let Some(webhook) = config.webhooks.iter().find(|w| w.secret.is_empty()) {
+        return Err(std::io::Error::other(format!(
+            "Webhook {} has no secret",
+            webhook.id
+        )));
+    }
+    let secrets: BTreeMap<String, String> = config
+        .webhooks
+        .iter()
+        .map(|w| (w.id.clone(), w.secret.clone()))
+        .collect();
+    secure_settings::set(data_dir, &WEBHOOK_SECRETS, Some(&secrets))
+        .map_err(std::io::Error::other)?;
+
+    let mut config = config.clone();
+    for webhook in &mut config.webhooks {
+        webhook.secret.clear();
+    }
+    let json = serde_json::to_string_pretty(&config)?;
+    std::fs::write(config_path(data_dir), json)
+}
+
+/// Move secrets older versions wrote to `webhooks.json` into the
+/// encrypted settings.
+pub fn migrate_secrets(data_dir: &Path) -> std::io::Result<()> {
+    if load_file(data_dir)
+        .webhooks
+        .iter()
+        .all(|w| w.secret.is_empty())
+    {
+        return Ok(());
+    }
+    save(data_dir, &load(data_dir)?)
+}
+
+/// A new random ID or secret.
+pub fn random_token() -> String {
+    hex::encode(&SymmetricKey::generate().as_bytes()[..16])
+}
+
+/// Check a webhook URL: HTTP(S), and on the local machine unless remote
+/// targets are allowed.
+pub fn validate_url(url: &str, allow_remote: bool) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Webhook URLs must use http or https".to_string());
+    }
+    if !is_local(&parsed) && !allow_remote {
+        return Err("Only localhost webhooks are allowed".to_string());
+    }
+    Ok(())
+}
+
+/// Whether `url` points at this machine.
+fn is_local(url: &Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// The proxy to deliver to `url` through, given the app's HTTP proxy
+/// (see [`app_update::http_proxy`]): none for localhost targets, which
+/// Tor cannot reach. Remote targets are refused when that proxy is.
+pub fn delivery_proxy(
+    url: &str,
+    http_proxy: &Result<Option<Url>, CommandError>,
+) -> Result<Option<Url>, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if is_local(&parsed) {
+        return Ok(None);
+    }
+    match http_proxy {
+        Ok(proxy) => Ok(proxy.clone()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The JSON payload for an event.
+pub fn payload(event: WebhookEvent, data: &AppEvent, timestamp: u64) -> String {
+    serde_json::json!({
+        "event": event,
+        "timestamp": timestamp,
+        "data": data,
+    })
+    .to_string()
+}
+
+/// `sha256=<hex>` HMAC of `body` under `secret`.
+pub fn sign(secret: &str, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    format!("sha256={}", hex::encode(hmac::sign(&key, body.as_bytes())))
+}
+
+/// POST a signed payload to a webhook, through `proxy` if set. Redirects
+/// are treated as failures.
+pub async fn deliver(webhook: &Webhook, body: String, proxy: Option<&Url>) -> Result<(), String> {
+    if webhook.secret.is_empty() {
+        return Err("Webhook has no secret".to_string());
+    }
+    let mut builder = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy.as_str()).map_err(|e| e.to_string())?);
+    }
+    let client = builder.build().map_err(|e| e.to_string())?;
+    client
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header(SIGNATURE_HEADER, sign(&webhook.secret, &body))
+        .body(body)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Start sending events to the configured webhooks.
+pub fn start(app: AppHandle, data_dir: PathBuf) {
+    let mut rx = events::subscribe();
+    background::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let Some(kind) = WebhookEvent::from_app_event(&event) else {
+                continue;
+            };
+            // Re-read each time so changed webhooks apply immediately
+            let config = match load(&data_dir) {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::warn!("Failed to load webhooks: {}", e);
+                    continue;
+                }
+            };
+            let body = payload(kind, &event, clock::now_secs());
+            let http_proxy = {
+                let state = app.state::<Mutex<AppState>>();
+                let state = state.lock().unwrap();
+                app_update::http_proxy(&state)
+            };
+            for webhook in config.webhooks {
+                if !webhook.enabled || !webhook.events.contains(&kind) {
+                    continue;
+                }
+                // The URL was checked when added, but the policy may have tightened
+                if validate_url(&webhook.url, config.allow_remote).is_err() {
+                    continue;
+                }
+                let proxy = match delivery_proxy(&webhook.url, &http_proxy) {
+                    Ok(proxy) => proxy,
+                    Err(e) => {
+                        tracing::warn!("Webhook {} skipped: {}", webhook.id, e);
+                        continue;
+                    }
+                };
+                let body = body.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = deliver(&webhook, body, proxy.as_ref()).await {
+                        tracing::warn!("Webhook {} failed: {}", webhook.id, e);
+                    }
+                });
+            }
+        }
+    });
+}
+
+// INLINE_TEST_REQUIRED: tests exercise crate-private URL policy and payload signing
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_only_localhost_by_default() {
+        assert!(validate_url("http://localhost:8123/hook", false).is_ok());
+        assert!(validate_url("http://127.0.0.1/hook", false).is_ok());
+        assert!(validate_url("http://[::1]:9000/", false).is_ok());
+        assert!(validate_url("https://example.com/hook", false).is_err());
+        assert!(validate_url("https://example.com/hook", true).is_ok());
+        assert!(validate_url("ftp://localhost/", true).is_err());
+        assert!(validate_url("not a url", true).is_err());
+    }
+
+    #[test]
+    fn test_only_remote_targets_use_the_proxy() {
+        let tor = Url::parse("socks5h://127.0.0.1:9050").unwrap();
+        let proxy = Ok(Some(tor.clone()));
+        assert_eq!(
+            delivery_proxy("http://localhost:8123/hook", &proxy),
+            Ok(None)
+        );
+        assert_eq!(
+            delivery_proxy("https://example.com/hook", &proxy),
+            Ok(Some(tor))
+        );
+
+        let refused = Err(CommandError::Privacy("Tor".to_string()));
+        assert_eq!(delivery_proxy("http://127.0.0.1/hook", &refused), Ok(None));
+        assert!(delivery_proxy("https://example.com/hook", &refused).is_err());
+    }
+
+    #[test]
+    fn test_signature_is_hmac_sha256_of_body() {
+        let signature = sign("secret", "{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(signature, sign("secret", "{}"));
+        assert_ne!(signature, sign("other", "{}"));
+    }
+
+    #[test]
+    fn test_events_map_and_payload_names_event() {
+        let event = AppEvent::CardUpdated {
+            contacts_notified: 2,
+        };
+        let kind = WebhookEvent::from_app_event(&event).unwrap();
+        assert_eq!(kind, WebhookEvent::CardUpdated);
+        assert_eq!(WebhookEvent::from_app_event(&AppEvent::SyncStarted), None);
+
+        let json: serde_json::Value = serde_json::from_str(&payload(kind, &event, 42)).unwrap();
+        assert_eq!(json["event"], "card_updated");
+        assert_eq!(json["timestamp"], 42);
+        assert_eq!(json["data"]["contacts_notified"], 2);
+    }
+
+    fn data_dir() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        secure_settings::open(temp.path(), &SymmetricKey::generate()).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_config_roundtrip() {
+        let temp = data_dir();
+        assert_eq!(load(temp.path()).unwrap(), WebhookConfig::default());
+        let config = WebhookConfig {
+            webhooks: vec![Webhook {
+                id: "w1".to_string(),
+                url: "http://localhost/hook".to_string(),
+                events: vec![WebhookEvent::SyncFinished],
+                secret: "s".to_string(),
+                enabled: true,
+            }],
+            allow_remote: false,
+        };
+        save(temp.path(), &config).unwrap();
+        assert_eq!(load(temp.path()).unwrap(), config);
+        assert!(load_file(temp.path()).webhooks[0].secret.is_empty());
+
+        // An empty secret never replaces a stored one
+        let mut blank = config.clone();
+        blank.webhooks[0].secret.clear();
+        assert!(save(temp.path(), &blank).is_err());
+        assert_eq!(load(temp.path()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_unreadable_secret_fails_to_load() {
+        let temp = data_dir();
+        std::fs::write(
+            config_path(temp.path()),
+            r#"{"webhooks":[{"id":"w1","url":"http://localhost/hook","events":[],"enabled":true}]}"#,
+        )
+        .unwrap();
+        assert!(load(temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_plaintext_secrets_move_to_encrypted_settings() {
+        let temp = data_dir();
+        std::fs::write(
+            config_path(temp.path()),
+            r#"{"webhooks":[{"id":"w1","url":"http://localhost/hook","events":[],"secret":"s","enabled":true}]}"#,
+        )
+        .unwrap();
+
+        migrate_secrets(temp.path()).unwrap();
+        assert!(load_file(temp.path()).webhooks[0].secret.is_empty());
+        assert_eq!(load(temp.path()).unwrap().webhooks[0].secret, "s");
+    }
+}
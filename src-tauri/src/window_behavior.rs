@@ -0,0 +1,119 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Window Behavior
+//!
+//! What closing and minimizing the main window does: hide to the tray or
+//! quit, and minimize normally or to the tray. Stored in
+//! `window_behavior.json` in the data dir and enforced by the window event
+//! handler in `lib.rs`.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Window, WindowEvent};
+
+use crate::settings_file;
+use crate::state::AppState;
+
+/// Settings file for the close and minimize behavior.
+const SETTINGS_FILE: &str = "window_behavior.json";
+
+/// Close and minimize preferences for the main window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowBehavior {
+    /// Closing the window hides it to the tray instead of quitting.
+    pub close_to_tray: bool,
+    /// Minimizing the window hides it to the tray.
+    pub minimize_to_tray: bool,
+}
+
+impl Default for WindowBehavior {
+    fn default() -> Self {
+        Self {
+            close_to_tray: true,
+            minimize_to_tray: false,
+        }
+    }
+}
+
+/// The close and minimize behavior.
+pub fn load(data_dir: &Path) -> WindowBehavior {
+    settings_file::load(data_dir, SETTINGS_FILE)
+}
+
+/// Change the close and minimize behavior.
+pub fn save(data_dir: &Path, behavior: &WindowBehavior) -> std::io::Result<()> {
+    settings_file::save(data_dir, SETTINGS_FILE, behavior)
+}
+
+/// Apply close/minimize behavior to a main window event.
+pub fn handle_event(window: &Window, event: &WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+    // Defaults until the app state is initialized
+    let behavior = || {
+        window
+            .try_state::<Mutex<AppState>>()
+            .map(|state| load(state.lock().unwrap().data_dir()))
+            .unwrap_or_default()
+    };
+
+    match event {
+        WindowEvent::CloseRequested { api, .. } => {
+            if behavior().close_to_tray {
+                let _ = window.hide();
+                api.prevent_close();
+            } else {
+                // Quit even if secondary windows or the tray would keep us alive
+                window.app_handle().exit(0);
+            }
+        }
+        // There is no dedicated minimize event; a resize reports it
+        WindowEvent::Resized(_) => {
+            if window.is_minimized().unwrap_or(false) && behavior().minimize_to_tray {
+                let _ = window.hide();
+            }
+        }
+        _ => {}
+    }
+}
+
+// INLINE_TEST_REQUIRED: tests write the private settings file name directly
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_defaults_hide_on_close() {
+        let temp = TempDir::new().unwrap();
+        let behavior = load(temp.path());
+        assert!(behavior.close_to_tray);
+        assert!(!behavior.minimize_to_tray);
+    }
+
+    #[test]
+    fn test_roundtrip_and_partial_file() {
+        let temp = TempDir::new().unwrap();
+        let behavior = WindowBehavior {
+            close_to_tray: false,
+            minimize_to_tray: true,
+        };
+        save(temp.path(), &behavior).unwrap();
+        assert_eq!(load(temp.path()), behavior);
+
+        std::fs::write(
+            temp.path().join(SETTINGS_FILE),
+            r#"{"minimize_to_tray":true}"#,
+        )
+        .unwrap();
+        let partial = load(temp.path());
+        assert!(partial.close_to_tray, "Missing keys keep their defaults");
+        assert!(partial.minimize_to_tray);
+    }
+}
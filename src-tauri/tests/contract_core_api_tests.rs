@@ -68,6 +68,67 @@ fn contract_storage_search_contacts() {
     assert!(results.is_empty());
 }
 
+#[test]
+fn contract_storage_loads_validations_for_contact() {
+    // The contact page reads all of a contact's validations in one query
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("test.db");
+    let storage = Storage::open(db_path.to_str().unwrap(), SymmetricKey::generate()).unwrap();
+    let identity = Identity::create("Validator");
+
+    for (field, contact) in [
+        ("email", "contact-1"),
+        ("phone", "contact-1"),
+        ("email", "contact-2"),
+    ] {
+        let validation =
+            vauchi_core::ProfileValidation::create_signed(&identity, field, "value", contact);
+        storage.save_validation(&validation).unwrap();
+    }
+
+    let validations = storage.load_validations_for_contact("contact-1").unwrap();
+    assert_eq!(validations.len(), 2);
+    assert!(validations
+        .iter()
+        .all(|v| v.contact_id() == Some("contact-1")));
+}
+
+#[test]
+fn contract_storage_contact_overrides_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("test.db");
+    let key = SymmetricKey::generate();
+    let storage = Storage::open(db_path.to_str().unwrap(), key).unwrap();
+
+    storage
+        .save_contact_override("contact-1", "email", false)
+        .unwrap();
+    let overrides = storage.load_contact_overrides("contact-1").unwrap();
+    assert_eq!(overrides, vec![("email".to_string(), false)]);
+
+    storage
+        .delete_contact_override("contact-1", "email")
+        .unwrap();
+    assert!(storage
+        .load_contact_overrides("contact-1")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn contract_visibility_rules_iterate_field_ids() {
+    let mut contact =
+        Contact::from_exchange([1; 32], ContactCard::new("Peer"), SymmetricKey::generate());
+    contact.visibility_rules_mut().set_nobody("email");
+
+    let field_ids: Vec<&String> = contact
+        .visibility_rules()
+        .iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(field_ids, vec!["email"]);
+}
+
 // ============================================================
 // Identity contracts
 // ============================================================
@@ -185,6 +246,29 @@ fn contract_contact_accessors_compile() {
     }
 }
 
+#[test]
+fn contract_contact_from_import_has_card() {
+    // Dropped vCards become contacts without a key exchange
+    let mut card = ContactCard::new("Alice");
+    card.add_field(ContactField::new(
+        FieldType::Email,
+        "work",
+        "alice@example.com",
+    ))
+    .unwrap();
+    let contact = Contact::from_import(card);
+    assert_eq!(contact.display_name(), "Alice");
+    assert_eq!(contact.card().fields().len(), 1);
+}
+
+#[test]
+fn contract_device_link_response_can_be_wiped() {
+    // Desktop wraps the decrypted response, which carries the master seed,
+    // in `Zeroizing` — compile-time assertion
+    fn _assert_zeroize<T: zeroize::Zeroize>() {}
+    _assert_zeroize::<vauchi_core::exchange::DeviceLinkResponse>();
+}
+
 // ============================================================
 // Delivery contracts (SP-12b)
 // ============================================================
@@ -322,3 +406,32 @@ fn contract_delivery_create_and_count_record() {
 // NOTE: Desktop exchange code needs updating to match current core API.
 // Exchange contract tests are deferred until desktop/core exchange
 // API alignment is resolved (pre-existing compilation issue).
+
+// ============================================================
+// Content update contracts
+// ============================================================
+
+#[tokio::test]
+async fn contract_content_manager_applies_selected_types() {
+    use vauchi_core::content::{ApplyResult, ContentConfig, ContentManager, ContentType};
+
+    // Desktop downloads only the content types the user allows
+    async fn apply(manager: &ContentManager, types: &[ContentType]) -> bool {
+        matches!(
+            manager.apply_updates_for(types).await,
+            Ok(ApplyResult::Applied { .. })
+        )
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let manager = ContentManager::new(ContentConfig {
+        storage_path: dir.path().to_path_buf(),
+        remote_updates_enabled: false,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(
+        !apply(&manager, &[]).await,
+        "Nothing is applied with remote updates off and no types selected"
+    );
+}
@@ -228,6 +228,35 @@ export function tauriMockScript(): string {
           },
         }),
         get_default_theme_id: () => 'dark-default',
+        get_active_theme: () => state._activeTheme || { theme_id: 'dark-default', follow_system: true },
+        set_active_theme: (args) => {
+          state._activeTheme = args.themeId
+            ? { theme_id: args.themeId, follow_system: false }
+            : { theme_id: 'dark-default', follow_system: true };
+          persistState();
+          return state._activeTheme;
+        },
+
+        // Accessibility
+        get_accessibility_settings: () => state._accessibility || {
+          reduce_motion: false,
+          high_contrast: false,
+          large_touch_targets: false,
+          large_qr_modules: false,
+        },
+        set_accessibility_settings: (args) => {
+          state._accessibility = args.settings;
+          persistState();
+          return null;
+        },
+
+        // Startup: everything is ready right away
+        get_startup_report: () => ({
+          setup_ms: 0,
+          setup_budget_ms: 500,
+          phases: [],
+          ready: ['i18n', 'themes', 'state'],
+        }),
 
         get_locales: () => [
           { code: 'en', name: 'English', english_name: 'English', is_rtl: false },
@@ -333,6 +362,7 @@ export function tauriMockScript(): string {
         // Aha moments
         check_aha_moment_localized: () => null,
         check_aha_moment_with_context: () => null,
+        take_aha_moments: () => [],
 
         // Field validation
         validate_contact_field: () => ({ field_id: 'f-1', validator_name: 'Test', trust_level: 'low_confidence', timestamp: Date.now() }),
@@ -485,6 +515,7 @@ export function tauriMockScript(): string {
         },
         metadata: { currentWindow: { label: 'main' }, currentWebview: { label: 'main' } },
         convertFileSrc: (path) => path,
+        transformCallback: () => 0,
       };
     })();
   `;
@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-import { createSignal, createResource, onMount } from 'solid-js';
+import { createEffect, createSignal, createResource, onCleanup, onMount, Show } from 'solid-js';
 import { invoke } from '@tauri-apps/api/core';
 import { OnboardingWizard } from './pages/onboarding';
 import Lock from './pages/Lock';
@@ -21,7 +21,15 @@ import ContactDuplicates from './pages/ContactDuplicates';
 import ContactMerge from './pages/ContactMerge';
 import ContactSettings from './pages/ContactSettings';
 import { initializeTheme } from './services/themeService';
+import {
+  applyAccessibilitySettings,
+  getAccessibilitySettings,
+  migrateLegacyAccessibilitySettings,
+} from './services/accessibilityService';
 import { initializeLocale } from './services/i18nService';
+import { takeAhaMoments, type AhaMoment } from './services/ahaService';
+import { listenForFileImports } from './services/fileImportService';
+import { waitForStartup } from './services/startupService';
 
 type Page =
   | 'setup'
@@ -61,22 +69,37 @@ async function checkPasswordEnabled(): Promise<boolean> {
 
 function App() {
   const [page, setPage] = createSignal<Page>('home');
-  const [hasIdentity] = createResource(checkIdentity);
-  const [passwordEnabled] = createResource(checkPasswordEnabled);
+  const [started, setStarted] = createSignal(false);
+  const [hasIdentity] = createResource(started, checkIdentity);
+  const [passwordEnabled] = createResource(started, checkPasswordEnabled);
   const [authenticated, setAuthenticated] = createSignal(false);
   const [mergePrimaryId, setMergePrimaryId] = createSignal('');
   const [mergeSecondaryId, setMergeSecondaryId] = createSignal('');
+  const [milestones, setMilestones] = createSignal<AhaMoment[]>([]);
 
   // Apply saved settings on app startup
   onMount(async () => {
-    // Accessibility settings
-    const reduceMotion = localStorage.getItem('a11y-reduce-motion') === 'true';
-    const highContrast = localStorage.getItem('a11y-high-contrast') === 'true';
-    const largeTouchTargets = localStorage.getItem('a11y-large-touch-targets') === 'true';
+    // Files opened with the app are announced as soon as the state is
+    // loaded, so listen before waiting for it
+    const unlistenFileImports = listenForFileImports(() => setPage('contacts'));
+    onCleanup(() => void unlistenFileImports.then((unlisten) => unlisten()));
 
-    document.documentElement.setAttribute('data-reduce-motion', String(reduceMotion));
-    document.documentElement.setAttribute('data-high-contrast', String(highContrast));
-    document.documentElement.setAttribute('data-large-touch-targets', String(largeTouchTargets));
+    // The backend loads the app state and locale files after the window
+    // is shown; commands and strings are usable once they are announced
+    try {
+      await waitForStartup('state');
+      await waitForStartup('i18n');
+    } catch (e) {
+      console.error('Failed to wait for startup:', e);
+    }
+
+    // Accessibility settings
+    try {
+      await migrateLegacyAccessibilitySettings();
+      applyAccessibilitySettings(await getAccessibilitySettings());
+    } catch (e) {
+      console.error('Failed to load accessibility settings:', e);
+    }
 
     // Initialize locale (loads all strings) and theme
     try {
@@ -89,10 +112,34 @@ function App() {
     } catch (e) {
       console.error('Failed to initialize theme:', e);
     }
+    setStarted(true);
+  });
+
+  // Desktop milestones are reached by commands in the backend; pick them up
+  // whenever the user moves to another page and show them one at a time
+  const showNextMilestone = () => {
+    setTimeout(() => {
+      setMilestones((queue) => queue.slice(1));
+      if (milestones().length > 0) showNextMilestone();
+    }, 4000);
+  };
+  createEffect(async () => {
+    page();
+    const unlocked = !passwordEnabled() || authenticated();
+    if (!started() || !hasIdentity() || !unlocked) return;
+    try {
+      const reached = await takeAhaMoments();
+      if (reached.length === 0) return;
+      const idle = milestones().length === 0;
+      setMilestones((queue) => [...queue, ...reached]);
+      if (idle) showNextMilestone();
+    } catch (e) {
+      console.error('Failed to load milestones:', e);
+    }
   });
 
   const currentPage = () => {
-    if (hasIdentity.loading || passwordEnabled.loading)
+    if (!started() || hasIdentity.loading || passwordEnabled.loading)
       return (
         <div class="loading" role="status" aria-live="polite" aria-busy="true">
           Loading...
@@ -157,6 +204,14 @@ function App() {
         Skip to main content
       </a>
       <div id="main-content">{currentPage()}</div>
+      <Show when={milestones()[0]}>
+        {(moment) => (
+          <div class="aha-moment" role="status" aria-live="polite">
+            <h2>{moment().title}</h2>
+            <p>{moment().message}</p>
+          </div>
+        )}
+      </Show>
     </div>
   );
 }
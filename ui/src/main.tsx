@@ -4,7 +4,14 @@
 
 import { render } from 'solid-js/web';
 import App from './App';
+import QrWindow from './pages/QrWindow';
 import './styles/app.css';
 import './styles/onboarding.css';
 
-render(() => <App />, document.getElementById('root')!);
+const params = new URLSearchParams(window.location.search);
+
+render(
+  () =>
+    params.get('window') === 'qr' ? <QrWindow kind={params.get('kind') ?? 'exchange'} /> : <App />,
+  document.getElementById('root')!
+);
@@ -6,6 +6,7 @@ import { createResource, createSignal, For, Show } from 'solid-js';
 import { invoke } from '@tauri-apps/api/core';
 import { t } from '../services/i18nService';
 import QRCanvas from '../components/QRCanvas';
+import { errorMessage } from '../utils/errorMessage';
 
 // --- Domain types ---
 
@@ -192,7 +193,7 @@ function Devices(props: DevicesProps) {
         });
       }
     } catch (e) {
-      setLinkState({ step: 'failed', error: String(e) });
+      setLinkState({ step: 'failed', error: errorMessage(e) });
     }
   };
 
@@ -222,7 +223,7 @@ function Devices(props: DevicesProps) {
       }
       refetch();
     } catch (e) {
-      setLinkState({ step: 'failed', error: String(e) });
+      setLinkState({ step: 'failed', error: errorMessage(e) });
     }
   };
 
@@ -293,7 +294,7 @@ function Devices(props: DevicesProps) {
         }
       }
     } catch (e) {
-      setLinkState({ step: 'failed', error: String(e) });
+      setLinkState({ step: 'failed', error: errorMessage(e) });
     }
   };
 
@@ -308,7 +309,7 @@ function Devices(props: DevicesProps) {
       refetch();
       setShowRevokeConfirm(null);
     } catch (e) {
-      setError(String(e));
+      setError(errorMessage(e));
     }
 
     setIsRevoking(false);
@@ -468,7 +469,7 @@ function Devices(props: DevicesProps) {
                     fingerprint: confirmation.fingerprint,
                   });
                 } catch (e) {
-                  setLinkState({ step: 'failed', error: String(e) });
+                  setLinkState({ step: 'failed', error: errorMessage(e) });
                 }
               };
               return (
@@ -806,7 +807,7 @@ function Devices(props: DevicesProps) {
                     setLinkState({ step: 'failed', error: finishResult.message });
                   }
                 } catch (e) {
-                  setLinkState({ step: 'failed', error: String(e) });
+                  setLinkState({ step: 'failed', error: errorMessage(e) });
                 }
               };
               return (
@@ -42,9 +42,16 @@ interface ShredReport {
   all_clear: boolean;
 }
 
-interface BroadcastResult {
-  sent: number;
-  total: number;
+interface RecipientDelivery {
+  contact_id: string;
+  display_name: string | null;
+  status: 'sent' | 'queued' | 'not_found' | 'blocked' | 'no_session' | 'failed';
+}
+
+interface BroadcastReport {
+  alert_id: string;
+  recipients: RecipientDelivery[];
+  relay_error: string | null;
 }
 
 function EmergencyWipe(props: EmergencyWipePageProps) {
@@ -164,13 +171,40 @@ function EmergencyWipe(props: EmergencyWipePageProps) {
       return;
     }
     try {
-      const result = (await invoke('send_emergency_broadcast')) as BroadcastResult;
-      showSuccess(`Alert queued for ${result.sent} of ${result.total} contacts`);
+      const report = (await invoke('trigger_emergency_broadcast', {
+        location: null,
+      })) as BroadcastReport;
+      const total = report.recipients.length;
+      const sent = report.recipients.filter((r) => r.status === 'sent').length;
+      const queued = report.recipients.filter((r) => r.status === 'queued').length;
+      showSuccess(
+        queued > 0
+          ? `Alert sent to ${sent} of ${total} contacts, ${queued} queued for next sync`
+          : `Alert sent to ${sent} of ${total} contacts`
+      );
     } catch (e) {
       setErrorMessage(`Failed to send: ${e}`);
     }
   };
 
+  const handleTestBroadcast = async () => {
+    clearMessages();
+    try {
+      const report = (await invoke('test_emergency_broadcast', {
+        location: null,
+      })) as BroadcastReport;
+      const total = report.recipients.length;
+      const sent = report.recipients.filter((r) => r.status === 'sent').length;
+      if (report.relay_error) {
+        setErrorMessage(`Test alert could not reach the relay: ${report.relay_error}`);
+      } else {
+        showSuccess(`Test alert delivered to ${sent} of ${total} contacts`);
+      }
+    } catch (e) {
+      setErrorMessage(`Failed to send test: ${e}`);
+    }
+  };
+
   // --- Emergency Wipe with Countdown ---
 
   const WIPE_COUNTDOWN_SECONDS = 10;
@@ -310,6 +344,13 @@ function EmergencyWipe(props: EmergencyWipePageProps) {
               >
                 Send Alert Now
               </button>
+              <button
+                class="secondary"
+                onClick={handleTestBroadcast}
+                aria-label="Send a test alert to verify the setup"
+              >
+                Send Test Alert
+              </button>
               <button
                 class="danger"
                 onClick={handleDisableConfig}
@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+// Always-on-top secondary window showing the current exchange or device-link QR.
+
+import { createSignal, onCleanup, onMount, Show } from 'solid-js';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import QRCanvas from '../components/QRCanvas';
+
+interface QrWindowContent {
+  kind: string;
+  data: string;
+}
+
+interface QrWindowProps {
+  kind: string;
+}
+
+function QrWindow(props: QrWindowProps) {
+  const [content, setContent] = createSignal<QrWindowContent | null>(null);
+  const [error, setError] = createSignal('');
+
+  const load = async (kind: string) => {
+    try {
+      setContent(await invoke<QrWindowContent>('get_qr_window_content', { kind }));
+      setError('');
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  onMount(async () => {
+    await load(props.kind);
+    const unlisten = await listen<string>('qr-window-kind', (event) => load(event.payload));
+    onCleanup(unlisten);
+  });
+
+  return (
+    <main class="qr-window" data-tauri-drag-region>
+      <Show when={content()} fallback={<p class="error">{error()}</p>}>
+        {(c) => (
+          <QRCanvas
+            data={c().data}
+            size={280}
+            description={c().kind === 'exchange' ? 'Contact exchange QR code' : 'Device link QR code'}
+          />
+        )}
+      </Show>
+      <button class="qr-window-close" onClick={() => getCurrentWindow().close()} aria-label="Close">
+        &times;
+      </button>
+    </main>
+  );
+}
+
+export default QrWindow;
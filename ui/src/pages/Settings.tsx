@@ -10,10 +10,17 @@ import {
   selectTheme,
   getSelectedThemeId,
   getFollowSystem,
-  setFollowSystem,
-  getDefaultThemeId,
+  setActiveTheme,
+  getActiveTheme,
   type Theme,
 } from '../services/themeService';
+import {
+  getAccessibilitySettings,
+  setAccessibilitySettings,
+  getUiScale,
+  setUiScale,
+  UI_SCALE_OPTIONS,
+} from '../services/accessibilityService';
 import {
   getAvailableLocales,
   setLocale,
@@ -68,6 +75,7 @@ function Settings(props: SettingsProps) {
 
   // Content updates state
   const [contentUpdatesEnabled, setContentUpdatesEnabled] = createSignal(true);
+  const [contentAutoApply, setContentAutoApply] = createSignal(false);
   const [isCheckingContent, setIsCheckingContent] = createSignal(false);
   const [contentUpdateMessage, setContentUpdateMessage] = createSignal('');
   const [hasContentUpdates, setHasContentUpdates] = createSignal(false);
@@ -76,6 +84,8 @@ function Settings(props: SettingsProps) {
   const [reduceMotion, setReduceMotion] = createSignal(false);
   const [highContrast, setHighContrast] = createSignal(false);
   const [largeTouchTargets, setLargeTouchTargets] = createSignal(false);
+  const [largeQrModules, setLargeQrModules] = createSignal(false);
+  const [uiScale, setUiScaleSignal] = createSignal(1);
 
   // Auth mode — "normal", "duress", or "unauthenticated"
   const [authMode, setAuthMode] = createSignal('unauthenticated');
@@ -110,10 +120,17 @@ function Settings(props: SettingsProps) {
   const [selectedLocaleCode, setSelectedLocaleCode] = createSignal<string>('en');
 
   // Apply accessibility settings to document
-  const applyAccessibilitySettings = () => {
-    document.documentElement.setAttribute('data-reduce-motion', String(reduceMotion()));
-    document.documentElement.setAttribute('data-high-contrast', String(highContrast()));
-    document.documentElement.setAttribute('data-large-touch-targets', String(largeTouchTargets()));
+  const saveAccessibilitySettings = async () => {
+    try {
+      await setAccessibilitySettings({
+        reduce_motion: reduceMotion(),
+        high_contrast: highContrast(),
+        large_touch_targets: largeTouchTargets(),
+        large_qr_modules: largeQrModules(),
+      });
+    } catch (e) {
+      console.error('Failed to save accessibility settings:', e);
+    }
   };
 
   // Load sync status, relay URL, and accessibility settings on mount
@@ -136,19 +153,21 @@ function Settings(props: SettingsProps) {
     try {
       const settings = (await invoke('get_content_settings')) as ContentSettings;
       setContentUpdatesEnabled(settings.enabled);
+      setContentAutoApply(settings.auto_apply);
     } catch (e) {
       console.error('Failed to get content settings:', e);
     }
 
-    // Load accessibility settings from localStorage
-    const savedReduceMotion = localStorage.getItem('a11y-reduce-motion') === 'true';
-    const savedHighContrast = localStorage.getItem('a11y-high-contrast') === 'true';
-    const savedLargeTouchTargets = localStorage.getItem('a11y-large-touch-targets') === 'true';
-
-    setReduceMotion(savedReduceMotion);
-    setHighContrast(savedHighContrast);
-    setLargeTouchTargets(savedLargeTouchTargets);
-    applyAccessibilitySettings();
+    try {
+      const a11y = await getAccessibilitySettings();
+      setReduceMotion(a11y.reduce_motion);
+      setHighContrast(a11y.high_contrast);
+      setLargeTouchTargets(a11y.large_touch_targets);
+      setLargeQrModules(a11y.large_qr_modules);
+      setUiScaleSignal(await getUiScale());
+    } catch (e) {
+      console.error('Failed to load accessibility settings:', e);
+    }
 
     // Load themes and locales
     try {
@@ -230,33 +249,49 @@ function Settings(props: SettingsProps) {
     }
   });
 
-  const toggleReduceMotion = () => {
-    const newValue = !reduceMotion();
-    setReduceMotion(newValue);
-    localStorage.setItem('a11y-reduce-motion', String(newValue));
-    applyAccessibilitySettings();
+  const toggleReduceMotion = async () => {
+    setReduceMotion(!reduceMotion());
+    await saveAccessibilitySettings();
+  };
+
+  const toggleHighContrast = async () => {
+    setHighContrast(!highContrast());
+    await saveAccessibilitySettings();
+    // High contrast overrides the selected theme
+    try {
+      const active = await getActiveTheme();
+      await selectTheme(active.theme_id);
+      setSelectedThemeId(active.theme_id);
+    } catch (e) {
+      console.error('Failed to apply theme:', e);
+    }
+  };
+
+  const toggleLargeTouchTargets = async () => {
+    setLargeTouchTargets(!largeTouchTargets());
+    await saveAccessibilitySettings();
   };
 
-  const toggleHighContrast = () => {
-    const newValue = !highContrast();
-    setHighContrast(newValue);
-    localStorage.setItem('a11y-high-contrast', String(newValue));
-    applyAccessibilitySettings();
+  const toggleLargeQrModules = async () => {
+    setLargeQrModules(!largeQrModules());
+    await saveAccessibilitySettings();
   };
 
-  const toggleLargeTouchTargets = () => {
-    const newValue = !largeTouchTargets();
-    setLargeTouchTargets(newValue);
-    localStorage.setItem('a11y-large-touch-targets', String(newValue));
-    applyAccessibilitySettings();
+  const handleUiScaleChange = async (factor: number) => {
+    try {
+      await setUiScale(factor);
+      setUiScaleSignal(factor);
+    } catch (e) {
+      console.error('Failed to set UI scale:', e);
+    }
   };
 
   const handleThemeChange = async (themeId: string) => {
     try {
+      // Explicit selection disables follow-system
+      await setActiveTheme(themeId);
       await selectTheme(themeId);
       setSelectedThemeId(themeId);
-      // Explicit selection disables follow-system
-      setFollowSystem(false);
       setFollowSystemSignal(false);
     } catch (e) {
       console.error('Failed to change theme:', e);
@@ -265,17 +300,14 @@ function Settings(props: SettingsProps) {
 
   const handleFollowSystemToggle = async () => {
     const newValue = !followSystem();
-    setFollowSystem(newValue);
     setFollowSystemSignal(newValue);
-    if (newValue) {
-      // Reset to system-appropriate default
-      try {
-        const defaultId = await getDefaultThemeId();
-        await selectTheme(defaultId);
-        setSelectedThemeId(defaultId);
-      } catch (e) {
-        console.error('Failed to apply system theme:', e);
-      }
+    try {
+      // Following the system resets to the system-appropriate default
+      const active = await setActiveTheme(newValue ? null : selectedThemeId() || null);
+      await selectTheme(active.theme_id);
+      setSelectedThemeId(active.theme_id);
+    } catch (e) {
+      console.error('Failed to apply system theme:', e);
     }
   };
 
@@ -555,6 +587,16 @@ function Settings(props: SettingsProps) {
     }
   };
 
+  const toggleContentAutoApply = async () => {
+    const newValue = !contentAutoApply();
+    try {
+      await invoke('set_content_auto_apply', { enabled: newValue });
+      setContentAutoApply(newValue);
+    } catch (e) {
+      console.error('Failed to toggle content auto-apply:', e);
+    }
+  };
+
   const checkContentUpdates = async () => {
     setIsCheckingContent(true);
     setContentUpdateMessage('');
@@ -581,9 +623,17 @@ function Settings(props: SettingsProps) {
     setContentUpdateMessage('Applying updates...');
 
     try {
-      await invoke('apply_content_updates');
-      setHasContentUpdates(false);
-      setContentUpdateMessage('Content updated successfully');
+      const result = (await invoke('apply_content_updates')) as {
+        success: boolean;
+        error: string | null;
+        verification: string | null;
+      };
+      if (result.success) {
+        setHasContentUpdates(false);
+        setContentUpdateMessage('Content updated successfully');
+      } else {
+        setContentUpdateMessage(result.error ?? 'Content update failed');
+      }
     } catch (e) {
       setContentUpdateMessage(String(e));
     }
@@ -996,6 +1046,40 @@ function Settings(props: SettingsProps) {
             <span class="toggle-slider" aria-hidden="true" />
           </div>
         </div>
+
+        <div class="accessibility-toggle">
+          <label for="large-qr-toggle">
+            Large QR Modules
+            <span class="toggle-description">
+              Draw QR codes with fewer, bigger squares for easier scanning
+            </span>
+          </label>
+          <div class="toggle-switch">
+            <input
+              type="checkbox"
+              id="large-qr-toggle"
+              checked={largeQrModules()}
+              onChange={toggleLargeQrModules}
+            />
+            <span class="toggle-slider" aria-hidden="true" />
+          </div>
+        </div>
+
+        <div class="accessibility-toggle">
+          <label for="ui-scale-select">
+            UI Scale
+            <span class="toggle-description">Make everything larger or smaller</span>
+          </label>
+          <select
+            id="ui-scale-select"
+            value={String(uiScale())}
+            onChange={(e) => handleUiScaleChange(Number(e.currentTarget.value))}
+          >
+            <For each={UI_SCALE_OPTIONS}>
+              {(factor) => <option value={String(factor)}>{Math.round(factor * 100)}%</option>}
+            </For>
+          </select>
+        </div>
       </section>
 
       <section class="settings-section" aria-labelledby="appearance-section-title">
@@ -1270,6 +1354,23 @@ function Settings(props: SettingsProps) {
           </div>
         </div>
 
+        <div class="accessibility-toggle">
+          <label for="content-auto-apply-toggle">
+            Apply Automatically
+            <span class="toggle-description">Install verified updates found by scheduled checks</span>
+          </label>
+          <div class="toggle-switch">
+            <input
+              type="checkbox"
+              id="content-auto-apply-toggle"
+              checked={contentAutoApply()}
+              onChange={toggleContentAutoApply}
+              disabled={!contentUpdatesEnabled()}
+            />
+            <span class="toggle-slider" aria-hidden="true" />
+          </div>
+        </div>
+
         <Show when={contentUpdateMessage()}>
           <p class="sync-message" role="status" aria-live="polite">
             {contentUpdateMessage()}
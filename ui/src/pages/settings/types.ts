@@ -36,6 +36,9 @@ export interface ContentSettings {
   enabled: boolean;
   content_url: string;
   check_interval_secs: number;
+  auto_apply: boolean;
+  mirror_urls: string[];
+  disabled_types: string[];
 }
 
 /** Current content update status including available updates and last check timestamp. */
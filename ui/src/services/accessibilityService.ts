@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Accessibility Service
+ *
+ * Loads and saves the accessibility settings stored by the backend and
+ * applies them as attributes on the document root.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface AccessibilitySettings {
+  reduce_motion: boolean;
+  high_contrast: boolean;
+  large_touch_targets: boolean;
+  large_qr_modules: boolean;
+}
+
+/**
+ * Get the accessibility settings.
+ */
+export async function getAccessibilitySettings(): Promise<AccessibilitySettings> {
+  return await invoke<AccessibilitySettings>('get_accessibility_settings');
+}
+
+/**
+ * Save the accessibility settings and apply them.
+ */
+export async function setAccessibilitySettings(settings: AccessibilitySettings): Promise<void> {
+  await invoke('set_accessibility_settings', { settings });
+  applyAccessibilitySettings(settings);
+}
+
+/** Keys older versions kept the settings under in localStorage. */
+const LEGACY_KEYS = {
+  reduce_motion: 'a11y-reduce-motion',
+  high_contrast: 'a11y-high-contrast',
+  large_touch_targets: 'a11y-large-touch-targets',
+} as const;
+
+/**
+ * Move settings older versions kept in localStorage to the backend, once.
+ */
+export async function migrateLegacyAccessibilitySettings(): Promise<void> {
+  const keys = Object.values(LEGACY_KEYS);
+  if (!keys.some((key) => localStorage.getItem(key) !== null)) return;
+
+  const settings = await getAccessibilitySettings();
+  for (const [field, key] of Object.entries(LEGACY_KEYS)) {
+    const saved = localStorage.getItem(key);
+    if (saved !== null) {
+      settings[field as keyof typeof LEGACY_KEYS] = saved === 'true';
+    }
+  }
+  await invoke('set_accessibility_settings', { settings });
+  keys.forEach((key) => localStorage.removeItem(key));
+}
+
+/** UI scale factors offered in settings. */
+export const UI_SCALE_OPTIONS = [0.8, 0.9, 1, 1.1, 1.25, 1.5, 1.75, 2];
+
+/**
+ * Get the UI scale factor (1 = 100%).
+ */
+export async function getUiScale(): Promise<number> {
+  return await invoke<number>('get_ui_scale');
+}
+
+/**
+ * Set the UI scale factor; the backend persists and applies it.
+ */
+export async function setUiScale(factor: number): Promise<void> {
+  await invoke('set_ui_scale', { factor });
+}
+
+/**
+ * Apply the settings as data attributes used by the stylesheets.
+ */
+export function applyAccessibilitySettings(settings: AccessibilitySettings): void {
+  const root = document.documentElement;
+  root.setAttribute('data-reduce-motion', String(settings.reduce_motion));
+  root.setAttribute('data-high-contrast', String(settings.high_contrast));
+  root.setAttribute('data-large-touch-targets', String(settings.large_touch_targets));
+}
@@ -29,6 +29,16 @@ export async function checkAhaMoment(momentType: string): Promise<AhaMoment | nu
   });
 }
 
+/**
+ * Take the desktop milestones (tenth contact, first label, first linked
+ * device, first backup) reached since last asked. Each is returned once.
+ */
+export async function takeAhaMoments(): Promise<AhaMoment[]> {
+  return await invoke<AhaMoment[]>('take_aha_moments', {
+    localeCode: getSelectedLocale(),
+  });
+}
+
 /**
  * Check and trigger an aha moment with context (e.g., contact name).
  */
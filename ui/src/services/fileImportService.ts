@@ -0,0 +1,49 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * File Import Service
+ *
+ * The backend previews files dropped onto the window or opened with the
+ * app and announces them on `file-import`. vCard contacts are only added
+ * once the user confirms.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+const FILE_IMPORT_EVENT = 'file-import';
+
+export interface ImportedContact {
+  display_name: string;
+  fields: { field_type: string; label: string; value: string }[];
+}
+
+type FileImport =
+  | { kind: 'vcard'; file_name: string; contacts: ImportedContact[]; known: number }
+  | { kind: 'backup' | 'invite'; file_name: string }
+  | { kind: 'unsupported'; file_name: string; reason: string };
+
+/**
+ * Ask before adding the new contacts of each previewed vCard.
+ * `onImported` is called with the number added.
+ */
+export async function listenForFileImports(
+  onImported: (count: number) => void
+): Promise<UnlistenFn> {
+  return await listen<FileImport>(FILE_IMPORT_EVENT, async (event) => {
+    const file = event.payload;
+    if (file.kind !== 'vcard' || file.contacts.length === 0) return;
+
+    const names = file.contacts.map((c) => c.display_name).join(', ');
+    const question = `Add ${file.contacts.length} contact(s) from ${file.file_name}?\n${names}`;
+    if (!window.confirm(question)) return;
+    try {
+      const count = await invoke<number>('import_vcard_contacts', { contacts: file.contacts });
+      onImported(count);
+    } catch (e) {
+      console.error('Failed to import contacts:', e);
+    }
+  });
+}
@@ -24,6 +24,24 @@ export interface HelpCategory {
   name: string;
 }
 
+/** FAQs and walkthrough steps for the "?" panel of an app screen. */
+export interface ContextHelp {
+  screen_id: string;
+  title: string;
+  faqs: FaqItem[];
+  walkthrough: string[];
+}
+
+/** Screens with context help. */
+export type HelpScreen = 'exchange' | 'device-link' | 'sync' | 'visibility';
+
+/**
+ * Get context help for a screen in the current app locale.
+ */
+export async function getHelpForContext(screenId: HelpScreen): Promise<ContextHelp> {
+  return await invoke<ContextHelp>('get_help_for_context', { screenId });
+}
+
 /**
  * Get all help categories.
  */
@@ -82,3 +100,52 @@ export async function getFaqsByCategoryLocalized(
 export async function searchFaqsLocalized(query: string, localeCode: string): Promise<FaqItem[]> {
   return await invoke<FaqItem[]>('search_help_localized', { query, localeCode });
 }
+
+/** A help search that returned no results. */
+export interface UnansweredSearch {
+  query: string;
+  count: number;
+  last_searched: number;
+}
+
+/**
+ * Record whether an FAQ was helpful, with an optional comment. Stored locally only.
+ */
+export async function submitHelpFeedback(
+  faqId: string,
+  helpful: boolean,
+  comment?: string
+): Promise<void> {
+  await invoke('submit_help_feedback', { faqId, helpful, comment: comment ?? null });
+}
+
+/**
+ * Get the most frequent searches that returned no results.
+ */
+export async function getTopUnansweredSearches(limit?: number): Promise<UnansweredSearch[]> {
+  return await invoke<UnansweredSearch[]>('get_top_unanswered_searches', { limit: limit ?? null });
+}
+
+/** Outcome of one troubleshooter check. */
+export interface TroubleshootStep {
+  check: string;
+  status: 'ok' | 'warning' | 'error' | 'skipped';
+  finding: string;
+  suggestion: string | null;
+  help_screen: HelpScreen | null;
+}
+
+export interface TroubleshootReport {
+  kind: string;
+  steps: TroubleshootStep[];
+  has_errors: boolean;
+}
+
+/**
+ * Run the checks for a common problem.
+ */
+export async function runTroubleshooter(
+  kind: 'sync' | 'device_link'
+): Promise<TroubleshootReport> {
+  return await invoke<TroubleshootReport>('run_troubleshooter', { kind });
+}
@@ -0,0 +1,47 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Onboarding Service
+ *
+ * Tracks the guided tour for new users.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type OnboardingStep = 'create_identity' | 'add_field' | 'first_exchange' | 'first_sync';
+
+export interface OnboardingStepInfo {
+  id: OnboardingStep;
+  title: string;
+  completed: boolean;
+}
+
+export interface OnboardingState {
+  steps: OnboardingStepInfo[];
+  current_step: OnboardingStep | null;
+  skipped: boolean;
+  finished: boolean;
+}
+
+/**
+ * Get the guided tour state.
+ */
+export async function getOnboardingState(): Promise<OnboardingState> {
+  return await invoke<OnboardingState>('get_onboarding_state');
+}
+
+/**
+ * Mark a tour step as completed.
+ */
+export async function completeOnboardingStep(step: OnboardingStep): Promise<OnboardingState> {
+  return await invoke<OnboardingState>('complete_onboarding_step', { step });
+}
+
+/**
+ * Skip the rest of the guided tour.
+ */
+export async function skipOnboarding(): Promise<OnboardingState> {
+  return await invoke<OnboardingState>('skip_onboarding');
+}
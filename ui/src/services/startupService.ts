@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Startup Service
+ *
+ * The backend initializes some components after the window is shown and
+ * announces each one on `startup://ready`.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+const READY_EVENT = 'startup://ready';
+
+/**
+ * Components initialized in the background: locale strings, user themes,
+ * and the app state most commands need (storage key and identity).
+ */
+export type StartupComponent = 'i18n' | 'themes' | 'state';
+
+interface StartupReport {
+  ready: string[];
+}
+
+/**
+ * Resolve once `component` is ready. Listens before asking the backend,
+ * so an announcement in between is not missed.
+ */
+export async function waitForStartup(component: StartupComponent): Promise<void> {
+  let announce = () => {};
+  const announced = new Promise<void>((resolve) => (announce = resolve));
+  const unlisten = await listen<string>(READY_EVENT, (event) => {
+    if (event.payload === component) announce();
+  });
+  try {
+    const report = await invoke<StartupReport>('get_startup_report');
+    if (!report.ready.includes(component)) {
+      await announced;
+    }
+  } finally {
+    unlisten();
+  }
+}
@@ -9,6 +9,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
 export interface ThemeColors {
   bg_primary: string;
@@ -33,6 +34,16 @@ export interface Theme {
   colors: ThemeColors;
 }
 
+/** Persisted theme choice, resolved by the backend. */
+export interface ActiveTheme {
+  theme_id: string;
+  follow_system: boolean;
+  system_dark: boolean;
+}
+
+/** Emitted by the backend when the OS switches between dark and light mode. */
+const SYSTEM_THEME_CHANGED_EVENT = 'theme://system-changed';
+
 const THEME_STORAGE_KEY = 'selected-theme';
 const FOLLOW_SYSTEM_KEY = 'theme-follow-system';
 
@@ -58,6 +69,22 @@ export async function getDefaultThemeId(): Promise<string> {
   return await invoke<string>('get_default_theme_id', { preferDark: prefersDark });
 }
 
+/**
+ * Get the active theme persisted by the backend.
+ */
+export async function getActiveTheme(): Promise<ActiveTheme> {
+  return await invoke<ActiveTheme>('get_active_theme');
+}
+
+/**
+ * Persist the active theme. `null` follows the system appearance.
+ */
+export async function setActiveTheme(themeId: string | null): Promise<ActiveTheme> {
+  const active = await invoke<ActiveTheme>('set_active_theme', { themeId });
+  setFollowSystem(active.follow_system);
+  return active;
+}
+
 /**
  * Get the currently selected theme ID.
  */
@@ -81,26 +108,21 @@ export function setFollowSystem(enabled: boolean): void {
   localStorage.setItem(FOLLOW_SYSTEM_KEY, String(enabled));
 }
 
-let systemThemeCleanup: (() => void) | null = null;
+let systemThemeCleanup: UnlistenFn | null = null;
 
 /**
- * Start listening for system color scheme changes.
+ * Start listening for system color scheme changes reported by the backend.
  * When "Follow System" is enabled, automatically switches to the
  * appropriate default theme when the OS toggles dark/light mode.
  */
-export function startSystemThemeListener(): void {
+export async function startSystemThemeListener(): Promise<void> {
   stopSystemThemeListener();
 
-  const mediaQuery = window.matchMedia('(prefers-color-scheme: dark)');
-  const handler = async () => {
-    if (getFollowSystem()) {
-      const defaultId = await getDefaultThemeId();
-      await selectTheme(defaultId);
+  systemThemeCleanup = await listen<ActiveTheme>(SYSTEM_THEME_CHANGED_EVENT, async (event) => {
+    if (event.payload.follow_system) {
+      await selectTheme(event.payload.theme_id);
     }
-  };
-
-  mediaQuery.addEventListener('change', handler);
-  systemThemeCleanup = () => mediaQuery.removeEventListener('change', handler);
+  });
 }
 
 /**
@@ -163,22 +185,13 @@ export async function selectTheme(themeId: string): Promise<void> {
 }
 
 /**
- * Initialize theme from saved preference or system default.
+ * Initialize theme from the persisted choice or system default.
  * Starts the system theme listener for automatic switching.
  */
 export async function initializeTheme(): Promise<void> {
-  if (getFollowSystem()) {
-    const defaultId = await getDefaultThemeId();
-    await selectTheme(defaultId);
-  } else {
-    const savedThemeId = getSelectedThemeId();
-    if (savedThemeId) {
-      await selectTheme(savedThemeId);
-    } else {
-      const defaultId = await getDefaultThemeId();
-      await selectTheme(defaultId);
-    }
-  }
+  const active = await getActiveTheme();
+  setFollowSystem(active.follow_system);
+  await selectTheme(active.theme_id);
 
-  startSystemThemeListener();
+  await startSystemThemeListener();
 }
@@ -16,6 +16,24 @@ export interface ValidationStatus {
   color: string;
   validated_by_me: boolean;
   display_text: string;
+  stale_count: number;
+}
+
+export type SignatureStatus = 'valid' | 'tampered' | 'unknown_validator';
+
+export interface VerifiedValidation {
+  validation: FieldValidation;
+  signature: SignatureStatus;
+}
+
+export interface FieldValidationSummary {
+  field_id: string;
+  field_label: string;
+  status: ValidationStatus;
+}
+
+export interface ValidationFreshness {
+  max_age_months: number;
 }
 
 export interface FieldValidation {
@@ -26,6 +44,16 @@ export interface FieldValidation {
   validated_at: number;
 }
 
+export interface IncomingValidationRequest {
+  request_id: string;
+  contact_id: string;
+  field_id: string;
+  field_label: string;
+  field_value: string;
+  requested_at: number;
+  received_at: number;
+}
+
 /**
  * Validate a contact's field (attest that the value is correct).
  */
@@ -56,6 +84,22 @@ export async function getFieldValidationStatus(
   });
 }
 
+/**
+ * Get the validation status of every field of a contact in one call.
+ */
+export async function getContactValidationSummary(
+  contactId: string
+): Promise<FieldValidationSummary[]> {
+  return await invoke<FieldValidationSummary[]>('get_contact_validation_summary', { contactId });
+}
+
+/**
+ * Re-verify the signatures of all validations of a contact's fields.
+ */
+export async function verifyValidationSignatures(contactId: string): Promise<VerifiedValidation[]> {
+  return await invoke<VerifiedValidation[]>('verify_validation_signatures', { contactId });
+}
+
 /**
  * Revoke your validation of a field.
  */
@@ -82,3 +126,97 @@ export async function getFieldValidationCount(contactId: string, fieldId: string
 export async function listMyValidations(): Promise<FieldValidation[]> {
   return await invoke<FieldValidation[]>('list_my_validations');
 }
+
+/**
+ * Get the age after which validations are stale.
+ */
+export async function getValidationFreshness(): Promise<ValidationFreshness> {
+  return await invoke<ValidationFreshness>('get_validation_freshness');
+}
+
+/**
+ * Set the age after which validations are stale.
+ */
+export async function setValidationFreshness(settings: ValidationFreshness): Promise<void> {
+  await invoke('set_validation_freshness', { settings });
+}
+
+/**
+ * List your stale validations that are worth re-confirming.
+ */
+export async function listStaleValidations(): Promise<FieldValidation[]> {
+  return await invoke<FieldValidation[]>('list_stale_validations');
+}
+
+/**
+ * Re-sign your validation of a field with a new timestamp.
+ */
+export async function refreshValidation(
+  contactId: string,
+  fieldId: string
+): Promise<FieldValidation> {
+  return await invoke<FieldValidation>('refresh_validation', {
+    contactId,
+    fieldId,
+  });
+}
+
+/**
+ * Ask a contact to validate one of your own card fields.
+ * Returns the request ID; the request is sent on the next sync.
+ */
+export async function requestFieldValidation(contactId: string, fieldId: string): Promise<string> {
+  return await invoke<string>('request_field_validation', {
+    contactId,
+    fieldId,
+  });
+}
+
+/**
+ * List requests from contacts to validate their fields.
+ */
+export async function listIncomingValidationRequests(): Promise<IncomingValidationRequest[]> {
+  return await invoke<IncomingValidationRequest[]>('list_incoming_validation_requests');
+}
+
+/**
+ * Accept a validation request, validating the requested field.
+ */
+export async function acceptValidationRequest(requestId: string): Promise<FieldValidation> {
+  return await invoke<FieldValidation>('accept_validation_request', { requestId });
+}
+
+/**
+ * Decline a validation request.
+ */
+export async function declineValidationRequest(requestId: string): Promise<void> {
+  await invoke('decline_validation_request', { requestId });
+}
+
+export interface TrustNode {
+  id: string;
+  display_name: string;
+  is_self: boolean;
+  verified: boolean;
+  recovery_trusted: boolean;
+  blocked: boolean;
+}
+
+export interface TrustEdge {
+  from: string;
+  to: string;
+  kind: 'validation' | 'vouch' | 'mutual_verification';
+  weight: number;
+}
+
+export interface TrustGraph {
+  nodes: TrustNode[];
+  edges: TrustEdge[];
+}
+
+/**
+ * Get the web-of-trust graph of the user and their contacts.
+ */
+export async function getTrustGraph(): Promise<TrustGraph> {
+  return await invoke<TrustGraph>('get_trust_graph');
+}
@@ -0,0 +1,16 @@
+// SPDX-FileCopyrightText: 2026 Mattia Egloff <mattia.egloff@pm.me>
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Text to show for an error thrown by invoke().
+ * Commands reject with a serialized CommandError
+ * (`{ kind, code, message, i18n_key }`),
+ * which String() would turn into "[object Object]".
+ */
+export function errorMessage(e: unknown): string {
+  if (typeof e === 'object' && e !== null && 'message' in e) {
+    return String((e as { message: unknown }).message);
+  }
+  return String(e);
+}